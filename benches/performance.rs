@@ -1,7 +1,7 @@
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use pgmold::diff::{compute_diff, planner::plan_migration, MigrationOp};
-use pgmold::model::{Column, Index, IndexType, PgType, Table};
-use pgmold::parser::parse_sql_string;
+use pgmold::model::{Column, Index, IndexType, PgType, Schema, Table};
+use pgmold::parser::{load_schema_sources, parse_sql_string};
 use pgmold::pg::sqlgen::generate_sql;
 use std::collections::BTreeMap;
 
@@ -41,6 +41,9 @@ fn build_table(index: usize) -> Table {
             default: None,
             comment: None,
             generated: None,
+            statistics: None,
+            compression: None,
+            identity: None,
         },
     );
     columns.insert(
@@ -52,6 +55,9 @@ fn build_table(index: usize) -> Table {
             default: None,
             comment: None,
             generated: None,
+            statistics: None,
+            compression: None,
+            identity: None,
         },
     );
     columns.insert(
@@ -63,6 +69,9 @@ fn build_table(index: usize) -> Table {
             default: None,
             comment: None,
             generated: None,
+            statistics: None,
+            compression: None,
+            identity: None,
         },
     );
 
@@ -77,14 +86,17 @@ fn build_table(index: usize) -> Table {
             unique: false,
             index_type: IndexType::BTree,
             predicate: None,
-            is_constraint: false,
+            nulls_not_distinct: false,
+            column_options: Vec::new(),
         }],
         foreign_keys: Vec::new(),
         check_constraints: Vec::new(),
+        unique_constraints: Vec::new(),
         exclusion_constraints: Vec::new(),
         comment: None,
         row_level_security: false,
         force_row_level_security: false,
+        replica_identity: None,
         policies: Vec::new(),
         partition_by: None,
         owner: None,
@@ -151,6 +163,56 @@ fn bench_diff(criterion: &mut Criterion) {
     group.finish();
 }
 
+/// A schema of `table_count` tables with `column_count` columns each, built
+/// directly so that large sizes do not pay for parsing.
+fn build_wide_schema(table_count: usize, column_count: usize) -> Schema {
+    let mut schema = Schema::new();
+    for i in 0..table_count {
+        let mut table = build_table(i);
+        for c in 0..column_count {
+            let name = format!("col_{c}");
+            table.columns.insert(
+                name.clone(),
+                Column {
+                    name,
+                    data_type: PgType::Text,
+                    nullable: true,
+                    default: None,
+                    comment: None,
+                    generated: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
+                },
+            );
+        }
+        schema.tables.insert(format!("public.table_{i}"), table);
+    }
+    schema
+}
+
+fn bench_diff_wide(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("diff_wide");
+    group.sample_size(10);
+
+    // 1,000 tables x 50 columns: ~50k columns.
+    let from = build_wide_schema(1_000, 50);
+    group.bench_function("identical", |bencher| {
+        bencher.iter(|| compute_diff(&from, &from));
+    });
+
+    let mut to = from.clone();
+    for i in 0..10 {
+        let table = to.tables.get_mut(&format!("public.table_{i}")).unwrap();
+        table.columns.get_mut("col_0").unwrap().data_type = PgType::Integer;
+    }
+    group.bench_function("few_changes", |bencher| {
+        bencher.iter(|| compute_diff(&from, &to));
+    });
+
+    group.finish();
+}
+
 fn bench_plan(criterion: &mut Criterion) {
     let mut group = criterion.benchmark_group("plan_migration");
 
@@ -182,11 +244,61 @@ fn bench_generate_sql(criterion: &mut Criterion) {
     group.finish();
 }
 
+/// Writes one table (with an index) per file, like a large schema repository.
+fn write_schema_dir(file_count: usize) -> tempfile::TempDir {
+    let dir = tempfile::TempDir::new().unwrap();
+    for i in 0..file_count {
+        std::fs::write(
+            dir.path().join(format!("table_{i:05}.sql")),
+            format!(
+                "CREATE TABLE public.table_{i} (\n  id BIGINT PRIMARY KEY,\n  name TEXT NOT NULL,\n  email VARCHAR(255),\n  created_at TIMESTAMP NOT NULL DEFAULT NOW()\n);\n\n\
+                 CREATE INDEX table_{i}_email_idx ON public.table_{i} (email);\n"
+            ),
+        )
+        .unwrap();
+    }
+    dir
+}
+
+fn bench_load_directory(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("load_directory");
+    group.sample_size(10);
+
+    // A one-thread pool reproduces the old sequential load for comparison.
+    let single_thread = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+
+    for (label, count) in [("medium", 200), ("large", 2000)] {
+        let dir = write_schema_dir(count);
+        let sources = vec![dir.path().display().to_string()];
+        group.bench_with_input(
+            BenchmarkId::new("sequential", label),
+            &sources,
+            |bencher, sources| {
+                bencher.iter(|| single_thread.install(|| load_schema_sources(sources).unwrap()));
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("parallel", label),
+            &sources,
+            |bencher, sources| {
+                bencher.iter(|| load_schema_sources(sources).unwrap());
+            },
+        );
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_parse,
     bench_diff,
+    bench_diff_wide,
     bench_plan,
-    bench_generate_sql
+    bench_generate_sql,
+    bench_load_directory
 );
 criterion_main!(benches);
@@ -0,0 +1,79 @@
+//! Entry points for tools built on pgmold's model rather than its migrations.
+
+use std::collections::BTreeMap;
+
+use crate::filter::{filter_schema, Filter};
+use crate::model::Schema;
+use crate::pg::connection::PgConnection;
+use crate::pg::database_url::DatabaseUrl;
+use crate::pg::introspect::{introspect_relation_stats, introspect_schema, RelationStats};
+use crate::pg::metrics::Metrics;
+use crate::util::Result;
+
+/// What [`introspect`] reads.
+pub struct IntrospectOptions {
+    pub database_url: DatabaseUrl,
+    pub target_schemas: Vec<String>,
+    /// Applied to the introspected schema; relations it filters out are left
+    /// out of the stats too.
+    pub filter: Option<Filter>,
+    pub include_extension_objects: bool,
+    /// Time introspection and count its queries, returned in
+    /// `IntrospectResult::metrics`.
+    pub collect_metrics: bool,
+}
+
+impl IntrospectOptions {
+    pub fn new(database_url: impl Into<DatabaseUrl>, target_schemas: Vec<String>) -> Self {
+        Self {
+            database_url: database_url.into(),
+            target_schemas,
+            filter: None,
+            include_extension_objects: false,
+            collect_metrics: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct IntrospectResult {
+    pub schema: Schema,
+    /// Catalog metadata for each table, partition, view and sequence in
+    /// `schema`, by qualified name.
+    pub stats: BTreeMap<String, RelationStats>,
+    pub metrics: Option<Metrics>,
+}
+
+/// Introspects the database as `plan` does, without loading any schema
+/// sources or computing a plan.
+pub async fn introspect(options: IntrospectOptions) -> Result<IntrospectResult> {
+    let connection = PgConnection::connect(&options.database_url).await?;
+    if options.collect_metrics {
+        connection.start_metrics();
+    }
+    let schema = introspect_schema(
+        &connection,
+        &options.target_schemas,
+        options.include_extension_objects,
+    )
+    .await;
+    let metrics = connection.finish_metrics();
+    let schema = match &options.filter {
+        Some(filter) => filter_schema(&schema?, filter),
+        None => schema?,
+    };
+
+    let mut stats = introspect_relation_stats(&connection, &options.target_schemas).await?;
+    stats.retain(|name, _| {
+        schema.tables.contains_key(name)
+            || schema.views.contains_key(name)
+            || schema.sequences.contains_key(name)
+            || schema.partitions.contains_key(name)
+    });
+
+    Ok(IntrospectResult {
+        schema,
+        stats,
+        metrics,
+    })
+}
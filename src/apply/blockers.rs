@@ -0,0 +1,190 @@
+use std::collections::BTreeSet;
+use std::time::{Duration, Instant};
+
+use sqlx::Row;
+
+use crate::diff::MigrationOp;
+use crate::lint::locks::{detect_lock_hazards, LockLevel};
+use crate::pg::connection::PgConnection;
+use crate::util::{Result, SchemaError};
+
+/// How long a transaction may already have been open while holding a lock on a
+/// table the migration locks exclusively, and how long to wait for such
+/// transactions to finish before giving up. Both are PostgreSQL intervals
+/// (e.g., `30s`, `2min`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionAgeCheck {
+    pub max_age: String,
+    pub wait: Option<String>,
+}
+
+/// A transaction, open longer than the allowed age, holding a lock on a table
+/// the migration needs `ACCESS EXCLUSIVE` on. DDL queued behind it would in
+/// turn block every other query on the table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockingTransaction {
+    pub pid: i32,
+    pub table: String,
+    pub state: Option<String>,
+    pub age_seconds: i64,
+    pub query: Option<String>,
+}
+
+/// Tables `ops` take an `ACCESS EXCLUSIVE` lock on.
+pub fn exclusively_locked_tables(ops: &[MigrationOp]) -> BTreeSet<String> {
+    detect_lock_hazards(ops)
+        .into_iter()
+        .filter(|hazard| hazard.lock_level == LockLevel::AccessExclusive)
+        .map(|hazard| hazard.table)
+        .collect()
+}
+
+/// Transactions older than `max_age` holding any lock on `tables`.
+pub async fn find_blocking_transactions(
+    connection: &PgConnection,
+    tables: &BTreeSet<String>,
+    max_age: &str,
+) -> Result<Vec<BlockingTransaction>> {
+    if tables.is_empty() {
+        return Ok(Vec::new());
+    }
+    let tables: Vec<&String> = tables.iter().collect();
+    let rows = sqlx::query(
+        r#"
+        SELECT DISTINCT
+            a.pid,
+            n.nspname || '.' || c.relname AS table_name,
+            a.state,
+            extract(epoch FROM now() - a.xact_start)::int8 AS age_seconds,
+            left(a.query, 200) AS query
+        FROM pg_catalog.pg_locks l
+        JOIN pg_catalog.pg_class c ON c.oid = l.relation
+        JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+        JOIN pg_catalog.pg_stat_activity a ON a.pid = l.pid
+        WHERE l.granted
+          AND a.pid <> pg_backend_pid()
+          AND a.xact_start < now() - $2::interval
+          AND n.nspname || '.' || c.relname = ANY($1::text[])
+        ORDER BY age_seconds DESC, a.pid
+        "#,
+    )
+    .bind(&tables)
+    .bind(max_age)
+    .fetch_all(&mut *connection.acquire().await?)
+    .await
+    .map_err(|e| {
+        SchemaError::DatabaseError(format!("Failed to check for blocking transactions: {e}"))
+    })?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| BlockingTransaction {
+            pid: row.get("pid"),
+            table: row.get("table_name"),
+            state: row.get("state"),
+            age_seconds: row.get("age_seconds"),
+            query: row.get("query"),
+        })
+        .collect())
+}
+
+/// Fails before anything runs when a long-running transaction holds a lock on
+/// a table the migration locks exclusively, instead of queueing the DDL behind
+/// it. With a wait, polls until the transactions finish or the wait runs out.
+pub async fn check_blocking_transactions(
+    connection: &PgConnection,
+    ops: &[MigrationOp],
+    check: &TransactionAgeCheck,
+) -> Result<()> {
+    let tables = exclusively_locked_tables(ops);
+    if tables.is_empty() {
+        return Ok(());
+    }
+    let deadline = match &check.wait {
+        Some(wait) => Some(Instant::now() + interval_duration(connection, wait).await?),
+        None => None,
+    };
+    loop {
+        let blockers = find_blocking_transactions(connection, &tables, &check.max_age).await?;
+        if blockers.is_empty() {
+            return Ok(());
+        }
+        if deadline.is_none_or(|deadline| Instant::now() >= deadline) {
+            return Err(SchemaError::ValidationError(blocking_report(
+                &blockers,
+                &check.max_age,
+            )));
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+/// Lets PostgreSQL parse `interval`, so waits accept the same units as the
+/// age threshold.
+async fn interval_duration(connection: &PgConnection, interval: &str) -> Result<Duration> {
+    let seconds: f64 = sqlx::query_scalar("SELECT extract(epoch FROM $1::interval)::float8")
+        .bind(interval)
+        .fetch_one(&mut *connection.acquire().await?)
+        .await
+        .map_err(|e| SchemaError::ValidationError(format!("Invalid wait '{interval}': {e}")))?;
+    Ok(Duration::from_secs_f64(seconds.max(0.0)))
+}
+
+fn blocking_report(blockers: &[BlockingTransaction], max_age: &str) -> String {
+    let lines: Vec<String> = blockers
+        .iter()
+        .map(|blocker| {
+            format!(
+                "  pid {} on {} ({}, open {}s): {}",
+                blocker.pid,
+                blocker.table,
+                blocker.state.as_deref().unwrap_or("unknown"),
+                blocker.age_seconds,
+                blocker.query.as_deref().unwrap_or("")
+            )
+        })
+        .collect();
+    format!(
+        "{} transaction(s) open longer than {max_age} hold locks on tables this migration locks exclusively:\n{}",
+        blockers.len(),
+        lines.join("\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::QualifiedName;
+
+    #[test]
+    fn only_exclusively_locked_tables_are_checked() {
+        let ops = vec![
+            MigrationOp::DropColumn {
+                table: QualifiedName::new("public", "users"),
+                column: "legacy".to_string(),
+            },
+            MigrationOp::DropEnum("public.status".to_string()),
+        ];
+
+        assert_eq!(
+            exclusively_locked_tables(&ops),
+            BTreeSet::from(["public.users".to_string()])
+        );
+    }
+
+    #[test]
+    fn report_lists_each_blocking_transaction() {
+        let blockers = vec![BlockingTransaction {
+            pid: 4242,
+            table: "public.users".to_string(),
+            state: Some("idle in transaction".to_string()),
+            age_seconds: 312,
+            query: Some("SELECT * FROM users".to_string()),
+        }];
+
+        assert_eq!(
+            blocking_report(&blockers, "30s"),
+            "1 transaction(s) open longer than 30s hold locks on tables this migration locks exclusively:\n  pid 4242 on public.users (idle in transaction, open 312s): SELECT * FROM users"
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use super::{apply_target_schema, ApplyOptions, ApplyResult};
+use crate::model::Schema;
+use crate::pg::connection::PgConnection;
+use crate::pg::database_url::DatabaseUrl;
+use crate::util::{Result, SchemaError};
+
+/// The outcome of applying the schema to one database of a fleet.
+#[derive(Debug)]
+pub struct DatabaseApplyResult {
+    pub database: DatabaseUrl,
+    pub result: Result<ApplyResult>,
+}
+
+/// Reads one database URL per line, skipping blank lines and `#` comments.
+pub fn read_databases_file(path: &str) -> Result<Vec<String>> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| SchemaError::ParseError(format!("Failed to read {path}: {e}")))?;
+    let urls: Vec<String> = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect();
+    if urls.is_empty() {
+        return Err(SchemaError::ParseError(format!(
+            "{path} lists no database URLs"
+        )));
+    }
+    Ok(urls)
+}
+
+/// Plans and applies `target` against every database, at most `concurrency`
+/// at a time. Each database is planned against its own current state and
+/// succeeds or fails on its own; results come back in input order.
+pub async fn apply_to_databases(
+    target: &Schema,
+    database_urls: &[DatabaseUrl],
+    options: ApplyOptions,
+    target_schemas: &[String],
+    concurrency: usize,
+) -> Vec<DatabaseApplyResult> {
+    let permits = Arc::new(Semaphore::new(concurrency.max(1)));
+    let target = Arc::new(target.clone());
+    let target_schemas = Arc::new(target_schemas.to_vec());
+    let mut tasks = JoinSet::new();
+    for (index, url) in database_urls.iter().cloned().enumerate() {
+        let permits = Arc::clone(&permits);
+        let target = Arc::clone(&target);
+        let target_schemas = Arc::clone(&target_schemas);
+        let options = options.clone();
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let result = async {
+                let connection = PgConnection::connect(&url).await?;
+                apply_target_schema(&target, &connection, options, &target_schemas).await
+            }
+            .await;
+            (
+                index,
+                DatabaseApplyResult {
+                    database: url,
+                    result,
+                },
+            )
+        });
+    }
+
+    let mut results: Vec<(usize, DatabaseApplyResult)> = Vec::with_capacity(database_urls.len());
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok(entry) => results.push(entry),
+            Err(e) => std::panic::resume_unwind(e.into_panic()),
+        }
+    }
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn databases_file_skips_blank_lines_and_comments() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("urls.txt");
+        std::fs::write(
+            &path,
+            "# tenants\npostgres://a/db1\n\n  postgres://b/db2  \n# retired: postgres://c/db3\n",
+        )
+        .unwrap();
+
+        let urls = read_databases_file(path.to_str().unwrap()).unwrap();
+        assert_eq!(urls, vec!["postgres://a/db1", "postgres://b/db2"]);
+    }
+
+    #[test]
+    fn databases_file_must_list_a_database() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("urls.txt");
+        std::fs::write(&path, "# nothing yet\n").unwrap();
+
+        assert!(read_databases_file(path.to_str().unwrap()).is_err());
+    }
+}
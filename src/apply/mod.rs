@@ -1,17 +1,57 @@
-use sqlx::Executor;
-
 use crate::diff::{
-    compute_diff, compute_diff_with_flags, planner::plan_migration_checked, MigrationOp,
+    compute_diff_with_options,
+    planner::{plan_migration_checked, plan_migration_subset},
+    AdoptionMode, DefaultComparison, DiffOptions, MigrationOp, TypeEquivalences,
+};
+use crate::filter::{
+    exclude_external, exclude_unmanaged_partitions, filter_by_target_schemas, filter_schema, Filter,
 };
-use crate::filter::{filter_by_target_schemas, filter_schema, Filter};
-use crate::lint::{lint_migration_plan, LintOptions, LintResult, LintSeverity};
+use crate::lint::{
+    cascade_schema_drops, inbound_foreign_key_drops, lint_migration_plan, DropThreshold,
+    LintOptions, LintResult, LintSeverity,
+};
+use crate::model::{parse_qualified_name, Schema};
 use crate::parser::load_schema_sources;
 use crate::pg::connection::PgConnection;
-use crate::pg::introspect::introspect_schema;
-use crate::pg::sqlgen::generate_sql;
-use crate::plan::PlanOptions;
-use crate::provider::load_schema_from_sources;
+use crate::pg::introspect::{
+    check_required_settings, estimate_table_sizes, introspect_schema,
+    introspect_schema_with_known_bodies,
+};
+use crate::pg::retention::expired_partitions;
+use crate::pg::sqlgen::{
+    generate_op_sql_with_options, generate_sql, generate_sql_with_options,
+    materialized_view_refreshes, quote_ident, SqlOptions,
+};
+use crate::plan::{
+    check_trigger_functions, transaction_phases, with_concurrent_indexes, with_retention_drops,
+    PlanOptions,
+};
+use crate::provider::{load_schema_from_sources, MergeOptions};
 use crate::util::{Result, SchemaError};
+use std::collections::{BTreeMap, HashSet};
+use std::str::FromStr;
+use std::time::Instant;
+
+mod blockers;
+mod fleet;
+mod noop;
+mod outcome;
+mod roles;
+mod savepoint;
+mod script;
+mod server_version;
+
+pub use blockers::{
+    check_blocking_transactions, exclusively_locked_tables, find_blocking_transactions,
+    BlockingTransaction, TransactionAgeCheck,
+};
+pub use fleet::{apply_to_databases, read_databases_file, DatabaseApplyResult};
+pub use noop::{noop_reason, SkippedOperation};
+pub use outcome::{OperationOutcome, OperationStatus};
+pub use roles::{check_roles_exist, find_missing_roles, referenced_roles};
+pub use savepoint::{execute_isolated, failures_error, StatementFailure};
+pub use script::{write_script, SessionTimeouts};
+pub use server_version::check_server_version;
 
 #[derive(Debug, Clone)]
 pub struct VerifyResult {
@@ -34,18 +74,19 @@ pub async fn verify_after_apply(
     let raw_current = introspect_schema(connection, target_schemas, false).await?;
     let current = filter_schema(&raw_current, filter);
     let current = if options.exclude_unmanaged_partitions {
-        crate::filter::exclude_unmanaged_partitions(&current, &target)
+        exclude_unmanaged_partitions(&current, &target)
     } else {
         current
     };
-    let residual_operations = plan_migration_checked(compute_diff_with_flags(
+    let residual_operations = plan_migration_checked(compute_diff_with_options(
         &current,
         &target,
-        options.manage_ownership,
-        options.manage_grants,
-        &options.excluded_grant_roles,
+        &options.diff_options(),
     ))
-    .map_err(|e| SchemaError::ValidationError(e.to_string()))?;
+    .map_err(|e| SchemaError::ValidationError(e.to_string()))?
+    .into_iter()
+    .filter(|op| filter.should_plan(op))
+    .collect::<Vec<_>>();
     let convergent = residual_operations.is_empty();
     Ok(VerifyResult {
         convergent,
@@ -57,6 +98,90 @@ pub async fn verify_after_apply(
 pub struct ApplyOptions {
     pub dry_run: bool,
     pub allow_destructive: bool,
+    /// Restrict the migration to operations touching these objects (plus their
+    /// dependencies). Empty means apply everything.
+    pub only: Vec<ObjectSelector>,
+    /// Drop the foreign keys that still reference a dropped table before
+    /// dropping it, instead of failing lint, and drop schemas with `CASCADE`.
+    pub cascade: bool,
+    /// Refuse to run while a transaction older than this holds a lock on a
+    /// table the migration locks exclusively.
+    pub transaction_age_check: Option<TransactionAgeCheck>,
+    /// Create materialized views `WITH NO DATA` and refresh them one at a
+    /// time after the migration commits, so populating a large view does not
+    /// hold the migration's locks.
+    pub defer_materialized_view_refresh: bool,
+    /// Create and drop indexes `CONCURRENTLY`, outside the migration's
+    /// transaction; see [`with_concurrent_indexes`].
+    pub concurrent_indexes: bool,
+    /// Leaves the objects it excludes out of both sides of the diff, and
+    /// changes to schemas it marks observe-only out of the plan.
+    pub filter: Filter,
+    /// See [`PlanOptions::include_extension_objects`].
+    pub include_extension_objects: bool,
+    /// See [`PlanOptions::exclude_unmanaged_partitions`].
+    pub exclude_unmanaged_partitions: bool,
+    pub manage_ownership: bool,
+    pub manage_grants: bool,
+    pub excluded_grant_roles: HashSet<String>,
+    /// See [`PlanOptions::role_aliases`].
+    pub role_aliases: BTreeMap<String, String>,
+    /// `Additive` never drops or strips anything the sources leave out.
+    pub adoption_mode: AdoptionMode,
+    pub type_equivalences: TypeEquivalences,
+    pub default_comparison: DefaultComparison,
+    /// See [`DiffOptions::detect_renames`].
+    pub detect_renames: bool,
+    /// Tables and partitions this admits may be dropped without
+    /// `allow_destructive`; overrides `PGMOLD_DROP_THRESHOLD`.
+    pub drop_threshold: Option<DropThreshold>,
+    /// `SET LOCAL` timeouts run at the start of the migration transaction.
+    pub timeouts: SessionTimeouts,
+}
+
+impl ApplyOptions {
+    pub fn diff_options(&self) -> DiffOptions<'_> {
+        DiffOptions {
+            manage_ownership: self.manage_ownership,
+            manage_grants: self.manage_grants,
+            excluded_grant_roles: &self.excluded_grant_roles,
+            adoption_mode: self.adoption_mode,
+            type_equivalences: &self.type_equivalences,
+            default_comparison: self.default_comparison,
+            detect_renames: self.detect_renames,
+        }
+    }
+}
+
+/// A schema-qualified object name used to select operations for a partial apply.
+/// Table-scoped operations (columns, indexes, constraints, policies, triggers)
+/// are selected through their table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectSelector {
+    pub schema: String,
+    pub name: String,
+}
+
+impl ObjectSelector {
+    pub fn matches(&self, op: &MigrationOp) -> bool {
+        op.subject().is_some_and(|subject| {
+            parse_qualified_name(&subject) == (self.schema.clone(), self.name.clone())
+        })
+    }
+}
+
+impl FromStr for ObjectSelector {
+    type Err = SchemaError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (schema, name) = parse_qualified_name(s.trim());
+        if schema.is_empty() || name.is_empty() {
+            return Err(SchemaError::ParseError(format!(
+                "Invalid object selector '{s}': expected [schema.]name"
+            )));
+        }
+        Ok(Self { schema, name })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -65,6 +190,11 @@ pub struct ApplyResult {
     pub sql_statements: Vec<String>,
     pub lint_results: Vec<LintResult>,
     pub applied: bool,
+    /// Operations not executed because the database already matched them.
+    pub skipped: Vec<SkippedOperation>,
+    /// Every operation in plan order with its statements, status and how long
+    /// it took. Empty for a dry run.
+    pub outcomes: Vec<OperationOutcome>,
 }
 
 pub async fn apply_migration(
@@ -87,14 +217,59 @@ pub async fn apply_migration_with_schemas(
     options: ApplyOptions,
     target_schemas: &[String],
 ) -> Result<ApplyResult> {
-    let target = load_schema_sources(schema_sources)?;
-    let current = introspect_schema(connection, target_schemas, false).await?;
+    let mut target = load_schema_sources(schema_sources)?;
+    target.external.extend(MergeOptions::from_env()?.external);
+    apply_target_schema(&target, connection, options, target_schemas).await
+}
+
+/// Like [`apply_migration_with_schemas`], for a target schema already loaded.
+pub async fn apply_target_schema(
+    target: &Schema,
+    connection: &PgConnection,
+    options: ApplyOptions,
+    target_schemas: &[String],
+) -> Result<ApplyResult> {
+    let mut target = exclude_external(&filter_schema(target, &options.filter), &target.external);
+    target.apply_role_aliases(&options.role_aliases);
+    let current = introspect_schema_with_known_bodies(
+        connection,
+        target_schemas,
+        options.include_extension_objects,
+        &target,
+    )
+    .await?;
+    let current = exclude_external(&filter_schema(&current, &options.filter), &target.external);
+    let current = if options.exclude_unmanaged_partitions {
+        exclude_unmanaged_partitions(&current, &target)
+    } else {
+        current
+    };
 
-    let ops = plan_migration_checked(compute_diff(&current, &target))
-        .map_err(|e| SchemaError::ValidationError(e.to_string()))?;
+    let mut ops = plan_target_ops(&current, &target, &options)?;
+    if options.only.is_empty() && !target.partition_retention.is_empty() {
+        let expired = expired_partitions(connection, &current, &target).await?;
+        ops = with_retention_drops(ops, &expired, &options.filter);
+    }
 
-    let lint_options = LintOptions::from_env(options.allow_destructive);
-    let lint_results = lint_migration_plan(&ops, &lint_options);
+    let mut lint_options = LintOptions::from_env(options.allow_destructive);
+    if options.drop_threshold.is_some() {
+        lint_options.drop_threshold = options.drop_threshold;
+    }
+    if lint_options.drop_threshold.is_some() {
+        let dropped: Vec<String> = ops
+            .iter()
+            .filter_map(|op| match op {
+                MigrationOp::DropTable(name) | MigrationOp::DropPartition(name) => {
+                    Some(name.clone())
+                }
+                _ => None,
+            })
+            .collect();
+        lint_options.table_sizes = estimate_table_sizes(connection, &dropped).await?;
+    }
+    lint_options.current = Some(current.clone());
+    let mut lint_results = lint_migration_plan(&ops, &lint_options);
+    lint_results.extend(check_required_settings(connection, &lint_options).await?);
 
     let error_messages: Vec<String> = lint_results
         .iter()
@@ -109,28 +284,111 @@ pub async fn apply_migration_with_schemas(
         )));
     }
 
-    let sql = generate_sql(&ops);
+    let sql_options = SqlOptions {
+        defer_materialized_view_data: options.defer_materialized_view_refresh,
+        ..SqlOptions::default()
+    };
+    let sql = generate_sql_with_options(&ops, &sql_options);
+    let refreshes = if options.defer_materialized_view_refresh {
+        materialized_view_refreshes(&ops)
+    } else {
+        Vec::new()
+    };
 
     if options.dry_run {
         return Ok(ApplyResult {
-            operations: ops,
+            operations: ops.into_iter().chain(refreshes).collect(),
             sql_statements: sql,
             lint_results,
             applied: false,
+            skipped: Vec::new(),
+            outcomes: Vec::new(),
         });
     }
 
-    let mut transaction = connection
-        .pool()
-        .begin()
-        .await
-        .map_err(|e| SchemaError::DatabaseError(format!("Failed to begin transaction: {e}")))?;
+    check_roles_exist(connection, &ops).await?;
+    check_server_version(connection, &ops).await?;
+    if let Some(check) = &options.transaction_age_check {
+        check_blocking_transactions(connection, &ops, check).await?;
+    }
+
+    let phases = transaction_phases(ops.clone());
+    let mut outcomes = Vec::new();
+    for op in &phases.before {
+        let outcome = execute_outside_transaction(connection, op)
+            .await
+            .map_err(|e| SchemaError::DatabaseError(not_started(&e, &outcomes)))?;
+        outcomes.push(outcome);
+    }
 
-    for statement in &sql {
-        transaction
-            .execute(statement.as_str())
+    let mut transaction = connection.pool().begin().await.map_err(|e| {
+        SchemaError::DatabaseError(not_started(
+            &format!("Failed to begin transaction: {e}"),
+            &outcomes,
+        ))
+    })?;
+    for statement in options.timeouts.statements() {
+        sqlx::Executor::execute(&mut *transaction, statement.as_str())
             .await
-            .map_err(|e| SchemaError::DatabaseError(format!("Failed to execute SQL: {e}")))?;
+            .map_err(|e| SchemaError::DatabaseError(format!("Failed to run {statement}: {e}")))?;
+    }
+
+    // Each statement runs in its own savepoint so one failure does not hide
+    // the rest; any failure rolls back the whole transaction at the end.
+    let mut skipped = Vec::new();
+    let mut failures = Vec::new();
+    let mut statement_index = 0;
+    for op in &phases.transaction {
+        let statements = generate_op_sql_with_options(op, &sql_options);
+        let started = Instant::now();
+        if let Some(reason) = noop_reason(&mut transaction, op).await? {
+            statement_index += statements.len();
+            outcomes.push(OperationOutcome {
+                operation: op.clone(),
+                statements: statements.clone(),
+                status: OperationStatus::Skipped,
+                duration: started.elapsed(),
+            });
+            skipped.push(SkippedOperation {
+                operation: op.clone(),
+                statements,
+                reason,
+            });
+            continue;
+        }
+        let mut status = OperationStatus::Applied;
+        for statement in &statements {
+            if let Err(error_message) = execute_isolated(&mut transaction, statement).await? {
+                failures.push(StatementFailure {
+                    statement_index,
+                    sql: statement.clone(),
+                    error_message,
+                });
+                status = OperationStatus::Failed;
+            }
+            statement_index += 1;
+        }
+        outcomes.push(OperationOutcome {
+            operation: op.clone(),
+            statements,
+            status,
+            duration: started.elapsed(),
+        });
+    }
+
+    if !failures.is_empty() {
+        transaction.rollback().await.map_err(|e| {
+            SchemaError::DatabaseError(format!("Failed to roll back transaction: {e}"))
+        })?;
+        let mut error = failures_error(&failures);
+        if let (SchemaError::DatabaseError(message), Some(indexes)) =
+            (&mut error, dropped_concurrently(&outcomes))
+        {
+            message.push_str(&format!(
+                "\n{indexes} were dropped concurrently before the transaction and stay dropped"
+            ));
+        }
+        return Err(error);
     }
 
     transaction
@@ -138,23 +396,203 @@ pub async fn apply_migration_with_schemas(
         .await
         .map_err(|e| SchemaError::DatabaseError(format!("Failed to commit transaction: {e}")))?;
 
+    for op in &phases.after {
+        outcomes.push(
+            execute_outside_transaction(connection, op)
+                .await
+                .map_err(|e| SchemaError::DatabaseError(format!("Migration committed, but {e}")))?,
+        );
+    }
+
+    // Each refresh runs on its own once the migration has committed; a
+    // failure leaves the view unpopulated but the migration in place.
+    for op in &refreshes {
+        let statements = generate_sql(std::slice::from_ref(op));
+        let started = Instant::now();
+        for statement in &statements {
+            sqlx::query(statement)
+                .execute(connection.pool())
+                .await
+                .map_err(|e| {
+                    SchemaError::DatabaseError(format!(
+                        "Migration committed, but {statement} failed: {e}"
+                    ))
+                })?;
+        }
+        outcomes.push(OperationOutcome {
+            operation: op.clone(),
+            statements,
+            status: OperationStatus::Applied,
+            duration: started.elapsed(),
+        });
+    }
+
     Ok(ApplyResult {
-        operations: ops,
+        operations: ops.into_iter().chain(refreshes).collect(),
         sql_statements: sql,
         lint_results,
         applied: true,
+        skipped,
+        outcomes,
     })
 }
 
+/// Runs `op` outside any transaction, for the operations PostgreSQL refuses
+/// to run inside one. The error names the statement that failed. A failed
+/// concurrent build leaves an invalid index behind, which is dropped so the
+/// next attempt can reuse its name.
+pub async fn execute_outside_transaction(
+    connection: &PgConnection,
+    op: &MigrationOp,
+) -> std::result::Result<OperationOutcome, String> {
+    let statements = generate_sql(std::slice::from_ref(op));
+    let started = Instant::now();
+    for statement in &statements {
+        if let Err(e) = sqlx::query(statement).execute(connection.pool()).await {
+            let mut message = format!("{statement} failed: {e}");
+            if let MigrationOp::CreateIndexConcurrently { table, index } = op {
+                match drop_invalid_index(connection, &table.schema, &index.name).await {
+                    Ok(true) => message.push_str("; dropped the invalid index it left behind"),
+                    Ok(false) => {}
+                    Err(cleanup) => message.push_str(&format!(
+                        "; the invalid index it left behind could not be dropped: {cleanup}"
+                    )),
+                }
+            }
+            return Err(message);
+        }
+    }
+    Ok(OperationOutcome {
+        operation: op.clone(),
+        statements,
+        status: OperationStatus::Applied,
+        duration: started.elapsed(),
+    })
+}
+
+/// The ordered operations taking `current` to `target` under `options`.
+fn plan_target_ops(
+    current: &Schema,
+    target: &Schema,
+    options: &ApplyOptions,
+) -> Result<Vec<MigrationOp>> {
+    let mut ops = compute_diff_with_options(current, target, &options.diff_options());
+    if options.cascade {
+        ops.extend(inbound_foreign_key_drops(&ops, current));
+    }
+    let ops = if options.only.is_empty() {
+        plan_migration_checked(ops)
+    } else {
+        plan_migration_subset(ops, |op| options.only.iter().any(|s| s.matches(op)))
+    }
+    .map_err(|e| SchemaError::ValidationError(e.to_string()))?;
+    let ops: Vec<MigrationOp> = ops
+        .into_iter()
+        .filter(|op| options.filter.should_plan(op))
+        .collect();
+    let ops = if options.cascade {
+        cascade_schema_drops(ops)
+    } else {
+        ops
+    };
+    let ops = if options.concurrent_indexes {
+        with_concurrent_indexes(ops)
+    } else {
+        ops
+    };
+    check_trigger_functions(&ops, target)?;
+    Ok(ops)
+}
+
+/// The message for `error` stopping the migration before its transaction
+/// began. The indexes `done` already dropped concurrently stay dropped, so
+/// they are named rather than claiming nothing ran.
+pub fn not_started(error: &str, done: &[OperationOutcome]) -> String {
+    match dropped_concurrently(done) {
+        None => format!("{error}; the migration was not started"),
+        Some(indexes) => format!(
+            "{error}; the transaction was not started, but {indexes} were already dropped concurrently"
+        ),
+    }
+}
+
+/// The indexes `done` dropped concurrently, which no rollback restores, or
+/// None when it dropped none.
+pub fn dropped_concurrently(done: &[OperationOutcome]) -> Option<String> {
+    let indexes: Vec<String> = done
+        .iter()
+        .filter_map(|outcome| match &outcome.operation {
+            MigrationOp::DropIndexConcurrently { table, index_name } => {
+                Some(crate::model::qualified_name(&table.schema, index_name))
+            }
+            _ => None,
+        })
+        .collect();
+    (!indexes.is_empty()).then(|| indexes.join(", "))
+}
+
+/// Drops `schema.index_name` if it exists and is invalid, as an interrupted
+/// `CREATE INDEX CONCURRENTLY` leaves it. Returns whether it was dropped.
+async fn drop_invalid_index(
+    connection: &PgConnection,
+    schema: &str,
+    index_name: &str,
+) -> std::result::Result<bool, sqlx::Error> {
+    let index = format!("{}.{}", quote_ident(schema), quote_ident(index_name));
+    let invalid: Option<bool> = sqlx::query_scalar(
+        "SELECT NOT indisvalid FROM pg_index WHERE indexrelid = to_regclass($1)",
+    )
+    .bind(&index)
+    .fetch_optional(connection.pool())
+    .await?;
+    if invalid != Some(true) {
+        return Ok(false);
+    }
+    sqlx::query(&format!("DROP INDEX CONCURRENTLY IF EXISTS {index};"))
+        .execute(connection.pool())
+        .await?;
+    Ok(true)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::QualifiedName;
 
     #[test]
     fn apply_options_default() {
         let options = ApplyOptions::default();
         assert!(!options.dry_run);
         assert!(!options.allow_destructive);
+        assert!(options.only.is_empty());
+    }
+
+    #[test]
+    fn object_selector_defaults_to_public_schema() {
+        let selector: ObjectSelector = "users".parse().unwrap();
+        assert_eq!(selector.schema, "public");
+        assert_eq!(selector.name, "users");
+
+        let selector: ObjectSelector = "auth.sessions".parse().unwrap();
+        assert_eq!(selector.schema, "auth");
+        assert_eq!(selector.name, "sessions");
+
+        assert!(".users".parse::<ObjectSelector>().is_err());
+    }
+
+    #[test]
+    fn object_selector_matches_table_scoped_ops() {
+        let selector: ObjectSelector = "public.users".parse().unwrap();
+        let drop_column = MigrationOp::DropColumn {
+            table: QualifiedName::new("public", "users"),
+            column: "legacy".to_string(),
+        };
+        let other_table = MigrationOp::DropColumn {
+            table: QualifiedName::new("public", "orders"),
+            column: "legacy".to_string(),
+        };
+        assert!(selector.matches(&drop_column));
+        assert!(!selector.matches(&other_table));
     }
 
     #[test]
@@ -164,8 +602,64 @@ mod tests {
             sql_statements: vec!["CREATE TABLE test;".to_string()],
             lint_results: Vec::new(),
             applied: false,
+            skipped: Vec::new(),
+            outcomes: Vec::new(),
         };
         assert!(!result.applied);
         assert_eq!(result.sql_statements.len(), 1);
     }
+
+    #[test]
+    fn additive_plan_drops_nothing() {
+        let current = crate::parser::parse_sql_string(
+            "CREATE TABLE users (id bigint PRIMARY KEY);
+             CREATE TABLE legacy (id bigint);
+             CREATE TABLE audit (id bigint);",
+        )
+        .unwrap();
+        let target = crate::parser::parse_sql_string(
+            "CREATE TABLE users (id bigint PRIMARY KEY, email text);",
+        )
+        .unwrap();
+        let drops = |options: &ApplyOptions| {
+            plan_target_ops(&current, &target, options)
+                .unwrap()
+                .into_iter()
+                .filter(|op| matches!(op, MigrationOp::DropTable(_)))
+                .count()
+        };
+
+        assert_eq!(drops(&ApplyOptions::default()), 2);
+        assert_eq!(
+            drops(&ApplyOptions {
+                adoption_mode: AdoptionMode::Additive,
+                ..ApplyOptions::default()
+            }),
+            0
+        );
+    }
+
+    #[test]
+    fn not_started_names_indexes_already_dropped() {
+        assert_eq!(
+            not_started("DROP INDEX failed", &[]),
+            "DROP INDEX failed; the migration was not started"
+        );
+
+        let dropped = OperationOutcome {
+            operation: MigrationOp::DropIndexConcurrently {
+                table: QualifiedName::new("public", "users"),
+                index_name: "users_name_idx".to_string(),
+            },
+            statements: Vec::new(),
+            status: OperationStatus::Applied,
+            duration: std::time::Duration::ZERO,
+        };
+        let message = not_started("DROP INDEX failed", &[dropped]);
+        assert!(!message.contains("migration was not started"), "{message}");
+        assert!(
+            message.contains("public.users_name_idx were already dropped"),
+            "{message}"
+        );
+    }
 }
@@ -0,0 +1,239 @@
+use crate::diff::{CommentObjectType, MigrationOp, OwnerObjectKind};
+use crate::util::{Result, SchemaError};
+
+/// An operation left out of an apply because the database already matched it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedOperation {
+    pub operation: MigrationOp,
+    pub statements: Vec<String>,
+    pub reason: String,
+}
+
+/// A catalog predicate that holds when an operation's effect is already in
+/// place. Parameters are bound as text and cast in the query.
+struct NoopCheck {
+    query: &'static str,
+    params: Vec<Option<String>>,
+    reason: String,
+}
+
+const RELATION_FLAG_QUERY: &str = "SELECT EXISTS (
+    SELECT 1 FROM pg_class c JOIN pg_namespace n ON n.oid = c.relnamespace
+    WHERE n.nspname = $1 AND c.relname = $2 AND c.relrowsecurity = $3::boolean)";
+const RELATION_FORCE_FLAG_QUERY: &str = "SELECT EXISTS (
+    SELECT 1 FROM pg_class c JOIN pg_namespace n ON n.oid = c.relnamespace
+    WHERE n.nspname = $1 AND c.relname = $2 AND c.relforcerowsecurity = $3::boolean)";
+const SCHEMA_EXISTS_QUERY: &str =
+    "SELECT EXISTS (SELECT 1 FROM pg_namespace WHERE nspname = $1) = $2::boolean";
+const EXTENSION_EXISTS_QUERY: &str =
+    "SELECT EXISTS (SELECT 1 FROM pg_extension WHERE extname = $1) = $2::boolean";
+const RELATION_OWNER_QUERY: &str = "SELECT EXISTS (
+    SELECT 1 FROM pg_class c JOIN pg_namespace n ON n.oid = c.relnamespace
+    WHERE n.nspname = $1 AND c.relname = $2 AND pg_get_userbyid(c.relowner) = $3)";
+const TABLE_COMMENT_QUERY: &str = "SELECT EXISTS (
+    SELECT 1 FROM pg_class c JOIN pg_namespace n ON n.oid = c.relnamespace
+    WHERE n.nspname = $1 AND c.relname = $2
+      AND obj_description(c.oid, 'pg_class') IS NOT DISTINCT FROM $3)";
+const COLUMN_COMMENT_QUERY: &str = "SELECT EXISTS (
+    SELECT 1 FROM pg_attribute a
+    JOIN pg_class c ON c.oid = a.attrelid
+    JOIN pg_namespace n ON n.oid = c.relnamespace
+    WHERE n.nspname = $1 AND c.relname = $2 AND a.attname = $3 AND NOT a.attisdropped
+      AND col_description(c.oid, a.attnum) IS NOT DISTINCT FROM $4)";
+const POLICY_COMMENT_QUERY: &str = "SELECT EXISTS (
+    SELECT 1 FROM pg_policy p
+    JOIN pg_class c ON c.oid = p.polrelid
+    JOIN pg_namespace n ON n.oid = c.relnamespace
+    WHERE n.nspname = $1 AND c.relname = $2 AND p.polname = $3
+      AND obj_description(p.oid, 'pg_policy') IS NOT DISTINCT FROM $4)";
+
+fn text(value: &str) -> Option<String> {
+    Some(value.to_string())
+}
+
+fn flag(value: bool) -> Option<String> {
+    Some(value.to_string())
+}
+
+/// The check that confirms `op` is a no-op, for the operations where one
+/// catalog lookup settles it. Everything else always runs.
+fn noop_check(op: &MigrationOp) -> Option<NoopCheck> {
+    let check = |query, params, reason: &str| {
+        Some(NoopCheck {
+            query,
+            params,
+            reason: reason.to_string(),
+        })
+    };
+    match op {
+        MigrationOp::EnableRls { table } | MigrationOp::DisableRls { table } => {
+            let enable = matches!(op, MigrationOp::EnableRls { .. });
+            check(
+                RELATION_FLAG_QUERY,
+                vec![text(&table.schema), text(&table.name), flag(enable)],
+                if enable {
+                    "row level security already enabled"
+                } else {
+                    "row level security already disabled"
+                },
+            )
+        }
+        MigrationOp::ForceRls { table } | MigrationOp::NoForceRls { table } => {
+            let force = matches!(op, MigrationOp::ForceRls { .. });
+            check(
+                RELATION_FORCE_FLAG_QUERY,
+                vec![text(&table.schema), text(&table.name), flag(force)],
+                if force {
+                    "row level security already forced"
+                } else {
+                    "row level security already not forced"
+                },
+            )
+        }
+        MigrationOp::CreateSchema(schema) => check(
+            SCHEMA_EXISTS_QUERY,
+            vec![text(&schema.name), flag(true)],
+            "schema already exists",
+        ),
+        MigrationOp::DropSchema { name, .. } => check(
+            SCHEMA_EXISTS_QUERY,
+            vec![text(name), flag(false)],
+            "schema does not exist",
+        ),
+        MigrationOp::CreateExtension(extension) => check(
+            EXTENSION_EXISTS_QUERY,
+            vec![text(&extension.name), flag(true)],
+            "extension already installed",
+        ),
+        MigrationOp::DropExtension(name) => check(
+            EXTENSION_EXISTS_QUERY,
+            vec![text(name), flag(false)],
+            "extension not installed",
+        ),
+        MigrationOp::AlterOwner {
+            object_kind:
+                OwnerObjectKind::Table
+                | OwnerObjectKind::Partition
+                | OwnerObjectKind::View
+                | OwnerObjectKind::MaterializedView
+                | OwnerObjectKind::Sequence,
+            schema,
+            name,
+            new_owner,
+            ..
+        } => check(
+            RELATION_OWNER_QUERY,
+            vec![text(schema), text(name), text(new_owner)],
+            "owner already set",
+        ),
+        MigrationOp::SetComment {
+            object_type,
+            schema,
+            name,
+            column,
+            target,
+            comment,
+            ..
+        } => match (object_type, column, target) {
+            (CommentObjectType::Table, None, _) => check(
+                TABLE_COMMENT_QUERY,
+                vec![text(schema), text(name), comment.clone()],
+                "comment already set",
+            ),
+            (CommentObjectType::Column, Some(column), _) => check(
+                COLUMN_COMMENT_QUERY,
+                vec![text(schema), text(name), text(column), comment.clone()],
+                "comment already set",
+            ),
+            (CommentObjectType::Policy, _, Some(table)) => check(
+                POLICY_COMMENT_QUERY,
+                vec![text(schema), text(table), text(name), comment.clone()],
+                "comment already set",
+            ),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns why `op` can be skipped, or `None` when it must run. Pass the open
+/// apply transaction so the check sees the statements already executed in
+/// it; a statement repeated later in a plan is skipped this way once its
+/// effect is in place.
+pub async fn noop_reason(
+    connection: &mut sqlx::PgConnection,
+    op: &MigrationOp,
+) -> Result<Option<String>> {
+    let Some(check) = noop_check(op) else {
+        return Ok(None);
+    };
+    let mut query = sqlx::query_scalar::<_, bool>(check.query);
+    for param in check.params {
+        query = query.bind(param);
+    }
+    let satisfied = query
+        .fetch_one(connection)
+        .await
+        .map_err(|e| SchemaError::DatabaseError(format!("Failed to check for no-op: {e}")))?;
+    Ok(satisfied.then_some(check.reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{PgSchema, QualifiedName};
+
+    #[test]
+    fn rls_checks_bind_the_expected_flag() {
+        let table = QualifiedName::new("app", "notes");
+        let enable = noop_check(&MigrationOp::EnableRls {
+            table: table.clone(),
+        })
+        .unwrap();
+        assert_eq!(enable.query, RELATION_FLAG_QUERY);
+        assert_eq!(enable.params, vec![text("app"), text("notes"), flag(true)]);
+
+        let no_force = noop_check(&MigrationOp::NoForceRls { table }).unwrap();
+        assert_eq!(no_force.query, RELATION_FORCE_FLAG_QUERY);
+        assert_eq!(no_force.params[2], flag(false));
+    }
+
+    #[test]
+    fn comment_checks_compare_nullable_text() {
+        let clear = noop_check(&MigrationOp::SetComment {
+            object_type: CommentObjectType::Column,
+            schema: "public".to_string(),
+            name: "users".to_string(),
+            arguments: None,
+            column: Some("email".to_string()),
+            target: None,
+            on_domain: false,
+            comment: None,
+        })
+        .unwrap();
+        assert_eq!(clear.query, COLUMN_COMMENT_QUERY);
+        assert_eq!(
+            clear.params,
+            vec![text("public"), text("users"), text("email"), None]
+        );
+    }
+
+    #[test]
+    fn operations_without_a_cheap_check_always_run() {
+        assert!(noop_check(&MigrationOp::DropTable("public.t".to_string())).is_none());
+        assert!(noop_check(&MigrationOp::AlterOwner {
+            object_kind: OwnerObjectKind::Function,
+            schema: "public".to_string(),
+            name: "f".to_string(),
+            args: Some(String::new()),
+            new_owner: "app".to_string(),
+        })
+        .is_none());
+        assert!(noop_check(&MigrationOp::CreateSchema(PgSchema {
+            name: "app".to_string(),
+            grants: Vec::new(),
+            comment: None,
+        }))
+        .is_some());
+    }
+}
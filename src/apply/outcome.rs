@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::diff::MigrationOp;
+
+/// What happened to one operation during an apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationStatus {
+    Applied,
+    /// Left out because the database already matched it.
+    Skipped,
+    /// At least one of its statements failed; the whole apply was rolled back.
+    Failed,
+}
+
+/// One planned operation, the statements generated for it, and how running
+/// them went.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OperationOutcome {
+    pub operation: MigrationOp,
+    pub statements: Vec<String>,
+    pub status: OperationStatus,
+    pub duration: Duration,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_serializes_in_snake_case() {
+        let outcome = OperationOutcome {
+            operation: MigrationOp::DropEnum("public.status".to_string()),
+            statements: vec!["DROP TYPE \"public\".\"status\";".to_string()],
+            status: OperationStatus::Skipped,
+            duration: Duration::from_millis(3),
+        };
+
+        let json = serde_json::to_value(&outcome).unwrap();
+        assert_eq!(json["status"], "skipped");
+        assert_eq!(json["duration"]["nanos"], 3_000_000);
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::BTreeSet;
+
+use crate::diff::MigrationOp;
+use crate::model::ROLE_KEYWORDS;
+use crate::pg::connection::PgConnection;
+use crate::util::{Result, SchemaError};
+
+/// Collects every role that policies, grants, revokes, ownership changes and
+/// default privileges in `ops` name. Pseudo-roles such as `PUBLIC` are skipped.
+pub fn referenced_roles(ops: &[MigrationOp]) -> BTreeSet<String> {
+    let mut roles = BTreeSet::new();
+    for op in ops {
+        match op {
+            MigrationOp::CreatePolicy(policy) => roles.extend(policy.roles.iter().cloned()),
+            MigrationOp::AlterPolicy { changes, .. } => {
+                roles.extend(changes.roles.iter().flatten().cloned())
+            }
+            MigrationOp::AlterOwner { new_owner, .. } => {
+                roles.insert(new_owner.clone());
+            }
+            MigrationOp::GrantPrivileges { grantee, .. }
+            | MigrationOp::RevokePrivileges { grantee, .. } => {
+                roles.insert(grantee.clone());
+            }
+            MigrationOp::AlterDefaultPrivileges {
+                target_role,
+                grantee,
+                ..
+            } => {
+                roles.insert(target_role.clone());
+                roles.insert(grantee.clone());
+            }
+            _ => {}
+        }
+    }
+    roles.retain(|role| {
+        !ROLE_KEYWORDS
+            .iter()
+            .any(|pseudo| role.eq_ignore_ascii_case(pseudo))
+    });
+    roles
+}
+
+/// Returns the roles referenced by `ops` that do not exist in `pg_roles`.
+pub async fn find_missing_roles(
+    connection: &PgConnection,
+    ops: &[MigrationOp],
+) -> Result<Vec<String>> {
+    let referenced = referenced_roles(ops);
+    if referenced.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let names: Vec<String> = referenced.iter().cloned().collect();
+    let existing: BTreeSet<String> = sqlx::query_scalar::<_, String>(
+        "SELECT rolname::text FROM pg_roles WHERE rolname::text = ANY($1)",
+    )
+    .bind(&names)
+    .fetch_all(connection.pool())
+    .await
+    .map_err(|e| SchemaError::DatabaseError(format!("Failed to look up roles: {e}")))?
+    .into_iter()
+    .collect();
+
+    Ok(referenced
+        .into_iter()
+        .filter(|role| !existing.contains(role))
+        .collect())
+}
+
+/// Fails with every missing role listed at once when `ops` reference roles
+/// the target cluster does not have, before any statement is executed.
+pub async fn check_roles_exist(connection: &PgConnection, ops: &[MigrationOp]) -> Result<()> {
+    let missing = find_missing_roles(connection, ops).await?;
+    if missing.is_empty() {
+        return Ok(());
+    }
+    Err(SchemaError::ValidationError(missing_roles_message(
+        &missing,
+    )))
+}
+
+fn missing_roles_message(missing: &[String]) -> String {
+    let create: Vec<String> = missing
+        .iter()
+        .map(|role| format!("  CREATE ROLE \"{}\" NOLOGIN;", role.replace('"', "\"\"")))
+        .collect();
+    format!(
+        "Migration references {} role(s) that do not exist on the target database: {}\n\
+         Create them before applying:\n{}\n\
+         or leave their grants out of the plan with --exclude-grants-for-role.",
+        missing.len(),
+        missing.join(", "),
+        create.join("\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::{GrantObjectKind, OwnerObjectKind, PolicyChanges};
+    use crate::model::{Policy, PolicyCommand, Privilege, QualifiedName};
+
+    fn policy(roles: &[&str]) -> MigrationOp {
+        MigrationOp::CreatePolicy(Policy {
+            name: "own_rows".to_string(),
+            table_schema: "public".to_string(),
+            table: "notes".to_string(),
+            command: PolicyCommand::All,
+            roles: roles.iter().map(|r| r.to_string()).collect(),
+            using_expr: None,
+            check_expr: None,
+            comment: None,
+        })
+    }
+
+    #[test]
+    fn collects_roles_from_policies_grants_and_owners() {
+        let ops = vec![
+            policy(&["authenticated", "public"]),
+            MigrationOp::AlterPolicy {
+                table: QualifiedName::new("public", "notes"),
+                name: "own_rows".to_string(),
+                changes: PolicyChanges {
+                    roles: Some(vec!["service_role".to_string()]),
+                    using_expr: None,
+                    check_expr: None,
+                },
+            },
+            MigrationOp::AlterOwner {
+                object_kind: OwnerObjectKind::Table,
+                schema: "public".to_string(),
+                name: "notes".to_string(),
+                args: None,
+                new_owner: "app_owner".to_string(),
+            },
+            MigrationOp::GrantPrivileges {
+                object_kind: GrantObjectKind::Table,
+                schema: "public".to_string(),
+                name: "notes".to_string(),
+                args: None,
+                grantee: "PUBLIC".to_string(),
+                privileges: vec![Privilege::Select],
+                with_grant_option: false,
+            },
+            MigrationOp::RevokePrivileges {
+                object_kind: GrantObjectKind::Table,
+                schema: "public".to_string(),
+                name: "notes".to_string(),
+                args: None,
+                grantee: "analyst".to_string(),
+                privileges: vec![Privilege::Delete],
+                revoke_grant_option: false,
+            },
+            MigrationOp::DropTable("public.legacy".to_string()),
+        ];
+
+        let roles: Vec<String> = referenced_roles(&ops).into_iter().collect();
+        assert_eq!(
+            roles,
+            vec!["analyst", "app_owner", "authenticated", "service_role"]
+        );
+    }
+
+    #[test]
+    fn skips_pseudo_roles() {
+        let roles = referenced_roles(&[policy(&["PUBLIC", "CURRENT_USER", "session_user"])]);
+        assert!(roles.is_empty());
+    }
+
+    #[test]
+    fn message_lists_every_missing_role_with_remediation() {
+        let message = missing_roles_message(&["analyst".to_string(), "app\"owner".to_string()]);
+        assert!(message.contains("2 role(s)"), "{message}");
+        assert!(message.contains("analyst, app\"owner"), "{message}");
+        assert!(
+            message.contains("CREATE ROLE \"analyst\" NOLOGIN;"),
+            "{message}"
+        );
+        assert!(
+            message.contains("CREATE ROLE \"app\"\"owner\" NOLOGIN;"),
+            "{message}"
+        );
+        assert!(message.contains("--exclude-grants-for-role"), "{message}");
+    }
+}
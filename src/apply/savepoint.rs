@@ -0,0 +1,90 @@
+use serde::Serialize;
+use sqlx::Executor;
+
+use crate::util::{Result, SchemaError};
+
+const SAVEPOINT: &str = "pgmold_statement";
+
+/// A statement that failed during an apply. The index counts every statement
+/// in the plan, including ones skipped as no-ops.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StatementFailure {
+    pub statement_index: usize,
+    pub sql: String,
+    pub error_message: String,
+}
+
+/// Executes `statement` inside a savepoint so a failure leaves the enclosing
+/// transaction usable for the rest of the plan. Returns the rows affected, or
+/// the database error message when the statement failed and was rolled back
+/// to the savepoint. Failing to manage the savepoint itself is an error.
+pub async fn execute_isolated(
+    connection: &mut sqlx::PgConnection,
+    statement: &str,
+) -> Result<std::result::Result<u64, String>> {
+    savepoint_command(connection, &format!("SAVEPOINT {SAVEPOINT}")).await?;
+    let outcome = match connection.execute(statement).await {
+        Ok(result) => Ok(result.rows_affected()),
+        Err(e) => {
+            savepoint_command(connection, &format!("ROLLBACK TO SAVEPOINT {SAVEPOINT}")).await?;
+            Err(e.to_string())
+        }
+    };
+    savepoint_command(connection, &format!("RELEASE SAVEPOINT {SAVEPOINT}")).await?;
+    Ok(outcome)
+}
+
+async fn savepoint_command(connection: &mut sqlx::PgConnection, command: &str) -> Result<()> {
+    connection
+        .execute(command)
+        .await
+        .map(|_| ())
+        .map_err(|e| SchemaError::DatabaseError(format!("Failed to run {command}: {e}")))
+}
+
+/// The error reported once an apply that collected `failures` has been rolled
+/// back, listing every failing statement.
+pub fn failures_error(failures: &[StatementFailure]) -> SchemaError {
+    let details: Vec<String> = failures
+        .iter()
+        .map(|failure| {
+            format!(
+                "  Statement {}: {}\n    Error: {}",
+                failure.statement_index + 1,
+                failure.sql,
+                failure.error_message
+            )
+        })
+        .collect();
+    SchemaError::DatabaseError(format!(
+        "Migration failed with {} error(s); transaction rolled back:\n{}",
+        failures.len(),
+        details.join("\n")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failures_error_lists_every_statement() {
+        let error = failures_error(&[
+            StatementFailure {
+                statement_index: 0,
+                sql: "ALTER TABLE a ADD COLUMN x int;".to_string(),
+                error_message: "relation \"a\" does not exist".to_string(),
+            },
+            StatementFailure {
+                statement_index: 3,
+                sql: "DROP VIEW v;".to_string(),
+                error_message: "view \"v\" does not exist".to_string(),
+            },
+        ]);
+        let message = error.to_string();
+        assert!(message.contains("2 error(s)"), "{message}");
+        assert!(message.contains("Statement 1: ALTER TABLE a"), "{message}");
+        assert!(message.contains("Statement 4: DROP VIEW v;"), "{message}");
+        assert!(message.contains("view \"v\" does not exist"), "{message}");
+    }
+}
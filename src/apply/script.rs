@@ -0,0 +1,169 @@
+//! `apply --export` writes the migration as a SQL script for someone else to
+//! run. The script holds exactly the statements `apply` executes, in the same
+//! single transaction and with the same session settings; only the per-statement
+//! savepoints and no-op checks, which need a live connection, are left out.
+//! Concurrent index drops and builds go before `BEGIN` and after `COMMIT`, as
+//! `apply` runs them.
+
+use std::io::{self, Write};
+
+use crate::diff::MigrationOp;
+use crate::parser::SourceLocations;
+use crate::pg::sqlgen::{annotation, escape_string, generate_sql};
+use crate::plan::transaction_phases;
+
+/// `SET LOCAL` timeouts applied at the start of the migration transaction.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionTimeouts {
+    pub lock_timeout: Option<String>,
+    pub statement_timeout: Option<String>,
+}
+
+impl SessionTimeouts {
+    pub fn statements(&self) -> Vec<String> {
+        [
+            ("lock_timeout", &self.lock_timeout),
+            ("statement_timeout", &self.statement_timeout),
+        ]
+        .into_iter()
+        .filter_map(|(setting, value)| {
+            value
+                .as_ref()
+                .map(|value| format!("SET LOCAL {setting} = '{}';", escape_string(value)))
+        })
+        .collect()
+    }
+}
+
+/// Writes `ops` as a script wrapped in `BEGIN`/`COMMIT`. Each run of
+/// operations of the same kind gets a separator, and each operation its
+/// [`annotation`] above its statements. Returns how many of the migration's
+/// statements were written.
+pub fn write_script<W: Write>(
+    ops: &[MigrationOp],
+    timeouts: &SessionTimeouts,
+    locations: &SourceLocations,
+    out: &mut W,
+) -> io::Result<usize> {
+    let phases = transaction_phases(ops.to_vec());
+    let mut written = 0;
+    writeln!(out, "-- Generated by pgmold apply --export")?;
+    writeln!(out, "-- {} operation(s)", ops.len())?;
+    if !phases.before.is_empty() {
+        written += write_operations(&phases.before, locations, out)?;
+    }
+    writeln!(out)?;
+    writeln!(out, "BEGIN;")?;
+    for statement in timeouts.statements() {
+        writeln!(out, "{statement}")?;
+    }
+
+    written += write_operations(&phases.transaction, locations, out)?;
+
+    writeln!(out)?;
+    writeln!(out, "COMMIT;")?;
+    if !phases.after.is_empty() {
+        written += write_operations(&phases.after, locations, out)?;
+    }
+    Ok(written)
+}
+
+fn write_operations<W: Write>(
+    ops: &[MigrationOp],
+    locations: &SourceLocations,
+    out: &mut W,
+) -> io::Result<usize> {
+    let mut written = 0;
+    let mut previous_kind = None;
+    for op in ops {
+        let kind = op.kind();
+        if previous_kind.as_ref() != Some(&kind) {
+            writeln!(out)?;
+            writeln!(out, "-- ==== {kind} ====")?;
+            previous_kind = Some(kind);
+        }
+        writeln!(out)?;
+        writeln!(out, "{}", annotation(op, locations))?;
+        for statement in generate_sql(std::slice::from_ref(op)) {
+            writeln!(out, "{statement}")?;
+            written += 1;
+        }
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::compute_diff;
+    use crate::diff::planner::plan_migration_checked;
+    use crate::model::Schema;
+    use crate::parser::parse_sql_string;
+
+    #[test]
+    fn script_wraps_statements_in_one_transaction() {
+        let target = parse_sql_string(
+            "CREATE TABLE users (id BIGINT PRIMARY KEY, email TEXT);
+             CREATE TABLE teams (id BIGINT PRIMARY KEY);
+             CREATE INDEX users_email_idx ON users (email);",
+        )
+        .unwrap();
+        let ops = plan_migration_checked(compute_diff(&Schema::new(), &target)).unwrap();
+        let timeouts = SessionTimeouts {
+            lock_timeout: Some("5s".to_string()),
+            statement_timeout: None,
+        };
+
+        let mut script = Vec::new();
+        let written = write_script(&ops, &timeouts, &SourceLocations::new(), &mut script).unwrap();
+        let script = String::from_utf8(script).unwrap();
+
+        assert_eq!(written, crate::pg::sqlgen::generate_sql(&ops).len());
+        assert!(script.contains("\nBEGIN;\nSET LOCAL lock_timeout = '5s';\n"));
+        assert!(script.ends_with("\nCOMMIT;\n"));
+        assert_eq!(script.matches("-- ==== CreateTable ====").count(), 1);
+        assert!(script.contains("\n-- pgmold: CreateTable public.users\n"));
+        for statement in crate::pg::sqlgen::generate_sql(&ops) {
+            assert!(script.contains(&statement), "missing {statement}");
+        }
+        let create_users = script.find("CREATE TABLE \"public\".\"users\"").unwrap();
+        let create_index = script.find("CREATE INDEX").unwrap();
+        assert!(create_users < create_index);
+    }
+
+    #[test]
+    fn script_runs_concurrent_indexes_outside_the_transaction() {
+        let current = parse_sql_string(
+            "CREATE TABLE users (id BIGINT PRIMARY KEY, email TEXT, name TEXT);
+             CREATE INDEX users_name_idx ON users (name);",
+        )
+        .unwrap();
+        let target = parse_sql_string(
+            "CREATE TABLE users (id BIGINT PRIMARY KEY, email TEXT, name TEXT, bio TEXT);
+             CREATE INDEX users_email_idx ON users (email);",
+        )
+        .unwrap();
+        let ops = crate::plan::with_concurrent_indexes(
+            plan_migration_checked(compute_diff(&current, &target)).unwrap(),
+        );
+
+        let mut script = Vec::new();
+        write_script(
+            &ops,
+            &SessionTimeouts::default(),
+            &SourceLocations::new(),
+            &mut script,
+        )
+        .unwrap();
+        let script = String::from_utf8(script).unwrap();
+
+        let drop_index = script.find("DROP INDEX CONCURRENTLY").unwrap();
+        let begin = script.find("\nBEGIN;\n").unwrap();
+        let add_column = script.find("ADD COLUMN").unwrap();
+        let commit = script.find("\nCOMMIT;\n").unwrap();
+        let create_index = script.find("CREATE INDEX CONCURRENTLY").unwrap();
+        assert!(drop_index < begin);
+        assert!(begin < add_column && add_column < commit);
+        assert!(commit < create_index);
+    }
+}
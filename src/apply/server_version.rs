@@ -0,0 +1,150 @@
+use crate::diff::MigrationOp;
+use crate::pg::connection::PgConnection;
+use crate::util::{Result, SchemaError};
+
+/// `server_version_num` of the first release supporting `NULLS NOT DISTINCT`.
+const NULLS_NOT_DISTINCT_SINCE: i32 = 150_000;
+
+/// `server_version_num` of the first release supporting per-column
+/// `SET COMPRESSION`.
+const COMPRESSION_SINCE: i32 = 140_000;
+
+/// Fails before anything runs when the plan uses syntax the target server is
+/// too old to accept, instead of rolling back on the first rejected statement.
+pub async fn check_server_version(connection: &PgConnection, ops: &[MigrationOp]) -> Result<()> {
+    let server_version_num = connection.server_version_num().await?;
+    let unsupported = unsupported_on(ops, server_version_num);
+    if !unsupported.is_empty() {
+        return Err(SchemaError::ValidationError(format!(
+            "Migration uses NULLS NOT DISTINCT, which requires PostgreSQL 15 or later: {}",
+            unsupported.join(", ")
+        )));
+    }
+    let compressed = compression_unsupported_on(ops, server_version_num);
+    if !compressed.is_empty() {
+        return Err(SchemaError::ValidationError(format!(
+            "Migration sets column compression, which requires PostgreSQL 14 or later: {}",
+            compressed.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+/// Columns in `ops` whose compression method `server_version_num` cannot set.
+fn compression_unsupported_on(ops: &[MigrationOp], server_version_num: i32) -> Vec<String> {
+    if server_version_num >= COMPRESSION_SINCE {
+        return Vec::new();
+    }
+    let mut columns = Vec::new();
+    for op in ops {
+        match op {
+            MigrationOp::CreateTable(table) => columns.extend(
+                table
+                    .columns
+                    .values()
+                    .filter(|column| column.compression.is_some())
+                    .map(|column| format!("{}.{}.{}", table.schema, table.name, column.name)),
+            ),
+            MigrationOp::AddColumn { table, column } if column.compression.is_some() => {
+                columns.push(format!("{table}.{}", column.name))
+            }
+            MigrationOp::AlterColumn {
+                table,
+                column,
+                changes,
+            } if changes.compression.is_some() => columns.push(format!("{table}.{column}")),
+            _ => {}
+        }
+    }
+    columns
+}
+
+/// Names of the unique indexes and constraints in `ops` that
+/// `server_version_num` cannot create.
+fn unsupported_on(ops: &[MigrationOp], server_version_num: i32) -> Vec<String> {
+    if server_version_num >= NULLS_NOT_DISTINCT_SINCE {
+        return Vec::new();
+    }
+    let mut names = Vec::new();
+    let mut check = |table: String, name: &str, nulls_not_distinct: bool| {
+        if nulls_not_distinct {
+            names.push(format!("{table}.{name}"));
+        }
+    };
+    for op in ops {
+        match op {
+            MigrationOp::CreateTable(table) => {
+                let qualified = format!("{}.{}", table.schema, table.name);
+                for index in &table.indexes {
+                    check(qualified.clone(), &index.name, index.nulls_not_distinct);
+                }
+                for unique in &table.unique_constraints {
+                    check(qualified.clone(), &unique.name, unique.nulls_not_distinct);
+                }
+            }
+            MigrationOp::AddIndex { table, index }
+            | MigrationOp::CreateIndexConcurrently { table, index } => {
+                check(table.to_string(), &index.name, index.nulls_not_distinct)
+            }
+            MigrationOp::AddUniqueConstraint {
+                table,
+                unique_constraint,
+            } => check(
+                table.to_string(),
+                &unique_constraint.name,
+                unique_constraint.nulls_not_distinct,
+            ),
+            _ => {}
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::ColumnChanges;
+    use crate::model::{QualifiedName, UniqueConstraint};
+
+    #[test]
+    fn nulls_not_distinct_requires_postgres_15() {
+        let index = |nulls_not_distinct| MigrationOp::AddUniqueConstraint {
+            table: QualifiedName::new("public", "users"),
+            unique_constraint: UniqueConstraint {
+                name: "users_email_key".to_string(),
+                columns: vec!["email".to_string()],
+                nulls_not_distinct,
+                deferrable: false,
+                initially_deferred: false,
+            },
+        };
+
+        assert_eq!(
+            unsupported_on(&[index(true), index(false)], 140_011),
+            vec!["public.users.users_email_key".to_string()]
+        );
+        assert!(unsupported_on(&[index(true)], 150_004).is_empty());
+    }
+
+    #[test]
+    fn column_compression_requires_postgres_14() {
+        let op = MigrationOp::AlterColumn {
+            table: QualifiedName::new("public", "documents"),
+            column: "body".to_string(),
+            changes: ColumnChanges {
+                data_type: None,
+                nullable: None,
+                default: None,
+                statistics: None,
+                compression: Some(Some("lz4".to_string())),
+                identity: None,
+            },
+        };
+
+        assert_eq!(
+            compression_unsupported_on(std::slice::from_ref(&op), 130_015),
+            vec!["public.documents.body".to_string()]
+        );
+        assert!(compression_unsupported_on(&[op], 140_011).is_empty());
+    }
+}
@@ -1,6 +1,8 @@
 use std::collections::{BTreeMap, BTreeSet};
 
+use crate::diff::{compute_diff, planner::plan_migration_checked};
 use crate::model::{PgType, Schema};
+use crate::provider::load_schema_from_sources;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum IssueSeverity {
@@ -28,6 +30,34 @@ pub fn check_schema(schema: &Schema) -> Vec<SchemaIssue> {
     issues
 }
 
+/// Everything `pgmold check` verifies about `sources` without a database:
+/// that they load (parse, merge and finalize, which rejects policies on
+/// missing tables and unknown columns), pass [`check_schema`], and create in
+/// an order the planner can resolve. A source that fails to load is reported
+/// as the single `invalid_source` issue.
+pub fn validate_sources(sources: &[String]) -> Vec<SchemaIssue> {
+    let schema = match load_schema_from_sources(sources) {
+        Ok(schema) => schema,
+        Err(error) => {
+            return vec![SchemaIssue {
+                rule: "invalid_source",
+                severity: IssueSeverity::Error,
+                message: error.to_string(),
+            }]
+        }
+    };
+
+    let mut issues = check_schema(&schema);
+    if let Err(error) = plan_migration_checked(compute_diff(&Schema::new(), &schema)) {
+        issues.push(SchemaIssue {
+            rule: "unorderable_schema",
+            severity: IssueSeverity::Error,
+            message: format!("The schema cannot be created in any order: {error}"),
+        });
+    }
+    issues
+}
+
 pub fn has_errors(issues: &[SchemaIssue]) -> bool {
     issues
         .iter()
@@ -270,6 +300,32 @@ mod tests {
         assert!(issues.is_empty(), "Expected no issues, got: {issues:?}");
     }
 
+    #[test]
+    fn validate_sources_reports_load_failures_as_issues() {
+        let dir = tempfile::tempdir().unwrap();
+        let valid = dir.path().join("valid.sql");
+        std::fs::write(
+            &valid,
+            "CREATE TABLE users (id BIGINT PRIMARY KEY);\n\
+             CREATE TABLE orders (id BIGINT PRIMARY KEY, user_id BIGINT REFERENCES users(id));",
+        )
+        .unwrap();
+        let orphan_policy = dir.path().join("policy.sql");
+        std::fs::write(
+            &orphan_policy,
+            "CREATE POLICY owner_only ON accounts USING (true);",
+        )
+        .unwrap();
+
+        let source = |path: &std::path::Path| format!("sql:{}", path.display());
+        assert!(validate_sources(&[source(&valid)]).is_empty());
+
+        let issues = validate_sources(&[source(&orphan_policy)]);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].rule, "invalid_source");
+        assert!(issues[0].message.contains("owner_only"));
+    }
+
     #[test]
     fn fk_referencing_missing_table() {
         let mut schema = parse_sql_string(
@@ -296,6 +352,8 @@ mod tests {
                 referenced_columns: vec!["id".to_string()],
                 on_delete: ReferentialAction::NoAction,
                 on_update: ReferentialAction::NoAction,
+                deferrable: false,
+                initially_deferred: false,
             });
 
         let issues = check_schema(&schema);
@@ -332,6 +390,8 @@ mod tests {
                 referenced_columns: vec!["nonexistent".to_string()],
                 on_delete: ReferentialAction::NoAction,
                 on_update: ReferentialAction::NoAction,
+                deferrable: false,
+                initially_deferred: false,
             });
 
         let issues = check_schema(&schema);
@@ -468,6 +528,8 @@ mod tests {
                 referenced_columns: vec!["id".to_string()],
                 on_delete: ReferentialAction::NoAction,
                 on_update: ReferentialAction::NoAction,
+                deferrable: false,
+                initially_deferred: false,
             });
         schema
             .tables
@@ -482,6 +544,8 @@ mod tests {
                 referenced_columns: vec!["id".to_string()],
                 on_delete: ReferentialAction::NoAction,
                 on_update: ReferentialAction::NoAction,
+                deferrable: false,
+                initially_deferred: false,
             });
 
         let issues = check_schema(&schema);
@@ -0,0 +1,204 @@
+//! `plan --comment-format markdown` renders the plan as a Markdown document a
+//! CI job can post as a pull request comment as is: a summary, the lint
+//! results and lock warnings `apply` would report, the riskiest operations,
+//! the objects the plan affects, and the SQL folded away in a `<details>` block.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::str::FromStr;
+
+use pgmold::diff::MigrationOp;
+use pgmold::lint::locks::LockWarning;
+use pgmold::lint::{LintResult, LintSeverity};
+use pgmold::plan::PlanRisk;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum CommentFormat {
+    Markdown,
+}
+
+impl FromStr for CommentFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "markdown" => Ok(Self::Markdown),
+            other => Err(format!(
+                "Invalid comment format '{other}': expected markdown"
+            )),
+        }
+    }
+}
+
+pub(super) struct PlanComment<'a> {
+    pub ops: &'a [MigrationOp],
+    pub statements: &'a [String],
+    pub lint_results: &'a [LintResult],
+    pub lock_warnings: &'a [LockWarning],
+    pub affected: &'a [String],
+    /// Unified diffs of the function, view and policy definitions the plan
+    /// rewrites; see [`pgmold::plan::render_diff`].
+    pub definition_diffs: &'a [String],
+    pub risk: &'a PlanRisk,
+}
+
+pub(super) fn render_markdown(plan: &PlanComment) -> String {
+    let mut out = String::from("### pgmold plan\n\n");
+    if plan.statements.is_empty() {
+        out.push_str("No changes required.\n");
+        return out;
+    }
+
+    let mut kinds: BTreeMap<String, usize> = BTreeMap::new();
+    for op in plan.ops {
+        *kinds.entry(op.kind()).or_default() += 1;
+    }
+    let kinds: Vec<String> = kinds
+        .iter()
+        .map(|(kind, count)| format!("{count} {kind}"))
+        .collect();
+    let _ = writeln!(
+        out,
+        "**{} operations, {} statements** ({})",
+        plan.ops.len(),
+        plan.statements.len(),
+        kinds.join(", ")
+    );
+    let _ = writeln!(
+        out,
+        "\n**Risk: {} ({}/100)**",
+        plan.risk.level, plan.risk.score
+    );
+
+    if !plan.lint_results.is_empty() {
+        out.push_str(
+            "\n#### Lint\n\n| Severity | Rule | Message |\n|----------|------|---------|\n",
+        );
+        for result in plan.lint_results {
+            let severity = match result.severity {
+                LintSeverity::Error => "Error",
+                LintSeverity::Warning => "Warning",
+            };
+            let _ = writeln!(
+                out,
+                "| {severity} | `{}` | {} |",
+                result.rule,
+                table_cell(&result.message)
+            );
+        }
+    }
+
+    if !plan.lock_warnings.is_empty() {
+        out.push_str("\n#### Lock warnings\n\n");
+        for warning in plan.lock_warnings {
+            let _ = writeln!(out, "- {}", warning.message);
+        }
+    }
+
+    let riskiest = plan.risk.riskiest();
+    if !riskiest.is_empty() {
+        out.push_str(
+            "\n#### Riskiest operations\n\n| Score | Operation | Factors |\n|-------|-----------|---------|\n",
+        );
+        for op in riskiest.into_iter().take(10) {
+            let _ = writeln!(
+                out,
+                "| {} | `{}` | {} |",
+                op.score,
+                table_cell(&op.key),
+                table_cell(&op.factors.join(", "))
+            );
+        }
+    }
+
+    if !plan.affected.is_empty() {
+        out.push_str("\n#### Affected objects\n\n");
+        for line in plan.affected {
+            let _ = writeln!(out, "- `{line}`");
+        }
+    }
+
+    if !plan.definition_diffs.is_empty() {
+        out.push_str("\n#### Definition changes\n");
+        for diff in plan.definition_diffs {
+            let _ = write!(out, "\n```diff\n{diff}\n```\n");
+        }
+    }
+
+    let _ = write!(
+        out,
+        "\n<details>\n<summary>SQL ({} statements)</summary>\n\n```sql\n{}\n```\n\n</details>\n",
+        plan.statements.len(),
+        plan.statements.join("\n\n")
+    );
+    out
+}
+
+/// `text` made safe for a single Markdown table cell.
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pgmold::lint::LintOptions;
+    use pgmold::model::QualifiedName;
+    use pgmold::plan::assess_risk;
+
+    #[test]
+    fn renders_summary_lint_table_and_folded_sql() {
+        let ops = vec![MigrationOp::DropColumn {
+            table: QualifiedName::new("public", "users"),
+            column: "bio".to_string(),
+        }];
+        let statements = vec!["ALTER TABLE \"public\".\"users\" DROP COLUMN \"bio\";".to_string()];
+        let lint_results = vec![LintResult {
+            rule: "deny_drop_column",
+            severity: LintSeverity::Error,
+            message: "Dropping column public.users.bio requires --allow-destructive flag"
+                .to_string(),
+        }];
+        let risk = assess_risk(&ops, &LintOptions::default(), &BTreeMap::new());
+        let markdown = render_markdown(&PlanComment {
+            ops: &ops,
+            statements: &statements,
+            lint_results: &lint_results,
+            lock_warnings: &[],
+            affected: &[],
+            definition_diffs: &[],
+            risk: &risk,
+        });
+
+        assert!(markdown.contains("**1 operations, 1 statements** (1 DropColumn)"));
+        assert!(markdown.contains("| Error | `deny_drop_column` | Dropping column"));
+        assert!(markdown.contains(
+            "<summary>SQL (1 statements)</summary>\n\n```sql\nALTER TABLE \"public\".\"users\" DROP COLUMN \"bio\";\n```"
+        ));
+        assert!(markdown.contains("**Risk: high ("));
+        assert!(markdown.contains("#### Riskiest operations"));
+        assert!(!markdown.contains("#### Lock warnings"));
+        assert!(!markdown.contains("#### Definition changes"));
+    }
+
+    #[test]
+    fn renders_definition_diffs_as_diff_blocks() {
+        let ops = vec![MigrationOp::DropTable("public.sessions".to_string())];
+        let statements = vec!["DROP TABLE \"public\".\"sessions\";".to_string()];
+        let diff = "--- view public.active (database)\n+++ view public.active (schema sources)\n@@ -1 +1 @@\n-SELECT 1\n+SELECT 2".to_string();
+        let risk = assess_risk(&ops, &LintOptions::default(), &BTreeMap::new());
+        let markdown = render_markdown(&PlanComment {
+            ops: &ops,
+            statements: &statements,
+            lint_results: &[],
+            lock_warnings: &[],
+            affected: &[],
+            definition_diffs: std::slice::from_ref(&diff),
+            risk: &risk,
+        });
+
+        assert!(markdown.contains(&format!(
+            "#### Definition changes\n\n```diff\n{diff}\n```\n"
+        )));
+    }
+}
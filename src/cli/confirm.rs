@@ -0,0 +1,140 @@
+//! Last-chance confirmation before `apply` destroys data. Run from a terminal,
+//! `apply` lists the operations that drop objects and asks for each object's
+//! name to be typed back before executing anything; `--yes` skips the prompt.
+//! Runs without a terminal (CI, scripts) are not prompted.
+
+use std::io::{BufRead, Write};
+
+use anyhow::{anyhow, Result};
+
+use pgmold::diff::MigrationOp;
+
+/// Operation kind and object name of each operation in `ops` that drops
+/// something holding data or depended on by other objects. A view or
+/// trigger the plan drops only to create it again is left out.
+pub(super) fn destructive_objects(ops: &[MigrationOp]) -> Vec<(String, String)> {
+    ops.iter()
+        .enumerate()
+        .filter(|(index, op)| match op {
+            MigrationOp::DropView { .. } | MigrationOp::DropTrigger { .. } => {
+                !ops[index + 1..].iter().any(|next| op.is_recreated_by(next))
+            }
+            _ => matches!(
+                op,
+                MigrationOp::DropSchema { .. }
+                    | MigrationOp::DropExtension(_)
+                    | MigrationOp::DropEnum(_)
+                    | MigrationOp::DropDomain(_)
+                    | MigrationOp::DropTable(_)
+                    | MigrationOp::DropPartition(_)
+                    | MigrationOp::DropColumn { .. }
+                    | MigrationOp::DropUniqueConstraint { .. }
+                    | MigrationOp::DropSequence(_)
+            ),
+        })
+        .filter_map(|(_, op)| Some((op.kind(), op.object()?)))
+        .collect()
+}
+
+/// Lists `objects` on `output`, then reads one line per object from `input`
+/// and fails unless it is that object's name.
+pub(super) fn confirm_destructive(
+    objects: &[(String, String)],
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> Result<()> {
+    writeln!(output, "This migration will destroy:")?;
+    for (kind, name) in objects {
+        writeln!(output, "  {kind} {name}")?;
+    }
+    for (_, name) in objects {
+        write!(output, "Type {name} to confirm: ")?;
+        output.flush()?;
+        let mut answer = String::new();
+        input.read_line(&mut answer)?;
+        if answer.trim() != name {
+            return Err(anyhow!(
+                "Confirmation for {name} did not match; nothing was applied"
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pgmold::model::QualifiedName;
+    use pgmold::parser::parse_sql_string;
+
+    #[test]
+    fn each_destroyed_object_must_be_typed_back() {
+        let ops = vec![
+            MigrationOp::DropTable("public.sessions".to_string()),
+            MigrationOp::DropColumn {
+                table: QualifiedName::new("public", "users"),
+                column: "legacy_id".to_string(),
+            },
+            MigrationOp::DropIndex {
+                table: QualifiedName::new("public", "users"),
+                index_name: "users_legacy_id_idx".to_string(),
+            },
+        ];
+        let objects = destructive_objects(&ops);
+        assert_eq!(
+            objects,
+            vec![
+                ("DropTable".to_string(), "public.sessions".to_string()),
+                (
+                    "DropColumn".to_string(),
+                    "public.users.legacy_id".to_string()
+                ),
+            ]
+        );
+
+        let mut output = Vec::new();
+        let mut typed = "public.sessions\npublic.users.legacy_id\n".as_bytes();
+        confirm_destructive(&objects, &mut typed, &mut output).unwrap();
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .contains("  DropColumn public.users.legacy_id"));
+
+        let mut mistyped = "public.sessions\nlegacy_id\n".as_bytes();
+        assert!(confirm_destructive(&objects, &mut mistyped, &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn recreated_views_and_triggers_need_no_confirmation() {
+        let schema = parse_sql_string(
+            "CREATE TABLE users (id bigint, email text);
+             CREATE FUNCTION touch() RETURNS trigger LANGUAGE plpgsql AS $$ BEGIN RETURN NEW; END $$;
+             CREATE VIEW user_emails AS SELECT email FROM users;
+             CREATE TRIGGER users_touch BEFORE UPDATE ON users FOR EACH ROW EXECUTE FUNCTION touch();",
+        )
+        .unwrap();
+        let view = schema.views["public.user_emails"].clone();
+        let trigger = schema.triggers["public.users.users_touch"].clone();
+        let ops = vec![
+            MigrationOp::DropView {
+                name: "public.user_emails".to_string(),
+                materialized: false,
+            },
+            MigrationOp::DropTrigger {
+                target_schema: "public".to_string(),
+                target_name: "users".to_string(),
+                name: "users_touch".to_string(),
+            },
+            MigrationOp::DropView {
+                name: "public.old_emails".to_string(),
+                materialized: false,
+            },
+            MigrationOp::CreateView(view),
+            MigrationOp::CreateTrigger(trigger),
+        ];
+
+        assert_eq!(
+            destructive_objects(&ops),
+            vec![("DropView".to_string(), "public.old_emails".to_string())]
+        );
+    }
+}
@@ -0,0 +1,524 @@
+//! Named profiles from `.pgmold.toml`, selected with `--profile <name>`.
+//!
+//! A profile is expanded into ordinary command-line flags before clap parses
+//! the arguments, so anything passed explicitly on the command line wins and
+//! every command keeps validating its own flags. Settings without a flag
+//! (production mode, lint severities) are exported through the same
+//! environment variables the library already reads.
+//!
+//! The top-level `[protected]` table applies with or without a profile: it
+//! maps object types to name globs that no plan may drop or alter, and is
+//! exported as `PGMOLD_PROTECTED`. The top-level `external` list names
+//! objects other systems own, as `-- pgmold:external` does in a schema file,
+//! and is exported as `PGMOLD_EXTERNAL`. The top-level `[type_equivalence]`
+//! table maps column types to the types the diff treats them as, and is
+//! exported as `PGMOLD_TYPE_EQUIVALENCE`. The top-level `[settings]` table
+//! maps cluster settings to the requirement plan and apply check them
+//! against, and is exported as `PGMOLD_REQUIRED_SETTINGS`.
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use clap::CommandFactory;
+use serde::Deserialize;
+
+use pgmold::filter::ObjectType;
+use pgmold::lint::parse_required_settings;
+
+use super::Cli;
+
+const CONFIG_FILE: &str = ".pgmold.toml";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    #[serde(default)]
+    profiles: BTreeMap<String, Profile>,
+    /// Object type (as in `--include-types`) to name globs.
+    #[serde(default)]
+    protected: BTreeMap<String, Vec<String>>,
+    /// Globs for objects pgmold must never create, alter or drop.
+    #[serde(default)]
+    external: Vec<String>,
+    /// Column type to the type it is equivalent to, such as
+    /// `email_address = "text"` for a domain over `text`.
+    #[serde(default)]
+    type_equivalence: BTreeMap<String, String>,
+    /// Setting name to the requirement it must meet, such as
+    /// `max_connections = ">= 200"` or `wal_level = "logical"`.
+    #[serde(default)]
+    settings: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Profile {
+    /// Environment variable holding the database URL.
+    database_env: Option<String>,
+    #[serde(default)]
+    target_schemas: Vec<String>,
+    #[serde(default)]
+    include: Vec<String>,
+    #[serde(default)]
+    exclude: Vec<String>,
+    #[serde(default)]
+    include_types: Vec<String>,
+    #[serde(default)]
+    exclude_types: Vec<String>,
+    /// Schema name to `manage`, `observe` or `ignore`.
+    #[serde(default)]
+    schema_modes: BTreeMap<String, String>,
+    /// Role named in the schema files to the role standing for it in this
+    /// database.
+    #[serde(default)]
+    role_aliases: BTreeMap<String, String>,
+    #[serde(default)]
+    allow_destructive: bool,
+    /// Enables production safety checks, like `PGMOLD_PROD=1`.
+    #[serde(default)]
+    production: bool,
+    /// Denies changes logical replication cannot follow, like
+    /// `--logical-replication`.
+    #[serde(default)]
+    logical_replication: bool,
+    /// Lint rule name to `error`, `warning` or `off`.
+    #[serde(default)]
+    lint: BTreeMap<String, String>,
+}
+
+/// Expands the profile named by `--profile` into `args` and exports the
+/// `[protected]` and `external` lists. Returns `args` unchanged when no
+/// profile is requested. The config file is `.pgmold.toml` in the working
+/// directory unless `PGMOLD_CONFIG` points elsewhere; it is only required
+/// with `--profile`.
+pub(super) fn apply_profile(args: Vec<OsString>) -> Result<Vec<OsString>> {
+    let requested = requested_profile(&args);
+
+    let path = std::env::var_os("PGMOLD_CONFIG")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(CONFIG_FILE));
+    if requested.is_none() && !path.exists() {
+        return Ok(args);
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| anyhow!("Failed to read {}: {e}", path.display()))?;
+    let mut config: ConfigFile =
+        toml::from_str(&content).map_err(|e| anyhow!("Invalid {}: {e}", path.display()))?;
+
+    if !config.protected.is_empty() {
+        let spec = protected_spec(&config.protected)
+            .map_err(|e| anyhow!("Invalid [protected] in {}: {e}", path.display()))?;
+        std::env::set_var("PGMOLD_PROTECTED", spec);
+    }
+    if !config.external.is_empty() {
+        for glob in &config.external {
+            glob::Pattern::new(glob).map_err(|e| {
+                anyhow!(
+                    "Invalid external pattern '{glob}' in {}: {e}",
+                    path.display()
+                )
+            })?;
+        }
+        std::env::set_var("PGMOLD_EXTERNAL", config.external.join(","));
+    }
+    if !config.type_equivalence.is_empty() {
+        let spec: Vec<String> = config
+            .type_equivalence
+            .iter()
+            .map(|(alias, target)| format!("{alias}={target}"))
+            .collect();
+        std::env::set_var("PGMOLD_TYPE_EQUIVALENCE", spec.join(";"));
+    }
+    if !config.settings.is_empty() {
+        let spec = settings_spec(&config.settings)
+            .map_err(|e| anyhow!("Invalid [settings] in {}: {e}", path.display()))?;
+        std::env::set_var("PGMOLD_REQUIRED_SETTINGS", spec);
+    }
+
+    let Some(name) = requested else {
+        return Ok(args);
+    };
+    let profile = config.profiles.remove(&name).ok_or_else(|| {
+        let known: Vec<&str> = config.profiles.keys().map(String::as_str).collect();
+        anyhow!(
+            "Unknown profile '{name}' in {} (available: {})",
+            path.display(),
+            known.join(", ")
+        )
+    })?;
+
+    let database_url = match &profile.database_env {
+        Some(var) => Some(std::env::var(var).map_err(|_| {
+            anyhow!("Profile '{name}' reads the database URL from ${var}, which is not set")
+        })?),
+        None => None,
+    };
+
+    if profile.production {
+        std::env::set_var("PGMOLD_PROD", "1");
+    }
+    if profile.logical_replication {
+        std::env::set_var("PGMOLD_LOGICAL_REPLICATION", "1");
+    }
+    if !profile.lint.is_empty() {
+        let spec: Vec<String> = profile
+            .lint
+            .iter()
+            .map(|(rule, level)| format!("{rule}={level}"))
+            .collect();
+        std::env::set_var("PGMOLD_LINT_SEVERITY", spec.join(","));
+    }
+
+    Ok(expand_args(args, &profile, database_url))
+}
+
+/// Validates the `[protected]` table and renders it as `type:glob,...` for
+/// `PGMOLD_PROTECTED`.
+fn protected_spec(protected: &BTreeMap<String, Vec<String>>) -> Result<String> {
+    let mut entries = Vec::new();
+    for (object_type, globs) in protected {
+        let object_type: ObjectType = object_type.parse().map_err(|e: String| anyhow!(e))?;
+        for glob in globs {
+            glob::Pattern::new(glob).map_err(|e| anyhow!("Invalid pattern '{glob}': {e}"))?;
+            entries.push(format!("{object_type}:{glob}"));
+        }
+    }
+    Ok(entries.join(","))
+}
+
+/// Validates the `[settings]` table and renders it as `name=requirement;...`
+/// for `PGMOLD_REQUIRED_SETTINGS`.
+fn settings_spec(settings: &BTreeMap<String, String>) -> Result<String> {
+    let spec: Vec<String> = settings
+        .iter()
+        .map(|(name, requirement)| format!("{name}={requirement}"))
+        .collect();
+    let spec = spec.join(";");
+    let parsed = parse_required_settings(&spec).map_err(|e| anyhow!(e))?;
+    if parsed.len() != settings.len() {
+        return Err(anyhow!("Setting requirements may not contain ';'"));
+    }
+    Ok(spec)
+}
+
+fn requested_profile(args: &[OsString]) -> Option<String> {
+    let mut iter = args.iter().filter_map(|arg| arg.to_str());
+    while let Some(arg) = iter.next() {
+        if arg == "--profile" {
+            return iter.next().map(str::to_string);
+        }
+        if let Some(name) = arg.strip_prefix("--profile=") {
+            return Some(name.to_string());
+        }
+    }
+    None
+}
+
+/// Inserts the profile's flags right after the subcommand name, or after the
+/// nested subcommand that follows it (`drift accept`), skipping flags that
+/// subcommand does not accept or that the user already passed.
+fn expand_args(
+    mut args: Vec<OsString>,
+    profile: &Profile,
+    database_url: Option<String>,
+) -> Vec<OsString> {
+    let root = Cli::command();
+    let Some((mut position, mut command)) = args.iter().enumerate().skip(1).find_map(|(i, arg)| {
+        let arg = arg.to_str()?;
+        root.find_subcommand(arg).map(|command| (i, command))
+    }) else {
+        return args;
+    };
+    while let Some(nested) = args
+        .get(position + 1)
+        .and_then(|arg| arg.to_str())
+        .and_then(|arg| command.find_subcommand(arg))
+    {
+        position += 1;
+        command = nested;
+    }
+
+    let mut flags: Vec<(&str, Vec<String>)> = Vec::new();
+    if let Some(url) = database_url {
+        flags.push(("database", vec![url]));
+    }
+    if !profile.target_schemas.is_empty() {
+        flags.push(("target-schemas", vec![profile.target_schemas.join(",")]));
+    }
+    for (long, values) in [("include", &profile.include), ("exclude", &profile.exclude)] {
+        if !values.is_empty() {
+            flags.push((long, values.clone()));
+        }
+    }
+    for (long, values) in [
+        ("include-types", &profile.include_types),
+        ("exclude-types", &profile.exclude_types),
+    ] {
+        if !values.is_empty() {
+            flags.push((long, vec![values.join(",")]));
+        }
+    }
+    if !profile.schema_modes.is_empty() {
+        let modes: Vec<String> = profile
+            .schema_modes
+            .iter()
+            .map(|(schema, mode)| format!("{schema}={mode}"))
+            .collect();
+        flags.push(("schema-mode", vec![modes.join(",")]));
+    }
+    if !profile.role_aliases.is_empty() {
+        let aliases: Vec<String> = profile
+            .role_aliases
+            .iter()
+            .map(|(role, alias)| format!("{role}={alias}"))
+            .collect();
+        flags.push(("role-alias", vec![aliases.join(",")]));
+    }
+    if profile.allow_destructive {
+        flags.push(("allow-destructive", Vec::new()));
+    }
+
+    let mut injected: Vec<OsString> = Vec::new();
+    for (long, values) in flags {
+        let Some(arg) = command.get_arguments().find(|a| a.get_long() == Some(long)) else {
+            continue;
+        };
+        let passed = args.iter().filter_map(|a| a.to_str()).any(|a| {
+            a == format!("--{long}")
+                || a.starts_with(&format!("--{long}="))
+                || arg
+                    .get_short()
+                    .is_some_and(|short| a.starts_with(&format!("-{short}")))
+        });
+        if passed {
+            continue;
+        }
+        if values.is_empty() {
+            injected.push(format!("--{long}").into());
+        }
+        for value in values {
+            injected.push(format!("--{long}").into());
+            injected.push(value.into());
+        }
+    }
+
+    args.splice(position + 1..position + 1, injected);
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn args(list: &[&str]) -> Vec<OsString> {
+        list.iter().map(OsString::from).collect()
+    }
+
+    fn profile() -> Profile {
+        toml::from_str::<ConfigFile>(
+            r#"
+            [profiles.prod]
+            database_env = "PROD_DATABASE_URL"
+            target_schemas = ["public", "auth"]
+            exclude = ["_*", "tmp_*"]
+            include_types = ["tables", "views"]
+            schema_modes = { auth = "observe", graphql = "ignore" }
+            role_aliases = { app_rw = "app_rw_prod" }
+            allow_destructive = true
+            production = true
+            lint = { deny_drop_column = "warning" }
+            "#,
+        )
+        .unwrap()
+        .profiles
+        .remove("prod")
+        .unwrap()
+    }
+
+    #[test]
+    fn finds_requested_profile() {
+        assert_eq!(
+            requested_profile(&args(&["pgmold", "--profile", "prod", "plan"])),
+            Some("prod".to_string())
+        );
+        assert_eq!(
+            requested_profile(&args(&["pgmold", "plan", "--profile=dev"])),
+            Some("dev".to_string())
+        );
+        assert_eq!(requested_profile(&args(&["pgmold", "plan"])), None);
+    }
+
+    #[test]
+    fn expands_profile_into_supported_flags() {
+        let expanded = expand_args(
+            args(&[
+                "pgmold",
+                "--profile",
+                "prod",
+                "apply",
+                "-s",
+                "sql:schema.sql",
+            ]),
+            &profile(),
+            Some("postgres://prod/db".to_string()),
+        );
+        assert_eq!(
+            expanded,
+            args(&[
+                "pgmold",
+                "--profile",
+                "prod",
+                "apply",
+                "--database",
+                "postgres://prod/db",
+                "--target-schemas",
+                "public,auth",
+                "--exclude",
+                "_*",
+                "--exclude",
+                "tmp_*",
+                "--include-types",
+                "tables,views",
+                "--schema-mode",
+                "auth=observe,graphql=ignore",
+                "--role-alias",
+                "app_rw=app_rw_prod",
+                "--allow-destructive",
+                "-s",
+                "sql:schema.sql",
+            ])
+        );
+        Cli::try_parse_from(expanded).unwrap();
+    }
+
+    #[test]
+    fn explicit_flags_and_unsupported_flags_are_not_injected() {
+        let expanded = expand_args(
+            args(&[
+                "pgmold",
+                "lint",
+                "-s",
+                "sql:schema.sql",
+                "-dpostgres://local/db",
+                "--target-schemas=public",
+                "--role-alias=app_rw=app_rw_local",
+                "--profile",
+                "prod",
+            ]),
+            &profile(),
+            Some("postgres://prod/db".to_string()),
+        );
+        assert_eq!(
+            expanded,
+            args(&[
+                "pgmold",
+                "lint",
+                "-s",
+                "sql:schema.sql",
+                "-dpostgres://local/db",
+                "--target-schemas=public",
+                "--role-alias=app_rw=app_rw_local",
+                "--profile",
+                "prod",
+            ])
+        );
+        Cli::try_parse_from(expanded).unwrap();
+    }
+
+    #[test]
+    fn expands_profile_after_nested_subcommand() {
+        let expanded = expand_args(
+            args(&[
+                "pgmold",
+                "--profile",
+                "prod",
+                "drift",
+                "accept",
+                "--object",
+                "public.users",
+                "-s",
+                "sql:schema/",
+            ]),
+            &profile(),
+            Some("postgres://prod/db".to_string()),
+        );
+        assert_eq!(
+            expanded,
+            args(&[
+                "pgmold",
+                "--profile",
+                "prod",
+                "drift",
+                "accept",
+                "--database",
+                "postgres://prod/db",
+                "--target-schemas",
+                "public,auth",
+                "--object",
+                "public.users",
+                "-s",
+                "sql:schema/",
+            ])
+        );
+        Cli::try_parse_from(expanded).unwrap();
+    }
+
+    #[test]
+    fn renders_and_validates_protected_list() {
+        let config: ConfigFile = toml::from_str(
+            r#"
+            [protected]
+            tables = ["public.audit_*", "billing.*"]
+            functions = ["auth.*"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            protected_spec(&config.protected).unwrap(),
+            "functions:auth.*,tables:public.audit_*,tables:billing.*"
+        );
+
+        let config: ConfigFile = toml::from_str("[protected]\nwidgets = [\"x\"]\n").unwrap();
+        assert!(protected_spec(&config.protected).is_err());
+        let config: ConfigFile = toml::from_str("[protected]\ntables = [\"[\"]\n").unwrap();
+        assert!(protected_spec(&config.protected).is_err());
+    }
+
+    #[test]
+    fn renders_and_validates_settings() {
+        let config: ConfigFile = toml::from_str(
+            r#"
+            [settings]
+            max_connections = ">= 200"
+            wal_level = "logical"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            settings_spec(&config.settings).unwrap(),
+            "max_connections=>= 200;wal_level=logical"
+        );
+
+        let config: ConfigFile = toml::from_str(
+            "[settings]
+max_connections = \">= lots\"\n",
+        )
+        .unwrap();
+        assert!(settings_spec(&config.settings).is_err());
+        let config: ConfigFile = toml::from_str(
+            "[settings]
+wal_level = \"logical; replica\"\n",
+        )
+        .unwrap();
+        assert!(settings_spec(&config.settings).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_profile_keys() {
+        let result = toml::from_str::<ConfigFile>("[profiles.dev]\ndatabase = \"x\"\n");
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,302 @@
+use std::collections::BTreeMap;
+
+use crate::model::{qualified_name, QualifiedName, Schema, Table};
+
+use super::{compute_diff_with_options, AdoptionMode, DiffOptions, MigrationOp};
+
+/// Objects `from` has and `to` does not declare, which a full plan would
+/// drop, grouped by kind (`tables`, `columns`, `indexes`, ...) and sorted by
+/// name. Removals that are not objects, such as revoked privileges or cleared
+/// comments, are left out.
+pub fn orphaned_objects(
+    from: &Schema,
+    to: &Schema,
+    options: &DiffOptions,
+) -> BTreeMap<String, Vec<String>> {
+    let options = DiffOptions {
+        adoption_mode: AdoptionMode::Full,
+        ..*options
+    };
+    let mut orphans: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for op in compute_diff_with_options(from, to, &options) {
+        if !removes_undeclared(&op, to) {
+            continue;
+        }
+        if let Some((kind, name)) = dropped_object(&op) {
+            orphans.entry(kind.to_string()).or_default().push(name);
+        }
+    }
+    for names in orphans.values_mut() {
+        names.sort();
+        names.dedup();
+    }
+    orphans
+}
+
+/// The kind and name of the object `op` drops, named as `--include-types`
+/// names kinds. Table elements are named `table.element`.
+fn dropped_object(op: &MigrationOp) -> Option<(&'static str, String)> {
+    let element = |table: &QualifiedName, name: &str| format!("{table}.{name}");
+    Some(match op {
+        MigrationOp::DropSchema { name, .. } => ("schemas", name.clone()),
+        MigrationOp::DropExtension(name) => ("extensions", name.clone()),
+        MigrationOp::DropServer(name) => ("servers", name.clone()),
+        MigrationOp::DropEnum(name) => ("enums", name.clone()),
+        MigrationOp::DropDomain(name) => ("domains", name.clone()),
+        MigrationOp::DropTable(name) => ("tables", name.clone()),
+        MigrationOp::DropPartition(name) => ("partitions", name.clone()),
+        MigrationOp::DropSequence(name) => ("sequences", name.clone()),
+        MigrationOp::DropView { name, .. } => ("views", name.clone()),
+        MigrationOp::DropFunction { name, args } => ("functions", format!("{name}({args})")),
+        MigrationOp::DropAggregate { name, args } => ("aggregates", format!("{name}({args})")),
+        MigrationOp::DropTrigger {
+            target_schema,
+            target_name,
+            name,
+        } => ("triggers", format!("{target_schema}.{target_name}.{name}")),
+        MigrationOp::DropColumn { table, column } => ("columns", element(table, column)),
+        MigrationOp::DropPrimaryKey { table } => ("primarykeys", table.to_string()),
+        MigrationOp::DropIndex { table, index_name } => ("indexes", element(table, index_name)),
+        MigrationOp::DropUniqueConstraint {
+            table,
+            constraint_name,
+        } => ("uniqueconstraints", element(table, constraint_name)),
+        MigrationOp::DropForeignKey {
+            table,
+            foreign_key_name,
+        } => ("foreignkeys", element(table, foreign_key_name)),
+        MigrationOp::DropCheckConstraint {
+            table,
+            constraint_name,
+        } => ("checkconstraints", element(table, constraint_name)),
+        MigrationOp::DropExclusionConstraint {
+            table,
+            constraint_name,
+        } => ("exclusionconstraints", element(table, constraint_name)),
+        MigrationOp::DropPolicy { table, name } => ("policies", element(table, name)),
+        MigrationOp::DropRawObject(name) => ("rawobjects", name.clone()),
+        _ => return None,
+    })
+}
+
+/// Whether `op` removes something `to` does not declare: drops an object or
+/// table element missing from `to`, or disables row level security, revokes
+/// privileges or clears a comment. Drops of objects `to` still declares are
+/// part of a drop-and-recreate and are not removals.
+pub(super) fn removes_undeclared(op: &MigrationOp, to: &Schema) -> bool {
+    let table = |name: &QualifiedName| to.tables.get(&name.to_string());
+    let table_lacks = |name: &QualifiedName, has: &dyn Fn(&Table) -> bool| {
+        table(name).is_none_or(|table| !has(table))
+    };
+    match op {
+        MigrationOp::DropSchema { name, .. } => !to.schemas.contains_key(name),
+        MigrationOp::DropExtension(name) => !to.extensions.contains_key(name),
+        MigrationOp::DropServer(name) => !to.servers.contains_key(name),
+        MigrationOp::DropEnum(name) => !to.enums.contains_key(name),
+        MigrationOp::DropDomain(name) => !to.domains.contains_key(name),
+        MigrationOp::DropTable(name) => !to.tables.contains_key(name),
+        MigrationOp::DropPartition(name) => !to.partitions.contains_key(name),
+        MigrationOp::DropSequence(name) => !to.sequences.contains_key(name),
+        MigrationOp::DropView { name, .. } => !to.views.contains_key(name),
+        MigrationOp::DropFunction { name, args } => !to.functions.values().any(|function| {
+            qualified_name(&function.schema, &function.name) == *name
+                && function.args_string() == *args
+        }),
+        MigrationOp::DropAggregate { name, args } => !to.aggregates.values().any(|aggregate| {
+            qualified_name(&aggregate.schema, &aggregate.name) == *name
+                && aggregate.args_string() == *args
+        }),
+        MigrationOp::DropTrigger {
+            target_schema,
+            target_name,
+            name,
+        } => !to.triggers.values().any(|trigger| {
+            trigger.target_schema == *target_schema
+                && trigger.target_name == *target_name
+                && trigger.name == *name
+        }),
+        MigrationOp::DropColumn { table, column } => {
+            table_lacks(table, &|t| t.columns.contains_key(column))
+        }
+        MigrationOp::DropPrimaryKey { table } => table_lacks(table, &|t| t.primary_key.is_some()),
+        MigrationOp::DropIndex { table, index_name } => {
+            table_lacks(table, &|t| t.indexes.iter().any(|i| i.name == *index_name))
+        }
+        MigrationOp::DropUniqueConstraint {
+            table,
+            constraint_name,
+        } => table_lacks(table, &|t| {
+            t.unique_constraints
+                .iter()
+                .any(|c| c.name == *constraint_name)
+        }),
+        MigrationOp::DropForeignKey {
+            table,
+            foreign_key_name,
+        } => table_lacks(table, &|t| {
+            t.foreign_keys.iter().any(|fk| fk.name == *foreign_key_name)
+        }),
+        MigrationOp::DropCheckConstraint {
+            table,
+            constraint_name,
+        } => table_lacks(table, &|t| {
+            t.check_constraints
+                .iter()
+                .any(|c| c.name == *constraint_name)
+        }),
+        MigrationOp::DropExclusionConstraint {
+            table,
+            constraint_name,
+        } => table_lacks(table, &|t| {
+            t.exclusion_constraints
+                .iter()
+                .any(|c| c.name == *constraint_name)
+        }),
+        MigrationOp::DropPolicy { table, name } => {
+            table_lacks(table, &|t| t.policies.iter().any(|p| p.name == *name))
+        }
+        MigrationOp::DropRawObject(name) => !to.raw_objects.contains_key(name),
+        MigrationOp::DisableRls { .. }
+        | MigrationOp::NoForceRls { .. }
+        | MigrationOp::SetReplicaIdentity { identity: None, .. }
+        | MigrationOp::RevokePrivileges { .. }
+        | MigrationOp::AlterDefaultPrivileges { revoke: true, .. }
+        | MigrationOp::SetComment { comment: None, .. } => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_sql_string;
+
+    #[test]
+    fn orphans_group_undeclared_objects_by_kind() {
+        let from = parse_sql_string(
+            "CREATE TABLE users (id BIGINT PRIMARY KEY, email TEXT, legacy TEXT);
+             CREATE INDEX users_email_idx ON users (email);
+             CREATE INDEX users_legacy_idx ON users (legacy);
+             CREATE TABLE audit_log (id BIGINT);
+             COMMENT ON TABLE users IS 'people';",
+        )
+        .unwrap();
+        let to = parse_sql_string(
+            "CREATE TABLE users (id BIGINT PRIMARY KEY, email TEXT);
+             CREATE INDEX users_email_idx ON users (lower(email));",
+        )
+        .unwrap();
+        let type_equivalences = crate::diff::TypeEquivalences::default();
+        let excluded = std::collections::HashSet::new();
+        let options = DiffOptions {
+            manage_ownership: false,
+            manage_grants: false,
+            excluded_grant_roles: &excluded,
+            adoption_mode: AdoptionMode::Additive,
+            type_equivalences: &type_equivalences,
+            default_comparison: Default::default(),
+            detect_renames: false,
+        };
+
+        assert_eq!(
+            orphaned_objects(&from, &to, &options),
+            BTreeMap::from([
+                (
+                    "columns".to_string(),
+                    vec!["public.users.legacy".to_string()]
+                ),
+                (
+                    "indexes".to_string(),
+                    vec!["public.users.users_legacy_idx".to_string()]
+                ),
+                ("tables".to_string(), vec!["public.audit_log".to_string()]),
+            ])
+        );
+    }
+
+    #[test]
+    fn drops_of_declared_objects_are_not_removals() {
+        let to = parse_sql_string(
+            "CREATE TABLE users (id BIGINT PRIMARY KEY, email TEXT);
+             CREATE INDEX users_email_idx ON users (email);",
+        )
+        .unwrap();
+        let users = QualifiedName::new("public", "users");
+
+        assert!(!removes_undeclared(
+            &MigrationOp::DropIndex {
+                table: users.clone(),
+                index_name: "users_email_idx".to_string(),
+            },
+            &to
+        ));
+        assert!(removes_undeclared(
+            &MigrationOp::DropIndex {
+                table: users.clone(),
+                index_name: "users_legacy_idx".to_string(),
+            },
+            &to
+        ));
+        assert!(removes_undeclared(
+            &MigrationOp::DropColumn {
+                table: users,
+                column: "legacy".to_string(),
+            },
+            &to
+        ));
+        assert!(!removes_undeclared(
+            &MigrationOp::DropTable("public.users".to_string()),
+            &to
+        ));
+        assert!(removes_undeclared(
+            &MigrationOp::DropTable("public.audit_log".to_string()),
+            &to
+        ));
+    }
+
+    #[test]
+    fn additive_diff_keeps_additions_and_recreates_only() {
+        let from = parse_sql_string(
+            "CREATE TABLE users (id BIGINT PRIMARY KEY, legacy TEXT);
+             CREATE INDEX users_id_idx ON users (id);
+             ALTER TABLE users ENABLE ROW LEVEL SECURITY;
+             CREATE TABLE audit_log (id BIGINT);",
+        )
+        .unwrap();
+        let to = parse_sql_string(
+            "CREATE TABLE users (id BIGINT PRIMARY KEY, email TEXT);
+             CREATE UNIQUE INDEX users_id_idx ON users (id);",
+        )
+        .unwrap();
+        let excluded = std::collections::HashSet::new();
+        let ops = crate::diff::compute_diff_with_options(
+            &from,
+            &to,
+            &crate::diff::DiffOptions {
+                manage_ownership: false,
+                manage_grants: false,
+                excluded_grant_roles: &excluded,
+                adoption_mode: crate::diff::AdoptionMode::Additive,
+                type_equivalences: &crate::diff::TypeEquivalences::default(),
+                default_comparison: crate::diff::DefaultComparison::default(),
+                detect_renames: false,
+            },
+        );
+
+        assert!(ops.iter().any(
+            |op| matches!(op, MigrationOp::AddColumn { column, .. } if column.name == "email")
+        ));
+        assert!(ops
+            .iter()
+            .any(|op| matches!(op, MigrationOp::AddIndex { .. })));
+        assert!(ops
+            .iter()
+            .any(|op| matches!(op, MigrationOp::DropIndex { .. })));
+        assert!(!ops.iter().any(|op| matches!(
+            op,
+            MigrationOp::DropTable(_)
+                | MigrationOp::DropColumn { .. }
+                | MigrationOp::DisableRls { .. }
+        )));
+    }
+}
@@ -2,6 +2,7 @@ use std::collections::HashSet;
 
 use crate::model::{parse_qualified_name, qualified_name, Policy, QualifiedName, Schema};
 use crate::parser::{extract_function_references, extract_table_references};
+use crate::util::query_selects_star;
 
 use super::MigrationOp;
 
@@ -164,7 +165,7 @@ pub(super) fn generate_policy_ops_for_affected_tables(
 /// silently loses the comment until the next plan run. Re-emit `SetComment`
 /// alongside the recreate so the comment survives in lockstep with the
 /// policy.
-fn push_policy_recreate_comment(ops: &mut Vec<MigrationOp>, policy: &Policy) {
+pub(super) fn push_policy_recreate_comment(ops: &mut Vec<MigrationOp>, policy: &Policy) {
     let Some(comment_text) = policy.comment.as_ref() else {
         return;
     };
@@ -313,6 +314,77 @@ pub(super) fn generate_view_ops_for_affected_tables(
     (additional_ops, views_to_filter)
 }
 
+/// Extract qualified names of views in `from` that `to` still defines with `SELECT *`
+/// over a table gaining columns in `ops`. PostgreSQL fixed their column lists when it
+/// expanded the star, so they are rebuilt (with their dependents) to pick the new
+/// columns up.
+pub(super) fn star_views_over_widened_tables(
+    ops: &[MigrationOp],
+    from: &Schema,
+    to: &Schema,
+) -> HashSet<String> {
+    let widened_tables: HashSet<String> = ops
+        .iter()
+        .filter_map(|op| {
+            if let MigrationOp::AddColumn { table, .. } = op {
+                Some(table.to_string())
+            } else {
+                None
+            }
+        })
+        .collect();
+    if widened_tables.is_empty() {
+        return HashSet::new();
+    }
+
+    from.views
+        .iter()
+        .filter_map(|(key, view)| {
+            let target = to.views.get(key)?;
+            if !query_selects_star(&target.query) {
+                return None;
+            }
+            extract_table_references(&target.query, &target.schema)
+                .iter()
+                .any(|reference| widened_tables.contains(&reference.qualified_name()))
+                .then(|| qualified_name(&view.schema, &view.name))
+        })
+        .collect()
+}
+
+/// Extract qualified names of views being altered in place that other views in `from`
+/// select from. `CREATE OR REPLACE VIEW` cannot drop, rename or retype output columns,
+/// and a materialized view replace is a plain DROP + CREATE, so any altered view with
+/// dependents is rebuilt together with them instead.
+pub(super) fn altered_views_with_dependents(ops: &[MigrationOp], from: &Schema) -> HashSet<String> {
+    let altered_views: HashSet<&String> = ops
+        .iter()
+        .filter_map(|op| {
+            if let MigrationOp::AlterView { name, .. } = op {
+                Some(name)
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    if altered_views.is_empty() {
+        return HashSet::new();
+    }
+
+    let mut with_dependents = HashSet::new();
+    for view in from.views.values() {
+        let qualified_view_name = qualified_name(&view.schema, &view.name);
+        for reference in extract_table_references(&view.query, &view.schema) {
+            let referenced = reference.qualified_name();
+            if referenced != qualified_view_name && altered_views.contains(&referenced) {
+                with_dependents.insert(referenced);
+            }
+        }
+    }
+    with_dependents
+}
+
 /// Generate policy drop/create ops for policies that reference functions being dropped.
 /// PostgreSQL requires dependent policies to be dropped before dropping functions they reference.
 /// Returns (additional_ops, policies_to_filter) where policies_to_filter are (table, name) pairs
@@ -325,16 +397,7 @@ pub(super) fn generate_policy_ops_for_function_changes(
     let mut additional_ops = Vec::new();
     let mut policies_to_filter = HashSet::new();
 
-    let dropped_functions: HashSet<String> = ops
-        .iter()
-        .filter_map(|op| {
-            if let MigrationOp::DropFunction { name, .. } = op {
-                Some(name.clone())
-            } else {
-                None
-            }
-        })
-        .collect();
+    let dropped_functions = dropped_function_names(ops);
 
     if dropped_functions.is_empty() {
         return (additional_ops, policies_to_filter);
@@ -376,6 +439,104 @@ pub(super) fn generate_policy_ops_for_function_changes(
     (additional_ops, policies_to_filter)
 }
 
+/// Extract qualified names of functions being dropped, either because they were
+/// removed from the target or because a signature change (return type, argument
+/// names or defaults) forces DROP + CREATE instead of CREATE OR REPLACE.
+fn dropped_function_names(ops: &[MigrationOp]) -> HashSet<String> {
+    ops.iter()
+        .filter_map(|op| {
+            if let MigrationOp::DropFunction { name, .. } = op {
+                Some(name.clone())
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Generate trigger drop/create ops for triggers that execute a function being dropped.
+/// PostgreSQL refuses to drop a function while a trigger still references it, so the
+/// trigger is dropped first and recreated once the new function exists.
+pub(super) fn generate_trigger_ops_for_function_changes(
+    ops: &[MigrationOp],
+    from: &Schema,
+    to: &Schema,
+) -> Vec<MigrationOp> {
+    let mut additional_ops = Vec::new();
+
+    let dropped_functions = dropped_function_names(ops);
+    if dropped_functions.is_empty() {
+        return additional_ops;
+    }
+
+    let existing_trigger_drops: HashSet<(String, String, String)> =
+        collect_existing_drops(ops, |op| match op {
+            MigrationOp::DropTrigger {
+                target_schema,
+                target_name,
+                name,
+            } => Some((target_schema.clone(), target_name.clone(), name.clone())),
+            _ => None,
+        });
+
+    for (key, trigger) in &from.triggers {
+        let function = qualified_name(&trigger.function_schema, &trigger.function_name);
+        if !dropped_functions.contains(&function) {
+            continue;
+        }
+        if existing_trigger_drops.contains(&(
+            trigger.target_schema.clone(),
+            trigger.target_name.clone(),
+            trigger.name.clone(),
+        )) {
+            continue;
+        }
+
+        additional_ops.push(MigrationOp::DropTrigger {
+            target_schema: trigger.target_schema.clone(),
+            target_name: trigger.target_name.clone(),
+            name: trigger.name.clone(),
+        });
+        if let Some(target_trigger) = to.triggers.get(key) {
+            additional_ops.push(MigrationOp::CreateTrigger(target_trigger.clone()));
+        }
+    }
+
+    additional_ops
+}
+
+/// Generate view drop/create ops for views that call a function being dropped, plus
+/// every view that transitively selects from them. Returns the same shape as
+/// [`generate_view_ops_for_affected_tables`] so callers can filter duplicate AlterView ops.
+pub(super) fn generate_view_ops_for_function_changes(
+    ops: &[MigrationOp],
+    from: &Schema,
+    to: &Schema,
+) -> (Vec<MigrationOp>, HashSet<String>) {
+    let dropped_functions = dropped_function_names(ops);
+    if dropped_functions.is_empty() {
+        return (Vec::new(), HashSet::new());
+    }
+
+    let referencing_views: HashSet<String> = from
+        .views
+        .values()
+        .filter(|view| {
+            extract_function_references(&view.query, &view.schema)
+                .iter()
+                .any(|func_ref| {
+                    let referenced = qualified_name(&func_ref.schema, &func_ref.name);
+                    dropped_functions
+                        .iter()
+                        .any(|dropped| function_names_match(dropped, &referenced))
+                })
+        })
+        .map(|view| qualified_name(&view.schema, &view.name))
+        .collect();
+
+    generate_view_ops_for_affected_tables(ops, from, to, &referencing_views)
+}
+
 /// Check if a policy references any of the given functions in its USING or WITH CHECK expressions.
 fn policy_references_functions(policy: &Policy, function_names: &HashSet<String>) -> bool {
     let policy_func_refs = extract_function_references_from_policy(policy);
@@ -436,6 +597,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         from.tables.insert("public.users".to_string(), users_table);
@@ -450,6 +614,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         posts_table.foreign_keys.push(ForeignKey {
@@ -460,6 +627,8 @@ mod tests {
             referenced_columns: vec!["id".to_string()],
             on_delete: ReferentialAction::NoAction,
             on_update: ReferentialAction::NoAction,
+            deferrable: false,
+            initially_deferred: false,
         });
         from.tables.insert("public.posts".to_string(), posts_table);
 
@@ -474,6 +643,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         to.tables
@@ -489,6 +661,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         posts_table_uuid.foreign_keys.push(ForeignKey {
@@ -499,6 +674,8 @@ mod tests {
             referenced_columns: vec!["id".to_string()],
             on_delete: ReferentialAction::NoAction,
             on_update: ReferentialAction::NoAction,
+            deferrable: false,
+            initially_deferred: false,
         });
         to.tables
             .insert("public.posts".to_string(), posts_table_uuid);
@@ -555,6 +732,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         from.tables
@@ -570,6 +750,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         fertilizer_app.foreign_keys.push(ForeignKey {
@@ -580,6 +763,8 @@ mod tests {
             referenced_columns: vec!["id".to_string()],
             on_delete: ReferentialAction::NoAction,
             on_update: ReferentialAction::NoAction,
+            deferrable: false,
+            initially_deferred: false,
         });
         from.tables
             .insert("mrv.FertilizerApplication".to_string(), fertilizer_app);
@@ -596,6 +781,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         to.tables
@@ -611,6 +799,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         fertilizer_app_uuid.foreign_keys.push(ForeignKey {
@@ -621,6 +812,8 @@ mod tests {
             referenced_columns: vec!["id".to_string()],
             on_delete: ReferentialAction::NoAction,
             on_update: ReferentialAction::NoAction,
+            deferrable: false,
+            initially_deferred: false,
         });
         to.tables
             .insert("mrv.FertilizerApplication".to_string(), fertilizer_app_uuid);
@@ -682,6 +875,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         users_table.policies.push(Policy {
@@ -707,6 +903,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         users_table_uuid.policies.push(Policy {
@@ -775,6 +974,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         users_table.policies.push(Policy {
@@ -800,6 +1002,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         users_table_uuid.policies.push(Policy {
@@ -857,6 +1062,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         from.tables.insert("public.users".to_string(), users_table);
@@ -895,6 +1103,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         to.tables
@@ -971,6 +1182,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         users_table.columns.insert(
@@ -982,6 +1196,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         from.tables.insert("public.users".to_string(), users_table);
@@ -995,6 +1212,9 @@ mod tests {
                 owner: None,
                 grants: vec![],
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
 
@@ -1009,6 +1229,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         users_table_uuid.columns.insert(
@@ -1020,6 +1243,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         to.tables
@@ -1034,6 +1260,9 @@ mod tests {
                 owner: None,
                 grants: vec![],
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
 
@@ -1085,6 +1314,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         users_table.columns.insert(
@@ -1096,6 +1328,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         users_table.policies.push(Policy {
@@ -1121,6 +1356,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         users_table_to.policies.push(Policy {
@@ -1186,10 +1424,10 @@ mod tests {
             name: "check_access".to_string(),
             schema: "public".to_string(),
             arguments: vec![FunctionArg {
-                name: Some("user_name".to_string()),
+                name: Some("role_name".to_string()),
                 data_type: "text".to_string(),
                 mode: ArgMode::In,
-                default: Some("'superuser'".to_string()),
+                default: Some("'admin'".to_string()),
             }],
             return_type: "boolean".to_string(),
             language: "sql".to_string(),
@@ -1285,6 +1523,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         users_table.columns.insert(
@@ -1296,6 +1537,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         from.tables.insert("public.users".to_string(), users_table);
@@ -1334,6 +1578,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         to.tables.insert("public.users".to_string(), users_table_to);
@@ -1397,6 +1644,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         users_table.columns.insert(
@@ -1408,6 +1658,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         from.tables.insert("public.users".to_string(), users_table);
@@ -1421,6 +1674,9 @@ mod tests {
                 owner: None,
                 grants: vec![],
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
 
@@ -1435,6 +1691,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         to.tables.insert("public.users".to_string(), users_table_to);
@@ -1448,6 +1707,9 @@ mod tests {
                 owner: None,
                 grants: vec![],
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
 
@@ -1469,4 +1731,330 @@ mod tests {
             "Should have exactly 1 CreateView op"
         );
     }
+
+    fn function_returning(name: &str, return_type: &str, body: &str) -> Function {
+        Function {
+            name: name.to_string(),
+            schema: "public".to_string(),
+            arguments: vec![],
+            return_type: return_type.to_string(),
+            language: "sql".to_string(),
+            body: body.to_string(),
+            volatility: Volatility::Stable,
+            security: SecurityType::Invoker,
+            config_params: vec![],
+            owner: None,
+            grants: Vec::new(),
+            comment: None,
+        }
+    }
+
+    fn insert_function(schema: &mut crate::model::Schema, func: Function) {
+        schema
+            .functions
+            .insert(qualified_name(&func.schema, &func.signature()), func);
+    }
+
+    #[test]
+    fn generates_trigger_ops_for_function_signature_changes() {
+        let mut from = empty_schema();
+        let mut to = empty_schema();
+        insert_function(
+            &mut from,
+            function_returning("audit_func", "trigger", "SELECT 1"),
+        );
+        insert_function(
+            &mut to,
+            function_returning("audit_func", "void", "SELECT 1"),
+        );
+        from.tables
+            .insert("public.users".to_string(), simple_table("users"));
+        to.tables
+            .insert("public.users".to_string(), simple_table("users"));
+
+        let trigger = Trigger {
+            name: "audit_trigger".to_string(),
+            target_schema: "public".to_string(),
+            target_name: "users".to_string(),
+            function_schema: "public".to_string(),
+            function_name: "audit_func".to_string(),
+            events: vec![TriggerEvent::Insert],
+            timing: TriggerTiming::After,
+            for_each_row: true,
+            when_clause: None,
+            function_args: vec![],
+            enabled: TriggerEnabled::Origin,
+            update_columns: vec![],
+            old_table_name: None,
+            new_table_name: None,
+            is_constraint: false,
+            deferrable: false,
+            initially_deferred: false,
+            comment: None,
+        };
+        from.triggers
+            .insert("public.users.audit_trigger".to_string(), trigger.clone());
+        to.triggers
+            .insert("public.users.audit_trigger".to_string(), trigger);
+
+        let ops = compute_diff(&from, &to);
+
+        assert_eq!(
+            ops.iter()
+                .filter(|op| matches!(op, MigrationOp::DropFunction { .. }))
+                .count(),
+            1
+        );
+        assert_eq!(
+            ops.iter()
+                .filter(|op| matches!(op, MigrationOp::DropTrigger { name, .. } if name == "audit_trigger"))
+                .count(),
+            1,
+            "Trigger must be dropped before its function"
+        );
+        assert_eq!(
+            ops.iter()
+                .filter(
+                    |op| matches!(op, MigrationOp::CreateTrigger(t) if t.name == "audit_trigger")
+                )
+                .count(),
+            1,
+            "Trigger must be recreated after its function"
+        );
+    }
+
+    #[test]
+    fn generates_view_ops_for_function_signature_changes() {
+        let mut from = empty_schema();
+        let mut to = empty_schema();
+        insert_function(
+            &mut from,
+            function_returning("score", "integer", "SELECT 1"),
+        );
+        insert_function(&mut to, function_returning("score", "bigint", "SELECT 1"));
+
+        let scored = View {
+            name: "scored".to_string(),
+            schema: "public".to_string(),
+            query: "SELECT public.score() AS score".to_string(),
+            materialized: false,
+            owner: None,
+            grants: vec![],
+            comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
+        };
+        let top_scored = View {
+            name: "top_scored".to_string(),
+            schema: "public".to_string(),
+            query: "SELECT score FROM public.scored".to_string(),
+            materialized: false,
+            owner: None,
+            grants: vec![],
+            comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
+        };
+        for view in [scored, top_scored] {
+            let key = qualified_name(&view.schema, &view.name);
+            from.views.insert(key.clone(), view.clone());
+            to.views.insert(key, view);
+        }
+
+        let ops = compute_diff(&from, &to);
+
+        let dropped_views: Vec<&str> = ops
+            .iter()
+            .filter_map(|op| match op {
+                MigrationOp::DropView { name, .. } => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        let created_views: Vec<&str> = ops
+            .iter()
+            .filter_map(|op| match op {
+                MigrationOp::CreateView(view) => Some(view.name.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        assert!(dropped_views.contains(&"public.scored"));
+        assert!(
+            dropped_views.contains(&"public.top_scored"),
+            "Transitively dependent view must also be dropped"
+        );
+        assert_eq!(created_views.len(), 2);
+    }
+
+    #[test]
+    fn body_only_function_change_keeps_dependents() {
+        let mut from = empty_schema();
+        let mut to = empty_schema();
+        insert_function(
+            &mut from,
+            function_returning("score", "integer", "SELECT 1"),
+        );
+        insert_function(&mut to, function_returning("score", "integer", "SELECT 2"));
+
+        let view = View {
+            name: "scored".to_string(),
+            schema: "public".to_string(),
+            query: "SELECT public.score() AS score".to_string(),
+            materialized: false,
+            owner: None,
+            grants: vec![],
+            comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
+        };
+        from.views.insert("public.scored".to_string(), view.clone());
+        to.views.insert("public.scored".to_string(), view);
+
+        let ops = compute_diff(&from, &to);
+
+        assert_eq!(ops.len(), 1, "Expected only AlterFunction, got {ops:?}");
+        assert!(matches!(ops[0], MigrationOp::AlterFunction { .. }));
+    }
+
+    #[test]
+    fn rebuilds_star_views_when_their_table_gains_columns() {
+        // `from` stands for the database, where the star was expanded at creation.
+        let from = crate::parser::parse_sql_string(
+            "CREATE TABLE public.users (id integer, name text);
+             CREATE VIEW public.all_users AS SELECT users.id, users.name FROM public.users;
+             CREATE VIEW public.named_users AS SELECT users.id, users.name FROM public.users;
+             CREATE VIEW public.user_names AS SELECT all_users.name FROM public.all_users;",
+        )
+        .unwrap();
+        let to = crate::parser::parse_sql_string(
+            "CREATE TABLE public.users (id integer, name text, email text);
+             CREATE VIEW public.all_users AS SELECT * FROM public.users;
+             CREATE VIEW public.named_users AS SELECT users.id, users.name FROM public.users;
+             CREATE VIEW public.user_names AS SELECT all_users.name FROM public.all_users;",
+        )
+        .unwrap();
+
+        let ops = compute_diff(&from, &to);
+
+        let mut dropped: Vec<&str> = ops
+            .iter()
+            .filter_map(|op| match op {
+                MigrationOp::DropView { name, .. } => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        dropped.sort();
+        assert_eq!(dropped, vec!["public.all_users", "public.user_names"]);
+        assert!(ops
+            .iter()
+            .any(|op| matches!(op, MigrationOp::CreateView(view) if view.query.contains('*'))));
+        assert!(!ops
+            .iter()
+            .any(|op| matches!(op, MigrationOp::AlterView { .. })));
+    }
+
+    #[test]
+    fn rebuilds_dependent_views_when_base_view_altered() {
+        let mut from = empty_schema();
+        let mut to = empty_schema();
+        from.tables
+            .insert("public.users".to_string(), simple_table("users"));
+        to.tables
+            .insert("public.users".to_string(), simple_table("users"));
+
+        let make_view = |name: &str, query: &str| View {
+            name: name.to_string(),
+            schema: "public".to_string(),
+            query: query.to_string(),
+            materialized: false,
+            owner: None,
+            grants: vec![],
+            comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
+        };
+        let dependent = make_view("active_users", "SELECT id FROM public.user_summary");
+        let report = make_view("active_report", "SELECT id FROM public.active_users");
+        from.views.insert(
+            "public.user_summary".to_string(),
+            make_view("user_summary", "SELECT id, name FROM public.users"),
+        );
+        to.views.insert(
+            "public.user_summary".to_string(),
+            make_view("user_summary", "SELECT id FROM public.users"),
+        );
+        for view in [dependent, report] {
+            let key = qualified_name(&view.schema, &view.name);
+            from.views.insert(key.clone(), view.clone());
+            to.views.insert(key, view);
+        }
+
+        let ops = compute_diff(&from, &to);
+
+        assert!(
+            !ops.iter()
+                .any(|op| matches!(op, MigrationOp::AlterView { .. })),
+            "Altered base view must be rebuilt, not replaced: {ops:?}"
+        );
+        let mut dropped_views: Vec<&str> = ops
+            .iter()
+            .filter_map(|op| match op {
+                MigrationOp::DropView { name, .. } => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        dropped_views.sort();
+        assert_eq!(
+            dropped_views,
+            vec![
+                "public.active_report",
+                "public.active_users",
+                "public.user_summary"
+            ]
+        );
+        let recreated_summary = ops.iter().find_map(|op| match op {
+            MigrationOp::CreateView(view) if view.name == "user_summary" => Some(view),
+            _ => None,
+        });
+        assert_eq!(
+            recreated_summary.map(|view| view.query.as_str()),
+            Some("SELECT id FROM public.users")
+        );
+    }
+
+    #[test]
+    fn altered_view_without_dependents_is_replaced_in_place() {
+        let mut from = empty_schema();
+        let mut to = empty_schema();
+        let view = View {
+            name: "user_summary".to_string(),
+            schema: "public".to_string(),
+            query: "SELECT id, name FROM public.users".to_string(),
+            materialized: false,
+            owner: None,
+            grants: vec![],
+            comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
+        };
+        from.views
+            .insert("public.user_summary".to_string(), view.clone());
+        to.views.insert(
+            "public.user_summary".to_string(),
+            View {
+                query: "SELECT id FROM public.users".to_string(),
+                ..view
+            },
+        );
+
+        let ops = compute_diff(&from, &to);
+
+        assert_eq!(ops.len(), 1, "Expected only AlterView, got {ops:?}");
+        assert!(matches!(ops[0], MigrationOp::AlterView { .. }));
+    }
 }
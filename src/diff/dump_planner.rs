@@ -1,7 +1,7 @@
 use super::op_key::extract_relation_references;
 use super::MigrationOp;
 use crate::model::qualified_name;
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 /// Unlike plan_migration, keeps OWNED BY inline in CREATE SEQUENCE
 /// by placing sequences after tables they reference.
@@ -23,6 +23,7 @@ pub(crate) fn plan_dump(ops: Vec<MigrationOp>) -> Vec<MigrationOp> {
     let mut grant_privileges = Vec::new();
     let mut alter_default_privileges = Vec::new();
     let mut set_comments = Vec::new();
+    let mut set_sequence_values = Vec::new();
 
     for op in ops {
         match op {
@@ -40,14 +41,19 @@ pub(crate) fn plan_dump(ops: Vec<MigrationOp>) -> Vec<MigrationOp> {
             MigrationOp::CreateView(_) => create_views.push(op),
             MigrationOp::CreateTrigger(_) => create_triggers.push(op),
             MigrationOp::EnableRls { .. } => enable_rls.push(op),
-            MigrationOp::ForceRls { .. } => enable_rls.push(op),
+            MigrationOp::ForceRls { .. } | MigrationOp::SetReplicaIdentity { .. } => {
+                enable_rls.push(op)
+            }
             MigrationOp::CreatePolicy(_) => create_policies.push(op),
             MigrationOp::AlterOwner { .. } => alter_owners.push(op),
             MigrationOp::GrantPrivileges { .. } => grant_privileges.push(op),
             MigrationOp::AlterDefaultPrivileges { .. } => alter_default_privileges.push(op),
             MigrationOp::SetComment { .. } => set_comments.push(op),
-            MigrationOp::DropSchema(_)
+            MigrationOp::SetSequenceValue { .. } => set_sequence_values.push(op),
+            MigrationOp::DropSchema { .. }
             | MigrationOp::DropExtension(_)
+            | MigrationOp::UpdateExtension { .. }
+            | MigrationOp::SetExtensionSchema { .. }
             | MigrationOp::DropServer(_)
             | MigrationOp::AlterServer { .. }
             | MigrationOp::DropEnum(_)
@@ -58,14 +64,19 @@ pub(crate) fn plan_dump(ops: Vec<MigrationOp>) -> Vec<MigrationOp> {
             | MigrationOp::DropPartition(_)
             | MigrationOp::AddColumn { .. }
             | MigrationOp::DropColumn { .. }
+            | MigrationOp::RenameTable { .. }
+            | MigrationOp::RenameSequence { .. }
+            | MigrationOp::RenameColumn { .. }
             | MigrationOp::AlterColumn { .. }
             | MigrationOp::AddPrimaryKey { .. }
             | MigrationOp::DropPrimaryKey { .. }
             | MigrationOp::AddIndex { .. }
             | MigrationOp::DropIndex { .. }
+            | MigrationOp::AddUniqueConstraint { .. }
             | MigrationOp::DropUniqueConstraint { .. }
             | MigrationOp::AddForeignKey { .. }
             | MigrationOp::DropForeignKey { .. }
+            | MigrationOp::AlterForeignKeyDeferral { .. }
             | MigrationOp::AddCheckConstraint { .. }
             | MigrationOp::DropCheckConstraint { .. }
             | MigrationOp::AddExclusionConstraint { .. }
@@ -79,17 +90,26 @@ pub(crate) fn plan_dump(ops: Vec<MigrationOp>) -> Vec<MigrationOp> {
             | MigrationOp::DropAggregate { .. }
             | MigrationOp::DropView { .. }
             | MigrationOp::AlterView { .. }
+            | MigrationOp::AlterViewOptions { .. }
+            | MigrationOp::RefreshMaterializedView { .. }
             | MigrationOp::DropTrigger { .. }
             | MigrationOp::AlterTriggerEnabled { .. }
             | MigrationOp::DropSequence(_)
             | MigrationOp::AlterSequence { .. }
+            | MigrationOp::SetSchema { .. }
             | MigrationOp::BackfillHint { .. }
             | MigrationOp::SetColumnNotNull { .. }
+            | MigrationOp::CreateIndexConcurrently { .. }
+            | MigrationOp::DropIndexConcurrently { .. }
+            | MigrationOp::AddPrimaryKeyUsingIndex { .. }
+            | MigrationOp::AddUniqueConstraintUsingIndex { .. }
             | MigrationOp::RevokePrivileges { .. }
             | MigrationOp::CreateVersionSchema { .. }
             | MigrationOp::DropVersionSchema { .. }
             | MigrationOp::CreateVersionView { .. }
-            | MigrationOp::DropVersionView { .. } => {}
+            | MigrationOp::DropVersionView { .. }
+            | MigrationOp::ApplyRawObject(_)
+            | MigrationOp::DropRawObject(_) => {}
         }
     }
 
@@ -115,6 +135,7 @@ pub(crate) fn plan_dump(ops: Vec<MigrationOp>) -> Vec<MigrationOp> {
     result.extend(grant_privileges);
     result.extend(alter_default_privileges);
     result.extend(set_comments);
+    result.extend(set_sequence_values);
 
     result
 }
@@ -205,7 +226,8 @@ fn kahn_sort(
         }
     }
 
-    let mut queue: VecDeque<String> = in_degree
+    // Ready names are taken in name order so the dump is identical across runs.
+    let mut ready: BTreeSet<String> = in_degree
         .iter()
         .filter(|(_, &count)| count == 0)
         .map(|(name, _)| name.clone())
@@ -213,25 +235,26 @@ fn kahn_sort(
 
     let mut sorted_names: Vec<String> = Vec::new();
 
-    while let Some(name) = queue.pop_front() {
-        sorted_names.push(name.clone());
+    while let Some(name) = ready.pop_first() {
         if let Some(dependents) = reverse_deps.get(&name) {
             for dependent in dependents {
                 if let Some(count) = in_degree.get_mut(dependent) {
                     *count -= 1;
                     if *count == 0 {
-                        queue.push_back(dependent.clone());
+                        ready.insert(dependent.clone());
                     }
                 }
             }
         }
+        sorted_names.push(name);
     }
 
-    let unsorted: Vec<String> = named_ops
+    let mut unsorted: Vec<String> = named_ops
         .keys()
         .filter(|name| !sorted_names.contains(name))
         .cloned()
         .collect();
+    unsorted.sort();
     sorted_names.extend(unsorted);
 
     sorted_names
@@ -294,4 +317,31 @@ mod tests {
             "GrantPrivileges should come before AlterDefaultPrivileges in dump"
         );
     }
+
+    #[test]
+    fn plan_dump_orders_independent_tables_by_name() {
+        let names = |ops: Vec<MigrationOp>| -> Vec<String> {
+            plan_dump(ops)
+                .into_iter()
+                .filter_map(|op| match op {
+                    MigrationOp::CreateTable(t) => Some(t.name),
+                    _ => None,
+                })
+                .collect()
+        };
+        let tables = ["teams", "audit", "users", "billing"];
+
+        let forward = tables
+            .iter()
+            .map(|n| MigrationOp::CreateTable(simple_table_with_fks(n, vec![])))
+            .collect();
+        let backward = tables
+            .iter()
+            .rev()
+            .map(|n| MigrationOp::CreateTable(simple_table_with_fks(n, vec![])))
+            .collect();
+
+        assert_eq!(names(forward), vec!["audit", "billing", "teams", "users"]);
+        assert_eq!(names(backward), vec!["audit", "billing", "teams", "users"]);
+    }
 }
@@ -1,33 +1,48 @@
+mod adoption;
 mod dependencies;
 pub(crate) mod dump_planner;
 mod grants;
+mod moves;
 mod objects;
 mod op_key;
 pub mod planner;
+mod relocate;
+mod renames;
 mod table_elements;
+mod type_equivalence;
 mod types;
 
-use std::collections::HashSet;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
 
 use crate::model::{QualifiedName, Schema};
+pub use adoption::orphaned_objects;
+pub use type_equivalence::TypeEquivalences;
 pub use types::{
-    ColumnChanges, CommentObjectType, DiffOptions, DomainChanges, EnumValuePosition,
-    GrantObjectKind, MigrationOp, OwnerObjectKind, PolicyChanges, SequenceChanges,
+    AdoptionMode, ColumnChanges, CommentObjectType, DefaultComparison, DiffOptions, DomainChanges,
+    EnumValuePosition, GrantObjectKind, IdentityChange, MigrationOp, OwnerObjectKind,
+    PolicyChanges, SequenceChanges, ViewOptionChanges,
 };
 
 use dependencies::{
-    generate_fk_ops_for_type_changes, generate_policy_ops_for_affected_tables,
-    generate_policy_ops_for_function_changes, generate_trigger_ops_for_affected_tables,
-    generate_view_ops_for_affected_tables, tables_with_dropped_columns, type_changed_columns,
+    altered_views_with_dependents, generate_fk_ops_for_type_changes,
+    generate_policy_ops_for_affected_tables, generate_policy_ops_for_function_changes,
+    generate_trigger_ops_for_affected_tables, generate_trigger_ops_for_function_changes,
+    generate_view_ops_for_affected_tables, generate_view_ops_for_function_changes,
+    star_views_over_widened_tables, tables_with_dropped_columns, type_changed_columns,
 };
 use grants::diff_default_privileges;
+use moves::relocate_moved_objects;
 use objects::{
     diff_aggregates, diff_domains, diff_enums, diff_extensions, diff_functions, diff_partitions,
-    diff_schemas, diff_sequences, diff_servers, diff_tables, diff_triggers, diff_views,
+    diff_raw_objects, diff_schemas, diff_sequences, diff_servers, diff_tables, diff_triggers,
+    diff_views,
 };
+use renames::rename_objects;
 use table_elements::{
     diff_check_constraints, diff_columns, diff_exclusion_constraints, diff_force_rls,
-    diff_foreign_keys, diff_indexes, diff_policies, diff_primary_keys, diff_rls,
+    diff_foreign_keys, diff_indexes, diff_policies, diff_primary_keys, diff_replica_identity,
+    diff_rls, diff_unique_constraints,
 };
 
 pub fn compute_diff(from: &Schema, to: &Schema) -> Vec<MigrationOp> {
@@ -41,36 +56,65 @@ pub fn compute_diff_with_flags(
     manage_grants: bool,
     excluded_grant_roles: &HashSet<String>,
 ) -> Vec<MigrationOp> {
-    let options = DiffOptions {
-        manage_ownership,
-        manage_grants,
-        excluded_grant_roles,
-    };
-    let mut ops = Vec::new();
+    compute_diff_with_options(
+        from,
+        to,
+        &DiffOptions {
+            manage_ownership,
+            manage_grants,
+            excluded_grant_roles,
+            adoption_mode: AdoptionMode::Full,
+            type_equivalences: &TypeEquivalences::default(),
+            default_comparison: DefaultComparison::default(),
+            detect_renames: false,
+        },
+    )
+}
 
-    ops.extend(diff_schemas(from, to, &options));
-    ops.extend(diff_extensions(from, to, &options));
-    ops.extend(diff_servers(from, to, &options));
-    ops.extend(diff_enums(from, to, &options));
-    ops.extend(diff_domains(from, to, &options));
-    ops.extend(diff_tables(from, to, &options));
-    ops.extend(diff_partitions(from, to, &options));
-    ops.extend(diff_functions(from, to, &options));
-    ops.extend(diff_aggregates(from, to, &options));
-    ops.extend(diff_views(from, to, &options));
+pub fn compute_diff_with_options(
+    from: &Schema,
+    to: &Schema,
+    options: &DiffOptions,
+) -> Vec<MigrationOp> {
+    let (relocated, mut ops) = relocate_moved_objects(from, to);
+    let from = relocated.as_ref().unwrap_or(from);
+    let (renamed, rename_ops) = rename_objects(from, to, options);
+    let from = renamed.as_ref().unwrap_or(from);
+    ops.extend(rename_ops);
+
+    ops.extend(diff_schemas(from, to, options));
+    ops.extend(diff_extensions(from, to, options));
+    ops.extend(diff_servers(from, to, options));
+    ops.extend(diff_enums(from, to, options));
+    ops.extend(diff_domains(from, to, options));
+    ops.extend(diff_tables(from, to, options));
+    ops.extend(diff_partitions(from, to, options));
+    ops.extend(diff_functions(from, to, options));
+    ops.extend(diff_aggregates(from, to, options));
+    ops.extend(diff_views(from, to, options));
     ops.extend(diff_triggers(from, to));
-    ops.extend(diff_sequences(from, to, &options));
+    ops.extend(diff_sequences(from, to, options));
+    ops.extend(diff_raw_objects(from, to));
 
     for (name, to_table) in &to.tables {
         if let Some(from_table) = from.tables.get(name) {
-            ops.extend(diff_columns(from_table, to_table));
+            ops.extend(diff_columns(
+                from_table,
+                to_table,
+                options.type_equivalences,
+                options.default_comparison,
+            ));
             ops.extend(diff_primary_keys(from_table, to_table));
-            ops.extend(diff_indexes(from_table, to_table));
+            let mut index_ops = diff_indexes(from_table, to_table);
+            index_ops.extend(diff_unique_constraints(from_table, to_table));
+            let replica_identity_ops = diff_replica_identity(from_table, to_table, &index_ops);
+            ops.extend(index_ops);
             ops.extend(diff_foreign_keys(from_table, to_table));
             ops.extend(diff_check_constraints(from_table, to_table));
             ops.extend(diff_exclusion_constraints(from_table, to_table));
             ops.extend(diff_rls(from_table, to_table));
             ops.extend(diff_force_rls(from_table, to_table));
+            ops.extend(replica_identity_ops);
             ops.extend(diff_policies(from_table, to_table));
         } else {
             if to_table.row_level_security {
@@ -83,12 +127,25 @@ pub fn compute_diff_with_flags(
                     table: QualifiedName::new(&to_table.schema, &to_table.name),
                 });
             }
+            if to_table.replica_identity.is_some() {
+                ops.push(MigrationOp::SetReplicaIdentity {
+                    table: QualifiedName::new(&to_table.schema, &to_table.name),
+                    identity: to_table.replica_identity.clone(),
+                });
+            }
             for policy in &to_table.policies {
                 ops.push(MigrationOp::CreatePolicy(policy.clone()));
             }
         }
     }
 
+    // Removals are left out before dependent drop-and-recreate ops are derived
+    // from the plan, and again at the end for any those ops introduce.
+    let additive = options.adoption_mode == AdoptionMode::Additive;
+    if additive {
+        ops.retain(|op| !adoption::removes_undeclared(op, to));
+    }
+
     let type_change_columns = type_changed_columns(&ops);
     let affected_tables: std::collections::HashSet<String> = type_change_columns
         .iter()
@@ -163,6 +220,38 @@ pub fn compute_diff_with_flags(
     }
     ops.extend(column_drop_view_ops);
 
+    // `SELECT *` views keep the column list they were created with, so they are
+    // rebuilt when their tables gain columns.
+    let widened_star_views = star_views_over_widened_tables(&ops, from, to);
+    let (star_view_ops, star_views_to_filter) =
+        generate_view_ops_for_affected_tables(&ops, from, to, &widened_star_views);
+    if !star_views_to_filter.is_empty() {
+        ops.retain(|op| {
+            if let MigrationOp::AlterView { name, .. } = op {
+                !star_views_to_filter.contains(name)
+            } else {
+                true
+            }
+        });
+    }
+    ops.extend(star_view_ops);
+
+    // Views selected from by other views are rebuilt with their dependents rather
+    // than replaced in place; the planner orders the drops dependents-first.
+    let altered_base_views = altered_views_with_dependents(&ops, from);
+    let (view_cascade_ops, view_cascade_to_filter) =
+        generate_view_ops_for_affected_tables(&ops, from, to, &altered_base_views);
+    if !view_cascade_to_filter.is_empty() {
+        ops.retain(|op| {
+            if let MigrationOp::AlterView { name, .. } = op {
+                !view_cascade_to_filter.contains(name)
+            } else {
+                true
+            }
+        });
+    }
+    ops.extend(view_cascade_ops);
+
     // Drop/recreate policies that reference functions being dropped
     let (policy_ops, policies_to_filter) = generate_policy_ops_for_function_changes(&ops, from, to);
     if !policies_to_filter.is_empty() {
@@ -177,10 +266,31 @@ pub fn compute_diff_with_flags(
 
     ops.extend(policy_ops);
 
+    // Drop/recreate triggers and views that depend on functions being dropped.
+    // Body-only changes plan as AlterFunction (CREATE OR REPLACE) and never reach
+    // here; only signature changes that force DROP + CREATE do.
+    ops.extend(generate_trigger_ops_for_function_changes(&ops, from, to));
+    let (function_view_ops, function_views_to_filter) =
+        generate_view_ops_for_function_changes(&ops, from, to);
+    if !function_views_to_filter.is_empty() {
+        ops.retain(|op| {
+            if let MigrationOp::AlterView { name, .. } = op {
+                !function_views_to_filter.contains(name)
+            } else {
+                true
+            }
+        });
+    }
+    ops.extend(function_view_ops);
+
     ops.extend(diff_default_privileges(from, to));
 
     ops.extend(diff_comments(from, to));
 
+    if additive {
+        ops.retain(|op| !adoption::removes_undeclared(op, to));
+    }
+
     debug_assert!(
         {
             let drop_policy_keys: std::collections::HashSet<(String, String)> = ops
@@ -232,6 +342,41 @@ pub fn compute_diff_with_flags(
     ops
 }
 
+/// Where a key of two maps being diffed was found.
+enum Paired<'a, V> {
+    OnlyFrom,
+    OnlyTo(&'a V),
+    Both(&'a V, &'a V),
+}
+
+/// Walks `from` and `to` together in key order. Unlike looking each key up in
+/// the other map, this costs one comparison per entry, which matters for the
+/// per-column loops on schemas with tens of thousands of columns.
+fn pair_by_key<'a, K: Ord, V>(
+    from: &'a BTreeMap<K, V>,
+    to: &'a BTreeMap<K, V>,
+) -> impl Iterator<Item = (&'a K, Paired<'a, V>)> {
+    let mut from = from.iter().peekable();
+    let mut to = to.iter().peekable();
+    std::iter::from_fn(move || {
+        let order = match (from.peek(), to.peek()) {
+            (Some((from_key, _)), Some((to_key, _))) => from_key.cmp(to_key),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => return None,
+        };
+        Some(match order {
+            Ordering::Less => from.next().map(|(k, _)| (k, Paired::OnlyFrom))?,
+            Ordering::Greater => to.next().map(|(k, v)| (k, Paired::OnlyTo(v)))?,
+            Ordering::Equal => {
+                let (k, from_value) = from.next()?;
+                let (_, to_value) = to.next()?;
+                (k, Paired::Both(from_value, to_value))
+            }
+        })
+    })
+}
+
 fn diff_comments(from: &Schema, to: &Schema) -> Vec<MigrationOp> {
     let mut ops = Vec::new();
 
@@ -251,12 +396,14 @@ fn diff_comments(from: &Schema, to: &Schema) -> Vec<MigrationOp> {
             });
         }
 
-        for (col_name, to_col) in &to_table.columns {
-            let from_col_comment = from
-                .tables
-                .get(key)
-                .and_then(|t| t.columns.get(col_name))
-                .and_then(|c| c.comment.as_ref());
+        let empty = BTreeMap::new();
+        let from_columns = from.tables.get(key).map_or(&empty, |t| &t.columns);
+        for (col_name, paired) in pair_by_key(from_columns, &to_table.columns) {
+            let (from_col_comment, to_col) = match paired {
+                Paired::OnlyFrom => continue,
+                Paired::OnlyTo(to_col) => (None, to_col),
+                Paired::Both(from_col, to_col) => (from_col.comment.as_ref(), to_col),
+            };
             if to_col.comment.as_ref() != from_col_comment {
                 ops.push(MigrationOp::SetComment {
                     object_type: CommentObjectType::Column,
@@ -530,10 +677,12 @@ pub(super) mod test_helpers {
             primary_key: None,
             foreign_keys: Vec::new(),
             check_constraints: Vec::new(),
+            unique_constraints: Vec::new(),
             exclusion_constraints: Vec::new(),
             comment: None,
             row_level_security: false,
             force_row_level_security: false,
+            replica_identity: None,
             policies: Vec::new(),
             partition_by: None,
             owner: None,
@@ -549,6 +698,9 @@ pub(super) mod test_helpers {
             default: None,
             comment: None,
             generated: None,
+            statistics: None,
+            compression: None,
+            identity: None,
         }
     }
 }
@@ -560,10 +712,119 @@ mod tests {
     use super::*;
     use crate::model::{
         qualified_name, ArgMode, Column, Domain, EnumType, ForeignKey, Function, FunctionArg,
-        Index, IndexType, PgType, ReferentialAction, SecurityType, Sequence, SequenceDataType,
-        View, Volatility,
+        IdentityGeneration, Index, IndexType, PgType, ReferentialAction, ReplicaIdentity,
+        SecurityType, Sequence, SequenceDataType, SequenceOwner, UniqueConstraint, View,
+        Volatility,
     };
 
+    #[test]
+    fn pair_by_key_walks_both_maps_in_key_order() {
+        let from: BTreeMap<&str, i32> = [("a", 1), ("b", 2), ("d", 4)].into();
+        let to: BTreeMap<&str, i32> = [("b", 20), ("c", 30), ("d", 40), ("e", 50)].into();
+
+        let pairs: Vec<String> = pair_by_key(&from, &to)
+            .map(|(key, paired)| match paired {
+                Paired::OnlyFrom => format!("-{key}"),
+                Paired::OnlyTo(v) => format!("+{key}={v}"),
+                Paired::Both(f, t) => format!("{key}:{f}->{t}"),
+            })
+            .collect();
+
+        assert_eq!(pairs, vec!["-a", "b:2->20", "+c=30", "d:4->40", "+e=50"]);
+    }
+
+    #[test]
+    fn column_diff_lists_drops_after_adds_and_alters() {
+        let mut from_table = simple_table("users");
+        for name in ["a", "c"] {
+            from_table
+                .columns
+                .insert(name.to_string(), simple_column(name, PgType::Text));
+        }
+        let mut to_table = simple_table("users");
+        to_table
+            .columns
+            .insert("b".to_string(), simple_column("b", PgType::Text));
+        to_table
+            .columns
+            .insert("c".to_string(), simple_column("c", PgType::Integer));
+
+        let ops = table_elements::diff_columns(
+            &from_table,
+            &to_table,
+            &TypeEquivalences::default(),
+            DefaultComparison::default(),
+        );
+
+        assert!(matches!(&ops[0], MigrationOp::AddColumn { column, .. } if column.name == "b"));
+        assert!(matches!(&ops[1], MigrationOp::AlterColumn { column, .. } if column == "c"));
+        assert!(matches!(&ops[2], MigrationOp::DropColumn { column, .. } if column == "a"));
+        assert_eq!(ops.len(), 3);
+    }
+
+    #[test]
+    fn column_diff_skips_equivalent_types() {
+        let mut from_table = simple_table("users");
+        from_table.columns.insert(
+            "email".to_string(),
+            simple_column(
+                "email",
+                PgType::UserDefined("public.email_address".to_string()),
+            ),
+        );
+        let mut to_table = simple_table("users");
+        to_table
+            .columns
+            .insert("email".to_string(), simple_column("email", PgType::Text));
+
+        let rules = TypeEquivalences::parse("email_address=text");
+        assert!(table_elements::diff_columns(
+            &from_table,
+            &to_table,
+            &rules,
+            DefaultComparison::default()
+        )
+        .is_empty());
+        assert_eq!(
+            table_elements::diff_columns(
+                &from_table,
+                &to_table,
+                &TypeEquivalences::default(),
+                DefaultComparison::default()
+            )
+            .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn strict_default_comparison_alters_respelled_defaults() {
+        let table_with_default = |default: &str| {
+            let mut table = simple_table("events");
+            let mut column = simple_column("created_at", PgType::TimestampTz);
+            column.default = Some(default.to_string());
+            table.columns.insert("created_at".to_string(), column);
+            table
+        };
+        let from_table = table_with_default("CURRENT_TIMESTAMP");
+        let to_table = table_with_default("now()");
+        let rules = TypeEquivalences::default();
+
+        assert!(table_elements::diff_columns(
+            &from_table,
+            &to_table,
+            &rules,
+            DefaultComparison::Lenient
+        )
+        .is_empty());
+        let ops =
+            table_elements::diff_columns(&from_table, &to_table, &rules, DefaultComparison::Strict);
+        assert!(matches!(
+            &ops[..],
+            [MigrationOp::AlterColumn { changes, .. }] if changes.default.is_some()
+        ));
+    }
+
     #[test]
     fn detects_added_enum() {
         let from = empty_schema();
@@ -709,7 +970,8 @@ mod tests {
             unique: true,
             index_type: IndexType::BTree,
             predicate: None,
-            is_constraint: false,
+            nulls_not_distinct: false,
+            column_options: Vec::new(),
         });
         to.tables.insert("users".to_string(), table);
 
@@ -730,7 +992,8 @@ mod tests {
             unique: true,
             index_type: IndexType::BTree,
             predicate: None,
-            is_constraint: false,
+            nulls_not_distinct: false,
+            column_options: Vec::new(),
         });
         from.tables.insert("users".to_string(), from_table);
 
@@ -748,13 +1011,12 @@ mod tests {
     fn detects_removed_unique_constraint() {
         let mut from = empty_schema();
         let mut from_table = simple_table("users");
-        from_table.indexes.push(Index {
+        from_table.unique_constraints.push(UniqueConstraint {
             name: "users_email_unique".to_string(),
             columns: vec!["email".to_string()],
-            unique: true,
-            index_type: IndexType::BTree,
-            predicate: None,
-            is_constraint: true,
+            nulls_not_distinct: false,
+            deferrable: false,
+            initially_deferred: false,
         });
         from.tables.insert("users".to_string(), from_table);
 
@@ -778,19 +1040,19 @@ mod tests {
             unique: true,
             index_type: IndexType::BTree,
             predicate: None,
-            is_constraint: false,
+            nulls_not_distinct: false,
+            column_options: Vec::new(),
         });
         from.tables.insert("users".to_string(), from_table);
 
         let mut to = empty_schema();
         let mut to_table = simple_table("users");
-        to_table.indexes.push(Index {
+        to_table.unique_constraints.push(UniqueConstraint {
             name: "users_email_unique".to_string(),
             columns: vec!["email".to_string()],
-            unique: true,
-            index_type: IndexType::BTree,
-            predicate: None,
-            is_constraint: true,
+            nulls_not_distinct: false,
+            deferrable: false,
+            initially_deferred: false,
         });
         to.tables.insert("users".to_string(), to_table);
 
@@ -801,7 +1063,102 @@ mod tests {
             .any(|op| matches!(op, MigrationOp::DropIndex { .. })));
         assert!(ops
             .iter()
-            .any(|op| matches!(op, MigrationOp::AddIndex { .. })));
+            .any(|op| matches!(op, MigrationOp::AddUniqueConstraint { .. })));
+    }
+
+    #[test]
+    fn index_method_change_recreates_index() {
+        let with_method = |index_type| {
+            let mut schema = empty_schema();
+            let mut table = simple_table("events");
+            table.indexes.push(Index {
+                name: "events_created_at_idx".to_string(),
+                columns: vec!["created_at".to_string()],
+                unique: false,
+                index_type,
+                predicate: None,
+                nulls_not_distinct: false,
+                column_options: Vec::new(),
+            });
+            schema.tables.insert("events".to_string(), table);
+            schema
+        };
+
+        let ops = compute_diff(
+            &with_method(IndexType::BTree),
+            &with_method(IndexType::Brin),
+        );
+        assert_eq!(ops.len(), 2);
+        assert!(
+            matches!(&ops[0], MigrationOp::DropIndex { index_name, .. } if index_name == "events_created_at_idx")
+        );
+        assert!(
+            matches!(&ops[1], MigrationOp::AddIndex { index, .. } if index.index_type == IndexType::Brin)
+        );
+    }
+
+    #[test]
+    fn index_column_order_change_recreates_index() {
+        let with_options = |column_options| {
+            let mut schema = empty_schema();
+            let mut table = simple_table("events");
+            table.indexes.push(Index {
+                name: "events_created_at_idx".to_string(),
+                columns: vec!["created_at".to_string()],
+                unique: false,
+                index_type: IndexType::BTree,
+                predicate: None,
+                nulls_not_distinct: false,
+                column_options,
+            });
+            schema.tables.insert("events".to_string(), table);
+            schema
+        };
+        let descending = vec![crate::model::IndexColumnOptions::new(
+            None, None, true, None,
+        )];
+
+        assert!(compute_diff(
+            &with_options(descending.clone()),
+            &with_options(descending.clone())
+        )
+        .is_empty());
+        let ops = compute_diff(&with_options(Vec::new()), &with_options(descending));
+        assert_eq!(ops.len(), 2);
+        assert!(
+            matches!(&ops[1], MigrationOp::AddIndex { index, .. } if index.column_options[0].descending)
+        );
+    }
+
+    #[test]
+    fn detects_nulls_not_distinct_change() {
+        let unique = |nulls_not_distinct| UniqueConstraint {
+            name: "users_email_key".to_string(),
+            columns: vec!["email".to_string()],
+            nulls_not_distinct,
+            deferrable: false,
+            initially_deferred: false,
+        };
+        let mut from = empty_schema();
+        let mut from_table = simple_table("users");
+        from_table.unique_constraints.push(unique(false));
+        from.tables.insert("users".to_string(), from_table);
+
+        let mut to = empty_schema();
+        let mut to_table = simple_table("users");
+        to_table.unique_constraints.push(unique(true));
+        to.tables.insert("users".to_string(), to_table);
+
+        let ops = compute_diff(&from, &to);
+        assert_eq!(ops.len(), 2);
+        assert!(ops
+            .iter()
+            .any(|op| matches!(op, MigrationOp::DropUniqueConstraint { .. })));
+        assert!(ops.iter().any(|op| matches!(
+            op,
+            MigrationOp::AddUniqueConstraint { unique_constraint, .. }
+                if unique_constraint.nulls_not_distinct
+        )));
     }
 
     #[test]
@@ -820,6 +1177,8 @@ mod tests {
             referenced_columns: vec!["id".to_string()],
             on_delete: ReferentialAction::Cascade,
             on_update: ReferentialAction::NoAction,
+            deferrable: false,
+            initially_deferred: false,
         });
         to.tables.insert("posts".to_string(), table);
 
@@ -842,6 +1201,8 @@ mod tests {
             referenced_columns: vec!["id".to_string()],
             on_delete: ReferentialAction::Cascade,
             on_update: ReferentialAction::NoAction,
+            deferrable: false,
+            initially_deferred: false,
         });
         from.tables.insert("posts".to_string(), from_table);
 
@@ -855,6 +1216,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn foreign_key_deferral_change_alters_the_constraint() {
+        let foreign_key = |initially_deferred| ForeignKey {
+            name: "posts_user_id_fkey".to_string(),
+            columns: vec!["user_id".to_string()],
+            referenced_table: "users".to_string(),
+            referenced_schema: "public".to_string(),
+            referenced_columns: vec!["id".to_string()],
+            on_delete: ReferentialAction::Cascade,
+            on_update: ReferentialAction::NoAction,
+            deferrable: true,
+            initially_deferred,
+        };
+        let mut from = empty_schema();
+        let mut from_table = simple_table("posts");
+        from_table.foreign_keys.push(foreign_key(false));
+        from.tables.insert("posts".to_string(), from_table);
+        let mut to = empty_schema();
+        let mut to_table = simple_table("posts");
+        to_table.foreign_keys.push(foreign_key(true));
+        to.tables.insert("posts".to_string(), to_table);
+
+        let ops = compute_diff(&from, &to);
+        assert_eq!(
+            ops,
+            vec![MigrationOp::AlterForeignKeyDeferral {
+                table: QualifiedName::new("public", "posts"),
+                foreign_key_name: "posts_user_id_fkey".to_string(),
+                deferrable: true,
+                initially_deferred: true,
+            }]
+        );
+    }
+
     #[test]
     fn detects_added_function() {
         let from = empty_schema();
@@ -1070,8 +1465,8 @@ mod tests {
     }
 
     #[test]
-    fn function_with_changed_default_uses_drop_create() {
-        // PostgreSQL doesn't allow changing parameter defaults via CREATE OR REPLACE.
+    fn function_with_added_default_uses_alter_and_removed_default_uses_drop_create() {
+        // CREATE OR REPLACE may add or change parameter defaults, but not remove them.
         let mut from = empty_schema();
         let func_old = Function {
             name: "my_func".to_string(),
@@ -1123,8 +1518,14 @@ mod tests {
         );
 
         let ops = compute_diff(&from, &to);
+        assert_eq!(ops.len(), 1, "Should have only ALTER operation");
+        assert!(
+            matches!(&ops[0], MigrationOp::AlterFunction { .. }),
+            "Should be AlterFunction, got: {:?}",
+            &ops[0]
+        );
 
-        // Should generate DROP + CREATE
+        let ops = compute_diff(&to, &from);
         assert_eq!(ops.len(), 2, "Should have DROP and CREATE operations");
         assert!(
             matches!(&ops[0], MigrationOp::DropFunction { .. }),
@@ -1138,6 +1539,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn function_with_changed_default_value_uses_alter() {
+        let from = crate::parser::parse_sql_string(
+            "CREATE FUNCTION page(n integer DEFAULT 10) RETURNS integer LANGUAGE sql AS $$ SELECT n $$;",
+        )
+        .unwrap();
+        let to = crate::parser::parse_sql_string(
+            "CREATE FUNCTION page(n integer DEFAULT 50) RETURNS integer LANGUAGE sql AS $$ SELECT n $$;",
+        )
+        .unwrap();
+
+        let ops = compute_diff(&from, &to);
+        assert_eq!(
+            ops.len(),
+            1,
+            "Should have only ALTER operation, got: {ops:?}"
+        );
+        assert!(matches!(&ops[0], MigrationOp::AlterFunction { .. }));
+        assert!(compute_diff(&from, &from).is_empty());
+    }
+
     #[test]
     fn function_with_changed_return_type_uses_drop_create() {
         // PostgreSQL doesn't allow changing RETURNS TABLE column names via CREATE OR REPLACE.
@@ -1219,6 +1641,9 @@ mod tests {
             owner: None,
             grants: Vec::new(),
             comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
         };
         from.views
             .insert(qualified_name(&view.schema, &view.name), view);
@@ -1248,6 +1673,9 @@ mod tests {
                 owner: None,
                 grants: Vec::new(),
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
 
@@ -1270,6 +1698,9 @@ mod tests {
                 owner: None,
                 grants: Vec::new(),
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
         let to = empty_schema();
@@ -1295,6 +1726,9 @@ mod tests {
                 owner: None,
                 grants: Vec::new(),
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
 
@@ -1310,6 +1744,9 @@ mod tests {
                 owner: None,
                 grants: Vec::new(),
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
 
@@ -1320,6 +1757,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn detects_view_option_change_without_replacing_view() {
+        let view = crate::model::View {
+            name: "my_docs".to_string(),
+            schema: "public".to_string(),
+            query: "SELECT * FROM docs".to_string(),
+            materialized: false,
+            owner: None,
+            grants: Vec::new(),
+            comment: None,
+            security_barrier: true,
+            security_invoker: false,
+            check_option: None,
+        };
+        let mut from = empty_schema();
+        from.views
+            .insert("public.my_docs".to_string(), view.clone());
+
+        let mut to = empty_schema();
+        to.views.insert(
+            "public.my_docs".to_string(),
+            crate::model::View {
+                security_barrier: false,
+                security_invoker: true,
+                ..view
+            },
+        );
+
+        let ops = compute_diff(&from, &to);
+        assert_eq!(
+            ops,
+            vec![MigrationOp::AlterViewOptions {
+                name: "public.my_docs".to_string(),
+                changes: ViewOptionChanges {
+                    security_barrier: Some(false),
+                    security_invoker: Some(true),
+                    check_option: None,
+                },
+            }]
+        );
+    }
+
     #[test]
     fn detects_added_materialized_view() {
         let from = empty_schema();
@@ -1335,6 +1814,9 @@ mod tests {
                 owner: None,
                 grants: Vec::new(),
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
 
@@ -1711,6 +2193,7 @@ mod tests {
                 name: "uuid-ossp".to_string(),
                 version: None,
                 schema: None,
+                relocatable: false,
                 comment: None,
             },
         );
@@ -1729,6 +2212,7 @@ mod tests {
                 name: "pgcrypto".to_string(),
                 version: None,
                 schema: None,
+                relocatable: false,
                 comment: None,
             },
         );
@@ -1739,6 +2223,67 @@ mod tests {
         assert!(matches!(&ops[0], MigrationOp::DropExtension(name) if name == "pgcrypto"));
     }
 
+    #[test]
+    fn updates_extension_to_declared_version() {
+        let extension = |version: Option<&str>| {
+            let mut schema = empty_schema();
+            schema.extensions.insert(
+                "postgis".to_string(),
+                crate::model::Extension {
+                    name: "postgis".to_string(),
+                    version: version.map(str::to_string),
+                    schema: None,
+                    relocatable: false,
+                    comment: None,
+                },
+            );
+            schema
+        };
+
+        let ops = compute_diff(&extension(Some("3.3.2")), &extension(Some("3.4.0")));
+        assert_eq!(
+            ops,
+            vec![MigrationOp::UpdateExtension {
+                name: "postgis".to_string(),
+                from_version: Some("3.3.2".to_string()),
+                version: "3.4.0".to_string(),
+            }]
+        );
+        assert!(compute_diff(&extension(Some("3.3.2")), &extension(None)).is_empty());
+    }
+
+    #[test]
+    fn relocates_extension_to_declared_schema() {
+        let extension = |schema: Option<&str>, relocatable| {
+            let mut db = empty_schema();
+            db.extensions.insert(
+                "citext".to_string(),
+                crate::model::Extension {
+                    name: "citext".to_string(),
+                    version: None,
+                    schema: schema.map(str::to_string),
+                    relocatable,
+                    comment: None,
+                },
+            );
+            db
+        };
+
+        let ops = compute_diff(
+            &extension(Some("public"), true),
+            &extension(Some("ext"), false),
+        );
+        assert_eq!(
+            ops,
+            vec![MigrationOp::SetExtensionSchema {
+                name: "citext".to_string(),
+                schema: "ext".to_string(),
+                relocatable: true,
+            }]
+        );
+        assert!(compute_diff(&extension(Some("public"), true), &extension(None, false)).is_empty());
+    }
+
     #[test]
     fn detects_added_extension_comment() {
         let mut from = empty_schema();
@@ -1748,6 +2293,7 @@ mod tests {
                 name: "hstore".to_string(),
                 version: None,
                 schema: None,
+                relocatable: false,
                 comment: None,
             },
         );
@@ -1758,6 +2304,7 @@ mod tests {
                 name: "hstore".to_string(),
                 version: None,
                 schema: None,
+                relocatable: false,
                 comment: Some("key/value store".to_string()),
             },
         );
@@ -1788,6 +2335,7 @@ mod tests {
                 name: "hstore".to_string(),
                 version: None,
                 schema: None,
+                relocatable: false,
                 comment: Some("old".to_string()),
             },
         );
@@ -1798,6 +2346,7 @@ mod tests {
                 name: "hstore".to_string(),
                 version: None,
                 schema: None,
+                relocatable: false,
                 comment: Some("new".to_string()),
             },
         );
@@ -1830,6 +2379,7 @@ mod tests {
                 name: "btree_gist".to_string(),
                 version: None,
                 schema: None,
+                relocatable: false,
                 comment: Some("default postgres comment".to_string()),
             },
         );
@@ -1840,6 +2390,7 @@ mod tests {
                 name: "btree_gist".to_string(),
                 version: None,
                 schema: None,
+                relocatable: false,
                 comment: None,
             },
         );
@@ -1860,6 +2411,7 @@ mod tests {
                 name: "hstore".to_string(),
                 version: None,
                 schema: None,
+                relocatable: false,
                 comment: Some("k/v".to_string()),
             },
         );
@@ -2151,7 +2703,7 @@ mod tests {
 
         let ops = compute_diff(&from, &to);
         assert_eq!(ops.len(), 1);
-        assert!(matches!(&ops[0], MigrationOp::DropSchema(name) if name == "old_schema"));
+        assert!(matches!(&ops[0], MigrationOp::DropSchema { name, .. } if name == "old_schema"));
     }
 
     fn make_trigger(name: &str, target: &str) -> crate::model::Trigger {
@@ -2250,6 +2802,9 @@ mod tests {
                 owner: None,
                 grants: Vec::new(),
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
 
@@ -2348,6 +2903,54 @@ mod tests {
             .any(|op| matches!(op, MigrationOp::DropSequence(n) if n == "public.old_seq")));
     }
 
+    #[test]
+    fn sequence_owned_by_managed_column_is_not_dropped() {
+        let owned_sequence = |name: &str, column: &str| Sequence {
+            name: name.to_string(),
+            schema: "public".to_string(),
+            data_type: SequenceDataType::Integer,
+            start: None,
+            increment: None,
+            min_value: None,
+            max_value: None,
+            cycle: false,
+            cache: None,
+            owned_by: Some(SequenceOwner {
+                table_schema: "public".to_string(),
+                table_name: "users".to_string(),
+                column_name: column.to_string(),
+            }),
+            owner: None,
+            grants: Vec::new(),
+            comment: None,
+        };
+        let mut users = simple_table("users");
+        users
+            .columns
+            .insert("id".to_string(), simple_column("id", PgType::Integer));
+        let mut from = empty_schema();
+        from.tables
+            .insert("public.users".to_string(), users.clone());
+        from.sequences.insert(
+            "public.users_id_seq".to_string(),
+            owned_sequence("users_id_seq", "id"),
+        );
+        from.sequences.insert(
+            "public.users_legacy_seq".to_string(),
+            owned_sequence("users_legacy_seq", "legacy"),
+        );
+        let mut to = empty_schema();
+        to.tables.insert("public.users".to_string(), users);
+
+        let ops = compute_diff(&from, &to);
+        assert!(!ops
+            .iter()
+            .any(|op| matches!(op, MigrationOp::DropSequence(n) if n == "public.users_id_seq")));
+        assert!(ops.iter().any(
+            |op| matches!(op, MigrationOp::DropSequence(n) if n == "public.users_legacy_seq")
+        ));
+    }
+
     #[test]
     fn diff_alter_sequence() {
         let mut from = empty_schema();
@@ -2889,6 +3492,9 @@ mod tests {
                 default: Some("''::character varying".to_string()),
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         from.tables.insert("public.users".to_string(), from_table);
@@ -2904,6 +3510,9 @@ mod tests {
                 default: Some("''::character VARYING".to_string()),
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         to.tables.insert("public.users".to_string(), to_table);
@@ -2928,6 +3537,9 @@ mod tests {
                 default: Some("NULL::character varying".to_string()),
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         from.tables.insert("public.users".to_string(), from_table);
@@ -2943,6 +3555,9 @@ mod tests {
                 default: Some("NULL::character VARYING".to_string()),
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         to.tables.insert("public.users".to_string(), to_table);
@@ -3115,6 +3730,29 @@ CREATE TRIGGER "on_auth_user_created" AFTER INSERT ON "auth"."users" FOR EACH RO
         );
     }
 
+    #[test]
+    fn trigger_arguments_compare_by_value() {
+        use crate::parser::parse_sql_string;
+
+        let parsed = parse_sql_string(
+            "CREATE TRIGGER touch BEFORE UPDATE ON public.docs
+                 FOR EACH ROW EXECUTE PROCEDURE public.set_updated_at(updated_at, 30);",
+        )
+        .unwrap();
+        let parsed_trigger = parsed.triggers.get("public.docs.touch").unwrap();
+        let db_trigger = crate::model::Trigger {
+            function_args: vec!["'updated_at'".to_string(), "'30'".to_string()],
+            ..parsed_trigger.clone()
+        };
+        assert!(triggers_semantically_equal(&db_trigger, parsed_trigger));
+
+        let changed = crate::model::Trigger {
+            function_args: vec!["'created_at'".to_string(), "'30'".to_string()],
+            ..parsed_trigger.clone()
+        };
+        assert!(!triggers_semantically_equal(&changed, parsed_trigger));
+    }
+
     #[test]
     fn multiple_triggers_across_schemas_match() {
         // Reproduces the exact bug report scenario with 3 triggers across 2 schemas
@@ -3392,6 +4030,9 @@ CREATE TRIGGER "on_user_role_change" AFTER INSERT OR UPDATE OR DELETE ON "public
                 owner: Some("oldowner".to_string()),
                 grants: Vec::new(),
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
 
@@ -3406,6 +4047,9 @@ CREATE TRIGGER "on_user_role_change" AFTER INSERT OR UPDATE OR DELETE ON "public
                 owner: Some("newowner".to_string()),
                 grants: Vec::new(),
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
 
@@ -4112,6 +4756,9 @@ CREATE TRIGGER "on_user_role_change" AFTER INSERT OR UPDATE OR DELETE ON "public
                 owner: Some("oldowner".to_string()),
                 grants: Vec::new(),
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
 
@@ -4126,6 +4773,9 @@ CREATE TRIGGER "on_user_role_change" AFTER INSERT OR UPDATE OR DELETE ON "public
                 owner: Some("newowner".to_string()),
                 grants: Vec::new(),
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
 
@@ -4158,6 +4808,9 @@ CREATE TRIGGER "on_user_role_change" AFTER INSERT OR UPDATE OR DELETE ON "public
                 owner: Some("oldowner".to_string()),
                 grants: Vec::new(),
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
 
@@ -4172,6 +4825,9 @@ CREATE TRIGGER "on_user_role_change" AFTER INSERT OR UPDATE OR DELETE ON "public
                 owner: Some("newowner".to_string()),
                 grants: Vec::new(),
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
 
@@ -4251,6 +4907,104 @@ CREATE TRIGGER "on_user_role_change" AFTER INSERT OR UPDATE OR DELETE ON "public
         );
     }
 
+    #[test]
+    fn raw_objects_compare_by_content_hash() {
+        let to = crate::parser::parse_sql_string(
+            "-- pgmold:raw english_search
+             CREATE TEXT SEARCH CONFIGURATION english_search (COPY = english);
+             -- pgmold:end
+             -- pgmold:raw french_search
+             CREATE TEXT SEARCH CONFIGURATION french_search (COPY = french);
+             -- pgmold:end",
+        )
+        .unwrap();
+        let mut from = Schema::new();
+        for (name, raw) in &to.raw_objects {
+            from.raw_objects.insert(
+                name.clone(),
+                crate::model::RawObject {
+                    sql: String::new(),
+                    ..raw.clone()
+                },
+            );
+        }
+        from.raw_objects
+            .get_mut("french_search")
+            .unwrap()
+            .content_hash = "stale".to_string();
+        from.raw_objects.insert(
+            "legacy".to_string(),
+            crate::model::RawObject::new("legacy", "SELECT 1;", Vec::new()),
+        );
+
+        let ops = compute_diff(&from, &to);
+        assert_eq!(ops.len(), 2, "{ops:?}");
+        assert!(ops.iter().any(
+            |op| matches!(op, MigrationOp::ApplyRawObject(raw) if raw.name == "french_search")
+        ));
+        assert!(ops
+            .iter()
+            .any(|op| matches!(op, MigrationOp::DropRawObject(name) if name == "legacy")));
+    }
+
+    #[test]
+    fn unalterable_policy_changes_drop_and_recreate() {
+        use crate::parser::parse_sql_string;
+
+        let from = parse_sql_string(
+            "CREATE TABLE notes (id BIGINT PRIMARY KEY, owner TEXT);
+             ALTER TABLE notes ENABLE ROW LEVEL SECURITY;
+             CREATE POLICY by_command ON notes FOR SELECT USING (owner = current_user);
+             CREATE POLICY by_roles ON notes FOR SELECT TO app_rw USING (true);
+             CREATE POLICY by_check ON notes FOR INSERT WITH CHECK (owner = current_user);
+             CREATE POLICY by_using ON notes FOR ALL TO app_rw USING (true);
+             CREATE POLICY dropped_using ON notes FOR ALL USING (true) WITH CHECK (true);",
+        )
+        .unwrap();
+        let mut to = parse_sql_string(
+            "CREATE TABLE notes (id BIGINT PRIMARY KEY, owner TEXT);
+             ALTER TABLE notes ENABLE ROW LEVEL SECURITY;
+             CREATE POLICY by_command ON notes FOR UPDATE USING (owner = current_user);
+             CREATE POLICY by_roles ON notes FOR SELECT TO app_rw USING (true);
+             CREATE POLICY by_check ON notes FOR INSERT WITH CHECK (true);
+             CREATE POLICY by_using ON notes FOR ALL TO app_ro USING (false);
+             CREATE POLICY dropped_using ON notes FOR ALL WITH CHECK (true);",
+        )
+        .unwrap();
+        let notes = to.tables.get_mut("public.notes").unwrap();
+        notes
+            .policies
+            .iter_mut()
+            .find(|p| p.name == "by_roles")
+            .unwrap()
+            .roles
+            .clear();
+
+        let ops = compute_diff(&from, &to);
+        let recreated: Vec<&str> = ops
+            .iter()
+            .filter_map(|op| match op {
+                MigrationOp::DropPolicy { name, .. } => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(recreated, vec!["by_command", "by_roles", "dropped_using"]);
+        assert_eq!(
+            ops.iter()
+                .filter(|op| matches!(op, MigrationOp::CreatePolicy(_)))
+                .count(),
+            3
+        );
+        let altered: Vec<&str> = ops
+            .iter()
+            .filter_map(|op| match op {
+                MigrationOp::AlterPolicy { name, .. } => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(altered, vec!["by_check", "by_using"]);
+    }
+
     #[test]
     fn drop_column_with_dependent_view_and_policy() {
         use crate::diff::planner::plan_migration;
@@ -4287,6 +5041,9 @@ CREATE TRIGGER "on_user_role_change" AFTER INSERT OR UPDATE OR DELETE ON "public
                 owner: None,
                 grants: vec![],
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
 
@@ -4317,6 +5074,9 @@ CREATE TRIGGER "on_user_role_change" AFTER INSERT OR UPDATE OR DELETE ON "public
                 owner: None,
                 grants: vec![],
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
 
@@ -4424,6 +5184,9 @@ CREATE TRIGGER "on_user_role_change" AFTER INSERT OR UPDATE OR DELETE ON "public
                 owner: None,
                 grants: vec![],
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
         from.views.insert(
@@ -4436,6 +5199,9 @@ CREATE TRIGGER "on_user_role_change" AFTER INSERT OR UPDATE OR DELETE ON "public
                 owner: None,
                 grants: vec![],
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
         from.views.insert(
@@ -4448,6 +5214,9 @@ CREATE TRIGGER "on_user_role_change" AFTER INSERT OR UPDATE OR DELETE ON "public
                 owner: None,
                 grants: vec![],
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
 
@@ -4468,6 +5237,9 @@ CREATE TRIGGER "on_user_role_change" AFTER INSERT OR UPDATE OR DELETE ON "public
                 owner: None,
                 grants: vec![],
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
         to.views.insert(
@@ -4480,6 +5252,9 @@ CREATE TRIGGER "on_user_role_change" AFTER INSERT OR UPDATE OR DELETE ON "public
                 owner: None,
                 grants: vec![],
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
         to.views.insert(
@@ -4492,6 +5267,9 @@ CREATE TRIGGER "on_user_role_change" AFTER INSERT OR UPDATE OR DELETE ON "public
                 owner: None,
                 grants: vec![],
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
 
@@ -4804,6 +5582,9 @@ CREATE TRIGGER "on_user_role_change" AFTER INSERT OR UPDATE OR DELETE ON "public
                 owner: None,
                 grants: vec![],
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
 
@@ -4833,6 +5614,9 @@ CREATE TRIGGER "on_user_role_change" AFTER INSERT OR UPDATE OR DELETE ON "public
                 owner: None,
                 grants: vec![],
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
 
@@ -4866,4 +5650,146 @@ CREATE TRIGGER "on_user_role_change" AFTER INSERT OR UPDATE OR DELETE ON "public
             assert_eq!(view.name, "vcs_project_view");
         }
     }
+
+    #[test]
+    fn statistics_target_changes_alter_only_the_catalog() {
+        use crate::parser::parse_sql_string;
+
+        let from = parse_sql_string("CREATE TABLE events (id BIGINT, kind TEXT);").unwrap();
+        let to = parse_sql_string(
+            "CREATE TABLE events (id BIGINT, kind TEXT);
+ALTER TABLE events ALTER COLUMN kind SET STATISTICS 1000;",
+        )
+        .unwrap();
+
+        let ops = compute_diff(&from, &to);
+        assert_eq!(ops.len(), 1);
+        let MigrationOp::AlterColumn {
+            column, changes, ..
+        } = &ops[0]
+        else {
+            panic!("expected AlterColumn, got {ops:?}");
+        };
+        assert_eq!(column, "kind");
+        assert_eq!(changes.statistics, Some(Some(1000)));
+        assert!(changes.is_catalog_only());
+
+        let reset = compute_diff(&to, &from);
+        assert!(matches!(
+            &reset[0],
+            MigrationOp::AlterColumn { changes, .. } if changes.statistics == Some(None)
+        ));
+    }
+
+    #[test]
+    fn compression_changes_alter_only_the_catalog() {
+        use crate::parser::parse_sql_string;
+
+        let from = parse_sql_string("CREATE TABLE documents (id BIGINT, body TEXT);").unwrap();
+        let to = parse_sql_string(
+            "CREATE TABLE documents (id BIGINT, body TEXT);
+ALTER TABLE documents ALTER COLUMN body SET COMPRESSION lz4;",
+        )
+        .unwrap();
+
+        let ops = compute_diff(&from, &to);
+        assert!(matches!(
+            ops.as_slice(),
+            [MigrationOp::AlterColumn { column, changes, .. }]
+                if column == "body"
+                    && changes.compression == Some(Some("lz4".to_string()))
+                    && changes.is_catalog_only()
+        ));
+    }
+
+    #[test]
+    fn identity_changes_alter_the_column_in_place() {
+        use crate::parser::parse_sql_string;
+
+        let plain = parse_sql_string("CREATE TABLE orders (id BIGINT NOT NULL);").unwrap();
+        let identity = parse_sql_string(
+            "CREATE TABLE orders (id BIGINT GENERATED ALWAYS AS IDENTITY (START WITH 100));",
+        )
+        .unwrap();
+        let by_default =
+            parse_sql_string("CREATE TABLE orders (id BIGINT GENERATED BY DEFAULT AS IDENTITY);")
+                .unwrap();
+
+        let identity_change = |from: &Schema, to: &Schema| match compute_diff(from, to).as_slice() {
+            [MigrationOp::AlterColumn { changes, .. }] => changes.identity.clone(),
+            ops => panic!("expected one AlterColumn, got {ops:?}"),
+        };
+
+        assert!(matches!(
+            identity_change(&plain, &identity),
+            Some(IdentityChange::Add(added)) if added.start == Some(100)
+        ));
+        assert_eq!(
+            identity_change(&identity, &plain),
+            Some(IdentityChange::Drop)
+        );
+        assert!(matches!(
+            identity_change(&identity, &by_default),
+            Some(IdentityChange::Set(set)) if set.generation == IdentityGeneration::ByDefault
+        ));
+
+        // The database reports every sequence option; only declared ones count.
+        let mut introspected = identity.clone();
+        let column = introspected
+            .tables
+            .get_mut("public.orders")
+            .unwrap()
+            .columns
+            .get_mut("id")
+            .unwrap();
+        column.identity.as_mut().unwrap().increment = Some(1);
+        column.identity.as_mut().unwrap().cache = Some(1);
+        assert!(compute_diff(&introspected, &identity).is_empty());
+    }
+
+    #[test]
+    fn replica_identity_changes_are_set_after_their_index() {
+        use crate::parser::parse_sql_string;
+
+        let table = "CREATE TABLE events (id BIGINT PRIMARY KEY, key TEXT NOT NULL);";
+        let plain = parse_sql_string(table).unwrap();
+        let full = parse_sql_string(&format!(
+            "{table} ALTER TABLE events REPLICA IDENTITY FULL;"
+        ))
+        .unwrap();
+        let using_index = parse_sql_string(&format!(
+            "{table} CREATE UNIQUE INDEX events_key_idx ON events (key);
+             ALTER TABLE events REPLICA IDENTITY USING INDEX events_key_idx;"
+        ))
+        .unwrap();
+        let events = QualifiedName::new("public", "events");
+
+        assert_eq!(
+            compute_diff(&plain, &full),
+            vec![MigrationOp::SetReplicaIdentity {
+                table: events.clone(),
+                identity: Some(ReplicaIdentity::Full),
+            }]
+        );
+        assert_eq!(
+            compute_diff(&full, &plain),
+            vec![MigrationOp::SetReplicaIdentity {
+                table: events.clone(),
+                identity: None,
+            }]
+        );
+
+        let planned = crate::diff::planner::plan_migration(compute_diff(&full, &using_index));
+        assert!(matches!(
+            planned.as_slice(),
+            [
+                MigrationOp::AddIndex { .. },
+                MigrationOp::SetReplicaIdentity {
+                    identity: Some(ReplicaIdentity::Index(index)),
+                    ..
+                },
+            ] if index == "events_key_idx"
+        ));
+        assert!(compute_diff(&using_index, &using_index).is_empty());
+    }
 }
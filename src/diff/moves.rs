@@ -0,0 +1,198 @@
+//! Objects the sources move to another schema with `-- pgmold:set-schema`.
+//! Without the hint the diff sees an object dropped from one schema and an
+//! unrelated one created in the other, losing the table's data. With it, the
+//! current schema is relocated first, so the rest of the diff compares the
+//! object with itself, and the move becomes one `ALTER ... SET SCHEMA`.
+
+use crate::model::{parse_qualified_name, qualified_name, PgType, QualifiedName, Schema};
+
+use super::relocate::{relocatable, relocate_table};
+use super::{MigrationOp, OwnerObjectKind};
+
+/// `from` with every object named in `to.schema_moves` moved to its new
+/// schema, along with the operations that move it. Hints whose object is not
+/// [`relocatable`] are ignored. The schema is `None` when `to` has no hints.
+pub(super) fn relocate_moved_objects(
+    from: &Schema,
+    to: &Schema,
+) -> (Option<Schema>, Vec<MigrationOp>) {
+    if to.schema_moves.is_empty() {
+        return (None, Vec::new());
+    }
+    let mut relocated = from.clone();
+    let mut ops = Vec::new();
+
+    for (old, new_schema) in &to.schema_moves {
+        let (schema, name) = parse_qualified_name(old);
+        let new = qualified_name(new_schema, &name);
+        let set_schema = |object_kind, args| MigrationOp::SetSchema {
+            object_kind,
+            schema: schema.clone(),
+            name: name.clone(),
+            args,
+            new_schema: new_schema.clone(),
+        };
+
+        if relocatable(&relocated.tables, &to.tables, old, &new) {
+            relocate_table(
+                &mut relocated,
+                &QualifiedName::new(&schema, &name),
+                &QualifiedName::new(new_schema, &name),
+            );
+            ops.push(set_schema(OwnerObjectKind::Table, None));
+        } else if relocatable(&relocated.views, &to.views, old, &new) {
+            let mut view = relocated.views.remove(old).unwrap();
+            view.schema = new_schema.clone();
+            let kind = if view.materialized {
+                OwnerObjectKind::MaterializedView
+            } else {
+                OwnerObjectKind::View
+            };
+            relocated.views.insert(new, view);
+            ops.push(set_schema(kind, None));
+        } else if relocatable(&relocated.sequences, &to.sequences, old, &new) {
+            let mut sequence = relocated.sequences.remove(old).unwrap();
+            sequence.schema = new_schema.clone();
+            relocated.sequences.insert(new, sequence);
+            ops.push(set_schema(OwnerObjectKind::Sequence, None));
+        } else if relocatable(&relocated.enums, &to.enums, old, &new) {
+            let mut enum_type = relocated.enums.remove(old).unwrap();
+            enum_type.schema = new_schema.clone();
+            relocated.enums.insert(new.clone(), enum_type);
+            retype_columns(&mut relocated, old, &new);
+            ops.push(set_schema(OwnerObjectKind::Type, None));
+        } else if relocatable(&relocated.domains, &to.domains, old, &new) {
+            let mut domain = relocated.domains.remove(old).unwrap();
+            domain.schema = new_schema.clone();
+            relocated.domains.insert(new.clone(), domain);
+            retype_columns(&mut relocated, old, &new);
+            ops.push(set_schema(OwnerObjectKind::Domain, None));
+        } else {
+            let overloads: Vec<String> = relocated
+                .functions
+                .keys()
+                .filter(|key| {
+                    key.strip_prefix(old.as_str())
+                        .is_some_and(|args| args.starts_with('('))
+                })
+                .cloned()
+                .collect();
+            for key in overloads {
+                let new_key = format!("{new}{}", &key[old.len()..]);
+                if !relocatable(&relocated.functions, &to.functions, &key, &new_key) {
+                    continue;
+                }
+                let mut function = relocated.functions.remove(&key).unwrap();
+                function.schema = new_schema.clone();
+                ops.push(set_schema(
+                    OwnerObjectKind::Function,
+                    Some(function.args_string()),
+                ));
+                relocated.functions.insert(new_key, function);
+            }
+        }
+    }
+
+    (Some(relocated), ops)
+}
+
+/// Points columns and domains declared with type `old` at `new`.
+fn retype_columns(schema: &mut Schema, old: &str, new: &str) {
+    fn retype(data_type: &mut PgType, old: &str, new: &str) {
+        match data_type {
+            PgType::UserDefined(name) if name == old => *name = new.to_string(),
+            PgType::Array(inner) => retype(inner, old, new),
+            _ => {}
+        }
+    }
+
+    for table in schema.tables.values_mut() {
+        for column in table.columns.values_mut() {
+            retype(&mut column.data_type, old, new);
+        }
+    }
+    for domain in schema.domains.values_mut() {
+        retype(&mut domain.data_type, old, new);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diff::compute_diff;
+    use crate::diff::planner::plan_migration_checked;
+    use crate::parser::parse_sql_string;
+
+    #[test]
+    fn set_schema_hint_moves_objects_instead_of_recreating_them() {
+        let from = parse_sql_string(
+            "CREATE TYPE public.status AS ENUM ('new', 'done');
+             CREATE TABLE public.orders (id integer PRIMARY KEY, status public.status);
+             CREATE TABLE public.order_items (
+                 order_id integer REFERENCES public.orders (id)
+             );
+             CREATE FUNCTION public.touch(n integer) RETURNS integer
+                 LANGUAGE sql AS $$ SELECT n $$;",
+        )
+        .unwrap();
+        let to = parse_sql_string(
+            "-- pgmold:set-schema public.orders app
+             -- pgmold:set-schema public.status app
+             -- pgmold:set-schema public.touch app
+             CREATE SCHEMA app;
+             CREATE TYPE app.status AS ENUM ('new', 'done');
+             CREATE TABLE app.orders (id integer PRIMARY KEY, status app.status);
+             CREATE TABLE public.order_items (
+                 order_id integer REFERENCES app.orders (id)
+             );
+             CREATE FUNCTION app.touch(n integer) RETURNS integer
+                 LANGUAGE sql AS $$ SELECT n $$;",
+        )
+        .unwrap();
+
+        let ops = plan_migration_checked(compute_diff(&from, &to)).unwrap();
+        let sql = crate::pg::sqlgen::generate_sql(&ops);
+        assert_eq!(
+            sql,
+            vec![
+                "CREATE SCHEMA IF NOT EXISTS \"app\";",
+                "ALTER TABLE \"public\".\"orders\" SET SCHEMA \"app\";",
+                "ALTER TYPE \"public\".\"status\" SET SCHEMA \"app\";",
+                "ALTER FUNCTION \"public\".\"touch\"(integer) SET SCHEMA \"app\";",
+            ],
+            "{ops:?}"
+        );
+    }
+
+    #[test]
+    fn set_schema_hint_moves_owned_sequences_with_their_table() {
+        let from = parse_sql_string("CREATE TABLE public.orders (id serial PRIMARY KEY);").unwrap();
+        let to = parse_sql_string(
+            "-- pgmold:set-schema public.orders app
+             CREATE SCHEMA app;
+             CREATE TABLE app.orders (id serial PRIMARY KEY);",
+        )
+        .unwrap();
+
+        let ops = plan_migration_checked(compute_diff(&from, &to)).unwrap();
+        let sql = crate::pg::sqlgen::generate_sql(&ops);
+        assert_eq!(
+            sql,
+            vec![
+                "CREATE SCHEMA IF NOT EXISTS \"app\";",
+                "ALTER TABLE \"public\".\"orders\" SET SCHEMA \"app\";",
+            ],
+            "{ops:?}"
+        );
+    }
+
+    #[test]
+    fn applied_move_is_not_repeated() {
+        let schema = parse_sql_string(
+            "-- pgmold:set-schema public.orders app
+             CREATE SCHEMA app;
+             CREATE TABLE app.orders (id integer);",
+        )
+        .unwrap();
+        assert!(compute_diff(&schema, &schema).is_empty());
+    }
+}
@@ -8,7 +8,7 @@ use crate::util::optional_expressions_equal;
 use super::grants::{create_grants_for_new_object, diff_grants_for_object};
 use super::{
     DiffOptions, DomainChanges, EnumValuePosition, GrantObjectKind, MigrationOp, OwnerObjectKind,
-    SequenceChanges,
+    SequenceChanges, ViewOptionChanges,
 };
 
 fn emit_ownership_change(
@@ -17,15 +17,16 @@ fn emit_ownership_change(
     from_owner: &Option<String>,
     to_owner: &Option<String>,
     object_kind: OwnerObjectKind,
-    coords: &ObjectCoords,
+    coords: impl Fn() -> ObjectCoords,
 ) {
     if options.manage_ownership && from_owner != to_owner {
         if let Some(new_owner) = to_owner {
+            let coords = coords();
             ops.push(MigrationOp::AlterOwner {
                 object_kind,
-                schema: coords.schema.clone(),
-                name: coords.name.clone(),
-                args: coords.args.clone(),
+                schema: coords.schema,
+                name: coords.name,
+                args: coords.args,
                 new_owner: new_owner.clone(),
             });
         }
@@ -38,9 +39,10 @@ fn emit_grants_diff(
     from_grants: &[Grant],
     to_grants: &[Grant],
     object_kind: GrantObjectKind,
-    coords: &ObjectCoords,
+    coords: impl Fn() -> ObjectCoords,
 ) {
-    if options.manage_grants {
+    if options.manage_grants && from_grants != to_grants {
+        let coords = coords();
         ops.extend(diff_grants_for_object(
             from_grants,
             to_grants,
@@ -58,9 +60,10 @@ fn emit_grants_for_new_object(
     options: &DiffOptions,
     grants: &[Grant],
     object_kind: GrantObjectKind,
-    coords: &ObjectCoords,
+    coords: impl Fn() -> ObjectCoords,
 ) {
-    if options.manage_grants {
+    if options.manage_grants && !grants.is_empty() {
+        let coords = coords();
         ops.extend(create_grants_for_new_object(
             grants,
             object_kind,
@@ -113,7 +116,8 @@ fn diff_objects<K, V, FCreate, FUpdate, FDrop, FCoords, FOwnerKind, FOwner, FGra
     FGrants: Fn(&V) -> &[Grant],
 {
     for (key, to_val) in to {
-        let c = coords(key.as_ref(), to_val);
+        // Built only when an ownership or grant op is emitted.
+        let c = || coords(key.as_ref(), to_val);
         if let Some(from_val) = from.get(key) {
             on_update(ops, key, from_val, to_val);
             if let Some(owner_kind) = get_owner_kind(to_val) {
@@ -123,7 +127,7 @@ fn diff_objects<K, V, FCreate, FUpdate, FDrop, FCoords, FOwnerKind, FOwner, FGra
                     get_owner(from_val),
                     get_owner(to_val),
                     owner_kind,
-                    &c,
+                    c,
                 );
             }
             if let Some(gk) = grant_kind {
@@ -133,16 +137,16 @@ fn diff_objects<K, V, FCreate, FUpdate, FDrop, FCoords, FOwnerKind, FOwner, FGra
                     get_grants(from_val),
                     get_grants(to_val),
                     gk,
-                    &c,
+                    c,
                 );
             }
         } else {
             ops.push(on_create(key, to_val));
             if let Some(owner_kind) = get_owner_kind(to_val) {
-                emit_ownership_change(ops, options, &None, get_owner(to_val), owner_kind, &c);
+                emit_ownership_change(ops, options, &None, get_owner(to_val), owner_kind, c);
             }
             if let Some(gk) = grant_kind {
-                emit_grants_for_new_object(ops, options, get_grants(to_val), gk, &c);
+                emit_grants_for_new_object(ops, options, get_grants(to_val), gk, c);
             }
         }
     }
@@ -173,7 +177,10 @@ pub(super) fn diff_schemas(from: &Schema, to: &Schema, options: &DiffOptions) ->
         &to.schemas,
         |_key, pg_schema| MigrationOp::CreateSchema(pg_schema.clone()),
         |_ops, _key, _from_val, _to_val| {},
-        |name, _val| MigrationOp::DropSchema(name.clone()),
+        |name, _val| MigrationOp::DropSchema {
+            name: name.clone(),
+            cascade: false,
+        },
         |name, _val| ObjectCoords {
             schema: name.to_string(),
             name: name.to_string(),
@@ -209,6 +216,27 @@ pub(super) fn diff_servers(from: &Schema, to: &Schema, _options: &DiffOptions) -
     ops
 }
 
+/// Raw objects are compared by content hash only: the database records
+/// nothing else about them.
+pub(super) fn diff_raw_objects(from: &Schema, to: &Schema) -> Vec<MigrationOp> {
+    let mut ops = Vec::new();
+    for (name, to_raw) in &to.raw_objects {
+        let unchanged = from
+            .raw_objects
+            .get(name)
+            .is_some_and(|from_raw| from_raw.content_hash == to_raw.content_hash);
+        if !unchanged {
+            ops.push(MigrationOp::ApplyRawObject(to_raw.clone()));
+        }
+    }
+    for name in from.raw_objects.keys() {
+        if !to.raw_objects.contains_key(name) {
+            ops.push(MigrationOp::DropRawObject(name.clone()));
+        }
+    }
+    ops
+}
+
 fn servers_differ_ignoring_unmanaged(from: &Server, to: &Server) -> bool {
     let from_normalized = Server {
         owner: if to.owner.is_some() {
@@ -238,7 +266,27 @@ pub(super) fn diff_extensions(
         &from.extensions,
         &to.extensions,
         |_key, ext| MigrationOp::CreateExtension(ext.clone()),
-        |_ops, _key, _from_val, _to_val| {},
+        |ops, name, from_ext, to_ext| {
+            // An undeclared schema or version accepts whatever is installed.
+            if let Some(schema) = &to_ext.schema {
+                if from_ext.schema.as_ref() != Some(schema) {
+                    ops.push(MigrationOp::SetExtensionSchema {
+                        name: name.clone(),
+                        schema: schema.clone(),
+                        relocatable: from_ext.relocatable,
+                    });
+                }
+            }
+            if let Some(version) = &to_ext.version {
+                if from_ext.version.as_ref() != Some(version) {
+                    ops.push(MigrationOp::UpdateExtension {
+                        name: name.clone(),
+                        from_version: from_ext.version.clone(),
+                        version: version.clone(),
+                    });
+                }
+            }
+        },
         |name, _val| MigrationOp::DropExtension(name.clone()),
         |name, _val| ObjectCoords {
             schema: String::new(),
@@ -478,10 +526,26 @@ pub(super) fn diff_views(from: &Schema, to: &Schema, options: &DiffOptions) -> V
         |_key, view| MigrationOp::CreateView(view.clone()),
         |ops, _key, from_view, to_view| {
             if !from_view.semantically_equals(to_view) {
+                // CREATE OR REPLACE VIEW replaces the view's options too.
                 ops.push(MigrationOp::AlterView {
                     name: qualified_name(&to_view.schema, &to_view.name),
                     new_view: to_view.clone(),
                 });
+            } else if !to_view.materialized {
+                let changes = ViewOptionChanges {
+                    security_barrier: (from_view.security_barrier != to_view.security_barrier)
+                        .then_some(to_view.security_barrier),
+                    security_invoker: (from_view.security_invoker != to_view.security_invoker)
+                        .then_some(to_view.security_invoker),
+                    check_option: (from_view.check_option != to_view.check_option)
+                        .then_some(to_view.check_option),
+                };
+                if changes.has_changes() {
+                    ops.push(MigrationOp::AlterViewOptions {
+                        name: qualified_name(&to_view.schema, &to_view.name),
+                        changes,
+                    });
+                }
             }
         },
         |_key, view| MigrationOp::DropView {
@@ -550,7 +614,7 @@ fn triggers_equal_except_enabled(from: &Trigger, to: &Trigger) -> bool {
         && optional_expressions_equal(&from.when_clause, &to.when_clause)
         && from.function_schema == to.function_schema
         && from.function_name == to.function_name
-        && from.function_args == to.function_args
+        && from.argument_values() == to.argument_values()
         && from.old_table_name == to.old_table_name
         && from.new_table_name == to.new_table_name
         && from.is_constraint == to.is_constraint
@@ -571,11 +635,22 @@ pub(super) fn diff_sequences(
     to: &Schema,
     options: &DiffOptions,
 ) -> Vec<MigrationOp> {
+    // Sequences owned by a serial or identity column exist only as a side
+    // effect of that column; while the column is managed they are not drops.
+    let from_sequences: BTreeMap<String, Sequence> = from
+        .sequences
+        .iter()
+        .filter(|(key, sequence)| {
+            to.sequences.contains_key(*key) || !is_owned_by_managed_column(sequence, to)
+        })
+        .map(|(key, sequence)| (key.clone(), sequence.clone()))
+        .collect();
+
     let mut ops = Vec::new();
     diff_objects(
         &mut ops,
         options,
-        &from.sequences,
+        &from_sequences,
         &to.sequences,
         |_key, to_seq| MigrationOp::CreateSequence(to_seq.clone()),
         |ops, name, from_seq, to_seq| {
@@ -596,6 +671,14 @@ pub(super) fn diff_sequences(
     ops
 }
 
+fn is_owned_by_managed_column(sequence: &Sequence, to: &Schema) -> bool {
+    sequence.owned_by.as_ref().is_some_and(|owner| {
+        to.tables
+            .get(&qualified_name(&owner.table_schema, &owner.table_name))
+            .is_some_and(|table| table.columns.contains_key(&owner.column_name))
+    })
+}
+
 pub(super) fn compute_sequence_changes(from: &Sequence, to: &Sequence) -> Option<SequenceChanges> {
     let changes = SequenceChanges {
         data_type: (from.data_type != to.data_type).then(|| to.data_type.clone()),
@@ -1,5 +1,5 @@
 use super::{CommentObjectType, GrantObjectKind, MigrationOp, OwnerObjectKind};
-use crate::model::{qualified_name, QualifiedName};
+use crate::model::{parse_qualified_name, qualified_name, QualifiedName};
 use crate::parser::extract_table_references;
 use std::collections::HashSet;
 
@@ -35,6 +35,8 @@ pub(crate) enum OpKey {
     DropSchema(String),
     CreateExtension(String),
     DropExtension(String),
+    UpdateExtension(String),
+    SetExtensionSchema(String),
     CreateServer(String),
     DropServer(String),
     AlterServer(String),
@@ -85,6 +87,14 @@ pub(crate) enum OpKey {
         table: QualifiedName,
         name: String,
     },
+    AlterForeignKey {
+        table: QualifiedName,
+        name: String,
+    },
+    AddUniqueConstraintUsingIndex {
+        table: QualifiedName,
+        name: String,
+    },
     AddCheckConstraint {
         table: QualifiedName,
         name: String,
@@ -113,6 +123,9 @@ pub(crate) enum OpKey {
     NoForceRls {
         table: QualifiedName,
     },
+    SetReplicaIdentity {
+        table: QualifiedName,
+    },
     CreatePolicy {
         table: QualifiedName,
         name: String,
@@ -148,6 +161,8 @@ pub(crate) enum OpKey {
     CreateView(String),
     DropView(String),
     AlterView(String),
+    AlterViewOptions(String),
+    RefreshMaterializedView(String),
     CreateTrigger {
         target: QualifiedName,
         name: String,
@@ -163,11 +178,24 @@ pub(crate) enum OpKey {
     CreateSequence(String),
     DropSequence(String),
     AlterSequence(String),
+    SetSequenceValue(String),
     AlterOwner {
         object_kind: OwnerObjectKind,
         schema: String,
         name: String,
     },
+    SetSchema {
+        object_kind: OwnerObjectKind,
+        schema: String,
+        name: String,
+        args: Option<String>,
+    },
+    RenameTable(QualifiedName),
+    RenameSequence(QualifiedName),
+    RenameColumn {
+        table: QualifiedName,
+        column: String,
+    },
     BackfillHint {
         table: QualifiedName,
         column: String,
@@ -215,6 +243,8 @@ pub(crate) enum OpKey {
         version_schema: String,
         name: String,
     },
+    ApplyRawObject(String),
+    DropRawObject(String),
     SetComment {
         object_type: CommentObjectType,
         schema: String,
@@ -234,9 +264,11 @@ impl OpKey {
     pub(crate) fn from_op(op: &MigrationOp) -> Self {
         match op {
             MigrationOp::CreateSchema(s) => OpKey::CreateSchema(s.name.clone()),
-            MigrationOp::DropSchema(name) => OpKey::DropSchema(name.clone()),
+            MigrationOp::DropSchema { name, .. } => OpKey::DropSchema(name.clone()),
             MigrationOp::CreateExtension(ext) => OpKey::CreateExtension(ext.name.clone()),
             MigrationOp::DropExtension(name) => OpKey::DropExtension(name.clone()),
+            MigrationOp::UpdateExtension { name, .. } => OpKey::UpdateExtension(name.clone()),
+            MigrationOp::SetExtensionSchema { name, .. } => OpKey::SetExtensionSchema(name.clone()),
             MigrationOp::CreateServer(s) => OpKey::CreateServer(s.name.clone()),
             MigrationOp::DropServer(name) => OpKey::DropServer(name.clone()),
             MigrationOp::AlterServer { name, .. } => OpKey::AlterServer(name.clone()),
@@ -279,10 +311,21 @@ impl OpKey {
                 table: table.clone(),
                 name: index.name.clone(),
             },
-            MigrationOp::DropIndex { table, index_name } => OpKey::DropIndex {
+            MigrationOp::DropIndex { table, index_name }
+            | MigrationOp::DropIndexConcurrently { table, index_name } => OpKey::DropIndex {
                 table: table.clone(),
                 name: index_name.clone(),
             },
+            // AddUniqueConstraint maps to OpKey::AddIndex intentionally: the
+            // constraint builds an index of the same name, and needs the same
+            // ordering (after its table and columns, before foreign keys).
+            MigrationOp::AddUniqueConstraint {
+                table,
+                unique_constraint,
+            } => OpKey::AddIndex {
+                table: table.clone(),
+                name: unique_constraint.name.clone(),
+            },
             // DropUniqueConstraint maps to OpKey::DropIndex intentionally:
             // both need identical ordering (run before DropTable/DropColumn,
             // after AddIndex in replace-in-place scenarios).
@@ -304,6 +347,14 @@ impl OpKey {
                 table: table.clone(),
                 name: foreign_key_name.clone(),
             },
+            MigrationOp::AlterForeignKeyDeferral {
+                table,
+                foreign_key_name,
+                ..
+            } => OpKey::AlterForeignKey {
+                table: table.clone(),
+                name: foreign_key_name.clone(),
+            },
             MigrationOp::AddCheckConstraint {
                 table,
                 check_constraint,
@@ -344,6 +395,9 @@ impl OpKey {
             MigrationOp::NoForceRls { table } => OpKey::NoForceRls {
                 table: table.clone(),
             },
+            MigrationOp::SetReplicaIdentity { table, .. } => OpKey::SetReplicaIdentity {
+                table: table.clone(),
+            },
             MigrationOp::CreatePolicy(p) => OpKey::CreatePolicy {
                 table: QualifiedName::new(&p.table_schema, &p.table),
                 name: p.name.clone(),
@@ -379,6 +433,10 @@ impl OpKey {
             MigrationOp::CreateView(v) => OpKey::CreateView(qualified_name(&v.schema, &v.name)),
             MigrationOp::DropView { name, .. } => OpKey::DropView(name.clone()),
             MigrationOp::AlterView { name, .. } => OpKey::AlterView(name.clone()),
+            MigrationOp::AlterViewOptions { name, .. } => OpKey::AlterViewOptions(name.clone()),
+            MigrationOp::RefreshMaterializedView { name } => {
+                OpKey::RefreshMaterializedView(name.clone())
+            }
             MigrationOp::CreateTrigger(t) => OpKey::CreateTrigger {
                 target: QualifiedName::new(&t.target_schema, &t.target_name),
                 name: t.name.clone(),
@@ -405,6 +463,7 @@ impl OpKey {
             }
             MigrationOp::DropSequence(name) => OpKey::DropSequence(name.clone()),
             MigrationOp::AlterSequence { name, .. } => OpKey::AlterSequence(name.clone()),
+            MigrationOp::SetSequenceValue { name, .. } => OpKey::SetSequenceValue(name.clone()),
             MigrationOp::AlterOwner {
                 object_kind,
                 schema,
@@ -415,6 +474,24 @@ impl OpKey {
                 schema: schema.clone(),
                 name: name.clone(),
             },
+            MigrationOp::SetSchema {
+                object_kind,
+                schema,
+                name,
+                args,
+                ..
+            } => OpKey::SetSchema {
+                object_kind: *object_kind,
+                schema: schema.clone(),
+                name: name.clone(),
+                args: args.clone(),
+            },
+            MigrationOp::RenameTable { table, .. } => OpKey::RenameTable(table.clone()),
+            MigrationOp::RenameSequence { sequence, .. } => OpKey::RenameSequence(sequence.clone()),
+            MigrationOp::RenameColumn { table, column, .. } => OpKey::RenameColumn {
+                table: table.clone(),
+                column: column.clone(),
+            },
             MigrationOp::BackfillHint { table, column, .. } => OpKey::BackfillHint {
                 table: table.clone(),
                 column: column.clone(),
@@ -423,6 +500,19 @@ impl OpKey {
                 table: table.clone(),
                 column: column.clone(),
             },
+            MigrationOp::CreateIndexConcurrently { table, index } => OpKey::AddIndex {
+                table: table.clone(),
+                name: index.name.clone(),
+            },
+            MigrationOp::AddPrimaryKeyUsingIndex { table, .. } => OpKey::AddPrimaryKey {
+                table: table.clone(),
+            },
+            MigrationOp::AddUniqueConstraintUsingIndex {
+                table, index_name, ..
+            } => OpKey::AddUniqueConstraintUsingIndex {
+                table: table.clone(),
+                name: index_name.clone(),
+            },
             MigrationOp::GrantPrivileges {
                 object_kind,
                 schema,
@@ -488,6 +578,8 @@ impl OpKey {
                 version_schema: version_schema.clone(),
                 name: name.clone(),
             },
+            MigrationOp::ApplyRawObject(raw) => OpKey::ApplyRawObject(raw.name.clone()),
+            MigrationOp::DropRawObject(name) => OpKey::DropRawObject(name.clone()),
             MigrationOp::SetComment {
                 object_type,
                 schema,
@@ -508,6 +600,469 @@ impl OpKey {
             },
         }
     }
+
+    /// Qualified name of the object this operation belongs to. Table-scoped
+    /// operations (columns, constraints, indexes, policies, triggers) report
+    /// their table. Returns `None` for operations not tied to one object.
+    pub(crate) fn subject(&self) -> Option<String> {
+        match self {
+            OpKey::CreateSchema(name)
+            | OpKey::DropSchema(name)
+            | OpKey::CreateExtension(name)
+            | OpKey::DropExtension(name)
+            | OpKey::UpdateExtension(name)
+            | OpKey::SetExtensionSchema(name)
+            | OpKey::CreateServer(name)
+            | OpKey::DropServer(name)
+            | OpKey::AlterServer(name)
+            | OpKey::CreateEnum(name)
+            | OpKey::DropEnum(name)
+            | OpKey::AddEnumValue {
+                enum_name: name, ..
+            }
+            | OpKey::CreateDomain(name)
+            | OpKey::DropDomain(name)
+            | OpKey::AlterDomain(name)
+            | OpKey::CreateTable(name)
+            | OpKey::DropTable(name)
+            | OpKey::CreatePartition(name)
+            | OpKey::DropPartition(name)
+            | OpKey::CreateFunction { name, .. }
+            | OpKey::DropFunction { name, .. }
+            | OpKey::AlterFunction { name, .. }
+            | OpKey::CreateAggregate { name, .. }
+            | OpKey::DropAggregate { name, .. }
+            | OpKey::CreateView(name)
+            | OpKey::DropView(name)
+            | OpKey::AlterView(name)
+            | OpKey::AlterViewOptions(name)
+            | OpKey::RefreshMaterializedView(name)
+            | OpKey::CreateSequence(name)
+            | OpKey::DropSequence(name)
+            | OpKey::AlterSequence(name)
+            | OpKey::SetSequenceValue(name) => Some(name.clone()),
+            OpKey::AddColumn { table, .. }
+            | OpKey::DropColumn { table, .. }
+            | OpKey::AlterColumn { table, .. }
+            | OpKey::AddPrimaryKey { table }
+            | OpKey::DropPrimaryKey { table }
+            | OpKey::AddIndex { table, .. }
+            | OpKey::DropIndex { table, .. }
+            | OpKey::AddForeignKey { table, .. }
+            | OpKey::DropForeignKey { table, .. }
+            | OpKey::AlterForeignKey { table, .. }
+            | OpKey::AddUniqueConstraintUsingIndex { table, .. }
+            | OpKey::AddCheckConstraint { table, .. }
+            | OpKey::DropCheckConstraint { table, .. }
+            | OpKey::AddExclusionConstraint { table, .. }
+            | OpKey::DropExclusionConstraint { table, .. }
+            | OpKey::EnableRls { table }
+            | OpKey::DisableRls { table }
+            | OpKey::ForceRls { table }
+            | OpKey::NoForceRls { table }
+            | OpKey::SetReplicaIdentity { table }
+            | OpKey::CreatePolicy { table, .. }
+            | OpKey::DropPolicy { table, .. }
+            | OpKey::AlterPolicy { table, .. }
+            | OpKey::BackfillHint { table, .. }
+            | OpKey::SetColumnNotNull { table, .. }
+            | OpKey::RenameTable(table)
+            | OpKey::RenameSequence(table)
+            | OpKey::RenameColumn { table, .. }
+            | OpKey::CreateTrigger { target: table, .. }
+            | OpKey::DropTrigger { target: table, .. }
+            | OpKey::AlterTriggerEnabled { target: table, .. } => Some(table.to_string()),
+            OpKey::AlterOwner { schema, name, .. }
+            | OpKey::SetSchema { schema, name, .. }
+            | OpKey::GrantPrivileges { schema, name, .. }
+            | OpKey::RevokePrivileges { schema, name, .. }
+            | OpKey::SetComment { schema, name, .. } => Some(qualified_name(schema, name)),
+            OpKey::CreateVersionView {
+                version_schema,
+                name,
+            }
+            | OpKey::DropVersionView {
+                version_schema,
+                name,
+            } => Some(qualified_name(version_schema, name)),
+            OpKey::AlterDefaultPrivileges { .. }
+            | OpKey::CreateVersionSchema { .. }
+            | OpKey::DropVersionSchema { .. }
+            | OpKey::ApplyRawObject(_)
+            | OpKey::DropRawObject(_) => None,
+        }
+    }
+
+    /// Like `subject`, but naming the column, constraint, index, policy or
+    /// trigger a table-scoped operation touches rather than only its table.
+    pub(crate) fn object(&self) -> Option<String> {
+        match self {
+            OpKey::AddColumn { table, column }
+            | OpKey::DropColumn { table, column }
+            | OpKey::AlterColumn { table, column }
+            | OpKey::BackfillHint { table, column }
+            | OpKey::SetColumnNotNull { table, column }
+            | OpKey::RenameColumn { table, column } => Some(format!("{table}.{column}")),
+            OpKey::AddIndex { table, name }
+            | OpKey::DropIndex { table, name }
+            | OpKey::AddForeignKey { table, name }
+            | OpKey::DropForeignKey { table, name }
+            | OpKey::AlterForeignKey { table, name }
+            | OpKey::AddUniqueConstraintUsingIndex { table, name }
+            | OpKey::AddCheckConstraint { table, name }
+            | OpKey::DropCheckConstraint { table, name }
+            | OpKey::AddExclusionConstraint { table, name }
+            | OpKey::DropExclusionConstraint { table, name }
+            | OpKey::CreatePolicy { table, name }
+            | OpKey::DropPolicy { table, name }
+            | OpKey::AlterPolicy { table, name }
+            | OpKey::CreateTrigger {
+                target: table,
+                name,
+            }
+            | OpKey::DropTrigger {
+                target: table,
+                name,
+            }
+            | OpKey::AlterTriggerEnabled {
+                target: table,
+                name,
+            } => Some(format!("{table}.{name}")),
+            _ => self.subject(),
+        }
+    }
+
+    /// PostgreSQL schema the operation belongs to. None for database-wide
+    /// objects (extensions, foreign servers, global default privileges).
+    pub(crate) fn schema(&self) -> Option<String> {
+        match self {
+            OpKey::CreateSchema(name) | OpKey::DropSchema(name) => Some(name.clone()),
+            OpKey::CreateExtension(_)
+            | OpKey::DropExtension(_)
+            | OpKey::UpdateExtension(_)
+            | OpKey::SetExtensionSchema(_)
+            | OpKey::CreateServer(_)
+            | OpKey::DropServer(_)
+            | OpKey::AlterServer(_) => None,
+            OpKey::AlterDefaultPrivileges { schema, .. } => schema.clone(),
+            OpKey::CreateVersionSchema { base_schema, .. }
+            | OpKey::DropVersionSchema { base_schema, .. } => Some(base_schema.clone()),
+            _ => self
+                .subject()
+                .map(|subject| parse_qualified_name(&subject).0),
+        }
+    }
+
+    /// Name of the operation kind, such as `AddColumn`.
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            OpKey::CreateSchema(_) => "CreateSchema",
+            OpKey::DropSchema(_) => "DropSchema",
+            OpKey::CreateExtension(_) => "CreateExtension",
+            OpKey::DropExtension(_) => "DropExtension",
+            OpKey::UpdateExtension(_) => "UpdateExtension",
+            OpKey::SetExtensionSchema(_) => "SetExtensionSchema",
+            OpKey::CreateServer(_) => "CreateServer",
+            OpKey::DropServer(_) => "DropServer",
+            OpKey::AlterServer(_) => "AlterServer",
+            OpKey::CreateEnum(_) => "CreateEnum",
+            OpKey::DropEnum(_) => "DropEnum",
+            OpKey::AddEnumValue { .. } => "AddEnumValue",
+            OpKey::CreateDomain(_) => "CreateDomain",
+            OpKey::DropDomain(_) => "DropDomain",
+            OpKey::AlterDomain(_) => "AlterDomain",
+            OpKey::CreateTable(_) => "CreateTable",
+            OpKey::DropTable(_) => "DropTable",
+            OpKey::CreatePartition(_) => "CreatePartition",
+            OpKey::DropPartition(_) => "DropPartition",
+            OpKey::AddColumn { .. } => "AddColumn",
+            OpKey::DropColumn { .. } => "DropColumn",
+            OpKey::AlterColumn { .. } => "AlterColumn",
+            OpKey::AddPrimaryKey { .. } => "AddPrimaryKey",
+            OpKey::DropPrimaryKey { .. } => "DropPrimaryKey",
+            OpKey::AddIndex { .. } => "AddIndex",
+            OpKey::DropIndex { .. } => "DropIndex",
+            OpKey::AddForeignKey { .. } => "AddForeignKey",
+            OpKey::DropForeignKey { .. } => "DropForeignKey",
+            OpKey::AlterForeignKey { .. } => "AlterForeignKey",
+            OpKey::AddUniqueConstraintUsingIndex { .. } => "AddUniqueConstraintUsingIndex",
+            OpKey::AddCheckConstraint { .. } => "AddCheckConstraint",
+            OpKey::DropCheckConstraint { .. } => "DropCheckConstraint",
+            OpKey::AddExclusionConstraint { .. } => "AddExclusionConstraint",
+            OpKey::DropExclusionConstraint { .. } => "DropExclusionConstraint",
+            OpKey::EnableRls { .. } => "EnableRls",
+            OpKey::DisableRls { .. } => "DisableRls",
+            OpKey::ForceRls { .. } => "ForceRls",
+            OpKey::NoForceRls { .. } => "NoForceRls",
+            OpKey::SetReplicaIdentity { .. } => "SetReplicaIdentity",
+            OpKey::CreatePolicy { .. } => "CreatePolicy",
+            OpKey::DropPolicy { .. } => "DropPolicy",
+            OpKey::AlterPolicy { .. } => "AlterPolicy",
+            OpKey::CreateFunction { .. } => "CreateFunction",
+            OpKey::DropFunction { .. } => "DropFunction",
+            OpKey::AlterFunction { .. } => "AlterFunction",
+            OpKey::CreateAggregate { .. } => "CreateAggregate",
+            OpKey::DropAggregate { .. } => "DropAggregate",
+            OpKey::CreateView(_) => "CreateView",
+            OpKey::DropView(_) => "DropView",
+            OpKey::AlterView(_) => "AlterView",
+            OpKey::AlterViewOptions(_) => "AlterViewOptions",
+            OpKey::RefreshMaterializedView(_) => "RefreshMaterializedView",
+            OpKey::CreateTrigger { .. } => "CreateTrigger",
+            OpKey::DropTrigger { .. } => "DropTrigger",
+            OpKey::AlterTriggerEnabled { .. } => "AlterTriggerEnabled",
+            OpKey::CreateSequence(_) => "CreateSequence",
+            OpKey::DropSequence(_) => "DropSequence",
+            OpKey::AlterSequence(_) => "AlterSequence",
+            OpKey::SetSequenceValue(_) => "SetSequenceValue",
+            OpKey::AlterOwner { .. } => "AlterOwner",
+            OpKey::SetSchema { .. } => "SetSchema",
+            OpKey::RenameTable(_) => "RenameTable",
+            OpKey::RenameSequence(_) => "RenameSequence",
+            OpKey::RenameColumn { .. } => "RenameColumn",
+            OpKey::BackfillHint { .. } => "BackfillHint",
+            OpKey::SetColumnNotNull { .. } => "SetColumnNotNull",
+            OpKey::GrantPrivileges { .. } => "GrantPrivileges",
+            OpKey::RevokePrivileges { .. } => "RevokePrivileges",
+            OpKey::AlterDefaultPrivileges { .. } => "AlterDefaultPrivileges",
+            OpKey::CreateVersionSchema { .. } => "CreateVersionSchema",
+            OpKey::DropVersionSchema { .. } => "DropVersionSchema",
+            OpKey::CreateVersionView { .. } => "CreateVersionView",
+            OpKey::DropVersionView { .. } => "DropVersionView",
+            OpKey::ApplyRawObject(_) => "ApplyRawObject",
+            OpKey::DropRawObject(_) => "DropRawObject",
+            OpKey::SetComment { .. } => "SetComment",
+        }
+    }
+
+    /// Identity that stays the same across plans and pgmold versions, as
+    /// `kind:schema.name`, followed by `:detail` for whatever else tells
+    /// operations on that object apart, such as a grantee.
+    pub(crate) fn identity(&self) -> String {
+        let kind = self.kind();
+        let with_args = |name: &str, args: &Option<String>| match args {
+            Some(args) => format!("{name}({args})"),
+            None => name.to_string(),
+        };
+        match self {
+            OpKey::AddEnumValue { enum_name, value } => format!("{kind}:{enum_name}:{value}"),
+            OpKey::CreateFunction { name, args }
+            | OpKey::DropFunction { name, args }
+            | OpKey::AlterFunction { name, args }
+            | OpKey::CreateAggregate { name, args }
+            | OpKey::DropAggregate { name, args } => format!("{kind}:{name}({args})"),
+            OpKey::AlterOwner {
+                object_kind,
+                schema,
+                name,
+            } => format!(
+                "{kind}:{}:{}",
+                qualified_name(schema, name),
+                owner_kind_label(object_kind)
+            ),
+            OpKey::SetSchema {
+                object_kind,
+                schema,
+                name,
+                args,
+            } => format!(
+                "{kind}:{}:{}",
+                with_args(&qualified_name(schema, name), args),
+                owner_kind_label(object_kind)
+            ),
+            OpKey::GrantPrivileges {
+                object_kind,
+                schema,
+                name,
+                args,
+                grantee,
+            }
+            | OpKey::RevokePrivileges {
+                object_kind,
+                schema,
+                name,
+                args,
+                grantee,
+            } => format!(
+                "{kind}:{}:{}:{grantee}",
+                with_args(&qualified_name(schema, name), args),
+                grant_kind_label(object_kind)
+            ),
+            OpKey::AlterDefaultPrivileges {
+                target_role,
+                schema,
+                object_type,
+                grantee,
+            } => format!(
+                "{kind}:{}:{target_role}:{object_type}:{grantee}",
+                schema.as_deref().unwrap_or("*")
+            ),
+            OpKey::CreateVersionSchema {
+                base_schema,
+                version,
+            }
+            | OpKey::DropVersionSchema {
+                base_schema,
+                version,
+            } => format!("{kind}:{base_schema}:{version}"),
+            OpKey::ApplyRawObject(name) | OpKey::DropRawObject(name) => format!("{kind}:{name}"),
+            OpKey::SetComment {
+                object_type,
+                schema,
+                name,
+                arguments,
+                column,
+                target,
+                on_domain,
+            } => {
+                let mut identity = format!(
+                    "{kind}:{}",
+                    with_args(&qualified_name(schema, name), arguments)
+                );
+                if let Some(column) = column {
+                    identity.push_str(&format!(".{column}"));
+                }
+                identity.push_str(&format!(":{}", comment_type_label(object_type)));
+                if let Some(target) = target {
+                    let on = if *on_domain { "domain " } else { "" };
+                    identity.push_str(&format!(":on {on}{target}"));
+                }
+                identity
+            }
+            _ => format!("{kind}:{}", self.object().unwrap_or_default()),
+        }
+    }
+
+    /// Key of the operation that recreates what this drop removes, for drops
+    /// that are one half of a drop-and-recreate pair.
+    pub(crate) fn recreate_key(&self) -> Option<OpKey> {
+        match self {
+            OpKey::DropTable(name) => Some(OpKey::CreateTable(name.clone())),
+            OpKey::DropView(name) => Some(OpKey::CreateView(name.clone())),
+            OpKey::DropSequence(name) => Some(OpKey::CreateSequence(name.clone())),
+            OpKey::DropFunction { name, args } => Some(OpKey::CreateFunction {
+                name: name.clone(),
+                args: args.clone(),
+            }),
+            OpKey::DropAggregate { name, args } => Some(OpKey::CreateAggregate {
+                name: name.clone(),
+                args: args.clone(),
+            }),
+            OpKey::DropPrimaryKey { table } => Some(OpKey::AddPrimaryKey {
+                table: table.clone(),
+            }),
+            OpKey::DropIndex { table, name } => Some(OpKey::AddIndex {
+                table: table.clone(),
+                name: name.clone(),
+            }),
+            OpKey::DropForeignKey { table, name } => Some(OpKey::AddForeignKey {
+                table: table.clone(),
+                name: name.clone(),
+            }),
+            OpKey::DropCheckConstraint { table, name } => Some(OpKey::AddCheckConstraint {
+                table: table.clone(),
+                name: name.clone(),
+            }),
+            OpKey::DropExclusionConstraint { table, name } => Some(OpKey::AddExclusionConstraint {
+                table: table.clone(),
+                name: name.clone(),
+            }),
+            OpKey::DropPolicy { table, name } => Some(OpKey::CreatePolicy {
+                table: table.clone(),
+                name: name.clone(),
+            }),
+            OpKey::DropTrigger { target, name } => Some(OpKey::CreateTrigger {
+                target: target.clone(),
+                name: name.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl MigrationOp {
+    /// Qualified name of the object this operation touches, with table-scoped
+    /// operations reporting their table.
+    pub fn subject(&self) -> Option<String> {
+        OpKey::from_op(self).subject()
+    }
+
+    /// PostgreSQL schema this operation belongs to, or None for database-wide
+    /// objects such as extensions.
+    pub fn schema(&self) -> Option<String> {
+        OpKey::from_op(self).schema()
+    }
+
+    /// Stable identity of this operation within a plan: the same object touched
+    /// by the same kind of change yields the same identity across plans.
+    pub fn identity(&self) -> String {
+        OpKey::from_op(self).identity()
+    }
+
+    /// Name of the operation's variant, such as `AddColumn`.
+    pub fn kind(&self) -> String {
+        OpKey::from_op(self).kind().to_string()
+    }
+
+    /// Qualified name of the object this operation touches, including the
+    /// column or constraint name for table-scoped operations.
+    pub fn object(&self) -> Option<String> {
+        OpKey::from_op(self).object()
+    }
+
+    /// Whether `next` creates again what this drop removes, making the two a
+    /// drop-and-recreate pair.
+    pub fn is_recreated_by(&self, next: &MigrationOp) -> bool {
+        OpKey::from_op(self)
+            .recreate_key()
+            .is_some_and(|create| create == OpKey::from_op(next))
+    }
+}
+
+fn owner_kind_label(kind: &OwnerObjectKind) -> &'static str {
+    match kind {
+        OwnerObjectKind::Table => "table",
+        OwnerObjectKind::Partition => "partition",
+        OwnerObjectKind::View => "view",
+        OwnerObjectKind::MaterializedView => "materialized view",
+        OwnerObjectKind::Sequence => "sequence",
+        OwnerObjectKind::Function => "function",
+        OwnerObjectKind::Aggregate => "aggregate",
+        OwnerObjectKind::Type => "type",
+        OwnerObjectKind::Domain => "domain",
+    }
+}
+
+fn grant_kind_label(kind: &GrantObjectKind) -> &'static str {
+    match kind {
+        GrantObjectKind::Table => "table",
+        GrantObjectKind::View => "view",
+        GrantObjectKind::Sequence => "sequence",
+        GrantObjectKind::Function => "function",
+        GrantObjectKind::Aggregate => "aggregate",
+        GrantObjectKind::Schema => "schema",
+        GrantObjectKind::Type => "type",
+        GrantObjectKind::Domain => "domain",
+    }
+}
+
+fn comment_type_label(object_type: &CommentObjectType) -> &'static str {
+    match object_type {
+        CommentObjectType::Table => "table",
+        CommentObjectType::Column => "column",
+        CommentObjectType::View => "view",
+        CommentObjectType::MaterializedView => "materialized view",
+        CommentObjectType::Function => "function",
+        CommentObjectType::Aggregate => "aggregate",
+        CommentObjectType::Type => "type",
+        CommentObjectType::Domain => "domain",
+        CommentObjectType::Schema => "schema",
+        CommentObjectType::Sequence => "sequence",
+        CommentObjectType::Trigger => "trigger",
+        CommentObjectType::Extension => "extension",
+        CommentObjectType::Policy => "policy",
+        CommentObjectType::Constraint => "constraint",
+    }
 }
 
 /// Adds edge: Create<object> → Grant/Revoke, so objects exist before granting.
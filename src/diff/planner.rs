@@ -3,19 +3,27 @@ use super::op_key::{
     parse_type_ref, OpKey,
 };
 use super::{MigrationOp, OwnerObjectKind};
-use crate::model::{parse_qualified_name, qualified_name, Function, QualifiedName};
+use crate::model::{parse_qualified_name, qualified_name, Function, PgType, QualifiedName};
 use crate::parser::{
-    extract_function_references, extract_rowtype_references, extract_table_references,
+    extract_declare_type_references, extract_function_references, extract_rowtype_references,
+    extract_table_references,
 };
 use petgraph::algo::{tarjan_scc, toposort};
 use petgraph::graph::{DiGraph, NodeIndex};
-use std::collections::{HashMap, HashSet};
+use petgraph::Direction;
+use sqlparser::ast::{ArrayElemTypeDef, DataType};
+use sqlparser::dialect::PostgreSqlDialect;
+use sqlparser::parser::Parser;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum PlanError {
     #[error("Circular dependency detected involving: {0}")]
     CyclicDependency(String),
+    #[error("No planned operation matches the requested objects")]
+    EmptySelection,
 }
 
 /// Pre-collected node sets used across multiple edge-building methods.
@@ -46,11 +54,13 @@ struct NodeSets {
     add_exclusions: Vec<NodeIndex>,
     enable_rls: Vec<NodeIndex>,
     force_rls: Vec<NodeIndex>,
+    replica_identities: Vec<NodeIndex>,
     policies: Vec<NodeIndex>,
     triggers: Vec<NodeIndex>,
     views: Vec<NodeIndex>,
     version_views: Vec<NodeIndex>,
     alter_columns: Vec<NodeIndex>,
+    alter_column_defaults: Vec<NodeIndex>,
     alter_views: Vec<NodeIndex>,
     alter_sequences: Vec<NodeIndex>,
     drop_functions: Vec<NodeIndex>,
@@ -73,6 +83,11 @@ struct NodeSets {
     drop_version_schemas: Vec<NodeIndex>,
     drop_schemas: Vec<NodeIndex>,
     drop_version_views: Vec<NodeIndex>,
+    default_privileges: Vec<NodeIndex>,
+    set_schemas: Vec<NodeIndex>,
+    rename_tables: Vec<NodeIndex>,
+    rename_columns: Vec<NodeIndex>,
+    rename_sequences: Vec<NodeIndex>,
 }
 
 impl NodeSets {
@@ -81,7 +96,14 @@ impl NodeSets {
             schemas: graph.nodes_matching(|k| matches!(k, OpKey::CreateSchema(_))),
             version_schemas: graph
                 .nodes_matching(|k| matches!(k, OpKey::CreateVersionSchema { .. })),
-            extensions: graph.nodes_matching(|k| matches!(k, OpKey::CreateExtension(_))),
+            extensions: graph.nodes_matching(|k| {
+                matches!(
+                    k,
+                    OpKey::CreateExtension(_)
+                        | OpKey::UpdateExtension(_)
+                        | OpKey::SetExtensionSchema(_)
+                )
+            }),
             servers: graph.nodes_matching(|k| matches!(k, OpKey::CreateServer(_))),
             enums: graph.nodes_matching(|k| matches!(k, OpKey::CreateEnum(_))),
             add_enum_values: graph.nodes_matching(|k| matches!(k, OpKey::AddEnumValue { .. })),
@@ -103,11 +125,18 @@ impl NodeSets {
             enable_rls: graph.nodes_matching(|k| matches!(k, OpKey::EnableRls { .. })),
             force_rls: graph
                 .nodes_matching(|k| matches!(k, OpKey::ForceRls { .. } | OpKey::NoForceRls { .. })),
+            replica_identities: graph
+                .nodes_matching(|k| matches!(k, OpKey::SetReplicaIdentity { .. })),
             policies: graph.nodes_matching(|k| matches!(k, OpKey::CreatePolicy { .. })),
             triggers: graph.nodes_matching(|k| matches!(k, OpKey::CreateTrigger { .. })),
             views: graph.nodes_matching(|k| matches!(k, OpKey::CreateView(_))),
             version_views: graph.nodes_matching(|k| matches!(k, OpKey::CreateVersionView { .. })),
-            alter_columns: graph.nodes_matching(|k| matches!(k, OpKey::AlterColumn { .. })),
+            alter_columns: graph.ops_matching(
+                |op| matches!(op, MigrationOp::AlterColumn { changes, .. } if !changes.is_catalog_only()),
+            ),
+            alter_column_defaults: graph.ops_matching(
+                |op| matches!(op, MigrationOp::AlterColumn { changes, .. } if changes.is_catalog_only()),
+            ),
             alter_views: graph.nodes_matching(|k| matches!(k, OpKey::AlterView(_))),
             alter_sequences: graph.nodes_matching(|k| matches!(k, OpKey::AlterSequence(_))),
             drop_functions: graph.nodes_matching(|k| matches!(k, OpKey::DropFunction { .. })),
@@ -133,6 +162,12 @@ impl NodeSets {
             drop_schemas: graph.nodes_matching(|k| matches!(k, OpKey::DropSchema(_))),
             drop_version_views: graph
                 .nodes_matching(|k| matches!(k, OpKey::DropVersionView { .. })),
+            default_privileges: graph
+                .nodes_matching(|k| matches!(k, OpKey::AlterDefaultPrivileges { .. })),
+            set_schemas: graph.nodes_matching(|k| matches!(k, OpKey::SetSchema { .. })),
+            rename_tables: graph.nodes_matching(|k| matches!(k, OpKey::RenameTable(_))),
+            rename_columns: graph.nodes_matching(|k| matches!(k, OpKey::RenameColumn { .. })),
+            rename_sequences: graph.nodes_matching(|k| matches!(k, OpKey::RenameSequence(_))),
         }
     }
 }
@@ -180,6 +215,17 @@ impl MigrationGraph {
             .collect()
     }
 
+    fn ops_matching<F>(&self, predicate: F) -> Vec<NodeIndex>
+    where
+        F: Fn(&MigrationOp) -> bool,
+    {
+        self.nodes
+            .values()
+            .copied()
+            .filter(|&node| predicate(&self.graph[node]))
+            .collect()
+    }
+
     fn edges_all_to_all(&mut self, from: &[NodeIndex], to: &[NodeIndex]) {
         for &f in from {
             for &t in to {
@@ -203,6 +249,7 @@ impl MigrationGraph {
         self.add_drop_column_edges(&ns);
         self.add_modification_pattern_edges(&ns);
         self.add_creates_before_final_drops_edges(&ns);
+        self.add_default_privilege_edges(&ns);
     }
 
     /// Tier 1: Schema infrastructure — schemas and version schemas before everything.
@@ -214,8 +261,56 @@ impl MigrationGraph {
         self.edges_all_to_all(&ns.schemas, &ns.functions);
         self.edges_all_to_all(&ns.schemas, &ns.aggregates);
         self.edges_all_to_all(&ns.schemas, &ns.views);
+        // `CREATE EXTENSION ... SCHEMA` and `SET SCHEMA` name the schema.
+        self.edges_all_to_all(&ns.schemas, &ns.extensions);
         self.edges_all_to_all(&ns.version_schemas, &ns.version_views);
 
+        // Objects move as soon as the schema they move into exists, ahead of
+        // everything that may refer to them by their new names and of the
+        // drop of the schema they leave.
+        self.edges_all_to_all(&ns.schemas, &ns.set_schemas);
+        if !ns.set_schemas.is_empty() {
+            let after_moves = self.nodes_matching(|k| {
+                !matches!(
+                    k,
+                    OpKey::CreateSchema(_)
+                        | OpKey::CreateExtension(_)
+                        | OpKey::UpdateExtension(_)
+                        | OpKey::SetExtensionSchema(_)
+                        | OpKey::CreateServer(_)
+                        | OpKey::SetSchema { .. }
+                )
+            });
+            self.edges_all_to_all(&ns.set_schemas, &after_moves);
+        }
+
+        // Renames follow the moves, which leave tables under their old names
+        // in their new schemas, and precede everything that may refer to the
+        // new names. Column renames name their table by its new name, and the
+        // renamed serial sequences follow the renames that named them.
+        self.edges_all_to_all(&ns.rename_tables, &ns.rename_columns);
+        self.edges_all_to_all(&ns.rename_tables, &ns.rename_sequences);
+        self.edges_all_to_all(&ns.rename_columns, &ns.rename_sequences);
+        if !ns.rename_tables.is_empty() || !ns.rename_columns.is_empty() {
+            let after_renames = self.nodes_matching(|k| {
+                !matches!(
+                    k,
+                    OpKey::CreateSchema(_)
+                        | OpKey::CreateExtension(_)
+                        | OpKey::UpdateExtension(_)
+                        | OpKey::SetExtensionSchema(_)
+                        | OpKey::CreateServer(_)
+                        | OpKey::SetSchema { .. }
+                        | OpKey::RenameTable(_)
+                        | OpKey::RenameSequence(_)
+                        | OpKey::RenameColumn { .. }
+                )
+            });
+            self.edges_all_to_all(&ns.rename_tables, &after_renames);
+            self.edges_all_to_all(&ns.rename_columns, &after_renames);
+            self.edges_all_to_all(&ns.rename_sequences, &after_renames);
+        }
+
         self.edges_all_to_all(&ns.extensions, &ns.enums);
         self.edges_all_to_all(&ns.extensions, &ns.domains);
         self.edges_all_to_all(&ns.extensions, &ns.tables);
@@ -347,6 +442,7 @@ impl MigrationGraph {
         self.edges_all_to_all(&ns.tables, &ns.add_exclusions);
         self.edges_all_to_all(&ns.tables, &ns.enable_rls);
         self.edges_all_to_all(&ns.tables, &ns.force_rls);
+        self.edges_all_to_all(&ns.tables, &ns.replica_identities);
         self.edges_all_to_all(&ns.tables, &ns.policies);
         self.edges_all_to_all(&ns.tables, &ns.triggers);
         self.edges_all_to_all(&ns.tables, &ns.views);
@@ -360,6 +456,10 @@ impl MigrationGraph {
         self.edges_all_to_all(&ns.add_columns, &ns.add_checks);
         self.edges_all_to_all(&ns.add_columns, &ns.add_exclusions);
 
+        // REPLICA IDENTITY USING INDEX needs the index, and DEFAULT the primary key.
+        self.edges_all_to_all(&ns.add_indexes, &ns.replica_identities);
+        self.edges_all_to_all(&ns.add_pks, &ns.replica_identities);
+
         self.edges_all_to_all(&ns.add_columns, &ns.views);
         self.edges_all_to_all(&ns.add_columns, &ns.alter_views);
         self.edges_all_to_all(&ns.add_columns, &ns.policies);
@@ -390,6 +490,12 @@ impl MigrationGraph {
 
         // Aggregates depend on their SFUNC function, so drop aggregates before dropping functions.
         self.edges_all_to_all(&ns.drop_aggregates, &ns.drop_functions);
+        // Policies, triggers and views that call a function must go before the function
+        // does; signature changes plan as DropFunction + CreateFunction with these
+        // dependents dropped and recreated around the pair.
+        self.edges_all_to_all(&ns.drop_policies, &ns.drop_functions);
+        self.edges_all_to_all(&ns.drop_triggers, &ns.drop_functions);
+        self.edges_all_to_all(&ns.drop_views, &ns.drop_functions);
         // Views can reference aggregates; drop views before aggregates they consumed.
         self.edges_all_to_all(&ns.drop_views, &ns.drop_aggregates);
 
@@ -406,10 +512,24 @@ impl MigrationGraph {
         self.edges_all_to_all(&ns.drop_domains, &ns.drop_extensions);
         self.edges_all_to_all(&ns.drop_servers, &ns.drop_extensions);
 
+        // A schema is dropped without CASCADE, so everything in it goes first.
+        for members in [
+            &ns.drop_partitions,
+            &ns.drop_sequences,
+            &ns.drop_enums,
+            &ns.drop_domains,
+            &ns.drop_functions,
+            &ns.drop_aggregates,
+            &ns.drop_views,
+        ] {
+            self.edges_all_to_all(members, &ns.drop_schemas);
+        }
         self.edges_all_to_all(&ns.drop_extensions, &ns.drop_schemas);
     }
 
     /// ALTER column dependencies: drop constraints before alter, recreate after.
+    /// Catalog-only changes (defaults, statistics targets, compression) stay
+    /// out of this pattern.
     fn add_alter_column_edges(&mut self, ns: &NodeSets) {
         self.edges_all_to_all(&ns.drop_fks, &ns.alter_columns);
         self.edges_all_to_all(&ns.drop_indexes, &ns.alter_columns);
@@ -482,8 +602,10 @@ impl MigrationGraph {
             &ns.add_checks,
             &ns.enable_rls,
             &ns.force_rls,
+            &ns.replica_identities,
             &ns.version_views,
             &ns.alter_columns,
+            &ns.alter_column_defaults,
             &ns.alter_sequences,
         ]
         .into_iter()
@@ -516,6 +638,23 @@ impl MigrationGraph {
         self.edges_all_to_all(&all_creates, &final_drops);
     }
 
+    /// Default privileges last: they apply to objects created after them, so
+    /// objects this plan creates must not pick them up implicitly.
+    fn add_default_privilege_edges(&mut self, ns: &NodeSets) {
+        for creates in [
+            &ns.enums,
+            &ns.domains,
+            &ns.sequences,
+            &ns.functions,
+            &ns.aggregates,
+            &ns.tables,
+            &ns.partitions,
+            &ns.views,
+        ] {
+            self.edges_all_to_all(creates, &ns.default_privileges);
+        }
+    }
+
     fn get_op(&self, key: &OpKey) -> Option<&MigrationOp> {
         self.nodes.get(key).map(|&idx| &self.graph[idx])
     }
@@ -643,6 +782,14 @@ impl MigrationGraph {
                                 .push((OpKey::CreateTable(ref_qualified.clone()), key.clone()));
                             edges_to_add.push((OpKey::CreateView(ref_qualified), key.clone()));
                         }
+
+                        // Enums and domains already precede every function by tier;
+                        // these edges let `plan_migration_subset` pull them in too.
+                        for type_qualified in declared_variable_types(func) {
+                            edges_to_add
+                                .push((OpKey::CreateEnum(type_qualified.clone()), key.clone()));
+                            edges_to_add.push((OpKey::CreateDomain(type_qualified), key.clone()));
+                        }
                     }
                 }
 
@@ -874,6 +1021,18 @@ impl MigrationGraph {
                     }
                 }
 
+                // AlterViewOptions applies to the view as created or replaced
+                OpKey::AlterViewOptions(view_name) => {
+                    edges_to_add.push((OpKey::CreateView(view_name.clone()), key.clone()));
+                    edges_to_add.push((OpKey::AlterView(view_name.clone()), key.clone()));
+                }
+
+                // A refresh populates the view as created or replaced
+                OpKey::RefreshMaterializedView(view_name) => {
+                    edges_to_add.push((OpKey::CreateView(view_name.clone()), key.clone()));
+                    edges_to_add.push((OpKey::AlterView(view_name.clone()), key.clone()));
+                }
+
                 // AlterColumn must happen after dropping dependent objects,
                 // and new defaults may reference functions
                 OpKey::AlterColumn { table, .. } => {
@@ -1170,9 +1329,26 @@ impl MigrationGraph {
                     }
                 }
 
+                // A raw object runs after every change to the objects it declares
+                // it depends on, and after the raw objects among them.
+                OpKey::ApplyRawObject(_) => {
+                    if let Some(MigrationOp::ApplyRawObject(raw)) = self.get_op(key) {
+                        for dependency in &raw.depends_on {
+                            edges_to_add
+                                .push((OpKey::ApplyRawObject(dependency.clone()), key.clone()));
+                            for other in &keys {
+                                if other.subject().as_ref() == Some(dependency) {
+                                    edges_to_add.push((other.clone(), key.clone()));
+                                }
+                            }
+                        }
+                    }
+                }
+
                 _ => {}
             }
         }
+        edges_to_add.extend(self.extension_use_edges(&keys));
 
         // Add all collected edges
         for (from, to) in edges_to_add {
@@ -1180,20 +1356,137 @@ impl MigrationGraph {
         }
     }
 
+    /// Column types, defaults and generated expressions, and function
+    /// signatures and bodies, can use what an extension provides (`DEFAULT
+    /// uuid_generate_v4()`, `citext` arguments). The plan does not say what
+    /// that is, so each type or function an operation uses that the plan does
+    /// not create is taken from the extensions installed in its schema, and
+    /// from any extension being updated.
+    fn extension_use_edges(&self, keys: &[OpKey]) -> Vec<(OpKey, OpKey)> {
+        let extensions: Vec<(&OpKey, Option<String>)> = keys
+            .iter()
+            .filter_map(|key| match self.get_op(key)? {
+                MigrationOp::CreateExtension(extension) => Some((
+                    key,
+                    Some(
+                        extension
+                            .schema
+                            .clone()
+                            .unwrap_or_else(|| "public".to_string()),
+                    ),
+                )),
+                MigrationOp::SetExtensionSchema { schema, .. } => Some((key, Some(schema.clone()))),
+                MigrationOp::UpdateExtension { .. } => Some((key, None)),
+                _ => None,
+            })
+            .collect();
+        if extensions.is_empty() {
+            return Vec::new();
+        }
+
+        let mut edges = Vec::new();
+        for key in keys {
+            let Some(op) = self.get_op(key) else {
+                continue;
+            };
+            let (expressions, schema, mut used): (Vec<&String>, &str, HashSet<String>) = match op {
+                MigrationOp::CreateFunction(func) => {
+                    (vec![&func.body], &func.schema, signature_user_types(func))
+                }
+                MigrationOp::AddColumn { table, column } => (
+                    column.default.iter().chain(&column.generated).collect(),
+                    &table.schema,
+                    HashSet::new(),
+                ),
+                MigrationOp::AlterColumn { table, changes, .. } => (
+                    changes.default.iter().flatten().collect(),
+                    &table.schema,
+                    HashSet::new(),
+                ),
+                _ => continue,
+            };
+            used.extend(referenced_user_types(op));
+            for expression in expressions {
+                used.extend(
+                    extract_function_references(expression, schema)
+                        .into_iter()
+                        .map(|reference| qualified_name(&reference.schema, &reference.name)),
+                );
+            }
+
+            for name in used.iter().filter(|name| !creates(keys, name)) {
+                let (schema, _) = parse_qualified_name(name);
+                for (extension, extension_schema) in &extensions {
+                    if extension_schema
+                        .as_ref()
+                        .is_none_or(|installed| *installed == schema)
+                    {
+                        edges.push(((*extension).clone(), key.clone()));
+                    }
+                }
+            }
+        }
+        edges
+    }
+
+    /// Orders the operations so every dependency runs first. Operations with no
+    /// ordering constraint between them are emitted by operation kind, then
+    /// qualified name, so the same set of changes always produces the same plan
+    /// regardless of the order the diff listed them in.
     pub fn topological_sort(&self) -> Result<Vec<MigrationOp>, PlanError> {
-        let sorted = toposort(&self.graph, None).map_err(|cycle| {
+        toposort(&self.graph, None).map_err(|cycle| {
             let node = cycle.node_id();
             let op = &self.graph[node];
             PlanError::CyclicDependency(format!("{op:?}"))
         })?;
 
-        Ok(sorted
-            .into_iter()
-            .map(|node| self.graph[node].clone())
-            .collect())
+        let tie_break_keys: HashMap<NodeIndex, TieBreakKey> = self
+            .graph
+            .node_indices()
+            .map(|node| (node, tie_break_key(&self.graph[node])))
+            .collect();
+        let mut in_degree: HashMap<NodeIndex, usize> = self
+            .graph
+            .node_indices()
+            .map(|node| {
+                let count = self
+                    .graph
+                    .neighbors_directed(node, Direction::Incoming)
+                    .count();
+                (node, count)
+            })
+            .collect();
+
+        let mut ready: BinaryHeap<Reverse<(&TieBreakKey, NodeIndex)>> = in_degree
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(node, _)| Reverse((&tie_break_keys[node], *node)))
+            .collect();
+
+        let mut sorted = Vec::with_capacity(self.graph.node_count());
+        while let Some(Reverse((_, node))) = ready.pop() {
+            sorted.push(self.graph[node].clone());
+            for next in self.graph.neighbors_directed(node, Direction::Outgoing) {
+                let count = in_degree.get_mut(&next).expect("node has an in-degree");
+                *count -= 1;
+                if *count == 0 {
+                    ready.push(Reverse((&tie_break_keys[&next], next)));
+                }
+            }
+        }
+        Ok(sorted)
     }
 }
 
+/// Tie-break order for operations the dependency graph leaves unordered:
+/// operation kind, then qualified name, then the full identity so distinct
+/// operations never compare equal.
+type TieBreakKey = (String, String, String);
+
+fn tie_break_key(op: &MigrationOp) -> TieBreakKey {
+    (op.kind(), op.subject().unwrap_or_default(), op.identity())
+}
+
 impl Default for MigrationGraph {
     fn default() -> Self {
         Self::new()
@@ -1268,6 +1561,8 @@ fn push_expression_ref_edges(
 /// Includes:
 /// - `RETURNS SETOF <relation>` (early-bound in the function signature);
 /// - `%ROWTYPE` references in the body;
+/// - Variable types in plpgsql `DECLARE` sections, which may be row types of
+///   tables or views (see `declared_variable_types`);
 /// - For `LANGUAGE sql` only, every relation referenced in the body — PostgreSQL
 ///   parses and validates the SQL at CREATE time. `LANGUAGE plpgsql` resolves
 ///   references lazily at call time, so its body is intentionally not walked.
@@ -1280,6 +1575,7 @@ fn hard_body_relation_deps(func: &Function) -> HashSet<String> {
     for r in extract_rowtype_references(&func.body, &func.schema) {
         deps.insert(qualified_name(&r.schema, &r.name));
     }
+    deps.extend(declared_variable_types(func));
     if func.language.eq_ignore_ascii_case("sql") {
         for r in extract_table_references(&func.body, &func.schema) {
             deps.insert(qualified_name(&r.schema, &r.name));
@@ -1288,6 +1584,64 @@ fn hard_body_relation_deps(func: &Function) -> HashSet<String> {
     deps
 }
 
+/// Qualified names of the types a plpgsql function declares variables with.
+/// PostgreSQL resolves them when it validates the body at CREATE time, so the
+/// enum, domain, table or view each names must exist by then.
+fn declared_variable_types(func: &Function) -> HashSet<String> {
+    if !func.language.eq_ignore_ascii_case("plpgsql") {
+        return HashSet::new();
+    }
+    extract_declare_type_references(&func.body, &func.schema)
+        .into_iter()
+        .map(|r| qualified_name(&r.schema, &r.name))
+        .collect()
+}
+
+/// Qualified names of the types in `func`'s arguments and return type that
+/// are not built in, with unqualified names resolved in the function's schema.
+fn signature_user_types(func: &Function) -> HashSet<String> {
+    fn custom_type(data_type: &DataType) -> Option<String> {
+        match data_type {
+            DataType::Custom(name, _) => Some(name.to_string()),
+            DataType::Array(
+                ArrayElemTypeDef::SquareBracket(inner, _)
+                | ArrayElemTypeDef::AngleBracket(inner)
+                | ArrayElemTypeDef::Parenthesis(inner),
+            ) => custom_type(inner),
+            _ => None,
+        }
+    }
+
+    let return_type = extract_setof_type_ref(&func.return_type).unwrap_or(&func.return_type);
+    func.arguments
+        .iter()
+        .map(|argument| argument.data_type.as_str())
+        .chain(std::iter::once(return_type))
+        .filter_map(|data_type| {
+            let mut parser = Parser::new(&PostgreSqlDialect {})
+                .try_with_sql(data_type)
+                .ok()?;
+            custom_type(&parser.parse_data_type().ok()?)
+        })
+        .map(|type_ref| {
+            let (schema, name) = parse_type_ref(&type_ref, &func.schema);
+            qualified_name(&schema, &name)
+        })
+        .collect()
+}
+
+/// Whether the plan creates a type, relation or function named `name`.
+fn creates(keys: &[OpKey], name: &str) -> bool {
+    keys.iter().any(|key| match key {
+        OpKey::CreateEnum(created)
+        | OpKey::CreateDomain(created)
+        | OpKey::CreateTable(created)
+        | OpKey::CreateFunction { name: created, .. }
+        | OpKey::CreateAggregate { name: created, .. } => created == name,
+        _ => false,
+    })
+}
+
 fn drop_targets_table(other: &OpKey, table: &QualifiedName) -> bool {
     match other {
         OpKey::DropForeignKey { table: t, .. }
@@ -1314,6 +1668,147 @@ pub fn plan_migration_checked(ops: Vec<MigrationOp>) -> Result<Vec<MigrationOp>,
     graph.topological_sort()
 }
 
+/// Plans only the operations accepted by `selected`, plus everything they transitively
+/// depend on. Dependencies are followed through the content-aware edges (FK targets,
+/// function, table and view references), the schema and user-defined types an
+/// operation needs, and both halves of drop-and-recreate pairs, so the subset never
+/// references an object the full plan would have created first. The blanket tier
+/// edges only order the result; they do not widen the selection.
+pub fn plan_migration_subset(
+    ops: Vec<MigrationOp>,
+    selected: impl Fn(&MigrationOp) -> bool,
+) -> Result<Vec<MigrationOp>, PlanError> {
+    let processed_ops = split_sequence_owned_by_ops(ops);
+    let processed_ops = split_cyclic_foreign_keys(processed_ops);
+
+    let mut graph = MigrationGraph::new();
+    for op in processed_ops {
+        graph.add_vertex(op);
+    }
+    graph.add_content_aware_edges();
+
+    let seeds: Vec<NodeIndex> = graph
+        .nodes
+        .values()
+        .copied()
+        .filter(|&node| selected(&graph.graph[node]))
+        .collect();
+    if seeds.is_empty() {
+        return Err(PlanError::EmptySelection);
+    }
+    let included = graph.dependency_closure(seeds);
+
+    graph.add_type_level_edges();
+    Ok(graph
+        .topological_sort()?
+        .into_iter()
+        .filter(|op| included.contains(&OpKey::from_op(op)))
+        .collect())
+}
+
+/// The dependency edges between `ops` that touch an operation accepted by
+/// `selected`, as (runs first, runs after) pairs. Only the edges derived from
+/// what operations reference are listed; the tier edges that order whole kinds
+/// of operation against each other say nothing about one object in particular.
+pub fn dependency_edges(
+    ops: Vec<MigrationOp>,
+    selected: impl Fn(&MigrationOp) -> bool,
+) -> Vec<(MigrationOp, MigrationOp)> {
+    let processed_ops = split_sequence_owned_by_ops(ops);
+    let processed_ops = split_cyclic_foreign_keys(processed_ops);
+
+    let mut graph = MigrationGraph::new();
+    for op in processed_ops {
+        graph.add_vertex(op);
+    }
+    graph.add_content_aware_edges();
+
+    let mut seen = HashSet::new();
+    graph
+        .graph
+        .raw_edges()
+        .iter()
+        .map(|edge| (edge.source(), edge.target()))
+        .filter(|&(from, to)| {
+            (selected(&graph.graph[from]) || selected(&graph.graph[to])) && seen.insert((from, to))
+        })
+        .map(|(from, to)| (graph.graph[from].clone(), graph.graph[to].clone()))
+        .collect()
+}
+
+impl MigrationGraph {
+    /// Expands `seeds` to every operation they cannot run without. Must be called
+    /// before the tier edges are added, since those order unrelated objects.
+    fn dependency_closure(&self, seeds: Vec<NodeIndex>) -> HashSet<OpKey> {
+        let recreated_by: HashMap<OpKey, OpKey> = self
+            .nodes
+            .keys()
+            .filter_map(|key| key.recreate_key().map(|create| (create, key.clone())))
+            .collect();
+
+        let mut visited: HashSet<NodeIndex> = HashSet::new();
+        let mut pending = seeds;
+        while let Some(node) = pending.pop() {
+            if !visited.insert(node) {
+                continue;
+            }
+            let key = OpKey::from_op(&self.graph[node]);
+            pending.extend(
+                self.graph
+                    .neighbors_directed(node, petgraph::Direction::Incoming),
+            );
+
+            let mut required = Vec::new();
+            if let Some(create) = key.recreate_key() {
+                required.push(create);
+            }
+            if let Some(drop) = recreated_by.get(&key) {
+                required.push(drop.clone());
+            }
+            if let Some(subject) = key.subject() {
+                let (schema, _) = parse_qualified_name(&subject);
+                required.push(OpKey::CreateSchema(schema));
+            }
+            for type_name in referenced_user_types(&self.graph[node]) {
+                required.push(OpKey::CreateEnum(type_name.clone()));
+                required.push(OpKey::CreateDomain(type_name.clone()));
+                required.extend(self.nodes.keys().filter(|other| {
+                    matches!(other, OpKey::AddEnumValue { enum_name, .. } if *enum_name == type_name)
+                }).cloned());
+            }
+            pending.extend(required.iter().filter_map(|k| self.nodes.get(k).copied()));
+        }
+
+        visited
+            .into_iter()
+            .map(|node| OpKey::from_op(&self.graph[node]))
+            .collect()
+    }
+}
+
+/// Qualified names of the user-defined types an operation's columns are declared with.
+fn referenced_user_types(op: &MigrationOp) -> Vec<String> {
+    fn base_type(data_type: &PgType) -> Option<&str> {
+        match data_type {
+            PgType::Array(inner) => base_type(inner),
+            PgType::UserDefined(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    let types: Vec<&PgType> = match op {
+        MigrationOp::CreateTable(table) => table.columns.values().map(|c| &c.data_type).collect(),
+        MigrationOp::AddColumn { column, .. } => vec![&column.data_type],
+        MigrationOp::AlterColumn { changes, .. } => changes.data_type.iter().collect(),
+        _ => Vec::new(),
+    };
+    types
+        .into_iter()
+        .filter_map(base_type)
+        .map(str::to_string)
+        .collect()
+}
+
 /// Detects cycles in the inline foreign-key graph among `CreateTable` ops and breaks
 /// them by extracting the cycle-forming FKs into separate `AddForeignKey` ops. Mutual
 /// references (A → B and B → A) and longer FK rings would otherwise produce unplannable
@@ -1431,7 +1926,7 @@ pub fn plan_migration(ops: Vec<MigrationOp>) -> Vec<MigrationOp> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::diff::test_helpers::simple_table_with_fks;
+    use crate::diff::test_helpers::{simple_table_with_fks, simple_table_with_schema};
     use crate::diff::{ColumnChanges, OwnerObjectKind, PolicyChanges};
     use crate::model::*;
     use std::collections::BTreeMap;
@@ -1445,6 +1940,8 @@ mod tests {
             referenced_columns: vec!["id".to_string()],
             on_delete: ReferentialAction::NoAction,
             on_update: ReferentialAction::NoAction,
+            deferrable: false,
+            initially_deferred: false,
         }
     }
 
@@ -1508,6 +2005,9 @@ mod tests {
                     default: None,
                     comment: None,
                     generated: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
                 },
             },
         ];
@@ -1582,7 +2082,8 @@ mod tests {
                     unique: true,
                     index_type: IndexType::BTree,
                     predicate: None,
-                    is_constraint: false,
+                    nulls_not_distinct: false,
+                    column_options: Vec::new(),
                 },
             },
             MigrationOp::AddColumn {
@@ -1594,6 +2095,9 @@ mod tests {
                     default: None,
                     comment: None,
                     generated: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
                 },
             },
         ];
@@ -1711,6 +2215,9 @@ mod tests {
             owner: None,
             grants: Vec::new(),
             comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
         };
         let view_b = View {
             name: "view_b".to_string(),
@@ -1721,6 +2228,9 @@ mod tests {
             owner: None,
             grants: Vec::new(),
             comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
         };
         let view_c = View {
             name: "view_c".to_string(),
@@ -1731,6 +2241,9 @@ mod tests {
             owner: None,
             grants: Vec::new(),
             comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
         };
 
         let ops = vec![
@@ -1904,7 +2417,8 @@ mod tests {
             unique: true,
             index_type: IndexType::BTree,
             predicate: Some("active = true".to_string()),
-            is_constraint: false,
+            nulls_not_distinct: false,
+            column_options: Vec::new(),
         };
 
         let ops = vec![
@@ -1948,6 +2462,8 @@ mod tests {
             referenced_columns: vec!["id".to_string()],
             on_delete: ReferentialAction::NoAction,
             on_update: ReferentialAction::NoAction,
+            deferrable: false,
+            initially_deferred: false,
         };
 
         let ops = vec![
@@ -1958,6 +2474,9 @@ mod tests {
                     data_type: Some(PgType::Uuid),
                     nullable: None,
                     default: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
                 },
             },
             MigrationOp::DropForeignKey {
@@ -2019,6 +2538,9 @@ mod tests {
                     data_type: Some(PgType::Uuid),
                     nullable: None,
                     default: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
                 },
             },
             MigrationOp::DropPolicy {
@@ -2087,6 +2609,9 @@ mod tests {
                     data_type: Some(PgType::Uuid),
                     nullable: None,
                     default: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
                 },
             },
             MigrationOp::DropTrigger {
@@ -2135,6 +2660,9 @@ mod tests {
             owner: None,
             grants: vec![],
             comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
         };
 
         let ops = vec![
@@ -2145,6 +2673,9 @@ mod tests {
                     data_type: Some(PgType::Uuid),
                     nullable: None,
                     default: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
                 },
             },
             MigrationOp::DropView {
@@ -2292,6 +2823,9 @@ mod tests {
             owner: None,
             grants: vec![],
             comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
         };
 
         let ops = vec![
@@ -2405,6 +2939,9 @@ mod tests {
                     data_type: Some(PgType::Text),
                     nullable: None,
                     default: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
                 },
             },
             MigrationOp::DropForeignKey {
@@ -2615,6 +3152,9 @@ mod tests {
                 default: Some("nextval('auth.refresh_tokens_id_seq'::regclass)".to_string()),
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         columns.insert(
@@ -2626,6 +3166,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
 
@@ -2639,10 +3182,12 @@ mod tests {
             }),
             foreign_keys: Vec::new(),
             check_constraints: Vec::new(),
+            unique_constraints: Vec::new(),
             exclusion_constraints: Vec::new(),
             comment: None,
             row_level_security: false,
             force_row_level_security: false,
+            replica_identity: None,
             policies: Vec::new(),
             partition_by: None,
             owner: None,
@@ -2731,6 +3276,9 @@ mod tests {
             owner: None,
             grants: vec![],
             comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
         };
 
         let ops = vec![
@@ -3028,6 +3576,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         columns.insert(
@@ -3039,6 +3590,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
 
@@ -3058,12 +3612,16 @@ mod tests {
                 referenced_columns: vec!["id".to_string()],
                 on_delete: ReferentialAction::NoAction,
                 on_update: ReferentialAction::NoAction,
+                deferrable: false,
+                initially_deferred: false,
             }],
             check_constraints: vec![],
+            unique_constraints: Vec::new(),
             exclusion_constraints: vec![],
             comment: None,
             row_level_security: false,
             force_row_level_security: false,
+            replica_identity: None,
             policies: vec![],
             partition_by: None,
             owner: None,
@@ -3586,6 +4144,9 @@ mod tests {
             owner: None,
             grants: Vec::new(),
             comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
         }
     }
 
@@ -3615,6 +4176,7 @@ mod tests {
             name: name.to_string(),
             version: None,
             schema: None,
+            relocatable: false,
             comment: None,
         }
     }
@@ -3671,6 +4233,9 @@ mod tests {
             default: None,
             comment: None,
             generated: None,
+            statistics: None,
+            compression: None,
+            identity: None,
         }
     }
 
@@ -3852,6 +4417,113 @@ mod tests {
         );
     }
 
+    #[test]
+    fn extension_before_column_defaults() {
+        let users = QualifiedName::new("public", "users");
+        let mut column = make_column("id");
+        column.default = Some("uuid_generate_v4()".to_string());
+        let ops = vec![
+            MigrationOp::AddColumn {
+                table: users.clone(),
+                column,
+            },
+            MigrationOp::AlterColumn {
+                table: users,
+                column: "external_id".to_string(),
+                changes: ColumnChanges {
+                    data_type: None,
+                    nullable: None,
+                    default: Some(Some("uuid_generate_v4()".to_string())),
+                    statistics: None,
+                    compression: None,
+                    identity: None,
+                },
+            },
+            MigrationOp::CreateExtension(make_extension("uuid-ossp")),
+        ];
+        let planned = plan_migration(ops);
+        assert!(
+            matches!(planned[0], MigrationOp::CreateExtension(_)),
+            "{planned:?}"
+        );
+    }
+
+    #[test]
+    fn extensions_ordered_only_before_what_uses_them() {
+        let users = QualifiedName::new("public", "users");
+        let mut normalize_email = make_simple_function("normalize_email", "public");
+        normalize_email.arguments = vec![FunctionArg {
+            name: Some("email".to_string()),
+            data_type: "citext".to_string(),
+            mode: ArgMode::In,
+            default: None,
+        }];
+        let mut external_id = make_column("external_id");
+        external_id.default = Some("extensions.uuid_generate_v4()".to_string());
+        let mut uuid_ossp = make_extension("uuid-ossp");
+        uuid_ossp.schema = Some("extensions".to_string());
+        let ops = vec![
+            MigrationOp::CreateExtension(make_extension("citext")),
+            MigrationOp::CreateExtension(uuid_ossp),
+            MigrationOp::CreateFunction(normalize_email),
+            MigrationOp::CreateFunction(make_simple_function("compute_total", "public")),
+            MigrationOp::AddColumn {
+                table: users.clone(),
+                column: external_id,
+            },
+            MigrationOp::AddColumn {
+                table: users,
+                column: make_column("name"),
+            },
+        ];
+
+        let mut edges: Vec<(String, String)> = dependency_edges(ops, |_| true)
+            .into_iter()
+            .filter(|(from, _)| matches!(from, MigrationOp::CreateExtension(_)))
+            .map(|(from, to)| (from.object().unwrap(), to.object().unwrap()))
+            .collect();
+        edges.sort();
+        assert_eq!(
+            edges,
+            vec![
+                ("citext".to_string(), "public.normalize_email".to_string()),
+                (
+                    "uuid-ossp".to_string(),
+                    "public.users.external_id".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn raw_object_after_declared_dependencies() {
+        let ops = vec![
+            MigrationOp::ApplyRawObject(RawObject::new(
+                "a_search",
+                "CREATE TEXT SEARCH CONFIGURATION a_search (COPY = b_dictionary);",
+                vec!["b_dictionary".to_string(), "public.z_documents".to_string()],
+            )),
+            MigrationOp::ApplyRawObject(RawObject::new(
+                "b_dictionary",
+                "CREATE TEXT SEARCH DICTIONARY b_dictionary (TEMPLATE = simple);",
+                Vec::new(),
+            )),
+            MigrationOp::CreateTable(simple_table_with_fks("z_documents", vec![])),
+        ];
+        let planned = plan_migration(ops);
+        let position =
+            |predicate: &dyn Fn(&MigrationOp) -> bool| planned.iter().position(predicate).unwrap();
+        let search = position(
+            &|op| matches!(op, MigrationOp::ApplyRawObject(raw) if raw.name == "a_search"),
+        );
+        assert!(
+            position(
+                &|op| matches!(op, MigrationOp::ApplyRawObject(raw) if raw.name == "b_dictionary")
+            ) < search
+        );
+        assert!(position(&|op| matches!(op, MigrationOp::CreateTable(_))) < search);
+    }
+
     // --- Type ordering (enums, domains) ---
 
     #[test]
@@ -4096,6 +4768,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn function_declaring_table_row_type_after_table() {
+        let func = make_function_with_body(
+            "latest_user",
+            "public",
+            "DECLARE r users; BEGIN SELECT * INTO r FROM users LIMIT 1; RETURN r.id; END;",
+            "integer",
+        );
+        let ops = vec![
+            MigrationOp::CreateFunction(func),
+            MigrationOp::CreateTable(simple_table_with_fks("users", vec![])),
+        ];
+        let planned = plan_migration(ops);
+        assert_op_position(
+            &planned,
+            "CreateTable(users)",
+            "CreateFunction(latest_user)",
+            |op| matches!(op, MigrationOp::CreateTable(t) if t.name == "users"),
+            |op| matches!(op, MigrationOp::CreateFunction(f) if f.name == "latest_user"),
+        );
+    }
+
     #[test]
     fn function_with_rowtype_ref_to_table_not_in_migration() {
         let func = make_function_with_body(
@@ -4188,7 +4882,8 @@ mod tests {
                     unique: false,
                     index_type: IndexType::BTree,
                     predicate: None,
-                    is_constraint: false,
+                    nulls_not_distinct: false,
+                    column_options: Vec::new(),
                 },
             },
             MigrationOp::CreateTable(simple_table_with_fks("users", vec![])),
@@ -4461,7 +5156,8 @@ mod tests {
                     unique: false,
                     index_type: IndexType::BTree,
                     predicate: None,
-                    is_constraint: false,
+                    nulls_not_distinct: false,
+                    column_options: Vec::new(),
                 },
             },
             MigrationOp::CreateFunction(make_simple_function("normalize_name", "auth")),
@@ -4495,7 +5191,8 @@ mod tests {
                     unique: false,
                     index_type: IndexType::BTree,
                     predicate: Some("auth.is_active(status)".to_string()),
-                    is_constraint: false,
+                    nulls_not_distinct: false,
+                    column_options: Vec::new(),
                 },
             },
             MigrationOp::CreateFunction(make_simple_function("is_active", "auth")),
@@ -4530,6 +5227,9 @@ mod tests {
                     default: Some("auth.generate_tracking_id()".to_string()),
                     comment: None,
                     generated: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
                 },
             },
             MigrationOp::CreateFunction(make_simple_function("generate_tracking_id", "auth")),
@@ -4598,6 +5298,9 @@ mod tests {
                     owner: None,
                     grants: Vec::new(),
                     comment: None,
+                    security_barrier: false,
+                    security_invoker: false,
+                    check_option: None,
                 },
             },
             MigrationOp::CreateFunction(make_simple_function("is_active", "auth")),
@@ -4629,6 +5332,9 @@ mod tests {
                     data_type: None,
                     nullable: None,
                     default: Some(Some("auth.generate_tracking_id()".to_string())),
+                    statistics: None,
+                    compression: None,
+                    identity: None,
                 },
             },
             MigrationOp::CreateFunction(make_simple_function("generate_tracking_id", "auth")),
@@ -4690,6 +5396,9 @@ mod tests {
                     default: Some("true".to_string()),
                     comment: None,
                     generated: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
                 },
             },
             MigrationOp::CreateFunction(make_simple_function("some_func", "public")),
@@ -4724,6 +5433,9 @@ mod tests {
                     default: Some("true".to_string()),
                     comment: None,
                     generated: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
                 },
             },
             MigrationOp::CreateFunction(make_simple_function("some_func", "public")),
@@ -4753,6 +5465,9 @@ mod tests {
                     default: Some("true".to_string()),
                     comment: None,
                     generated: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
                 },
             },
         ];
@@ -4784,6 +5499,9 @@ mod tests {
                     default: Some("true".to_string()),
                     comment: None,
                     generated: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
                 },
             },
         ];
@@ -4813,6 +5531,9 @@ mod tests {
                     default: None,
                     comment: None,
                     generated: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
                 },
             },
         ];
@@ -5000,7 +5721,10 @@ mod tests {
     #[test]
     fn drop_table_before_drop_schema() {
         let ops = vec![
-            MigrationOp::DropSchema("api".to_string()),
+            MigrationOp::DropSchema {
+                name: "api".to_string(),
+                cascade: false,
+            },
             MigrationOp::DropTable("api.users".to_string()),
         ];
         let planned = plan_migration(ops);
@@ -5009,7 +5733,31 @@ mod tests {
             "DropTable",
             "DropSchema",
             |op| matches!(op, MigrationOp::DropTable(_)),
-            |op| matches!(op, MigrationOp::DropSchema(_)),
+            |op| matches!(op, MigrationOp::DropSchema { .. }),
+        );
+    }
+
+    #[test]
+    fn schema_members_dropped_before_schema() {
+        let ops = vec![
+            MigrationOp::DropSchema {
+                name: "api".to_string(),
+                cascade: false,
+            },
+            MigrationOp::DropView {
+                name: "api.active_users".to_string(),
+                materialized: false,
+            },
+            MigrationOp::DropFunction {
+                name: "api.current_user_id".to_string(),
+                args: String::new(),
+            },
+            MigrationOp::DropEnum("api.status".to_string()),
+        ];
+        let planned = plan_migration(ops);
+        assert!(
+            matches!(planned.last(), Some(MigrationOp::DropSchema { .. })),
+            "{planned:?}"
         );
     }
 
@@ -5025,6 +5773,9 @@ mod tests {
                     data_type: Some(PgType::Uuid),
                     nullable: None,
                     default: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
                 },
             },
             MigrationOp::DropForeignKey {
@@ -5056,6 +5807,9 @@ mod tests {
                     data_type: Some(PgType::Uuid),
                     nullable: None,
                     default: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
                 },
             },
         ];
@@ -5091,6 +5845,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn drop_trigger_before_drop_function() {
+        let ops = vec![
+            MigrationOp::DropFunction {
+                name: "public.audit_fn".to_string(),
+                args: "".to_string(),
+            },
+            MigrationOp::DropTrigger {
+                target_schema: "public".to_string(),
+                target_name: "users".to_string(),
+                name: "audit".to_string(),
+            },
+        ];
+        let planned = plan_migration(ops);
+        assert_op_position(
+            &planned,
+            "DropTrigger",
+            "DropFunction",
+            |op| matches!(op, MigrationOp::DropTrigger { .. }),
+            |op| matches!(op, MigrationOp::DropFunction { .. }),
+        );
+    }
+
+    #[test]
+    fn drop_view_before_drop_function() {
+        let ops = vec![
+            MigrationOp::DropFunction {
+                name: "public.score".to_string(),
+                args: "".to_string(),
+            },
+            MigrationOp::DropView {
+                name: "public.scored".to_string(),
+                materialized: false,
+            },
+        ];
+        let planned = plan_migration(ops);
+        assert_op_position(
+            &planned,
+            "DropView",
+            "DropFunction",
+            |op| matches!(op, MigrationOp::DropView { .. }),
+            |op| matches!(op, MigrationOp::DropFunction { .. }),
+        );
+    }
+
+    #[test]
+    fn drop_policy_before_drop_function() {
+        let ops = vec![
+            MigrationOp::DropFunction {
+                name: "public.check_access".to_string(),
+                args: "".to_string(),
+            },
+            MigrationOp::DropPolicy {
+                table: QualifiedName::new("public", "users"),
+                name: "access".to_string(),
+            },
+        ];
+        let planned = plan_migration(ops);
+        assert_op_position(
+            &planned,
+            "DropPolicy",
+            "DropFunction",
+            |op| matches!(op, MigrationOp::DropPolicy { .. }),
+            |op| matches!(op, MigrationOp::DropFunction { .. }),
+        );
+    }
+
     #[test]
     fn drop_view_before_create_view() {
         let ops = vec![
@@ -5318,6 +6139,9 @@ mod tests {
                     data_type: Some(PgType::BigInt),
                     nullable: None,
                     default: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
                 },
             },
             MigrationOp::DropForeignKey {
@@ -6317,6 +7141,9 @@ mod tests {
             default: None,
             comment: None,
             generated: Some("compute_derived()".to_string()),
+            statistics: None,
+            compression: None,
+            identity: None,
         };
 
         let ops = vec![
@@ -6712,4 +7539,134 @@ mod tests {
             "both function-overload grants must survive the planner with distinct OpKeys"
         );
     }
+
+    #[test]
+    fn subset_pulls_in_referenced_tables() {
+        let ops = vec![
+            MigrationOp::CreateTable(simple_table_with_fks("posts", vec![make_fk("users")])),
+            MigrationOp::CreateTable(simple_table_with_fks("users", vec![])),
+            MigrationOp::CreateTable(simple_table_with_fks("audit_log", vec![])),
+        ];
+
+        let planned = plan_migration_subset(
+            ops,
+            |op| matches!(op, MigrationOp::CreateTable(t) if t.name == "posts"),
+        )
+        .unwrap();
+
+        let names: Vec<&str> = planned
+            .iter()
+            .filter_map(|op| match op {
+                MigrationOp::CreateTable(t) => Some(t.name.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(names, vec!["users", "posts"]);
+    }
+
+    #[test]
+    fn subset_pulls_in_schema_and_column_types() {
+        let mut accounts = simple_table_with_schema("accounts", "app");
+        let mut status = make_column("status");
+        status.data_type = PgType::Array(Box::new(PgType::UserDefined("app.status".to_string())));
+        accounts.columns.insert("status".to_string(), status);
+
+        let ops = vec![
+            MigrationOp::CreateTable(accounts),
+            MigrationOp::CreateEnum(make_enum("status", "app")),
+            MigrationOp::CreateEnum(make_enum("unrelated", "app")),
+            MigrationOp::CreateSchema(make_schema("app")),
+            MigrationOp::CreateFunction(make_simple_function("helper", "public")),
+        ];
+
+        let planned =
+            plan_migration_subset(ops, |op| op.subject().as_deref() == Some("app.accounts"))
+                .unwrap();
+
+        assert_eq!(planned.len(), 3, "unexpected ops: {planned:?}");
+        assert!(matches!(&planned[0], MigrationOp::CreateSchema(s) if s.name == "app"));
+        assert!(matches!(&planned[1], MigrationOp::CreateEnum(e) if e.name == "status"));
+        assert!(matches!(&planned[2], MigrationOp::CreateTable(t) if t.name == "accounts"));
+    }
+
+    #[test]
+    fn subset_pulls_in_types_declared_in_function_bodies() {
+        let func = make_function_with_body(
+            "next_status",
+            "app",
+            "DECLARE s status := 'new'; BEGIN RETURN s; END;",
+            "text",
+        );
+        let ops = vec![
+            MigrationOp::CreateFunction(func),
+            MigrationOp::CreateEnum(make_enum("status", "app")),
+            MigrationOp::CreateEnum(make_enum("unrelated", "app")),
+        ];
+
+        let planned = plan_migration_subset(
+            ops,
+            |op| matches!(op, MigrationOp::CreateFunction(f) if f.name == "next_status"),
+        )
+        .unwrap();
+
+        assert_eq!(planned.len(), 2, "unexpected ops: {planned:?}");
+        assert!(matches!(&planned[0], MigrationOp::CreateEnum(e) if e.name == "status"));
+        assert!(matches!(&planned[1], MigrationOp::CreateFunction(_)));
+    }
+
+    #[test]
+    fn subset_keeps_drop_and_recreate_together() {
+        let ops = vec![
+            MigrationOp::CreateView(make_view("active_users", "public", "SELECT 2")),
+            MigrationOp::DropView {
+                name: "public.active_users".to_string(),
+                materialized: false,
+            },
+            MigrationOp::CreateView(make_view("other", "public", "SELECT 1")),
+        ];
+
+        let planned = plan_migration_subset(
+            ops,
+            |op| matches!(op, MigrationOp::CreateView(v) if v.name == "active_users"),
+        )
+        .unwrap();
+
+        assert_eq!(planned.len(), 2, "unexpected ops: {planned:?}");
+        assert!(matches!(&planned[0], MigrationOp::DropView { .. }));
+        assert!(matches!(&planned[1], MigrationOp::CreateView(v) if v.name == "active_users"));
+    }
+
+    #[test]
+    fn subset_without_matches_is_rejected() {
+        let ops = vec![MigrationOp::CreateTable(simple_table_with_fks(
+            "users",
+            vec![],
+        ))];
+
+        let result = plan_migration_subset(ops, |_| false);
+        assert!(matches!(result, Err(PlanError::EmptySelection)));
+    }
+
+    #[test]
+    fn dependency_edges_name_what_orders_an_object() {
+        let ops = vec![
+            MigrationOp::CreateTable(simple_table_with_fks("posts", vec![make_fk("users")])),
+            MigrationOp::CreateTable(simple_table_with_fks("users", vec![])),
+            MigrationOp::CreateTable(simple_table_with_fks("audit_log", vec![])),
+        ];
+
+        let edges = dependency_edges(ops, |op| op.subject().as_deref() == Some("public.posts"));
+
+        let named: Vec<(String, String)> = edges
+            .iter()
+            .map(|(from, to)| (from.subject().unwrap(), to.subject().unwrap()))
+            .collect();
+        assert!(
+            named.contains(&("public.users".to_string(), "public.posts".to_string())),
+            "unexpected edges: {named:?}"
+        );
+        assert!(named
+            .iter()
+            .all(|(from, to)| from != "public.audit_log" && to != "public.audit_log"));
+    }
 }
@@ -0,0 +1,113 @@
+//! Shared by schema moves (`moves`) and renames (`renames`), which both give
+//! an object of the current schema a new key before the rest of the diff runs.
+
+use std::collections::BTreeMap;
+
+use crate::model::{qualified_name, QualifiedName, Schema};
+
+/// Whether `old` can become `new`: `from` holds the object only under its old
+/// key and `to` only under its new one, so a hint that was already applied
+/// changes nothing.
+pub(super) fn relocatable<T>(
+    from: &BTreeMap<String, T>,
+    to: &BTreeMap<String, T>,
+    old: &str,
+    new: &str,
+) -> bool {
+    from.contains_key(old)
+        && !from.contains_key(new)
+        && to.contains_key(new)
+        && !to.contains_key(old)
+}
+
+/// Gives the table `old` the schema and name of `new`, along with what
+/// PostgreSQL keeps pointing at it by OID: its policies, triggers, partitions,
+/// owned sequences and the foreign keys referencing it. A sequence the table
+/// owns follows it into a new schema, as `SET SCHEMA` takes it along, and the
+/// serial defaults are pointed at it there.
+pub(super) fn relocate_table(schema: &mut Schema, old: &QualifiedName, new: &QualifiedName) {
+    let points_at_old =
+        |table_schema: &str, table_name: &str| table_schema == old.schema && table_name == old.name;
+
+    let mut table = schema.tables.remove(&old.to_string()).unwrap();
+    table.schema = new.schema.clone();
+    table.name = new.name.clone();
+    for policy in &mut table.policies {
+        policy.table_schema = new.schema.clone();
+        policy.table = new.name.clone();
+    }
+
+    let owned: Vec<String> = schema
+        .sequences
+        .iter()
+        .filter(|(_, sequence)| {
+            sequence
+                .owned_by
+                .as_ref()
+                .is_some_and(|owner| points_at_old(&owner.table_schema, &owner.table_name))
+        })
+        .map(|(key, _)| key.clone())
+        .collect();
+    for key in owned {
+        let mut sequence = schema.sequences.remove(&key).unwrap();
+        if let Some(owner) = &mut sequence.owned_by {
+            owner.table_schema = new.schema.clone();
+            owner.table_name = new.name.clone();
+        }
+        if sequence.schema != new.schema {
+            let old_default = nextval(&sequence.schema, &sequence.name);
+            sequence.schema = new.schema.clone();
+            let new_default = nextval(&sequence.schema, &sequence.name);
+            for column in table.columns.values_mut() {
+                if column.default.as_deref() == Some(old_default.as_str()) {
+                    column.default = Some(new_default.clone());
+                }
+            }
+        }
+        schema
+            .sequences
+            .insert(qualified_name(&sequence.schema, &sequence.name), sequence);
+    }
+    schema.tables.insert(new.to_string(), table);
+
+    for table in schema.tables.values_mut() {
+        for foreign_key in &mut table.foreign_keys {
+            if points_at_old(
+                &foreign_key.referenced_schema,
+                &foreign_key.referenced_table,
+            ) {
+                foreign_key.referenced_schema = new.schema.clone();
+                foreign_key.referenced_table = new.name.clone();
+            }
+        }
+    }
+    for partition in schema.partitions.values_mut() {
+        if points_at_old(&partition.parent_schema, &partition.parent_name) {
+            partition.parent_schema = new.schema.clone();
+            partition.parent_name = new.name.clone();
+        }
+    }
+    let triggers: Vec<String> = schema
+        .triggers
+        .iter()
+        .filter(|(_, trigger)| points_at_old(&trigger.target_schema, &trigger.target_name))
+        .map(|(key, _)| key.clone())
+        .collect();
+    for key in triggers {
+        let mut trigger = schema.triggers.remove(&key).unwrap();
+        trigger.target_schema = new.schema.clone();
+        trigger.target_name = new.name.clone();
+        let key = format!("{new}.{}", trigger.name);
+        schema.triggers.insert(key, trigger);
+    }
+}
+
+/// The default of a serial column drawing from `schema.sequence`, as the
+/// parser writes it.
+pub(super) fn nextval(schema: &str, sequence: &str) -> String {
+    if schema == "public" {
+        format!("nextval('{sequence}'::regclass)")
+    } else {
+        format!("nextval('{schema}.{sequence}'::regclass)")
+    }
+}
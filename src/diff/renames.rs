@@ -0,0 +1,383 @@
+//! Tables and columns renamed by a `-- pgmold:rename` hint or, with
+//! `detect_renames`, by pairing a dropped object with an added one of the same
+//! shape. The current schema takes the new names before the diff runs, so a
+//! rename is one `ALTER TABLE ... RENAME` instead of a drop and a create.
+//! Indexes and constraints keep their names, as PostgreSQL leaves them; a
+//! serial column's sequence takes the name the sources give it.
+
+use std::collections::BTreeMap;
+
+use crate::model::{parse_qualified_name, qualified_name, QualifiedName, Schema, Sequence, Table};
+
+use super::relocate::{nextval, relocatable, relocate_table};
+use super::{DiffOptions, MigrationOp};
+
+/// `from` with the tables and columns `to` renames given their new names,
+/// along with the operations that rename them. Hints whose object is not
+/// [`relocatable`] are ignored. The schema is `None` when nothing is renamed.
+pub(super) fn rename_objects(
+    from: &Schema,
+    to: &Schema,
+    options: &DiffOptions,
+) -> (Option<Schema>, Vec<MigrationOp>) {
+    if to.renames.is_empty() && !options.detect_renames {
+        return (None, Vec::new());
+    }
+    let mut renamed = from.clone();
+    let mut ops = Vec::new();
+
+    for (old, new) in table_renames(&renamed, to, options) {
+        let (schema, name) = parse_qualified_name(&old);
+        let (_, new_name) = parse_qualified_name(&new);
+        relocate_table(
+            &mut renamed,
+            &QualifiedName::new(&schema, &name),
+            &QualifiedName::new(&schema, &new_name),
+        );
+        ops.push(MigrationOp::RenameTable {
+            table: QualifiedName::new(&schema, &name),
+            new_name,
+        });
+        rename_owned_sequences(&mut renamed, to, &new, &mut ops);
+    }
+
+    for (key, old, new) in column_renames(&renamed, to, options) {
+        let table = renamed.tables.get(&key).unwrap();
+        let table_name = QualifiedName::new(&table.schema, &table.name);
+        rename_column(&mut renamed, &table_name, &old, &new);
+        ops.push(MigrationOp::RenameColumn {
+            table: table_name,
+            column: old,
+            new_name: new,
+        });
+        rename_owned_sequences(&mut renamed, to, &key, &mut ops);
+    }
+
+    if ops.is_empty() {
+        return (None, ops);
+    }
+    (Some(renamed), ops)
+}
+
+/// Old and new keys of the tables to rename: those a hint names, then with
+/// `detect_renames` each dropped table whose columns match exactly one added
+/// table in the same schema, and no other dropped table's.
+fn table_renames(from: &Schema, to: &Schema, options: &DiffOptions) -> Vec<(String, String)> {
+    let mut renames: Vec<(String, String)> = to
+        .renames
+        .iter()
+        .filter(|(old, _)| old.matches('.').count() == 1)
+        .filter_map(|(old, new_name)| {
+            let (schema, _) = parse_qualified_name(old);
+            let new = qualified_name(&schema, new_name);
+            relocatable(&from.tables, &to.tables, old, &new).then_some((old.clone(), new))
+        })
+        .collect();
+    if !options.detect_renames {
+        return renames;
+    }
+
+    let dropped: Vec<&Table> = from
+        .tables
+        .iter()
+        .filter(|(key, _)| !to.tables.contains_key(*key))
+        .filter(|(key, _)| !renames.iter().any(|(old, _)| old == *key))
+        .map(|(_, table)| table)
+        .collect();
+    let added: Vec<&Table> = to
+        .tables
+        .iter()
+        .filter(|(key, _)| !from.tables.contains_key(*key))
+        .filter(|(key, _)| !renames.iter().any(|(_, new)| new == *key))
+        .map(|(_, table)| table)
+        .collect();
+    let matches = |a: &Table, b: &Table| a.schema == b.schema && same_columns(a, b, options);
+    for old in &dropped {
+        let candidates: Vec<&&Table> = added.iter().filter(|new| matches(old, new)).collect();
+        if let [new] = candidates.as_slice() {
+            if dropped.iter().filter(|other| matches(other, new)).count() == 1 {
+                renames.push((
+                    qualified_name(&old.schema, &old.name),
+                    qualified_name(&new.schema, &new.name),
+                ));
+            }
+        }
+    }
+    renames
+}
+
+/// Whether both tables have columns, with the same names and equivalent
+/// types.
+fn same_columns(a: &Table, b: &Table, options: &DiffOptions) -> bool {
+    !a.columns.is_empty()
+        && a.columns.len() == b.columns.len()
+        && a.columns.iter().all(|(name, column)| {
+            b.columns.get(name).is_some_and(|other| {
+                options
+                    .type_equivalences
+                    .equivalent(&column.data_type, &other.data_type)
+            })
+        })
+}
+
+/// Table key, old and new name of the columns to rename: those a hint names,
+/// then with `detect_renames` the one column dropped from a table when the
+/// only column added to it has an equivalent type.
+fn column_renames(
+    from: &Schema,
+    to: &Schema,
+    options: &DiffOptions,
+) -> Vec<(String, String, String)> {
+    let mut renames: Vec<(String, String, String)> = to
+        .renames
+        .iter()
+        .filter_map(|(old, new_name)| {
+            let (table, column) = old.rsplit_once('.')?;
+            if !table.contains('.') {
+                return None;
+            }
+            let from_table = from.tables.get(table)?;
+            let to_table = to.tables.get(table)?;
+            relocatable(&from_table.columns, &to_table.columns, column, new_name)
+                .then(|| (table.to_string(), column.to_string(), new_name.clone()))
+        })
+        .collect();
+    if !options.detect_renames {
+        return renames;
+    }
+
+    for (key, to_table) in &to.tables {
+        let Some(from_table) = from.tables.get(key) else {
+            continue;
+        };
+        if renames.iter().any(|(table, _, _)| table == key) {
+            continue;
+        }
+        let dropped: Vec<_> = from_table
+            .columns
+            .values()
+            .filter(|column| !to_table.columns.contains_key(&column.name))
+            .collect();
+        let added: Vec<_> = to_table
+            .columns
+            .values()
+            .filter(|column| !from_table.columns.contains_key(&column.name))
+            .collect();
+        if let ([old], [new]) = (dropped.as_slice(), added.as_slice()) {
+            if options
+                .type_equivalences
+                .equivalent(&old.data_type, &new.data_type)
+            {
+                renames.push((key.clone(), old.name.clone(), new.name.clone()));
+            }
+        }
+    }
+    renames
+}
+
+/// Gives the sequences owned by the renamed table `key` the names `to` gives
+/// the sequences owned by the same columns, such as the `<table>_<column>_seq`
+/// of a serial column, and points the column defaults at them.
+fn rename_owned_sequences(schema: &mut Schema, to: &Schema, key: &str, ops: &mut Vec<MigrationOp>) {
+    let owned_by = |sequences: &BTreeMap<String, Sequence>| {
+        sequences
+            .iter()
+            .filter_map(|(sequence_key, sequence)| {
+                let owner = sequence.owned_by.as_ref()?;
+                (qualified_name(&owner.table_schema, &owner.table_name) == key)
+                    .then(|| (owner.column_name.clone(), sequence_key.clone()))
+            })
+            .collect::<Vec<_>>()
+    };
+    let targets = owned_by(&to.sequences);
+
+    for (column, old) in owned_by(&schema.sequences) {
+        let Some((_, new)) = targets.iter().find(|(owner, _)| *owner == column) else {
+            continue;
+        };
+        if !relocatable(&schema.sequences, &to.sequences, &old, new) {
+            continue;
+        }
+        let mut sequence = schema.sequences.remove(&old).unwrap();
+        let old_default = nextval(&sequence.schema, &sequence.name);
+        let (_, new_name) = parse_qualified_name(new);
+        ops.push(MigrationOp::RenameSequence {
+            sequence: QualifiedName::new(&sequence.schema, &sequence.name),
+            new_name: new_name.clone(),
+        });
+        sequence.name = new_name;
+        let new_default = nextval(&sequence.schema, &sequence.name);
+        if let Some(column) = schema
+            .tables
+            .get_mut(key)
+            .and_then(|table| table.columns.get_mut(&column))
+        {
+            if column.default.as_deref() == Some(old_default.as_str()) {
+                column.default = Some(new_default);
+            }
+        }
+        schema.sequences.insert(new.clone(), sequence);
+    }
+}
+
+/// Renames a column along with the indexes, keys, trigger column lists and
+/// owned sequences that name it.
+fn rename_column(schema: &mut Schema, table_name: &QualifiedName, old: &str, new: &str) {
+    let rename = |columns: &mut Vec<String>| {
+        for column in columns.iter_mut().filter(|column| *column == old) {
+            *column = new.to_string();
+        }
+    };
+
+    let table = schema.tables.get_mut(&table_name.to_string()).unwrap();
+    let mut column = table.columns.remove(old).unwrap();
+    column.name = new.to_string();
+    table.columns.insert(new.to_string(), column);
+    for index in &mut table.indexes {
+        rename(&mut index.columns);
+    }
+    for unique_constraint in &mut table.unique_constraints {
+        rename(&mut unique_constraint.columns);
+    }
+    if let Some(primary_key) = &mut table.primary_key {
+        rename(&mut primary_key.columns);
+    }
+    for foreign_key in &mut table.foreign_keys {
+        rename(&mut foreign_key.columns);
+    }
+
+    for table in schema.tables.values_mut() {
+        for foreign_key in &mut table.foreign_keys {
+            if foreign_key.referenced_schema == table_name.schema
+                && foreign_key.referenced_table == table_name.name
+            {
+                rename(&mut foreign_key.referenced_columns);
+            }
+        }
+    }
+    for trigger in schema.triggers.values_mut() {
+        if trigger.target_schema == table_name.schema && trigger.target_name == table_name.name {
+            rename(&mut trigger.update_columns);
+        }
+    }
+    for sequence in schema.sequences.values_mut() {
+        if let Some(owner) = &mut sequence.owned_by {
+            if owner.table_schema == table_name.schema
+                && owner.table_name == table_name.name
+                && owner.column_name == old
+            {
+                owner.column_name = new.to_string();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::diff::planner::plan_migration_checked;
+    use crate::diff::{
+        compute_diff, compute_diff_with_options, AdoptionMode, DefaultComparison, DiffOptions,
+        TypeEquivalences,
+    };
+    use crate::parser::parse_sql_string;
+    use crate::pg::sqlgen::generate_sql;
+
+    #[test]
+    fn rename_hints_rename_tables_and_columns() {
+        let from = parse_sql_string(
+            "CREATE TABLE public.users (id integer PRIMARY KEY, mail text);
+             CREATE TABLE public.posts (
+                 id integer PRIMARY KEY,
+                 author_id integer REFERENCES public.users (id)
+             );",
+        )
+        .unwrap();
+        let to = parse_sql_string(
+            "-- pgmold:rename public.users accounts
+             -- pgmold:rename public.accounts.mail email
+             CREATE TABLE public.accounts (id integer PRIMARY KEY, email text);
+             CREATE TABLE public.posts (
+                 id integer PRIMARY KEY,
+                 author_id integer REFERENCES public.accounts (id)
+             );",
+        )
+        .unwrap();
+
+        let ops = plan_migration_checked(compute_diff(&from, &to)).unwrap();
+        let sql = generate_sql(&ops);
+        assert!(
+            sql.starts_with(&[
+                "ALTER TABLE \"public\".\"users\" RENAME TO \"accounts\";".to_string(),
+                "ALTER TABLE \"public\".\"accounts\" RENAME COLUMN \"mail\" TO \"email\";"
+                    .to_string(),
+            ]),
+            "{sql:?}"
+        );
+        assert!(
+            !sql.iter().any(|statement| statement.contains("DROP")),
+            "{sql:?}"
+        );
+
+        assert!(compute_diff(&to, &to).is_empty());
+    }
+
+    #[test]
+    fn table_rename_renames_serial_sequences() {
+        let from =
+            parse_sql_string("CREATE TABLE public.invoices (id serial PRIMARY KEY);").unwrap();
+        let to = parse_sql_string(
+            "-- pgmold:rename public.invoices bills
+             CREATE TABLE public.bills (id serial PRIMARY KEY);",
+        )
+        .unwrap();
+
+        let ops = plan_migration_checked(compute_diff(&from, &to)).unwrap();
+        let sql = generate_sql(&ops);
+        assert_eq!(
+            sql,
+            vec![
+                "ALTER TABLE \"public\".\"invoices\" RENAME TO \"bills\";",
+                "ALTER SEQUENCE \"public\".\"invoices_id_seq\" RENAME TO \"bills_id_seq\";",
+            ],
+            "{ops:?}"
+        );
+    }
+
+    #[test]
+    fn detection_pairs_unambiguous_renames_only_when_enabled() {
+        let from = parse_sql_string(
+            "CREATE TABLE public.users (id integer, mail text, age integer);
+             CREATE TABLE public.audit (id integer);",
+        )
+        .unwrap();
+        let to = parse_sql_string(
+            "CREATE TABLE public.users (id integer, email text, age integer);
+             CREATE TABLE public.audit_log (id integer);
+             CREATE TABLE public.events (id integer);",
+        )
+        .unwrap();
+        let excluded = HashSet::new();
+        let type_equivalences = TypeEquivalences::default();
+        let options = DiffOptions {
+            manage_ownership: false,
+            manage_grants: false,
+            excluded_grant_roles: &excluded,
+            adoption_mode: AdoptionMode::Full,
+            type_equivalences: &type_equivalences,
+            default_comparison: DefaultComparison::default(),
+            detect_renames: true,
+        };
+
+        let sql = generate_sql(&compute_diff_with_options(&from, &to, &options));
+        assert!(sql.contains(
+            &"ALTER TABLE \"public\".\"users\" RENAME COLUMN \"mail\" TO \"email\";".to_string()
+        ));
+        // `audit` could have become `audit_log` or `events`.
+        assert!(!sql.iter().any(|statement| statement.contains("RENAME TO")));
+
+        let sql = generate_sql(&compute_diff(&from, &to));
+        assert!(!sql.iter().any(|statement| statement.contains("RENAME")));
+    }
+}
@@ -1,7 +1,13 @@
-use crate::model::{Column, Index, Policy, QualifiedName, Table};
-use crate::util::{expressions_semantically_equal, optional_expressions_equal};
+use crate::model::{Column, Identity, Index, Policy, QualifiedName, ReplicaIdentity, Table};
+use crate::util::{
+    expressions_semantically_equal, optional_defaults_leniently_equal, optional_expressions_equal,
+};
 
-use super::{ColumnChanges, MigrationOp, PolicyChanges};
+use super::dependencies::push_policy_recreate_comment;
+use super::{
+    pair_by_key, ColumnChanges, DefaultComparison, IdentityChange, MigrationOp, Paired,
+    PolicyChanges, TypeEquivalences,
+};
 
 pub(super) fn diff_exclusion_constraints(from_table: &Table, to_table: &Table) -> Vec<MigrationOp> {
     let mut ops = Vec::new();
@@ -51,51 +57,62 @@ pub(super) fn diff_exclusion_constraints(from_table: &Table, to_table: &Table) -
     ops
 }
 
-pub(super) fn diff_columns(from_table: &Table, to_table: &Table) -> Vec<MigrationOp> {
+pub(super) fn diff_columns(
+    from_table: &Table,
+    to_table: &Table,
+    type_equivalences: &TypeEquivalences,
+    default_comparison: DefaultComparison,
+) -> Vec<MigrationOp> {
     let mut ops = Vec::new();
-    let qualified_table_name = QualifiedName::new(&to_table.schema, &to_table.name);
-
-    for (name, column) in &to_table.columns {
-        if let Some(from_column) = from_table.columns.get(name) {
-            if is_unmanaged_generated_column(from_column, column) {
-                continue;
+    let mut drops = Vec::new();
+    let to_name = || QualifiedName::new(&to_table.schema, &to_table.name);
+    let from_name = || QualifiedName::new(&from_table.schema, &from_table.name);
+
+    for (name, paired) in pair_by_key(&from_table.columns, &to_table.columns) {
+        match paired {
+            Paired::Both(from_column, column) => {
+                if is_unmanaged_generated_column(from_column, column) {
+                    continue;
+                }
+                if generated_expression_changed(from_column, column) {
+                    ops.push(MigrationOp::DropColumn {
+                        table: from_name(),
+                        column: name.clone(),
+                    });
+                    ops.push(MigrationOp::AddColumn {
+                        table: to_name(),
+                        column: column.clone(),
+                    });
+                } else {
+                    let changes = compute_column_changes(
+                        from_column,
+                        column,
+                        type_equivalences,
+                        default_comparison,
+                    );
+                    if changes.has_changes() {
+                        ops.push(MigrationOp::AlterColumn {
+                            table: to_name(),
+                            column: name.clone(),
+                            changes,
+                        });
+                    }
+                }
             }
-            if generated_expression_changed(from_column, column) {
-                ops.push(MigrationOp::DropColumn {
-                    table: QualifiedName::new(&from_table.schema, &from_table.name),
-                    column: name.clone(),
-                });
+            Paired::OnlyTo(column) => {
                 ops.push(MigrationOp::AddColumn {
-                    table: qualified_table_name.clone(),
+                    table: to_name(),
                     column: column.clone(),
                 });
-            } else {
-                let changes = compute_column_changes(from_column, column);
-                if changes.has_changes() {
-                    ops.push(MigrationOp::AlterColumn {
-                        table: qualified_table_name.clone(),
-                        column: name.clone(),
-                        changes,
-                    });
-                }
             }
-        } else {
-            ops.push(MigrationOp::AddColumn {
-                table: qualified_table_name.clone(),
-                column: column.clone(),
-            });
-        }
-    }
-
-    for name in from_table.columns.keys() {
-        if !to_table.columns.contains_key(name) {
-            ops.push(MigrationOp::DropColumn {
-                table: QualifiedName::new(&from_table.schema, &from_table.name),
+            Paired::OnlyFrom => drops.push(MigrationOp::DropColumn {
+                table: from_name(),
                 column: name.clone(),
-            });
+            }),
         }
     }
 
+    ops.extend(drops);
     ops
 }
 
@@ -114,12 +131,33 @@ fn is_unmanaged_generated_column(from: &Column, to: &Column) -> bool {
     from.generated.is_some() && to.generated.is_none()
 }
 
-pub(super) fn compute_column_changes(from: &Column, to: &Column) -> ColumnChanges {
+pub(super) fn compute_column_changes(
+    from: &Column,
+    to: &Column,
+    type_equivalences: &TypeEquivalences,
+    default_comparison: DefaultComparison,
+) -> ColumnChanges {
+    let defaults_equal = match default_comparison {
+        DefaultComparison::Strict => optional_expressions_equal(&from.default, &to.default),
+        DefaultComparison::Lenient => optional_defaults_leniently_equal(&from.default, &to.default),
+    };
     ColumnChanges {
-        data_type: (from.data_type != to.data_type).then(|| to.data_type.clone()),
+        data_type: (!type_equivalences.equivalent(&from.data_type, &to.data_type))
+            .then(|| to.data_type.clone()),
         nullable: (from.nullable != to.nullable).then_some(to.nullable),
-        default: (!optional_expressions_equal(&from.default, &to.default))
-            .then(|| to.default.clone()),
+        default: (!defaults_equal).then(|| to.default.clone()),
+        statistics: (from.statistics != to.statistics).then_some(to.statistics),
+        compression: (from.compression != to.compression).then(|| to.compression.clone()),
+        identity: identity_change(from.identity.as_ref(), to.identity.as_ref()),
+    }
+}
+
+fn identity_change(from: Option<&Identity>, to: Option<&Identity>) -> Option<IdentityChange> {
+    match (from, to) {
+        (None, Some(to)) => Some(IdentityChange::Add(to.clone())),
+        (Some(_), None) => Some(IdentityChange::Drop),
+        (Some(from), Some(to)) if !to.satisfied_by(from) => Some(IdentityChange::Set(to.clone())),
+        _ => None,
     }
 }
 
@@ -167,7 +205,8 @@ pub(super) fn indexes_semantically_equal(from: &Index, to: &Index) -> bool {
             .all(|(a, b)| a == b || expressions_semantically_equal(a, b))
         && from.unique == to.unique
         && from.index_type == to.index_type
-        && from.is_constraint == to.is_constraint
+        && from.nulls_not_distinct == to.nulls_not_distinct
+        && from.column_options == to.column_options
         && optional_expressions_equal(&from.predicate, &to.predicate)
 }
 
@@ -186,7 +225,10 @@ pub(super) fn diff_indexes(from_table: &Table, to_table: &Table) -> Vec<Migratio
                 });
             }
             Some(from_index) if !indexes_semantically_equal(from_index, index) => {
-                ops.push(drop_index_op(from_qualified_table_name(), from_index));
+                ops.push(MigrationOp::DropIndex {
+                    table: from_qualified_table_name(),
+                    index_name: from_index.name.clone(),
+                });
                 ops.push(MigrationOp::AddIndex {
                     table: qualified_table_name.clone(),
                     index: index.clone(),
@@ -198,25 +240,63 @@ pub(super) fn diff_indexes(from_table: &Table, to_table: &Table) -> Vec<Migratio
 
     for index in &from_table.indexes {
         if !to_table.indexes.iter().any(|i| i.name == index.name) {
-            ops.push(drop_index_op(from_qualified_table_name(), index));
+            ops.push(MigrationOp::DropIndex {
+                table: from_qualified_table_name(),
+                index_name: index.name.clone(),
+            });
         }
     }
 
     ops
 }
 
-fn drop_index_op(table: QualifiedName, index: &Index) -> MigrationOp {
-    if index.is_constraint {
-        MigrationOp::DropUniqueConstraint {
-            table,
-            constraint_name: index.name.clone(),
+/// A changed `UNIQUE` constraint is dropped and added again: PostgreSQL can
+/// only alter the deferral of foreign keys in place.
+pub(super) fn diff_unique_constraints(from_table: &Table, to_table: &Table) -> Vec<MigrationOp> {
+    let mut ops = Vec::new();
+    let qualified_table_name = QualifiedName::new(&to_table.schema, &to_table.name);
+
+    for to_constraint in &to_table.unique_constraints {
+        let matching_from = from_table
+            .unique_constraints
+            .iter()
+            .find(|uc| uc.name == to_constraint.name);
+
+        match matching_from {
+            Some(from_constraint) if from_constraint == to_constraint => {}
+            Some(from_constraint) => {
+                ops.push(MigrationOp::DropUniqueConstraint {
+                    table: qualified_table_name.clone(),
+                    constraint_name: from_constraint.name.clone(),
+                });
+                ops.push(MigrationOp::AddUniqueConstraint {
+                    table: qualified_table_name.clone(),
+                    unique_constraint: to_constraint.clone(),
+                });
+            }
+            None => {
+                ops.push(MigrationOp::AddUniqueConstraint {
+                    table: qualified_table_name.clone(),
+                    unique_constraint: to_constraint.clone(),
+                });
+            }
         }
-    } else {
-        MigrationOp::DropIndex {
-            table,
-            index_name: index.name.clone(),
+    }
+
+    for from_constraint in &from_table.unique_constraints {
+        if !to_table
+            .unique_constraints
+            .iter()
+            .any(|uc| uc.name == from_constraint.name)
+        {
+            ops.push(MigrationOp::DropUniqueConstraint {
+                table: QualifiedName::new(&from_table.schema, &from_table.name),
+                constraint_name: from_constraint.name.clone(),
+            });
         }
     }
+
+    ops
 }
 
 pub(super) fn diff_foreign_keys(from_table: &Table, to_table: &Table) -> Vec<MigrationOp> {
@@ -224,15 +304,27 @@ pub(super) fn diff_foreign_keys(from_table: &Table, to_table: &Table) -> Vec<Mig
     let qualified_table_name = QualifiedName::new(&to_table.schema, &to_table.name);
 
     for foreign_key in &to_table.foreign_keys {
-        if !from_table
+        match from_table
             .foreign_keys
             .iter()
-            .any(|fk| fk.name == foreign_key.name)
+            .find(|fk| fk.name == foreign_key.name)
         {
-            ops.push(MigrationOp::AddForeignKey {
+            None => ops.push(MigrationOp::AddForeignKey {
                 table: qualified_table_name.clone(),
                 foreign_key: foreign_key.clone(),
-            });
+            }),
+            Some(existing)
+                if existing.deferrable != foreign_key.deferrable
+                    || existing.initially_deferred != foreign_key.initially_deferred =>
+            {
+                ops.push(MigrationOp::AlterForeignKeyDeferral {
+                    table: qualified_table_name.clone(),
+                    foreign_key_name: foreign_key.name.clone(),
+                    deferrable: foreign_key.deferrable,
+                    initially_deferred: foreign_key.initially_deferred,
+                });
+            }
+            Some(_) => {}
         }
     }
 
@@ -334,12 +426,48 @@ pub(super) fn diff_force_rls(from_table: &Table, to_table: &Table) -> Vec<Migrat
     ops
 }
 
+/// `index_ops` are the table's index changes: an index that is dropped and
+/// recreated loses its place as the replica identity, so it is set again.
+pub(super) fn diff_replica_identity(
+    from_table: &Table,
+    to_table: &Table,
+    index_ops: &[MigrationOp],
+) -> Vec<MigrationOp> {
+    let index_recreated = match &to_table.replica_identity {
+        Some(ReplicaIdentity::Index(index)) => index_ops.iter().any(|op| {
+            matches!(
+                op,
+                MigrationOp::DropIndex { index_name, .. }
+                | MigrationOp::DropUniqueConstraint { constraint_name: index_name, .. }
+                    if index_name == index
+            )
+        }),
+        _ => false,
+    };
+    if from_table.replica_identity == to_table.replica_identity && !index_recreated {
+        return Vec::new();
+    }
+    vec![MigrationOp::SetReplicaIdentity {
+        table: QualifiedName::new(&to_table.schema, &to_table.name),
+        identity: to_table.replica_identity.clone(),
+    }]
+}
+
 pub(super) fn diff_policies(from_table: &Table, to_table: &Table) -> Vec<MigrationOp> {
     let mut ops = Vec::new();
     let qualified_table_name = QualifiedName::new(&to_table.schema, &to_table.name);
 
     for policy in &to_table.policies {
         if let Some(from_policy) = from_table.policies.iter().find(|p| p.name == policy.name) {
+            if policy_needs_recreate(from_policy, policy) {
+                ops.push(MigrationOp::DropPolicy {
+                    table: qualified_table_name.clone(),
+                    name: policy.name.clone(),
+                });
+                ops.push(MigrationOp::CreatePolicy(policy.clone()));
+                push_policy_recreate_comment(&mut ops, policy);
+                continue;
+            }
             let changes = compute_policy_changes(from_policy, policy);
             if changes.has_changes() {
                 ops.push(MigrationOp::AlterPolicy {
@@ -365,6 +493,17 @@ pub(super) fn diff_policies(from_table: &Table, to_table: &Table) -> Vec<Migrati
     ops
 }
 
+/// Whether `from` can only become `to` by dropping and recreating the policy.
+/// `ALTER POLICY` cannot change the command or remove a `USING` or
+/// `WITH CHECK` expression, and pgmold only alters roles to a non-empty list,
+/// so emptying the list (back to the PUBLIC default) also recreates.
+fn policy_needs_recreate(from: &Policy, to: &Policy) -> bool {
+    from.command != to.command
+        || (to.roles.is_empty() && !from.roles.is_empty())
+        || (from.using_expr.is_some() && to.using_expr.is_none())
+        || (from.check_expr.is_some() && to.check_expr.is_none())
+}
+
 pub(super) fn compute_policy_changes(from: &Policy, to: &Policy) -> PolicyChanges {
     PolicyChanges {
         roles: (from.roles != to.roles).then(|| to.roles.clone()),
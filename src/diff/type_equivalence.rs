@@ -0,0 +1,151 @@
+//! Column types to treat as the same when diffing, so a type written one way
+//! in the sources and reported another way by the database (or a domain
+//! standing in for its base type) does not produce an `AlterColumn`.
+//!
+//! Built-in aliases such as `int4` and `integer` always match. Further rules
+//! come from the `[type_equivalence]` table in `.pgmold.toml`, which the CLI
+//! exports as `PGMOLD_TYPE_EQUIVALENCE`.
+
+use std::collections::BTreeMap;
+
+use crate::model::{normalize_pg_type, PgType};
+
+/// Type names mapped to the type they are equivalent to. Names are compared
+/// after alias normalization and include any modifiers, so
+/// `"character varying(255)"` and `"character varying"` are separate rules.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TypeEquivalences {
+    rules: BTreeMap<String, String>,
+}
+
+impl TypeEquivalences {
+    pub fn new<'a>(rules: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        Self {
+            rules: rules
+                .into_iter()
+                .map(|(alias, target)| {
+                    (
+                        normalize_pg_type(alias).into_owned(),
+                        normalize_pg_type(target).into_owned(),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        std::env::var("PGMOLD_TYPE_EQUIVALENCE")
+            .map(|spec| Self::parse(&spec))
+            .unwrap_or_default()
+    }
+
+    /// Parses `alias=target;alias=target` as written to
+    /// `PGMOLD_TYPE_EQUIVALENCE`. Entries are separated by `;` since type
+    /// modifiers such as `numeric(10,2)` contain commas. Entries without `=`
+    /// are ignored.
+    pub fn parse(spec: &str) -> Self {
+        Self::new(spec.split(';').filter_map(|entry| entry.split_once('=')))
+    }
+
+    /// Identifies these rules in cache keys.
+    pub fn cache_key(&self) -> String {
+        self.rules
+            .iter()
+            .map(|(alias, target)| format!("{alias}={target}"))
+            .collect::<Vec<_>>()
+            .join(";")
+    }
+
+    /// Whether a column of type `from` already is of type `to` as far as the
+    /// diff is concerned.
+    pub fn equivalent(&self, from: &PgType, to: &PgType) -> bool {
+        if from == to {
+            return true;
+        }
+        let (from, to) = (type_name(from), type_name(to));
+        from == to || self.resolve(&from) == self.resolve(&to)
+    }
+
+    fn resolve<'a>(&'a self, name: &'a str) -> &'a str {
+        self.rules.get(name).map_or(name, String::as_str)
+    }
+}
+
+/// The name PostgreSQL reports for `pg_type`, in the normalized form
+/// [`normalize_pg_type`] gives.
+fn type_name(pg_type: &PgType) -> String {
+    let with_length = |name: &str, length: &Option<u32>| match length {
+        Some(length) => format!("{name}({length})"),
+        None => name.to_string(),
+    };
+    match pg_type {
+        PgType::Integer => "integer".to_string(),
+        PgType::BigInt => "bigint".to_string(),
+        PgType::SmallInt => "smallint".to_string(),
+        PgType::Real => "real".to_string(),
+        PgType::DoublePrecision => "double precision".to_string(),
+        PgType::Varchar(length) => with_length("character varying", length),
+        PgType::Char(length) => with_length("character", &length.or(Some(1))),
+        PgType::Text => "text".to_string(),
+        PgType::Boolean => "boolean".to_string(),
+        PgType::TimestampTz => "timestamp with time zone".to_string(),
+        PgType::Timestamp => "timestamp without time zone".to_string(),
+        PgType::TimeTz => "time with time zone".to_string(),
+        PgType::Time => "time without time zone".to_string(),
+        PgType::Date => "date".to_string(),
+        PgType::Interval => "interval".to_string(),
+        PgType::Bytea => "bytea".to_string(),
+        PgType::Uuid => "uuid".to_string(),
+        PgType::Json => "json".to_string(),
+        PgType::Jsonb => "jsonb".to_string(),
+        PgType::Inet => "inet".to_string(),
+        PgType::Cidr => "cidr".to_string(),
+        PgType::Macaddr => "macaddr".to_string(),
+        PgType::Macaddr8 => "macaddr8".to_string(),
+        PgType::Point => "point".to_string(),
+        PgType::Xml => "xml".to_string(),
+        PgType::Vector(dimensions) => with_length("vector", dimensions),
+        PgType::Geometry(..) | PgType::Geography(..) => format!("{pg_type:?}"),
+        PgType::Array(inner) => format!("{}[]", type_name(inner)),
+        PgType::UserDefined(name) | PgType::BuiltinNamed(name) => {
+            normalize_pg_type(name).into_owned()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_aliases_are_equivalent() {
+        let none = TypeEquivalences::default();
+        assert!(none.equivalent(&PgType::BuiltinNamed("int4".to_string()), &PgType::Integer));
+        assert!(none.equivalent(
+            &PgType::BuiltinNamed("varchar".to_string()),
+            &PgType::Varchar(None)
+        ));
+        assert!(!none.equivalent(&PgType::Varchar(Some(255)), &PgType::Varchar(None)));
+        assert!(!none.equivalent(&PgType::Text, &PgType::Varchar(None)));
+    }
+
+    #[test]
+    fn configured_rules_match_after_normalization() {
+        let rules = TypeEquivalences::parse("public.email_address=text; varchar = text");
+        assert!(rules.equivalent(
+            &PgType::UserDefined("public.email_address".to_string()),
+            &PgType::Text
+        ));
+        assert!(rules.equivalent(&PgType::Text, &PgType::Varchar(None)));
+        assert!(!rules.equivalent(&PgType::Text, &PgType::Varchar(Some(255))));
+    }
+
+    #[test]
+    fn aliases_of_one_target_are_equivalent_to_each_other() {
+        let rules = TypeEquivalences::parse("email_address=text;varchar=text");
+        assert!(rules.equivalent(
+            &PgType::UserDefined("email_address".to_string()),
+            &PgType::Varchar(None)
+        ));
+    }
+}
@@ -1,19 +1,57 @@
 use std::collections::HashSet;
 
+use serde::{Deserialize, Serialize};
+
+use super::TypeEquivalences;
+
 use crate::model::{
     Aggregate, CheckConstraint, Column, Domain, EnumType, ExclusionConstraint, Extension,
-    ForeignKey, Function, Index, Partition, PgSchema, PgType, Policy, PrimaryKey, Privilege,
-    QualifiedName, Sequence, SequenceDataType, SequenceOwner, Server, Table, Trigger,
-    TriggerEnabled, VersionView, View,
+    ForeignKey, Function, Identity, Index, Partition, PgSchema, PgType, Policy, PrimaryKey,
+    Privilege, QualifiedName, RawObject, ReplicaIdentity, Sequence, SequenceDataType,
+    SequenceOwner, Server, Table, Trigger, TriggerEnabled, UniqueConstraint, VersionView, View,
+    ViewCheckOption,
 };
 
 pub struct DiffOptions<'a> {
     pub manage_ownership: bool,
     pub manage_grants: bool,
     pub excluded_grant_roles: &'a HashSet<String>,
+    pub adoption_mode: AdoptionMode,
+    /// Column types to treat as the same when comparing columns.
+    pub type_equivalences: &'a TypeEquivalences,
+    pub default_comparison: DefaultComparison,
+    /// Plan a table or column that disappears as a rename of one that
+    /// appears with the same columns or type, without a `-- pgmold:rename`
+    /// hint.
+    pub detect_renames: bool,
+}
+
+/// How the diff treats what the database has but the target schema does not
+/// declare.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AdoptionMode {
+    /// The target schema is authoritative: undeclared objects are dropped.
+    #[default]
+    Full,
+    /// Only declared objects are managed, for adopting pgmold incrementally.
+    /// Nothing the target leaves out is dropped, and row level security,
+    /// privileges and comments are never removed.
+    Additive,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// How column defaults are compared.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DefaultComparison {
+    /// Defaults match when they are the same expression once the casts and
+    /// parentheses PostgreSQL adds are stripped.
+    Strict,
+    /// Also matches defaults that only differ in spelling, such as `now()`
+    /// and `CURRENT_TIMESTAMP`, or `'x'::character varying` and `'x'`.
+    #[default]
+    Lenient,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum CommentObjectType {
     Table,
     Column,
@@ -36,7 +74,7 @@ pub enum CommentObjectType {
     Constraint,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum OwnerObjectKind {
     Table,
     Partition,
@@ -49,7 +87,7 @@ pub enum OwnerObjectKind {
     Domain,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum GrantObjectKind {
     Table,
     View,
@@ -61,12 +99,30 @@ pub enum GrantObjectKind {
     Domain,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MigrationOp {
     CreateSchema(PgSchema),
-    DropSchema(String),
+    DropSchema {
+        name: String,
+        /// `CASCADE`: also drop whatever the schema still contains.
+        cascade: bool,
+    },
     CreateExtension(Extension),
     DropExtension(String),
+    /// `ALTER EXTENSION ... UPDATE TO` the declared version.
+    UpdateExtension {
+        name: String,
+        from_version: Option<String>,
+        version: String,
+    },
+    /// `ALTER EXTENSION ... SET SCHEMA` to the declared schema. `relocatable`
+    /// is false when the installed extension cannot be moved, which lint
+    /// rejects.
+    SetExtensionSchema {
+        name: String,
+        schema: String,
+        relocatable: bool,
+    },
     CreateServer(Server),
     DropServer(String),
     AlterServer {
@@ -118,6 +174,10 @@ pub enum MigrationOp {
         table: QualifiedName,
         index_name: String,
     },
+    AddUniqueConstraint {
+        table: QualifiedName,
+        unique_constraint: UniqueConstraint,
+    },
     DropUniqueConstraint {
         table: QualifiedName,
         constraint_name: String,
@@ -130,6 +190,14 @@ pub enum MigrationOp {
         table: QualifiedName,
         foreign_key_name: String,
     },
+    /// Changes only whether a foreign key's check may be deferred, which
+    /// `ALTER CONSTRAINT` does in place.
+    AlterForeignKeyDeferral {
+        table: QualifiedName,
+        foreign_key_name: String,
+        deferrable: bool,
+        initially_deferred: bool,
+    },
     AddCheckConstraint {
         table: QualifiedName,
         check_constraint: CheckConstraint,
@@ -158,6 +226,11 @@ pub enum MigrationOp {
     NoForceRls {
         table: QualifiedName,
     },
+    /// `ALTER TABLE ... REPLICA IDENTITY`; `None` resets it to `DEFAULT`.
+    SetReplicaIdentity {
+        table: QualifiedName,
+        identity: Option<ReplicaIdentity>,
+    },
     CreatePolicy(Policy),
     DropPolicy {
         table: QualifiedName,
@@ -192,6 +265,15 @@ pub enum MigrationOp {
         name: String,
         new_view: View,
     },
+    AlterViewOptions {
+        name: String,
+        changes: ViewOptionChanges,
+    },
+    /// `REFRESH MATERIALIZED VIEW`, populating a materialized view created
+    /// `WITH NO DATA`.
+    RefreshMaterializedView {
+        name: String,
+    },
     CreateTrigger(Trigger),
     DropTrigger {
         target_schema: String,
@@ -210,6 +292,11 @@ pub enum MigrationOp {
         name: String,
         changes: SequenceChanges,
     },
+    /// Moves a sequence to the value the sources recorded with `setval`.
+    SetSequenceValue {
+        name: String,
+        value: i64,
+    },
     AlterOwner {
         object_kind: OwnerObjectKind,
         schema: String,
@@ -217,6 +304,37 @@ pub enum MigrationOp {
         args: Option<String>,
         new_owner: String,
     },
+    /// `ALTER ... SET SCHEMA`, moving an object a `-- pgmold:set-schema` hint
+    /// names rather than dropping and recreating it. `schema` is where it is
+    /// now.
+    SetSchema {
+        object_kind: OwnerObjectKind,
+        schema: String,
+        name: String,
+        args: Option<String>,
+        new_schema: String,
+    },
+    /// `ALTER TABLE ... RENAME TO`, for a table a `-- pgmold:rename` hint
+    /// names or rename detection matched by its columns. `table` is its
+    /// current name.
+    RenameTable {
+        table: QualifiedName,
+        new_name: String,
+    },
+    /// `ALTER SEQUENCE ... RENAME TO`, for the sequence of a serial column
+    /// whose table or column is renamed, so it takes the name `serial` gives
+    /// it in the sources.
+    RenameSequence {
+        sequence: QualifiedName,
+        new_name: String,
+    },
+    /// `ALTER TABLE ... RENAME COLUMN`. `table` is the table's name once any
+    /// `RenameTable` has run.
+    RenameColumn {
+        table: QualifiedName,
+        column: String,
+        new_name: String,
+    },
     BackfillHint {
         table: QualifiedName,
         column: String,
@@ -226,6 +344,38 @@ pub enum MigrationOp {
         table: QualifiedName,
         column: String,
     },
+    /// `CREATE INDEX CONCURRENTLY`, emitted by the zero-downtime plan and by
+    /// plans with `PlanOptions::concurrent_indexes` so a large table is
+    /// indexed without blocking writes. Never produced by the diff and cannot
+    /// run inside a transaction.
+    CreateIndexConcurrently {
+        table: QualifiedName,
+        index: Index,
+    },
+    /// `DROP INDEX CONCURRENTLY`, the counterpart of `CreateIndexConcurrently`
+    /// for `DropIndex`. Never produced by the diff and cannot run inside a
+    /// transaction.
+    DropIndexConcurrently {
+        table: QualifiedName,
+        index_name: String,
+    },
+    /// `ADD CONSTRAINT ... PRIMARY KEY USING INDEX`, attaching a primary key
+    /// to an existing unique index without scanning the table. Emitted by the
+    /// zero-downtime plan in place of `AddPrimaryKey`.
+    AddPrimaryKeyUsingIndex {
+        table: QualifiedName,
+        index_name: String,
+    },
+    /// `ADD CONSTRAINT ... UNIQUE USING INDEX`, turning a unique index built
+    /// `CONCURRENTLY` into the constraint without scanning the table again.
+    /// Emitted by [`crate::plan::with_concurrent_indexes`] in place of adding
+    /// the constraint directly.
+    AddUniqueConstraintUsingIndex {
+        table: QualifiedName,
+        index_name: String,
+        deferrable: bool,
+        initially_deferred: bool,
+    },
     GrantPrivileges {
         object_kind: GrantObjectKind,
         schema: String,
@@ -285,9 +435,31 @@ pub enum MigrationOp {
         version_schema: String,
         name: String,
     },
+
+    /// Runs a raw object's SQL and records its content hash, for a raw object
+    /// that is new or whose content changed.
+    ApplyRawObject(RawObject),
+    /// Forgets a raw object the sources no longer declare. Its SQL cannot be
+    /// undone automatically, so whatever it created is left in place.
+    DropRawObject(String),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ViewOptionChanges {
+    pub security_barrier: Option<bool>,
+    pub security_invoker: Option<bool>,
+    pub check_option: Option<Option<ViewCheckOption>>,
+}
+
+impl ViewOptionChanges {
+    pub fn has_changes(&self) -> bool {
+        self.security_barrier.is_some()
+            || self.security_invoker.is_some()
+            || self.check_option.is_some()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PolicyChanges {
     pub roles: Option<Vec<String>>,
     pub using_expr: Option<Option<String>>,
@@ -300,20 +472,49 @@ impl PolicyChanges {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ColumnChanges {
     pub data_type: Option<PgType>,
     pub nullable: Option<bool>,
     pub default: Option<Option<String>>,
+    #[serde(default)]
+    pub statistics: Option<Option<i32>>,
+    #[serde(default)]
+    pub compression: Option<Option<String>>,
+    #[serde(default)]
+    pub identity: Option<IdentityChange>,
+}
+
+/// How an existing column's identity changes: `ADD GENERATED ... AS
+/// IDENTITY`, `DROP IDENTITY`, or `SET GENERATED` and its sequence options.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IdentityChange {
+    Add(Identity),
+    Drop,
+    Set(Identity),
 }
 
 impl ColumnChanges {
     pub fn has_changes(&self) -> bool {
-        self.data_type.is_some() || self.nullable.is_some() || self.default.is_some()
+        self.data_type.is_some()
+            || self.nullable.is_some()
+            || self.default.is_some()
+            || self.statistics.is_some()
+            || self.compression.is_some()
+            || self.identity.is_some()
+    }
+
+    /// Only the default, statistics target, compression method or identity
+    /// changes: `SET DEFAULT`, `DROP DEFAULT`, `SET STATISTICS`, `SET
+    /// COMPRESSION` and the identity forms update the catalog without
+    /// rewriting or scanning the table, and nothing that depends on the
+    /// column has to be dropped around it.
+    pub fn is_catalog_only(&self) -> bool {
+        self.has_changes() && self.data_type.is_none() && self.nullable.is_none()
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct DomainChanges {
     pub default: Option<Option<String>>,
     pub not_null: Option<bool>,
@@ -325,7 +526,7 @@ impl DomainChanges {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct SequenceChanges {
     pub data_type: Option<SequenceDataType>,
     pub increment: Option<i64>,
@@ -350,7 +551,7 @@ impl SequenceChanges {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EnumValuePosition {
     Before(String),
     After(String),
@@ -0,0 +1,427 @@
+//! Accepting drift on one object into the schema sources: the statements
+//! declaring the object are rewritten from its definition in the database,
+//! leaving the rest of its file and every other source as they were.
+
+use crate::dump::generate_dump;
+use crate::model::{parse_qualified_name, qualified_name, Schema};
+use crate::parser::{split_statements, SqlStatement};
+use crate::pg::connection::PgConnection;
+use crate::pg::introspect::introspect_schema;
+use crate::plan::isolate;
+use crate::provider::locate_sources;
+use crate::util::{Result, SchemaError};
+use regex::Regex;
+use sqlparser::dialect::PostgreSqlDialect;
+use sqlparser::tokenizer::{Token, Tokenizer};
+use std::path::Path;
+
+/// The new content of the source file declaring an accepted object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Acceptance {
+    /// Qualified name of the object.
+    pub object: String,
+    /// The file declaring it.
+    pub file: String,
+    /// The file's content with the object's statements rewritten.
+    pub content: String,
+    /// True when the database no longer has the object, so its statements
+    /// were removed rather than rewritten.
+    pub dropped: bool,
+}
+
+/// Introspects `target_schemas` and accepts the database's definition of
+/// `object` into the `sql:` source declaring it. See [`accept_object`].
+pub async fn accept_drift(
+    object: &str,
+    schema_sources: &[String],
+    conn: &PgConnection,
+    target_schemas: &[String],
+) -> Result<Acceptance> {
+    let (schema, _) = parse_qualified_name(object);
+    if !target_schemas.contains(&schema) {
+        return Err(SchemaError::ValidationError(format!(
+            "{object} is outside the target schemas ({})",
+            target_schemas.join(", ")
+        )));
+    }
+    let actual = introspect_schema(conn, target_schemas, false).await?;
+    accept_object(object, schema_sources, &actual)
+}
+
+/// Rewrites the statements declaring `object` in its source file from its
+/// definition in `actual`: the first is replaced by the object as `dump`
+/// would write it, along with its triggers, and the rest are removed. An
+/// unqualified `object` is looked up in the `public` schema. Statements about
+/// the object in other files are left alone.
+pub fn accept_object(object: &str, sources: &[String], actual: &Schema) -> Result<Acceptance> {
+    let (schema, name) = parse_qualified_name(object);
+    let object = qualified_name(&schema, &name);
+    let locations = locate_sources(sources)?;
+    let location = locations.get(&object).ok_or_else(|| {
+        SchemaError::ValidationError(format!(
+            "{object} is not declared in the sql: schema sources; add it with `pgmold dump` first"
+        ))
+    })?;
+    if !Path::new(&location.file).is_file() {
+        return Err(SchemaError::ValidationError(format!(
+            "{object} is declared in {}, which cannot be rewritten",
+            location.file
+        )));
+    }
+    let current = std::fs::read_to_string(&location.file)
+        .map_err(|e| SchemaError::ParseError(format!("Cannot read {}: {e}", location.file)))?;
+
+    let definition = generate_dump(&isolate(actual, &object), None);
+    let content =
+        replace_object_statements(&current, &object, definition.trim_end()).ok_or_else(|| {
+            SchemaError::ValidationError(format!(
+                "No statement in {} declares {object}",
+                location.file
+            ))
+        })?;
+    Ok(Acceptance {
+        file: location.file.clone(),
+        dropped: definition.is_empty(),
+        object,
+        content,
+    })
+}
+
+/// `content` with its first statement about `object` replaced by
+/// `definition` and the others removed, or None when it has none. An empty
+/// `definition` removes them all.
+fn replace_object_statements(content: &str, object: &str, definition: &str) -> Option<String> {
+    let target = parse_qualified_name(object);
+    let header_end = Regex::new(r"(?i)\(|\bAS\b").expect("valid regex");
+    let matching: Vec<SqlStatement> = split_statements(content)
+        .into_iter()
+        .filter(|statement| {
+            let header = header_end
+                .find(&statement.code)
+                .map_or(statement.code.as_str(), |end| {
+                    &statement.code[..end.start()]
+                });
+            statement_target(header).as_ref() == Some(&target)
+        })
+        .collect();
+    if matching.is_empty() {
+        return None;
+    }
+
+    let bytes = content.as_bytes();
+    let mut rewritten = String::with_capacity(content.len() + definition.len());
+    let mut cursor = 0;
+    for (index, statement) in matching.iter().enumerate() {
+        if index == 0 && !definition.is_empty() {
+            rewritten.push_str(&content[cursor..statement.start]);
+            rewritten.push_str(definition);
+            cursor = statement.end;
+            continue;
+        }
+        // Take the removed statement's whole line, and the blank line after
+        // it, so the statements around it keep their spacing.
+        let mut start = statement.start;
+        while start > cursor && matches!(bytes[start - 1], b' ' | b'\t') {
+            start -= 1;
+        }
+        let mut end = skip_line_end(bytes, statement.end);
+        if end > statement.end {
+            let after_blank = skip_line_end(bytes, end);
+            if after_blank > end && content[end..after_blank].trim().is_empty() {
+                end = after_blank;
+            }
+        }
+        rewritten.push_str(&content[cursor..start]);
+        cursor = end;
+    }
+    rewritten.push_str(&content[cursor..]);
+    Some(rewritten)
+}
+
+/// Words that may come between `CREATE`, `ALTER` or `COMMENT ON` and the kind
+/// of object.
+const MODIFIERS: &[&str] = &[
+    "OR",
+    "REPLACE",
+    "TEMP",
+    "TEMPORARY",
+    "UNLOGGED",
+    "MATERIALIZED",
+    "RECURSIVE",
+    "UNIQUE",
+    "CONSTRAINT",
+    "GLOBAL",
+    "LOCAL",
+    "FOREIGN",
+];
+
+/// Kinds of object a `GRANT` or `REVOKE` may name before the object.
+const GRANT_KINDS: &[&str] = &[
+    "TABLE",
+    "SEQUENCE",
+    "FUNCTION",
+    "PROCEDURE",
+    "ROUTINE",
+    "SCHEMA",
+    "TYPE",
+    "DOMAIN",
+];
+
+/// The object `header`, a statement up to its first parenthesis or `AS`, is
+/// about, as `(schema, name)`: what it creates, alters, comments on, grants
+/// or revokes, or the table of the index, trigger, policy or column it
+/// names. Other names in the statement, such as a trigger's function, are
+/// not its target.
+fn statement_target(header: &str) -> Option<(String, String)> {
+    let tokens: Vec<Token> = Tokenizer::new(&PostgreSqlDialect {}, header)
+        .tokenize()
+        .ok()?
+        .into_iter()
+        .filter(|token| !matches!(token, Token::Whitespace(_)))
+        .collect();
+    let keyword = |index: usize| match tokens.get(index) {
+        Some(Token::Word(word)) if word.quote_style.is_none() => word.value.to_uppercase(),
+        _ => String::new(),
+    };
+    let after = |from: usize, wanted: &str| {
+        (from..tokens.len())
+            .find(|&index| keyword(index) == wanted)
+            .map(|index| index + 1)
+    };
+    let skip_modifiers = |mut index: usize| {
+        while MODIFIERS.contains(&keyword(index).as_str()) {
+            index += 1;
+        }
+        index
+    };
+
+    let mut column = false;
+    let mut index = match keyword(0).as_str() {
+        "CREATE" => {
+            let kind = skip_modifiers(1);
+            match keyword(kind).as_str() {
+                "INDEX" | "TRIGGER" | "POLICY" => after(kind, "ON")?,
+                _ => kind + 1,
+            }
+        }
+        "ALTER" => skip_modifiers(1) + 1,
+        "COMMENT" if keyword(1) == "ON" => match keyword(2).as_str() {
+            "TRIGGER" | "POLICY" | "RULE" | "CONSTRAINT" => after(3, "ON")?,
+            "COLUMN" => {
+                column = true;
+                3
+            }
+            _ => skip_modifiers(2) + 1,
+        },
+        "GRANT" | "REVOKE" => {
+            let on = after(1, "ON")?;
+            if GRANT_KINDS.contains(&keyword(on).as_str()) {
+                on + 1
+            } else {
+                on
+            }
+        }
+        _ => return None,
+    };
+    while matches!(
+        keyword(index).as_str(),
+        "IF" | "NOT" | "EXISTS" | "ONLY" | "CONCURRENTLY"
+    ) {
+        index += 1;
+    }
+
+    let mut parts = Vec::new();
+    while let Some(Token::Word(word)) = tokens.get(index) {
+        parts.push(if word.quote_style.is_some() {
+            word.value.clone()
+        } else {
+            word.value.to_lowercase()
+        });
+        if tokens.get(index + 1) != Some(&Token::Period) {
+            break;
+        }
+        index += 2;
+    }
+    if column {
+        parts.pop();
+    }
+    match parts.as_slice() {
+        [name] => Some(("public".to_string(), name.clone())),
+        [schema, name] => Some((schema.clone(), name.clone())),
+        _ => None,
+    }
+}
+
+/// Offset past the spaces and line break after `offset`, or `offset` itself
+/// when anything else follows on that line.
+fn skip_line_end(bytes: &[u8], offset: usize) -> usize {
+    let mut index = offset;
+    while index < bytes.len() && matches!(bytes[index], b' ' | b'\t' | b'\r') {
+        index += 1;
+    }
+    if bytes.get(index) == Some(&b'\n') {
+        index + 1
+    } else if index == bytes.len() {
+        index
+    } else {
+        offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_sql_string;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const SOURCE: &str = "-- Accounts
+CREATE TABLE users (
+    id bigint PRIMARY KEY,
+    email text
+);
+
+CREATE INDEX users_email_idx ON users (email);
+
+CREATE TABLE posts (
+    id bigint PRIMARY KEY,
+    author_id bigint REFERENCES users (id)
+);
+
+COMMENT ON COLUMN users.email IS 'Login; never shared';
+
+CREATE VIEW user_emails AS SELECT email FROM users;
+";
+
+    #[test]
+    fn rewrites_only_the_statements_about_the_object() {
+        let rewritten =
+            replace_object_statements(SOURCE, "public.users", "CREATE TABLE users ();").unwrap();
+        assert_eq!(
+            rewritten,
+            "-- Accounts
+CREATE TABLE users ();
+
+CREATE TABLE posts (
+    id bigint PRIMARY KEY,
+    author_id bigint REFERENCES users (id)
+);
+
+CREATE VIEW user_emails AS SELECT email FROM users;
+"
+        );
+        assert_eq!(
+            replace_object_statements(SOURCE, "public.orders", "CREATE TABLE orders ();"),
+            None
+        );
+        assert_eq!(
+            replace_object_statements(SOURCE, "auth.users", "CREATE TABLE auth.users ();"),
+            None
+        );
+    }
+
+    #[test]
+    fn escape_strings_do_not_split_statements() {
+        let source = r"COMMENT ON TABLE users IS E'it\'s; -- not a comment';
+CREATE TABLE orders (id bigint);
+CREATE FUNCTION add_one(integer) RETURNS integer LANGUAGE sql RETURN $1 + 1;
+CREATE TABLE users (id bigint);
+";
+        let rewritten =
+            replace_object_statements(source, "public.orders", "CREATE TABLE orders ();").unwrap();
+        assert_eq!(
+            rewritten,
+            r"COMMENT ON TABLE users IS E'it\'s; -- not a comment';
+CREATE TABLE orders ();
+CREATE FUNCTION add_one(integer) RETURNS integer LANGUAGE sql RETURN $1 + 1;
+CREATE TABLE users (id bigint);
+"
+        );
+        let rewritten =
+            replace_object_statements(source, "public.users", "CREATE TABLE users ();").unwrap();
+        assert_eq!(
+            rewritten,
+            r"CREATE TABLE users ();
+CREATE TABLE orders (id bigint);
+CREATE FUNCTION add_one(integer) RETURNS integer LANGUAGE sql RETURN $1 + 1;
+"
+        );
+    }
+
+    #[test]
+    fn triggers_belong_to_their_table_not_their_function() {
+        let source = "CREATE FUNCTION audit() RETURNS trigger LANGUAGE plpgsql AS $$ BEGIN RETURN NEW; END $$;
+
+CREATE TRIGGER audit AFTER UPDATE ON users FOR EACH ROW EXECUTE FUNCTION audit();
+";
+        let definition = "CREATE FUNCTION audit() RETURNS trigger LANGUAGE plpgsql AS $$ BEGIN RETURN NULL; END $$;";
+        let rewritten = replace_object_statements(source, "public.audit", definition).unwrap();
+        assert!(
+            rewritten.contains("CREATE TRIGGER audit AFTER UPDATE ON users"),
+            "{rewritten}"
+        );
+        assert!(rewritten.starts_with(definition), "{rewritten}");
+    }
+
+    #[test]
+    fn accepts_the_database_definition_into_the_declaring_file() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("users.sql"), SOURCE).unwrap();
+        fs::write(
+            dir.path().join("other.sql"),
+            "CREATE TABLE orders (id bigint);\n",
+        )
+        .unwrap();
+        let sources = vec![format!("sql:{}", dir.path().display())];
+        let actual = parse_sql_string(
+            "CREATE TABLE users (id bigint PRIMARY KEY, email text NOT NULL, name text);
+             CREATE TABLE orders (id bigint, total numeric);",
+        )
+        .unwrap();
+
+        let accepted = accept_object("users", &sources, &actual).unwrap();
+        assert_eq!(accepted.object, "public.users");
+        assert!(accepted.file.ends_with("users.sql"));
+        assert!(!accepted.dropped);
+        assert!(!accepted.content.contains("users_email_idx"));
+        assert!(accepted.content.contains("\"email\" TEXT NOT NULL"));
+        assert!(accepted.content.contains("CREATE TABLE posts ("));
+        let reparsed = parse_sql_string(&accepted.content).unwrap();
+        assert_eq!(
+            reparsed.tables["public.users"],
+            actual.tables["public.users"]
+        );
+
+        let dropped = accept_object("users", &sources, &Schema::new()).unwrap();
+        assert!(dropped.dropped);
+        assert!(!dropped.content.contains("CREATE TABLE users"));
+
+        assert!(accept_object("public.missing", &sources, &actual).is_err());
+    }
+
+    #[test]
+    fn keeps_the_comments_the_database_has() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("users.sql"), SOURCE).unwrap();
+        let sources = vec![format!("sql:{}", dir.path().display())];
+        let actual = parse_sql_string(
+            "CREATE TABLE users (id bigint PRIMARY KEY, email text NOT NULL);
+             COMMENT ON COLUMN users.email IS 'Login; never shared';",
+        )
+        .unwrap();
+
+        let accepted = accept_object("users", &sources, &actual).unwrap();
+        assert!(
+            accepted.content.contains("COMMENT ON COLUMN"),
+            "{}",
+            accepted.content
+        );
+        let reparsed = parse_sql_string(&accepted.content).unwrap();
+        assert_eq!(
+            reparsed.tables["public.users"].columns["email"]
+                .comment
+                .as_deref(),
+            Some("Login; never shared")
+        );
+    }
+}
@@ -1,10 +1,19 @@
 use crate::diff::{compute_diff, MigrationOp};
-use crate::filter::filter_by_target_schemas;
+use crate::filter::{exclude_external, filter_by_target_schemas};
+use crate::model::Schema;
 use crate::pg::connection::PgConnection;
-use crate::pg::introspect::introspect_schema;
+use crate::pg::introspect::{introspect_schema, introspect_schema_with_known_bodies};
+use crate::pg::unmanaged::{count_unmanaged_objects, UnmanagedObjects};
 use crate::provider::load_schema_from_sources;
-use crate::util::Result;
-use serde::Serialize;
+use crate::util::{Result, SchemaError};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+mod accept;
+
+pub use accept::{accept_drift, accept_object, Acceptance};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct DriftReport {
@@ -13,6 +22,9 @@ pub struct DriftReport {
     pub actual_fingerprint: String,
     #[serde(skip_serializing)]
     pub differences: Vec<MigrationOp>,
+    /// Objects the comparison could not cover because pgmold does not manage
+    /// their kind. Only filled in when the database was introspected here.
+    pub unmanaged_objects: Vec<UnmanagedObjects>,
 }
 
 pub async fn detect_drift(
@@ -22,21 +34,177 @@ pub async fn detect_drift(
 ) -> Result<DriftReport> {
     let expected = load_schema_from_sources(schema_sources)?;
     let expected = filter_by_target_schemas(&expected, target_schemas);
-    let actual = introspect_schema(conn, target_schemas, false).await?;
+    let actual =
+        introspect_schema_with_known_bodies(conn, target_schemas, false, &expected).await?;
+    let mut report = compare_schemas(&actual, &expected);
+    report.unmanaged_objects = count_unmanaged_objects(conn, target_schemas).await?;
+    Ok(report)
+}
 
+/// Reports how `actual` differs from `expected`, leaving out the objects
+/// `expected` declares external.
+pub fn compare_schemas(actual: &Schema, expected: &Schema) -> DriftReport {
     let expected_fingerprint = expected.fingerprint();
     let actual_fingerprint = actual.fingerprint();
     // ⚠ Fingerprints can diverge due to normalization gaps between parsed and
     // introspected schemas even when the schemas are semantically identical.
     // Use diff operations as the source of truth for drift detection.
-    let differences = compute_diff(&actual, &expected);
+    let differences = compute_diff(
+        &exclude_external(actual, &expected.external),
+        &exclude_external(expected, &expected.external),
+    );
     let has_drift = !differences.is_empty();
 
-    Ok(DriftReport {
+    DriftReport {
         has_drift,
         expected_fingerprint,
         actual_fingerprint,
         differences,
+        unmanaged_objects: Vec::new(),
+    }
+}
+
+/// One release in a manifest: the fingerprint `drift` reports for a database
+/// right after it was deployed, and optionally a schema source for it, which
+/// lets a database matching no fingerprint be compared with the release.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReleaseEntry {
+    pub fingerprint: String,
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+/// Reads a JSON manifest of release name to [`ReleaseEntry`].
+pub fn load_release_manifest(path: &Path) -> Result<BTreeMap<String, ReleaseEntry>> {
+    read_json(path, "manifest")
+}
+
+/// Reads a baseline published for a fleet of databases: a single
+/// [`ReleaseEntry`] as JSON.
+pub fn load_baseline(path: &Path) -> Result<ReleaseEntry> {
+    read_json(path, "baseline")
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path, kind: &str) -> Result<T> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| SchemaError::ParseError(format!("Failed to read {}: {e}", path.display())))?;
+    serde_json::from_str(&content)
+        .map_err(|e| SchemaError::ParseError(format!("Invalid {kind} {}: {e}", path.display())))
+}
+
+/// Checks the database against a published baseline without its schema
+/// sources.
+pub async fn check_baseline(
+    conn: &PgConnection,
+    target_schemas: &[String],
+    baseline: &ReleaseEntry,
+) -> Result<DriftReport> {
+    let actual = introspect_schema(conn, target_schemas, false).await?;
+    let mut report = compare_with_baseline(&actual, target_schemas, baseline)?;
+    report.unmanaged_objects = count_unmanaged_objects(conn, target_schemas).await?;
+    Ok(report)
+}
+
+/// Compares fingerprints only, loading the baseline's source for a detailed
+/// diff when they differ and it has one. Baseline fingerprints are recorded
+/// from deployed databases, so any mismatch counts as drift.
+pub fn compare_with_baseline(
+    actual: &Schema,
+    target_schemas: &[String],
+    baseline: &ReleaseEntry,
+) -> Result<DriftReport> {
+    let actual_fingerprint = actual.fingerprint();
+    if actual_fingerprint == baseline.fingerprint {
+        return Ok(DriftReport {
+            has_drift: false,
+            expected_fingerprint: baseline.fingerprint.clone(),
+            actual_fingerprint,
+            differences: Vec::new(),
+            unmanaged_objects: Vec::new(),
+        });
+    }
+    let differences = match &baseline.source {
+        Some(source) => {
+            let expected = load_schema_from_sources(std::slice::from_ref(source))?;
+            compute_diff(actual, &filter_by_target_schemas(&expected, target_schemas))
+        }
+        None => Vec::new(),
+    };
+    Ok(DriftReport {
+        has_drift: true,
+        expected_fingerprint: baseline.fingerprint.clone(),
+        actual_fingerprint,
+        differences,
+        unmanaged_objects: Vec::new(),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReleaseMatch {
+    /// The release whose fingerprint matches, or else the one with the fewest
+    /// differences among those with a source. `None` when neither exists.
+    pub release: Option<String>,
+    pub exact: bool,
+    pub actual_fingerprint: String,
+    /// What it would take to bring the database to `release`; empty on an
+    /// exact match.
+    #[serde(skip_serializing)]
+    pub residual: Vec<MigrationOp>,
+}
+
+/// Finds the release in `manifest` the database is on.
+pub async fn identify_release(
+    conn: &PgConnection,
+    target_schemas: &[String],
+    manifest: &BTreeMap<String, ReleaseEntry>,
+) -> Result<ReleaseMatch> {
+    let actual = introspect_schema(conn, target_schemas, false).await?;
+    match_release(&actual, target_schemas, manifest)
+}
+
+/// Matches an introspected schema against `manifest` by fingerprint, falling
+/// back to diffing it against every release that has a source.
+pub fn match_release(
+    actual: &Schema,
+    target_schemas: &[String],
+    manifest: &BTreeMap<String, ReleaseEntry>,
+) -> Result<ReleaseMatch> {
+    let actual_fingerprint = actual.fingerprint();
+    if let Some((release, _)) = manifest
+        .iter()
+        .find(|(_, entry)| entry.fingerprint == actual_fingerprint)
+    {
+        return Ok(ReleaseMatch {
+            release: Some(release.clone()),
+            exact: true,
+            actual_fingerprint,
+            residual: Vec::new(),
+        });
+    }
+
+    let mut best: Option<(String, Vec<MigrationOp>)> = None;
+    for (release, entry) in manifest {
+        let Some(source) = &entry.source else {
+            continue;
+        };
+        let expected = load_schema_from_sources(std::slice::from_ref(source))?;
+        let expected = filter_by_target_schemas(&expected, target_schemas);
+        let residual = compute_diff(actual, &expected);
+        if best
+            .as_ref()
+            .is_none_or(|(_, fewest)| residual.len() < fewest.len())
+        {
+            best = Some((release.clone(), residual));
+        }
+    }
+
+    let (release, residual) = best.unzip();
+    Ok(ReleaseMatch {
+        release,
+        exact: false,
+        actual_fingerprint,
+        residual: residual.unwrap_or_default(),
     })
 }
 
@@ -53,6 +221,7 @@ mod tests {
             expected_fingerprint: "abc123".to_string(),
             actual_fingerprint: "def456".to_string(),
             differences: vec![],
+            unmanaged_objects: vec![],
         };
 
         assert!(!report.has_drift);
@@ -71,10 +240,12 @@ mod tests {
             primary_key: None,
             foreign_keys: Vec::new(),
             check_constraints: Vec::new(),
+            unique_constraints: Vec::new(),
             exclusion_constraints: Vec::new(),
             comment: None,
             row_level_security: false,
             force_row_level_security: false,
+            replica_identity: None,
             policies: Vec::new(),
             partition_by: None,
 
@@ -90,6 +261,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
 
@@ -103,9 +277,104 @@ mod tests {
             expected_fingerprint: "abc".to_string(),
             actual_fingerprint: "xyz".to_string(),
             differences,
+            unmanaged_objects: vec![],
         };
 
         assert!(report.has_drift);
         assert_eq!(report.differences.len(), 1);
     }
+
+    #[test]
+    fn matches_release_by_fingerprint_then_by_fewest_differences() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let v1 = dir.path().join("v1.sql");
+        let v2 = dir.path().join("v2.sql");
+        std::fs::write(&v1, "CREATE TABLE public.users (id integer);").unwrap();
+        std::fs::write(&v2, "CREATE TABLE public.users (id integer, email text);").unwrap();
+        let actual = crate::parser::parse_sql_string(
+            "CREATE TABLE public.users (id integer, email text, bio text);",
+        )
+        .unwrap();
+
+        let entry = |source: &Path| ReleaseEntry {
+            fingerprint: "recorded-at-deploy".to_string(),
+            source: Some(format!("sql:{}", source.display())),
+        };
+        let mut manifest = BTreeMap::from([
+            ("v1".to_string(), entry(&v1)),
+            ("v2".to_string(), entry(&v2)),
+        ]);
+        let targets = vec!["public".to_string()];
+
+        let closest = match_release(&actual, &targets, &manifest).unwrap();
+        assert_eq!(closest.release.as_deref(), Some("v2"));
+        assert!(!closest.exact);
+        assert_eq!(closest.residual.len(), 1);
+
+        manifest.insert(
+            "v3".to_string(),
+            ReleaseEntry {
+                fingerprint: actual.fingerprint(),
+                source: None,
+            },
+        );
+        let exact = match_release(&actual, &targets, &manifest).unwrap();
+        assert_eq!(exact.release.as_deref(), Some("v3"));
+        assert!(exact.exact);
+        assert!(exact.residual.is_empty());
+    }
+
+    #[test]
+    fn baseline_compares_fingerprints_and_diffs_only_on_mismatch() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let source = dir.path().join("schema.sql");
+        std::fs::write(&source, "CREATE TABLE public.users (id integer);").unwrap();
+        let actual =
+            crate::parser::parse_sql_string("CREATE TABLE public.users (id integer, bio text);")
+                .unwrap();
+        let targets = vec!["public".to_string()];
+
+        let matching = ReleaseEntry {
+            fingerprint: actual.fingerprint(),
+            source: None,
+        };
+        assert!(
+            !compare_with_baseline(&actual, &targets, &matching)
+                .unwrap()
+                .has_drift
+        );
+
+        let fingerprint_only = ReleaseEntry {
+            fingerprint: "published".to_string(),
+            source: None,
+        };
+        let report = compare_with_baseline(&actual, &targets, &fingerprint_only).unwrap();
+        assert!(report.has_drift);
+        assert!(report.differences.is_empty());
+
+        let with_source = ReleaseEntry {
+            source: Some(format!("sql:{}", source.display())),
+            ..fingerprint_only
+        };
+        let report = compare_with_baseline(&actual, &targets, &with_source).unwrap();
+        assert!(report.has_drift);
+        assert_eq!(report.differences.len(), 1);
+    }
+
+    #[test]
+    fn external_objects_in_the_database_are_not_drift() {
+        let expected = crate::parser::parse_sql_string(
+            "-- pgmold:external public.legacy_*
+             CREATE TABLE public.users (id integer);",
+        )
+        .unwrap();
+        let actual = crate::parser::parse_sql_string(
+            "CREATE TABLE public.users (id integer);
+             CREATE TABLE public.legacy_orders (id integer);",
+        )
+        .unwrap();
+
+        let report = compare_schemas(&actual, &expected);
+        assert!(!report.has_drift, "{:?}", report.differences);
+    }
 }
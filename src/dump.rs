@@ -1,7 +1,8 @@
 use crate::diff::dump_planner::plan_dump;
 use crate::diff::{CommentObjectType, GrantObjectKind, MigrationOp, OwnerObjectKind};
 use crate::model::{Grant, QualifiedName, Schema};
-use crate::pg::sqlgen::generate_sql;
+use crate::pg::sqlgen::{generate_sql, generate_sql_iter};
+use std::io::{self, Write};
 
 fn push_owner_op(
     ops: &mut Vec<MigrationOp>,
@@ -353,6 +354,17 @@ pub fn schema_to_create_ops(schema: &Schema) -> Vec<MigrationOp> {
                 table: table_qualified.clone(),
             });
         }
+        if table.force_row_level_security {
+            ops.push(MigrationOp::ForceRls {
+                table: table_qualified.clone(),
+            });
+        }
+        if table.replica_identity.is_some() {
+            ops.push(MigrationOp::SetReplicaIdentity {
+                table: table_qualified.clone(),
+                identity: table.replica_identity.clone(),
+            });
+        }
 
         for policy in &table.policies {
             ops.push(MigrationOp::CreatePolicy(policy.clone()));
@@ -513,27 +525,48 @@ pub fn schema_to_create_ops(schema: &Schema) -> Vec<MigrationOp> {
         });
     }
 
+    for (name, value) in &schema.sequence_values {
+        ops.push(MigrationOp::SetSequenceValue {
+            name: name.clone(),
+            value: *value,
+        });
+    }
+
     ops
 }
 
 /// Generate SQL dump from a Schema.
 /// Returns a string containing all DDL statements in dependency order.
 pub fn generate_dump(schema: &Schema, header: Option<&str>) -> String {
+    let mut dump = Vec::new();
+    write_dump(schema, header, &mut dump).expect("writing to a Vec cannot fail");
+    String::from_utf8(dump).expect("generated SQL is valid UTF-8")
+}
+
+/// Writes the same dump as [`generate_dump`] to `out`, generating each
+/// statement just before it is written instead of building the whole dump
+/// in memory first.
+pub fn write_dump<W: Write>(schema: &Schema, header: Option<&str>, out: &mut W) -> io::Result<()> {
     let ops = schema_to_create_ops(schema);
 
     if ops.is_empty() {
-        return header.map(|h| format!("{h}\n")).unwrap_or_default();
+        if let Some(h) = header {
+            writeln!(out, "{h}")?;
+        }
+        return Ok(());
     }
 
+    if let Some(h) = header {
+        write!(out, "{h}\n\n")?;
+    }
     let planned = plan_dump(ops);
-    let statements = generate_sql(&planned);
-
-    let body = statements.join("\n\n") + "\n";
-
-    match header {
-        Some(h) => format!("{h}\n\n{body}"),
-        None => body,
+    for (i, statement) in generate_sql_iter(&planned).enumerate() {
+        if i > 0 {
+            out.write_all(b"\n\n")?;
+        }
+        out.write_all(statement.as_bytes())?;
     }
+    out.write_all(b"\n")
 }
 
 pub struct SplitDump {
@@ -568,10 +601,13 @@ pub fn generate_split_dump(schema: &Schema) -> SplitDump {
                 extension_ops.push(op)
             }
             MigrationOp::CreateEnum(_) | MigrationOp::CreateDomain(_) => type_ops.push(op),
-            MigrationOp::CreateSequence(_) => sequence_ops.push(op),
+            MigrationOp::CreateSequence(_) | MigrationOp::SetSequenceValue { .. } => {
+                sequence_ops.push(op)
+            }
             MigrationOp::CreateTable(_)
             | MigrationOp::CreatePartition(_)
-            | MigrationOp::EnableRls { .. } => table_ops.push(op),
+            | MigrationOp::EnableRls { .. }
+            | MigrationOp::ForceRls { .. } => table_ops.push(op),
             MigrationOp::CreateFunction(_) => function_ops.push(op),
             MigrationOp::CreateView(_) => view_ops.push(op),
             MigrationOp::CreateTrigger(_) => trigger_ops.push(op),
@@ -759,6 +795,25 @@ mod tests {
         assert!(dump.contains("CREATE TABLE"));
     }
 
+    #[test]
+    fn write_dump_separates_statements_with_blank_lines() {
+        let schema = parse_sql_string(
+            "CREATE TABLE users (id BIGINT PRIMARY KEY);\nCREATE TABLE teams (id BIGINT PRIMARY KEY);",
+        )
+        .unwrap();
+        let mut out = Vec::new();
+        write_dump(&schema, Some("-- header"), &mut out).unwrap();
+        let dump = String::from_utf8(out).unwrap();
+
+        let statements = generate_sql(&plan_dump(schema_to_create_ops(&schema)));
+        assert_eq!(statements.len(), 2);
+        assert_eq!(dump, format!("-- header\n\n{}\n", statements.join("\n\n")));
+
+        let mut out = Vec::new();
+        write_dump(&Schema::default(), Some("-- header"), &mut out).unwrap();
+        assert_eq!(out, b"-- header\n");
+    }
+
     #[test]
     fn sequence_round_trip() {
         use crate::model::{Sequence, SequenceDataType, SequenceOwner};
@@ -798,6 +853,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn sequence_values_come_last_and_round_trip() {
+        let mut schema = parse_sql_string(
+            r#"
+            CREATE TABLE orders (id BIGSERIAL PRIMARY KEY);
+            CREATE SEQUENCE invoice_seq;
+            "#,
+        )
+        .unwrap();
+        schema
+            .sequence_values
+            .insert("public.invoice_seq".to_string(), 77);
+
+        let dump = generate_dump(&schema, None);
+        assert!(
+            dump.trim_end()
+                .ends_with("SELECT setval('\"public\".\"invoice_seq\"', 77);"),
+            "{dump}"
+        );
+        let reparsed = parse_sql_string(&dump).unwrap();
+        assert_eq!(reparsed.sequence_values, schema.sequence_values);
+    }
+
     #[test]
     fn split_dump_empty_schema() {
         let schema = Schema::default();
@@ -872,6 +950,26 @@ mod tests {
         assert!(split.policies.contains("posts_select"));
     }
 
+    #[test]
+    fn dump_keeps_forced_row_level_security() {
+        let schema = parse_sql_string(
+            r#"
+            CREATE TABLE posts (id BIGINT PRIMARY KEY);
+            ALTER TABLE posts ENABLE ROW LEVEL SECURITY;
+            ALTER TABLE posts FORCE ROW LEVEL SECURITY;
+            "#,
+        )
+        .unwrap();
+
+        let dump = generate_dump(&schema, None);
+        assert!(dump.contains("FORCE ROW LEVEL SECURITY"));
+        assert!(generate_split_dump(&schema)
+            .tables
+            .contains("FORCE ROW LEVEL SECURITY"));
+        let reparsed = parse_sql_string(&dump).unwrap();
+        assert!(reparsed.tables["public.posts"].force_row_level_security);
+    }
+
     #[test]
     fn split_dump_non_empty_files_only() {
         let schema = parse_sql_string("CREATE TABLE users (id BIGINT PRIMARY KEY);").unwrap();
@@ -928,10 +1026,12 @@ mod tests {
             primary_key: None,
             foreign_keys: vec![],
             check_constraints: vec![],
+            unique_constraints: Vec::new(),
             exclusion_constraints: vec![],
             comment: None,
             row_level_security: false,
             force_row_level_security: false,
+            replica_identity: None,
             policies: vec![],
             partition_by: None,
             owner: None,
@@ -1063,10 +1163,12 @@ mod tests {
             primary_key: None,
             foreign_keys: vec![],
             check_constraints: vec![],
+            unique_constraints: Vec::new(),
             exclusion_constraints: vec![],
             comment: None,
             row_level_security: false,
             force_row_level_security: false,
+            replica_identity: None,
             policies: vec![],
             partition_by: None,
             owner: None,
@@ -1111,10 +1213,12 @@ mod tests {
             primary_key: None,
             foreign_keys: vec![],
             check_constraints: vec![],
+            unique_constraints: Vec::new(),
             exclusion_constraints: vec![],
             comment: None,
             row_level_security: false,
             force_row_level_security: false,
+            replica_identity: None,
             policies: vec![],
             partition_by: None,
             owner: None,
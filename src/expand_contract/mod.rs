@@ -1,6 +1,10 @@
 use crate::diff::MigrationOp;
-use crate::model::{versioned_schema_name, ColumnMapping, Schema, Table, VersionView};
-use std::collections::BTreeMap;
+use crate::model::{
+    parse_qualified_name, versioned_schema_name, ColumnMapping, Index, IndexType, QualifiedName,
+    Schema, Table, VersionView,
+};
+use crate::parser::truncate_identifier;
+use std::collections::{BTreeMap, HashSet};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Phase {
@@ -29,11 +33,105 @@ impl ExpandContractPlan {
     }
 }
 
-pub fn expand_operations(ops: Vec<MigrationOp>) -> ExpandContractPlan {
+/// Splits `ops` into expand, backfill and contract phases. `current` is the
+/// schema the operations run against, so indexes built ahead of time get
+/// names nothing there already uses.
+pub fn expand_operations(ops: Vec<MigrationOp>, current: &Schema) -> ExpandContractPlan {
     let mut plan = ExpandContractPlan::new();
+    let mut taken_names = relation_names(current, &ops);
+
+    // Existing columns a new primary key needs to become NOT NULL. Their
+    // SET NOT NULL runs in the contract phase right before the key is attached.
+    let primary_key_columns: HashSet<(QualifiedName, String)> = ops
+        .iter()
+        .filter_map(|op| match op {
+            MigrationOp::AddPrimaryKey { table, primary_key } => Some((table, primary_key)),
+            _ => None,
+        })
+        .flat_map(|(table, primary_key)| {
+            primary_key
+                .columns
+                .iter()
+                .map(|column| (table.clone(), column.clone()))
+        })
+        .collect();
+    let not_null_prerequisites: HashSet<(QualifiedName, String)> = ops
+        .iter()
+        .filter_map(|op| match op {
+            MigrationOp::AlterColumn {
+                table,
+                column,
+                changes,
+            } if changes.nullable == Some(false) => Some((table.clone(), column.clone())),
+            _ => None,
+        })
+        .filter(|key| primary_key_columns.contains(key))
+        .collect();
 
     for op in ops {
         match op {
+            MigrationOp::AddPrimaryKey { table, primary_key } => {
+                let index_name = primary_key_index_name(&table, &taken_names);
+                taken_names.insert(QualifiedName::new(&table.schema, &index_name));
+                plan.expand_ops.push(PhasedOp {
+                    phase: Phase::Expand,
+                    op: MigrationOp::CreateIndexConcurrently {
+                        table: table.clone(),
+                        index: Index {
+                            name: index_name.clone(),
+                            columns: primary_key.columns.clone(),
+                            unique: true,
+                            index_type: IndexType::BTree,
+                            predicate: None,
+                            nulls_not_distinct: false,
+                            column_options: Vec::new(),
+                        },
+                    },
+                    rationale: format!(
+                        "Build the unique index for the primary key of '{table}' without blocking writes"
+                    ),
+                });
+
+                for column in &primary_key.columns {
+                    if not_null_prerequisites.contains(&(table.clone(), column.clone())) {
+                        plan.contract_ops.push(PhasedOp {
+                            phase: Phase::Contract,
+                            op: MigrationOp::SetColumnNotNull {
+                                table: table.clone(),
+                                column: column.clone(),
+                            },
+                            rationale: format!(
+                                "Primary key column '{column}' must be NOT NULL before the key is attached"
+                            ),
+                        });
+                    }
+                }
+
+                plan.contract_ops.push(PhasedOp {
+                    phase: Phase::Contract,
+                    op: MigrationOp::AddPrimaryKeyUsingIndex { table, index_name },
+                    rationale: "Attach the primary key to the prebuilt index without a table scan"
+                        .to_string(),
+                });
+            }
+            MigrationOp::AlterColumn {
+                table,
+                column,
+                mut changes,
+            } if not_null_prerequisites.contains(&(table.clone(), column.clone())) => {
+                changes.nullable = None;
+                if changes.has_changes() {
+                    plan.expand_ops.push(PhasedOp {
+                        phase: Phase::Expand,
+                        op: MigrationOp::AlterColumn {
+                            table,
+                            column,
+                            changes,
+                        },
+                        rationale: "Direct operation".to_string(),
+                    });
+                }
+            }
             MigrationOp::AddColumn { table, column } => {
                 if !column.nullable {
                     let mut nullable_column = column.clone();
@@ -204,6 +302,73 @@ pub fn generate_drop_version_schema_ops(base_schema: &str, version: &str) -> Vec
     }]
 }
 
+/// Names of the relations in `current`, which indexes share a namespace
+/// with, and of the indexes `ops` create.
+fn relation_names(current: &Schema, ops: &[MigrationOp]) -> HashSet<QualifiedName> {
+    let mut names: HashSet<QualifiedName> = current
+        .tables
+        .keys()
+        .chain(current.partitions.keys())
+        .chain(current.views.keys())
+        .chain(current.sequences.keys())
+        .map(|key| {
+            let (schema, name) = parse_qualified_name(key);
+            QualifiedName::new(&schema, &name)
+        })
+        .collect();
+    for table in current.tables.values() {
+        let constraint_indexes = table
+            .indexes
+            .iter()
+            .map(|index| index.name.clone())
+            .chain(table.unique_constraints.iter().map(|u| u.name.clone()))
+            .chain(table.exclusion_constraints.iter().map(|e| e.name.clone()))
+            .chain(
+                table
+                    .primary_key
+                    .as_ref()
+                    .map(|_| pkey_name(&table.name, 0)),
+            );
+        names.extend(constraint_indexes.map(|name| QualifiedName::new(&table.schema, &name)));
+    }
+    for op in ops {
+        match op {
+            MigrationOp::AddIndex { table, index }
+            | MigrationOp::CreateIndexConcurrently { table, index } => {
+                names.insert(QualifiedName::new(&table.schema, &index.name));
+            }
+            _ => {}
+        }
+    }
+    names
+}
+
+/// Name for the index of a new primary key on `table`: `<table>_pkey`, with
+/// a number appended while `taken` has it.
+fn primary_key_index_name(table: &QualifiedName, taken: &HashSet<QualifiedName>) -> String {
+    (0..)
+        .map(|n| pkey_name(&table.name, n))
+        .find(|name| !taken.contains(&QualifiedName::new(&table.schema, name)))
+        .expect("some numbered name is free")
+}
+
+/// `<table>_pkey<n>` (no number for 0), shortening the table name rather
+/// than the suffix to fit the identifier length limit, as PostgreSQL does.
+fn pkey_name(table_name: &str, n: usize) -> String {
+    let suffix = if n == 0 {
+        "_pkey".to_string()
+    } else {
+        format!("_pkey{n}")
+    };
+    let stem = truncate_identifier(table_name);
+    let limit = truncate_identifier(&format!("{stem}{suffix}")).len() - suffix.len();
+    let end = (0..=limit.min(stem.len()))
+        .rev()
+        .find(|&end| stem.is_char_boundary(end))
+        .unwrap_or(0);
+    format!("{}{suffix}", &stem[..end])
+}
+
 /// Expand operations with version schema support for zero-downtime migrations.
 ///
 /// This creates version views in the expand phase and drops old version schemas
@@ -222,7 +387,7 @@ pub fn expand_operations_with_versioning(
     old_version: Option<&str>,
     base_schema: &str,
 ) -> ExpandContractPlan {
-    let mut plan = expand_operations(ops);
+    let mut plan = expand_operations(ops, schema);
 
     let version_ops =
         generate_version_schema_ops(schema, base_schema, new_version, &BTreeMap::new());
@@ -260,7 +425,7 @@ mod tests {
 
     #[test]
     fn empty_operations_produce_empty_plan() {
-        let plan = expand_operations(vec![]);
+        let plan = expand_operations(vec![], &Schema::new());
         assert!(plan.expand_ops.is_empty());
         assert!(plan.backfill_ops.is_empty());
         assert!(plan.contract_ops.is_empty());
@@ -275,6 +440,9 @@ mod tests {
             default: None,
             comment: None,
             generated: None,
+            statistics: None,
+            compression: None,
+            identity: None,
         };
 
         let ops = vec![MigrationOp::AddColumn {
@@ -282,7 +450,7 @@ mod tests {
             column,
         }];
 
-        let plan = expand_operations(ops);
+        let plan = expand_operations(ops, &Schema::new());
 
         assert_eq!(plan.expand_ops.len(), 1);
         assert_eq!(plan.backfill_ops.len(), 1);
@@ -314,6 +482,83 @@ mod tests {
         }
     }
 
+    #[test]
+    fn add_primary_key_builds_index_concurrently_and_attaches_it() {
+        let users = QualifiedName::new("public", "users");
+        let ops = vec![
+            MigrationOp::AlterColumn {
+                table: users.clone(),
+                column: "id".to_string(),
+                changes: crate::diff::ColumnChanges {
+                    data_type: None,
+                    nullable: Some(false),
+                    default: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
+                },
+            },
+            MigrationOp::AddPrimaryKey {
+                table: users.clone(),
+                primary_key: crate::model::PrimaryKey {
+                    columns: vec!["id".to_string()],
+                },
+            },
+        ];
+
+        let plan = expand_operations(ops, &Schema::new());
+        let sql = |phased: &[PhasedOp]| -> Vec<String> {
+            phased
+                .iter()
+                .flat_map(|p| crate::pg::sqlgen::generate_sql(std::slice::from_ref(&p.op)))
+                .collect()
+        };
+
+        assert_eq!(
+            sql(&plan.expand_ops),
+            vec![
+                "CREATE UNIQUE INDEX CONCURRENTLY \"users_pkey\" ON \"public\".\"users\" (\"id\");"
+            ]
+        );
+        assert!(plan.backfill_ops.is_empty());
+        assert_eq!(
+            sql(&plan.contract_ops),
+            vec![
+                "ALTER TABLE \"public\".\"users\" ALTER COLUMN \"id\" SET NOT NULL;",
+                "ALTER TABLE \"public\".\"users\" ADD CONSTRAINT \"users_pkey\" PRIMARY KEY USING INDEX \"users_pkey\";",
+            ]
+        );
+    }
+
+    #[test]
+    fn primary_key_index_name_fits_and_avoids_existing_indexes() {
+        let add_primary_key = |name: &str| MigrationOp::AddPrimaryKey {
+            table: QualifiedName::new("public", name),
+            primary_key: crate::model::PrimaryKey {
+                columns: vec!["id".to_string()],
+            },
+        };
+        let index_name = |plan: &ExpandContractPlan| match &plan.expand_ops[0].op {
+            MigrationOp::CreateIndexConcurrently { index, .. } => index.name.clone(),
+            other => panic!("expected a concurrent index, got {other:?}"),
+        };
+
+        let current = crate::parser::parse_sql_string(
+            "CREATE TABLE users (id integer); CREATE INDEX users_pkey ON users (id);",
+        )
+        .unwrap();
+        let plan = expand_operations(vec![add_primary_key("users")], &current);
+        assert_eq!(index_name(&plan), "users_pkey1");
+        assert!(matches!(
+            &plan.contract_ops[0].op,
+            MigrationOp::AddPrimaryKeyUsingIndex { index_name, .. } if index_name == "users_pkey1"
+        ));
+
+        let long = "a".repeat(70);
+        let plan = expand_operations(vec![add_primary_key(&long)], &Schema::new());
+        assert_eq!(index_name(&plan), format!("{}_pkey", "a".repeat(58)));
+    }
+
     #[test]
     fn add_nullable_column_stays_in_expand_only() {
         let column = Column {
@@ -323,6 +568,9 @@ mod tests {
             default: None,
             comment: None,
             generated: None,
+            statistics: None,
+            compression: None,
+            identity: None,
         };
 
         let ops = vec![MigrationOp::AddColumn {
@@ -330,7 +578,7 @@ mod tests {
             column,
         }];
 
-        let plan = expand_operations(ops);
+        let plan = expand_operations(ops, &Schema::new());
 
         assert_eq!(plan.expand_ops.len(), 1);
         assert_eq!(plan.backfill_ops.len(), 0);
@@ -357,6 +605,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         Table {
@@ -367,10 +618,12 @@ mod tests {
             primary_key: None,
             foreign_keys: Vec::new(),
             check_constraints: Vec::new(),
+            unique_constraints: Vec::new(),
             exclusion_constraints: Vec::new(),
             comment: None,
             row_level_security: false,
             force_row_level_security: false,
+            replica_identity: None,
             policies: Vec::new(),
             partition_by: None,
             owner: None,
@@ -390,6 +643,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         table.columns.insert(
@@ -401,6 +657,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
 
@@ -426,6 +685,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
 
@@ -461,6 +723,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         schema.tables.insert("public.users".to_string(), table);
@@ -527,6 +792,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         schema.tables.insert("public.users".to_string(), table);
@@ -538,6 +806,9 @@ mod tests {
             default: None,
             comment: None,
             generated: None,
+            statistics: None,
+            compression: None,
+            identity: None,
         };
 
         let ops = vec![MigrationOp::AddColumn {
@@ -580,10 +851,12 @@ mod tests {
             primary_key: None,
             foreign_keys: Vec::new(),
             check_constraints: Vec::new(),
+            unique_constraints: Vec::new(),
             exclusion_constraints: Vec::new(),
             comment: None,
             row_level_security: false,
             force_row_level_security: false,
+            replica_identity: None,
             policies: Vec::new(),
             partition_by: None,
             owner: None,
@@ -1,8 +1,9 @@
 use glob::Pattern;
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fmt;
 use std::str::FromStr;
 
+use crate::diff::MigrationOp;
 use crate::model::Schema;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -110,15 +111,68 @@ impl ObjectType {
     }
 }
 
+/// How pgmold treats a PostgreSQL schema, for schemas partly managed by a
+/// platform (e.g. Supabase's `auth`, `storage` and `graphql`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SchemaMode {
+    /// Diff and migrate the schema like any other.
+    #[default]
+    Manage,
+    /// Diff the schema so drift is reported, but never plan changes to it.
+    Observe,
+    /// Leave the schema out entirely.
+    Ignore,
+}
+
+impl FromStr for SchemaMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "manage" => Ok(SchemaMode::Manage),
+            "observe" => Ok(SchemaMode::Observe),
+            "ignore" => Ok(SchemaMode::Ignore),
+            _ => Err(format!(
+                "Invalid schema mode '{s}'. Valid modes: manage, observe, ignore"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SchemaMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            SchemaMode::Manage => "manage",
+            SchemaMode::Observe => "observe",
+            SchemaMode::Ignore => "ignore",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Parses a `schema=mode` pair, as accepted by `--schema-mode`.
+pub fn parse_schema_mode(spec: &str) -> Result<(String, SchemaMode), String> {
+    let (schema, mode) = spec
+        .split_once('=')
+        .ok_or_else(|| format!("Invalid schema mode '{spec}': expected schema=mode"))?;
+    let schema = schema.trim();
+    if schema.is_empty() {
+        return Err(format!("Invalid schema mode '{spec}': missing schema name"));
+    }
+    Ok((schema.to_string(), mode.trim().parse()?))
+}
+
 fn matches_any(patterns: &[Pattern], names: &[&str]) -> bool {
     patterns.iter().any(|p| names.iter().any(|n| p.matches(n)))
 }
 
+#[derive(Debug, Clone, Default)]
 pub struct Filter {
     include: Vec<Pattern>,
     exclude: Vec<Pattern>,
     include_types: HashSet<ObjectType>,
     exclude_types: HashSet<ObjectType>,
+    schema_modes: BTreeMap<String, SchemaMode>,
 }
 
 impl Filter {
@@ -143,9 +197,57 @@ impl Filter {
             exclude: exclude_patterns,
             include_types: include_types.iter().copied().collect(),
             exclude_types: exclude_types.iter().copied().collect(),
+            schema_modes: BTreeMap::new(),
         })
     }
 
+    /// Sets the per-schema modes; schemas not listed are managed.
+    pub fn with_schema_modes(mut self, schema_modes: BTreeMap<String, SchemaMode>) -> Self {
+        self.schema_modes = schema_modes;
+        self
+    }
+
+    pub fn schema_mode(&self, schema: &str) -> SchemaMode {
+        self.schema_modes.get(schema).copied().unwrap_or_default()
+    }
+
+    /// Returns false for operations in observe-only or ignored schemas, which
+    /// must never be planned for apply.
+    pub fn should_plan(&self, op: &MigrationOp) -> bool {
+        op.schema()
+            .is_none_or(|schema| self.schema_mode(&schema) == SchemaMode::Manage)
+    }
+
+    /// A description of this filter that is stable across runs, for keying
+    /// cached plans.
+    pub fn cache_key(&self) -> String {
+        let patterns = |patterns: &[Pattern]| {
+            patterns
+                .iter()
+                .map(Pattern::as_str)
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        let types = |types: &HashSet<ObjectType>| {
+            let mut names: Vec<String> = types.iter().map(|t| format!("{t:?}")).collect();
+            names.sort();
+            names.join(",")
+        };
+        let modes: Vec<String> = self
+            .schema_modes
+            .iter()
+            .map(|(schema, mode)| format!("{schema}={mode}"))
+            .collect();
+        format!(
+            "include={};exclude={};include_types={};exclude_types={};schema_modes={}",
+            patterns(&self.include),
+            patterns(&self.exclude),
+            types(&self.include_types),
+            types(&self.exclude_types),
+            modes.join(",")
+        )
+    }
+
     pub fn should_include(&self, name: &str) -> bool {
         self.check_inclusion(&[name])
     }
@@ -207,6 +309,7 @@ fn filter_table(table: &crate::model::Table, filter: &Filter) -> crate::model::T
     }
     if !filter.should_include_type(ObjectType::Indexes) {
         result.indexes = vec![];
+        result.unique_constraints = vec![];
     }
     if !filter.should_include_type(ObjectType::ForeignKeys) {
         result.foreign_keys = vec![];
@@ -233,6 +336,20 @@ pub fn filter_schema(schema: &Schema, filter: &Filter) -> Schema {
         }
     }
 
+    let unignored;
+    let schema = if filter
+        .schema_modes
+        .values()
+        .any(|m| *m == SchemaMode::Ignore)
+    {
+        unignored = retain_schemas(schema, &|name| {
+            filter.schema_mode(name) != SchemaMode::Ignore
+        });
+        &unignored
+    } else {
+        schema
+    };
+
     let strip_grants = !filter.should_include_type(ObjectType::Grants);
 
     let mut functions = filter_field(&schema.functions, filter, ObjectType::Functions);
@@ -287,13 +404,45 @@ pub fn filter_schema(schema: &Schema, filter: &Filter) -> Schema {
         },
         table_constraint_comments: schema.table_constraint_comments.clone(),
         domain_constraint_comments: schema.domain_constraint_comments.clone(),
+        raw_objects: schema.raw_objects.clone(),
+        external: schema.external.clone(),
+        schema_moves: schema.schema_moves.clone(),
+        renames: schema.renames.clone(),
+        partition_retention: schema.partition_retention.clone(),
+        sequence_values: BTreeMap::new(),
     };
+    // Keep the values of sequences that survived the filter, and of names
+    // the schema does not model as a sequence (such as a serial column's).
+    filtered.sequence_values = schema
+        .sequence_values
+        .iter()
+        .filter(|(name, _)| {
+            filtered.sequences.contains_key(*name) || !schema.sequences.contains_key(*name)
+        })
+        .map(|(name, value)| (name.clone(), *value))
+        .collect();
     // Drop sidecar entries whose parent (table or domain) was filtered out
     // so the diff loop cannot emit a `COMMENT ON CONSTRAINT ... ON missing`.
     filtered.drop_orphan_constraint_comments();
     filtered
 }
 
+/// `schema` without the objects its `external` globs name. Other systems own
+/// them, so a plan must never create, alter or drop them, whichever side of
+/// the diff they appear on.
+pub fn exclude_external(schema: &Schema, external: &BTreeSet<String>) -> Schema {
+    let globs: Vec<String> = external
+        .iter()
+        .filter(|glob| Pattern::new(glob).is_ok())
+        .cloned()
+        .collect();
+    if globs.is_empty() {
+        return schema.clone();
+    }
+    let filter = Filter::new(&[], &globs, &[], &[]).expect("patterns were validated");
+    filter_schema(schema, &filter)
+}
+
 pub fn exclude_unmanaged_partitions(current: &Schema, target: &Schema) -> Schema {
     let mut result = current.clone();
     result.partitions.retain(|key, partition| {
@@ -330,49 +479,50 @@ pub fn filter_by_target_schemas(schema: &Schema, target_schemas: &[String]) -> S
     );
 
     let allowed: HashSet<&str> = target_schemas.iter().map(|s| s.as_str()).collect();
+    retain_schemas(schema, &|name| allowed.contains(name))
+}
 
+/// Keeps only the objects whose PostgreSQL schema satisfies `keep`.
+fn retain_schemas(schema: &Schema, keep: &dyn Fn(&str) -> bool) -> Schema {
     fn retain_by_schema<V: Clone>(
         map: &BTreeMap<String, V>,
-        allowed: &HashSet<&str>,
+        keep: &dyn Fn(&str) -> bool,
         get_schema: fn(&V) -> &str,
     ) -> BTreeMap<String, V> {
         map.iter()
-            .filter(|(_, v)| allowed.contains(get_schema(v)))
+            .filter(|(_, v)| keep(get_schema(v)))
             .map(|(k, v)| (k.clone(), v.clone()))
             .collect()
     }
 
-    fn retain_by_key_schema(
-        map: &BTreeMap<String, String>,
-        allowed: &HashSet<&str>,
-    ) -> BTreeMap<String, String> {
+    fn retain_by_key_schema<V: Clone>(
+        map: &BTreeMap<String, V>,
+        keep: &dyn Fn(&str) -> bool,
+    ) -> BTreeMap<String, V> {
         map.iter()
-            .filter(|(key, _)| {
-                key.split_once('.')
-                    .is_some_and(|(s, _)| allowed.contains(s))
-            })
+            .filter(|(key, _)| key.split_once('.').is_some_and(|(s, _)| keep(s)))
             .map(|(k, v)| (k.clone(), v.clone()))
             .collect()
     }
 
     let mut result = Schema {
-        schemas: retain_by_schema(&schema.schemas, &allowed, |s| &s.name),
+        schemas: retain_by_schema(&schema.schemas, keep, |s| &s.name),
         extensions: schema.extensions.clone(),
         servers: schema.servers.clone(),
-        tables: retain_by_schema(&schema.tables, &allowed, |t| &t.schema),
-        enums: retain_by_schema(&schema.enums, &allowed, |e| &e.schema),
-        domains: retain_by_schema(&schema.domains, &allowed, |d| &d.schema),
-        functions: retain_by_schema(&schema.functions, &allowed, |f| &f.schema),
-        aggregates: retain_by_schema(&schema.aggregates, &allowed, |a| &a.schema),
-        views: retain_by_schema(&schema.views, &allowed, |v| &v.schema),
-        triggers: retain_by_schema(&schema.triggers, &allowed, |t| &t.target_schema),
-        sequences: retain_by_schema(&schema.sequences, &allowed, |s| &s.schema),
-        partitions: retain_by_schema(&schema.partitions, &allowed, |p| &p.schema),
+        tables: retain_by_schema(&schema.tables, keep, |t| &t.schema),
+        enums: retain_by_schema(&schema.enums, keep, |e| &e.schema),
+        domains: retain_by_schema(&schema.domains, keep, |d| &d.schema),
+        functions: retain_by_schema(&schema.functions, keep, |f| &f.schema),
+        aggregates: retain_by_schema(&schema.aggregates, keep, |a| &a.schema),
+        views: retain_by_schema(&schema.views, keep, |v| &v.schema),
+        triggers: retain_by_schema(&schema.triggers, keep, |t| &t.target_schema),
+        sequences: retain_by_schema(&schema.sequences, keep, |s| &s.schema),
+        partitions: retain_by_schema(&schema.partitions, keep, |p| &p.schema),
         default_privileges: schema
             .default_privileges
             .iter()
             .filter(|dp| match &dp.schema {
-                Some(s) => allowed.contains(s.as_str()),
+                Some(s) => keep(s),
                 None => true,
             })
             .cloned()
@@ -382,14 +532,14 @@ pub fn filter_by_target_schemas(schema: &Schema, target_schemas: &[String]) -> S
         pending_grants: Vec::new(),
         pending_revokes: Vec::new(),
         pending_comments: Vec::new(),
-        table_constraint_comments: retain_by_key_schema(
-            &schema.table_constraint_comments,
-            &allowed,
-        ),
-        domain_constraint_comments: retain_by_key_schema(
-            &schema.domain_constraint_comments,
-            &allowed,
-        ),
+        table_constraint_comments: retain_by_key_schema(&schema.table_constraint_comments, keep),
+        domain_constraint_comments: retain_by_key_schema(&schema.domain_constraint_comments, keep),
+        raw_objects: schema.raw_objects.clone(),
+        external: schema.external.clone(),
+        schema_moves: schema.schema_moves.clone(),
+        renames: schema.renames.clone(),
+        partition_retention: schema.partition_retention.clone(),
+        sequence_values: retain_by_key_schema(&schema.sequence_values, keep),
     };
     // Mirror the filter_schema path: drop orphan sidecar entries even
     // though the schema-prefix filter above already covers the only orphan
@@ -623,10 +773,12 @@ mod tests {
                 primary_key: None,
                 foreign_keys: vec![],
                 check_constraints: vec![],
+                unique_constraints: Vec::new(),
                 exclusion_constraints: vec![],
                 comment: None,
                 row_level_security: false,
                 force_row_level_security: false,
+                replica_identity: None,
                 policies: vec![],
                 partition_by: None,
 
@@ -644,10 +796,12 @@ mod tests {
                 primary_key: None,
                 foreign_keys: vec![],
                 check_constraints: vec![],
+                unique_constraints: Vec::new(),
                 exclusion_constraints: vec![],
                 comment: None,
                 row_level_security: false,
                 force_row_level_security: false,
+                replica_identity: None,
                 policies: vec![],
                 partition_by: None,
 
@@ -665,10 +819,12 @@ mod tests {
                 primary_key: None,
                 foreign_keys: vec![],
                 check_constraints: vec![],
+                unique_constraints: Vec::new(),
                 exclusion_constraints: vec![],
                 comment: None,
                 row_level_security: false,
                 force_row_level_security: false,
+                replica_identity: None,
                 policies: vec![],
                 partition_by: None,
 
@@ -693,6 +849,7 @@ mod tests {
                 name: "uuid-ossp".to_string(),
                 version: None,
                 schema: None,
+                relocatable: false,
                 comment: None,
             },
         );
@@ -717,10 +874,12 @@ mod tests {
                 primary_key: None,
                 foreign_keys: vec![],
                 check_constraints: vec![],
+                unique_constraints: Vec::new(),
                 exclusion_constraints: vec![],
                 comment: None,
                 row_level_security: false,
                 force_row_level_security: false,
+                replica_identity: None,
                 policies: vec![],
                 partition_by: None,
 
@@ -738,10 +897,12 @@ mod tests {
                 primary_key: None,
                 foreign_keys: vec![],
                 check_constraints: vec![],
+                unique_constraints: Vec::new(),
                 exclusion_constraints: vec![],
                 comment: None,
                 row_level_security: false,
                 force_row_level_security: false,
+                replica_identity: None,
                 policies: vec![],
                 partition_by: None,
 
@@ -761,6 +922,9 @@ mod tests {
                 owner: None,
                 grants: Vec::new(),
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
         schema.views.insert(
@@ -774,6 +938,9 @@ mod tests {
                 owner: None,
                 grants: Vec::new(),
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
 
@@ -1181,10 +1348,12 @@ mod tests {
                 primary_key: None,
                 foreign_keys: vec![],
                 check_constraints: vec![],
+                unique_constraints: Vec::new(),
                 exclusion_constraints: vec![],
                 comment: None,
                 row_level_security: false,
                 force_row_level_security: false,
+                replica_identity: None,
                 policies: vec![],
                 partition_by: None,
 
@@ -1230,10 +1399,12 @@ mod tests {
                 primary_key: None,
                 foreign_keys: vec![],
                 check_constraints: vec![],
+                unique_constraints: Vec::new(),
                 exclusion_constraints: vec![],
                 comment: None,
                 row_level_security: false,
                 force_row_level_security: false,
+                replica_identity: None,
                 policies: vec![],
                 partition_by: None,
 
@@ -1252,6 +1423,9 @@ mod tests {
                 owner: None,
                 grants: Vec::new(),
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         );
 
@@ -1272,6 +1446,7 @@ mod tests {
                 name: "uuid-ossp".to_string(),
                 version: None,
                 schema: None,
+                relocatable: false,
                 comment: None,
             },
         );
@@ -1285,10 +1460,12 @@ mod tests {
                 primary_key: None,
                 foreign_keys: vec![],
                 check_constraints: vec![],
+                unique_constraints: Vec::new(),
                 exclusion_constraints: vec![],
                 comment: None,
                 row_level_security: false,
                 force_row_level_security: false,
+                replica_identity: None,
                 policies: vec![],
                 partition_by: None,
 
@@ -1438,10 +1615,12 @@ mod tests {
             primary_key: None,
             foreign_keys: vec![],
             check_constraints: vec![],
+            unique_constraints: Vec::new(),
             exclusion_constraints: vec![],
             comment: None,
             row_level_security: true,
             force_row_level_security: false,
+            replica_identity: None,
             policies: vec![Policy {
                 name: "user_policy".to_string(),
                 table_schema: "public".to_string(),
@@ -1488,7 +1667,8 @@ mod tests {
                 unique: false,
                 index_type: IndexType::BTree,
                 predicate: None,
-                is_constraint: false,
+                nulls_not_distinct: false,
+                column_options: Vec::new(),
             }],
             primary_key: None,
             foreign_keys: vec![ForeignKey {
@@ -1499,12 +1679,16 @@ mod tests {
                 referenced_columns: vec!["id".to_string()],
                 on_delete: ReferentialAction::Cascade,
                 on_update: ReferentialAction::NoAction,
+                deferrable: false,
+                initially_deferred: false,
             }],
             check_constraints: vec![],
+            unique_constraints: Vec::new(),
             exclusion_constraints: vec![],
             comment: None,
             row_level_security: false,
             force_row_level_security: false,
+            replica_identity: None,
             policies: vec![],
             partition_by: None,
 
@@ -1548,15 +1732,18 @@ mod tests {
                 unique: false,
                 index_type: IndexType::BTree,
                 predicate: None,
-                is_constraint: false,
+                nulls_not_distinct: false,
+                column_options: Vec::new(),
             }],
             primary_key: None,
             foreign_keys: vec![],
             check_constraints: vec![],
+            unique_constraints: Vec::new(),
             exclusion_constraints: vec![],
             comment: None,
             row_level_security: true,
             force_row_level_security: false,
+            replica_identity: None,
             policies: vec![Policy {
                 name: "user_policy".to_string(),
                 table_schema: "public".to_string(),
@@ -1604,15 +1791,18 @@ mod tests {
                 unique: false,
                 index_type: IndexType::BTree,
                 predicate: None,
-                is_constraint: false,
+                nulls_not_distinct: false,
+                column_options: Vec::new(),
             }],
             primary_key: None,
             foreign_keys: vec![],
             check_constraints: vec![],
+            unique_constraints: Vec::new(),
             exclusion_constraints: vec![],
             comment: None,
             row_level_security: true,
             force_row_level_security: false,
+            replica_identity: None,
             policies: vec![Policy {
                 name: "user_policy".to_string(),
                 table_schema: "public".to_string(),
@@ -1679,10 +1869,12 @@ mod tests {
             primary_key: None,
             foreign_keys: vec![],
             check_constraints: vec![],
+            unique_constraints: Vec::new(),
             exclusion_constraints: vec![],
             comment: None,
             row_level_security: true,
             force_row_level_security: false,
+            replica_identity: None,
             policies: vec![Policy {
                 name: "user_policy".to_string(),
                 table_schema: "public".to_string(),
@@ -1756,15 +1948,18 @@ mod tests {
                 unique: false,
                 index_type: IndexType::BTree,
                 predicate: None,
-                is_constraint: false,
+                nulls_not_distinct: false,
+                column_options: Vec::new(),
             }],
             primary_key: None,
             foreign_keys: vec![],
             check_constraints: vec![],
+            unique_constraints: Vec::new(),
             exclusion_constraints: vec![],
             comment: None,
             row_level_security: false,
             force_row_level_security: false,
+            replica_identity: None,
             policies: vec![],
             partition_by: None,
 
@@ -1847,10 +2042,12 @@ mod tests {
                 primary_key: None,
                 foreign_keys: vec![],
                 check_constraints: vec![],
+                unique_constraints: Vec::new(),
                 exclusion_constraints: vec![],
                 comment: None,
                 row_level_security: false,
                 force_row_level_security: false,
+                replica_identity: None,
                 policies: vec![],
                 partition_by: None,
                 owner: None,
@@ -1921,10 +2118,12 @@ mod tests {
             primary_key: None,
             foreign_keys: vec![],
             check_constraints: vec![],
+            unique_constraints: Vec::new(),
             exclusion_constraints: vec![],
             comment: None,
             row_level_security: false,
             force_row_level_security: false,
+            replica_identity: None,
             policies: vec![],
             partition_by: None,
             owner: None,
@@ -1965,10 +2164,12 @@ mod tests {
                 primary_key: None,
                 foreign_keys: vec![],
                 check_constraints: vec![],
+                unique_constraints: Vec::new(),
                 exclusion_constraints: vec![],
                 comment: None,
                 row_level_security: false,
                 force_row_level_security: false,
+                replica_identity: None,
                 policies: vec![],
                 partition_by: None,
                 owner: None,
@@ -2000,10 +2201,12 @@ mod tests {
             primary_key: None,
             foreign_keys: vec![],
             check_constraints: vec![],
+            unique_constraints: Vec::new(),
             exclusion_constraints: vec![],
             comment: None,
             row_level_security: false,
             force_row_level_security: false,
+            replica_identity: None,
             policies: vec![],
             partition_by: None,
             owner: None,
@@ -2037,6 +2240,9 @@ mod tests {
             owner: None,
             grants: Vec::new(),
             comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
         }
     }
 
@@ -2274,6 +2480,7 @@ mod tests {
                 name: "uuid-ossp".to_string(),
                 version: None,
                 schema: None,
+                relocatable: false,
                 comment: None,
             },
         );
@@ -2283,6 +2490,7 @@ mod tests {
                 name: "pgcrypto".to_string(),
                 version: None,
                 schema: Some("public".to_string()),
+                relocatable: false,
                 comment: None,
             },
         );
@@ -2509,4 +2717,92 @@ mod tests {
         assert_eq!(result.tables.len(), 2);
         assert_eq!(result.functions.len(), 1);
     }
+
+    #[test]
+    fn parse_schema_mode_accepts_schema_equals_mode() {
+        assert_eq!(
+            parse_schema_mode("auth=observe").unwrap(),
+            ("auth".to_string(), SchemaMode::Observe)
+        );
+        assert_eq!(
+            parse_schema_mode(" storage = IGNORE ").unwrap(),
+            ("storage".to_string(), SchemaMode::Ignore)
+        );
+        assert!(parse_schema_mode("auth").is_err());
+        assert!(parse_schema_mode("=observe").is_err());
+        assert!(parse_schema_mode("auth=readonly").is_err());
+    }
+
+    #[test]
+    fn filter_schema_drops_ignored_schemas() {
+        let mut schema = Schema::default();
+        schema
+            .tables
+            .insert("public.users".to_string(), make_table("public", "users"));
+        schema
+            .tables
+            .insert("auth.users".to_string(), make_table("auth", "users"));
+        schema.functions.insert(
+            "graphql.resolve".to_string(),
+            make_function("graphql", "resolve"),
+        );
+
+        let filter = Filter::new(&[], &[], &[], &[]).unwrap().with_schema_modes(
+            [
+                ("auth".to_string(), SchemaMode::Observe),
+                ("graphql".to_string(), SchemaMode::Ignore),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let filtered = filter_schema(&schema, &filter);
+
+        assert_eq!(filtered.tables.len(), 2);
+        assert!(filtered.functions.is_empty());
+    }
+
+    #[test]
+    fn should_plan_skips_observed_schemas() {
+        let filter = Filter::new(&[], &[], &[], &[]).unwrap().with_schema_modes(
+            [("auth".to_string(), SchemaMode::Observe)]
+                .into_iter()
+                .collect(),
+        );
+
+        assert!(!filter.should_plan(&MigrationOp::DropTable("auth.users".to_string())));
+        assert!(!filter.should_plan(&MigrationOp::DropSchema {
+            name: "auth".to_string(),
+            cascade: false,
+        }));
+        assert!(filter.should_plan(&MigrationOp::DropTable("public.users".to_string())));
+        assert!(filter.should_plan(&MigrationOp::DropExtension("pgcrypto".to_string())));
+    }
+
+    #[test]
+    fn external_objects_are_left_out_of_both_sides_of_the_diff() {
+        let target = crate::parser::parse_sql_string(
+            "-- pgmold:external auth.*
+             CREATE TABLE public.profiles (
+                 id uuid PRIMARY KEY REFERENCES auth.users (id)
+             );",
+        )
+        .unwrap();
+        let current = crate::parser::parse_sql_string(
+            "CREATE TABLE auth.users (id uuid PRIMARY KEY);
+             CREATE TABLE auth.sessions (id uuid PRIMARY KEY);",
+        )
+        .unwrap();
+
+        let current = exclude_external(&current, &target.external);
+        let target = exclude_external(&target, &target.external);
+        assert!(current.tables.is_empty());
+
+        let ops = crate::diff::compute_diff(&current, &target);
+        assert!(ops
+            .iter()
+            .all(|op| op.schema().is_none_or(|schema| schema != "auth")));
+        assert!(ops
+            .iter()
+            .any(|op| matches!(op, MigrationOp::CreateTable(table) if table.name == "profiles")));
+    }
 }
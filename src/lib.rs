@@ -1,3 +1,4 @@
+pub mod api;
 pub mod apply;
 pub mod baseline;
 pub mod check;
@@ -13,5 +14,7 @@ pub mod parser;
 pub mod pg;
 pub mod plan;
 pub mod provider;
+pub mod snapshot;
+pub mod tenant;
 pub mod util;
 pub mod validate;
@@ -0,0 +1,157 @@
+//! Dropping a table that other tables still reference through foreign keys
+//! fails, and `DROP ... CASCADE` would silently take those constraints with
+//! it. The `deny_drop_referenced_table` rule names the referencing tables;
+//! `--cascade` acknowledges the drop and plans the constraint drops first.
+//! Schemas are likewise dropped without `CASCADE` unless `--cascade` is given.
+
+use std::collections::{BTreeMap, HashSet};
+
+use super::{LintResult, LintSeverity};
+use crate::diff::MigrationOp;
+use crate::model::{qualified_name, QualifiedName, Schema};
+
+/// A foreign key in `current` that references a table the plan drops, on a
+/// table the plan keeps, and that the plan does not drop itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InboundForeignKey {
+    pub dropped_table: String,
+    pub table: QualifiedName,
+    pub name: String,
+}
+
+pub fn inbound_foreign_keys(ops: &[MigrationOp], current: &Schema) -> Vec<InboundForeignKey> {
+    let dropped: HashSet<&str> = ops
+        .iter()
+        .filter_map(|op| match op {
+            MigrationOp::DropTable(name) => Some(name.as_str()),
+            _ => None,
+        })
+        .collect();
+    if dropped.is_empty() {
+        return Vec::new();
+    }
+    let dropped_foreign_keys: HashSet<(String, &str)> = ops
+        .iter()
+        .filter_map(|op| match op {
+            MigrationOp::DropForeignKey {
+                table,
+                foreign_key_name,
+            } => Some((table.to_string(), foreign_key_name.as_str())),
+            _ => None,
+        })
+        .collect();
+
+    let mut inbound = Vec::new();
+    for (name, table) in &current.tables {
+        if dropped.contains(name.as_str()) {
+            continue;
+        }
+        for foreign_key in &table.foreign_keys {
+            let referenced = qualified_name(
+                &foreign_key.referenced_schema,
+                &foreign_key.referenced_table,
+            );
+            if dropped.contains(referenced.as_str())
+                && !dropped_foreign_keys.contains(&(name.clone(), foreign_key.name.as_str()))
+            {
+                inbound.push(InboundForeignKey {
+                    dropped_table: referenced,
+                    table: QualifiedName::new(&table.schema, &table.name),
+                    name: foreign_key.name.clone(),
+                });
+            }
+        }
+    }
+    inbound
+}
+
+/// One error per dropped table that foreign keys in `current` still
+/// reference, listing the referencing tables.
+pub(super) fn lint_inbound_foreign_keys(ops: &[MigrationOp], current: &Schema) -> Vec<LintResult> {
+    let mut referencing: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for foreign_key in inbound_foreign_keys(ops, current) {
+        referencing
+            .entry(foreign_key.dropped_table)
+            .or_default()
+            .push(format!("{} ({})", foreign_key.table, foreign_key.name));
+    }
+    referencing
+        .into_iter()
+        .map(|(table, sources)| LintResult {
+            rule: "deny_drop_referenced_table",
+            severity: LintSeverity::Error,
+            message: format!(
+                "Dropping table {table} requires --cascade: it is referenced by foreign keys on {}",
+                sources.join(", ")
+            ),
+        })
+        .collect()
+}
+
+/// `DropForeignKey` operations for every inbound foreign key, which the
+/// planner orders before the table drops.
+pub fn inbound_foreign_key_drops(ops: &[MigrationOp], current: &Schema) -> Vec<MigrationOp> {
+    inbound_foreign_keys(ops, current)
+        .into_iter()
+        .map(|foreign_key| MigrationOp::DropForeignKey {
+            table: foreign_key.table,
+            foreign_key_name: foreign_key.name,
+        })
+        .collect()
+}
+
+/// Marks every schema drop in `ops` `CASCADE`, so objects the plan does not
+/// list go with the schema instead of failing the drop.
+pub fn cascade_schema_drops(ops: Vec<MigrationOp>) -> Vec<MigrationOp> {
+    ops.into_iter()
+        .map(|op| match op {
+            MigrationOp::DropSchema { name, .. } => MigrationOp::DropSchema {
+                name,
+                cascade: true,
+            },
+            op => op,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_sql_string;
+
+    #[test]
+    fn reports_tables_still_referencing_a_dropped_table() {
+        let current = parse_sql_string(
+            "CREATE TABLE accounts (id BIGINT PRIMARY KEY);
+             CREATE TABLE invoices (id BIGINT PRIMARY KEY,
+                 account_id BIGINT CONSTRAINT invoices_account_fkey REFERENCES accounts (id));
+             CREATE TABLE sessions (id BIGINT PRIMARY KEY,
+                 account_id BIGINT CONSTRAINT sessions_account_fkey REFERENCES accounts (id));",
+        )
+        .unwrap();
+        let drop_accounts = MigrationOp::DropTable("public.accounts".to_string());
+
+        let results = lint_inbound_foreign_keys(
+            &[
+                drop_accounts.clone(),
+                MigrationOp::DropTable("public.sessions".to_string()),
+            ],
+            &current,
+        );
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].rule, "deny_drop_referenced_table");
+        assert!(
+            results[0]
+                .message
+                .ends_with("foreign keys on public.invoices (invoices_account_fkey)"),
+            "{}",
+            results[0].message
+        );
+
+        let drops = inbound_foreign_key_drops(std::slice::from_ref(&drop_accounts), &current);
+        assert_eq!(drops.len(), 2);
+        let mut ops = vec![drop_accounts];
+        ops.extend(drops);
+        assert!(lint_inbound_foreign_keys(&ops, &current).is_empty());
+    }
+}
@@ -28,6 +28,17 @@ pub fn detect_lock_hazards(ops: &[MigrationOp]) -> Vec<LockWarning> {
                     message: format!("DROP TABLE acquires ACCESS EXCLUSIVE lock on table {table}"),
                 });
             }
+            MigrationOp::AddColumn { table, column } if column.generated.is_some() => {
+                warnings.push(LockWarning {
+                    operation: "AddColumn".to_string(),
+                    table: table.to_string(),
+                    lock_level: LockLevel::AccessExclusive,
+                    message: format!(
+                        "ADD COLUMN ... GENERATED ALWAYS AS ... STORED holds an ACCESS EXCLUSIVE lock on table {table} while it rewrites every row (column {})",
+                        column.name
+                    ),
+                });
+            }
             MigrationOp::DropColumn { table, column } => {
                 warnings.push(LockWarning {
                     operation: "DropColumn".to_string(),
@@ -152,6 +163,20 @@ pub fn detect_lock_hazards(ops: &[MigrationOp]) -> Vec<LockWarning> {
                     ),
                 });
             }
+            MigrationOp::AddUniqueConstraint {
+                table,
+                unique_constraint,
+            } => {
+                warnings.push(LockWarning {
+                    operation: "AddUniqueConstraint".to_string(),
+                    table: table.to_string(),
+                    lock_level: LockLevel::AccessExclusive,
+                    message: format!(
+                        "ADD CONSTRAINT UNIQUE acquires ACCESS EXCLUSIVE lock on table {table} while it builds its index (constraint {}; use --concurrent-indexes to build it without blocking)",
+                        unique_constraint.name
+                    ),
+                });
+            }
             MigrationOp::DropUniqueConstraint {
                 table,
                 constraint_name,
@@ -205,6 +230,16 @@ pub fn detect_lock_hazards(ops: &[MigrationOp]) -> Vec<LockWarning> {
                     ),
                 });
             }
+            MigrationOp::SetReplicaIdentity { table, .. } => {
+                warnings.push(LockWarning {
+                    operation: "SetReplicaIdentity".to_string(),
+                    table: table.to_string(),
+                    lock_level: LockLevel::AccessExclusive,
+                    message: format!(
+                        "REPLICA IDENTITY acquires ACCESS EXCLUSIVE lock on table {table}"
+                    ),
+                });
+            }
             MigrationOp::CreatePolicy(policy) => {
                 warnings.push(LockWarning {
                     operation: "CreatePolicy".to_string(),
@@ -298,6 +333,26 @@ pub fn detect_lock_hazards(ops: &[MigrationOp]) -> Vec<LockWarning> {
                     message: format!("ALTER VIEW acquires ACCESS EXCLUSIVE lock on view {name}"),
                 });
             }
+            MigrationOp::AlterViewOptions { name, .. } => {
+                warnings.push(LockWarning {
+                    operation: "AlterViewOptions".to_string(),
+                    table: name.clone(),
+                    lock_level: LockLevel::AccessExclusive,
+                    message: format!(
+                        "ALTER VIEW SET/RESET acquires ACCESS EXCLUSIVE lock on view {name}"
+                    ),
+                });
+            }
+            MigrationOp::RefreshMaterializedView { name } => {
+                warnings.push(LockWarning {
+                    operation: "RefreshMaterializedView".to_string(),
+                    table: name.clone(),
+                    lock_level: LockLevel::AccessExclusive,
+                    message: format!(
+                        "REFRESH MATERIALIZED VIEW acquires ACCESS EXCLUSIVE lock on view {name} while it runs the view's query"
+                    ),
+                });
+            }
             MigrationOp::DropSequence(name) => {
                 warnings.push(LockWarning {
                     operation: "DropSequence".to_string(),
@@ -368,6 +423,9 @@ mod tests {
                 data_type: Some(PgType::BigInt),
                 nullable: None,
                 default: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         }];
         let warnings = detect_lock_hazards(&ops);
@@ -378,6 +436,24 @@ mod tests {
         assert_eq!(warnings[0].lock_level, LockLevel::AccessExclusive);
     }
 
+    #[test]
+    fn default_only_alter_column_is_not_a_lock_hazard() {
+        let ops = vec![MigrationOp::AlterColumn {
+            table: QualifiedName::new("public", "users"),
+            column: "status".to_string(),
+            changes: ColumnChanges {
+                data_type: None,
+                nullable: None,
+                default: Some(Some("'active'".to_string())),
+                statistics: None,
+                compression: None,
+                identity: None,
+            },
+        }];
+
+        assert!(detect_lock_hazards(&ops).is_empty());
+    }
+
     #[test]
     fn detects_alter_column_set_not_null_lock() {
         let ops = vec![MigrationOp::AlterColumn {
@@ -387,6 +463,9 @@ mod tests {
                 data_type: None,
                 nullable: Some(false),
                 default: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         }];
         let warnings = detect_lock_hazards(&ops);
@@ -407,7 +486,8 @@ mod tests {
                 unique: false,
                 index_type: IndexType::BTree,
                 predicate: None,
-                is_constraint: false,
+                nulls_not_distinct: false,
+                column_options: Vec::new(),
             },
         }];
         let warnings = detect_lock_hazards(&ops);
@@ -459,6 +539,8 @@ mod tests {
                 referenced_columns: vec!["id".to_string()],
                 on_delete: ReferentialAction::Cascade,
                 on_update: ReferentialAction::NoAction,
+                deferrable: false,
+                initially_deferred: false,
             },
         }];
         let warnings = detect_lock_hazards(&ops);
@@ -526,6 +608,9 @@ mod tests {
                     default: None,
                     comment: None,
                     generated: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
                 },
             },
             MigrationOp::AlterColumn {
@@ -535,6 +620,9 @@ mod tests {
                     data_type: None,
                     nullable: None,
                     default: Some(Some("'default'".to_string())),
+                    statistics: None,
+                    compression: None,
+                    identity: None,
                 },
             },
         ];
@@ -558,7 +646,8 @@ mod tests {
                     unique: false,
                     index_type: IndexType::BTree,
                     predicate: None,
-                    is_constraint: false,
+                    nulls_not_distinct: false,
+                    column_options: Vec::new(),
                 },
             },
         ];
@@ -760,6 +849,9 @@ mod tests {
                 owner: None,
                 grants: Vec::new(),
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         }];
         let warnings = detect_lock_hazards(&ops);
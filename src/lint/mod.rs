@@ -1,12 +1,47 @@
+mod cascade;
 pub mod locks;
+mod protected;
+mod replication;
+mod settings;
+mod size;
+
+use std::collections::BTreeMap;
+
+use glob::Pattern;
 
 use crate::diff::MigrationOp;
-use crate::model::PgType;
+use crate::filter::ObjectType;
+use crate::model::{PgType, QualifiedName, Schema};
+
+use cascade::lint_inbound_foreign_keys;
+pub use cascade::{
+    cascade_schema_drops, inbound_foreign_key_drops, inbound_foreign_keys, InboundForeignKey,
+};
+pub use protected::parse_protected;
+pub use settings::{lint_required_settings, parse_required_settings, SettingRequirement};
+pub use size::{DropThreshold, TableSize};
 
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct LintOptions {
     pub allow_destructive: bool,
     pub is_production: bool,
+    /// Per-rule severity overrides keyed by rule name. `None` silences the rule.
+    pub severity_overrides: BTreeMap<String, Option<LintSeverity>>,
+    /// Objects no operation may drop or alter, as object type and name glob.
+    pub protected: Vec<(ObjectType, Pattern)>,
+    /// Deny operations logical replication cannot follow (see `replication`).
+    pub logical_replication: bool,
+    /// Tables and partitions admitted by this threshold may be dropped
+    /// without `allow_destructive` (see `size`).
+    pub drop_threshold: Option<DropThreshold>,
+    /// Estimated sizes of the tables and partitions the plan drops, by
+    /// qualified name.
+    pub table_sizes: BTreeMap<String, TableSize>,
+    /// Cluster settings the server must meet (see `settings`).
+    pub required_settings: Vec<(String, SettingRequirement)>,
+    /// The database's schema before the plan, whose foreign keys to dropped
+    /// tables deny the drop (see `cascade`).
+    pub current: Option<Schema>,
 }
 
 impl LintOptions {
@@ -16,10 +51,45 @@ impl LintOptions {
             is_production: std::env::var("PGMOLD_PROD")
                 .map(|v| v == "1")
                 .unwrap_or(false),
+            severity_overrides: std::env::var("PGMOLD_LINT_SEVERITY")
+                .map(|v| parse_severity_overrides(&v))
+                .unwrap_or_default(),
+            protected: std::env::var("PGMOLD_PROTECTED")
+                .map(|v| parse_protected(&v))
+                .unwrap_or_default(),
+            logical_replication: std::env::var("PGMOLD_LOGICAL_REPLICATION")
+                .map(|v| v == "1")
+                .unwrap_or(false),
+            drop_threshold: std::env::var("PGMOLD_DROP_THRESHOLD")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            table_sizes: BTreeMap::new(),
+            required_settings: std::env::var("PGMOLD_REQUIRED_SETTINGS")
+                .ok()
+                .and_then(|v| parse_required_settings(&v).ok())
+                .unwrap_or_default(),
+            current: None,
         }
     }
 }
 
+/// Parses `rule=error,rule=warning,rule=off` as written to `PGMOLD_LINT_SEVERITY`.
+/// Entries with an unknown level are ignored.
+pub fn parse_severity_overrides(spec: &str) -> BTreeMap<String, Option<LintSeverity>> {
+    spec.split(',')
+        .filter_map(|entry| {
+            let (rule, level) = entry.split_once('=')?;
+            let severity = match level.trim().to_ascii_lowercase().as_str() {
+                "error" => Some(LintSeverity::Error),
+                "warning" | "warn" => Some(LintSeverity::Warning),
+                "off" => None,
+                _ => return None,
+            };
+            Some((rule.trim().to_string(), severity))
+        })
+        .collect()
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum LintSeverity {
     Error,
@@ -34,7 +104,30 @@ pub struct LintResult {
 }
 
 pub fn lint_migration_plan(ops: &[MigrationOp], options: &LintOptions) -> Vec<LintResult> {
-    ops.iter().flat_map(|op| lint_op(op, options)).collect()
+    ops.iter()
+        .flat_map(|op| lint_op(op, options))
+        .chain(lint_index_rebuilds(ops))
+        .chain(
+            options
+                .current
+                .as_ref()
+                .map(|current| lint_inbound_foreign_keys(ops, current))
+                .unwrap_or_default(),
+        )
+        .filter_map(|mut result| {
+            if result.rule == protected::RULE {
+                return Some(result);
+            }
+            match options.severity_overrides.get(result.rule) {
+                Some(Some(severity)) => {
+                    result.severity = severity.clone();
+                    Some(result)
+                }
+                Some(None) => None,
+                None => Some(result),
+            }
+        })
+        .collect()
 }
 
 pub fn has_errors(results: &[LintResult]) -> bool {
@@ -43,8 +136,46 @@ pub fn has_errors(results: &[LintResult]) -> bool {
         .any(|r| matches!(r.severity, LintSeverity::Error))
 }
 
+/// Indexes the plan drops and creates again under the same name, as the diff
+/// does when their columns, method or predicate change. The table goes
+/// without the index until `CREATE INDEX` finishes scanning it.
+fn lint_index_rebuilds(ops: &[MigrationOp]) -> Vec<LintResult> {
+    let dropped: Vec<(&QualifiedName, &String)> = ops
+        .iter()
+        .filter_map(|op| match op {
+            MigrationOp::DropIndex { table, index_name }
+            | MigrationOp::DropIndexConcurrently { table, index_name } => Some((table, index_name)),
+            _ => None,
+        })
+        .collect();
+    ops.iter()
+        .filter_map(|op| match op {
+            MigrationOp::AddIndex { table, index }
+            | MigrationOp::CreateIndexConcurrently { table, index }
+                if dropped.contains(&(table, &index.name)) =>
+            {
+                Some(LintResult {
+                    rule: "warn_index_rebuild",
+                    severity: LintSeverity::Warning,
+                    message: format!(
+                        "Index \"{}\" on {table} is dropped and rebuilt USING {}; queries relying on it scan the table until the rebuild finishes",
+                        index.name,
+                        index.index_type.method()
+                    ),
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
 fn lint_op(op: &MigrationOp, options: &LintOptions) -> Vec<LintResult> {
-    let mut results = Vec::new();
+    let mut results: Vec<LintResult> = protected::lint_protected(op, &options.protected)
+        .into_iter()
+        .collect();
+    if options.logical_replication {
+        results.extend(replication::lint_replication(op));
+    }
 
     match op {
         MigrationOp::DropColumn { table, column } => {
@@ -59,6 +190,30 @@ fn lint_op(op: &MigrationOp, options: &LintOptions) -> Vec<LintResult> {
             }
         }
 
+        MigrationOp::RenameTable { table, new_name } => {
+            results.push(LintResult {
+                rule: "warn_rename",
+                severity: LintSeverity::Warning,
+                message: format!(
+                    "Renaming table {table} to {new_name} breaks queries that still use the old name"
+                ),
+            });
+        }
+
+        MigrationOp::RenameColumn {
+            table,
+            column,
+            new_name,
+        } => {
+            results.push(LintResult {
+                rule: "warn_rename",
+                severity: LintSeverity::Warning,
+                message: format!(
+                    "Renaming column {table}.{column} to {new_name} breaks queries that still use the old name"
+                ),
+            });
+        }
+
         MigrationOp::DropTable(name) => {
             if options.is_production {
                 results.push(LintResult {
@@ -68,6 +223,8 @@ fn lint_op(op: &MigrationOp, options: &LintOptions) -> Vec<LintResult> {
                         "Dropping table {name} is not allowed in production (PGMOLD_PROD=1)"
                     ),
                 });
+            } else if let Some(small) = size::small_drop(name, options) {
+                results.push(small);
             } else if !options.allow_destructive {
                 results.push(LintResult {
                     rule: "deny_drop_table",
@@ -77,6 +234,20 @@ fn lint_op(op: &MigrationOp, options: &LintOptions) -> Vec<LintResult> {
             }
         }
 
+        MigrationOp::DropPartition(name) if options.drop_threshold.is_some() => {
+            if let Some(small) = size::small_drop(name, options) {
+                results.push(small);
+            } else if !options.allow_destructive {
+                results.push(LintResult {
+                    rule: "deny_drop_partition",
+                    severity: LintSeverity::Error,
+                    message: format!(
+                        "Dropping partition {name} above the drop threshold requires --allow-destructive flag"
+                    ),
+                });
+            }
+        }
+
         MigrationOp::AlterColumn {
             table,
             column,
@@ -105,6 +276,46 @@ fn lint_op(op: &MigrationOp, options: &LintOptions) -> Vec<LintResult> {
             }
         }
 
+        MigrationOp::SetExtensionSchema {
+            name,
+            schema,
+            relocatable: false,
+        } => {
+            results.push(LintResult {
+                rule: "deny_extension_not_relocatable",
+                severity: LintSeverity::Error,
+                message: format!(
+                    "Extension \"{name}\" cannot be moved to schema \"{schema}\" because it is not relocatable; drop and recreate it there instead"
+                ),
+            });
+        }
+
+        MigrationOp::UpdateExtension {
+            name,
+            from_version,
+            version,
+        } => {
+            results.push(LintResult {
+                rule: "warn_extension_update",
+                severity: LintSeverity::Warning,
+                message: format!(
+                    "Updating extension \"{name}\" from {} to {version} runs its upgrade scripts, which may change or drop the functions, types and operators it owns",
+                    from_version.as_deref().unwrap_or("an unknown version")
+                ),
+            });
+        }
+
+        MigrationOp::AddColumn { table, column } if column.generated.is_some() => {
+            results.push(LintResult {
+                rule: "warn_generated_column_rewrite",
+                severity: LintSeverity::Warning,
+                message: format!(
+                    "Adding stored generated column {table}.{} computes it for every row, rewriting the table under an ACCESS EXCLUSIVE lock",
+                    column.name
+                ),
+            });
+        }
+
         MigrationOp::DropView { name, materialized } => {
             if !options.allow_destructive {
                 let (rule, view_type) = if *materialized {
@@ -187,7 +398,7 @@ fn lint_op(op: &MigrationOp, options: &LintOptions) -> Vec<LintResult> {
             }
         }
 
-        MigrationOp::DropSchema(name) => {
+        MigrationOp::DropSchema { name, cascade } => {
             if !options.allow_destructive {
                 results.push(LintResult {
                     rule: "deny_drop_schema",
@@ -197,6 +408,38 @@ fn lint_op(op: &MigrationOp, options: &LintOptions) -> Vec<LintResult> {
                     ),
                 });
             }
+            if *cascade {
+                results.push(LintResult {
+                    rule: "warn_drop_schema_cascade",
+                    severity: LintSeverity::Warning,
+                    message: format!(
+                        "Dropping schema \"{name}\" with CASCADE also drops every object in it the plan does not list"
+                    ),
+                });
+            }
+        }
+
+        MigrationOp::CreateView(view) | MigrationOp::AlterView { new_view: view, .. }
+            if crate::util::query_selects_star(&view.query) =>
+        {
+            results.push(LintResult {
+                rule: "warn_select_star_view",
+                severity: LintSeverity::Warning,
+                message: format!(
+                    "View {}.{} selects *, which PostgreSQL expands to the columns that exist now; list the columns explicitly",
+                    view.schema, view.name
+                ),
+            });
+        }
+
+        MigrationOp::DropRawObject(name) => {
+            results.push(LintResult {
+                rule: "warn_drop_raw_object",
+                severity: LintSeverity::Warning,
+                message: format!(
+                    "Raw object \"{name}\" is no longer declared; pgmold stops tracking it but cannot undo its SQL"
+                ),
+            });
         }
 
         MigrationOp::DropExtension(name) => {
@@ -225,6 +468,7 @@ fn lint_op(op: &MigrationOp, options: &LintOptions) -> Vec<LintResult> {
 
         MigrationOp::CreateSchema(_)
         | MigrationOp::CreateExtension(_)
+        | MigrationOp::SetExtensionSchema { .. }
         | MigrationOp::CreateServer(_)
         | MigrationOp::DropServer(_)
         | MigrationOp::AlterServer { .. }
@@ -240,8 +484,10 @@ fn lint_op(op: &MigrationOp, options: &LintOptions) -> Vec<LintResult> {
         | MigrationOp::DropPrimaryKey { .. }
         | MigrationOp::AddIndex { .. }
         | MigrationOp::DropIndex { .. }
+        | MigrationOp::AddUniqueConstraint { .. }
         | MigrationOp::AddForeignKey { .. }
         | MigrationOp::DropForeignKey { .. }
+        | MigrationOp::AlterForeignKeyDeferral { .. }
         | MigrationOp::AddCheckConstraint { .. }
         | MigrationOp::DropCheckConstraint { .. }
         | MigrationOp::AddExclusionConstraint { .. }
@@ -250,6 +496,7 @@ fn lint_op(op: &MigrationOp, options: &LintOptions) -> Vec<LintResult> {
         | MigrationOp::DisableRls { .. }
         | MigrationOp::ForceRls { .. }
         | MigrationOp::NoForceRls { .. }
+        | MigrationOp::SetReplicaIdentity { .. }
         | MigrationOp::CreatePolicy(_)
         | MigrationOp::DropPolicy { .. }
         | MigrationOp::AlterPolicy { .. }
@@ -260,11 +507,18 @@ fn lint_op(op: &MigrationOp, options: &LintOptions) -> Vec<LintResult> {
         | MigrationOp::DropAggregate { .. }
         | MigrationOp::CreateView(_)
         | MigrationOp::AlterView { .. }
+        | MigrationOp::AlterViewOptions { .. }
+        | MigrationOp::RefreshMaterializedView { .. }
         | MigrationOp::CreateTrigger(_)
         | MigrationOp::AlterTriggerEnabled { .. }
         | MigrationOp::CreateSequence(_)
         | MigrationOp::AlterOwner { .. }
+        | MigrationOp::SetSchema { .. }
         | MigrationOp::SetColumnNotNull { .. }
+        | MigrationOp::CreateIndexConcurrently { .. }
+        | MigrationOp::DropIndexConcurrently { .. }
+        | MigrationOp::AddPrimaryKeyUsingIndex { .. }
+        | MigrationOp::AddUniqueConstraintUsingIndex { .. }
         | MigrationOp::GrantPrivileges { .. }
         | MigrationOp::RevokePrivileges { .. }
         | MigrationOp::AlterDefaultPrivileges { .. }
@@ -272,8 +526,11 @@ fn lint_op(op: &MigrationOp, options: &LintOptions) -> Vec<LintResult> {
         | MigrationOp::DropVersionSchema { .. }
         | MigrationOp::CreateVersionView { .. }
         | MigrationOp::DropVersionView { .. }
+        | MigrationOp::ApplyRawObject(_)
         | MigrationOp::BackfillHint { .. }
-        | MigrationOp::SetComment { .. } => {}
+        | MigrationOp::SetComment { .. }
+        | MigrationOp::SetSequenceValue { .. }
+        | MigrationOp::RenameSequence { .. } => {}
     }
 
     results
@@ -290,7 +547,7 @@ fn is_type_narrowing(new_type: &PgType) -> bool {
 mod tests {
     use super::*;
     use crate::diff::ColumnChanges;
-    use crate::model::QualifiedName;
+    use crate::model::{Column, QualifiedName};
 
     #[test]
     fn blocks_drop_column_without_flag() {
@@ -301,6 +558,7 @@ mod tests {
         let options = LintOptions {
             allow_destructive: false,
             is_production: false,
+            ..LintOptions::default()
         };
 
         let results = lint_migration_plan(&ops, &options);
@@ -308,6 +566,194 @@ mod tests {
         assert_eq!(results[0].rule, "deny_drop_column");
     }
 
+    #[test]
+    fn severity_overrides_downgrade_or_silence_rules() {
+        let ops = vec![
+            MigrationOp::DropColumn {
+                table: QualifiedName::new("public", "users"),
+                column: "email".to_string(),
+            },
+            MigrationOp::DropTable("public.sessions".to_string()),
+        ];
+        let options = LintOptions {
+            allow_destructive: false,
+            is_production: true,
+            severity_overrides: parse_severity_overrides(
+                "deny_drop_column=warning, deny_drop_table_in_prod=off, bogus=loud",
+            ),
+            ..LintOptions::default()
+        };
+
+        let results = lint_migration_plan(&ops, &options);
+        assert!(!has_errors(&results));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].rule, "deny_drop_column");
+        assert_eq!(results[0].severity, LintSeverity::Warning);
+        assert!(!options.severity_overrides.contains_key("bogus"));
+    }
+
+    #[test]
+    fn protected_objects_block_even_with_allow_destructive() {
+        let ops = vec![
+            MigrationOp::DropColumn {
+                table: QualifiedName::new("public", "audit_log"),
+                column: "actor".to_string(),
+            },
+            MigrationOp::DropColumn {
+                table: QualifiedName::new("public", "users"),
+                column: "email".to_string(),
+            },
+        ];
+        let options = LintOptions {
+            allow_destructive: true,
+            is_production: false,
+            protected: parse_protected("tables:public.audit_*"),
+            ..LintOptions::default()
+        };
+
+        let results = lint_migration_plan(&ops, &options);
+        assert!(has_errors(&results));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].rule, "deny_protected_object");
+    }
+
+    #[test]
+    fn severity_overrides_cannot_relax_protected_objects() {
+        let ops = vec![MigrationOp::DropTable("public.audit_log".to_string())];
+        for spec in ["deny_protected_object=off", "deny_protected_object=warning"] {
+            let options = LintOptions {
+                severity_overrides: parse_severity_overrides(spec),
+                protected: parse_protected("tables:public.audit_*"),
+                ..LintOptions::default()
+            };
+
+            let results = lint_migration_plan(&ops, &options);
+            assert!(results
+                .iter()
+                .any(|r| r.rule == "deny_protected_object" && r.severity == LintSeverity::Error));
+        }
+    }
+
+    #[test]
+    fn severity_overrides_apply_to_referenced_table_drops() {
+        let current = crate::parser::parse_sql_string(
+            "CREATE TABLE accounts (id BIGINT PRIMARY KEY);
+             CREATE TABLE invoices (id BIGINT PRIMARY KEY,
+                 account_id BIGINT REFERENCES accounts (id));",
+        )
+        .unwrap();
+        let ops = vec![MigrationOp::DropTable("public.accounts".to_string())];
+        let referenced = |results: Vec<LintResult>| {
+            results
+                .into_iter()
+                .find(|r| r.rule == "deny_drop_referenced_table")
+                .map(|r| r.severity)
+        };
+        let options = LintOptions {
+            allow_destructive: true,
+            current: Some(current),
+            ..LintOptions::default()
+        };
+        assert_eq!(
+            referenced(lint_migration_plan(&ops, &options)),
+            Some(LintSeverity::Error)
+        );
+
+        let options = LintOptions {
+            severity_overrides: parse_severity_overrides("deny_drop_referenced_table=warning"),
+            ..options
+        };
+        assert_eq!(
+            referenced(lint_migration_plan(&ops, &options)),
+            Some(LintSeverity::Warning)
+        );
+    }
+
+    #[test]
+    fn logical_replication_denies_changes_the_subscriber_cannot_follow() {
+        let ops = vec![
+            MigrationOp::AlterColumn {
+                table: QualifiedName::new("public", "users"),
+                column: "age".to_string(),
+                changes: ColumnChanges {
+                    data_type: Some(PgType::BigInt),
+                    nullable: None,
+                    default: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
+                },
+            },
+            MigrationOp::DropPrimaryKey {
+                table: QualifiedName::new("public", "users"),
+            },
+            MigrationOp::AlterColumn {
+                table: QualifiedName::new("public", "users"),
+                column: "name".to_string(),
+                changes: ColumnChanges {
+                    data_type: None,
+                    nullable: Some(true),
+                    default: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
+                },
+            },
+        ];
+        let mut options = LintOptions::default();
+        assert!(lint_migration_plan(&ops, &options).is_empty());
+
+        options.logical_replication = true;
+        let results = lint_migration_plan(&ops, &options);
+        let rules: Vec<&str> = results.iter().map(|r| r.rule).collect();
+        assert_eq!(
+            rules,
+            vec![
+                "deny_replication_alter_column_type",
+                "deny_replication_drop_primary_key"
+            ]
+        );
+        assert!(has_errors(&results));
+    }
+
+    #[test]
+    fn drop_threshold_admits_small_tables_only() {
+        let ops = vec![
+            MigrationOp::DropTable("public.scratch".to_string()),
+            MigrationOp::DropTable("public.events".to_string()),
+            MigrationOp::DropTable("public.unanalyzed".to_string()),
+        ];
+        let mut options = LintOptions {
+            drop_threshold: Some("rows=1000,size=10MB".parse().unwrap()),
+            ..LintOptions::default()
+        };
+        for (name, rows, bytes) in [
+            ("public.scratch", Some(12), 16_384),
+            ("public.events", Some(5_000_000), 2 << 30),
+            ("public.unanalyzed", None, 8_192),
+        ] {
+            options
+                .table_sizes
+                .insert(name.to_string(), TableSize { rows, bytes });
+        }
+
+        let results = lint_migration_plan(&ops, &options);
+        let rules: Vec<(&str, LintSeverity)> = results
+            .iter()
+            .map(|r| (r.rule, r.severity.clone()))
+            .collect();
+        assert_eq!(
+            rules,
+            vec![
+                ("warn_drop_small_table", LintSeverity::Warning),
+                ("deny_drop_table", LintSeverity::Error),
+                ("deny_drop_table", LintSeverity::Error),
+            ]
+        );
+        assert!("rows=ten".parse::<DropThreshold>().is_err());
+        assert!("size=10XB".parse::<DropThreshold>().is_err());
+    }
+
     #[test]
     fn allows_drop_column_with_flag() {
         let ops = vec![MigrationOp::DropColumn {
@@ -317,6 +763,7 @@ mod tests {
         let options = LintOptions {
             allow_destructive: true,
             is_production: false,
+            ..LintOptions::default()
         };
 
         let results = lint_migration_plan(&ops, &options);
@@ -329,6 +776,7 @@ mod tests {
         let options = LintOptions {
             allow_destructive: false,
             is_production: false,
+            ..LintOptions::default()
         };
 
         let results = lint_migration_plan(&ops, &options);
@@ -342,6 +790,7 @@ mod tests {
         let options = LintOptions {
             allow_destructive: true,
             is_production: true,
+            ..LintOptions::default()
         };
 
         let results = lint_migration_plan(&ops, &options);
@@ -358,6 +807,9 @@ mod tests {
                 data_type: Some(PgType::Varchar(Some(50))),
                 nullable: None,
                 default: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         }];
         let options = LintOptions::default();
@@ -377,6 +829,9 @@ mod tests {
                 data_type: None,
                 nullable: Some(false),
                 default: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         }];
         let options = LintOptions::default();
@@ -386,6 +841,86 @@ mod tests {
         assert_eq!(results[0].rule, "warn_set_not_null");
     }
 
+    #[test]
+    fn warns_on_adding_stored_generated_column() {
+        let ops = vec![MigrationOp::AddColumn {
+            table: QualifiedName::new("public", "products"),
+            column: Column {
+                name: "price_dollars".to_string(),
+                data_type: PgType::Integer,
+                nullable: true,
+                default: None,
+                comment: None,
+                generated: Some("price_cents / 100".to_string()),
+                statistics: None,
+                compression: None,
+                identity: None,
+            },
+        }];
+
+        let results = lint_migration_plan(&ops, &LintOptions::default());
+        assert!(!has_errors(&results));
+        assert_eq!(results[0].rule, "warn_generated_column_rewrite");
+    }
+
+    #[test]
+    fn denies_moving_non_relocatable_extension() {
+        let set_schema = |relocatable| MigrationOp::SetExtensionSchema {
+            name: "postgis".to_string(),
+            schema: "ext".to_string(),
+            relocatable,
+        };
+
+        let results = lint_migration_plan(&[set_schema(false)], &LintOptions::default());
+        assert!(has_errors(&results));
+        assert_eq!(results[0].rule, "deny_extension_not_relocatable");
+        assert!(lint_migration_plan(&[set_schema(true)], &LintOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn warns_on_extension_update() {
+        let ops = vec![MigrationOp::UpdateExtension {
+            name: "postgis".to_string(),
+            from_version: Some("3.3.2".to_string()),
+            version: "3.4.0".to_string(),
+        }];
+
+        let results = lint_migration_plan(&ops, &LintOptions::default());
+        assert!(!has_errors(&results));
+        assert_eq!(results[0].rule, "warn_extension_update");
+    }
+
+    #[test]
+    fn warns_on_index_rebuild() {
+        let table = QualifiedName::new("public", "events");
+        let index = crate::model::Index {
+            name: "events_created_at_idx".to_string(),
+            columns: vec!["created_at".to_string()],
+            unique: false,
+            index_type: crate::model::IndexType::Brin,
+            predicate: None,
+            nulls_not_distinct: false,
+            column_options: Vec::new(),
+        };
+        let add = MigrationOp::AddIndex {
+            table: table.clone(),
+            index,
+        };
+        let ops = vec![
+            MigrationOp::DropIndex {
+                table,
+                index_name: "events_created_at_idx".to_string(),
+            },
+            add.clone(),
+        ];
+
+        let results = lint_migration_plan(&ops, &LintOptions::default());
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].rule, "warn_index_rebuild");
+        assert!(results[0].message.contains("USING brin"));
+        assert!(lint_migration_plan(&[add], &LintOptions::default()).is_empty());
+    }
+
     #[test]
     fn has_errors_returns_false_for_warnings_only() {
         let results = vec![LintResult {
@@ -405,6 +940,7 @@ mod tests {
         let options = LintOptions {
             allow_destructive: false,
             is_production: false,
+            ..LintOptions::default()
         };
 
         let results = lint_migration_plan(&ops, &options);
@@ -421,6 +957,7 @@ mod tests {
         let options = LintOptions {
             allow_destructive: true,
             is_production: false,
+            ..LintOptions::default()
         };
 
         let results = lint_migration_plan(&ops, &options);
@@ -436,6 +973,7 @@ mod tests {
         let options = LintOptions {
             allow_destructive: false,
             is_production: false,
+            ..LintOptions::default()
         };
 
         let results = lint_migration_plan(&ops, &options);
@@ -449,6 +987,7 @@ mod tests {
         let options = LintOptions {
             allow_destructive: false,
             is_production: false,
+            ..LintOptions::default()
         };
 
         let results = lint_migration_plan(&ops, &options);
@@ -462,6 +1001,7 @@ mod tests {
         let options = LintOptions {
             allow_destructive: true,
             is_production: false,
+            ..LintOptions::default()
         };
 
         let results = lint_migration_plan(&ops, &options);
@@ -478,6 +1018,7 @@ mod tests {
         let options = LintOptions {
             allow_destructive: false,
             is_production: false,
+            ..LintOptions::default()
         };
 
         let results = lint_migration_plan(&ops, &options);
@@ -495,6 +1036,7 @@ mod tests {
         let options = LintOptions {
             allow_destructive: true,
             is_production: false,
+            ..LintOptions::default()
         };
 
         let results = lint_migration_plan(&ops, &options);
@@ -507,6 +1049,7 @@ mod tests {
         let options = LintOptions {
             allow_destructive: false,
             is_production: false,
+            ..LintOptions::default()
         };
 
         let results = lint_migration_plan(&ops, &options);
@@ -520,6 +1063,7 @@ mod tests {
         let options = LintOptions {
             allow_destructive: true,
             is_production: false,
+            ..LintOptions::default()
         };
 
         let results = lint_migration_plan(&ops, &options);
@@ -545,6 +1089,25 @@ mod tests {
         assert!(matches!(results[0].severity, LintSeverity::Warning));
     }
 
+    #[test]
+    fn warns_on_select_star_view() {
+        let schema = crate::parser::parse_sql_string(
+            "CREATE VIEW public.all_users AS SELECT * FROM public.users;
+             CREATE VIEW public.user_ids AS SELECT users.id FROM public.users;",
+        )
+        .unwrap();
+        let ops: Vec<MigrationOp> = schema
+            .views
+            .into_values()
+            .map(MigrationOp::CreateView)
+            .collect();
+
+        let results = lint_migration_plan(&ops, &LintOptions::default());
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].rule, "warn_select_star_view");
+        assert!(results[0].message.contains("public.all_users"));
+    }
+
     #[test]
     fn allows_alter_sequence_without_restart() {
         use crate::diff::SequenceChanges;
@@ -571,6 +1134,7 @@ mod tests {
         let options = LintOptions {
             allow_destructive: false,
             is_production: false,
+            ..LintOptions::default()
         };
 
         let results = lint_migration_plan(&ops, &options);
@@ -587,6 +1151,7 @@ mod tests {
         let options = LintOptions {
             allow_destructive: true,
             is_production: false,
+            ..LintOptions::default()
         };
 
         let results = lint_migration_plan(&ops, &options);
@@ -595,10 +1160,14 @@ mod tests {
 
     #[test]
     fn blocks_drop_schema_without_flag() {
-        let ops = vec![MigrationOp::DropSchema("auth".to_string())];
+        let ops = vec![MigrationOp::DropSchema {
+            name: "auth".to_string(),
+            cascade: false,
+        }];
         let options = LintOptions {
             allow_destructive: false,
             is_production: false,
+            ..LintOptions::default()
         };
 
         let results = lint_migration_plan(&ops, &options);
@@ -608,22 +1177,44 @@ mod tests {
 
     #[test]
     fn allows_drop_schema_with_flag() {
-        let ops = vec![MigrationOp::DropSchema("auth".to_string())];
+        let ops = vec![MigrationOp::DropSchema {
+            name: "auth".to_string(),
+            cascade: false,
+        }];
         let options = LintOptions {
             allow_destructive: true,
             is_production: false,
+            ..LintOptions::default()
         };
 
         let results = lint_migration_plan(&ops, &options);
         assert!(!has_errors(&results));
     }
 
+    #[test]
+    fn warns_on_cascading_schema_drop() {
+        let ops = vec![MigrationOp::DropSchema {
+            name: "auth".to_string(),
+            cascade: true,
+        }];
+        let options = LintOptions {
+            allow_destructive: true,
+            ..Default::default()
+        };
+
+        let results = lint_migration_plan(&ops, &options);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].rule, "warn_drop_schema_cascade");
+        assert_eq!(results[0].severity, LintSeverity::Warning);
+    }
+
     #[test]
     fn blocks_drop_extension_without_flag() {
         let ops = vec![MigrationOp::DropExtension("uuid-ossp".to_string())];
         let options = LintOptions {
             allow_destructive: false,
             is_production: false,
+            ..LintOptions::default()
         };
 
         let results = lint_migration_plan(&ops, &options);
@@ -637,6 +1228,7 @@ mod tests {
         let options = LintOptions {
             allow_destructive: true,
             is_production: false,
+            ..LintOptions::default()
         };
 
         let results = lint_migration_plan(&ops, &options);
@@ -649,6 +1241,7 @@ mod tests {
         let options = LintOptions {
             allow_destructive: false,
             is_production: false,
+            ..LintOptions::default()
         };
 
         let results = lint_migration_plan(&ops, &options);
@@ -662,6 +1255,7 @@ mod tests {
         let options = LintOptions {
             allow_destructive: true,
             is_production: false,
+            ..LintOptions::default()
         };
 
         let results = lint_migration_plan(&ops, &options);
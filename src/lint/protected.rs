@@ -0,0 +1,357 @@
+//! Objects listed under `[protected]` in `.pgmold.toml` may never be dropped
+//! or altered by a plan. The CLI exports the list as `PGMOLD_PROTECTED`, and
+//! the `deny_protected_object` rule fires whatever `--allow-destructive` says.
+
+use glob::Pattern;
+
+use super::{LintResult, LintSeverity};
+use crate::diff::{CommentObjectType, GrantObjectKind, MigrationOp, OwnerObjectKind};
+use crate::filter::ObjectType;
+
+/// Never subject to severity overrides: the protection list is configuration,
+/// not a rule a run may relax.
+pub(super) const RULE: &str = "deny_protected_object";
+
+/// Parses `type:glob,type:glob` as written to `PGMOLD_PROTECTED`, such as
+/// `tables:public.audit_*,functions:auth.*`. Entries with an unknown type or
+/// an invalid glob are ignored; the CLI rejects them before exporting.
+pub fn parse_protected(spec: &str) -> Vec<(ObjectType, Pattern)> {
+    spec.split(',')
+        .filter_map(|entry| {
+            let (object_type, glob) = entry.split_once(':')?;
+            Some((
+                object_type.trim().parse().ok()?,
+                Pattern::new(glob.trim()).ok()?,
+            ))
+        })
+        .collect()
+}
+
+pub(super) fn lint_protected(
+    op: &MigrationOp,
+    protected: &[(ObjectType, Pattern)],
+) -> Option<LintResult> {
+    if protected.is_empty() {
+        return None;
+    }
+    let (object_type, name) = protected_targets(op)
+        .into_iter()
+        .find(|(object_type, name)| {
+            let bare = name.rsplit('.').next().unwrap_or(name);
+            protected.iter().any(|(t, pattern)| {
+                t == object_type && (pattern.matches(name) || pattern.matches(bare))
+            })
+        })?;
+    Some(LintResult {
+        rule: RULE,
+        severity: LintSeverity::Error,
+        message: format!("{name} is protected ({object_type}) and cannot be dropped or altered"),
+    })
+}
+
+/// Objects `op` drops or alters, as the object type and name a `[protected]`
+/// glob is matched against. Table elements report both their table and
+/// themselves as `schema.table.name`. Creating a new object touches nothing.
+fn protected_targets(op: &MigrationOp) -> Vec<(ObjectType, String)> {
+    let nested = |object_type: ObjectType, table: &dyn std::fmt::Display, name: &str| {
+        vec![
+            (ObjectType::Tables, table.to_string()),
+            (object_type, format!("{table}.{name}")),
+        ]
+    };
+    match op {
+        MigrationOp::CreateSchema(_)
+        | MigrationOp::CreateExtension(_)
+        | MigrationOp::CreateServer(_)
+        | MigrationOp::CreateEnum(_)
+        | MigrationOp::CreateDomain(_)
+        | MigrationOp::CreateTable(_)
+        | MigrationOp::CreatePartition(_)
+        | MigrationOp::CreateFunction(_)
+        | MigrationOp::CreateAggregate(_)
+        | MigrationOp::CreateView(_)
+        | MigrationOp::CreateSequence(_)
+        | MigrationOp::CreateVersionSchema { .. }
+        | MigrationOp::CreateVersionView { .. }
+        | MigrationOp::DropVersionSchema { .. }
+        | MigrationOp::DropVersionView { .. }
+        | MigrationOp::ApplyRawObject(_)
+        | MigrationOp::DropRawObject(_)
+        | MigrationOp::DropServer(_)
+        | MigrationOp::AlterServer { .. }
+        | MigrationOp::UpdateExtension { .. }
+        | MigrationOp::SetExtensionSchema { .. }
+        | MigrationOp::AlterDefaultPrivileges { .. } => Vec::new(),
+
+        MigrationOp::DropSchema { name, .. } => vec![(ObjectType::Schemas, name.clone())],
+        MigrationOp::DropExtension(name) => vec![(ObjectType::Extensions, name.clone())],
+        MigrationOp::DropEnum(name)
+        | MigrationOp::AddEnumValue {
+            enum_name: name, ..
+        } => {
+            vec![(ObjectType::Enums, name.clone())]
+        }
+        MigrationOp::DropDomain(name) | MigrationOp::AlterDomain { name, .. } => {
+            vec![(ObjectType::Domains, name.clone())]
+        }
+        MigrationOp::DropTable(name) => vec![(ObjectType::Tables, name.clone())],
+        MigrationOp::DropPartition(name) => vec![(ObjectType::Partitions, name.clone())],
+        MigrationOp::DropFunction { name, .. }
+        | MigrationOp::AlterFunction { name, .. }
+        | MigrationOp::DropAggregate { name, .. } => vec![(ObjectType::Functions, name.clone())],
+        MigrationOp::DropView { name, .. }
+        | MigrationOp::AlterView { name, .. }
+        | MigrationOp::AlterViewOptions { name, .. }
+        | MigrationOp::RefreshMaterializedView { name } => vec![(ObjectType::Views, name.clone())],
+        MigrationOp::DropSequence(name)
+        | MigrationOp::AlterSequence { name, .. }
+        | MigrationOp::SetSequenceValue { name, .. } => {
+            vec![(ObjectType::Sequences, name.clone())]
+        }
+        MigrationOp::RenameSequence { sequence, .. } => {
+            vec![(ObjectType::Sequences, sequence.to_string())]
+        }
+
+        MigrationOp::AddColumn { table, .. }
+        | MigrationOp::DropColumn { table, .. }
+        | MigrationOp::AlterColumn { table, .. }
+        | MigrationOp::AddPrimaryKey { table, .. }
+        | MigrationOp::DropPrimaryKey { table }
+        | MigrationOp::AddExclusionConstraint { table, .. }
+        | MigrationOp::DropExclusionConstraint { table, .. }
+        | MigrationOp::EnableRls { table }
+        | MigrationOp::DisableRls { table }
+        | MigrationOp::ForceRls { table }
+        | MigrationOp::NoForceRls { table }
+        | MigrationOp::SetReplicaIdentity { table, .. }
+        | MigrationOp::BackfillHint { table, .. }
+        | MigrationOp::SetColumnNotNull { table, .. }
+        | MigrationOp::RenameTable { table, .. }
+        | MigrationOp::RenameColumn { table, .. }
+        | MigrationOp::AddPrimaryKeyUsingIndex { table, .. } => {
+            vec![(ObjectType::Tables, table.to_string())]
+        }
+        MigrationOp::AddIndex { table, index }
+        | MigrationOp::CreateIndexConcurrently { table, index } => {
+            nested(ObjectType::Indexes, table, &index.name)
+        }
+        MigrationOp::AddUniqueConstraint {
+            table,
+            unique_constraint,
+        } => nested(ObjectType::Indexes, table, &unique_constraint.name),
+        MigrationOp::AddUniqueConstraintUsingIndex {
+            table, index_name, ..
+        } => nested(ObjectType::Indexes, table, index_name),
+        MigrationOp::DropIndex {
+            table,
+            index_name: name,
+        }
+        | MigrationOp::DropIndexConcurrently {
+            table,
+            index_name: name,
+        }
+        | MigrationOp::DropUniqueConstraint {
+            table,
+            constraint_name: name,
+        } => nested(ObjectType::Indexes, table, name),
+        MigrationOp::AddForeignKey { table, foreign_key } => {
+            nested(ObjectType::ForeignKeys, table, &foreign_key.name)
+        }
+        MigrationOp::DropForeignKey {
+            table,
+            foreign_key_name,
+        }
+        | MigrationOp::AlterForeignKeyDeferral {
+            table,
+            foreign_key_name,
+            ..
+        } => nested(ObjectType::ForeignKeys, table, foreign_key_name),
+        MigrationOp::AddCheckConstraint {
+            table,
+            check_constraint,
+        } => nested(ObjectType::CheckConstraints, table, &check_constraint.name),
+        MigrationOp::DropCheckConstraint {
+            table,
+            constraint_name,
+        } => nested(ObjectType::CheckConstraints, table, constraint_name),
+        MigrationOp::CreatePolicy(policy) => nested(
+            ObjectType::Policies,
+            &format!("{}.{}", policy.table_schema, policy.table),
+            &policy.name,
+        ),
+        MigrationOp::DropPolicy { table, name } | MigrationOp::AlterPolicy { table, name, .. } => {
+            nested(ObjectType::Policies, table, name)
+        }
+        MigrationOp::CreateTrigger(trigger) => nested(
+            ObjectType::Triggers,
+            &format!("{}.{}", trigger.target_schema, trigger.target_name),
+            &trigger.name,
+        ),
+        MigrationOp::DropTrigger {
+            target_schema,
+            target_name,
+            name,
+        }
+        | MigrationOp::AlterTriggerEnabled {
+            target_schema,
+            target_name,
+            name,
+            ..
+        } => nested(
+            ObjectType::Triggers,
+            &format!("{target_schema}.{target_name}"),
+            name,
+        ),
+
+        MigrationOp::AlterOwner {
+            object_kind,
+            schema,
+            name,
+            ..
+        }
+        | MigrationOp::SetSchema {
+            object_kind,
+            schema,
+            name,
+            ..
+        } => {
+            let object_type = match object_kind {
+                OwnerObjectKind::Table => ObjectType::Tables,
+                OwnerObjectKind::Partition => ObjectType::Partitions,
+                OwnerObjectKind::View | OwnerObjectKind::MaterializedView => ObjectType::Views,
+                OwnerObjectKind::Sequence => ObjectType::Sequences,
+                OwnerObjectKind::Function | OwnerObjectKind::Aggregate => ObjectType::Functions,
+                OwnerObjectKind::Type => ObjectType::Enums,
+                OwnerObjectKind::Domain => ObjectType::Domains,
+            };
+            vec![(object_type, format!("{schema}.{name}"))]
+        }
+        MigrationOp::GrantPrivileges {
+            object_kind,
+            schema,
+            name,
+            ..
+        }
+        | MigrationOp::RevokePrivileges {
+            object_kind,
+            schema,
+            name,
+            ..
+        } => {
+            let (object_type, name) = match object_kind {
+                GrantObjectKind::Table => (ObjectType::Tables, format!("{schema}.{name}")),
+                GrantObjectKind::View => (ObjectType::Views, format!("{schema}.{name}")),
+                GrantObjectKind::Sequence => (ObjectType::Sequences, format!("{schema}.{name}")),
+                GrantObjectKind::Function | GrantObjectKind::Aggregate => {
+                    (ObjectType::Functions, format!("{schema}.{name}"))
+                }
+                GrantObjectKind::Schema => (ObjectType::Schemas, name.clone()),
+                GrantObjectKind::Type => (ObjectType::Enums, format!("{schema}.{name}")),
+                GrantObjectKind::Domain => (ObjectType::Domains, format!("{schema}.{name}")),
+            };
+            vec![(object_type, name)]
+        }
+        MigrationOp::SetComment {
+            object_type,
+            schema,
+            name,
+            target,
+            ..
+        } => {
+            let qualified = format!("{schema}.{name}");
+            match object_type {
+                CommentObjectType::Table | CommentObjectType::Column => {
+                    vec![(ObjectType::Tables, qualified)]
+                }
+                CommentObjectType::View | CommentObjectType::MaterializedView => {
+                    vec![(ObjectType::Views, qualified)]
+                }
+                CommentObjectType::Function | CommentObjectType::Aggregate => {
+                    vec![(ObjectType::Functions, qualified)]
+                }
+                CommentObjectType::Type => vec![(ObjectType::Enums, qualified)],
+                CommentObjectType::Domain => vec![(ObjectType::Domains, qualified)],
+                CommentObjectType::Schema => vec![(ObjectType::Schemas, name.clone())],
+                CommentObjectType::Sequence => vec![(ObjectType::Sequences, qualified)],
+                CommentObjectType::Extension => vec![(ObjectType::Extensions, name.clone())],
+                CommentObjectType::Trigger
+                | CommentObjectType::Policy
+                | CommentObjectType::Constraint => {
+                    let object_type = match object_type {
+                        CommentObjectType::Trigger => ObjectType::Triggers,
+                        CommentObjectType::Policy => ObjectType::Policies,
+                        _ => ObjectType::CheckConstraints,
+                    };
+                    match target {
+                        Some(table) => {
+                            let table = if table.contains('.') {
+                                table.clone()
+                            } else {
+                                format!("{schema}.{table}")
+                            };
+                            nested(object_type, &table, name)
+                        }
+                        None => Vec::new(),
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::QualifiedName;
+
+    #[test]
+    fn parses_type_and_glob_pairs() {
+        let protected = parse_protected("tables:public.audit_*, functions:auth.*,bogus:x,tables");
+        assert_eq!(protected.len(), 2);
+        assert_eq!(protected[0].0, ObjectType::Tables);
+        assert_eq!(protected[1].1.as_str(), "auth.*");
+    }
+
+    #[test]
+    fn flags_drops_and_alterations_of_protected_objects_only() {
+        let protected = parse_protected("tables:public.audit_*,functions:auth.*");
+        let audit = QualifiedName::new("public", "audit_log");
+
+        let result = lint_protected(
+            &MigrationOp::DropColumn {
+                table: audit.clone(),
+                column: "actor".to_string(),
+            },
+            &protected,
+        )
+        .unwrap();
+        assert_eq!(result.rule, "deny_protected_object");
+        assert!(
+            result.message.contains("public.audit_log"),
+            "{}",
+            result.message
+        );
+
+        assert!(lint_protected(
+            &MigrationOp::DropFunction {
+                name: "auth.uid".to_string(),
+                args: String::new(),
+            },
+            &protected,
+        )
+        .is_some());
+        assert!(lint_protected(
+            &MigrationOp::DropTable("public.users".to_string()),
+            &protected
+        )
+        .is_none());
+        assert!(lint_protected(
+            &MigrationOp::DropIndex {
+                table: QualifiedName::new("public", "users"),
+                index_name: "audit_idx".to_string(),
+            },
+            &protected
+        )
+        .is_none());
+    }
+}
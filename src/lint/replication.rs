@@ -0,0 +1,62 @@
+//! Rules for migrating a database that logical replication is copying, as in
+//! a blue/green upgrade. DDL is not replicated, so a change on the publisher
+//! that the subscriber cannot follow stops replication. Enabled with
+//! `PGMOLD_LOGICAL_REPLICATION=1` (`--logical-replication`):
+//!
+//! - `deny_replication_add_column`: the subscriber rejects rows carrying a
+//!   column its table lacks.
+//! - `deny_replication_alter_column_type`: rows arrive in a type the
+//!   subscriber's column may not accept.
+//! - `deny_replication_drop_primary_key`: without a primary key the table
+//!   loses its replica identity, and UPDATE and DELETE stop replicating.
+//! - `deny_replication_add_enum_value`: the subscriber cannot store a label
+//!   its enum lacks.
+//! - `warn_replication_create_table`: a new table is not replicated until it
+//!   joins the publication and the subscription is refreshed.
+
+use super::{LintResult, LintSeverity};
+use crate::diff::MigrationOp;
+
+pub(super) fn lint_replication(op: &MigrationOp) -> Option<LintResult> {
+    let deny = |rule, message: String| LintResult {
+        rule,
+        severity: LintSeverity::Error,
+        message: format!("{message}, which breaks logical replication"),
+    };
+    match op {
+        MigrationOp::AddColumn { table, column } => Some(deny(
+            "deny_replication_add_column",
+            format!(
+                "Adding column {table}.{} publishes a column the subscriber lacks",
+                column.name
+            ),
+        )),
+        MigrationOp::AlterColumn {
+            table,
+            column,
+            changes,
+        } if changes.data_type.is_some() => Some(deny(
+            "deny_replication_alter_column_type",
+            format!("Changing the type of column {table}.{column} diverges from the subscriber"),
+        )),
+        MigrationOp::DropPrimaryKey { table } => Some(deny(
+            "deny_replication_drop_primary_key",
+            format!("Dropping the primary key of {table} removes its replica identity"),
+        )),
+        MigrationOp::AddEnumValue {
+            enum_name, value, ..
+        } => Some(deny(
+            "deny_replication_add_enum_value",
+            format!("Adding value '{value}' to enum {enum_name} publishes a label the subscriber lacks"),
+        )),
+        MigrationOp::CreateTable(table) => Some(LintResult {
+            rule: "warn_replication_create_table",
+            severity: LintSeverity::Warning,
+            message: format!(
+                "Table {}.{} is not replicated until it is added to the publication and the subscription is refreshed",
+                table.schema, table.name
+            ),
+        }),
+        _ => None,
+    }
+}
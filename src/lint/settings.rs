@@ -0,0 +1,192 @@
+//! Cluster settings a schema relies on, such as `wal_level = logical` for
+//! publications or `pg_stat_statements` in `shared_preload_libraries`. They
+//! are declared under `[settings]` in `.pgmold.toml`, which the CLI exports
+//! as `PGMOLD_REQUIRED_SETTINGS`. pgmold never changes them: plan and apply
+//! read `pg_settings` and report each one the server does not meet as
+//! `deny_unmet_setting`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use super::{LintOptions, LintResult, LintSeverity};
+
+/// What a setting's value must satisfy.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettingRequirement {
+    /// The value, compared case-insensitively.
+    Equals(String),
+    /// A numeric bound, in the unit `pg_settings.setting` reports.
+    AtLeast(f64),
+    Above(f64),
+    AtMost(f64),
+    Below(f64),
+    /// One entry of a comma-separated list such as `shared_preload_libraries`.
+    Includes(String),
+}
+
+impl FromStr for SettingRequirement {
+    type Err = String;
+
+    /// Parses `>= 200`, `> 0`, `<= 10`, `< 10`, `includes pg_stat_statements`,
+    /// `= logical` or a bare value.
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let spec = spec.trim();
+        let number = |value: &str| {
+            value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|n| n.is_finite())
+                .ok_or_else(|| format!("Expected a number in setting requirement '{spec}'"))
+        };
+        if let Some(value) = spec.strip_prefix(">=") {
+            Ok(Self::AtLeast(number(value)?))
+        } else if let Some(value) = spec.strip_prefix("<=") {
+            Ok(Self::AtMost(number(value)?))
+        } else if let Some(value) = spec.strip_prefix('>') {
+            Ok(Self::Above(number(value)?))
+        } else if let Some(value) = spec.strip_prefix('<') {
+            Ok(Self::Below(number(value)?))
+        } else if let Some(item) = spec.strip_prefix("includes ") {
+            Ok(Self::Includes(item.trim().to_string()))
+        } else {
+            let value = spec.strip_prefix('=').unwrap_or(spec).trim();
+            if value.is_empty() {
+                return Err("Empty setting requirement".to_string());
+            }
+            Ok(Self::Equals(value.to_string()))
+        }
+    }
+}
+
+// Bounds are finite, as parsed.
+impl Eq for SettingRequirement {}
+
+impl fmt::Display for SettingRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Equals(value) => write!(f, "= {value}"),
+            Self::AtLeast(bound) => write!(f, ">= {bound}"),
+            Self::Above(bound) => write!(f, "> {bound}"),
+            Self::AtMost(bound) => write!(f, "<= {bound}"),
+            Self::Below(bound) => write!(f, "< {bound}"),
+            Self::Includes(item) => write!(f, "includes {item}"),
+        }
+    }
+}
+
+impl SettingRequirement {
+    fn is_met_by(&self, value: &str) -> bool {
+        let number = || value.trim().parse::<f64>().ok();
+        match self {
+            Self::Equals(expected) => value.trim().eq_ignore_ascii_case(expected),
+            Self::AtLeast(bound) => number().is_some_and(|n| n >= *bound),
+            Self::Above(bound) => number().is_some_and(|n| n > *bound),
+            Self::AtMost(bound) => number().is_some_and(|n| n <= *bound),
+            Self::Below(bound) => number().is_some_and(|n| n < *bound),
+            Self::Includes(item) => value
+                .split(',')
+                .map(|entry| entry.trim().trim_matches('"'))
+                .any(|entry| entry == item),
+        }
+    }
+}
+
+/// Parses `name=requirement;name=requirement` as written to
+/// `PGMOLD_REQUIRED_SETTINGS`, such as
+/// `max_connections=>= 200;wal_level=logical`.
+pub fn parse_required_settings(spec: &str) -> Result<Vec<(String, SettingRequirement)>, String> {
+    spec.split(';')
+        .filter(|entry| !entry.trim().is_empty())
+        .map(|entry| {
+            let (name, requirement) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("Expected name=requirement, got '{entry}'"))?;
+            Ok((name.trim().to_ascii_lowercase(), requirement.parse()?))
+        })
+        .collect()
+}
+
+/// One `deny_unmet_setting` result per required setting that `current`
+/// (name to `pg_settings.setting`) does not meet or lacks, with the severity
+/// the options' overrides give the rule.
+pub fn lint_required_settings(
+    options: &LintOptions,
+    current: &BTreeMap<String, String>,
+) -> Vec<LintResult> {
+    let severity = match options.severity_overrides.get("deny_unmet_setting") {
+        Some(Some(severity)) => severity.clone(),
+        Some(None) => return Vec::new(),
+        None => LintSeverity::Error,
+    };
+    options
+        .required_settings
+        .iter()
+        .filter_map(|(name, requirement)| {
+            let message = match current.get(name) {
+                Some(value) if requirement.is_met_by(value) => return None,
+                Some(value) => format!("Setting {name} is '{value}' but must be {requirement}"),
+                None => format!("Setting {name} does not exist but must be {requirement}"),
+            };
+            Some(LintResult {
+                rule: "deny_unmet_setting",
+                severity: severity.clone(),
+                message,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_unmet_and_missing_settings() {
+        let options = LintOptions {
+            required_settings: parse_required_settings(
+                "max_connections=>= 200;wal_level=logical;\
+                 shared_preload_libraries=includes pg_stat_statements;pg_cron.host=localhost",
+            )
+            .unwrap(),
+            ..LintOptions::default()
+        };
+        let current = BTreeMap::from([
+            ("max_connections".to_string(), "100".to_string()),
+            ("wal_level".to_string(), "LOGICAL".to_string()),
+            (
+                "shared_preload_libraries".to_string(),
+                "auto_explain, pg_stat_statements".to_string(),
+            ),
+        ]);
+
+        let results = lint_required_settings(&options, &current);
+        let messages: Vec<&str> = results.iter().map(|r| r.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Setting max_connections is '100' but must be >= 200",
+                "Setting pg_cron.host does not exist but must be = localhost",
+            ]
+        );
+        assert!(results.iter().all(|r| r.severity == LintSeverity::Error));
+
+        let relaxed = LintOptions {
+            severity_overrides: BTreeMap::from([(
+                "deny_unmet_setting".to_string(),
+                Some(LintSeverity::Warning),
+            )]),
+            ..options
+        };
+        assert!(lint_required_settings(&relaxed, &current)
+            .iter()
+            .all(|r| r.severity == LintSeverity::Warning));
+    }
+
+    #[test]
+    fn rejects_malformed_requirements() {
+        assert!(parse_required_settings("max_connections=>= many").is_err());
+        assert!(parse_required_settings("wal_level").is_err());
+    }
+}
@@ -0,0 +1,111 @@
+//! A drop threshold lets small tables and partitions be dropped without
+//! `--allow-destructive`. Sizes are the planner's estimates (`reltuples` and
+//! `pg_total_relation_size`), read for the tables the plan drops; a table
+//! never analyzed has no row estimate and never counts as small. Written as
+//! `rows=1000,size=10MB` to `--drop-threshold` or `PGMOLD_DROP_THRESHOLD`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use super::{LintOptions, LintResult, LintSeverity};
+
+/// Largest table or partition that may be dropped without
+/// `--allow-destructive`. Every limit given must hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DropThreshold {
+    pub max_rows: Option<u64>,
+    pub max_bytes: Option<u64>,
+}
+
+impl FromStr for DropThreshold {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let mut threshold = DropThreshold {
+            max_rows: None,
+            max_bytes: None,
+        };
+        for entry in spec.split(',') {
+            let (key, value) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("Expected key=value in drop threshold, got '{entry}'"))?;
+            match key.trim() {
+                "rows" => {
+                    threshold.max_rows = Some(
+                        value
+                            .trim()
+                            .parse()
+                            .map_err(|_| format!("Invalid row count '{value}'"))?,
+                    );
+                }
+                "size" => threshold.max_bytes = Some(parse_size(value.trim())?),
+                other => {
+                    return Err(format!(
+                        "Unknown drop threshold '{other}' (expected rows or size)"
+                    ))
+                }
+            }
+        }
+        Ok(threshold)
+    }
+}
+
+/// Parses `512`, `64kB`, `10MB`, `1GB` or `1TB` into bytes, with 1024-byte
+/// units as `pg_size_pretty` prints them.
+fn parse_size(value: &str) -> Result<u64, String> {
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "KB" => 1 << 10,
+        "MB" => 1 << 20,
+        "GB" => 1 << 30,
+        "TB" => 1 << 40,
+        _ => return Err(format!("Invalid size '{value}'")),
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|number| number.checked_mul(multiplier))
+        .ok_or_else(|| format!("Invalid size '{value}'"))
+}
+
+/// Estimated size of a table, including its partitions, indexes and TOAST.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableSize {
+    /// `None` until the table (or one of its partitions) has been analyzed.
+    pub rows: Option<u64>,
+    pub bytes: u64,
+}
+
+impl fmt::Display for TableSize {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.rows {
+            Some(rows) => write!(formatter, "~{rows} rows, {} bytes", self.bytes),
+            None => write!(formatter, "unanalyzed, {} bytes", self.bytes),
+        }
+    }
+}
+
+impl DropThreshold {
+    pub fn admits(&self, size: &TableSize) -> bool {
+        let rows_ok = self
+            .max_rows
+            .is_none_or(|max| size.rows.is_some_and(|rows| rows <= max));
+        let bytes_ok = self.max_bytes.is_none_or(|max| size.bytes <= max);
+        rows_ok && bytes_ok
+    }
+}
+
+/// The warning for dropping `name` under the drop threshold, when it is.
+pub(super) fn small_drop(name: &str, options: &LintOptions) -> Option<LintResult> {
+    let threshold = options.drop_threshold?;
+    let size = options.table_sizes.get(name)?;
+    threshold.admits(size).then(|| LintResult {
+        rule: "warn_drop_small_table",
+        severity: LintSeverity::Warning,
+        message: format!("Dropping {name} ({size}), under the drop threshold"),
+    })
+}
@@ -0,0 +1,503 @@
+//! Builders for constructing a [`Schema`] in code rather than from SQL, so an
+//! embedding tool can diff a database against objects it describes itself.
+//!
+//! ```
+//! use pgmold::model::{ColumnBuilder, PgType, SchemaBuilder, TableBuilder};
+//!
+//! let schema = SchemaBuilder::new()
+//!     .table(
+//!         TableBuilder::new("public", "users")
+//!             .column(ColumnBuilder::new("id", PgType::BigInt).not_null())
+//!             .column(ColumnBuilder::new("email", PgType::Text).not_null())
+//!             .primary_key(["id"])
+//!             .unique("users_email_key", ["email"]),
+//!     )
+//!     .build()
+//!     .unwrap();
+//! assert!(schema.tables.contains_key("public.users"));
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::check::{check_schema, IssueSeverity};
+use crate::util::{Result, SchemaError};
+
+use super::{
+    qualified_name, CheckConstraint, Column, EnumType, ForeignKey, Index, IndexType, PgSchema,
+    PgType, PrimaryKey, ReferentialAction, Schema, Table, UniqueConstraint, View,
+};
+
+/// Builds a [`Column`]. Columns are nullable unless [`not_null`] is called.
+///
+/// [`not_null`]: ColumnBuilder::not_null
+#[derive(Debug, Clone)]
+pub struct ColumnBuilder {
+    column: Column,
+}
+
+impl ColumnBuilder {
+    pub fn new(name: &str, data_type: PgType) -> Self {
+        Self {
+            column: Column {
+                name: name.to_string(),
+                data_type,
+                nullable: true,
+                default: None,
+                comment: None,
+                generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
+            },
+        }
+    }
+
+    pub fn not_null(mut self) -> Self {
+        self.column.nullable = false;
+        self
+    }
+
+    /// A default expression, as it would appear after `DEFAULT`.
+    pub fn default(mut self, expression: &str) -> Self {
+        self.column.default = Some(expression.to_string());
+        self
+    }
+
+    /// A `GENERATED ALWAYS AS (expression) STORED` column.
+    pub fn generated(mut self, expression: &str) -> Self {
+        self.column.generated = Some(expression.to_string());
+        self
+    }
+
+    pub fn comment(mut self, comment: &str) -> Self {
+        self.column.comment = Some(comment.to_string());
+        self
+    }
+
+    pub fn build(self) -> Column {
+        self.column
+    }
+}
+
+/// Builds a [`Table`]; [`build`] checks that every constraint and index
+/// names columns the table has.
+///
+/// [`build`]: TableBuilder::build
+#[derive(Debug, Clone)]
+pub struct TableBuilder {
+    table: Table,
+    columns: Vec<Column>,
+}
+
+impl TableBuilder {
+    pub fn new(schema: &str, name: &str) -> Self {
+        Self {
+            table: Table {
+                schema: schema.to_string(),
+                name: name.to_string(),
+                columns: BTreeMap::new(),
+                indexes: Vec::new(),
+                primary_key: None,
+                foreign_keys: Vec::new(),
+                check_constraints: Vec::new(),
+                unique_constraints: Vec::new(),
+                exclusion_constraints: Vec::new(),
+                comment: None,
+                row_level_security: false,
+                force_row_level_security: false,
+                replica_identity: None,
+                policies: Vec::new(),
+                partition_by: None,
+                owner: None,
+                grants: Vec::new(),
+            },
+            columns: Vec::new(),
+        }
+    }
+
+    pub fn column(mut self, column: ColumnBuilder) -> Self {
+        self.columns.push(column.build());
+        self
+    }
+
+    /// Sets the primary key. Its columns become `NOT NULL`, as PostgreSQL
+    /// makes them.
+    pub fn primary_key<'a>(mut self, columns: impl IntoIterator<Item = &'a str>) -> Self {
+        self.table.primary_key = Some(PrimaryKey {
+            columns: names(columns),
+        });
+        self
+    }
+
+    /// A `UNIQUE` constraint.
+    pub fn unique<'a>(mut self, name: &str, columns: impl IntoIterator<Item = &'a str>) -> Self {
+        self.table.unique_constraints.push(UniqueConstraint {
+            name: name.to_string(),
+            columns: names(columns),
+            nulls_not_distinct: false,
+            deferrable: false,
+            initially_deferred: false,
+        });
+        self
+    }
+
+    /// A plain B-tree index.
+    pub fn index<'a>(mut self, name: &str, columns: impl IntoIterator<Item = &'a str>) -> Self {
+        self.table.indexes.push(Index {
+            name: name.to_string(),
+            columns: names(columns),
+            unique: false,
+            index_type: IndexType::BTree,
+            predicate: None,
+            nulls_not_distinct: false,
+            column_options: Vec::new(),
+        });
+        self
+    }
+
+    /// A foreign key with `NO ACTION` on delete and update; see
+    /// [`foreign_key_with_actions`](Self::foreign_key_with_actions).
+    pub fn foreign_key<'a>(
+        self,
+        name: &str,
+        columns: impl IntoIterator<Item = &'a str>,
+        referenced_table: (&str, &str),
+        referenced_columns: impl IntoIterator<Item = &'a str>,
+    ) -> Self {
+        self.foreign_key_with_actions(
+            name,
+            columns,
+            referenced_table,
+            referenced_columns,
+            ReferentialAction::NoAction,
+            ReferentialAction::NoAction,
+        )
+    }
+
+    /// A foreign key referencing `referenced_table` as `(schema, name)`.
+    pub fn foreign_key_with_actions<'a>(
+        mut self,
+        name: &str,
+        columns: impl IntoIterator<Item = &'a str>,
+        referenced_table: (&str, &str),
+        referenced_columns: impl IntoIterator<Item = &'a str>,
+        on_delete: ReferentialAction,
+        on_update: ReferentialAction,
+    ) -> Self {
+        self.table.foreign_keys.push(ForeignKey {
+            name: name.to_string(),
+            columns: names(columns),
+            referenced_schema: referenced_table.0.to_string(),
+            referenced_table: referenced_table.1.to_string(),
+            referenced_columns: names(referenced_columns),
+            on_delete,
+            on_update,
+            deferrable: false,
+            initially_deferred: false,
+        });
+        self
+    }
+
+    pub fn check(mut self, name: &str, expression: &str) -> Self {
+        self.table.check_constraints.push(CheckConstraint {
+            name: name.to_string(),
+            expression: expression.to_string(),
+        });
+        self
+    }
+
+    pub fn comment(mut self, comment: &str) -> Self {
+        self.table.comment = Some(comment.to_string());
+        self
+    }
+
+    pub fn row_level_security(mut self) -> Self {
+        self.table.row_level_security = true;
+        self
+    }
+
+    pub fn build(self) -> Result<Table> {
+        let Self { mut table, columns } = self;
+        let qualified = qualified_name(&table.schema, &table.name);
+        if table.name.is_empty() || table.schema.is_empty() {
+            return Err(invalid("A table needs a schema and a name".to_string()));
+        }
+        for column in columns {
+            if table.columns.contains_key(&column.name) {
+                return Err(invalid(format!(
+                    "Column {} is declared twice in table {qualified}",
+                    column.name
+                )));
+            }
+            table.columns.insert(column.name.clone(), column);
+        }
+
+        let missing_column = |owner: &str, columns: &[String]| {
+            columns
+                .iter()
+                .find(|column| !table.columns.contains_key(*column))
+                .map(|column| {
+                    invalid(format!(
+                        "{owner} on table {qualified} names column {column}, which the table does not have"
+                    ))
+                })
+        };
+        if let Some(primary_key) = &table.primary_key {
+            if let Some(error) = missing_column("The primary key", &primary_key.columns) {
+                return Err(error);
+            }
+        }
+        for index in &table.indexes {
+            if let Some(error) = missing_column(&format!("Index {}", index.name), &index.columns) {
+                return Err(error);
+            }
+        }
+        for unique in &table.unique_constraints {
+            if let Some(error) = missing_column(
+                &format!("Unique constraint {}", unique.name),
+                &unique.columns,
+            ) {
+                return Err(error);
+            }
+        }
+        for foreign_key in &table.foreign_keys {
+            if foreign_key.columns.len() != foreign_key.referenced_columns.len() {
+                return Err(invalid(format!(
+                    "Foreign key {} on table {qualified} has {} columns but references {}",
+                    foreign_key.name,
+                    foreign_key.columns.len(),
+                    foreign_key.referenced_columns.len()
+                )));
+            }
+            if let Some(error) = missing_column(
+                &format!("Foreign key {}", foreign_key.name),
+                &foreign_key.columns,
+            ) {
+                return Err(error);
+            }
+        }
+
+        if let Some(primary_key) = &table.primary_key {
+            for column in &primary_key.columns {
+                if let Some(column) = table.columns.get_mut(column) {
+                    column.nullable = false;
+                }
+            }
+        }
+        Ok(table)
+    }
+}
+
+/// Collects tables, enums and views into a [`Schema`]. [`build`] rejects
+/// objects declared twice and runs the same reference checks as
+/// `pgmold check`, such as foreign keys to tables the schema lacks.
+///
+/// [`build`]: SchemaBuilder::build
+#[derive(Debug, Clone, Default)]
+pub struct SchemaBuilder {
+    schemas: Vec<String>,
+    tables: Vec<TableBuilder>,
+    enums: Vec<EnumType>,
+    views: Vec<View>,
+}
+
+impl SchemaBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares a schema (namespace), as `CREATE SCHEMA` does.
+    pub fn schema(mut self, name: &str) -> Self {
+        self.schemas.push(name.to_string());
+        self
+    }
+
+    pub fn table(mut self, table: TableBuilder) -> Self {
+        self.tables.push(table);
+        self
+    }
+
+    pub fn enum_type<'a>(
+        mut self,
+        schema: &str,
+        name: &str,
+        values: impl IntoIterator<Item = &'a str>,
+    ) -> Self {
+        self.enums.push(EnumType {
+            schema: schema.to_string(),
+            name: name.to_string(),
+            values: names(values),
+            owner: None,
+            grants: Vec::new(),
+            comment: None,
+        });
+        self
+    }
+
+    /// A view over `query`, a `SELECT` statement.
+    pub fn view(self, schema: &str, name: &str, query: &str) -> Self {
+        self.push_view(schema, name, query, false)
+    }
+
+    pub fn materialized_view(self, schema: &str, name: &str, query: &str) -> Self {
+        self.push_view(schema, name, query, true)
+    }
+
+    pub fn build(self) -> Result<Schema> {
+        let mut schema = Schema::new();
+        for name in self.schemas {
+            schema.schemas.insert(
+                name.clone(),
+                PgSchema {
+                    name,
+                    grants: Vec::new(),
+                    comment: None,
+                },
+            );
+        }
+        for table in self.tables {
+            let table = table.build()?;
+            let key = qualified_name(&table.schema, &table.name);
+            if schema.tables.insert(key.clone(), table).is_some() {
+                return Err(invalid(format!("Table {key} is declared twice")));
+            }
+        }
+        for enum_type in self.enums {
+            let key = qualified_name(&enum_type.schema, &enum_type.name);
+            if schema.enums.insert(key.clone(), enum_type).is_some() {
+                return Err(invalid(format!("Enum {key} is declared twice")));
+            }
+        }
+        for view in self.views {
+            let key = qualified_name(&view.schema, &view.name);
+            if schema.tables.contains_key(&key) {
+                return Err(invalid(format!("View {key} has the name of a table")));
+            }
+            if schema.views.insert(key.clone(), view).is_some() {
+                return Err(invalid(format!("View {key} is declared twice")));
+            }
+        }
+
+        let errors: Vec<String> = check_schema(&schema)
+            .into_iter()
+            .filter(|issue| issue.severity == IssueSeverity::Error)
+            .map(|issue| issue.message)
+            .collect();
+        if !errors.is_empty() {
+            return Err(invalid(errors.join("; ")));
+        }
+        Ok(schema)
+    }
+
+    fn push_view(mut self, schema: &str, name: &str, query: &str, materialized: bool) -> Self {
+        self.views.push(View {
+            name: name.to_string(),
+            schema: schema.to_string(),
+            query: query.to_string(),
+            materialized,
+            owner: None,
+            grants: Vec::new(),
+            comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
+        });
+        self
+    }
+}
+
+fn names<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    names.into_iter().map(str::to_string).collect()
+}
+
+fn invalid(message: String) -> SchemaError {
+    SchemaError::ValidationError(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::compute_diff;
+    use crate::parser::parse_sql_string;
+
+    #[test]
+    fn built_schema_matches_parsed_sql() {
+        let built = SchemaBuilder::new()
+            .enum_type("public", "status", ["active", "inactive"])
+            .table(
+                TableBuilder::new("public", "users")
+                    .column(ColumnBuilder::new("id", PgType::BigInt))
+                    .column(ColumnBuilder::new("email", PgType::Text).not_null())
+                    .column(
+                        ColumnBuilder::new("status", PgType::UserDefined("public.status".into()))
+                            .default("'active'"),
+                    )
+                    .primary_key(["id"])
+                    .unique("users_email_key", ["email"]),
+            )
+            .table(
+                TableBuilder::new("public", "posts")
+                    .column(ColumnBuilder::new("id", PgType::BigInt))
+                    .column(ColumnBuilder::new("user_id", PgType::BigInt).not_null())
+                    .primary_key(["id"])
+                    .foreign_key_with_actions(
+                        "posts_user_id_fkey",
+                        ["user_id"],
+                        ("public", "users"),
+                        ["id"],
+                        ReferentialAction::Cascade,
+                        ReferentialAction::NoAction,
+                    ),
+            )
+            .build()
+            .unwrap();
+
+        let parsed = parse_sql_string(
+            "CREATE TYPE status AS ENUM ('active', 'inactive');
+             CREATE TABLE users (
+                 id BIGINT PRIMARY KEY,
+                 email TEXT NOT NULL UNIQUE,
+                 status status DEFAULT 'active'
+             );
+             CREATE TABLE posts (
+                 id BIGINT PRIMARY KEY,
+                 user_id BIGINT NOT NULL REFERENCES users (id) ON DELETE CASCADE
+             );",
+        )
+        .unwrap();
+
+        assert_eq!(compute_diff(&parsed, &built), vec![]);
+    }
+
+    #[test]
+    fn build_rejects_unknown_columns_and_tables() {
+        let error = TableBuilder::new("public", "users")
+            .column(ColumnBuilder::new("id", PgType::BigInt))
+            .index("users_email_idx", ["email"])
+            .build()
+            .unwrap_err();
+        assert!(error.to_string().contains("users_email_idx"));
+
+        let error = SchemaBuilder::new()
+            .table(
+                TableBuilder::new("public", "posts")
+                    .column(ColumnBuilder::new("user_id", PgType::BigInt))
+                    .foreign_key(
+                        "posts_user_id_fkey",
+                        ["user_id"],
+                        ("public", "users"),
+                        ["id"],
+                    ),
+            )
+            .build()
+            .unwrap_err();
+        assert!(matches!(error, SchemaError::ValidationError(_)));
+
+        let error = SchemaBuilder::new()
+            .table(TableBuilder::new("public", "users"))
+            .table(TableBuilder::new("public", "users"))
+            .build()
+            .unwrap_err();
+        assert!(error.to_string().contains("declared twice"));
+    }
+}
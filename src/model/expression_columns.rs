@@ -0,0 +1,180 @@
+use std::ops::ControlFlow;
+
+use sqlparser::ast::{Expr, Query, Visit, Visitor};
+use sqlparser::dialect::PostgreSqlDialect;
+use sqlparser::parser::Parser;
+
+use super::{qualified_name, Schema, Table};
+
+/// SQL value functions PostgreSQL accepts without parentheses, which the
+/// parser may hand back as bare identifiers.
+const VALUE_KEYWORDS: &[&str] = &[
+    "current_catalog",
+    "current_date",
+    "current_role",
+    "current_schema",
+    "current_time",
+    "current_timestamp",
+    "current_user",
+    "localtime",
+    "localtimestamp",
+    "session_user",
+    "user",
+];
+
+impl Schema {
+    /// Checks that policy USING/WITH CHECK expressions, check constraints and
+    /// index expressions and predicates only name columns their table
+    /// declares, so a typo is reported when the schema is loaded rather than
+    /// when the migration runs. Returns one message per unknown column, naming
+    /// the object and clause it appears in.
+    pub(super) fn unknown_expression_columns(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        for table in self.tables.values() {
+            let table_key = qualified_name(&table.schema, &table.name);
+            let mut check = |location: String, expr: &str| {
+                for column in unknown_columns(expr, table) {
+                    errors.push(format!(
+                        "{location} on table \"{table_key}\" references unknown column \"{column}\""
+                    ));
+                }
+            };
+            for policy in &table.policies {
+                if let Some(expr) = &policy.using_expr {
+                    check(format!("Policy \"{}\" USING expression", policy.name), expr);
+                }
+                if let Some(expr) = &policy.check_expr {
+                    check(
+                        format!("Policy \"{}\" WITH CHECK expression", policy.name),
+                        expr,
+                    );
+                }
+            }
+            for constraint in &table.check_constraints {
+                check(
+                    format!("Check constraint \"{}\"", constraint.name),
+                    &constraint.expression,
+                );
+            }
+            for index in &table.indexes {
+                for column in &index.columns {
+                    if !table.columns.contains_key(column) {
+                        check(format!("Index \"{}\" expression", index.name), column);
+                    }
+                }
+                if let Some(predicate) = &index.predicate {
+                    check(format!("Index \"{}\" predicate", index.name), predicate);
+                }
+            }
+        }
+        errors
+    }
+}
+
+/// Bare identifiers in `expr` that are not columns of `table`. Identifiers
+/// inside subqueries are scoped to the subquery and are not checked, and an
+/// expression that does not parse yields nothing. Unquoted identifiers match
+/// columns case-insensitively.
+fn unknown_columns(expr: &str, table: &Table) -> Vec<String> {
+    let Ok(parsed) = Parser::new(&PostgreSqlDialect {})
+        .try_with_sql(expr)
+        .and_then(|mut parser| parser.parse_expr())
+    else {
+        return Vec::new();
+    };
+
+    let mut collector = IdentifierCollector::default();
+    let _ = parsed.visit(&mut collector);
+
+    let mut unknown = Vec::new();
+    for (name, quoted) in collector.identifiers {
+        let known = if quoted {
+            table.columns.contains_key(&name)
+        } else {
+            VALUE_KEYWORDS.contains(&name.to_lowercase().as_str())
+                || table.columns.keys().any(|c| c.eq_ignore_ascii_case(&name))
+        };
+        if !known && !unknown.contains(&name) {
+            unknown.push(name);
+        }
+    }
+    unknown
+}
+
+#[derive(Default)]
+struct IdentifierCollector {
+    subquery_depth: usize,
+    identifiers: Vec<(String, bool)>,
+}
+
+impl Visitor for IdentifierCollector {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, _query: &Query) -> ControlFlow<()> {
+        self.subquery_depth += 1;
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &Query) -> ControlFlow<()> {
+        self.subquery_depth -= 1;
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<()> {
+        if let (0, Expr::Identifier(ident)) = (self.subquery_depth, expr) {
+            self.identifiers
+                .push((ident.value.clone(), ident.quote_style.is_some()));
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::parse_sql_string;
+
+    fn errors(sql: &str) -> Vec<String> {
+        parse_sql_string(sql).unwrap().unknown_expression_columns()
+    }
+
+    #[test]
+    fn reports_unknown_columns_with_their_location() {
+        let errors = errors(
+            r#"
+            CREATE TABLE users (id BIGINT PRIMARY KEY, owner_id BIGINT, email TEXT);
+            ALTER TABLE users ENABLE ROW LEVEL SECURITY;
+            CREATE POLICY own_rows ON users USING (ownerid = 1) WITH CHECK (owner_id = 1);
+            CREATE INDEX users_email_idx ON users (lower(emial)) WHERE deleted_at IS NULL;
+            "#,
+        );
+        assert_eq!(
+            errors,
+            vec![
+                "Policy \"own_rows\" USING expression on table \"public.users\" references unknown column \"ownerid\"",
+                "Index \"users_email_idx\" expression on table \"public.users\" references unknown column \"emial\"",
+                "Index \"users_email_idx\" predicate on table \"public.users\" references unknown column \"deleted_at\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn accepts_value_keywords_subqueries_and_qualified_names() {
+        let errors = errors(
+            r#"
+            CREATE TABLE members (id BIGINT PRIMARY KEY, "UserName" TEXT, role TEXT);
+            CREATE TABLE users (
+                id BIGINT PRIMARY KEY,
+                "OwnerName" TEXT,
+                created_at TIMESTAMPTZ CHECK (created_at <= now())
+            );
+            ALTER TABLE users ENABLE ROW LEVEL SECURITY;
+            CREATE POLICY mine ON users USING (
+                "OwnerName" = current_user
+                AND EXISTS (SELECT 1 FROM members m WHERE m."UserName" = session_user AND role = 'admin')
+                AND users.id > 0
+            );
+            "#,
+        );
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+}
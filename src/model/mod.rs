@@ -4,6 +4,17 @@ use std::borrow::Cow;
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 
+mod builder;
+mod expression_columns;
+mod references;
+mod role_aliases;
+mod undeclared;
+
+pub use builder::{ColumnBuilder, SchemaBuilder, TableBuilder};
+pub(crate) use references::mentions_identifier;
+pub use references::ReferenceTarget;
+pub use role_aliases::parse_role_alias;
+
 /// A PostgreSQL qualified name consisting of a schema and object name.
 ///
 /// Used as the `table` field in `MigrationOp` variants to replace bare `String`
@@ -20,7 +31,7 @@ use std::fmt;
 /// assert_eq!(name.name, "users");
 /// assert_eq!(name.to_string(), "public.users");
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct QualifiedName {
     pub schema: String,
     pub name: String,
@@ -192,6 +203,64 @@ pub struct Schema {
     /// emitted via the `ON DOMAIN` form.
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     pub domain_constraint_comments: BTreeMap<String, String>,
+    /// Raw SQL blocks keyed by name; see [`RawObject`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub raw_objects: BTreeMap<String, RawObject>,
+    /// Objects the sources use without declaring them, as `schema.name`
+    /// globs; see [`Schema::undeclared_references`].
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub external: BTreeSet<String>,
+    /// Qualified names the database holds objects under, mapped to the
+    /// schemas the sources moved them to; the diff moves them in place.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub schema_moves: BTreeMap<String, String>,
+    /// Qualified names the database holds tables and columns under, mapped
+    /// to the names the sources gave them; the diff renames them in place.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub renames: BTreeMap<String, String>,
+    /// Partitioned tables mapped to how long their range partitions are
+    /// kept, as a PostgreSQL interval; plans drop older partitions.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub partition_retention: BTreeMap<String, String>,
+    /// Sequences mapped to the value they have reached, set by
+    /// `SELECT setval(...)`; only dumps and plans that opt in carry them.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sequence_values: BTreeMap<String, i64>,
+}
+
+/// Table recording the content hash of every raw object applied to a
+/// database, which is all introspection can know about them.
+pub const RAW_OBJECTS_TABLE: &str = "pgmold.raw_objects";
+
+/// A named block of SQL for objects pgmold does not model, declared in a
+/// schema source between `-- pgmold:raw <name> [depends=<a>,<b>]` and
+/// `-- pgmold:end`. It is applied as written, after the objects it depends
+/// on, and re-applied whenever its content hash changes, so its SQL should
+/// be safe to run again.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RawObject {
+    pub name: String,
+    /// Empty when introspected, since only the hash is recorded.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub sql: String,
+    /// Qualified names of objects, or names of other raw objects, that must
+    /// exist before this one is applied.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    pub content_hash: String,
+}
+
+impl RawObject {
+    pub fn new(name: &str, sql: &str, depends_on: Vec<String>) -> Self {
+        use sha2::{Digest, Sha256};
+        let sql = sql.trim().to_string();
+        RawObject {
+            name: name.to_string(),
+            content_hash: hex::encode(Sha256::digest(sql.as_bytes())),
+            sql,
+            depends_on,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -226,11 +295,17 @@ pub struct Table {
     pub foreign_keys: Vec<ForeignKey>,
     pub check_constraints: Vec<CheckConstraint>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unique_constraints: Vec<UniqueConstraint>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub exclusion_constraints: Vec<ExclusionConstraint>,
     pub comment: Option<String>,
     pub row_level_security: bool,
     #[serde(default)]
     pub force_row_level_security: bool,
+    /// `REPLICA IDENTITY` set with `ALTER TABLE`; `None` is `DEFAULT`, the
+    /// primary key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replica_identity: Option<ReplicaIdentity>,
     pub policies: Vec<Policy>,
     pub partition_by: Option<PartitionKey>,
     pub owner: Option<String>,
@@ -238,6 +313,19 @@ pub struct Table {
     pub grants: Vec<Grant>,
 }
 
+/// What logical replication writes to identify the old row of an `UPDATE` or
+/// `DELETE`, beyond the primary key it uses by default.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ReplicaIdentity {
+    /// Every column.
+    Full,
+    /// Nothing, so updates and deletes cannot be replicated.
+    Nothing,
+    /// The columns of a unique, non-partial, non-deferrable index on `NOT
+    /// NULL` columns, named without a schema.
+    Index(String),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Column {
     pub name: String,
@@ -247,6 +335,61 @@ pub struct Column {
     pub comment: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub generated: Option<String>,
+    /// Per-column `ANALYZE` statistics target set with
+    /// `ALTER COLUMN ... SET STATISTICS`; `None` uses `default_statistics_target`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub statistics: Option<i32>,
+    /// TOAST compression method (`pglz`, `lz4`) set with
+    /// `ALTER COLUMN ... SET COMPRESSION`; `None` uses `default_toast_compression`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<String>,
+    /// `GENERATED { ALWAYS | BY DEFAULT } AS IDENTITY`; identity columns are
+    /// always `NOT NULL` and carry no default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity: Option<Identity>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum IdentityGeneration {
+    Always,
+    ByDefault,
+}
+
+/// An identity column's generation kind and the options of its implicit
+/// sequence. Options the source leaves out are `None`; introspection fills
+/// all of them in, so only the ones the source declares are compared.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Identity {
+    pub generation: IdentityGeneration,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub increment: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_value: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_value: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cycle: Option<bool>,
+}
+
+impl Identity {
+    /// Whether the database's `actual` identity satisfies this declared one:
+    /// the same generation kind, and every option declared here matching.
+    pub fn satisfied_by(&self, actual: &Identity) -> bool {
+        fn declared<T: PartialEq>(declared: &Option<T>, actual: &Option<T>) -> bool {
+            declared.is_none() || declared == actual
+        }
+        self.generation == actual.generation
+            && declared(&self.start, &actual.start)
+            && declared(&self.increment, &actual.increment)
+            && declared(&self.min_value, &actual.min_value)
+            && declared(&self.max_value, &actual.max_value)
+            && declared(&self.cache, &actual.cache)
+            && declared(&self.cycle, &actual.cycle)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -295,7 +438,59 @@ pub struct Index {
     pub unique: bool,
     pub index_type: IndexType,
     pub predicate: Option<String>,
-    pub is_constraint: bool,
+    /// `NULLS NOT DISTINCT` (PostgreSQL 15+): a unique index that treats NULLs
+    /// as equal, so at most one row may hold them.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub nulls_not_distinct: bool,
+    /// Per-column collation, operator class and sort order. Empty when every
+    /// column uses the defaults, otherwise one entry per column.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub column_options: Vec<IndexColumnOptions>,
+}
+
+/// How one index column is collated, compared and ordered. Only what differs
+/// from the defaults is recorded, so a declared and an introspected column
+/// compare equal.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct IndexColumnOptions {
+    /// `COLLATE`, when it differs from the column's own collation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collation: Option<String>,
+    /// Operator class such as `gin_trgm_ops`, when not the type's default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opclass: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub descending: bool,
+    /// `NULLS FIRST` (`true`) or `NULLS LAST` (`false`), when it differs from
+    /// the direction's default (first for `DESC`, last for `ASC`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nulls_first: Option<bool>,
+}
+
+impl IndexColumnOptions {
+    pub fn new(
+        collation: Option<String>,
+        opclass: Option<String>,
+        descending: bool,
+        nulls_first: Option<bool>,
+    ) -> Self {
+        Self {
+            collation,
+            opclass,
+            descending,
+            nulls_first: nulls_first.filter(|first| *first != descending),
+        }
+    }
+
+    /// `options` as stored on an `Index`: empty when every column uses the
+    /// defaults.
+    pub fn normalize(options: Vec<Self>) -> Vec<Self> {
+        if options.iter().all(|o| *o == Self::default()) {
+            Vec::new()
+        } else {
+            options
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
@@ -304,6 +499,23 @@ pub enum IndexType {
     Hash,
     Gin,
     Gist,
+    Brin,
+    SpGist,
+}
+
+impl IndexType {
+    /// The access method name, as written after `USING` and stored in
+    /// `pg_am.amname`.
+    pub fn method(&self) -> &'static str {
+        match self {
+            IndexType::BTree => "btree",
+            IndexType::Hash => "hash",
+            IndexType::Gin => "gin",
+            IndexType::Gist => "gist",
+            IndexType::Brin => "brin",
+            IndexType::SpGist => "spgist",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -320,6 +532,13 @@ pub struct ForeignKey {
     pub referenced_columns: Vec<String>,
     pub on_delete: ReferentialAction,
     pub on_update: ReferentialAction,
+    /// `DEFERRABLE`: the check may be postponed to the end of the transaction.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deferrable: bool,
+    /// `INITIALLY DEFERRED`: the check is postponed unless a transaction
+    /// asks for it sooner. Only meaningful when `deferrable`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub initially_deferred: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
@@ -345,6 +564,23 @@ impl CheckConstraint {
     }
 }
 
+/// A table's `UNIQUE` constraint. PostgreSQL backs it with a unique index of
+/// the same name, which belongs to the constraint rather than to
+/// [`Table::indexes`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct UniqueConstraint {
+    pub name: String,
+    pub columns: Vec<String>,
+    /// `NULLS NOT DISTINCT` (PostgreSQL 15+): NULLs compare equal, so at most
+    /// one row may hold them.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub nulls_not_distinct: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deferrable: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub initially_deferred: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub struct ExclusionElement {
     pub column_or_expression: String,
@@ -422,6 +658,10 @@ pub struct Extension {
     pub name: String,
     pub version: Option<String>,
     pub schema: Option<String>,
+    /// Whether `ALTER EXTENSION ... SET SCHEMA` can move it, as introspected
+    /// from `pg_extension.extrelocatable`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub relocatable: bool,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub comment: Option<String>,
 }
@@ -532,6 +772,32 @@ pub struct Policy {
     pub comment: Option<String>,
 }
 
+/// Role specifications PostgreSQL accepts in place of a role name.
+pub const ROLE_KEYWORDS: &[&str] = &["public", "current_user", "current_role", "session_user"];
+
+impl Policy {
+    /// Lowercases role keywords, sorts and deduplicates `roles`, so a policy
+    /// compares equal however its `TO` clause was spelled or ordered.
+    pub fn normalize_roles(roles: impl IntoIterator<Item = String>) -> Vec<String> {
+        let roles: BTreeSet<String> = roles
+            .into_iter()
+            .map(|role| {
+                let lowered = role.to_ascii_lowercase();
+                if ROLE_KEYWORDS.contains(&lowered.as_str()) {
+                    lowered
+                } else {
+                    role
+                }
+            })
+            .collect();
+        if roles.is_empty() {
+            vec!["public".to_string()]
+        } else {
+            roles.into_iter().collect()
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum PolicyCommand {
     All,
@@ -579,13 +845,30 @@ impl Function {
             && self.language == other.language
             && self.volatility == other.volatility
             && self.security == other.security
-            && self.config_params == other.config_params
+            && self.config_params_match(other)
             && normalize_sql_body(&self.body) == normalize_sql_body(&other.body)
     }
 
+    /// Compares `SET` clauses the way PostgreSQL stores them in `proconfig`:
+    /// in any order, with single quotes dropped, values other than quoted
+    /// identifiers compared case-insensitively and `= DEFAULT` meaning no
+    /// setting. A value set `FROM CURRENT` matches whatever the database
+    /// recorded.
+    fn config_params_match(&self, other: &Function) -> bool {
+        let ours = canonical_config_params(&self.config_params);
+        let theirs = canonical_config_params(&other.config_params);
+        ours.len() == theirs.len()
+            && ours.iter().all(|(key, value)| {
+                theirs.get(key).is_some_and(|other_value| {
+                    value.is_none() || other_value.is_none() || value == other_value
+                })
+            })
+    }
+
     /// Checks if the function differences require DROP + CREATE instead of CREATE OR REPLACE.
-    /// PostgreSQL doesn't allow changing parameter names, defaults, or return types
-    /// via CREATE OR REPLACE.
+    /// PostgreSQL doesn't allow changing parameter names or return types, or
+    /// removing parameter defaults, via CREATE OR REPLACE. Adding a default or
+    /// changing its value is allowed.
     pub fn requires_drop_recreate(&self, other: &Function) -> bool {
         // Return type changes (including RETURNS TABLE column names) require DROP+CREATE
         if normalize_pg_type(&self.return_type) != normalize_pg_type(&other.return_type) {
@@ -597,13 +880,11 @@ impl Function {
         }
 
         for (self_arg, other_arg) in self.arguments.iter().zip(other.arguments.iter()) {
-            // Check if types/modes match but names or defaults differ
-            // Use semantic comparison for defaults to handle PostgreSQL normalization
-            let defaults_differ =
-                !crate::util::optional_expressions_equal(&self_arg.default, &other_arg.default);
+            // Check if types/modes match but names differ or a default is removed
+            let default_removed = self_arg.default.is_some() && other_arg.default.is_none();
             if normalize_pg_type(&self_arg.data_type) == normalize_pg_type(&other_arg.data_type)
                 && self_arg.mode == other_arg.mode
-                && (self_arg.name != other_arg.name || defaults_differ)
+                && (self_arg.name != other_arg.name || default_removed)
             {
                 return true;
             }
@@ -613,6 +894,51 @@ impl Function {
     }
 }
 
+/// Config params by lowercase name, with `None` for `FROM CURRENT`.
+fn canonical_config_params(params: &[(String, String)]) -> BTreeMap<String, Option<Vec<String>>> {
+    params
+        .iter()
+        .filter(|(_, value)| !value.eq_ignore_ascii_case("DEFAULT"))
+        .map(|(key, value)| {
+            let items = (!value.eq_ignore_ascii_case("FROM CURRENT")).then(|| {
+                config_value_items(value)
+                    .into_iter()
+                    .map(
+                        |item| match item.strip_prefix('\'').and_then(|i| i.strip_suffix('\'')) {
+                            Some(inner) => inner.replace("''", "'").to_lowercase(),
+                            None if item.starts_with('"') => item.to_string(),
+                            None => item.to_lowercase(),
+                        },
+                    )
+                    .collect()
+            });
+            (key.to_lowercase(), items)
+        })
+        .collect()
+}
+
+/// Splits a function `SET` value into its comma-separated items, keeping
+/// quoted items whole, e.g. `"$user", public, 'a,b'`.
+pub fn config_value_items(value: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut start = 0;
+    for (i, ch) in value.char_indices() {
+        match (quote, ch) {
+            (Some(open), _) if ch == open => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(ch),
+            (None, ',') => {
+                items.push(value[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(value[start..].trim());
+    items
+}
+
 /// Splits on commas that are not inside parentheses.
 /// Handles types like `numeric(10,2)` without breaking on the inner comma.
 fn split_top_level_commas(s: &str) -> Vec<&str> {
@@ -760,6 +1086,45 @@ pub struct View {
     pub grants: Vec<Grant>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub comment: Option<String>,
+    /// `WITH (security_barrier)`: quals from the view are evaluated before
+    /// user-supplied functions, preventing leaks through leaky predicates.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub security_barrier: bool,
+    /// `WITH (security_invoker = true)`: underlying relations are checked against
+    /// the querying role (and its RLS policies) instead of the view owner.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub security_invoker: bool,
+    /// `WITH [CASCADED | LOCAL] CHECK OPTION` on an updatable view. PostgreSQL
+    /// stores this as the `check_option` reloption.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub check_option: Option<ViewCheckOption>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ViewCheckOption {
+    Local,
+    Cascaded,
+}
+
+impl ViewCheckOption {
+    /// Returns the SQL keyword for this check option
+    pub fn as_sql_str(&self) -> &'static str {
+        match self {
+            ViewCheckOption::Local => "LOCAL",
+            ViewCheckOption::Cascaded => "CASCADED",
+        }
+    }
+
+    /// Parse from SQL keyword or reloption value
+    pub fn from_sql_str(s: &str) -> Option<Self> {
+        if s.eq_ignore_ascii_case("LOCAL") {
+            Some(ViewCheckOption::Local)
+        } else if s.eq_ignore_ascii_case("CASCADED") {
+            Some(ViewCheckOption::Cascaded)
+        } else {
+            None
+        }
+    }
 }
 
 impl View {
@@ -903,6 +1268,8 @@ pub struct Trigger {
     pub when_clause: Option<String>,
     pub function_schema: String,
     pub function_name: String,
+    /// Arguments as written in the `EXECUTE FUNCTION` call, such as
+    /// `'updated_at'` or `42`; see [`Trigger::argument_values`].
     pub function_args: Vec<String>,
     pub enabled: TriggerEnabled,
     pub old_table_name: Option<String>,
@@ -917,6 +1284,76 @@ pub struct Trigger {
     pub comment: Option<String>,
 }
 
+impl Trigger {
+    /// The strings the trigger function receives in `TG_ARGV`. PostgreSQL
+    /// turns every argument into a string, so `updated_at`, `'updated_at'`
+    /// and `$$updated_at$$` are the same argument.
+    pub fn argument_values(&self) -> Vec<String> {
+        self.function_args
+            .iter()
+            .map(|arg| trigger_argument_value(arg))
+            .collect()
+    }
+}
+
+fn trigger_argument_value(arg: &str) -> String {
+    let arg = arg.trim();
+    if let Some(body) = arg
+        .strip_prefix(['E', 'e'])
+        .and_then(|rest| rest.strip_prefix('\''))
+        .and_then(|rest| rest.strip_suffix('\''))
+    {
+        let mut value = String::with_capacity(body.len());
+        let mut chars = body.chars();
+        while let Some(c) = chars.next() {
+            match (c, chars.clone().next()) {
+                ('\\', Some(next)) => {
+                    chars.next();
+                    value.push(match next {
+                        'n' => '\n',
+                        't' => '\t',
+                        'r' => '\r',
+                        other => other,
+                    });
+                }
+                ('\'', Some('\'')) => {
+                    chars.next();
+                    value.push('\'');
+                }
+                _ => value.push(c),
+            }
+        }
+        return value;
+    }
+    if let Some(body) = arg
+        .strip_prefix('\'')
+        .and_then(|rest| rest.strip_suffix('\''))
+    {
+        return body.replace("''", "'");
+    }
+    if let Some(body) = arg
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+    {
+        return body.replace("\"\"", "\"");
+    }
+    if let Some(rest) = arg.strip_prefix('$') {
+        if let Some(tag_end) = rest.find('$') {
+            let tag = &arg[..tag_end + 2];
+            if let Some(body) = arg
+                .strip_prefix(tag)
+                .and_then(|rest| rest.strip_suffix(tag))
+            {
+                return body.to_string();
+            }
+        }
+    }
+    if arg.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '.') {
+        return arg.to_string();
+    }
+    arg.to_lowercase()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub struct SequenceOwner {
     pub table_schema: String,
@@ -1007,9 +1444,20 @@ impl Schema {
             default_privileges: Vec::new(),
             table_constraint_comments: BTreeMap::new(),
             domain_constraint_comments: BTreeMap::new(),
+            raw_objects: BTreeMap::new(),
+            external: BTreeSet::new(),
+            schema_moves: BTreeMap::new(),
+            renames: BTreeMap::new(),
+            partition_retention: BTreeMap::new(),
+            sequence_values: BTreeMap::new(),
         }
     }
 
+    /// The function `key` names, also when the key lists `OUT` arguments.
+    pub fn function_mut(&mut self, key: &str) -> Option<&mut Function> {
+        find_function_mut(&mut self.functions, key)
+    }
+
     pub fn fingerprint(&self) -> String {
         use sha2::{Digest, Sha256};
         let json = serde_json::to_string(self).expect("Schema must serialize");
@@ -1018,7 +1466,8 @@ impl Schema {
     }
 
     /// Associates pending policies with their respective tables and applies pending ownership.
-    /// Returns an error if a policy references a table that doesn't exist.
+    /// Returns an error if a policy references a table that doesn't exist, or if a policy,
+    /// check constraint or index expression names a column its table does not declare.
     pub fn finalize(&mut self) -> Result<(), String> {
         let pending = std::mem::take(&mut self.pending_policies);
         for policy in pending {
@@ -1040,6 +1489,11 @@ impl Schema {
         self.apply_pending_revokes(false);
         self.apply_pending_comments(false);
         self.merge_all_grants();
+
+        let unknown_columns = self.unknown_expression_columns();
+        if !unknown_columns.is_empty() {
+            return Err(unknown_columns.join("\n"));
+        }
         Ok(())
     }
 
@@ -1118,7 +1572,7 @@ impl Schema {
                 }
             }
             PendingOwnerObjectType::Function => {
-                if let Some(func) = self.functions.get_mut(&po.object_key) {
+                if let Some(func) = self.function_mut(&po.object_key) {
                     func.owner = Some(po.owner.clone());
                     true
                 } else {
@@ -1190,9 +1644,7 @@ impl Schema {
                 .map(|v| &mut v.grants)
                 .or_else(|| self.tables.get_mut(key).map(|t| &mut t.grants)),
             PendingGrantObjectType::Sequence => self.sequences.get_mut(key).map(|s| &mut s.grants),
-            PendingGrantObjectType::Function => self
-                .functions
-                .get_mut(key)
+            PendingGrantObjectType::Function => find_function_mut(&mut self.functions, key)
                 .map(|f| &mut f.grants)
                 .or_else(|| self.aggregates.get_mut(key).map(|a| &mut a.grants)),
             PendingGrantObjectType::Aggregate => {
@@ -1290,7 +1742,7 @@ impl Schema {
                 false
             }
             PendingCommentObjectType::Function => {
-                if let Some(func) = self.functions.get_mut(&pc.object_key) {
+                if let Some(func) = self.function_mut(&pc.object_key) {
                     func.comment = pc.comment.clone();
                     true
                 } else {
@@ -1519,6 +1971,18 @@ fn merge_grants_by_grantee(grants: &mut Vec<Grant>) {
     *grants = merged.into_values().collect();
 }
 
+fn find_function_mut<'a>(
+    functions: &'a mut BTreeMap<String, Function>,
+    key: &str,
+) -> Option<&'a mut Function> {
+    if functions.contains_key(key) {
+        return functions.get_mut(key);
+    }
+    functions
+        .values_mut()
+        .find(|f| qualified_name(&f.schema, &f.signature_with_out_args()) == key)
+}
+
 impl Default for Schema {
     fn default() -> Self {
         Self::new()
@@ -1526,15 +1990,32 @@ impl Default for Schema {
 }
 
 impl Function {
+    /// The argument types identifying the function, as
+    /// `pg_get_function_identity_arguments` lists them: `OUT` arguments are
+    /// not part of a function's identity.
     pub fn args_string(&self) -> String {
         self.arguments
             .iter()
+            .filter(|a| a.mode != ArgMode::Out)
             .map(|a| a.data_type.as_str())
             .collect::<Vec<_>>()
             .join(", ")
     }
 
     pub fn signature(&self) -> String {
+        let args = self
+            .arguments
+            .iter()
+            .filter(|a| a.mode != ArgMode::Out)
+            .map(|a| normalize_pg_type(&a.data_type))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("{}({})", self.name, args)
+    }
+
+    /// Like [`Function::signature`], listing `OUT` arguments too, as
+    /// `COMMENT ON FUNCTION` and friends may.
+    fn signature_with_out_args(&self) -> String {
         let args = self
             .arguments
             .iter()
@@ -1679,10 +2160,12 @@ mod tests {
                 primary_key: None,
                 foreign_keys: Vec::new(),
                 check_constraints: Vec::new(),
+                unique_constraints: Vec::new(),
                 exclusion_constraints: Vec::new(),
                 comment: None,
                 row_level_security: false,
                 force_row_level_security: false,
+                replica_identity: None,
                 policies: Vec::new(),
                 partition_by: None,
                 owner: None,
@@ -1701,10 +2184,12 @@ mod tests {
                 primary_key: None,
                 foreign_keys: Vec::new(),
                 check_constraints: Vec::new(),
+                unique_constraints: Vec::new(),
                 exclusion_constraints: Vec::new(),
                 comment: None,
                 row_level_security: false,
                 force_row_level_security: false,
+                replica_identity: None,
                 policies: Vec::new(),
                 partition_by: None,
                 owner: None,
@@ -1847,10 +2332,12 @@ mod tests {
                 primary_key: None,
                 foreign_keys: Vec::new(),
                 check_constraints: Vec::new(),
+                unique_constraints: Vec::new(),
                 exclusion_constraints: Vec::new(),
                 comment: None,
                 row_level_security: false,
                 force_row_level_security: false,
+                replica_identity: None,
                 policies: Vec::new(),
                 partition_by: None,
                 owner: None,
@@ -1903,10 +2390,12 @@ mod tests {
             primary_key: None,
             foreign_keys: Vec::new(),
             check_constraints: Vec::new(),
+            unique_constraints: Vec::new(),
             exclusion_constraints: Vec::new(),
             comment: None,
             row_level_security: false,
             force_row_level_security: false,
+            replica_identity: None,
             policies: Vec::new(),
             partition_by: None,
             owner: None,
@@ -1939,6 +2428,8 @@ mod tests {
             referenced_columns: vec!["id".to_string()],
             on_delete: ReferentialAction::Cascade,
             on_update: ReferentialAction::NoAction,
+            deferrable: false,
+            initially_deferred: false,
         };
         assert_eq!(fk.referenced_schema, "auth");
     }
@@ -2031,10 +2522,12 @@ mod tests {
                 primary_key: None,
                 foreign_keys: Vec::new(),
                 check_constraints: Vec::new(),
+                unique_constraints: Vec::new(),
                 exclusion_constraints: Vec::new(),
                 comment: None,
                 row_level_security: false,
                 force_row_level_security: false,
+                replica_identity: None,
                 policies: Vec::new(),
                 partition_by: None,
                 owner: None,
@@ -2053,10 +2546,12 @@ mod tests {
                 primary_key: None,
                 foreign_keys: Vec::new(),
                 check_constraints: Vec::new(),
+                unique_constraints: Vec::new(),
                 exclusion_constraints: Vec::new(),
                 comment: None,
                 row_level_security: false,
                 force_row_level_security: false,
+                replica_identity: None,
                 policies: Vec::new(),
                 partition_by: None,
                 owner: None,
@@ -2329,6 +2824,9 @@ mod tests {
             owner: None,
             grants: Vec::new(),
             comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
         };
 
         let introspected_view = View {
@@ -2339,6 +2837,9 @@ mod tests {
             owner: None,
             grants: Vec::new(),
             comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
         };
 
         assert!(parsed_view.semantically_equals(&introspected_view));
@@ -2354,6 +2855,9 @@ mod tests {
             owner: None,
             grants: Vec::new(),
             comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
         };
 
         let introspected_view = View {
@@ -2364,6 +2868,9 @@ mod tests {
             owner: None,
             grants: Vec::new(),
             comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
         };
 
         assert!(parsed_view.semantically_equals(&introspected_view));
@@ -2379,6 +2886,9 @@ mod tests {
             owner: None,
             grants: Vec::new(),
             comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
         };
 
         let introspected_view = View {
@@ -2389,6 +2899,9 @@ mod tests {
             owner: None,
             grants: Vec::new(),
             comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
         };
 
         assert!(parsed_view.semantically_equals(&introspected_view));
@@ -2404,6 +2917,9 @@ mod tests {
             owner: None,
             grants: Vec::new(),
             comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
         };
 
         let introspected_view = View {
@@ -2414,6 +2930,9 @@ mod tests {
             owner: None,
             grants: Vec::new(),
             comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
         };
 
         assert!(parsed_view.semantically_equals(&introspected_view));
@@ -2429,6 +2948,9 @@ mod tests {
             owner: None,
             grants: Vec::new(),
             comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
         };
 
         let introspected_view = View {
@@ -2439,6 +2961,9 @@ mod tests {
             owner: None,
             grants: Vec::new(),
             comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
         };
 
         assert!(parsed_view.semantically_equals(&introspected_view));
@@ -2454,6 +2979,9 @@ mod tests {
             owner: None,
             grants: Vec::new(),
             comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
         };
 
         let view2 = View {
@@ -2464,6 +2992,9 @@ mod tests {
             owner: None,
             grants: Vec::new(),
             comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
         };
 
         assert!(!view1.semantically_equals(&view2));
@@ -2507,6 +3038,47 @@ mod tests {
         assert_eq!(func.config_params[0].0, "search_path");
     }
 
+    #[test]
+    fn config_params_compare_as_postgres_stores_them() {
+        let with_config = |params: &[(&str, &str)]| Function {
+            name: "test".to_string(),
+            schema: "public".to_string(),
+            arguments: vec![],
+            return_type: "void".to_string(),
+            language: "sql".to_string(),
+            body: "SELECT 1".to_string(),
+            volatility: Volatility::Volatile,
+            security: SecurityType::Definer,
+            config_params: params
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            owner: None,
+            grants: Vec::new(),
+            comment: None,
+        };
+        let authored = with_config(&[
+            ("work_mem", "'64MB'"),
+            ("search_path", "\"$user\", PUBLIC, pg_temp"),
+            ("timezone", "FROM CURRENT"),
+            ("statement_timeout", "DEFAULT"),
+        ]);
+        let introspected = with_config(&[
+            ("search_path", "\"$user\", public, pg_temp"),
+            ("timezone", "UTC"),
+            ("work_mem", "64MB"),
+        ]);
+        assert!(authored.semantically_equals(&introspected));
+
+        let changed = with_config(&[
+            ("search_path", "public"),
+            ("timezone", "UTC"),
+            ("work_mem", "64MB"),
+        ]);
+        assert!(!authored.semantically_equals(&changed));
+        assert!(!authored.semantically_equals(&with_config(&[])));
+    }
+
     #[test]
     fn table_has_owner_field() {
         let table = Table {
@@ -2517,10 +3089,12 @@ mod tests {
             primary_key: None,
             foreign_keys: Vec::new(),
             check_constraints: Vec::new(),
+            unique_constraints: Vec::new(),
             exclusion_constraints: Vec::new(),
             comment: None,
             row_level_security: false,
             force_row_level_security: false,
+            replica_identity: None,
             policies: Vec::new(),
             partition_by: None,
             owner: Some("postgres".to_string()),
@@ -2539,6 +3113,9 @@ mod tests {
             owner: Some("postgres".to_string()),
             grants: Vec::new(),
             comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
         };
         assert_eq!(view.owner, Some("postgres".to_string()));
     }
@@ -2619,10 +3196,12 @@ mod tests {
             primary_key: None,
             foreign_keys: Vec::new(),
             check_constraints: Vec::new(),
+            unique_constraints: Vec::new(),
             exclusion_constraints: Vec::new(),
             comment: None,
             row_level_security: false,
             force_row_level_security: false,
+            replica_identity: None,
             policies: Vec::new(),
             partition_by: None,
             owner: Some("postgres".to_string()),
@@ -2637,10 +3216,12 @@ mod tests {
             primary_key: None,
             foreign_keys: Vec::new(),
             check_constraints: Vec::new(),
+            unique_constraints: Vec::new(),
             exclusion_constraints: Vec::new(),
             comment: None,
             row_level_security: false,
             force_row_level_security: false,
+            replica_identity: None,
             policies: Vec::new(),
             partition_by: None,
             owner: Some("admin".to_string()),
@@ -2660,10 +3241,12 @@ mod tests {
             primary_key: None,
             foreign_keys: Vec::new(),
             check_constraints: Vec::new(),
+            unique_constraints: Vec::new(),
             exclusion_constraints: Vec::new(),
             comment: None,
             row_level_security: false,
             force_row_level_security: false,
+            replica_identity: None,
             policies: Vec::new(),
             partition_by: None,
             owner: Some("postgres".to_string()),
@@ -2689,10 +3272,12 @@ mod tests {
                 primary_key: None,
                 foreign_keys: Vec::new(),
                 check_constraints: Vec::new(),
+                unique_constraints: Vec::new(),
                 exclusion_constraints: Vec::new(),
                 comment: None,
                 row_level_security: false,
                 force_row_level_security: false,
+                replica_identity: None,
                 policies: Vec::new(),
                 partition_by: None,
                 owner: Some("postgres".to_string()),
@@ -2711,10 +3296,12 @@ mod tests {
                 primary_key: None,
                 foreign_keys: Vec::new(),
                 check_constraints: Vec::new(),
+                unique_constraints: Vec::new(),
                 exclusion_constraints: Vec::new(),
                 comment: None,
                 row_level_security: false,
                 force_row_level_security: false,
+                replica_identity: None,
                 policies: Vec::new(),
                 partition_by: None,
                 owner: None,
@@ -2747,10 +3334,12 @@ mod tests {
             primary_key: None,
             foreign_keys: Vec::new(),
             check_constraints: Vec::new(),
+            unique_constraints: Vec::new(),
             exclusion_constraints: Vec::new(),
             comment: None,
             row_level_security: false,
             force_row_level_security: false,
+            replica_identity: None,
             policies: Vec::new(),
             partition_by: None,
             owner: Some("postgres".to_string()),
@@ -2774,10 +3363,12 @@ mod tests {
             primary_key: None,
             foreign_keys: Vec::new(),
             check_constraints: Vec::new(),
+            unique_constraints: Vec::new(),
             exclusion_constraints: Vec::new(),
             comment: None,
             row_level_security: false,
             force_row_level_security: false,
+            replica_identity: None,
             policies: Vec::new(),
             partition_by: None,
             owner: Some("postgres".to_string()),
@@ -2827,6 +3418,9 @@ mod tests {
             owner: Some("postgres".to_string()),
             grants: vec![grant],
             comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
         };
 
         assert_eq!(view.grants.len(), 1);
@@ -0,0 +1,258 @@
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+use sqlparser::dialect::PostgreSqlDialect;
+use sqlparser::tokenizer::{Token, Tokenizer};
+
+use super::{parse_qualified_name, qualified_name, QualifiedName, Schema};
+use crate::parser::{extract_function_references, extract_table_references};
+use crate::util::SchemaError;
+
+/// An object whose users `Schema::references_of` looks for.
+///
+/// Parsed from `[schema.]table.column` for a column, or `[schema.]name()` for a
+/// function (all overloads); the schema defaults to `public`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReferenceTarget {
+    Column {
+        table: QualifiedName,
+        column: String,
+    },
+    Function(QualifiedName),
+}
+
+impl FromStr for ReferenceTarget {
+    type Err = SchemaError;
+
+    fn from_str(s: &str) -> Result<Self, SchemaError> {
+        let s = s.trim();
+        let invalid = || {
+            SchemaError::ParseError(format!(
+                "Invalid reference target '{s}': expected [schema.]table.column or [schema.]function()"
+            ))
+        };
+        if let Some(function) = s.strip_suffix("()") {
+            let (schema, name) = parse_qualified_name(function);
+            if schema.is_empty() || name.is_empty() || name.contains('.') {
+                return Err(invalid());
+            }
+            return Ok(Self::Function(QualifiedName { schema, name }));
+        }
+        let (table, column) = s.rsplit_once('.').ok_or_else(invalid)?;
+        let (schema, name) = parse_qualified_name(table);
+        if schema.is_empty() || name.is_empty() || column.is_empty() || name.contains('.') {
+            return Err(invalid());
+        }
+        Ok(Self::Column {
+            table: QualifiedName { schema, name },
+            column: column.to_string(),
+        })
+    }
+}
+
+impl Schema {
+    /// Lists the views, policies, triggers, column defaults and generated
+    /// expressions, check constraints and domains that refer to `target`, one
+    /// line per object in the style of `plan::affected_objects`.
+    ///
+    /// Column references are found by identifier: an expression refers to the
+    /// column when it names it and is attached to the column's table or selects
+    /// from it. A view that only uses `SELECT *` is not reported.
+    pub fn references_of(&self, target: &ReferenceTarget) -> Vec<String> {
+        let mut found = BTreeSet::new();
+
+        for view in self.views.values() {
+            if target.is_referenced_by(&view.query, &view.schema, None) {
+                let kind = if view.materialized {
+                    "materialized view"
+                } else {
+                    "view"
+                };
+                found.insert(format!(
+                    "{kind} {}",
+                    qualified_name(&view.schema, &view.name)
+                ));
+            }
+        }
+
+        for table in self.tables.values() {
+            let table_name = QualifiedName::new(&table.schema, &table.name);
+            let owner = Some(&table_name);
+            for policy in &table.policies {
+                let uses_target = policy
+                    .using_expr
+                    .iter()
+                    .chain(policy.check_expr.iter())
+                    .any(|expr| target.is_referenced_by(expr, &table.schema, owner));
+                if uses_target {
+                    found.insert(format!("policy {} on {table_name}", policy.name));
+                }
+            }
+            for column in table.columns.values() {
+                if let Some(default) = &column.default {
+                    if target.is_referenced_by(default, &table.schema, owner) {
+                        found.insert(format!("default of column {table_name}.{}", column.name));
+                    }
+                }
+                if let Some(generated) = &column.generated {
+                    if target.is_referenced_by(generated, &table.schema, owner) {
+                        found.insert(format!("generated column {table_name}.{}", column.name));
+                    }
+                }
+            }
+            for check in &table.check_constraints {
+                if target.is_referenced_by(&check.expression, &table.schema, owner) {
+                    found.insert(format!("check constraint {} on {table_name}", check.name));
+                }
+            }
+        }
+
+        for trigger in self.triggers.values() {
+            let target_table = QualifiedName::new(&trigger.target_schema, &trigger.target_name);
+            let uses_target = match target {
+                ReferenceTarget::Column { table, column } => {
+                    *table == target_table && trigger.update_columns.contains(column)
+                }
+                ReferenceTarget::Function(function) => {
+                    function.schema == trigger.function_schema
+                        && function.name == trigger.function_name
+                }
+            } || trigger.when_clause.as_ref().is_some_and(|when| {
+                target.is_referenced_by(when, &trigger.target_schema, Some(&target_table))
+            });
+            if uses_target {
+                found.insert(format!("trigger {} on {target_table}", trigger.name));
+            }
+        }
+
+        for domain in self.domains.values() {
+            let uses_target = domain
+                .default
+                .iter()
+                .chain(domain.check_constraints.iter().map(|c| &c.expression))
+                .any(|expr| target.is_referenced_by(expr, &domain.schema, None));
+            if uses_target {
+                found.insert(format!(
+                    "domain {}",
+                    qualified_name(&domain.schema, &domain.name)
+                ));
+            }
+        }
+
+        found.into_iter().collect()
+    }
+}
+
+impl ReferenceTarget {
+    /// Whether `sql`, written in `default_schema` and attached to the table
+    /// `owner` if any, refers to this target.
+    fn is_referenced_by(
+        &self,
+        sql: &str,
+        default_schema: &str,
+        owner: Option<&QualifiedName>,
+    ) -> bool {
+        match self {
+            Self::Column { table, column } => {
+                mentions_identifier(sql, column)
+                    && (owner == Some(table)
+                        || extract_table_references(sql, default_schema)
+                            .iter()
+                            .any(|r| r.schema == table.schema && r.name == table.name))
+            }
+            Self::Function(function) => extract_function_references(sql, default_schema)
+                .iter()
+                .any(|r| r.schema == function.schema && r.name == function.name),
+        }
+    }
+}
+
+/// Whether `sql` contains `ident` as an identifier, folding unquoted words to
+/// lower case as PostgreSQL does. String literals and comments never match.
+pub(crate) fn mentions_identifier(sql: &str, ident: &str) -> bool {
+    let dialect = PostgreSqlDialect {};
+    let Ok(tokens) = Tokenizer::new(&dialect, sql).tokenize() else {
+        return false;
+    };
+    tokens.iter().any(|token| match token {
+        Token::Word(word) if word.quote_style.is_some() => word.value == ident,
+        Token::Word(word) => word.value.to_lowercase() == ident,
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_sql_string;
+
+    fn schema() -> Schema {
+        parse_sql_string(
+            r#"
+            CREATE FUNCTION current_tenant() RETURNS bigint LANGUAGE sql AS $$ SELECT 1::bigint $$;
+            CREATE FUNCTION touch() RETURNS trigger LANGUAGE plpgsql AS $$ BEGIN RETURN NEW; END $$;
+            CREATE TABLE users (
+                id BIGINT PRIMARY KEY,
+                tenant_id BIGINT NOT NULL DEFAULT current_tenant(),
+                email TEXT CHECK (email <> ''),
+                note TEXT DEFAULT 'email'
+            );
+            CREATE TABLE orders (id BIGINT PRIMARY KEY, email TEXT);
+            CREATE VIEW user_emails AS SELECT id, email FROM users;
+            CREATE VIEW order_emails AS SELECT id, email FROM orders;
+            CREATE TRIGGER users_touch BEFORE UPDATE OF email ON users
+                FOR EACH ROW EXECUTE FUNCTION touch();
+            ALTER TABLE users ENABLE ROW LEVEL SECURITY;
+            CREATE POLICY tenant_rows ON users USING (tenant_id = current_tenant());
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn parses_column_and_function_targets() {
+        assert_eq!(
+            "users.email".parse::<ReferenceTarget>().unwrap(),
+            ReferenceTarget::Column {
+                table: QualifiedName::new("public", "users"),
+                column: "email".to_string(),
+            }
+        );
+        assert_eq!(
+            "auth.uid()".parse::<ReferenceTarget>().unwrap(),
+            ReferenceTarget::Function(QualifiedName::new("auth", "uid"))
+        );
+        assert!("users".parse::<ReferenceTarget>().is_err());
+        assert!("a.b.c.d".parse::<ReferenceTarget>().is_err());
+    }
+
+    #[test]
+    fn finds_everything_referencing_a_column() {
+        let target = "public.users.email".parse().unwrap();
+        assert_eq!(
+            schema().references_of(&target),
+            vec![
+                "check constraint users_email_check on public.users",
+                "trigger users_touch on public.users",
+                "view public.user_emails",
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_everything_calling_a_function() {
+        let target = "current_tenant()".parse().unwrap();
+        assert_eq!(
+            schema().references_of(&target),
+            vec![
+                "default of column public.users.tenant_id",
+                "policy tenant_rows on public.users",
+            ]
+        );
+        let target = "touch()".parse().unwrap();
+        assert_eq!(
+            schema().references_of(&target),
+            vec!["trigger users_touch on public.users"]
+        );
+    }
+}
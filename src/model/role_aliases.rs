@@ -0,0 +1,128 @@
+use std::collections::BTreeMap;
+
+use super::{Grant, Schema};
+
+/// Parses a `schema_role=database_role` pair, as accepted by `--role-alias`.
+pub fn parse_role_alias(spec: &str) -> Result<(String, String), String> {
+    let (from, to) = spec.split_once('=').ok_or_else(|| {
+        format!("Invalid role alias '{spec}': expected schema_role=database_role")
+    })?;
+    let (from, to) = (from.trim(), to.trim());
+    if from.is_empty() || to.is_empty() {
+        return Err(format!("Invalid role alias '{spec}': missing role name"));
+    }
+    Ok((from.to_string(), to.to_string()))
+}
+
+impl Schema {
+    /// Renames the roles in owners, grants and default privileges through
+    /// `aliases`, which maps a role named in the schema sources to the role
+    /// that stands for it in the database being diffed (`app_rw` to
+    /// `app_rw_staging`). Applied to the declared schema before diffing so
+    /// environment-specific role names compare equal and generated
+    /// statements name the database's role. Roles without an alias are kept.
+    pub fn apply_role_aliases(&mut self, aliases: &BTreeMap<String, String>) {
+        if aliases.is_empty() {
+            return;
+        }
+        let rename = |role: &mut String| {
+            if let Some(alias) = aliases.get(role.as_str()) {
+                role.clone_from(alias);
+            }
+        };
+        let rename_owner = |owner: &mut Option<String>| {
+            if let Some(owner) = owner {
+                rename(owner);
+            }
+        };
+        let rename_grants = |grants: &mut Vec<Grant>| {
+            for grant in grants {
+                rename(&mut grant.grantee);
+            }
+        };
+
+        for schema in self.schemas.values_mut() {
+            rename_grants(&mut schema.grants);
+        }
+        for server in self.servers.values_mut() {
+            rename_owner(&mut server.owner);
+        }
+        for table in self.tables.values_mut() {
+            rename_owner(&mut table.owner);
+            rename_grants(&mut table.grants);
+        }
+        for partition in self.partitions.values_mut() {
+            rename_owner(&mut partition.owner);
+        }
+        for enum_type in self.enums.values_mut() {
+            rename_owner(&mut enum_type.owner);
+            rename_grants(&mut enum_type.grants);
+        }
+        for domain in self.domains.values_mut() {
+            rename_owner(&mut domain.owner);
+            rename_grants(&mut domain.grants);
+        }
+        for function in self.functions.values_mut() {
+            rename_owner(&mut function.owner);
+            rename_grants(&mut function.grants);
+        }
+        for aggregate in self.aggregates.values_mut() {
+            rename_owner(&mut aggregate.owner);
+            rename_grants(&mut aggregate.grants);
+        }
+        for view in self.views.values_mut() {
+            rename_owner(&mut view.owner);
+            rename_grants(&mut view.grants);
+        }
+        for sequence in self.sequences.values_mut() {
+            rename_owner(&mut sequence.owner);
+            rename_grants(&mut sequence.grants);
+        }
+        for default_privilege in &mut self.default_privileges {
+            rename(&mut default_privilege.target_role);
+            rename(&mut default_privilege.grantee);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::compute_diff_with_flags;
+    use crate::parser::parse_sql_string;
+
+    #[test]
+    fn aliased_roles_compare_equal_to_the_database() {
+        let declared = "CREATE TABLE orders (id BIGINT PRIMARY KEY);
+             ALTER TABLE orders OWNER TO app_owner;
+             GRANT SELECT, INSERT ON orders TO app_rw;";
+        let mut target = parse_sql_string(declared).unwrap();
+        let current = parse_sql_string(
+            "CREATE TABLE orders (id BIGINT PRIMARY KEY);
+             ALTER TABLE orders OWNER TO app_owner_staging;
+             GRANT SELECT, INSERT ON orders TO app_rw_staging;",
+        )
+        .unwrap();
+        let excluded = std::collections::HashSet::new();
+
+        let ops = compute_diff_with_flags(&current, &target, true, true, &excluded);
+        assert!(!ops.is_empty());
+
+        target.apply_role_aliases(&BTreeMap::from([
+            ("app_rw".to_string(), "app_rw_staging".to_string()),
+            ("app_owner".to_string(), "app_owner_staging".to_string()),
+        ]));
+        let ops = compute_diff_with_flags(&current, &target, true, true, &excluded);
+        assert!(ops.is_empty(), "{ops:?}");
+    }
+
+    #[test]
+    fn parses_role_alias_pairs() {
+        assert_eq!(
+            parse_role_alias("app_rw = app_rw_staging").unwrap(),
+            ("app_rw".to_string(), "app_rw_staging".to_string())
+        );
+        assert!(parse_role_alias("app_rw").is_err());
+        assert!(parse_role_alias("=app_rw").is_err());
+    }
+}
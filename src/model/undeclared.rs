@@ -0,0 +1,113 @@
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use glob::Pattern;
+use regex::Regex;
+
+use super::{qualified_name, Schema};
+use crate::parser::extract_table_references;
+use crate::plan::BUILTIN_TRIGGER_FUNCTIONS;
+
+const SYSTEM_SCHEMAS: &[&str] = &["pg_catalog", "information_schema"];
+
+/// `name AS (` (optionally `[NOT] MATERIALIZED`), which in a view query only
+/// introduces a common table expression.
+static CTE_NAME: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)("?[A-Za-z_][A-Za-z0-9_$]*"?)\s+AS\s+(?:NOT\s+)?(?:MATERIALIZED\s+)?\("#)
+        .unwrap()
+});
+
+impl Schema {
+    /// Foreign key targets, trigger functions, view base relations and
+    /// policy tables the schema names without declaring, one line each,
+    /// except those matching an `external` glob. Call it before `finalize`,
+    /// which would reject the first policy on an undeclared table by itself.
+    ///
+    /// An unqualified relation in a view counts as declared when any schema
+    /// declares a relation of that name, since `search_path` may resolve it
+    /// there. Trigger functions are not checked once the schema declares an
+    /// extension, which may provide them.
+    pub fn undeclared_references(&self) -> Vec<String> {
+        let external: Vec<Pattern> = self
+            .external
+            .iter()
+            .filter_map(|glob| Pattern::new(glob).ok())
+            .collect();
+        let is_external = |name: &str| external.iter().any(|pattern| pattern.matches(name));
+        let is_table =
+            |name: &str| self.tables.contains_key(name) || self.partitions.contains_key(name);
+        let relation_names: HashSet<&str> = self
+            .tables
+            .values()
+            .map(|table| table.name.as_str())
+            .chain(self.partitions.values().map(|p| p.name.as_str()))
+            .chain(self.views.values().map(|view| view.name.as_str()))
+            .collect();
+
+        let mut undeclared = Vec::new();
+        for table in self.tables.values() {
+            for foreign_key in &table.foreign_keys {
+                let target = qualified_name(
+                    &foreign_key.referenced_schema,
+                    &foreign_key.referenced_table,
+                );
+                if !is_table(&target) && !is_external(&target) {
+                    undeclared.push(format!(
+                        "foreign key \"{}\" on {}.{} references table {target}",
+                        foreign_key.name, table.schema, table.name
+                    ));
+                }
+            }
+        }
+
+        for trigger in self.triggers.values() {
+            let function = qualified_name(&trigger.function_schema, &trigger.function_name);
+            let declared = self
+                .functions
+                .values()
+                .any(|f| f.schema == trigger.function_schema && f.name == trigger.function_name);
+            let may_be_external = SYSTEM_SCHEMAS.contains(&trigger.function_schema.as_str())
+                || !self.extensions.is_empty()
+                || BUILTIN_TRIGGER_FUNCTIONS.contains(&trigger.function_name.as_str());
+            if !declared && !may_be_external && !is_external(&function) {
+                undeclared.push(format!(
+                    "trigger \"{}\" on {}.{} calls function {function}()",
+                    trigger.name, trigger.target_schema, trigger.target_name
+                ));
+            }
+        }
+
+        for view in self.views.values() {
+            let ctes: HashSet<String> = CTE_NAME
+                .captures_iter(&view.query)
+                .map(|captures| captures[1].trim_matches('"').to_lowercase())
+                .collect();
+            let mut relations: Vec<String> = extract_table_references(&view.query, &view.schema)
+                .into_iter()
+                .filter(|relation| {
+                    !SYSTEM_SCHEMAS.contains(&relation.schema.as_str())
+                        && !ctes.contains(&relation.name.to_lowercase())
+                        && !relation_names.contains(relation.name.as_str())
+                })
+                .map(|relation| relation.qualified_name())
+                .filter(|name| !self.views.contains_key(name) && !is_external(name))
+                .collect();
+            relations.sort();
+            for relation in relations {
+                undeclared.push(format!(
+                    "view {}.{} selects from {relation}",
+                    view.schema, view.name
+                ));
+            }
+        }
+
+        for policy in &self.pending_policies {
+            let table = qualified_name(&policy.table_schema, &policy.table);
+            if !is_table(&table) && !is_external(&table) {
+                undeclared.push(format!("policy \"{}\" is on table {table}", policy.name));
+            }
+        }
+
+        undeclared
+    }
+}
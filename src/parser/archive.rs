@@ -0,0 +1,93 @@
+//! Reading `.sql` files straight out of `.tar.gz` / `.tgz` / `.zip` bundles.
+//!
+//! Archives are read in memory; nothing is extracted to disk.
+
+use crate::util::{Result, SchemaError};
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+enum ArchiveKind {
+    TarGz,
+    Zip,
+}
+
+fn archive_kind(path: &Path) -> Option<ArchiveKind> {
+    let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+    if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Some(ArchiveKind::TarGz)
+    } else if name.ends_with(".zip") {
+        Some(ArchiveKind::Zip)
+    } else {
+        None
+    }
+}
+
+/// Returns true when `path` names a schema bundle the loader can read.
+pub(super) fn is_archive(path: &Path) -> bool {
+    archive_kind(path).is_some()
+}
+
+/// Reads every `.sql` member of the archive at `path`, returning
+/// `(member path, contents)` pairs sorted by member path.
+pub(super) fn read_sql_entries(path: &Path) -> Result<Vec<(String, String)>> {
+    let archive_error =
+        |e: std::io::Error| SchemaError::ParseError(format!("{}: {e}", path.display()));
+    let file = File::open(path).map_err(archive_error)?;
+
+    let mut entries = Vec::new();
+    match archive_kind(path) {
+        Some(ArchiveKind::TarGz) => {
+            let mut archive = tar::Archive::new(GzDecoder::new(file));
+            for entry in archive.entries().map_err(archive_error)? {
+                let mut entry = entry.map_err(archive_error)?;
+                if !entry.header().entry_type().is_file() {
+                    continue;
+                }
+                let name = entry
+                    .path()
+                    .map_err(archive_error)?
+                    .to_string_lossy()
+                    .to_string();
+                if !is_sql_member(&name) {
+                    continue;
+                }
+                let mut content = String::new();
+                entry.read_to_string(&mut content).map_err(archive_error)?;
+                entries.push((name, content));
+            }
+        }
+        Some(ArchiveKind::Zip) => {
+            let zip_error = |e: zip::result::ZipError| {
+                SchemaError::ParseError(format!("{}: {e}", path.display()))
+            };
+            let mut archive = zip::ZipArchive::new(file).map_err(zip_error)?;
+            for index in 0..archive.len() {
+                let mut member = archive.by_index(index).map_err(zip_error)?;
+                if member.is_dir() || !is_sql_member(member.name()) {
+                    continue;
+                }
+                let name = member.name().to_string();
+                let mut content = String::new();
+                member.read_to_string(&mut content).map_err(archive_error)?;
+                entries.push((name, content));
+            }
+        }
+        None => {
+            return Err(SchemaError::ParseError(format!(
+                "Unsupported archive format: {}",
+                path.display()
+            )))
+        }
+    }
+
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(entries)
+}
+
+fn is_sql_member(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("sql"))
+}
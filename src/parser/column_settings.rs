@@ -0,0 +1,134 @@
+//! `ALTER TABLE ... ALTER COLUMN ... SET STATISTICS n` and
+//! `... SET COMPRESSION method` statements. The SQL parser does not accept
+//! them, so they are lifted out of the source before parsing and applied to
+//! the parsed columns afterwards.
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+use super::util::unquote_ident;
+use crate::model::{qualified_name, Schema};
+
+const IDENT: &str = r#"(?:"(?:[^"]|"")+"|[A-Za-z_][A-Za-z0-9_$]*)"#;
+
+static SET_COLUMN_SETTING: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(
+        r"(?im)^[ \t]*ALTER\s+TABLE\s+(?:IF\s+EXISTS\s+)?(?:ONLY\s+)?(?:({IDENT})\s*\.\s*)?({IDENT})\s+ALTER\s+(?:COLUMN\s+)?({IDENT})\s+SET\s+(?:STATISTICS\s+(-?\d+)|COMPRESSION\s+({IDENT}))\s*;"
+    ))
+    .unwrap()
+});
+
+/// A per-column setting declared in the source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ColumnSetting {
+    pub table_key: String,
+    pub column: String,
+    pub value: ColumnSettingValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum ColumnSettingValue {
+    /// `None` for `-1`, which restores `default_statistics_target`.
+    Statistics(Option<i32>),
+    /// `None` for `default`, which follows `default_toast_compression`.
+    Compression(Option<String>),
+}
+
+/// Splits `sql` into the SQL left for the regular parser and the column
+/// settings it declares. Each statement is blanked to the same number of
+/// lines so later warnings still point at the original file.
+pub(super) fn extract_column_settings(sql: &str) -> (String, Vec<ColumnSetting>) {
+    let mut settings = Vec::new();
+    let remaining = SET_COLUMN_SETTING.replace_all(sql, |captures: &regex::Captures| {
+        let schema = captures
+            .get(1)
+            .map(|m| unquote_ident(m.as_str()))
+            .unwrap_or("public");
+        let value = match (captures.get(4), captures.get(5)) {
+            (Some(target), _) => {
+                let target: i32 = target.as_str().parse().unwrap_or(-1);
+                ColumnSettingValue::Statistics((target >= 0).then_some(target))
+            }
+            (None, Some(method)) => {
+                let method = unquote_ident(method.as_str()).to_lowercase();
+                ColumnSettingValue::Compression((method != "default").then_some(method))
+            }
+            (None, None) => unreachable!("the pattern requires a setting"),
+        };
+        settings.push(ColumnSetting {
+            table_key: qualified_name(schema, unquote_ident(&captures[2])),
+            column: unquote_ident(&captures[3]).to_string(),
+            value,
+        });
+        "\n".repeat(captures[0].matches('\n').count())
+    });
+    (remaining.into_owned(), settings)
+}
+
+/// Applies each setting to its column. Like `ALTER TABLE ... ADD COLUMN`, a
+/// statement naming a table this source does not define is ignored.
+pub(super) fn apply_column_settings(schema: &mut Schema, settings: Vec<ColumnSetting>) {
+    for ColumnSetting {
+        table_key,
+        column,
+        value,
+    } in settings
+    {
+        let Some(column) = schema
+            .tables
+            .get_mut(&table_key)
+            .and_then(|table| table.columns.get_mut(&column))
+        else {
+            continue;
+        };
+        match value {
+            ColumnSettingValue::Statistics(target) => column.statistics = target,
+            ColumnSettingValue::Compression(method) => column.compression = method,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lifts_column_settings_and_keeps_line_numbers() {
+        let sql = "CREATE TABLE events (id BIGINT, kind TEXT);
+ALTER TABLE ONLY analytics.\"Events\"
+    ALTER COLUMN kind SET STATISTICS 1000;
+ALTER TABLE events ALTER kind SET STATISTICS -1;
+ALTER TABLE events ALTER COLUMN kind SET COMPRESSION LZ4;
+ALTER TABLE events ALTER COLUMN id SET COMPRESSION default;";
+
+        let (remaining, settings) = extract_column_settings(sql);
+        assert_eq!(remaining.split('\n').count(), 6);
+        assert!(!remaining.contains("ALTER"));
+        let setting = |table_key: &str, column: &str, value| ColumnSetting {
+            table_key: table_key.to_string(),
+            column: column.to_string(),
+            value,
+        };
+        assert_eq!(
+            settings,
+            vec![
+                setting(
+                    "analytics.Events",
+                    "kind",
+                    ColumnSettingValue::Statistics(Some(1000))
+                ),
+                setting(
+                    "public.events",
+                    "kind",
+                    ColumnSettingValue::Statistics(None)
+                ),
+                setting(
+                    "public.events",
+                    "kind",
+                    ColumnSettingValue::Compression(Some("lz4".to_string()))
+                ),
+                setting("public.events", "id", ColumnSettingValue::Compression(None)),
+            ]
+        );
+    }
+}
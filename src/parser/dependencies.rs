@@ -23,6 +23,18 @@ use super::util::unquote_ident;
 static ROWTYPE_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r#"(?i)(?:(\w+|"[^"]+")\s*\.\s*)?(\w+|"[^"]+")%ROWTYPE"#).unwrap());
 
+static COMMENT_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"--[^\n]*").unwrap());
+
+static DECLARE_SECTION_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)\bDECLARE\b(.*?)\bBEGIN\b").unwrap());
+
+static DECLARATION_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?is)^(?:\w+|"[^"]+")\s+(?:CONSTANT\s+)?(?:(\w+|"[^"]+")\s*\.\s*)?(\w+|"[^"]+")\s*(%)?"#,
+    )
+    .unwrap()
+});
+
 /// A reference to a database object (function, table, view, etc.)
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ObjectRef {
@@ -608,6 +620,47 @@ pub fn extract_rowtype_references(body: &str, default_schema: &str) -> HashSet<O
     refs
 }
 
+/// Extract the types of variables declared in the `DECLARE` sections of a
+/// plpgsql function body: `v status`, `v public.address`, `v mood[]`,
+/// `v CONSTANT app.level := 'x'`. Tables and views name their row types, so
+/// a reference may be a relation as well as an enum, domain or composite type.
+///
+/// Built-in types come back too (qualified with `default_schema`); they match
+/// no node in a plan, so callers can add edges for every reference. Aliases,
+/// cursors and `%TYPE`/`%ROWTYPE` declarations are skipped, the latter being
+/// covered by `extract_rowtype_references`. Like that function, this works on
+/// the raw body text and shares its false-positive caveat.
+pub fn extract_declare_type_references(body: &str, default_schema: &str) -> HashSet<ObjectRef> {
+    let mut refs = HashSet::new();
+
+    for section in DECLARE_SECTION_RE.captures_iter(body) {
+        let section = COMMENT_RE.replace_all(&section[1], "");
+        for declaration in section.split(';') {
+            let Some(cap) = DECLARATION_RE.captures(declaration.trim()) else {
+                continue;
+            };
+            if cap.get(3).is_some() {
+                continue;
+            }
+            let name = unquote_ident(&cap[2]);
+            if cap.get(1).is_none()
+                && ["alias", "cursor", "no", "scroll"]
+                    .iter()
+                    .any(|keyword| name.eq_ignore_ascii_case(keyword))
+            {
+                continue;
+            }
+            let schema = cap
+                .get(1)
+                .map(|m| unquote_ident(m.as_str()))
+                .unwrap_or(default_schema);
+            refs.insert(ObjectRef::new(schema, name));
+        }
+    }
+
+    refs
+}
+
 /// Perform topological sort on a set of objects with dependencies.
 ///
 /// Returns objects in an order where dependencies come before dependents.
@@ -657,11 +710,13 @@ where
         }
     }
 
-    // Kahn's algorithm: start with items that have no dependencies
+    // Kahn's algorithm: start with items that have no dependencies, in input
+    // order so that independent items keep their relative order
     let mut queue: VecDeque<String> = VecDeque::new();
-    for (key, &degree) in &in_degree {
-        if degree == 0 {
-            queue.push_back(key.clone());
+    for item in &items {
+        let key = get_key(item);
+        if in_degree[&key] == 0 {
+            queue.push_back(key);
         }
     }
 
@@ -858,6 +913,40 @@ mod tests {
         assert!(table_refs.contains(&ObjectRef::new("public", "users")));
     }
 
+    #[test]
+    fn extract_declare_types_from_every_block() {
+        let body = r#"
+            DECLARE
+                s order_status := 'new';
+                a billing."Address";
+                m mood[];
+                k CONSTANT app.level := 'high'; -- tier
+                n integer;
+                r users%ROWTYPE;
+                c users.created_at%TYPE;
+                cur CURSOR FOR SELECT 1;
+                p ALIAS FOR $1;
+            BEGIN
+                DECLARE inner_total money_amount;
+                BEGIN
+                    NULL;
+                END;
+            END;
+        "#;
+        let refs = extract_declare_type_references(body, "public");
+
+        let expected: HashSet<ObjectRef> = [
+            ObjectRef::new("public", "order_status"),
+            ObjectRef::new("billing", "Address"),
+            ObjectRef::new("public", "mood"),
+            ObjectRef::new("app", "level"),
+            ObjectRef::new("public", "integer"),
+            ObjectRef::new("public", "money_amount"),
+        ]
+        .into();
+        assert_eq!(refs, expected);
+    }
+
     #[test]
     fn extract_rowtype_simple_unqualified() {
         let body = "DECLARE v users%ROWTYPE;";
@@ -0,0 +1,24 @@
+//! `-- pgmold:external <name>[, <name>...]` marks objects the sources use but
+//! leave to someone else, such as tables another tool manages. Names are
+//! `schema.name` globs, so `auth.*` covers a whole schema.
+
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::sync::LazyLock;
+
+static EXTERNAL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*--\s*pgmold:external\s+(.+?)\s*$").unwrap());
+
+/// The names every `pgmold:external` line in `sql` lists.
+pub(super) fn extract_external(sql: &str) -> BTreeSet<String> {
+    sql.lines()
+        .filter_map(|line| EXTERNAL.captures(line))
+        .flat_map(|captures| {
+            captures[1]
+                .split([',', ' '])
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
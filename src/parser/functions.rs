@@ -20,14 +20,6 @@ pub(super) fn parse_create_function(
     security: Option<&FunctionSecurity>,
     set_params: &[FunctionDefinitionSetParam],
 ) -> Result<Function> {
-    let return_type_str = return_type
-        .map(|rt| normalize_pg_type(&rt.to_string()).into_owned())
-        .ok_or_else(|| {
-            SchemaError::ParseError(format!(
-                "Function {schema}.{name} is missing RETURNS clause"
-            ))
-        })?;
-
     let language_str = language
         .map(|l| l.to_string().to_lowercase())
         .unwrap_or_else(|| "sql".to_string());
@@ -70,7 +62,7 @@ pub(super) fn parse_create_function(
                         Some(SqlArgMode::In) => ArgMode::In,
                         Some(SqlArgMode::Out) => ArgMode::Out,
                         Some(SqlArgMode::InOut) => ArgMode::InOut,
-                        Some(SqlArgMode::Variadic) => ArgMode::In,
+                        Some(SqlArgMode::Variadic) => ArgMode::Variadic,
                         None => ArgMode::In,
                     };
                     FunctionArg {
@@ -84,6 +76,24 @@ pub(super) fn parse_create_function(
         })
         .unwrap_or_default();
 
+    // Without RETURNS, the output arguments decide the result type, as
+    // `pg_get_function_result` reports it: the single one's type, or record.
+    let output_types: Vec<&str> = arguments
+        .iter()
+        .filter(|arg| matches!(arg.mode, ArgMode::Out | ArgMode::InOut))
+        .map(|arg| arg.data_type.as_str())
+        .collect();
+    let return_type_str = match (return_type, output_types.as_slice()) {
+        (Some(rt), _) => normalize_pg_type(&rt.to_string()).into_owned(),
+        (None, [single]) => single.to_string(),
+        (None, [_, _, ..]) => "record".to_string(),
+        (None, []) => {
+            return Err(SchemaError::ParseError(format!(
+                "Function {schema}.{name} is missing RETURNS clause"
+            )))
+        }
+    };
+
     let config_params: Vec<(String, String)> = set_params
         .iter()
         .map(|param| {
@@ -224,7 +224,7 @@ pub(super) fn parse_grant_statements(sql: &str, schema: &mut Schema) -> Result<(
             }
             "FUNCTION" => {
                 let function_key = parse_function_signature(object_name_raw);
-                if let Some(func) = schema.functions.get_mut(&function_key) {
+                if let Some(func) = schema.function_mut(&function_key) {
                     func.grants.push(grant);
                 } else {
                     schema.pending_grants.push(PendingGrant {
@@ -336,7 +336,7 @@ pub(super) fn parse_revoke_statements(sql: &str, schema: &mut Schema) -> Result<
             }
             "FUNCTION" => {
                 let function_key = parse_function_signature(object_name_raw);
-                if let Some(func) = schema.functions.get_mut(&function_key) {
+                if let Some(func) = schema.function_mut(&function_key) {
                     revoke_from_grants(&mut func.grants, grantee, &privileges, grant_option_for);
                 } else {
                     schema.pending_revokes.push(PendingRevoke {
@@ -0,0 +1,175 @@
+//! Opt-in `${VAR}` substitution applied to schema files before parsing.
+//!
+//! Only variables on an explicit allowlist are substituted; any other `${...}`
+//! (for example a PL/v8 template literal) is left untouched. The CLI's
+//! `--env-var` / `--strict-env` flags populate the allowlist and strict mode
+//! through `PGMOLD_ENV_VARS` and `PGMOLD_STRICT_ENV`, mirroring `--strict`.
+
+use crate::util::{Result, SchemaError};
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::sync::LazyLock;
+
+static VAR_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)\}").unwrap());
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InterpolationOptions {
+    /// Variables that may be substituted. Empty disables interpolation.
+    pub allowed: BTreeSet<String>,
+    /// Fail when an allowed variable is referenced but not set.
+    pub strict: bool,
+}
+
+impl InterpolationOptions {
+    pub fn from_env() -> Self {
+        let allowed = std::env::var("PGMOLD_ENV_VARS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect();
+        Self {
+            allowed,
+            strict: matches!(std::env::var("PGMOLD_STRICT_ENV").as_deref(), Ok("1")),
+        }
+    }
+
+    /// Identifies these options, and the current value of each allowed
+    /// variable, in cache keys; empty when interpolation is off.
+    pub fn cache_key(&self) -> String {
+        self.cache_key_with(|name| std::env::var(name).ok())
+    }
+
+    pub(crate) fn cache_key_with(&self, lookup: impl Fn(&str) -> Option<String>) -> String {
+        if self.allowed.is_empty() {
+            return String::new();
+        }
+        let values: Vec<String> = self
+            .allowed
+            .iter()
+            .map(|name| match lookup(name) {
+                Some(value) => format!("{name}={value}"),
+                None => name.clone(),
+            })
+            .collect();
+        format!("{}:{}", self.strict, values.join("\0"))
+    }
+}
+
+/// Substitutes allowed `${VAR}` references in `sql` from the process environment.
+pub fn interpolate_env(sql: &str, options: &InterpolationOptions) -> Result<String> {
+    interpolate_with(sql, options, |name| std::env::var(name).ok())
+}
+
+fn interpolate_with(
+    sql: &str,
+    options: &InterpolationOptions,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String> {
+    if options.allowed.is_empty() {
+        return Ok(sql.to_string());
+    }
+
+    let mut undefined = BTreeSet::new();
+    let result = VAR_RE.replace_all(sql, |caps: &regex::Captures| {
+        let name = &caps[1];
+        if !options.allowed.contains(name) {
+            return caps[0].to_string();
+        }
+        lookup(name).unwrap_or_else(|| {
+            undefined.insert(name.to_string());
+            caps[0].to_string()
+        })
+    });
+
+    if !undefined.is_empty() {
+        let names = undefined.into_iter().collect::<Vec<_>>().join(", ");
+        if options.strict {
+            return Err(SchemaError::ParseError(format!(
+                "Undefined environment variable(s) referenced in schema: {names}"
+            )));
+        }
+        eprintln!("warning: undefined environment variable(s) left as-is: {names}");
+    }
+    Ok(result.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(allowed: &[&str], strict: bool) -> InterpolationOptions {
+        InterpolationOptions {
+            allowed: allowed.iter().map(|s| s.to_string()).collect(),
+            strict,
+        }
+    }
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "APP_ROLE" => Some("app_prod".to_string()),
+            "SCHEMA_PREFIX" => Some("tenant_a".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn substitutes_only_allowed_variables() {
+        let sql = "GRANT SELECT ON ${SCHEMA_PREFIX}_core.users TO ${APP_ROLE};";
+        let result = interpolate_with(sql, &options(&["APP_ROLE"], false), lookup).unwrap();
+        assert_eq!(
+            result,
+            "GRANT SELECT ON ${SCHEMA_PREFIX}_core.users TO app_prod;"
+        );
+    }
+
+    #[test]
+    fn disabled_without_allowlist() {
+        let sql = "GRANT SELECT ON users TO ${APP_ROLE};";
+        assert_eq!(
+            interpolate_with(sql, &options(&[], true), lookup).unwrap(),
+            sql
+        );
+    }
+
+    #[test]
+    fn undefined_allowed_variable_is_kept_unless_strict() {
+        let sql = "GRANT SELECT ON users TO ${MISSING};";
+        assert_eq!(
+            interpolate_with(sql, &options(&["MISSING"], false), lookup).unwrap(),
+            sql
+        );
+
+        let err = interpolate_with(sql, &options(&["MISSING"], true), lookup)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("MISSING"), "{err}");
+    }
+
+    #[test]
+    fn strict_mode_ignores_variables_outside_allowlist() {
+        let sql = "CREATE FUNCTION f() RETURNS text LANGUAGE plv8 AS $$ return `${x}`; $$;";
+        assert_eq!(
+            interpolate_with(sql, &options(&["APP_ROLE"], true), lookup).unwrap(),
+            sql
+        );
+    }
+
+    #[test]
+    fn cache_key_changes_with_allowed_variable_values() {
+        let opts = options(&["APP_ROLE"], false);
+        let key = |value: &str| {
+            let value = value.to_string();
+            opts.cache_key_with(move |name| (name == "APP_ROLE").then(|| value.clone()))
+        };
+        assert_ne!(key("app_rw"), key("app_ro"));
+        assert_eq!(key("app_rw"), key("app_rw"));
+        assert_ne!(
+            key("app_rw"),
+            options(&["APP_ROLE"], true).cache_key_with(|_| Some("app_rw".to_string()))
+        );
+        assert_eq!(options(&[], false).cache_key(), "");
+    }
+}
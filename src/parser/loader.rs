@@ -1,9 +1,13 @@
+use super::archive::{is_archive, read_sql_entries};
 use super::{
-    extract_function_references, extract_table_references, parse_sql_file, topological_sort,
+    extract_function_references, extract_table_references, parse_source_sql, parse_sql_file,
+    topological_sort,
 };
 use crate::model::Schema;
 use crate::util::{Result, SchemaError};
 use glob::glob;
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
 use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
@@ -39,14 +43,30 @@ fn extract_schema_dependencies(schema: &Schema) -> HashSet<String> {
     deps
 }
 
-/// Load schemas from multiple sources (files, directories, glob patterns).
+/// Load schemas from multiple sources (files, directories, glob patterns, and
+/// `.tar.gz` / `.tgz` / `.zip` bundles of SQL files).
 /// Returns a merged Schema or error on conflicts.
 pub fn load_schema_sources(sources: &[String]) -> Result<Schema> {
     // Resolve all sources to file paths, deduplicating
     let mut all_files: Vec<PathBuf> = Vec::new();
     let mut seen: BTreeSet<PathBuf> = BTreeSet::new();
+    // Archive members are parsed from memory, labelled `<archive>/<member>`.
+    let mut archive_members: Vec<(PathBuf, String)> = Vec::new();
 
     for source in sources {
+        let path = Path::new(source);
+        if path.is_file() && is_archive(path) {
+            let canonical = path
+                .canonicalize()
+                .map_err(|e| SchemaError::ParseError(format!("Cannot resolve path: {e}")))?;
+            if seen.insert(canonical) {
+                for (member, content) in read_sql_entries(path)? {
+                    archive_members.push((path.join(member), content));
+                }
+            }
+            continue;
+        }
+
         let files = resolve_source(source)?;
         for file in files {
             let canonical = file
@@ -58,21 +78,33 @@ pub fn load_schema_sources(sources: &[String]) -> Result<Schema> {
         }
     }
 
-    if all_files.is_empty() {
+    if all_files.is_empty() && archive_members.is_empty() {
         return Err(SchemaError::ParseError(
             "No SQL files found in provided sources".to_string(),
         ));
     }
 
-    // Parse all files, tracking file paths for error messages
-    let mut file_schemas: Vec<(PathBuf, Schema)> = Vec::new();
-    for file in &all_files {
-        let file_str = file.to_str().ok_or_else(|| {
-            SchemaError::ParseError(format!("Path contains invalid UTF-8: {}", file.display()))
-        })?;
-        let schema = parse_sql_file(file_str)?;
-        file_schemas.push((file.clone(), schema));
-    }
+    // Parse all files in parallel, tracking file paths for error messages.
+    // Results keep source order, so the merge below and the error reported
+    // for the first failing file do not depend on thread scheduling.
+    let parsed_files: Vec<Result<(PathBuf, Schema)>> = all_files
+        .into_par_iter()
+        .map(|file| {
+            let file_str = file.to_str().ok_or_else(|| {
+                SchemaError::ParseError(format!("Path contains invalid UTF-8: {}", file.display()))
+            })?;
+            let schema = parse_sql_file(file_str)?;
+            Ok((file, schema))
+        })
+        .collect();
+    let parsed_members: Vec<Result<(PathBuf, Schema)>> = archive_members
+        .into_par_iter()
+        .map(|(label, content)| Ok((label, parse_source_sql(&content)?)))
+        .collect();
+    let mut file_schemas = parsed_files
+        .into_iter()
+        .chain(parsed_members)
+        .collect::<Result<Vec<_>>>()?;
 
     // Sort files topologically based on dependencies
     file_schemas = topological_sort(
@@ -128,7 +160,22 @@ pub fn load_schema_sources(sources: &[String]) -> Result<Schema> {
             partitions,
             schema
         );
+        merge_objects!(
+            object_sources,
+            path,
+            merged,
+            "raw object",
+            raw_objects,
+            schema
+        );
 
+        merged.external.extend(schema.external);
+        merged.schema_moves.extend(schema.schema_moves);
+        merged.renames.extend(schema.renames);
+        merged
+            .partition_retention
+            .extend(schema.partition_retention);
+        merged.sequence_values.extend(schema.sequence_values);
         merged.pending_policies.extend(schema.pending_policies);
         merged.pending_owners.extend(schema.pending_owners);
         merged.pending_grants.extend(schema.pending_grants);
@@ -141,9 +188,41 @@ pub fn load_schema_sources(sources: &[String]) -> Result<Schema> {
     Ok(merged)
 }
 
+/// Hash of every file `load_schema_sources` would read for `sources`, in the
+/// same resolution order. Changes whenever a file is added, removed, renamed
+/// or edited, so it can key caches of the parsed result.
+pub fn hash_schema_sources(sources: &[String]) -> Result<String> {
+    let mut hasher = Sha256::new();
+    let mut seen: BTreeSet<PathBuf> = BTreeSet::new();
+    for source in sources {
+        let path = Path::new(source);
+        let files = if path.is_file() && is_archive(path) {
+            vec![path.to_path_buf()]
+        } else {
+            resolve_source(source)?
+        };
+        for file in files {
+            let canonical = file
+                .canonicalize()
+                .map_err(|e| SchemaError::ParseError(format!("Cannot resolve path: {e}")))?;
+            if !seen.insert(canonical) {
+                continue;
+            }
+            let content = std::fs::read(&file).map_err(|e| {
+                SchemaError::ParseError(format!("Cannot read {}: {e}", file.display()))
+            })?;
+            hasher.update(file.to_string_lossy().as_bytes());
+            hasher.update([0]);
+            hasher.update((content.len() as u64).to_le_bytes());
+            hasher.update(&content);
+        }
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
 /// Resolve a source pattern to a list of SQL file paths.
 /// Handles: single files, directories (recursive *.sql), and glob patterns.
-fn resolve_source(source: &str) -> Result<Vec<PathBuf>> {
+pub(super) fn resolve_source(source: &str) -> Result<Vec<PathBuf>> {
     let path = Path::new(source);
 
     if path.is_file() {
@@ -334,10 +413,12 @@ mod tests {
                 primary_key: None,
                 foreign_keys: Vec::new(),
                 check_constraints: Vec::new(),
+                unique_constraints: Vec::new(),
                 exclusion_constraints: Vec::new(),
                 comment: None,
                 row_level_security: false,
                 force_row_level_security: false,
+                replica_identity: None,
                 policies: Vec::new(),
                 partition_by: None,
 
@@ -357,10 +438,12 @@ mod tests {
                 primary_key: None,
                 foreign_keys: Vec::new(),
                 check_constraints: Vec::new(),
+                unique_constraints: Vec::new(),
                 exclusion_constraints: Vec::new(),
                 comment: None,
                 row_level_security: false,
                 force_row_level_security: false,
+                replica_identity: None,
                 policies: Vec::new(),
                 partition_by: None,
 
@@ -390,10 +473,12 @@ mod tests {
                 primary_key: None,
                 foreign_keys: Vec::new(),
                 check_constraints: Vec::new(),
+                unique_constraints: Vec::new(),
                 exclusion_constraints: Vec::new(),
                 comment: None,
                 row_level_security: false,
                 force_row_level_security: false,
+                replica_identity: None,
                 policies: Vec::new(),
                 partition_by: None,
 
@@ -413,10 +498,12 @@ mod tests {
                 primary_key: None,
                 foreign_keys: Vec::new(),
                 check_constraints: Vec::new(),
+                unique_constraints: Vec::new(),
                 exclusion_constraints: Vec::new(),
                 comment: None,
                 row_level_security: false,
                 force_row_level_security: false,
+                replica_identity: None,
                 policies: Vec::new(),
                 partition_by: None,
 
@@ -1197,4 +1284,136 @@ CREATE TRIGGER "on_auth_user_created" AFTER INSERT ON "auth"."users" FOR EACH RO
             .privileges
             .contains(&crate::model::Privilege::Usage));
     }
+
+    const BUNDLE_FILES: [(&str, &str); 3] = [
+        (
+            "schema/02_posts.sql",
+            "CREATE TABLE posts (id INT PRIMARY KEY, user_id INT REFERENCES users(id));",
+        ),
+        (
+            "schema/01_users.sql",
+            "CREATE TABLE users (id INT PRIMARY KEY);",
+        ),
+        ("README.md", "not sql"),
+    ];
+
+    fn write_zip_bundle(path: &Path) {
+        use std::io::Write;
+
+        let mut writer = zip::ZipWriter::new(fs::File::create(path).unwrap());
+        for (name, content) in BUNDLE_FILES {
+            writer
+                .start_file(name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn load_tar_gz_bundle() {
+        let temp = TempDir::new().unwrap();
+        let bundle = temp.path().join("bundle.tar.gz");
+
+        let encoder = flate2::write::GzEncoder::new(
+            fs::File::create(&bundle).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut builder = tar::Builder::new(encoder);
+        for (name, content) in BUNDLE_FILES {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let schema = load_schema_sources(&[bundle.display().to_string()]).unwrap();
+        assert!(schema.tables.contains_key("public.users"));
+        assert!(schema.tables.contains_key("public.posts"));
+    }
+
+    #[test]
+    fn load_zip_bundle_alongside_files() {
+        let temp = TempDir::new().unwrap();
+        let bundle = temp.path().join("bundle.zip");
+        write_zip_bundle(&bundle);
+
+        let extra = temp.path().join("comments.sql");
+        fs::write(&extra, "CREATE TABLE comments (id INT PRIMARY KEY);").unwrap();
+
+        let schema =
+            load_schema_sources(&[bundle.display().to_string(), extra.display().to_string()])
+                .unwrap();
+        assert_eq!(schema.tables.len(), 3);
+    }
+
+    #[test]
+    fn archive_members_are_read_in_path_order() {
+        let temp = TempDir::new().unwrap();
+        let bundle = temp.path().join("bundle.zip");
+        write_zip_bundle(&bundle);
+
+        let names: Vec<String> = read_sql_entries(&bundle)
+            .unwrap()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, vec!["schema/01_users.sql", "schema/02_posts.sql"]);
+    }
+
+    #[test]
+    fn source_hash_tracks_file_contents() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("users.sql");
+        fs::write(&file, "CREATE TABLE users (id INT);").unwrap();
+        let sources = vec![dir.path().display().to_string()];
+
+        let first = hash_schema_sources(&sources).unwrap();
+        assert_eq!(hash_schema_sources(&sources).unwrap(), first);
+
+        fs::write(&file, "CREATE TABLE users (id BIGINT);").unwrap();
+        let edited = hash_schema_sources(&sources).unwrap();
+        assert_ne!(edited, first);
+
+        fs::write(dir.path().join("posts.sql"), "CREATE TABLE posts (id INT);").unwrap();
+        assert_ne!(hash_schema_sources(&sources).unwrap(), edited);
+    }
+
+    #[test]
+    fn parallel_load_merges_in_source_order() {
+        let dir = TempDir::new().unwrap();
+        for i in 0..64 {
+            fs::write(
+                dir.path().join(format!("t_{i:02}.sql")),
+                format!("CREATE TABLE t_{i} (id INT PRIMARY KEY);"),
+            )
+            .unwrap();
+        }
+        fs::write(
+            dir.path().join("z_dup.sql"),
+            "CREATE TABLE t_0 (id INT PRIMARY KEY);",
+        )
+        .unwrap();
+
+        let sources = vec![dir.path().display().to_string()];
+        let err = load_schema_sources(&sources).unwrap_err().to_string();
+        let first = err.find("t_00.sql").expect(&err);
+        let second = err.find("z_dup.sql").expect(&err);
+        assert!(first < second, "{err}");
+        for _ in 0..4 {
+            assert_eq!(load_schema_sources(&sources).unwrap_err().to_string(), err);
+        }
+
+        fs::remove_file(dir.path().join("z_dup.sql")).unwrap();
+        let schema = load_schema_sources(&sources).unwrap();
+        assert_eq!(schema.tables.len(), 64);
+        assert_eq!(
+            schema.fingerprint(),
+            load_schema_sources(&sources).unwrap().fingerprint()
+        );
+    }
 }
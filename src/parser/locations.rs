@@ -0,0 +1,133 @@
+//! Where each object in a set of SQL schema sources is defined, so generated
+//! SQL can point back at the file and line it came from.
+
+use super::archive::{is_archive, read_sql_entries};
+use super::loader::resolve_source;
+use super::parse_source_sql;
+use crate::util::{Result, SchemaError};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+/// File, and 1-based line within it, defining an object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: usize,
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+/// Source locations keyed by qualified object name (without a function's
+/// argument list). When two files define objects of the same name, the
+/// first file wins.
+pub type SourceLocations = BTreeMap<String, SourceLocation>;
+
+/// Locates the schemas, extensions, types, tables, partitions, sequences,
+/// functions, aggregates and views defined by `sources`, resolved the same
+/// way as [`super::load_schema_sources`].
+pub fn locate_schema_objects(sources: &[String]) -> Result<SourceLocations> {
+    let mut files: Vec<(String, String)> = Vec::new();
+    for source in sources {
+        let path = Path::new(source);
+        if path.is_file() && is_archive(path) {
+            for (member, content) in read_sql_entries(path)? {
+                files.push((path.join(member).display().to_string(), content));
+            }
+            continue;
+        }
+        for file in resolve_source(source)? {
+            let content = std::fs::read_to_string(&file).map_err(|e| {
+                SchemaError::ParseError(format!("Cannot read {}: {e}", file.display()))
+            })?;
+            files.push((file.display().to_string(), content));
+        }
+    }
+
+    let mut locations = SourceLocations::new();
+    for (file, content) in files {
+        let schema = parse_source_sql(&content)?;
+        let names = schema
+            .schemas
+            .keys()
+            .chain(schema.extensions.keys())
+            .chain(schema.enums.keys())
+            .chain(schema.domains.keys())
+            .chain(schema.tables.keys())
+            .chain(schema.partitions.keys())
+            .chain(schema.sequences.keys())
+            .chain(schema.functions.keys())
+            .chain(schema.aggregates.keys())
+            .chain(schema.views.keys());
+        for key in names {
+            let name = key.split('(').next().unwrap_or(key);
+            if locations.contains_key(name) {
+                continue;
+            }
+            locations.insert(
+                name.to_string(),
+                SourceLocation {
+                    file: file.clone(),
+                    line: definition_line(&content, name),
+                },
+            );
+        }
+    }
+    Ok(locations)
+}
+
+/// First line with a `CREATE` naming the object, falling back to the first
+/// line mentioning it at all, then to line 1.
+fn definition_line(content: &str, qualified_name: &str) -> usize {
+    let bare_name = qualified_name.rsplit('.').next().unwrap_or(qualified_name);
+    let name = format!(r#"(^|[^\w"]|"){}("|[^\w"]|$)"#, regex::escape(bare_name));
+    let create = Regex::new(&format!(r"(?i)\bCREATE\b.*{name}")).expect("valid regex");
+    let mention = Regex::new(&format!("(?i){name}")).expect("valid regex");
+    let find = |re: &Regex| content.lines().position(|line| re.is_match(line));
+    find(&create)
+        .or_else(|| find(&mention))
+        .map_or(1, |index| index + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn locates_objects_by_file_and_line() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("users.sql"),
+            "-- Accounts\n\nCREATE TABLE users (\n    id BIGINT PRIMARY KEY\n);\n\nCREATE VIEW\n    active_users AS SELECT id FROM users;\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("functions.sql"),
+            "CREATE FUNCTION users_count() RETURNS bigint LANGUAGE sql AS $$ SELECT 1 $$;\n",
+        )
+        .unwrap();
+
+        let locations =
+            locate_schema_objects(&[dir.path().to_string_lossy().into_owned()]).unwrap();
+        let users = dir.path().join("users.sql").display().to_string();
+        assert_eq!(
+            locations["public.users"],
+            SourceLocation {
+                file: users.clone(),
+                line: 3
+            }
+        );
+        assert_eq!(locations["public.active_users"].line, 8);
+        assert_eq!(locations["public.users_count"].line, 1);
+        assert!(locations["public.users_count"]
+            .file
+            .ends_with("functions.sql"));
+    }
+}
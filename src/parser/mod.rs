@@ -5,13 +5,22 @@
 //! forcing explicit triage, not silent data loss. See ARCHITECTURE.md §
 //! "Match arm discipline".
 
+mod archive;
+mod column_settings;
 mod comments;
 mod dependencies;
+mod external;
 mod functions;
 mod grants;
+mod interpolate;
 mod loader;
+mod locations;
+mod moves;
 mod ownership;
 mod preprocess;
+mod raw;
+mod renames;
+mod retention;
 mod sequences;
 mod tables;
 mod unrecognized;
@@ -21,10 +30,13 @@ mod util;
 mod tests;
 
 pub use dependencies::{
-    extract_function_references, extract_rowtype_references, extract_table_references,
-    topological_sort, ObjectRef,
+    extract_declare_type_references, extract_function_references, extract_rowtype_references,
+    extract_table_references, topological_sort, ObjectRef,
 };
-pub use loader::load_schema_sources;
+pub use interpolate::{interpolate_env, InterpolationOptions};
+pub use loader::{hash_schema_sources, load_schema_sources};
+pub use locations::{locate_schema_objects, SourceLocation, SourceLocations};
+pub(crate) use preprocess::{split_statements, SqlStatement};
 pub use unrecognized::{find_unrecognized_statements, UnrecognizedStatement};
 
 use crate::model::*;
@@ -36,8 +48,9 @@ use sqlparser::ast::{
     AlterTableOperation, AlterType, AlterTypeAddValue, AlterTypeAddValuePosition,
     AlterTypeOperation, CreateAggregate, CreateAggregateOption, CreateDomain, CreateExtension,
     CreateFunction, CreateServerStatement, CreateTrigger, CreateView, DeferrableInitial,
-    DropDomain, DropExtension, DropFunction, DropTrigger, FunctionParallel, Grantee, GranteeName,
-    GranteesType, ObjectType, Owner, Privileges, RenameTableNameKind, SchemaName, Statement,
+    DropDomain, DropExtension, DropFunction, DropTrigger, Expr, FunctionParallel, Grantee,
+    GranteeName, GranteesType, IndexColumn, NullsDistinctOption, ObjectType, Owner, Privileges,
+    RenameTableNameKind, ReplicaIdentity as SqlReplicaIdentity, SchemaName, Statement,
     TableConstraint, TriggerEvent as SqlTriggerEvent, TriggerPeriod, TriggerReferencingType,
     UserDefinedTypeRepresentation,
 };
@@ -46,34 +59,52 @@ use sqlparser::parser::Parser;
 use std::collections::BTreeSet;
 use std::fs;
 
+use column_settings::{apply_column_settings, extract_column_settings};
 use comments::{apply_comment_statement, CommentStatement};
+use external::extract_external;
 use functions::parse_create_function;
 use grants::{
     all_privileges_for, apply_default_privileges_grant, apply_default_privileges_revoke,
     parse_grant_statements, parse_revoke_statements,
 };
+use moves::extract_schema_moves;
 use ownership::parse_owner_statements;
 use preprocess::preprocess_sql;
-use sequences::parse_create_sequence;
+use raw::extract_raw_objects;
+use renames::extract_renames;
+use retention::{check_retention, extract_retention};
+use sequences::{parse_create_sequence, parse_setval};
 use tables::{
-    apply_primary_key, parse_column_with_serial, parse_create_table, parse_referential_action,
+    apply_primary_key, constraint_deferral, parse_column_with_serial, parse_create_table,
+    parse_referential_action,
 };
+pub(crate) use util::truncate_identifier;
 use util::{
     extract_qualified_name, normalize_expr, parse_data_type, parse_for_values,
-    parse_for_values_required, parse_policy_command, truncate_identifier, unquote_ident,
+    parse_for_values_required, parse_policy_command, unquote_ident, view_option_enabled,
+    view_option_value,
 };
 
 pub fn parse_sql_file(path: &str) -> Result<Schema> {
     let content = fs::read_to_string(path)
         .map_err(|e| SchemaError::ParseError(format!("Failed to read file: {e}")))?;
-    parse_sql_string(&content)
+    parse_source_sql(&content)
+}
+
+/// Parses the contents of a schema source file, first applying any `${VAR}`
+/// interpolation enabled through [`InterpolationOptions::from_env`].
+fn parse_source_sql(content: &str) -> Result<Schema> {
+    parse_sql_string(&interpolate_env(
+        content,
+        &InterpolationOptions::from_env(),
+    )?)
 }
 
 /// Returns `true` when the parser should treat unrecognized top-level
 /// statements as errors instead of warnings. Controlled via the
 /// `PGMOLD_STRICT` environment variable; set to `1` by the CLI's
 /// `--strict` flag.
-fn strict_mode_from_env() -> bool {
+pub(crate) fn strict_mode_from_env() -> bool {
     matches!(std::env::var("PGMOLD_STRICT").as_deref(), Ok("1"))
 }
 
@@ -85,7 +116,28 @@ pub fn parse_sql_string(sql: &str) -> Result<Schema> {
 /// strict behavior (tests, library consumers that do not want to mutate
 /// process-wide env vars) should prefer this over `parse_sql_string`.
 pub fn parse_sql_string_with_strict(sql: &str, strict: bool) -> Result<Schema> {
-    let schema = parse_sql_string_inner(sql)?;
+    let external = extract_external(sql);
+    let schema_moves = extract_schema_moves(sql)?;
+    let renames = extract_renames(sql)?;
+    let retention = extract_retention(sql)?;
+    let (sql, raw_objects) = extract_raw_objects(sql)?;
+    let (sql, column_settings) = extract_column_settings(&sql);
+    let sql = sql.as_str();
+    let mut schema = parse_sql_string_inner(sql)?;
+    apply_column_settings(&mut schema, column_settings);
+    schema.external.extend(external);
+    schema.schema_moves.extend(schema_moves);
+    schema.renames.extend(renames);
+    schema.partition_retention.extend(retention);
+    check_retention(&schema)?;
+    for raw in raw_objects {
+        let name = raw.name.clone();
+        if schema.raw_objects.insert(name.clone(), raw).is_some() {
+            return Err(SchemaError::ParseError(format!(
+                "Duplicate raw object \"{name}\""
+            )));
+        }
+    }
     let unrecognized = find_unrecognized_statements(sql);
     for finding in &unrecognized {
         eprintln!("{}", finding.warning_message());
@@ -163,19 +215,24 @@ fn parse_sql_string_inner(sql: &str) -> Result<Schema> {
                         Some(sqlparser::ast::IndexType::GiST) => IndexType::Gist,
                         Some(sqlparser::ast::IndexType::GIN) => IndexType::Gin,
                         Some(sqlparser::ast::IndexType::Hash) => IndexType::Hash,
-                        Some(using) => panic!("unsupported index type: {using:?}"),
+                        Some(sqlparser::ast::IndexType::BRIN) => IndexType::Brin,
+                        Some(sqlparser::ast::IndexType::SPGiST) => IndexType::SpGist,
+                        Some(using) => {
+                            return Err(SchemaError::ParseError(format!(
+                                "Index \"{idx_name}\" uses unsupported index method {using}"
+                            )))
+                        }
                     };
+                    let (columns, column_options) =
+                        ci.columns.iter().map(parse_index_column).unzip();
                     table.indexes.push(Index {
                         name: idx_name,
-                        columns: ci
-                            .columns
-                            .iter()
-                            .map(|c| unquote_ident(&c.column.expr.to_string()).to_string())
-                            .collect(),
+                        columns,
                         unique: ci.unique,
                         index_type,
                         predicate: ci.predicate.as_ref().map(|p| p.to_string()),
-                        is_constraint: false,
+                        nulls_not_distinct: ci.nulls_distinct == Some(false),
+                        column_options: IndexColumnOptions::normalize(column_options),
                     });
                     table.indexes.sort();
                 }
@@ -215,19 +272,9 @@ fn parse_sql_string_inner(sql: &str) -> Result<Schema> {
                     table_schema: tbl_schema,
                     table: tbl_name,
                     command: parse_policy_command(&command),
-                    roles: {
-                        let parsed_roles: Vec<String> = to
-                            .iter()
-                            .flat_map(|owners: &Vec<sqlparser::ast::Owner>| {
-                                owners.iter().map(|o| strip_ident_quotes(&o.to_string()))
-                            })
-                            .collect();
-                        if parsed_roles.is_empty() {
-                            vec!["public".to_string()]
-                        } else {
-                            parsed_roles
-                        }
-                    },
+                    roles: Policy::normalize_roles(
+                        to.iter().flatten().map(policy_role_name),
+                    ),
                     using_expr: using.as_ref().map(|e: &sqlparser::ast::Expr| normalize_expr(&e.to_string())),
                     check_expr: with_check.as_ref().map(|e: &sqlparser::ast::Expr| normalize_expr(&e.to_string())),
                     comment: None,
@@ -261,6 +308,18 @@ fn parse_sql_string_inner(sql: &str) -> Result<Schema> {
                                 table.force_row_level_security = false;
                             }
                         }
+                        AlterTableOperation::ReplicaIdentity { identity } => {
+                            if let Some(table) = schema.tables.get_mut(&tbl_key) {
+                                table.replica_identity = match identity {
+                                    SqlReplicaIdentity::Default => None,
+                                    SqlReplicaIdentity::Full => Some(ReplicaIdentity::Full),
+                                    SqlReplicaIdentity::Nothing => Some(ReplicaIdentity::Nothing),
+                                    SqlReplicaIdentity::Index(index) => Some(
+                                        ReplicaIdentity::Index(unquote_ident(&index.to_string()).to_string()),
+                                    ),
+                                };
+                            }
+                        }
                         AlterTableOperation::EnableTrigger { name: trig_name } => {
                             let key = make_trigger_key(&tbl_schema, &tbl_name, &trig_name.value);
                             if let Some(trigger) = schema.triggers.get_mut(&key) {
@@ -305,6 +364,8 @@ fn parse_sql_string_inner(sql: &str) -> Result<Schema> {
                                             });
                                         let (ref_schema, ref_table) =
                                             extract_qualified_name(&fk.foreign_table);
+                                        let (deferrable, initially_deferred) =
+                                            constraint_deferral(fk.characteristics.as_ref());
                                         table.foreign_keys.push(ForeignKey {
                                             name: truncate_identifier(&fk_name),
                                             columns: fk
@@ -321,6 +382,8 @@ fn parse_sql_string_inner(sql: &str) -> Result<Schema> {
                                                 .collect(),
                                             on_delete: parse_referential_action(&fk.on_delete),
                                             on_update: parse_referential_action(&fk.on_update),
+                                            deferrable,
+                                            initially_deferred,
                                         });
                                     }
                                     TableConstraint::Check(chk) => {
@@ -337,28 +400,34 @@ fn parse_sql_string_inner(sql: &str) -> Result<Schema> {
                                         table.check_constraints.sort();
                                     }
                                     TableConstraint::Unique(uniq) => {
+                                        let columns: Vec<String> = uniq
+                                            .columns
+                                            .iter()
+                                            .map(|c| {
+                                                unquote_ident(&c.column.expr.to_string())
+                                                    .to_string()
+                                            })
+                                            .collect();
+                                        // Named as PostgreSQL names an unnamed UNIQUE constraint.
                                         let constraint_name = uniq
                                             .name
                                             .as_ref()
                                             .map(|n| unquote_ident(&n.to_string()).to_string())
-                                            .unwrap_or_else(|| format!("{tbl_name}_unique"));
-
-                                        table.indexes.push(Index {
-                                            name: constraint_name,
-                                            columns: uniq
-                                                .columns
-                                                .iter()
-                                                .map(|c| {
-                                                    unquote_ident(&c.column.expr.to_string())
-                                                        .to_string()
-                                                })
-                                                .collect(),
-                                            unique: true,
-                                            index_type: IndexType::BTree,
-                                            predicate: None,
-                                            is_constraint: true,
+                                            .unwrap_or_else(|| {
+                                                format!("{tbl_name}_{}_key", columns.join("_"))
+                                            });
+                                        let (deferrable, initially_deferred) =
+                                            constraint_deferral(uniq.characteristics.as_ref());
+
+                                        table.unique_constraints.push(UniqueConstraint {
+                                            name: truncate_identifier(&constraint_name),
+                                            columns,
+                                            nulls_not_distinct: uniq.nulls_distinct
+                                                == NullsDistinctOption::NotDistinct,
+                                            deferrable,
+                                            initially_deferred,
                                         });
-                                        table.indexes.sort();
+                                        table.unique_constraints.sort();
                                     }
                                     // PostgreSQL emits `PRIMARY KEY USING INDEX <idx>` /
                                     // `UNIQUE USING INDEX <idx>` when a standalone unique index
@@ -470,6 +539,12 @@ fn parse_sql_string_inner(sql: &str) -> Result<Schema> {
                                     }
                                 }
 
+                                for unique in &mut table.unique_constraints {
+                                    if unique.name == old_constraint_name {
+                                        unique.name = new_constraint_name.clone();
+                                    }
+                                }
+
                                 for fk in &mut table.foreign_keys {
                                     if fk.name == old_constraint_name {
                                         fk.name = new_constraint_name.clone();
@@ -533,7 +608,6 @@ fn parse_sql_string_inner(sql: &str) -> Result<Schema> {
                         | AlterTableOperation::DropConstraint { .. }
                         | AlterTableOperation::ValidateConstraint { .. }
                         | AlterTableOperation::DropPrimaryKey { .. }
-                        | AlterTableOperation::ReplicaIdentity { .. }
                         | AlterTableOperation::SetOptionsParens { .. }
                         | AlterTableOperation::EnableRule { .. }
                         | AlterTableOperation::DisableRule { .. }
@@ -688,6 +762,7 @@ fn parse_sql_string_inner(sql: &str) -> Result<Schema> {
                 name,
                 query,
                 materialized,
+                options,
                 ..
             }) => {
                 let (view_schema, view_name) = extract_qualified_name(&name);
@@ -699,6 +774,11 @@ fn parse_sql_string_inner(sql: &str) -> Result<Schema> {
                     owner: None,
                     grants: Vec::new(),
                     comment: None,
+                    security_barrier: view_option_enabled(&options, "security_barrier"),
+                    security_invoker: view_option_enabled(&options, "security_invoker"),
+                    check_option: view_option_value(&options, "check_option")
+                        .as_deref()
+                        .and_then(ViewCheckOption::from_sql_str),
                 };
                 let key = qualified_name(&view_schema, &view_name);
                 schema.views.insert(key, view);
@@ -721,6 +801,7 @@ fn parse_sql_string_inner(sql: &str) -> Result<Schema> {
                     schema: ext_schema
                         .as_ref()
                         .map(|s| unquote_ident(&s.to_string()).to_string()),
+                    relocatable: false,
                     comment: None,
                 };
                 schema.extensions.insert(ext_name, ext);
@@ -1056,6 +1137,7 @@ fn parse_sql_string_inner(sql: &str) -> Result<Schema> {
                         .as_ref()
                         .map(|args| {
                             args.iter()
+                                .filter(|a| !matches!(a.mode, Some(sqlparser::ast::ArgMode::Out)))
                                 .map(|a| {
                                     let type_str = a.data_type.to_string();
                                     normalize_pg_type(&type_str).into_owned()
@@ -1112,14 +1194,18 @@ fn parse_sql_string_inner(sql: &str) -> Result<Schema> {
                     schema.extensions.remove(&ext_name);
                 }
             }
+            Statement::Query(query) => {
+                if let Some((name, value)) = parse_setval(&query) {
+                    schema.sequence_values.insert(name, value);
+                }
+            }
             // Non-DDL and dialect-specific statements that pgmold does not
             // model. Listed explicitly (instead of a bare `_`) so adding a
             // new `Statement` variant upstream triggers a clippy warning and
             // forces triage. See ARCHITECTURE.md § "Match arm discipline".
 
-            // Data-manipulation and query statements.
-            Statement::Query(_)
-            | Statement::Insert(_)
+            // Data-manipulation statements.
+            Statement::Insert(_)
             | Statement::Update(_)
             | Statement::Delete(_)
             | Statement::Merge(_)
@@ -1234,6 +1320,13 @@ fn parse_sql_string_inner(sql: &str) -> Result<Schema> {
                                     found = true;
                                 }
                             }
+                            // Renaming a constraint's index renames the constraint.
+                            for unique in &mut table.unique_constraints {
+                                if unique.name == idx_name {
+                                    unique.name = new_name.clone();
+                                    found = true;
+                                }
+                            }
                         }
                         if !found {
                             for partition in schema.partitions.values_mut() {
@@ -1652,6 +1745,36 @@ fn parse_create_server(stmt: CreateServerStatement, schema: &mut Schema) {
     schema.servers.insert(name, server);
 }
 
+/// Unquoted role names fold to lowercase like any identifier; keywords such
+/// as `CURRENT_USER` become their lowercase spelling.
+fn policy_role_name(owner: &Owner) -> String {
+    match owner {
+        Owner::Ident(ident) if ident.quote_style.is_some() => ident.value.clone(),
+        Owner::Ident(ident) => ident.value.to_lowercase(),
+        Owner::CurrentRole => "current_role".to_string(),
+        Owner::CurrentUser => "current_user".to_string(),
+        Owner::SessionUser => "session_user".to_string(),
+    }
+}
+
+/// An index column's name or expression, without the `COLLATE`, operator
+/// class and sort order it is declared with.
+fn parse_index_column(column: &IndexColumn) -> (String, IndexColumnOptions) {
+    let (expr, collation) = if let Expr::Collate { expr, collation } = &column.column.expr {
+        (expr.as_ref(), Some(collation))
+    } else {
+        (&column.column.expr, None)
+    };
+    let last_part = |name: &sqlparser::ast::ObjectName| extract_qualified_name(name).1;
+    let options = IndexColumnOptions::new(
+        collation.map(last_part),
+        column.operator_class.as_ref().map(last_part),
+        column.column.options.asc == Some(false),
+        column.column.options.nulls_first,
+    );
+    (unquote_ident(&expr.to_string()).to_string(), options)
+}
+
 fn make_trigger_key(schema: &str, table: &str, trigger_name: &str) -> String {
     format!("{}.{}.{}", schema, table, trigger_name)
 }
@@ -0,0 +1,37 @@
+//! `-- pgmold:set-schema <schema.name> <new_schema>` says an object the
+//! database holds as `schema.name` now lives in `new_schema`, so the plan moves
+//! it with `ALTER ... SET SCHEMA` instead of dropping and recreating it. A
+//! function name without an argument list covers every overload.
+
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+use crate::util::{Result, SchemaError};
+
+static SET_SCHEMA: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*--\s*pgmold:set-schema\b(.*)$").unwrap());
+
+/// Old qualified name to new schema, for every `pgmold:set-schema` line in
+/// `sql`.
+pub(super) fn extract_schema_moves(sql: &str) -> Result<BTreeMap<String, String>> {
+    let mut moves = BTreeMap::new();
+    for (index, line) in sql.lines().enumerate() {
+        let Some(captures) = SET_SCHEMA.captures(line) else {
+            continue;
+        };
+        let words: Vec<&str> = captures[1].split_whitespace().collect();
+        match words.as_slice() {
+            [old, new_schema] if old.contains('.') => {
+                moves.insert(old.to_string(), new_schema.to_string());
+            }
+            _ => {
+                return Err(SchemaError::ParseError(format!(
+                    "line {}: pgmold:set-schema needs `<schema.name> <new_schema>`",
+                    index + 1
+                )))
+            }
+        }
+    }
+    Ok(moves)
+}
@@ -1,101 +1,180 @@
 use regex::Regex;
+use std::sync::LazyLock;
+
+/// What `sql` holds from some offset on, as [`next_token`] reads it.
+enum Token {
+    /// A `--` comment, up to but not including its line break.
+    LineComment,
+    /// A `/* */` comment, nested ones included.
+    BlockComment,
+    /// A string, quoted identifier or dollar-quoted body, quotes included.
+    Quoted,
+    /// Anything else, up to the next character that may start one of the
+    /// others.
+    Code,
+}
 
-pub(super) fn strip_comments(sql: &str) -> String {
+/// The token starting at `index` and the offset where it ends. `E'...'`
+/// strings take backslash escapes, and a `$` only opens a dollar quote when
+/// it starts a valid tag, so `$1` parameters stay code.
+fn next_token(sql: &str, index: usize) -> (Token, usize) {
     let bytes = sql.as_bytes();
     let length = bytes.len();
-    let mut result = String::with_capacity(length);
-    let mut index = 0;
-
-    while index < length {
-        match bytes[index] {
-            b'\'' => {
-                let start = index;
-                index += 1;
-                while index < length {
-                    if bytes[index] == b'\'' {
-                        index += 1;
-                        if index < length && bytes[index] == b'\'' {
-                            index += 1;
-                        } else {
-                            break;
-                        }
-                    } else {
-                        index += 1;
-                    }
+    let mut end = index + 1;
+    match bytes[index] {
+        b'\'' => {
+            let escapes = index > 0
+                && matches!(bytes[index - 1], b'E' | b'e')
+                && (index < 2 || !is_identifier_byte(bytes[index - 2]));
+            while end < length {
+                match bytes[end] {
+                    b'\\' if escapes => end += 2,
+                    b'\'' if bytes.get(end + 1) == Some(&b'\'') => end += 2,
+                    b'\'' => return (Token::Quoted, end + 1),
+                    _ => end += 1,
                 }
-                result.push_str(&sql[start..index]);
             }
-            b'"' => {
-                let start = index;
-                index += 1;
-                while index < length && bytes[index] != b'"' {
-                    index += 1;
-                }
-                if index < length {
-                    index += 1;
-                }
-                result.push_str(&sql[start..index]);
+            (Token::Quoted, length)
+        }
+        b'"' => {
+            while end < length && bytes[end] != b'"' {
+                end += 1;
             }
-            b'$' => {
-                let tag_start = index;
-                index += 1;
-                while index < length
-                    && (bytes[index].is_ascii_alphanumeric() || bytes[index] == b'_')
-                {
-                    index += 1;
+            (Token::Quoted, (end + 1).min(length))
+        }
+        b'$' if bytes
+            .get(end)
+            .is_some_and(|&byte| byte == b'$' || byte == b'_' || byte.is_ascii_alphabetic()) =>
+        {
+            while end < length && (bytes[end].is_ascii_alphanumeric() || bytes[end] == b'_') {
+                end += 1;
+            }
+            if bytes.get(end) != Some(&b'$') {
+                return (Token::Code, end);
+            }
+            let tag = &sql[index..=end];
+            let body = end + 1;
+            let end = sql[body..]
+                .find(tag)
+                .map_or(length, |close| body + close + tag.len());
+            (Token::Quoted, end)
+        }
+        b'-' if bytes.get(end) == Some(&b'-') => {
+            let end = sql[index..].find('\n').map_or(length, |line| index + line);
+            (Token::LineComment, end)
+        }
+        b'/' if bytes.get(end) == Some(&b'*') => {
+            end += 1;
+            let mut depth: usize = 1;
+            while depth > 0 {
+                if end + 1 >= length {
+                    return (Token::BlockComment, length);
                 }
-                if index < length && bytes[index] == b'$' {
-                    index += 1;
-                    let tag = &sql[tag_start..index];
-                    if let Some(close_offset) = sql[index..].find(tag) {
-                        index += close_offset + tag.len();
-                    } else {
-                        index = length;
-                    }
+                if bytes[end] == b'/' && bytes[end + 1] == b'*' {
+                    depth += 1;
+                    end += 2;
+                } else if bytes[end] == b'*' && bytes[end + 1] == b'/' {
+                    depth -= 1;
+                    end += 2;
+                } else {
+                    end += 1;
                 }
-                result.push_str(&sql[tag_start..index]);
             }
-            b'-' if index + 1 < length && bytes[index + 1] == b'-' => {
-                while index < length && bytes[index] != b'\n' {
-                    index += 1;
-                }
-                if index < length {
-                    result.push('\n');
-                    index += 1;
+            (Token::BlockComment, end)
+        }
+        _ => {
+            while end < length && !matches!(bytes[end], b'\'' | b'"' | b'$' | b'-' | b'/') {
+                end += 1;
+            }
+            (Token::Code, end)
+        }
+    }
+}
+
+fn is_identifier_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'$' || !byte.is_ascii()
+}
+
+pub(super) fn strip_comments(sql: &str) -> String {
+    let mut result = String::with_capacity(sql.len());
+    let mut index = 0;
+
+    while index < sql.len() {
+        let (token, end) = next_token(sql, index);
+        match token {
+            Token::LineComment if end < sql.len() => {
+                result.push('\n');
+                index = end + 1;
+                continue;
+            }
+            Token::LineComment => {}
+            Token::BlockComment => result.push(' '),
+            Token::Quoted | Token::Code => result.push_str(&sql[index..end]),
+        }
+        index = end;
+    }
+
+    result
+}
+
+/// One top-level statement of a SQL file: its byte range, from its first
+/// token through its semicolon, and its text with comments blanked out.
+pub(crate) struct SqlStatement {
+    pub start: usize,
+    pub end: usize,
+    pub code: String,
+}
+
+/// Splits `sql` at the semicolons outside strings, quoted identifiers,
+/// dollar quotes and comments. A last statement without a semicolon ends at
+/// the end of the file's content.
+pub(crate) fn split_statements(sql: &str) -> Vec<SqlStatement> {
+    let mut statements = Vec::new();
+    let mut start = None;
+    let mut code = String::new();
+    let mut index = 0;
+
+    while index < sql.len() {
+        let (token, end) = next_token(sql, index);
+        match token {
+            Token::LineComment | Token::BlockComment => {
+                if start.is_some() {
+                    code.push(' ');
                 }
             }
-            b'/' if index + 1 < length && bytes[index + 1] == b'*' => {
-                index += 2;
-                let mut depth: usize = 1;
-                while depth > 0 {
-                    if index + 1 >= length {
-                        index = length;
-                        break;
+            Token::Quoted => {
+                start.get_or_insert(index);
+                code.push_str(&sql[index..end]);
+            }
+            Token::Code => {
+                for (offset, character) in sql[index..end].char_indices() {
+                    if character.is_whitespace() && start.is_none() {
+                        continue;
                     }
-                    if bytes[index] == b'/' && bytes[index + 1] == b'*' {
-                        depth += 1;
-                        index += 2;
-                    } else if bytes[index] == b'*' && bytes[index + 1] == b'/' {
-                        depth -= 1;
-                        index += 2;
-                    } else {
-                        index += 1;
+                    let position = index + offset;
+                    let first = *start.get_or_insert(position);
+                    code.push(character);
+                    if character == ';' {
+                        statements.push(SqlStatement {
+                            start: first,
+                            end: position + 1,
+                            code: std::mem::take(&mut code),
+                        });
+                        start = None;
                     }
                 }
-                result.push(' ');
-            }
-            _ => {
-                let start = index;
-                index += 1;
-                while index < length && !matches!(bytes[index], b'\'' | b'"' | b'$' | b'-' | b'/') {
-                    index += 1;
-                }
-                result.push_str(&sql[start..index]);
             }
         }
+        index = end;
     }
-
-    result
+    if let Some(start) = start {
+        statements.push(SqlStatement {
+            start,
+            end: sql.trim_end().len(),
+            code,
+        });
+    }
+    statements
 }
 
 fn strip_do_blocks(sql: &str) -> String {
@@ -286,6 +365,49 @@ fn restore_quoted_content(mut sql: String, replacements: &[(String, String)]) ->
     sql
 }
 
+static VIEW_OPTIONS_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?is)(CREATE\s+(?:OR\s+REPLACE\s+)?(?:TEMP(?:ORARY)?\s+)?(?:RECURSIVE\s+)?VIEW\s+[^\s(;]+(?:\s*\([^)]*\))?)(?:\s+WITH\s*\(([^)]*)\))?(\s+AS\s[^;]*?)(\s+WITH\s+(?:(CASCADED|LOCAL)\s+)?CHECK\s+OPTION)?\s*(?:;|$)",
+    )
+    .unwrap()
+});
+
+/// Rewrites `CREATE VIEW` options into the `WITH (key = value, ...)` form that
+/// sqlparser accepts: bare booleans such as `WITH (security_barrier)` gain
+/// `= true`, and a trailing `WITH [CASCADED | LOCAL] CHECK OPTION` becomes the
+/// equivalent `check_option` storage parameter. Runs on quote-protected SQL.
+fn normalize_view_options(sql: &str) -> String {
+    VIEW_OPTIONS_RE
+        .replace_all(sql, |caps: &regex::Captures| {
+            let mut options: Vec<String> = caps
+                .get(2)
+                .map(|m| m.as_str())
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|option| !option.is_empty())
+                .map(|option| {
+                    if option.contains('=') {
+                        option.to_string()
+                    } else {
+                        format!("{option} = true")
+                    }
+                })
+                .collect();
+            if caps.get(4).is_some() {
+                let level = caps.get(5).map_or("cascaded", |m| m.as_str());
+                options.push(format!("check_option = '{}'", level.to_lowercase()));
+            }
+            let with_clause = if options.is_empty() {
+                String::new()
+            } else {
+                format!(" WITH ({})", options.join(", "))
+            };
+            format!("{}{}{};", &caps[1], with_clause, &caps[3])
+        })
+        .into_owned()
+}
+
 /// Replaces complete `ALTER DEFAULT PRIVILEGES ... ;` statements with
 /// identifier-style placeholders so the GRANT/REVOKE strip patterns below
 /// don't shred the inline GRANT/REVOKE body. Restored alongside quoted
@@ -294,7 +416,6 @@ pub(super) fn protect_alter_default_privileges(
     sql: String,
     replacements: &mut Vec<(String, String)>,
 ) -> String {
-    use std::sync::LazyLock;
     static ADP_RE: LazyLock<Regex> =
         LazyLock::new(|| Regex::new(r"(?is)\bALTER\s+DEFAULT\s+PRIVILEGES\s+[^;]+;").unwrap());
 
@@ -321,6 +442,7 @@ pub(super) fn preprocess_sql(sql: &str) -> String {
     let sql = reorder_sequence_options(&sql);
 
     let (protected, mut replacements) = protect_quoted_content(&sql);
+    let protected = normalize_view_options(&protected);
     let protected = protect_alter_default_privileges(protected, &mut replacements);
 
     let strip_patterns = [
@@ -367,6 +489,13 @@ mod tests {
         assert_eq!(result, "SELECT '-- not a comment';");
     }
 
+    #[test]
+    fn strip_comments_preserves_escape_strings() {
+        let sql = r"SELECT E'it\'s -- fine', $1; -- gone";
+        let result = strip_comments(sql);
+        assert_eq!(result, r"SELECT E'it\'s -- fine', $1; ");
+    }
+
     #[test]
     fn strip_comments_preserves_escaped_quotes() {
         let sql = "SELECT 'it''s -- fine';";
@@ -0,0 +1,96 @@
+//! Raw object blocks: SQL pgmold does not model, declared between
+//! `-- pgmold:raw <name> [depends=<a>,<b>]` and `-- pgmold:end` and kept out
+//! of the regular parse.
+
+use crate::model::RawObject;
+use crate::util::{Result, SchemaError};
+use regex::Regex;
+use std::sync::LazyLock;
+
+static RAW_START: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*--\s*pgmold:raw(?:\s+(\S+))?(?:\s+depends=(\S+))?\s*$").unwrap()
+});
+static RAW_END: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*--\s*pgmold:end\s*$").unwrap());
+
+/// Splits `sql` into the SQL left for the regular parser and the raw objects
+/// it declares. Each block's lines are blanked rather than removed, so line
+/// numbers in later warnings still refer to the original file.
+pub(super) fn extract_raw_objects(sql: &str) -> Result<(String, Vec<RawObject>)> {
+    let mut remaining = Vec::new();
+    let mut objects = Vec::new();
+    let mut open: Option<(usize, String, Vec<String>, Vec<&str>)> = None;
+
+    for (index, line) in sql.lines().enumerate() {
+        if let Some((_, name, depends_on, body)) = &mut open {
+            if RAW_END.is_match(line) {
+                objects.push(RawObject::new(name, &body.join("\n"), depends_on.clone()));
+                open = None;
+            } else {
+                body.push(line);
+            }
+            remaining.push("");
+        } else if let Some(captures) = RAW_START.captures(line) {
+            let name = captures.get(1).map(|m| m.as_str()).ok_or_else(|| {
+                SchemaError::ParseError(format!("line {}: pgmold:raw needs a name", index + 1))
+            })?;
+            let depends_on = captures
+                .get(2)
+                .map(|m| {
+                    m.as_str()
+                        .split(',')
+                        .filter(|dependency| !dependency.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default();
+            open = Some((index + 1, name.to_string(), depends_on, Vec::new()));
+            remaining.push("");
+        } else if RAW_END.is_match(line) {
+            return Err(SchemaError::ParseError(format!(
+                "line {}: pgmold:end without a matching pgmold:raw",
+                index + 1
+            )));
+        } else {
+            remaining.push(line);
+        }
+    }
+
+    if let Some((line, name, ..)) = open {
+        return Err(SchemaError::ParseError(format!(
+            "line {line}: raw object \"{name}\" is missing its closing -- pgmold:end"
+        )));
+    }
+    Ok((remaining.join("\n"), objects))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_named_blocks_and_keeps_line_numbers() {
+        let sql = "CREATE TABLE documents (id BIGINT, body TEXT);
+-- pgmold:raw english_search depends=public.documents
+CREATE TEXT SEARCH CONFIGURATION english_search (COPY = english);
+-- pgmold:end
+CREATE TABLE tags (id BIGINT);";
+
+        let (remaining, objects) = extract_raw_objects(sql).unwrap();
+        assert_eq!(remaining.lines().count(), 5);
+        assert_eq!(
+            remaining.lines().nth(4),
+            Some("CREATE TABLE tags (id BIGINT);")
+        );
+        assert!(!remaining.contains("TEXT SEARCH"));
+        assert_eq!(objects.len(), 1);
+        assert_eq!(objects[0].name, "english_search");
+        assert_eq!(objects[0].depends_on, vec!["public.documents"]);
+        assert_eq!(
+            objects[0].sql,
+            "CREATE TEXT SEARCH CONFIGURATION english_search (COPY = english);"
+        );
+
+        let unclosed = extract_raw_objects("-- pgmold:raw broken\nSELECT 1;").unwrap_err();
+        assert!(unclosed.to_string().contains("missing its closing"));
+    }
+}
@@ -0,0 +1,37 @@
+//! `-- pgmold:rename <schema.table> <new_name>` says a table the database
+//! holds as `schema.table` is now called `new_name`, and
+//! `-- pgmold:rename <schema.table.column> <new_name>` says the same of a
+//! column, with the table under its name in the sources. The plan renames
+//! them with `ALTER TABLE ... RENAME` instead of dropping and recreating them.
+
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+use crate::util::{Result, SchemaError};
+
+static RENAME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*--\s*pgmold:rename\b(.*)$").unwrap());
+
+/// Old qualified name to new name, for every `pgmold:rename` line in `sql`.
+pub(super) fn extract_renames(sql: &str) -> Result<BTreeMap<String, String>> {
+    let mut renames = BTreeMap::new();
+    for (index, line) in sql.lines().enumerate() {
+        let Some(captures) = RENAME.captures(line) else {
+            continue;
+        };
+        let words: Vec<&str> = captures[1].split_whitespace().collect();
+        match words.as_slice() {
+            [old, new_name] if matches!(old.matches('.').count(), 1 | 2) => {
+                renames.insert(old.to_string(), new_name.to_string());
+            }
+            _ => {
+                return Err(SchemaError::ParseError(format!(
+                    "line {}: pgmold:rename needs `<schema.table> <new_name>` or `<schema.table.column> <new_name>`",
+                    index + 1
+                )))
+            }
+        }
+    }
+    Ok(renames)
+}
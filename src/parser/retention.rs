@@ -0,0 +1,114 @@
+//! `-- pgmold:retention=<interval>` on the line before a partitioned table's
+//! `CREATE TABLE` says how long its partitions are kept, as a PostgreSQL
+//! interval such as `90d` or `6 months`. Plans drop the range partitions
+//! whose upper bound is older than that.
+
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+use super::util::unquote_ident;
+use crate::model::{qualified_name, Schema};
+use crate::util::{Result, SchemaError};
+
+const IDENT: &str = r#"(?:"(?:[^"]|"")+"|[A-Za-z_][A-Za-z0-9_$]*)"#;
+
+static RETENTION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*--\s*pgmold:retention\s*=\s*(.*?)\s*$").unwrap());
+
+static CREATE_TABLE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(
+        r"(?i)^\s*CREATE\s+(?:UNLOGGED\s+)?TABLE\s+(?:IF\s+NOT\s+EXISTS\s+)?(?:({IDENT})\s*\.\s*)?({IDENT})"
+    ))
+    .unwrap()
+});
+
+/// Table key to retention interval, for every `pgmold:retention` line in
+/// `sql`.
+pub(super) fn extract_retention(sql: &str) -> Result<BTreeMap<String, String>> {
+    let mut retention = BTreeMap::new();
+    let mut pending: Option<(usize, String)> = None;
+    for (index, line) in sql.lines().enumerate() {
+        if let Some(captures) = RETENTION.captures(line) {
+            if captures[1].is_empty() {
+                return Err(SchemaError::ParseError(format!(
+                    "line {}: pgmold:retention needs an interval, such as pgmold:retention=90d",
+                    index + 1
+                )));
+            }
+            pending = Some((index, captures[1].to_string()));
+            continue;
+        }
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with("--") {
+            continue;
+        }
+        let Some((line_index, interval)) = pending.take() else {
+            continue;
+        };
+        let Some(captures) = CREATE_TABLE.captures(line) else {
+            return Err(SchemaError::ParseError(format!(
+                "line {}: pgmold:retention must come right before a CREATE TABLE",
+                line_index + 1
+            )));
+        };
+        let schema = captures
+            .get(1)
+            .map(|m| unquote_ident(m.as_str()))
+            .unwrap_or("public");
+        retention.insert(
+            qualified_name(schema, unquote_ident(&captures[2])),
+            interval,
+        );
+    }
+    if let Some((line_index, _)) = pending {
+        return Err(SchemaError::ParseError(format!(
+            "line {}: pgmold:retention must come right before a CREATE TABLE",
+            line_index + 1
+        )));
+    }
+    Ok(retention)
+}
+
+/// Fails for a retention on a table that is not partitioned.
+pub(super) fn check_retention(schema: &Schema) -> Result<()> {
+    for table in schema.partition_retention.keys() {
+        if schema
+            .tables
+            .get(table)
+            .is_some_and(|table| table.partition_by.is_none())
+        {
+            return Err(SchemaError::ParseError(format!(
+                "pgmold:retention on {table}, which is not partitioned"
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retention_applies_to_the_next_create_table() {
+        let retention = extract_retention(
+            "-- pgmold:retention=90d\n\
+             -- events older than 90 days are dropped\n\
+             CREATE TABLE audit.\"Events\" (at timestamptz) PARTITION BY RANGE (at);\n\
+             -- pgmold:retention = 6 months\n\
+             CREATE TABLE IF NOT EXISTS logs (at timestamptz) PARTITION BY RANGE (at);",
+        )
+        .unwrap();
+        assert_eq!(
+            retention,
+            BTreeMap::from([
+                ("audit.Events".to_string(), "90d".to_string()),
+                ("public.logs".to_string(), "6 months".to_string()),
+            ])
+        );
+
+        assert!(extract_retention("-- pgmold:retention=90d\nCREATE INDEX i ON t (a);").is_err());
+        assert!(extract_retention("-- pgmold:retention=\nCREATE TABLE t (a int);").is_err());
+    }
+}
@@ -5,9 +5,14 @@
 
 use crate::model::*;
 use crate::util::Result;
-use sqlparser::ast::{DataType, Expr, ObjectName, SequenceOptions, UnaryOperator, Value};
+use sqlparser::ast::{
+    DataType, Expr, FunctionArg, FunctionArgExpr, FunctionArguments, ObjectName, Query, SelectItem,
+    SequenceOptions, SetExpr, UnaryOperator, Value,
+};
+use sqlparser::dialect::PostgreSqlDialect;
+use sqlparser::parser::Parser;
 
-use super::util::unquote_ident;
+use super::util::{extract_qualified_name, unquote_ident};
 
 pub(super) fn parse_create_sequence(
     schema: &str,
@@ -120,6 +125,101 @@ pub(super) fn parse_create_sequence(
     })
 }
 
+/// The identity of a column declared `GENERATED ... AS IDENTITY (options)`.
+/// Only the options written out are kept; PostgreSQL picks the rest from the
+/// column type, and those are not compared.
+pub(super) fn parse_identity(
+    generation: IdentityGeneration,
+    sequence_options: &[SequenceOptions],
+) -> Identity {
+    let mut identity = Identity {
+        generation,
+        start: None,
+        increment: None,
+        min_value: None,
+        max_value: None,
+        cache: None,
+        cycle: None,
+    };
+    for option in sequence_options {
+        match option {
+            SequenceOptions::IncrementBy(expr, _) => {
+                identity.increment = extract_i64_from_expr(expr)
+            }
+            SequenceOptions::MinValue(expr) => {
+                identity.min_value = expr.as_ref().and_then(extract_i64_from_expr)
+            }
+            SequenceOptions::MaxValue(expr) => {
+                identity.max_value = expr.as_ref().and_then(extract_i64_from_expr)
+            }
+            SequenceOptions::StartWith(expr, _) => identity.start = extract_i64_from_expr(expr),
+            SequenceOptions::Cache(expr) => identity.cache = extract_i64_from_expr(expr),
+            SequenceOptions::Cycle(no_cycle) => identity.cycle = Some(!*no_cycle),
+        }
+    }
+    identity
+}
+
+/// The sequence and the value it has reached, for a standalone
+/// `SELECT setval('schema.seq', value[, is_called])` as `pg_dump` writes it.
+/// With `is_called` false the next `nextval` returns `value` itself, so the
+/// value reached is one less.
+pub(super) fn parse_setval(query: &Query) -> Option<(String, i64)> {
+    let SetExpr::Select(select) = query.body.as_ref() else {
+        return None;
+    };
+    let ([SelectItem::UnnamedExpr(Expr::Function(function))], []) =
+        (select.projection.as_slice(), select.from.as_slice())
+    else {
+        return None;
+    };
+    let function_name = function.name.to_string().to_lowercase();
+    if function_name != "setval" && function_name != "pg_catalog.setval" {
+        return None;
+    }
+    let FunctionArguments::List(list) = &function.args else {
+        return None;
+    };
+    let args: Vec<&Expr> = list
+        .args
+        .iter()
+        .map(|arg| match arg {
+            FunctionArg::Unnamed(FunctionArgExpr::Expr(expr)) => Some(expr),
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+    let (sequence, value, is_called) = match args.as_slice() {
+        [sequence, value] => (*sequence, *value, true),
+        [sequence, value, Expr::Value(is_called)] => match is_called.value {
+            Value::Boolean(is_called) => (*sequence, *value, is_called),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let sequence = match sequence {
+        Expr::Cast { expr, .. } => expr.as_ref(),
+        other => other,
+    };
+    let Expr::Value(literal) = sequence else {
+        return None;
+    };
+    let Value::SingleQuotedString(sequence) = &literal.value else {
+        return None;
+    };
+    let name = Parser::new(&PostgreSqlDialect {})
+        .try_with_sql(sequence)
+        .ok()?
+        .parse_object_name(false)
+        .ok()?;
+    if !(1..=2).contains(&name.0.len()) {
+        return None;
+    }
+    let (schema, name) = extract_qualified_name(&name);
+    let value = extract_i64_from_expr(value)?;
+    let value = if is_called { value } else { value - 1 };
+    Some((qualified_name(&schema, &name), value))
+}
+
 fn extract_i64_from_expr(expr: &Expr) -> Option<i64> {
     match expr {
         Expr::Value(value_with_span) => {
@@ -8,8 +8,9 @@
 use crate::model::*;
 use crate::util::Result;
 use sqlparser::ast::{
-    ColumnDef, ColumnOption, DataType, Expr, FunctionArg as SqlFunctionArg, FunctionArgExpr,
-    FunctionArguments, GeneratedAs, GeneratedExpressionMode, PrimaryKeyConstraint,
+    ColumnDef, ColumnOption, ConstraintCharacteristics, DataType, DeferrableInitial, Expr,
+    FunctionArg as SqlFunctionArg, FunctionArgExpr, FunctionArguments, GeneratedAs,
+    GeneratedExpressionMode, NullsDistinctOption, PrimaryKeyConstraint,
     ReferentialAction as SqlReferentialAction, TableConstraint, Value,
 };
 use std::collections::BTreeMap;
@@ -38,10 +39,12 @@ pub(super) fn parse_create_table(
         primary_key: None,
         foreign_keys: Vec::new(),
         check_constraints: Vec::new(),
+        unique_constraints: Vec::new(),
         exclusion_constraints: Vec::new(),
         comment: None,
         row_level_security: false,
         force_row_level_security: false,
+        replica_identity: None,
         policies: Vec::new(),
         partition_by: partition_by.and_then(parse_partition_by),
         owner: None,
@@ -74,20 +77,24 @@ pub(super) fn parse_create_table(
                         col.nullable = false;
                     }
                 }
-                ColumnOption::Unique(_) => {
+                ColumnOption::Unique(unique) => {
+                    let (deferrable, initially_deferred) =
+                        constraint_deferral(unique.characteristics.as_ref());
                     let constraint_name = explicit_name
                         .clone()
                         .unwrap_or_else(|| format!("{}_{}_key", table.name, col_name));
-                    table.indexes.push(Index {
+                    table.unique_constraints.push(UniqueConstraint {
                         name: truncate_identifier(&constraint_name),
                         columns: vec![col_name.clone()],
-                        unique: true,
-                        index_type: IndexType::BTree,
-                        predicate: None,
-                        is_constraint: true,
+                        nulls_not_distinct: unique.nulls_distinct
+                            == NullsDistinctOption::NotDistinct,
+                        deferrable,
+                        initially_deferred,
                     });
                 }
                 ColumnOption::ForeignKey(fk) => {
+                    let (deferrable, initially_deferred) =
+                        constraint_deferral(fk.characteristics.as_ref());
                     let constraint_name = explicit_name
                         .clone()
                         .unwrap_or_else(|| format!("{}_{}_fkey", table.name, col_name));
@@ -117,6 +124,8 @@ pub(super) fn parse_create_table(
                         referenced_columns,
                         on_delete: parse_referential_action(&fk.on_delete),
                         on_update: parse_referential_action(&fk.on_update),
+                        deferrable,
+                        initially_deferred,
                     });
                 }
                 ColumnOption::Check(chk) => {
@@ -179,6 +188,8 @@ pub(super) fn parse_create_table(
                     });
 
                 let (ref_schema, ref_table) = extract_qualified_name(&fk.foreign_table);
+                let (deferrable, initially_deferred) =
+                    constraint_deferral(fk.characteristics.as_ref());
                 table.foreign_keys.push(ForeignKey {
                     name: truncate_identifier(&fk_name),
                     columns: fk_columns,
@@ -191,6 +202,8 @@ pub(super) fn parse_create_table(
                         .collect(),
                     on_delete: parse_referential_action(&fk.on_delete),
                     on_update: parse_referential_action(&fk.on_update),
+                    deferrable,
+                    initially_deferred,
                 });
             }
             TableConstraint::Check(chk) => {
@@ -232,13 +245,14 @@ pub(super) fn parse_create_table(
                         format!("{}_{}_key", table.name, uniq_columns.join("_"))
                     });
 
-                table.indexes.push(Index {
+                let (deferrable, initially_deferred) =
+                    constraint_deferral(uniq.characteristics.as_ref());
+                table.unique_constraints.push(UniqueConstraint {
                     name: truncate_identifier(&constraint_name),
                     columns: uniq_columns,
-                    unique: true,
-                    index_type: IndexType::BTree,
-                    predicate: None,
-                    is_constraint: true,
+                    nulls_not_distinct: uniq.nulls_distinct == NullsDistinctOption::NotDistinct,
+                    deferrable,
+                    initially_deferred,
                 });
             }
             TableConstraint::Exclusion(exc) => {
@@ -268,16 +282,8 @@ pub(super) fn parse_create_table(
                     .as_ref()
                     .map(|w| normalize_expr(&w.to_string()));
 
-                let deferrable = exc
-                    .characteristics
-                    .as_ref()
-                    .and_then(|c| c.deferrable)
-                    .unwrap_or(false);
-                let initially_deferred = exc
-                    .characteristics
-                    .as_ref()
-                    .and_then(|c| c.initially)
-                    .is_some_and(|i| matches!(i, sqlparser::ast::DeferrableInitial::Deferred));
+                let (deferrable, initially_deferred) =
+                    constraint_deferral(exc.characteristics.as_ref());
 
                 table
                     .exclusion_constraints
@@ -302,6 +308,7 @@ pub(super) fn parse_create_table(
 
     table.foreign_keys.sort();
     table.check_constraints.sort();
+    table.unique_constraints.sort();
     table.exclusion_constraints.sort();
     table.indexes.sort();
 
@@ -316,6 +323,7 @@ pub(super) fn parse_column_with_serial(
     let mut nullable = true;
     let mut default = None;
     let mut generated = None;
+    let mut identity = None;
 
     for option in &col_def.options {
         match &option.option {
@@ -347,6 +355,21 @@ pub(super) fn parse_column_with_serial(
                 }
                 generated = Some(normalize_expr(&expr.to_string()));
             }
+            ColumnOption::Generated {
+                generated_as: generated_as @ (GeneratedAs::Always | GeneratedAs::ByDefault),
+                generation_expr: None,
+                sequence_options,
+                ..
+            } => {
+                let generation = match generated_as {
+                    GeneratedAs::ByDefault => IdentityGeneration::ByDefault,
+                    GeneratedAs::Always | GeneratedAs::ExpStored => IdentityGeneration::Always,
+                };
+                identity = Some(super::sequences::parse_identity(
+                    generation,
+                    sequence_options.as_deref().unwrap_or_default(),
+                ));
+            }
             ColumnOption::PrimaryKey(_)
             | ColumnOption::Unique(_)
             | ColumnOption::ForeignKey(_)
@@ -372,6 +395,26 @@ pub(super) fn parse_column_with_serial(
 
     let col_name = unquote_ident(&col_def.name.to_string()).to_string();
 
+    if identity.is_some() {
+        if default.is_some() || detect_serial_type(&col_def.data_type).is_some() {
+            return Err(crate::util::SchemaError::ParseError(format!(
+                "Column \"{col_name}\": an identity column cannot also have a default"
+            )));
+        }
+        let column = Column {
+            name: col_name,
+            data_type: parse_data_type(&col_def.data_type)?,
+            nullable: false,
+            default: None,
+            comment: None,
+            generated: None,
+            statistics: None,
+            compression: None,
+            identity,
+        };
+        return Ok((column, None));
+    }
+
     if generated.is_some() {
         let column = Column {
             name: col_name,
@@ -380,6 +423,9 @@ pub(super) fn parse_column_with_serial(
             default: None,
             comment: None,
             generated,
+            statistics: None,
+            compression: None,
+            identity: None,
         };
         return Ok((column, None));
     }
@@ -413,6 +459,9 @@ pub(super) fn parse_column_with_serial(
             default: Some(format!("nextval('{nextval_ref}'::regclass)")),
             comment: None,
             generated: None,
+            statistics: None,
+            compression: None,
+            identity: None,
         };
 
         let sequence = Sequence {
@@ -444,6 +493,9 @@ pub(super) fn parse_column_with_serial(
             default,
             comment: None,
             generated: None,
+            statistics: None,
+            compression: None,
+            identity: None,
         };
         Ok((column, None))
     }
@@ -495,6 +547,18 @@ pub(super) fn parse_referential_action(action: &Option<SqlReferentialAction>) ->
     }
 }
 
+/// `(deferrable, initially_deferred)` from a constraint's `DEFERRABLE` and
+/// `INITIALLY` clauses; constraints without them are checked immediately.
+pub(super) fn constraint_deferral(
+    characteristics: Option<&ConstraintCharacteristics>,
+) -> (bool, bool) {
+    let deferrable = characteristics.and_then(|c| c.deferrable).unwrap_or(false);
+    let initially_deferred = characteristics
+        .and_then(|c| c.initially)
+        .is_some_and(|i| matches!(i, DeferrableInitial::Deferred));
+    (deferrable, initially_deferred)
+}
+
 /// Collect the set of known table columns referenced by a CHECK expression, in the order
 /// they first appear. Used to pick the Postgres-compatible default name for an unnamed
 /// CHECK constraint: `{table}_{column}_check` when exactly one known column is referenced,
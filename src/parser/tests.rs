@@ -81,6 +81,61 @@ SELECT id, email FROM users WHERE active = true;
     assert!(view.query.contains("SELECT"));
 }
 
+#[test]
+fn parse_view_security_options() {
+    let sql = r#"
+CREATE TABLE docs (id BIGINT PRIMARY KEY, owner_id BIGINT NOT NULL);
+
+CREATE VIEW barrier_docs WITH (security_barrier) AS SELECT id FROM docs;
+CREATE VIEW invoker_docs WITH (security_invoker = true, security_barrier = false) AS
+SELECT id FROM docs;
+CREATE VIEW plain_docs AS WITH d AS (SELECT id FROM docs) SELECT id FROM d;
+"#;
+
+    let schema = parse_sql_string(sql).expect("Should parse");
+
+    let barrier = &schema.views["public.barrier_docs"];
+    assert!(barrier.security_barrier);
+    assert!(!barrier.security_invoker);
+
+    let invoker = &schema.views["public.invoker_docs"];
+    assert!(!invoker.security_barrier);
+    assert!(invoker.security_invoker);
+
+    let plain = &schema.views["public.plain_docs"];
+    assert!(!plain.security_barrier);
+    assert!(!plain.security_invoker);
+}
+
+#[test]
+fn parse_view_check_option() {
+    let sql = r#"
+CREATE TABLE orders (id BIGINT PRIMARY KEY, status TEXT NOT NULL);
+
+CREATE VIEW open_orders AS SELECT id, status FROM orders WHERE status = 'open; pending'
+WITH LOCAL CHECK OPTION;
+CREATE VIEW barrier_orders WITH (security_barrier) AS SELECT id FROM orders WITH CHECK OPTION;
+CREATE VIEW all_orders AS SELECT id FROM orders WITH CASCADED CHECK OPTION;
+CREATE VIEW plain_orders AS SELECT id FROM orders;
+"#;
+
+    let schema = parse_sql_string(sql).expect("Should parse");
+
+    let open = &schema.views["public.open_orders"];
+    assert_eq!(open.check_option, Some(ViewCheckOption::Local));
+    assert!(open.query.contains("'open; pending'"));
+
+    let barrier = &schema.views["public.barrier_orders"];
+    assert!(barrier.security_barrier);
+    assert_eq!(barrier.check_option, Some(ViewCheckOption::Cascaded));
+
+    assert_eq!(
+        schema.views["public.all_orders"].check_option,
+        Some(ViewCheckOption::Cascaded)
+    );
+    assert_eq!(schema.views["public.plain_orders"].check_option, None);
+}
+
 #[test]
 fn parse_materialized_view() {
     let sql = r#"
@@ -795,6 +850,25 @@ fn parse_create_sequence_descending_defaults() {
     assert_eq!(seq.start, Some(-1));
 }
 
+#[test]
+fn parses_setval_as_sequence_value() {
+    let sql = r#"
+        CREATE SEQUENCE public.orders_id_seq;
+        CREATE SEQUENCE billing."Invoice_seq";
+        SELECT pg_catalog.setval('public.orders_id_seq', 1042, true);
+        SELECT setval('billing."Invoice_seq"'::regclass, 10, false);
+        SELECT 1;
+    "#;
+    let schema = parse_sql_string(sql).unwrap();
+    assert_eq!(
+        schema.sequence_values,
+        BTreeMap::from([
+            ("billing.Invoice_seq".to_string(), 9),
+            ("public.orders_id_seq".to_string(), 1042),
+        ])
+    );
+}
+
 #[test]
 fn parse_sequence_postgresql_order() {
     // PostgreSQL order: INCREMENT BY before START WITH
@@ -1635,6 +1709,9 @@ fn domain_round_trip_with_table_using_domain() {
             default: None,
             comment: None,
             generated: None,
+            statistics: None,
+            compression: None,
+            identity: None,
         },
     );
     users_columns.insert(
@@ -1646,6 +1723,9 @@ fn domain_round_trip_with_table_using_domain() {
             default: None,
             comment: None,
             generated: None,
+            statistics: None,
+            compression: None,
+            identity: None,
         },
     );
 
@@ -1661,10 +1741,12 @@ fn domain_round_trip_with_table_using_domain() {
             indexes: Vec::new(),
             foreign_keys: Vec::new(),
             check_constraints: Vec::new(),
+            unique_constraints: Vec::new(),
             exclusion_constraints: Vec::new(),
             comment: None,
             row_level_security: false,
             force_row_level_security: false,
+            replica_identity: None,
             policies: Vec::new(),
             partition_by: None,
 
@@ -1702,6 +1784,28 @@ fn parses_policy_with_quoted_role_names() {
     );
 }
 
+#[test]
+fn normalizes_policy_role_keywords_case_and_order() {
+    let sql = r#"
+        CREATE TABLE users (id BIGINT PRIMARY KEY);
+        CREATE POLICY everyone ON users FOR SELECT TO PUBLIC USING (true);
+        CREATE POLICY staff ON users FOR ALL TO Support, CURRENT_USER, "Ops" USING (true);
+    "#;
+    let schema = parse_sql_string(sql).unwrap();
+    let policies = &schema.tables["public.users"].policies;
+    let roles = |name: &str| {
+        policies
+            .iter()
+            .find(|policy| policy.name == name)
+            .unwrap()
+            .roles
+            .clone()
+    };
+
+    assert_eq!(roles("everyone"), vec!["public"]);
+    assert_eq!(roles("staff"), vec!["Ops", "current_user", "support"]);
+}
+
 #[test]
 fn parses_policy_before_table_in_same_file() {
     // Bug fix: policies should work regardless of statement order
@@ -2820,7 +2924,7 @@ fn comment_on_function_attaches_when_args_mix_in_and_out_modes() {
     let schema = parse_sql_string(sql).unwrap();
     let func = schema
         .functions
-        .get("public.upsert_out(integer, text)")
+        .get("public.upsert_out(integer)")
         .expect("function should be stored under canonical signature");
     assert_eq!(func.comment.as_deref(), Some("Upsert with OUT"));
 }
@@ -2839,6 +2943,33 @@ fn comment_on_function_attaches_when_arg_uses_variadic() {
     assert_eq!(func.comment.as_deref(), Some("Join variadic"));
 }
 
+#[test]
+fn function_signature_matches_identity_arguments() {
+    let sql = r#"
+        CREATE FUNCTION concat_all(sep text, VARIADIC parts text[]) RETURNS text
+            LANGUAGE sql AS $$ SELECT array_to_string(parts, sep) $$;
+        CREATE FUNCTION min_max(IN vals integer[], OUT lo integer, OUT hi integer)
+            LANGUAGE sql AS $$ SELECT min(v), max(v) FROM unnest(vals) v $$;
+        CREATE FUNCTION doubled(INOUT n integer)
+            LANGUAGE sql AS $$ SELECT n * 2 $$;
+        GRANT EXECUTE ON FUNCTION min_max(integer[]) TO app;
+    "#;
+    let schema = parse_sql_string(sql).unwrap();
+
+    let concat_all = &schema.functions["public.concat_all(text, text[])"];
+    assert_eq!(concat_all.arguments[1].mode, ArgMode::Variadic);
+
+    let min_max = &schema.functions["public.min_max(integer[])"];
+    assert_eq!(min_max.return_type, "record");
+    assert_eq!(min_max.args_string(), "integer[]");
+    assert_eq!(min_max.grants.len(), 1);
+
+    assert_eq!(
+        schema.functions["public.doubled(integer)"].return_type,
+        "integer"
+    );
+}
+
 #[test]
 fn comment_on_aggregate_attaches_when_arg_uses_int_alias() {
     let sql = r#"
@@ -3113,15 +3244,14 @@ fn parses_unique_constraint_in_create_table() {
     let schema = parse_sql_string(sql).unwrap();
     let table = schema.tables.get("public.users").unwrap();
 
-    let unique_idx = table
-        .indexes
+    let unique = table
+        .unique_constraints
         .iter()
-        .find(|idx| idx.name == "users_email_unique")
-        .expect("UNIQUE constraint should be parsed as an index");
+        .find(|unique| unique.name == "users_email_unique")
+        .expect("UNIQUE constraint should be parsed");
 
-    assert!(unique_idx.unique, "Index should be marked as unique");
-    assert!(unique_idx.is_constraint);
-    assert_eq!(unique_idx.columns, vec!["email"]);
+    assert_eq!(unique.columns, vec!["email"]);
+    assert!(table.indexes.is_empty());
 }
 
 #[test]
@@ -3139,22 +3269,61 @@ fn parses_unique_constraint_from_alter_table() {
     let schema = parse_sql_string(sql).unwrap();
     let table = schema.tables.get("auth.mfa_amr_claims").unwrap();
 
-    let unique_idx = table
-        .indexes
+    let unique = table
+        .unique_constraints
         .iter()
-        .find(|idx| idx.name == "mfa_amr_claims_session_id_authentication_method_pkey")
-        .expect("UNIQUE constraint from ALTER TABLE should be parsed as an index");
+        .find(|unique| unique.name == "mfa_amr_claims_session_id_authentication_method_pkey")
+        .expect("UNIQUE constraint from ALTER TABLE should be parsed");
+
+    assert_eq!(unique.columns, vec!["session_id", "authentication_method"]);
+}
+
+#[test]
+fn unnamed_unique_constraint_from_alter_table_is_named_like_postgres() {
+    let sql = r#"
+        CREATE TABLE memberships (org_id BIGINT, user_id BIGINT);
+        ALTER TABLE memberships ADD UNIQUE (org_id, user_id);
+    "#;
+    let schema = parse_sql_string(sql).unwrap();
+    let table = schema.tables.get("public.memberships").unwrap();
 
-    assert!(unique_idx.unique, "Index should be marked as unique");
-    assert!(unique_idx.is_constraint);
     assert_eq!(
-        unique_idx.columns,
-        vec!["session_id", "authentication_method"]
+        table.unique_constraints[0].name,
+        "memberships_org_id_user_id_key"
     );
 }
 
 #[test]
-fn unique_index_is_not_marked_as_constraint() {
+fn parses_nulls_not_distinct_on_unique_constraints_and_indexes() {
+    let sql = r#"
+        CREATE TABLE users (
+            id BIGINT PRIMARY KEY,
+            tenant_id BIGINT,
+            handle TEXT,
+            phone TEXT,
+            CONSTRAINT users_tenant_handle_key UNIQUE NULLS NOT DISTINCT (tenant_id, handle)
+        );
+        CREATE UNIQUE INDEX users_phone_idx ON users (phone) NULLS NOT DISTINCT;
+        CREATE UNIQUE INDEX users_handle_idx ON users (handle);
+    "#;
+    let schema = parse_sql_string(sql).unwrap();
+    let table = schema.tables.get("public.users").unwrap();
+    let nulls_not_distinct = |name: &str| {
+        table
+            .indexes
+            .iter()
+            .find(|idx| idx.name == name)
+            .unwrap_or_else(|| panic!("{name} should be parsed"))
+            .nulls_not_distinct
+    };
+
+    assert!(table.unique_constraints[0].nulls_not_distinct);
+    assert!(nulls_not_distinct("users_phone_idx"));
+    assert!(!nulls_not_distinct("users_handle_idx"));
+}
+
+#[test]
+fn unique_index_is_not_parsed_as_constraint() {
     let sql = r#"
         CREATE TABLE users (
             id BIGINT PRIMARY KEY,
@@ -3172,7 +3341,7 @@ fn unique_index_is_not_marked_as_constraint() {
         .expect("unique index should be parsed");
 
     assert!(index.unique);
-    assert!(!index.is_constraint);
+    assert!(table.unique_constraints.is_empty());
 }
 
 #[test]
@@ -3195,7 +3364,7 @@ fn partial_unique_index_preserves_where_clause() {
         .expect("partial unique index should be parsed");
 
     assert!(index.unique);
-    assert!(!index.is_constraint);
+    assert!(table.unique_constraints.is_empty());
     assert!(index.predicate.is_some(), "WHERE clause must be preserved");
     assert!(
         index
@@ -4256,6 +4425,74 @@ fn hash_index_method_is_preserved() {
     assert_eq!(index.index_type, IndexType::Hash);
 }
 
+#[test]
+fn brin_and_spgist_index_methods_are_preserved() {
+    let sql = r#"
+        CREATE TABLE public.events (
+            id BIGINT PRIMARY KEY,
+            created_at TIMESTAMPTZ,
+            location POINT
+        );
+        CREATE INDEX events_created_at_idx ON public.events USING BRIN (created_at);
+        CREATE INDEX events_location_idx ON public.events USING SPGIST (location);
+    "#;
+    let schema = parse_sql_string(sql).unwrap();
+    let methods: Vec<IndexType> = schema.tables["public.events"]
+        .indexes
+        .iter()
+        .map(|i| i.index_type.clone())
+        .collect();
+    assert_eq!(methods, vec![IndexType::Brin, IndexType::SpGist]);
+}
+
+#[test]
+fn unsupported_index_method_is_a_parse_error() {
+    let sql = r#"
+        CREATE TABLE public.events (id BIGINT PRIMARY KEY, payload TEXT);
+        CREATE INDEX events_payload_idx ON public.events USING bloom (payload);
+    "#;
+    let err = parse_sql_string(sql).unwrap_err();
+    assert!(
+        err.to_string().contains("unsupported index method"),
+        "{err}"
+    );
+}
+
+#[test]
+fn index_column_collation_opclass_and_order_are_preserved() {
+    let sql = r#"
+        CREATE TABLE public.products (id BIGINT PRIMARY KEY, sku TEXT, name TEXT);
+        CREATE INDEX products_idx ON public.products
+            (sku public.text_pattern_ops, name COLLATE "C" DESC NULLS LAST, id NULLS LAST);
+        CREATE INDEX products_id_idx ON public.products (id ASC NULLS LAST);
+    "#;
+    let schema = parse_sql_string(sql).unwrap();
+    let indexes = &schema.tables["public.products"].indexes;
+    let index = indexes.iter().find(|i| i.name == "products_idx").unwrap();
+    assert_eq!(index.columns, vec!["sku", "name", "id"]);
+    assert_eq!(
+        index.column_options,
+        vec![
+            IndexColumnOptions {
+                opclass: Some("text_pattern_ops".to_string()),
+                ..Default::default()
+            },
+            IndexColumnOptions {
+                collation: Some("C".to_string()),
+                descending: true,
+                nulls_first: Some(false),
+                ..Default::default()
+            },
+            IndexColumnOptions::default(),
+        ]
+    );
+    let plain = indexes
+        .iter()
+        .find(|i| i.name == "products_id_idx")
+        .unwrap();
+    assert!(plain.column_options.is_empty());
+}
+
 #[test]
 fn btree_index_method_defaults_when_no_using_clause() {
     let sql = r#"
@@ -4286,17 +4523,14 @@ fn parses_inline_column_unique_constraint() {
     let schema = parse_sql_string(sql).unwrap();
     let table = schema.tables.get("public.users").unwrap();
 
-    let unique_idx = table
-        .indexes
+    let unique = table
+        .unique_constraints
         .iter()
-        .find(|idx| idx.columns == vec!["email".to_string()])
-        .expect("inline UNIQUE should produce a unique index on email");
+        .find(|unique| unique.columns == vec!["email".to_string()])
+        .expect("inline UNIQUE should produce a unique constraint on email");
 
-    assert!(unique_idx.unique);
-    assert!(unique_idx.is_constraint);
-    assert_eq!(unique_idx.name, "users_email_key");
-    assert_eq!(unique_idx.index_type, IndexType::BTree);
-    assert!(unique_idx.predicate.is_none());
+    assert_eq!(unique.name, "users_email_key");
+    assert!(table.indexes.is_empty());
 }
 
 #[test]
@@ -4310,15 +4544,13 @@ fn parses_inline_column_named_unique_constraint() {
     let schema = parse_sql_string(sql).unwrap();
     let table = schema.tables.get("public.users").unwrap();
 
-    let unique_idx = table
-        .indexes
+    let unique = table
+        .unique_constraints
         .iter()
-        .find(|idx| idx.name == "users_email_uniq")
+        .find(|unique| unique.name == "users_email_uniq")
         .expect("named inline UNIQUE should use the provided constraint name");
 
-    assert!(unique_idx.unique);
-    assert!(unique_idx.is_constraint);
-    assert_eq!(unique_idx.columns, vec!["email".to_string()]);
+    assert_eq!(unique.columns, vec!["email".to_string()]);
 }
 
 #[test]
@@ -4346,7 +4578,10 @@ fn inline_unique_matches_out_of_line_unique() {
     let inline_table = inline.tables.get("public.users").unwrap();
     let ool_table = out_of_line.tables.get("public.users").unwrap();
 
-    assert_eq!(inline_table.indexes, ool_table.indexes);
+    assert_eq!(
+        inline_table.unique_constraints,
+        ool_table.unique_constraints
+    );
 }
 
 #[test]
@@ -4374,6 +4609,48 @@ fn parses_inline_column_references_foreign_key() {
     assert_eq!(fk.on_update, ReferentialAction::NoAction);
 }
 
+#[test]
+fn parses_deferrable_foreign_keys_and_unique_constraints() {
+    let sql = r#"
+        CREATE TABLE users (
+            id integer PRIMARY KEY,
+            email text UNIQUE DEFERRABLE
+        );
+        CREATE TABLE posts (
+            id integer PRIMARY KEY,
+            author_id integer REFERENCES users(id) DEFERRABLE INITIALLY DEFERRED,
+            editor_id integer,
+            slug text,
+            CONSTRAINT posts_slug_key UNIQUE (slug) DEFERRABLE INITIALLY DEFERRED
+        );
+        ALTER TABLE posts ADD CONSTRAINT posts_editor_id_fkey
+            FOREIGN KEY (editor_id) REFERENCES users(id) DEFERRABLE INITIALLY IMMEDIATE;
+    "#;
+    let schema = parse_sql_string(sql).unwrap();
+
+    let users = schema.tables.get("public.users").unwrap();
+    assert!(users.unique_constraints[0].deferrable);
+    assert!(!users.unique_constraints[0].initially_deferred);
+
+    let posts = schema.tables.get("public.posts").unwrap();
+    let deferral = |name: &str| {
+        let fk = posts
+            .foreign_keys
+            .iter()
+            .find(|fk| fk.name == name)
+            .unwrap();
+        (fk.deferrable, fk.initially_deferred)
+    };
+    assert_eq!(deferral("posts_author_id_fkey"), (true, true));
+    assert_eq!(deferral("posts_editor_id_fkey"), (true, false));
+    let slug = posts
+        .unique_constraints
+        .iter()
+        .find(|unique| unique.name == "posts_slug_key")
+        .unwrap();
+    assert!(slug.deferrable && slug.initially_deferred);
+}
+
 #[test]
 fn parses_inline_column_references_with_on_delete_cascade() {
     let sql = r#"
@@ -4622,12 +4899,12 @@ fn out_of_line_unnamed_unique_multi_column_name_matches_postgres() {
     let schema = parse_sql_string(sql).unwrap();
     let sessions = schema.tables.get("public.sessions").unwrap();
 
-    let idx = sessions
-        .indexes
+    let unique = sessions
+        .unique_constraints
         .iter()
-        .find(|i| i.columns == vec!["a".to_string(), "b".to_string()])
-        .expect("UNIQUE (a, b) index should exist");
-    assert_eq!(idx.name, "sessions_a_b_key");
+        .find(|u| u.columns == vec!["a".to_string(), "b".to_string()])
+        .expect("UNIQUE (a, b) constraint should exist");
+    assert_eq!(unique.name, "sessions_a_b_key");
 }
 
 #[test]
@@ -5172,3 +5449,71 @@ ALTER AGGREGATE public.group_concat(text) OWNER TO postgres;
 
     assert_eq!(agg.owner.as_deref(), Some("postgres"));
 }
+
+#[test]
+fn trigger_execute_procedure_and_argument_literals() {
+    let sql = r#"
+CREATE TABLE public.docs (id integer, updated_at timestamptz);
+CREATE FUNCTION public.set_updated_at() RETURNS trigger LANGUAGE plpgsql AS $$ BEGIN RETURN NEW; END $$;
+CREATE TRIGGER docs_touch BEFORE UPDATE ON public.docs
+    FOR EACH ROW EXECUTE PROCEDURE public.set_updated_at(updated_at, 'it''s', 42, E'a\'b', $$x$$, "Mixed");
+"#;
+    let schema = parse_sql_string(sql).unwrap();
+    let trigger = schema.triggers.get("public.docs.docs_touch").unwrap();
+    assert_eq!(trigger.function_name, "set_updated_at");
+    assert_eq!(
+        trigger.function_args,
+        vec![
+            "updated_at",
+            "'it''s'",
+            "42",
+            "E'a\\'b'",
+            "$$x$$",
+            "\"Mixed\""
+        ]
+    );
+    assert_eq!(
+        trigger.argument_values(),
+        vec!["updated_at", "it's", "42", "a'b", "x", "Mixed"]
+    );
+}
+
+#[test]
+fn identity_columns_keep_generation_and_declared_options() {
+    let sql = r#"
+CREATE TABLE public.orders (
+    id bigint GENERATED ALWAYS AS IDENTITY (INCREMENT BY 10 START WITH 100 NO CYCLE),
+    ref integer GENERATED BY DEFAULT AS IDENTITY,
+    total numeric
+);
+"#;
+    let schema = parse_sql_string(sql).unwrap();
+    let table = schema.tables.get("public.orders").unwrap();
+
+    let id = &table.columns["id"];
+    assert!(!id.nullable);
+    assert_eq!(id.default, None);
+    assert_eq!(
+        id.identity,
+        Some(Identity {
+            generation: IdentityGeneration::Always,
+            start: Some(100),
+            increment: Some(10),
+            min_value: None,
+            max_value: None,
+            cache: None,
+            cycle: Some(false),
+        })
+    );
+    assert_eq!(
+        table.columns["ref"].identity.as_ref().map(|i| i.generation),
+        Some(IdentityGeneration::ByDefault)
+    );
+    assert_eq!(table.columns["total"].identity, None);
+    assert!(schema.sequences.is_empty());
+
+    let err =
+        parse_sql_string("CREATE TABLE t (id integer DEFAULT 1 GENERATED ALWAYS AS IDENTITY);")
+            .unwrap_err();
+    assert!(err.to_string().contains("cannot also have a default"));
+}
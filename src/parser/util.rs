@@ -6,14 +6,14 @@
 use crate::model::*;
 use crate::util::{normalize_type_casts, Result, SchemaError};
 use sqlparser::ast::{
-    ArrayElemTypeDef, CharacterLength, CreatePolicyCommand, DataType, ForValues, ObjectName,
-    PartitionBoundValue, TimezoneInfo,
+    ArrayElemTypeDef, CharacterLength, CreatePolicyCommand, CreateTableOptions, DataType, Expr,
+    ForValues, ObjectName, PartitionBoundValue, SqlOption, TimezoneInfo, Value,
 };
 
 /// PostgreSQL's NAMEDATALEN is 64, so identifiers are truncated to 63 bytes.
 const PG_MAX_IDENTIFIER_LENGTH: usize = 63;
 
-pub(super) fn truncate_identifier(s: &str) -> String {
+pub(crate) fn truncate_identifier(s: &str) -> String {
     if s.len() <= PG_MAX_IDENTIFIER_LENGTH {
         s.to_string()
     } else {
@@ -42,6 +42,35 @@ pub(super) fn extract_qualified_name(name: &ObjectName) -> (String, String) {
     }
 }
 
+/// Value of a `CREATE VIEW ... WITH (key = value)` option, if present. Bare
+/// options are rewritten to `= true` by the preprocessor, so only key/value
+/// pairs need handling here.
+pub(super) fn view_option_value(options: &CreateTableOptions, key: &str) -> Option<String> {
+    let CreateTableOptions::With(options) = options else {
+        return None;
+    };
+    options.iter().find_map(|option| match option {
+        SqlOption::KeyValue { key: name, value } if name.value.eq_ignore_ascii_case(key) => {
+            match value {
+                Expr::Value(v) => match &v.value {
+                    Value::Boolean(enabled) => Some(enabled.to_string()),
+                    Value::SingleQuotedString(s) | Value::Number(s, _) => Some(s.clone()),
+                    _ => None,
+                },
+                Expr::Identifier(ident) => Some(ident.value.clone()),
+                _ => None,
+            }
+        }
+        _ => None,
+    })
+}
+
+/// Whether a boolean view option such as `security_barrier` is switched on.
+pub(super) fn view_option_enabled(options: &CreateTableOptions, key: &str) -> bool {
+    view_option_value(options, key)
+        .is_some_and(|value| matches!(value.to_lowercase().as_str(), "true" | "on" | "yes" | "1"))
+}
+
 pub(super) fn parse_policy_command(cmd: &Option<CreatePolicyCommand>) -> PolicyCommand {
     match cmd {
         Some(CreatePolicyCommand::All) => PolicyCommand::All,
@@ -1,29 +1,140 @@
-use crate::util::{sanitize_connection_error, sanitize_url, Result, SchemaError};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::util::{Result, SchemaError};
+use sqlx::pool::PoolConnection;
 use sqlx::postgres::PgPoolOptions;
 use sqlx::{Pool, Postgres};
 
+use super::database_url::DatabaseUrl;
+use super::metrics::Metrics;
+
 pub struct PgConnection {
     pool: Pool<Postgres>,
+    /// Metrics being collected, between `start_metrics` and `finish_metrics`.
+    metrics: Mutex<Option<Metrics>>,
 }
 
 impl PgConnection {
     pub async fn new(connection_string: &str) -> Result<Self> {
+        Self::connect(&DatabaseUrl::new(connection_string)).await
+    }
+
+    pub async fn connect(url: &DatabaseUrl) -> Result<Self> {
         let pool = PgPoolOptions::new()
             .max_connections(5)
-            .connect(connection_string)
+            .connect(url.expose())
             .await
             .map_err(|e| {
-                let sanitized_error = sanitize_connection_error(connection_string, &e.to_string());
                 SchemaError::DatabaseError(format!(
-                    "Failed to connect to {}: {sanitized_error}",
-                    sanitize_url(connection_string)
+                    "Failed to connect to {url}: {}",
+                    url.redact_message(&e.to_string())
                 ))
             })?;
 
-        Ok(PgConnection { pool })
+        Ok(PgConnection {
+            pool,
+            metrics: Mutex::new(None),
+        })
     }
 
     pub fn pool(&self) -> &Pool<Postgres> {
         &self.pool
     }
+
+    /// The server's `server_version_num`, such as 150004 for PostgreSQL 15.4.
+    pub async fn server_version_num(&self) -> Result<i32> {
+        sqlx::query_scalar("SELECT current_setting('server_version_num')::int")
+            .fetch_one(&mut *self.acquire().await?)
+            .await
+            .map_err(|e| SchemaError::DatabaseError(format!("Failed to read server version: {e}")))
+    }
+
+    /// Checks a connection out of the pool for one query, counting the query
+    /// and the time spent waiting when metrics are being collected.
+    pub async fn acquire(&self) -> Result<PoolConnection<Postgres>> {
+        let start = Instant::now();
+        let connection = self.pool.acquire().await.map_err(|e| {
+            SchemaError::DatabaseError(format!("Failed to acquire a pooled connection: {e}"))
+        })?;
+        let waited = start.elapsed();
+        self.record(|metrics| {
+            metrics.query_count += 1;
+            metrics.pool_wait += waited;
+        });
+        Ok(connection)
+    }
+
+    /// Starts collecting metrics, discarding any collected so far.
+    pub fn start_metrics(&self) {
+        *self.lock_metrics() = Some(Metrics::default());
+    }
+
+    /// Stops collecting metrics and returns what was collected since
+    /// `start_metrics`, or `None` if collection was not started.
+    pub fn finish_metrics(&self) -> Option<Metrics> {
+        self.lock_metrics().take()
+    }
+
+    /// Awaits `future`, adding its wall time to the introspection `category`.
+    pub(crate) async fn timed<T>(&self, category: &str, future: impl Future<Output = T>) -> T {
+        let start = Instant::now();
+        let output = future.await;
+        let elapsed = start.elapsed();
+        self.record(|metrics| {
+            *metrics
+                .introspection
+                .entry(category.to_string())
+                .or_default() += elapsed;
+        });
+        output
+    }
+
+    pub(crate) fn record(&self, update: impl FnOnce(&mut Metrics)) {
+        if let Some(metrics) = self.lock_metrics().as_mut() {
+            update(metrics);
+        }
+    }
+
+    fn lock_metrics(&self) -> std::sync::MutexGuard<'_, Option<Metrics>> {
+        self.metrics
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lazy_connection() -> PgConnection {
+        PgConnection {
+            pool: PgPoolOptions::new()
+                .connect_lazy("postgres://localhost/unused")
+                .unwrap(),
+            metrics: Mutex::new(None),
+        }
+    }
+
+    #[tokio::test]
+    async fn records_metrics_only_between_start_and_finish() {
+        let connection = lazy_connection();
+        connection.timed("tables", async {}).await;
+        assert_eq!(connection.finish_metrics(), None);
+
+        connection.start_metrics();
+        connection.timed("tables", async {}).await;
+        connection.timed("tables", async {}).await;
+        connection.timed("columns", async {}).await;
+        connection.record(|metrics| metrics.query_count += 3);
+
+        let metrics = connection.finish_metrics().unwrap();
+        assert_eq!(
+            metrics.introspection.keys().collect::<Vec<_>>(),
+            vec!["columns", "tables"]
+        );
+        assert_eq!(metrics.query_count, 3);
+        assert_eq!(connection.finish_metrics(), None);
+    }
 }
@@ -1,9 +1,12 @@
+use crate::lint::{lint_required_settings, LintOptions, LintResult, TableSize};
 use crate::model::*;
 use crate::pg::connection::PgConnection;
 use crate::pg::sqlgen::strip_ident_quotes;
 use crate::util::{normalize_sql_whitespace, Result, SchemaError};
+use serde::Serialize;
 use sqlx::Row;
 use std::collections::{BTreeMap, BTreeSet};
+use std::time::Instant;
 
 /// Queries run concurrently via try_join! — requires a connection pool
 /// with enough capacity (default max_connections=5 handles the concurrency
@@ -13,6 +16,40 @@ pub async fn introspect_schema(
     target_schemas: &[String],
     include_extension_objects: bool,
 ) -> Result<Schema> {
+    introspect_schema_with(connection, target_schemas, include_extension_objects, None).await
+}
+
+/// Function bodies and view queries longer than this many bytes are
+/// compared by hash against the known schema before being fetched.
+pub const LAZY_BODY_BYTES: i32 = 16 * 1024;
+
+/// Like [`introspect_schema`], for a database expected to match `known`
+/// (usually the schema sources). A function body or view query longer than
+/// [`LAZY_BODY_BYTES`] is first fetched as a hash; when it matches the hash of
+/// the same object in `known`, `known`'s text is used and the body never
+/// crosses the wire. Only bodies that differ are fetched in full.
+pub async fn introspect_schema_with_known_bodies(
+    connection: &PgConnection,
+    target_schemas: &[String],
+    include_extension_objects: bool,
+    known: &Schema,
+) -> Result<Schema> {
+    introspect_schema_with(
+        connection,
+        target_schemas,
+        include_extension_objects,
+        Some(known),
+    )
+    .await
+}
+
+async fn introspect_schema_with(
+    connection: &PgConnection,
+    target_schemas: &[String],
+    include_extension_objects: bool,
+    known: Option<&Schema>,
+) -> Result<Schema> {
+    let started = Instant::now();
     let (
         schemas,
         extensions,
@@ -35,6 +72,7 @@ pub async fn introspect_schema(
         mut all_columns,
         mut all_primary_keys,
         mut all_indexes,
+        mut all_unique_constraints,
         mut all_foreign_keys,
         mut all_check_constraints,
         mut all_exclusion_constraints,
@@ -44,37 +82,131 @@ pub async fn introspect_schema(
         default_privileges,
         table_constraint_comments,
         domain_constraint_comments,
+        raw_objects,
     ) = tokio::try_join!(
-        introspect_schemas(connection, target_schemas),
-        introspect_extensions(connection),
-        introspect_servers(connection),
-        introspect_enums(connection, target_schemas, include_extension_objects),
-        introspect_domains(connection, target_schemas, include_extension_objects),
-        introspect_tables(connection, target_schemas, include_extension_objects),
-        introspect_functions(connection, target_schemas, include_extension_objects),
-        introspect_aggregates(connection, target_schemas, include_extension_objects),
-        introspect_views(connection, target_schemas, include_extension_objects),
-        introspect_triggers(connection, target_schemas, include_extension_objects),
-        introspect_sequences(connection, target_schemas, include_extension_objects),
-        introspect_table_view_grants(connection, target_schemas),
-        introspect_sequence_grants(connection, target_schemas),
-        introspect_function_grants(connection, target_schemas),
-        introspect_schema_grants(connection, target_schemas),
-        introspect_type_grants(connection, target_schemas),
-        introspect_partition_keys(connection, target_schemas),
-        introspect_partitions(connection, target_schemas),
-        introspect_all_columns(connection, target_schemas),
-        introspect_all_primary_keys(connection, target_schemas),
-        introspect_all_indexes(connection, target_schemas),
-        introspect_all_foreign_keys(connection, target_schemas),
-        introspect_all_check_constraints(connection, target_schemas),
-        introspect_all_exclusion_constraints(connection, target_schemas),
-        introspect_all_rls(connection, target_schemas),
-        introspect_all_force_rls(connection, target_schemas),
-        introspect_all_policies(connection, target_schemas),
-        introspect_default_privileges(connection, target_schemas),
-        introspect_table_constraint_comments(connection, target_schemas),
-        introspect_domain_constraint_comments(connection, target_schemas),
+        connection.timed("schemas", introspect_schemas(connection, target_schemas)),
+        connection.timed("extensions", introspect_extensions(connection)),
+        connection.timed("servers", introspect_servers(connection)),
+        connection.timed(
+            "enums",
+            introspect_enums(connection, target_schemas, include_extension_objects)
+        ),
+        connection.timed(
+            "domains",
+            introspect_domains(connection, target_schemas, include_extension_objects)
+        ),
+        connection.timed(
+            "tables",
+            introspect_tables(connection, target_schemas, include_extension_objects)
+        ),
+        connection.timed(
+            "functions",
+            introspect_functions(
+                connection,
+                target_schemas,
+                include_extension_objects,
+                known.map(|known| &known.functions)
+            )
+        ),
+        connection.timed(
+            "aggregates",
+            introspect_aggregates(connection, target_schemas, include_extension_objects)
+        ),
+        connection.timed(
+            "views",
+            introspect_views(
+                connection,
+                target_schemas,
+                include_extension_objects,
+                known.map(|known| &known.views)
+            )
+        ),
+        connection.timed(
+            "triggers",
+            introspect_triggers(connection, target_schemas, include_extension_objects)
+        ),
+        connection.timed(
+            "sequences",
+            introspect_sequences(connection, target_schemas, include_extension_objects)
+        ),
+        connection.timed(
+            "table_view_grants",
+            introspect_table_view_grants(connection, target_schemas)
+        ),
+        connection.timed(
+            "sequence_grants",
+            introspect_sequence_grants(connection, target_schemas)
+        ),
+        connection.timed(
+            "function_grants",
+            introspect_function_grants(connection, target_schemas)
+        ),
+        connection.timed(
+            "schema_grants",
+            introspect_schema_grants(connection, target_schemas)
+        ),
+        connection.timed(
+            "type_grants",
+            introspect_type_grants(connection, target_schemas)
+        ),
+        connection.timed(
+            "partition_keys",
+            introspect_partition_keys(connection, target_schemas)
+        ),
+        connection.timed(
+            "partitions",
+            introspect_partitions(connection, target_schemas)
+        ),
+        connection.timed(
+            "columns",
+            introspect_all_columns(connection, target_schemas)
+        ),
+        connection.timed(
+            "primary_keys",
+            introspect_all_primary_keys(connection, target_schemas)
+        ),
+        connection.timed(
+            "indexes",
+            introspect_all_indexes(connection, target_schemas)
+        ),
+        connection.timed(
+            "unique_constraints",
+            introspect_all_unique_constraints(connection, target_schemas)
+        ),
+        connection.timed(
+            "foreign_keys",
+            introspect_all_foreign_keys(connection, target_schemas)
+        ),
+        connection.timed(
+            "check_constraints",
+            introspect_all_check_constraints(connection, target_schemas)
+        ),
+        connection.timed(
+            "exclusion_constraints",
+            introspect_all_exclusion_constraints(connection, target_schemas)
+        ),
+        connection.timed("rls", introspect_all_rls(connection, target_schemas)),
+        connection.timed(
+            "force_rls",
+            introspect_all_force_rls(connection, target_schemas)
+        ),
+        connection.timed(
+            "policies",
+            introspect_all_policies(connection, target_schemas)
+        ),
+        connection.timed(
+            "default_privileges",
+            introspect_default_privileges(connection, target_schemas)
+        ),
+        connection.timed(
+            "table_constraint_comments",
+            introspect_table_constraint_comments(connection, target_schemas)
+        ),
+        connection.timed(
+            "domain_constraint_comments",
+            introspect_domain_constraint_comments(connection, target_schemas)
+        ),
+        connection.timed("raw_objects", introspect_raw_objects(connection)),
     )?;
 
     let mut schema = Schema::new();
@@ -93,6 +225,7 @@ pub async fn introspect_schema(
     schema.default_privileges = default_privileges;
     schema.table_constraint_comments = table_constraint_comments;
     schema.domain_constraint_comments = domain_constraint_comments;
+    schema.raw_objects = raw_objects;
 
     for (qualified_name, grants) in table_view_grants {
         if let Some(table) = schema.tables.get_mut(&qualified_name) {
@@ -145,6 +278,10 @@ pub async fn introspect_schema(
             indexes.sort();
             table.indexes = indexes;
         }
+        if let Some(mut unique_constraints) = all_unique_constraints.remove(qualified_name) {
+            unique_constraints.sort();
+            table.unique_constraints = unique_constraints;
+        }
         if let Some(mut foreign_keys) = all_foreign_keys.remove(qualified_name) {
             foreign_keys.sort();
             table.foreign_keys = foreign_keys;
@@ -169,6 +306,7 @@ pub async fn introspect_schema(
         }
     }
 
+    connection.record(|metrics| metrics.introspection_total += started.elapsed());
     Ok(schema)
 }
 
@@ -178,19 +316,20 @@ async fn introspect_schemas(
 ) -> Result<BTreeMap<String, PgSchema>> {
     let rows = sqlx::query(
         r#"
-        SELECT nspname as name
+        SELECT nspname as name, obj_description(oid, 'pg_namespace') as comment
         FROM pg_namespace
         WHERE nspname NOT LIKE 'pg_%'
           AND nspname != 'information_schema'
         "#,
     )
-    .fetch_all(connection.pool())
+    .fetch_all(&mut *connection.acquire().await?)
     .await
     .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch schemas: {e}")))?;
 
     let mut schemas = BTreeMap::new();
     for row in rows {
         let name: String = row.get("name");
+        let comment: Option<String> = row.get("comment");
         // Always skip 'public' schema - it's a default schema that always exists in PostgreSQL.
         // Users who want to manage 'public' must include CREATE SCHEMA "public" in their SQL.
         if name == "public" {
@@ -203,8 +342,7 @@ async fn introspect_schemas(
                 PgSchema {
                     name,
                     grants: Vec::new(),
-                    // TODO: read schema comment from pg_description
-                    comment: None,
+                    comment,
                 },
             );
         }
@@ -220,13 +358,14 @@ async fn introspect_extensions(connection: &PgConnection) -> Result<BTreeMap<Str
             e.extname as name,
             e.extversion as version,
             n.nspname as schema,
+            e.extrelocatable as relocatable,
             obj_description(e.oid, 'pg_extension') as comment
         FROM pg_extension e
         JOIN pg_namespace n ON e.extnamespace = n.oid
         WHERE e.extname != 'plpgsql'
         "#,
     )
-    .fetch_all(connection.pool())
+    .fetch_all(&mut *connection.acquire().await?)
     .await
     .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch extensions: {e}")))?;
 
@@ -235,6 +374,7 @@ async fn introspect_extensions(connection: &PgConnection) -> Result<BTreeMap<Str
         let name: String = row.get("name");
         let version: Option<String> = row.get("version");
         let schema: Option<String> = row.get("schema");
+        let relocatable: bool = row.get("relocatable");
         let comment: Option<String> = row.get("comment");
 
         extensions.insert(
@@ -243,6 +383,7 @@ async fn introspect_extensions(connection: &PgConnection) -> Result<BTreeMap<Str
                 name,
                 version,
                 schema,
+                relocatable,
                 comment,
             },
         );
@@ -251,6 +392,177 @@ async fn introspect_extensions(connection: &PgConnection) -> Result<BTreeMap<Str
     Ok(extensions)
 }
 
+/// Content hashes recorded in the raw object tracking table, which exists
+/// once the first raw object has been applied.
+async fn introspect_raw_objects(connection: &PgConnection) -> Result<BTreeMap<String, RawObject>> {
+    let mut conn = connection.acquire().await?;
+    let tracked: bool = sqlx::query_scalar("SELECT to_regclass($1) IS NOT NULL")
+        .bind(RAW_OBJECTS_TABLE)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| SchemaError::DatabaseError(format!("Failed to look up raw objects: {e}")))?;
+    if !tracked {
+        return Ok(BTreeMap::new());
+    }
+
+    let rows = sqlx::query(&format!(
+        "SELECT name, content_hash FROM {RAW_OBJECTS_TABLE}"
+    ))
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch raw objects: {e}")))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let name: String = row.get("name");
+            let raw = RawObject {
+                name: name.clone(),
+                sql: String::new(),
+                depends_on: Vec::new(),
+                content_hash: row.get("content_hash"),
+            };
+            (name, raw)
+        })
+        .collect())
+}
+
+/// `pg_settings.setting` for each of `names` the server knows, by name.
+/// Numeric settings are in the unit `pg_settings.unit` names, such as 8kB
+/// pages for `shared_buffers`.
+pub async fn current_settings(
+    connection: &PgConnection,
+    names: &[String],
+) -> Result<BTreeMap<String, String>> {
+    if names.is_empty() {
+        return Ok(BTreeMap::new());
+    }
+    let rows = sqlx::query("SELECT name, setting FROM pg_settings WHERE name = ANY($1)")
+        .bind(names)
+        .fetch_all(&mut *connection.acquire().await?)
+        .await
+        .map_err(|e| SchemaError::DatabaseError(format!("Failed to read settings: {e}")))?;
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.get("name"), row.get("setting")))
+        .collect())
+}
+
+/// Lints the server's settings against the `required_settings` of
+/// `options`; see [`lint_required_settings`].
+pub async fn check_required_settings(
+    connection: &PgConnection,
+    options: &LintOptions,
+) -> Result<Vec<LintResult>> {
+    let names: Vec<String> = options
+        .required_settings
+        .iter()
+        .map(|(name, _)| name.clone())
+        .collect();
+    let current = current_settings(connection, &names).await?;
+    Ok(lint_required_settings(options, &current))
+}
+
+/// Estimated sizes of the `tables` that exist, by qualified name. A
+/// partitioned table counts all of its partitions.
+pub async fn estimate_table_sizes(
+    connection: &PgConnection,
+    tables: &[String],
+) -> Result<BTreeMap<String, TableSize>> {
+    if tables.is_empty() {
+        return Ok(BTreeMap::new());
+    }
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            n.nspname || '.' || c.relname AS name,
+            (SELECT CASE WHEN bool_or(p.reltuples < 0) THEN NULL
+                         ELSE SUM(p.reltuples)::bigint END
+             FROM pg_partition_tree(c.oid) t
+             JOIN pg_class p ON p.oid = t.relid
+             WHERE t.isleaf) AS estimated_rows,
+            (SELECT COALESCE(SUM(pg_total_relation_size(t.relid)), 0)::bigint
+             FROM pg_partition_tree(c.oid) t) AS total_bytes
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE c.relkind IN ('r', 'p')
+          AND n.nspname || '.' || c.relname = ANY($1)
+        "#,
+    )
+    .bind(tables)
+    .fetch_all(&mut *connection.acquire().await?)
+    .await
+    .map_err(|e| SchemaError::DatabaseError(format!("Failed to estimate table sizes: {e}")))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let rows: Option<i64> = row.get("estimated_rows");
+            let bytes: i64 = row.get("total_bytes");
+            let size = TableSize {
+                rows: rows.map(|rows| rows.max(0) as u64),
+                bytes: bytes.max(0) as u64,
+            };
+            (row.get("name"), size)
+        })
+        .collect())
+}
+
+/// Catalog metadata for a table, view, materialized view or sequence, which
+/// the model itself leaves out.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RelationStats {
+    pub oid: u32,
+    pub owner: String,
+    /// Planner estimate, `None` for relations never analyzed and for views
+    /// and sequences.
+    pub estimated_rows: Option<u64>,
+    /// Size including indexes and TOAST, as `pg_total_relation_size`.
+    pub total_bytes: u64,
+}
+
+/// [`RelationStats`] for the relations in `target_schemas`, by qualified name.
+pub async fn introspect_relation_stats(
+    connection: &PgConnection,
+    target_schemas: &[String],
+) -> Result<BTreeMap<String, RelationStats>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            n.nspname || '.' || c.relname AS name,
+            c.oid::bigint AS oid,
+            pg_get_userbyid(c.relowner) AS owner,
+            CASE WHEN c.relkind IN ('r', 'm') AND c.reltuples >= 0
+                 THEN c.reltuples::bigint END AS estimated_rows,
+            pg_total_relation_size(c.oid) AS total_bytes
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE c.relkind IN ('r', 'p', 'v', 'm', 'S')
+          AND n.nspname = ANY($1)
+        "#,
+    )
+    .bind(target_schemas)
+    .fetch_all(&mut *connection.acquire().await?)
+    .await
+    .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch relation stats: {e}")))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let oid: i64 = row.get("oid");
+            let estimated_rows: Option<i64> = row.get("estimated_rows");
+            let total_bytes: i64 = row.get("total_bytes");
+            let stats = RelationStats {
+                oid: oid as u32,
+                owner: row.get("owner"),
+                estimated_rows: estimated_rows.map(|rows| rows as u64),
+                total_bytes: total_bytes.max(0) as u64,
+            };
+            (row.get("name"), stats)
+        })
+        .collect())
+}
+
 async fn introspect_servers(connection: &PgConnection) -> Result<BTreeMap<String, Server>> {
     let rows = sqlx::query(
         r#"
@@ -266,7 +578,7 @@ async fn introspect_servers(connection: &PgConnection) -> Result<BTreeMap<String
         LEFT JOIN pg_roles u ON u.oid = s.srvowner
         "#,
     )
-    .fetch_all(connection.pool())
+    .fetch_all(&mut *connection.acquire().await?)
     .await
     .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch foreign servers: {e}")))?;
 
@@ -312,7 +624,8 @@ async fn introspect_enums(
 ) -> Result<BTreeMap<String, EnumType>> {
     let rows = sqlx::query(
         r#"
-        SELECT n.nspname, t.typname, array_agg(e.enumlabel ORDER BY e.enumsortorder) as labels, r.rolname AS owner
+        SELECT n.nspname, t.typname, array_agg(e.enumlabel ORDER BY e.enumsortorder) as labels, r.rolname AS owner,
+               obj_description(t.oid, 'pg_type') AS comment
         FROM pg_type t
         JOIN pg_enum e ON t.oid = e.enumtypid
         JOIN pg_namespace n ON t.typnamespace = n.oid
@@ -323,12 +636,12 @@ async fn introspect_enums(
               WHERE d.objid = t.oid
               AND d.deptype = 'e'
           ))
-        GROUP BY n.nspname, t.typname, r.rolname
+        GROUP BY t.oid, n.nspname, t.typname, r.rolname
         "#,
     )
     .bind(target_schemas)
     .bind(include_extension_objects)
-    .fetch_all(connection.pool())
+    .fetch_all(&mut *connection.acquire().await?)
     .await
     .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch enums: {e}")))?;
 
@@ -344,8 +657,7 @@ async fn introspect_enums(
             values: labels,
             owner: Some(owner),
             grants: Vec::new(),
-            // TODO: read enum type comment from pg_description
-            comment: None,
+            comment: row.get("comment"),
         };
         enums.insert(qualified_name(&schema, &name), enum_type);
     }
@@ -367,7 +679,8 @@ async fn introspect_domains(
             bt.typcategory::text AS base_category,
             t.typnotnull AS not_null,
             pg_get_expr(t.typdefaultbin, 0) AS default_expr,
-            r.rolname AS owner
+            r.rolname AS owner,
+            obj_description(t.oid, 'pg_type') AS comment
         FROM pg_type t
         JOIN pg_namespace n ON t.typnamespace = n.oid
         JOIN pg_type bt ON t.typbasetype = bt.oid
@@ -383,7 +696,7 @@ async fn introspect_domains(
     )
     .bind(target_schemas)
     .bind(include_extension_objects)
-    .fetch_all(connection.pool())
+    .fetch_all(&mut *connection.acquire().await?)
     .await
     .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch domains: {e}")))?;
 
@@ -456,8 +769,7 @@ async fn introspect_domains(
                 .unwrap_or_default(),
             owner: Some(owner),
             grants: Vec::new(),
-            // TODO: read domain comment from pg_description
-            comment: None,
+            comment: row.get("comment"),
         };
         domains.insert(qualified_name(&schema, &name), domain);
     }
@@ -491,7 +803,7 @@ async fn introspect_all_domain_constraints(
     )
     .bind(target_schemas)
     .bind(include_extension_objects)
-    .fetch_all(connection.pool())
+    .fetch_all(&mut *connection.acquire().await?)
     .await
     .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch domain constraints: {e}")))?;
 
@@ -538,7 +850,12 @@ async fn introspect_tables(
 ) -> Result<BTreeMap<String, Table>> {
     let rows = sqlx::query(
         r#"
-        SELECT n.nspname AS table_schema, c.relname AS table_name, r.rolname AS owner
+        SELECT n.nspname AS table_schema, c.relname AS table_name, r.rolname AS owner,
+               c.relreplident::text AS replica_identity,
+               (SELECT ic.relname FROM pg_index i
+                JOIN pg_class ic ON ic.oid = i.indexrelid
+                WHERE i.indrelid = c.oid AND i.indisreplident) AS replica_identity_index,
+               obj_description(c.oid, 'pg_class') AS comment
         FROM pg_class c
         JOIN pg_namespace n ON c.relnamespace = n.oid
         JOIN pg_roles r ON c.relowner = r.oid
@@ -554,7 +871,7 @@ async fn introspect_tables(
     )
     .bind(target_schemas)
     .bind(include_extension_objects)
-    .fetch_all(connection.pool())
+    .fetch_all(&mut *connection.acquire().await?)
     .await
     .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch tables: {e}")))?;
 
@@ -563,6 +880,16 @@ async fn introspect_tables(
         let schema: String = row.get("table_schema");
         let name: String = row.get("table_name");
         let owner: String = row.get("owner");
+        let replica_identity_index: Option<String> = row.get("replica_identity_index");
+        let replica_identity = match row.get::<String, _>("replica_identity").as_str() {
+            "f" => Some(ReplicaIdentity::Full),
+            "n" => Some(ReplicaIdentity::Nothing),
+            // Without its index, USING INDEX behaves as NOTHING.
+            "i" => Some(
+                replica_identity_index.map_or(ReplicaIdentity::Nothing, ReplicaIdentity::Index),
+            ),
+            _ => None,
+        };
         let table = Table {
             name: name.clone(),
             schema: schema.clone(),
@@ -571,11 +898,12 @@ async fn introspect_tables(
             primary_key: None,
             foreign_keys: Vec::new(),
             check_constraints: Vec::new(),
+            unique_constraints: Vec::new(),
             exclusion_constraints: Vec::new(),
-            // TODO: read table comment from pg_description
-            comment: None,
+            comment: row.get("comment"),
             row_level_security: false,
             force_row_level_security: false,
+            replica_identity,
             policies: Vec::new(),
             partition_by: None,
             owner: Some(owner),
@@ -607,7 +935,7 @@ async fn introspect_partition_keys(
         "#,
     )
     .bind(target_schemas)
-    .fetch_all(connection.pool())
+    .fetch_all(&mut *connection.acquire().await?)
     .await
     .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch partition keys: {e}")))?;
 
@@ -665,7 +993,7 @@ async fn introspect_partitions(
         "#,
     )
     .bind(target_schemas)
-    .fetch_all(connection.pool())
+    .fetch_all(&mut *connection.acquire().await?)
     .await
     .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch partitions: {e}")))?;
 
@@ -785,7 +1113,13 @@ async fn introspect_all_columns(
     connection: &PgConnection,
     target_schemas: &[String],
 ) -> Result<BTreeMap<String, BTreeMap<String, Column>>> {
-    let rows = sqlx::query(
+    // pg_attribute.attcompression only exists from PostgreSQL 14.
+    let compression = if connection.server_version_num().await? >= 140_000 {
+        "CASE a.attcompression WHEN 'p' THEN 'pglz' WHEN 'l' THEN 'lz4' END"
+    } else {
+        "NULL::text"
+    };
+    let query = format!(
         r#"
         SELECT
             c.table_schema,
@@ -805,22 +1139,41 @@ async fn introspect_all_columns(
             CASE WHEN a.attgenerated = 's'
                  THEN pg_catalog.pg_get_expr(ad.adbin, a.attrelid)
                  ELSE NULL
-            END AS generation_expression
+            END AS generation_expression,
+            NULLIF(a.attstattarget, -1)::int4 AS statistics_target,
+            {compression} AS compression,
+            NULLIF(a.attidentity, '')::text AS identity_generation,
+            s.seqstart AS identity_start,
+            s.seqincrement AS identity_increment,
+            s.seqmin AS identity_min,
+            s.seqmax AS identity_max,
+            s.seqcache AS identity_cache,
+            s.seqcycle AS identity_cycle,
+            pg_catalog.col_description(a.attrelid, a.attnum) AS comment
         FROM information_schema.columns c
         JOIN pg_catalog.pg_class t ON t.relname = c.table_name
         JOIN pg_catalog.pg_namespace n ON n.oid = t.relnamespace AND n.nspname = c.table_schema
         JOIN pg_catalog.pg_attribute a ON a.attrelid = t.oid AND a.attname = c.column_name
         LEFT JOIN pg_catalog.pg_attrdef ad ON ad.adrelid = a.attrelid AND ad.adnum = a.attnum
+        LEFT JOIN pg_catalog.pg_depend d
+            ON a.attidentity <> ''
+           AND d.classid = 'pg_catalog.pg_class'::regclass
+           AND d.refclassid = 'pg_catalog.pg_class'::regclass
+           AND d.refobjid = a.attrelid
+           AND d.refobjsubid = a.attnum
+           AND d.deptype = 'i'
+        LEFT JOIN pg_catalog.pg_sequence s ON s.seqrelid = d.objid
         WHERE c.table_schema = ANY($1::text[])
           AND t.relkind IN ('r', 'p')
           AND t.relispartition = false
         ORDER BY c.table_schema, c.table_name, c.ordinal_position
-        "#,
-    )
-    .bind(target_schemas)
-    .fetch_all(connection.pool())
-    .await
-    .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch columns: {e}")))?;
+        "#
+    );
+    let rows = sqlx::query(&query)
+        .bind(target_schemas)
+        .fetch_all(&mut *connection.acquire().await?)
+        .await
+        .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch columns: {e}")))?;
 
     let mut result: BTreeMap<String, BTreeMap<String, Column>> = BTreeMap::new();
     for row in rows {
@@ -838,6 +1191,23 @@ async fn introspect_all_columns(
         let atttypmod: i32 = row.get("atttypmod");
         let pg_format_type: String = row.get("pg_format_type");
         let generation_expression: Option<String> = row.get("generation_expression");
+        let statistics_target: Option<i32> = row.get("statistics_target");
+        let compression: Option<String> = row.get("compression");
+        let identity = row
+            .get::<Option<String>, _>("identity_generation")
+            .map(|generation| Identity {
+                generation: if generation == "d" {
+                    IdentityGeneration::ByDefault
+                } else {
+                    IdentityGeneration::Always
+                },
+                start: row.get("identity_start"),
+                increment: row.get("identity_increment"),
+                min_value: row.get("identity_min"),
+                max_value: row.get("identity_max"),
+                cache: row.get("identity_cache"),
+                cycle: row.get("identity_cycle"),
+            });
 
         let pg_type = match (domain_schema, domain_name) {
             (Some(schema), Some(name)) => PgType::UserDefined(format!("{schema}.{name}")),
@@ -861,9 +1231,11 @@ async fn introspect_all_columns(
                     data_type: pg_type,
                     nullable: is_nullable == "YES",
                     default: column_default,
-                    // TODO: read column comment from pg_description
-                    comment: None,
+                    comment: row.get("comment"),
                     generated: generation_expression,
+                    statistics: statistics_target,
+                    compression,
+                    identity,
                 },
             );
     }
@@ -1053,7 +1425,7 @@ async fn introspect_all_primary_keys(
         "#,
     )
     .bind(target_schemas)
-    .fetch_all(connection.pool())
+    .fetch_all(&mut *connection.acquire().await?)
     .await
     .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch primary keys: {e}")))?;
 
@@ -1075,7 +1447,13 @@ async fn introspect_all_indexes(
     connection: &PgConnection,
     target_schemas: &[String],
 ) -> Result<BTreeMap<String, Vec<Index>>> {
-    let rows = sqlx::query(
+    // pg_index.indnullsnotdistinct only exists from PostgreSQL 15.
+    let nulls_not_distinct = if connection.server_version_num().await? >= 150_000 {
+        "ix.indnullsnotdistinct"
+    } else {
+        "false"
+    };
+    let query = format!(
         r#"
         SELECT
             n.nspname AS table_schema,
@@ -1089,28 +1467,43 @@ async fn introspect_all_indexes(
                      ELSE (SELECT a.attname::text FROM pg_attribute a WHERE a.attrelid = t.oid AND a.attnum = ix.indkey[k])
                 END ORDER BY k
             ) FROM generate_series(0, array_length(ix.indkey, 1) - 1) AS k), ARRAY[]::text[]) as columns,
+            COALESCE((SELECT array_agg(COALESCE(
+                (SELECT c.collname::text FROM pg_collation c
+                 WHERE c.oid = ix.indcollation[k]
+                   AND c.oid <> COALESCE((SELECT a.attcollation FROM pg_attribute a
+                                          WHERE a.attrelid = t.oid AND a.attnum = ix.indkey[k]
+                                            AND ix.indkey[k] <> 0), 100)),
+                '') ORDER BY k
+            ) FROM generate_series(0, array_length(ix.indkey, 1) - 1) AS k), ARRAY[]::text[]) as collations,
+            COALESCE((SELECT array_agg(COALESCE(
+                (SELECT oc.opcname::text FROM pg_opclass oc
+                 WHERE oc.oid = ix.indclass[k] AND NOT oc.opcdefault),
+                '') ORDER BY k
+            ) FROM generate_series(0, array_length(ix.indkey, 1) - 1) AS k), ARRAY[]::text[]) as opclasses,
+            COALESCE((SELECT array_agg(COALESCE(ix.indoption[k], 0)::int4 ORDER BY k)
+                FROM generate_series(0, array_length(ix.indkey, 1) - 1) AS k), ARRAY[]::int4[]) as sort_options,
             pg_get_expr(ix.indpred, ix.indrelid) as predicate,
-            (uc.oid IS NOT NULL) AS is_constraint
+            {nulls_not_distinct} AS nulls_not_distinct
         FROM pg_index ix
         JOIN pg_class t ON t.oid = ix.indrelid
         JOIN pg_class i ON i.oid = ix.indexrelid
         JOIN pg_am am ON am.oid = i.relam
         JOIN pg_namespace n ON n.oid = t.relnamespace
-        LEFT JOIN pg_constraint uc ON uc.conindid = ix.indexrelid AND uc.contype = 'u'
         WHERE n.nspname = ANY($1::text[])
           AND NOT ix.indisprimary
           AND t.relkind IN ('r', 'p')
           AND t.relispartition = false
           AND NOT EXISTS (
-              SELECT 1 FROM pg_constraint ex
-              WHERE ex.conindid = ix.indexrelid AND ex.contype = 'x'
+              SELECT 1 FROM pg_constraint con
+              WHERE con.conindid = ix.indexrelid AND con.contype IN ('u', 'x')
           )
-        "#,
-    )
-    .bind(target_schemas)
-    .fetch_all(connection.pool())
-    .await
-    .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch indexes: {e}")))?;
+        "#
+    );
+    let rows = sqlx::query(&query)
+        .bind(target_schemas)
+        .fetch_all(&mut *connection.acquire().await?)
+        .await
+        .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch indexes: {e}")))?;
 
     let mut result: BTreeMap<String, Vec<Index>> = BTreeMap::new();
     for row in rows {
@@ -1121,14 +1514,38 @@ async fn introspect_all_indexes(
         let am_name: String = row.get("amname");
         let columns: Vec<String> = row.get("columns");
         let predicate: Option<String> = row.get("predicate");
-        let is_constraint: bool = row.get("is_constraint");
+        let nulls_not_distinct: bool = row.get("nulls_not_distinct");
+        let collations: Vec<String> = row.get("collations");
+        let opclasses: Vec<String> = row.get("opclasses");
+        let sort_options: Vec<i32> = row.get("sort_options");
+        let non_empty = |value: &String| Some(value.clone()).filter(|v| !v.is_empty());
+        let column_options = collations
+            .iter()
+            .zip(&opclasses)
+            .zip(&sort_options)
+            .map(|((collation, opclass), sort)| {
+                // pg_index.indoption bits: 1 = DESC, 2 = NULLS FIRST.
+                IndexColumnOptions::new(
+                    non_empty(collation),
+                    non_empty(opclass),
+                    sort & 1 != 0,
+                    Some(sort & 2 != 0),
+                )
+            })
+            .collect();
 
         let index_type = match am_name.as_str() {
             "btree" => IndexType::BTree,
             "hash" => IndexType::Hash,
             "gin" => IndexType::Gin,
             "gist" => IndexType::Gist,
-            _ => panic!("unsupported index type: {am_name}"),
+            "brin" => IndexType::Brin,
+            "spgist" => IndexType::SpGist,
+            _ => {
+                return Err(SchemaError::DatabaseError(format!(
+                    "Index \"{name}\" on {table_schema}.{table_name} uses unsupported index method {am_name}"
+                )))
+            }
         };
 
         result
@@ -1140,7 +1557,69 @@ async fn introspect_all_indexes(
                 unique,
                 index_type,
                 predicate,
-                is_constraint,
+                nulls_not_distinct,
+                column_options: IndexColumnOptions::normalize(column_options),
+            });
+    }
+
+    Ok(result)
+}
+
+async fn introspect_all_unique_constraints(
+    connection: &PgConnection,
+    target_schemas: &[String],
+) -> Result<BTreeMap<String, Vec<UniqueConstraint>>> {
+    // pg_index.indnullsnotdistinct only exists from PostgreSQL 15.
+    let nulls_not_distinct = if connection.server_version_num().await? >= 150_000 {
+        "ix.indnullsnotdistinct"
+    } else {
+        "false"
+    };
+    let query = format!(
+        r#"
+        SELECT
+            n.nspname AS table_schema,
+            class.relname AS table_name,
+            con.conname AS name,
+            array_agg(att.attname::text ORDER BY u.attposition) AS columns,
+            {nulls_not_distinct} AS nulls_not_distinct,
+            con.condeferrable AS deferrable,
+            con.condeferred AS initially_deferred
+        FROM pg_constraint con
+        JOIN pg_class class ON con.conrelid = class.oid
+        JOIN pg_namespace n ON n.oid = class.relnamespace
+        JOIN pg_index ix ON ix.indexrelid = con.conindid
+        CROSS JOIN LATERAL unnest(con.conkey) WITH ORDINALITY AS u(attnum, attposition)
+        JOIN pg_attribute att ON att.attrelid = class.oid AND att.attnum = u.attnum
+        WHERE n.nspname = ANY($1::text[])
+          AND con.contype = 'u'
+          AND class.relkind IN ('r', 'p')
+          AND class.relispartition = false
+        GROUP BY n.nspname, class.relname, con.conname, {nulls_not_distinct}, con.condeferrable, con.condeferred
+        "#
+    );
+    let rows = sqlx::query(&query)
+        .bind(target_schemas)
+        .fetch_all(&mut *connection.acquire().await?)
+        .await
+        .map_err(|e| {
+            SchemaError::DatabaseError(format!("Failed to fetch unique constraints: {e}"))
+        })?;
+
+    let mut result: BTreeMap<String, Vec<UniqueConstraint>> = BTreeMap::new();
+    for row in rows {
+        let table_schema: String = row.get("table_schema");
+        let table_name: String = row.get("table_name");
+
+        result
+            .entry(qualified_name(&table_schema, &table_name))
+            .or_default()
+            .push(UniqueConstraint {
+                name: row.get("name"),
+                columns: row.get("columns"),
+                nulls_not_distinct: row.get("nulls_not_distinct"),
+                deferrable: row.get("deferrable"),
+                initially_deferred: row.get("initially_deferred"),
             });
     }
 
@@ -1162,7 +1641,9 @@ async fn introspect_all_foreign_keys(
             array_agg(att.attname ORDER BY u.attposition) as columns,
             array_agg(ref_att.attname ORDER BY u.attposition) as referenced_columns,
             con.confdeltype,
-            con.confupdtype
+            con.confupdtype,
+            con.condeferrable AS deferrable,
+            con.condeferred AS initially_deferred
         FROM pg_constraint con
         JOIN pg_class class ON con.conrelid = class.oid
         JOIN pg_class ref_class ON con.confrelid = ref_class.oid
@@ -1175,11 +1656,11 @@ async fn introspect_all_foreign_keys(
           AND con.contype = 'f'
           AND class.relkind IN ('r', 'p')
           AND class.relispartition = false
-        GROUP BY n.nspname, class.relname, con.conname, ref_class.relname, ref_n.nspname, con.confdeltype, con.confupdtype
+        GROUP BY n.nspname, class.relname, con.conname, ref_class.relname, ref_n.nspname, con.confdeltype, con.confupdtype, con.condeferrable, con.condeferred
         "#,
     )
     .bind(target_schemas)
-    .fetch_all(connection.pool())
+    .fetch_all(&mut *connection.acquire().await?)
     .await
     .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch foreign keys: {e}")))?;
 
@@ -1194,6 +1675,8 @@ async fn introspect_all_foreign_keys(
         let referenced_columns: Vec<String> = row.get("referenced_columns");
         let confdeltype: i8 = row.get::<i8, _>("confdeltype");
         let confupdtype: i8 = row.get::<i8, _>("confupdtype");
+        let deferrable: bool = row.get("deferrable");
+        let initially_deferred: bool = row.get("initially_deferred");
 
         result
             .entry(qualified_name(&table_schema, &table_name))
@@ -1206,6 +1689,8 @@ async fn introspect_all_foreign_keys(
                 referenced_columns,
                 on_delete: map_referential_action(pg_char(confdeltype)),
                 on_update: map_referential_action(pg_char(confupdtype)),
+                deferrable,
+                initially_deferred,
             });
     }
 
@@ -1233,7 +1718,7 @@ async fn introspect_all_check_constraints(
         "#,
     )
     .bind(target_schemas)
-    .fetch_all(connection.pool())
+    .fetch_all(&mut *connection.acquire().await?)
     .await
     .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch check constraints: {e}")))?;
 
@@ -1285,7 +1770,7 @@ async fn introspect_all_exclusion_constraints(
         "#,
     )
     .bind(target_schemas)
-    .fetch_all(connection.pool())
+    .fetch_all(&mut *connection.acquire().await?)
     .await
     .map_err(|e| {
         SchemaError::DatabaseError(format!("Failed to fetch exclusion constraints: {e}"))
@@ -1463,7 +1948,7 @@ async fn introspect_all_rls(
         "#,
     )
     .bind(target_schemas)
-    .fetch_all(connection.pool())
+    .fetch_all(&mut *connection.acquire().await?)
     .await
     .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch RLS status: {e}")))?;
 
@@ -1496,7 +1981,7 @@ async fn introspect_all_force_rls(
         "#,
     )
     .bind(target_schemas)
-    .fetch_all(connection.pool())
+    .fetch_all(&mut *connection.acquire().await?)
     .await
     .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch FORCE RLS status: {e}")))?;
 
@@ -1522,9 +2007,9 @@ async fn introspect_all_policies(
             c.relname AS table_name,
             pol.polname as name,
             pol.polcmd as command,
-            COALESCE(
-                ARRAY(SELECT rolname FROM pg_roles WHERE oid = ANY(pol.polroles)),
-                ARRAY[]::text[]
+            ARRAY(
+                SELECT CASE WHEN role_oid = 0 THEN 'public' ELSE pg_get_userbyid(role_oid)::text END
+                FROM unnest(pol.polroles) AS role_oid
             ) as roles,
             pg_get_expr(pol.polqual, pol.polrelid) as using_expr,
             pg_get_expr(pol.polwithcheck, pol.polrelid) as check_expr,
@@ -1538,7 +2023,7 @@ async fn introspect_all_policies(
         "#,
     )
     .bind(target_schemas)
-    .fetch_all(connection.pool())
+    .fetch_all(&mut *connection.acquire().await?)
     .await
     .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch policies: {e}")))?;
 
@@ -1553,11 +2038,7 @@ async fn introspect_all_policies(
         let check_expr: Option<String> = row.get("check_expr");
         let comment: Option<String> = row.get("comment");
 
-        let roles = if roles.is_empty() {
-            vec!["public".to_string()]
-        } else {
-            roles
-        };
+        let roles = Policy::normalize_roles(roles);
 
         result
             .entry(qualified_name(&table_schema, &table_name))
@@ -1607,7 +2088,7 @@ async fn introspect_table_constraint_comments(
         "#,
     )
     .bind(target_schemas)
-    .fetch_all(connection.pool())
+    .fetch_all(&mut *connection.acquire().await?)
     .await
     .map_err(|e| {
         SchemaError::DatabaseError(format!("Failed to fetch table constraint comments: {e}"))
@@ -1651,7 +2132,7 @@ async fn introspect_domain_constraint_comments(
         "#,
     )
     .bind(target_schemas)
-    .fetch_all(connection.pool())
+    .fetch_all(&mut *connection.acquire().await?)
     .await
     .map_err(|e| {
         SchemaError::DatabaseError(format!("Failed to fetch domain constraint comments: {e}"))
@@ -1686,21 +2167,32 @@ async fn introspect_functions(
     connection: &PgConnection,
     target_schemas: &[String],
     include_extension_objects: bool,
+    known: Option<&BTreeMap<String, Function>>,
 ) -> Result<BTreeMap<String, Function>> {
+    // A long body is hashed trimmed, as `body` is below; see `known_body`.
     let rows = sqlx::query(
         r#"
         SELECT
+            p.oid::bigint as oid,
             p.proname as name,
             n.nspname as schema,
             pg_get_function_arguments(p.oid) as arguments,
             pg_get_function_result(p.oid) as return_type,
             l.lanname as language,
-            p.prosrc as body,
+            CASE WHEN $3::boolean AND octet_length(p.prosrc) > $4 THEN NULL
+                 ELSE p.prosrc END as body,
+            CASE WHEN $3::boolean AND octet_length(p.prosrc) > $4 THEN
+                encode(sha256(convert_to(
+                    regexp_replace(p.prosrc, '^[[:space:]]+|[[:space:]]+$', '', 'g'),
+                    'UTF8'
+                )), 'hex')
+            END as body_hash,
             p.provolatile as volatility,
             p.prosecdef as security_definer,
             p.proconfig as config_params,
             r.rolname as owner,
-            p.proargmodes as arg_modes
+            p.proargmodes as arg_modes,
+            obj_description(p.oid, 'pg_proc') as comment
         FROM pg_proc p
         JOIN pg_namespace n ON p.pronamespace = n.oid
         JOIN pg_language l ON p.prolang = l.oid
@@ -1716,18 +2208,22 @@ async fn introspect_functions(
     )
     .bind(target_schemas)
     .bind(include_extension_objects)
-    .fetch_all(connection.pool())
+    .bind(known.is_some())
+    .bind(LAZY_BODY_BYTES)
+    .fetch_all(&mut *connection.acquire().await?)
     .await
     .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch functions: {e}")))?;
 
     let mut functions = BTreeMap::new();
+    let mut unfetched = Vec::new();
     for row in rows {
         let name: String = row.get("name");
         let schema: String = row.get("schema");
         let arguments_str: String = row.get("arguments");
         let return_type: String = row.get("return_type");
         let language: String = row.get("language");
-        let body: String = row.get("body");
+        let body: Option<String> = row.get("body");
+        let body_hash: Option<String> = row.get("body_hash");
         let volatility_char: i8 = row.get::<i8, _>("volatility");
         let security_definer: bool = row.get("security_definer");
 
@@ -1784,29 +2280,68 @@ async fn introspect_functions(
 
         let owner: String = row.get("owner");
 
-        let func = Function {
+        let mut func = Function {
             name: name.clone(),
             schema: schema.clone(),
             arguments,
             return_type: crate::model::normalize_pg_type(&return_type).into_owned(),
             language,
-            body: body.trim().to_string(),
+            body: body.as_deref().unwrap_or_default().trim().to_string(),
             volatility,
             security,
             config_params,
             owner: Some(owner),
             grants: Vec::new(),
-            // TODO: read function comment from pg_description
-            comment: None,
+            comment: row.get("comment"),
         };
 
         let key = qualified_name(&schema, &func.signature());
+        if let Some(hash) = body_hash {
+            let known_body = known
+                .and_then(|known| known.get(&key))
+                .and_then(|known| known_body(&known.body, &hash, |body| body.trim().to_string()));
+            match known_body {
+                Some(body) => func.body = body,
+                None => unfetched.push((row.get::<i64, _>("oid"), key.clone())),
+            }
+        }
         functions.insert(key, func);
     }
 
+    if !unfetched.is_empty() {
+        let oids: Vec<i64> = unfetched.iter().map(|(oid, _)| *oid).collect();
+        let rows = sqlx::query("SELECT p.oid::bigint AS oid, p.prosrc AS body FROM pg_proc p WHERE p.oid::bigint = ANY($1)")
+            .bind(&oids)
+            .fetch_all(&mut *connection.acquire().await?)
+            .await
+            .map_err(|e| {
+                SchemaError::DatabaseError(format!("Failed to fetch function bodies: {e}"))
+            })?;
+        let bodies: BTreeMap<i64, String> = rows
+            .into_iter()
+            .map(|row| (row.get("oid"), row.get("body")))
+            .collect();
+        for (oid, key) in unfetched {
+            if let (Some(function), Some(body)) = (functions.get_mut(&key), bodies.get(&oid)) {
+                function.body = body.trim().to_string();
+            }
+        }
+    }
+
     Ok(functions)
 }
 
+/// `known`'s text, when `hash` (computed by the database over its own copy,
+/// processed by SQL that collapses or trims a subset of what `process`
+/// does) shows the two are the same. `known` must already be in processed
+/// form, or the match would prove nothing about the database's copy.
+fn known_body(known: &str, hash: &str, process: impl Fn(&str) -> String) -> Option<String> {
+    use sha2::{Digest, Sha256};
+
+    (process(known) == known && hex::encode(Sha256::digest(known.as_bytes())) == hash)
+        .then(|| known.to_string())
+}
+
 async fn introspect_aggregates(
     connection: &PgConnection,
     target_schemas: &[String],
@@ -1826,7 +2361,8 @@ async fn introspect_aggregates(
             finalfunc.proname AS finalfunc_name,
             a.agginitval AS initcond,
             p.proparallel AS parallel,
-            a.aggkind AS aggkind
+            a.aggkind AS aggkind,
+            obj_description(p.oid, 'pg_proc') AS comment
         FROM pg_proc p
         JOIN pg_namespace n ON p.pronamespace = n.oid
         JOIN pg_aggregate a ON a.aggfnoid = p.oid
@@ -1846,7 +2382,7 @@ async fn introspect_aggregates(
     )
     .bind(target_schemas)
     .bind(include_extension_objects)
-    .fetch_all(connection.pool())
+    .fetch_all(&mut *connection.acquire().await?)
     .await
     .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch aggregates: {e}")))?;
 
@@ -1901,7 +2437,7 @@ async fn introspect_aggregates(
             parallel,
             owner: Some(owner),
             grants: Vec::new(),
-            comment: None,
+            comment: row.get("comment"),
         };
 
         let key = qualified_name(&schema, &aggregate.signature());
@@ -1991,11 +2527,13 @@ fn parse_function_arguments(args_str: &str) -> Vec<FunctionArg> {
                 (arg, None)
             };
 
-            // Parse mode (IN, OUT, INOUT)
+            // Parse mode (IN, OUT, INOUT, VARIADIC)
             let (mode, arg_rest) = if let Some(rest) = arg_without_default.strip_prefix("INOUT ") {
                 (ArgMode::InOut, rest)
             } else if let Some(rest) = arg_without_default.strip_prefix("OUT ") {
                 (ArgMode::Out, rest)
+            } else if let Some(rest) = arg_without_default.strip_prefix("VARIADIC ") {
+                (ArgMode::Variadic, rest)
             } else if let Some(rest) = arg_without_default.strip_prefix("IN ") {
                 (ArgMode::In, rest)
             } else {
@@ -2024,46 +2562,126 @@ fn parse_function_arguments(args_str: &str) -> Vec<FunctionArg> {
         .collect()
 }
 
+/// Collapses and trims whitespace in a view definition as
+/// [`view_query`] does, for hashing a long one in the database.
+const VIEW_DEFINITION_HASH: &str = r#"
+    CASE WHEN $3::boolean AND octet_length(v.definition) > $4 THEN
+        encode(sha256(convert_to(
+            btrim(regexp_replace(rtrim(v.definition, ';'), '[[:space:]]+', ' ', 'g'), ' '),
+            'UTF8'
+        )), 'hex')
+    END AS definition_hash,
+    CASE WHEN $3::boolean AND octet_length(v.definition) > $4 THEN NULL
+         ELSE v.definition END AS definition"#;
+
+fn view_query(definition: &str) -> String {
+    normalize_sql_whitespace(definition.trim_end_matches(';'))
+}
+
 async fn fetch_views(
     connection: &PgConnection,
     target_schemas: &[String],
     include_extension_objects: bool,
+    known: Option<&BTreeMap<String, View>>,
     query: &str,
     name_column: &str,
     materialized: bool,
 ) -> Result<Vec<View>> {
-    let rows = sqlx::query(query)
+    let rows = sqlx::query(&query.replace("{definition}", VIEW_DEFINITION_HASH))
         .bind(target_schemas)
         .bind(include_extension_objects)
-        .fetch_all(connection.pool())
+        .bind(known.is_some())
+        .bind(LAZY_BODY_BYTES)
+        .fetch_all(&mut *connection.acquire().await?)
         .await
         .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch views: {e}")))?;
 
     let mut result = Vec::new();
+    let mut unfetched = Vec::new();
     for row in rows {
         let schema: String = row.get("schemaname");
         let name: String = row.get(name_column);
-        let definition: String = row.get("definition");
+        let definition: Option<String> = row.get("definition");
+        let definition_hash: Option<String> = row.get("definition_hash");
         let owner: String = row.get("owner");
-
+        let reloptions: Vec<String> = row.get("reloptions");
+
+        let query = match definition_hash {
+            Some(hash) => known
+                .and_then(|known| known.get(&qualified_name(&schema, &name)))
+                .and_then(|known| known_body(&known.query, &hash, view_query))
+                .unwrap_or_else(|| {
+                    unfetched.push((row.get::<i64, _>("oid"), result.len()));
+                    String::new()
+                }),
+            None => view_query(definition.as_deref().unwrap_or_default()),
+        };
         result.push(View {
             name,
             schema,
-            query: normalize_sql_whitespace(definition.trim_end_matches(';')),
+            query,
             materialized,
             owner: Some(owner),
             grants: Vec::new(),
-            // TODO: read view comment from pg_description
-            comment: None,
+            comment: row.get("comment"),
+            security_barrier: reloption_enabled(&reloptions, "security_barrier"),
+            security_invoker: reloption_enabled(&reloptions, "security_invoker"),
+            check_option: reloption_value(&reloptions, "check_option")
+                .and_then(ViewCheckOption::from_sql_str),
         });
     }
+
+    if !unfetched.is_empty() {
+        let oids: Vec<i64> = unfetched.iter().map(|(oid, _)| *oid).collect();
+        let rows = sqlx::query(
+            "SELECT c.oid::bigint AS oid, pg_get_viewdef(c.oid) AS definition \
+             FROM pg_class c WHERE c.oid::bigint = ANY($1)",
+        )
+        .bind(&oids)
+        .fetch_all(&mut *connection.acquire().await?)
+        .await
+        .map_err(|e| {
+            SchemaError::DatabaseError(format!("Failed to fetch view definitions: {e}"))
+        })?;
+        let definitions: BTreeMap<i64, String> = rows
+            .into_iter()
+            .map(|row| (row.get("oid"), row.get("definition")))
+            .collect();
+        for (oid, index) in unfetched {
+            if let Some(definition) = definitions.get(&oid) {
+                result[index].query = view_query(definition);
+            }
+        }
+    }
     Ok(result)
 }
 
+/// Whether a boolean storage parameter is switched on in `pg_class.reloptions`.
+/// Entries are stored as `key=value` with the value spelled as the user wrote it.
+fn reloption_enabled(reloptions: &[String], key: &str) -> bool {
+    reloption_value(reloptions, key).is_some_and(|value| {
+        matches!(
+            value.to_lowercase().as_str(),
+            "true" | "on" | "yes" | "1" | "t" | "y"
+        )
+    })
+}
+
+/// Raw value of a storage parameter in `pg_class.reloptions`, if present.
+fn reloption_value<'a>(reloptions: &'a [String], key: &str) -> Option<&'a str> {
+    reloptions.iter().find_map(|option| {
+        option
+            .split_once('=')
+            .filter(|(name, _)| *name == key)
+            .map(|(_, value)| value)
+    })
+}
+
 async fn introspect_views(
     connection: &PgConnection,
     target_schemas: &[String],
     include_extension_objects: bool,
+    known: Option<&BTreeMap<String, View>>,
 ) -> Result<BTreeMap<String, View>> {
     let mut views = BTreeMap::new();
 
@@ -2071,8 +2689,11 @@ async fn introspect_views(
         connection,
         target_schemas,
         include_extension_objects,
+        known,
         r#"
-        SELECT v.schemaname, v.viewname, v.definition, r.rolname AS owner
+        SELECT c.oid::bigint AS oid, v.schemaname, v.viewname, {definition}, r.rolname AS owner,
+               COALESCE(c.reloptions, '{}'::text[]) AS reloptions,
+               obj_description(c.oid, 'pg_class') AS comment
         FROM pg_views v
         JOIN pg_class c ON c.relname = v.viewname
         JOIN pg_namespace n ON c.relnamespace = n.oid AND n.nspname = v.schemaname
@@ -2097,8 +2718,11 @@ async fn introspect_views(
         connection,
         target_schemas,
         include_extension_objects,
+        known,
         r#"
-        SELECT v.schemaname, v.matviewname, v.definition, r.rolname AS owner
+        SELECT c.oid::bigint AS oid, v.schemaname, v.matviewname, {definition}, r.rolname AS owner,
+               COALESCE(c.reloptions, '{}'::text[]) AS reloptions,
+               obj_description(c.oid, 'pg_class') AS comment
         FROM pg_matviews v
         JOIN pg_class c ON c.relname = v.matviewname
         JOIN pg_namespace n ON c.relnamespace = n.oid AND n.nspname = v.schemaname
@@ -2159,7 +2783,8 @@ async fn introspect_triggers(
             t.tgnewtable AS new_table_name,
             t.tgconstraint <> 0 AS is_constraint,
             t.tgdeferrable AS is_deferrable,
-            t.tginitdeferred AS is_initially_deferred
+            t.tginitdeferred AS is_initially_deferred,
+            obj_description(t.oid, 'pg_trigger') AS comment
         FROM pg_trigger t
         JOIN pg_class c ON t.tgrelid = c.oid
         JOIN pg_namespace ns ON c.relnamespace = ns.oid
@@ -2178,7 +2803,7 @@ async fn introspect_triggers(
     )
     .bind(target_schemas)
     .bind(include_extension_objects)
-    .fetch_all(connection.pool())
+    .fetch_all(&mut *connection.acquire().await?)
     .await
     .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch triggers: {e}")))?;
 
@@ -2258,8 +2883,7 @@ async fn introspect_triggers(
             is_constraint,
             deferrable,
             initially_deferred,
-            // TODO: read trigger comment from pg_description
-            comment: None,
+            comment: row.get("comment"),
         };
 
         let key = format!("{table_schema}.{table_name}.{trigger_name}");
@@ -2298,12 +2922,12 @@ fn decode_trigger_args(raw: &[u8], nargs: i16) -> Result<Vec<String>> {
 }
 
 fn extract_when_clause(trigger_def: &str) -> Option<String> {
-    let upper = trigger_def.to_uppercase();
+    let upper = trigger_def.to_ascii_uppercase();
     if let Some(when_pos) = upper.find(" WHEN (") {
         let after_when = &trigger_def[when_pos + 7..];
         let mut depth = 1;
         let mut end_pos = 0;
-        for (i, c) in after_when.chars().enumerate() {
+        for (i, c) in after_when.char_indices() {
             match c {
                 '(' => depth += 1,
                 ')' => {
@@ -2343,7 +2967,8 @@ async fn introspect_sequences(
             c.relname as owned_table,
             cn.nspname as owned_schema,
             a.attname as owned_column,
-            r.rolname as owner
+            r.rolname as owner,
+            obj_description(seq_class.oid, 'pg_class') as comment
         FROM pg_sequences s
         JOIN pg_namespace n ON n.nspname = s.schemaname
         LEFT JOIN pg_class seq_class ON seq_class.relname = s.sequencename
@@ -2351,7 +2976,9 @@ async fn introspect_sequences(
             AND seq_class.relkind = 'S'
         LEFT JOIN pg_roles r ON seq_class.relowner = r.oid
         LEFT JOIN pg_depend d ON d.objid = seq_class.oid
-            AND d.deptype = 'a'
+            AND d.classid = 'pg_class'::regclass
+            AND d.refclassid = 'pg_class'::regclass
+            AND d.deptype IN ('a', 'i')
         LEFT JOIN pg_class c ON c.oid = d.refobjid
         LEFT JOIN pg_namespace cn ON cn.oid = c.relnamespace
         LEFT JOIN pg_attribute a ON a.attrelid = d.refobjid
@@ -2366,7 +2993,7 @@ async fn introspect_sequences(
     )
     .bind(target_schemas)
     .bind(include_extension_objects)
-    .fetch_all(connection.pool())
+    .fetch_all(&mut *connection.acquire().await?)
     .await
     .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch sequences: {e}")))?;
 
@@ -2419,8 +3046,7 @@ async fn introspect_sequences(
                 owned_by,
                 owner,
                 grants: Vec::new(),
-                // TODO: read sequence comment from pg_description
-                comment: None,
+                comment: row.get("comment"),
             },
         );
     }
@@ -2477,7 +3103,7 @@ where
 {
     let rows = sqlx::query(sql)
         .bind(target_schemas)
-        .fetch_all(connection.pool())
+        .fetch_all(&mut *connection.acquire().await?)
         .await
         .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch {context}: {e}")))?;
 
@@ -2716,7 +3342,7 @@ async fn introspect_default_privileges(
         "#,
     )
     .bind(target_schemas)
-    .fetch_all(connection.pool())
+    .fetch_all(&mut *connection.acquire().await?)
     .await
     .map_err(|e| SchemaError::DatabaseError(format!("Failed to fetch default privileges: {e}")))?;
 
@@ -2784,6 +3410,26 @@ async fn introspect_default_privileges(
 mod tests {
     use super::*;
 
+    #[test]
+    fn known_body_requires_matching_hash_of_processed_text() {
+        use sha2::{Digest, Sha256};
+
+        let hash = |text: &str| hex::encode(Sha256::digest(text.as_bytes()));
+        let trim = |body: &str| body.trim().to_string();
+
+        assert_eq!(
+            known_body("SELECT 1", &hash("SELECT 1"), trim),
+            Some("SELECT 1".to_string())
+        );
+        assert_eq!(known_body("SELECT 1", &hash("SELECT 2"), trim), None);
+        // Unprocessed text proves nothing about the database's processed copy.
+        assert_eq!(known_body(" SELECT 1\n", &hash(" SELECT 1\n"), trim), None);
+        assert_eq!(
+            known_body("SELECT a FROM t;", &hash("SELECT a FROM t;"), view_query),
+            None
+        );
+    }
+
     #[test]
     fn split_arguments_handles_commas_in_types() {
         let args = split_arguments("p_amount numeric(10,2), p_name text");
@@ -2839,6 +3485,14 @@ mod tests {
         assert_eq!(args[1].name, Some("p_enterprise_id".to_string()));
     }
 
+    #[test]
+    fn parse_function_arguments_reads_variadic_mode() {
+        let args = parse_function_arguments("sep text, VARIADIC parts text[]");
+        assert_eq!(args[1].name.as_deref(), Some("parts"));
+        assert_eq!(args[1].data_type, "text[]");
+        assert_eq!(args[1].mode, ArgMode::Variadic);
+    }
+
     #[test]
     fn parse_function_arguments_handles_unquoted_names() {
         let args = parse_function_arguments("role_name text, enterprise_id uuid");
@@ -2947,6 +3601,20 @@ mod tests {
         assert_eq!(normalize_proconfig_value("'64MB'"), "'64MB'");
     }
 
+    #[test]
+    fn reloption_enabled_accepts_boolean_spellings() {
+        let options = vec![
+            "security_barrier=on".to_string(),
+            "security_invoker=false".to_string(),
+            "check_option=local".to_string(),
+        ];
+        assert!(reloption_enabled(&options, "security_barrier"));
+        assert!(!reloption_enabled(&options, "security_invoker"));
+        assert!(!reloption_enabled(&options, "check_option"));
+        assert!(!reloption_enabled(&[], "security_barrier"));
+        assert_eq!(reloption_value(&options, "check_option"), Some("local"));
+    }
+
     #[test]
     fn map_pg_type_domain_based_on_numeric_returns_user_defined() {
         let result =
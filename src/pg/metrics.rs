@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Serialize, Serializer};
+
+/// Timings gathered while introspecting a database, for diagnosing slow plans
+/// against large or distant databases.
+///
+/// Introspection categories run concurrently and share the connection pool,
+/// so per-category durations overlap and include time spent waiting for a
+/// pooled connection; `pool_wait` is the sum of those waits.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Metrics {
+    /// Wall time of each introspection category, such as `tables` or `columns`.
+    #[serde(serialize_with = "serialize_millis_map", rename = "introspection_ms")]
+    pub introspection: BTreeMap<String, Duration>,
+    /// Wall time of the whole introspection pass.
+    #[serde(serialize_with = "serialize_millis", rename = "introspection_total_ms")]
+    pub introspection_total: Duration,
+    /// Number of queries sent to the database.
+    pub query_count: usize,
+    /// Total time queries waited to check a connection out of the pool.
+    #[serde(serialize_with = "serialize_millis", rename = "pool_wait_ms")]
+    pub pool_wait: Duration,
+}
+
+impl fmt::Display for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Introspection: {} in {} queries (pool wait {})",
+            format_duration(self.introspection_total),
+            self.query_count,
+            format_duration(self.pool_wait)
+        )?;
+        let mut categories: Vec<(&String, &Duration)> = self.introspection.iter().collect();
+        categories.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        for (category, duration) in categories {
+            writeln!(f, "  {category:<28} {}", format_duration(*duration))?;
+        }
+        Ok(())
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.1}ms", duration.as_secs_f64() * 1000.0)
+}
+
+fn millis(duration: &Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn serialize_millis<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(millis(duration))
+}
+
+fn serialize_millis_map<S: Serializer>(
+    durations: &BTreeMap<String, Duration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(durations.iter().map(|(k, v)| (k, millis(v))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Metrics {
+        Metrics {
+            introspection: BTreeMap::from([
+                ("columns".to_string(), Duration::from_millis(40)),
+                ("tables".to_string(), Duration::from_millis(12)),
+            ]),
+            introspection_total: Duration::from_millis(45),
+            query_count: 2,
+            pool_wait: Duration::from_micros(2500),
+        }
+    }
+
+    #[test]
+    fn report_lists_slowest_categories_first() {
+        let report = sample().to_string();
+        assert!(
+            report.starts_with("Introspection: 45.0ms in 2 queries (pool wait 2.5ms)"),
+            "{report}"
+        );
+        let columns = report.find("columns").unwrap();
+        let tables = report.find("tables").unwrap();
+        assert!(columns < tables, "{report}");
+    }
+
+    #[test]
+    fn serializes_durations_as_milliseconds() {
+        let json = serde_json::to_value(sample()).unwrap();
+        assert_eq!(json["introspection_ms"]["columns"], 40.0);
+        assert_eq!(json["introspection_total_ms"], 45.0);
+        assert_eq!(json["pool_wait_ms"], 2.5);
+        assert_eq!(json["query_count"], 2);
+    }
+}
@@ -1,7 +1,15 @@
 pub mod connection;
+pub mod database_url;
 pub mod introspect;
+pub mod metrics;
+pub mod retention;
+pub mod sequence_values;
 pub mod sqlgen;
+pub mod unmanaged;
 
 pub use connection::PgConnection;
+pub use database_url::DatabaseUrl;
 pub use introspect::introspect_schema;
+pub use metrics::Metrics;
 pub use sqlgen::{generate_sql, quote_ident};
+pub use unmanaged::{count_unmanaged_objects, UnmanagedObjects};
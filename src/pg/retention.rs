@@ -0,0 +1,116 @@
+use crate::model::{PartitionBound, PartitionStrategy, PgType, Schema};
+use crate::pg::connection::PgConnection;
+use crate::util::{Result, SchemaError};
+use sqlx::Row;
+
+/// The partitions in `current` that have outlived the retention `target`
+/// declares for their parent: range partitions on a single date or timestamp
+/// column whose upper bound is older than the retention interval, as the
+/// database's clock and time zone evaluate it. Partitions `target` declares
+/// itself, and those of a parent keyed on anything else, are kept.
+pub async fn expired_partitions(
+    connection: &PgConnection,
+    current: &Schema,
+    target: &Schema,
+) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    let mut bounds = Vec::new();
+    let mut intervals = Vec::new();
+    for (key, partition) in &current.partitions {
+        let parent = format!("{}.{}", partition.parent_schema, partition.parent_name);
+        let Some(interval) = target.partition_retention.get(&parent) else {
+            continue;
+        };
+        if target.partitions.contains_key(key) || !partitioned_by_time(current, &parent) {
+            continue;
+        }
+        let PartitionBound::Range { to, .. } = &partition.bound else {
+            continue;
+        };
+        // MAXVALUE and numeric bounds are not quoted; only literals can hold
+        // a timestamp.
+        let [upper] = to.as_slice() else {
+            continue;
+        };
+        let Some(upper) = upper
+            .strip_prefix('\'')
+            .and_then(|upper| upper.strip_suffix('\''))
+        else {
+            continue;
+        };
+        names.push(key.clone());
+        bounds.push(upper.replace("''", "'"));
+        intervals.push(interval.clone());
+    }
+    if names.is_empty() {
+        return Ok(names);
+    }
+
+    let rows = sqlx::query(
+        r#"
+        SELECT name
+        FROM unnest($1::text[], $2::text[], $3::text[]) AS p(name, upper_bound, retention)
+        WHERE upper_bound::timestamptz <= now() - retention::interval
+        ORDER BY name
+        "#,
+    )
+    .bind(&names)
+    .bind(&bounds)
+    .bind(&intervals)
+    .fetch_all(connection.pool())
+    .await
+    .map_err(|e| {
+        SchemaError::DatabaseError(format!("Failed to evaluate partition retention: {e}"))
+    })?;
+
+    Ok(rows.into_iter().map(|row| row.get("name")).collect())
+}
+
+/// Whether the table `parent` is range-partitioned on a single column of a
+/// type its bounds can be compared with the clock as: `date`, `timestamp` or
+/// `timestamptz`.
+fn partitioned_by_time(schema: &Schema, parent: &str) -> bool {
+    let Some(table) = schema.tables.get(parent) else {
+        return false;
+    };
+    let Some(key) = &table.partition_by else {
+        return false;
+    };
+    let [column] = key.columns.as_slice() else {
+        return false;
+    };
+    key.strategy == PartitionStrategy::Range
+        && key.expressions.is_empty()
+        && table
+            .columns
+            .get(column.trim_matches('"'))
+            .is_some_and(|column| {
+                matches!(
+                    column.data_type,
+                    PgType::Date | PgType::Timestamp | PgType::TimestampTz
+                )
+            })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_sql_string;
+
+    #[test]
+    fn only_time_keyed_range_partitions_expire() {
+        let schema = parse_sql_string(
+            "CREATE TABLE public.events (id bigint, at timestamptz) PARTITION BY RANGE (at);
+             CREATE TABLE public.days (id bigint, day date) PARTITION BY RANGE (day);
+             CREATE TABLE public.codes (id bigint, code text) PARTITION BY RANGE (code);
+             CREATE TABLE public.regions (id bigint, at date) PARTITION BY LIST (at);",
+        )
+        .unwrap();
+
+        assert!(partitioned_by_time(&schema, "public.events"));
+        assert!(partitioned_by_time(&schema, "public.days"));
+        assert!(!partitioned_by_time(&schema, "public.codes"));
+        assert!(!partitioned_by_time(&schema, "public.regions"));
+        assert!(!partitioned_by_time(&schema, "public.missing"));
+    }
+}
@@ -0,0 +1,76 @@
+use crate::pg::connection::PgConnection;
+use crate::util::{Result, SchemaError};
+use sqlx::Row;
+use std::collections::BTreeMap;
+
+/// The value each sequence in `target_schemas` has reached, keyed by
+/// qualified name. Sequences that were never advanced (or whose value the
+/// connecting role may not read) have none and are left out.
+pub async fn sequence_values(
+    connection: &PgConnection,
+    target_schemas: &[String],
+) -> Result<BTreeMap<String, i64>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT schemaname, sequencename, last_value
+        FROM pg_sequences
+        WHERE schemaname = ANY($1::text[])
+          AND last_value IS NOT NULL
+        "#,
+    )
+    .bind(target_schemas)
+    .fetch_all(connection.pool())
+    .await
+    .map_err(|e| SchemaError::DatabaseError(format!("Failed to read sequence values: {e}")))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let schema: String = row.get("schemaname");
+            let name: String = row.get("sequencename");
+            (format!("{schema}.{name}"), row.get("last_value"))
+        })
+        .collect())
+}
+
+/// The declared sequence values `current` has not reached yet. Sequences are
+/// only ever moved forward, so a database that is ahead of the sources keeps
+/// the keys it has handed out.
+pub fn lagging_sequences(
+    current: &BTreeMap<String, i64>,
+    declared: &BTreeMap<String, i64>,
+) -> Vec<(String, i64)> {
+    declared
+        .iter()
+        .filter(|(name, value)| current.get(*name).is_none_or(|reached| reached < *value))
+        .map(|(name, value)| (name.clone(), *value))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lagging_sequences_only_move_forward() {
+        let current = BTreeMap::from([
+            ("public.ahead_seq".to_string(), 500),
+            ("public.behind_seq".to_string(), 10),
+            ("public.equal_seq".to_string(), 42),
+        ]);
+        let declared = BTreeMap::from([
+            ("public.ahead_seq".to_string(), 100),
+            ("public.behind_seq".to_string(), 200),
+            ("public.equal_seq".to_string(), 42),
+            ("public.unused_seq".to_string(), 7),
+        ]);
+
+        assert_eq!(
+            lagging_sequences(&current, &declared),
+            vec![
+                ("public.behind_seq".to_string(), 200),
+                ("public.unused_seq".to_string(), 7),
+            ]
+        );
+    }
+}
@@ -1,17 +1,153 @@
+use std::collections::BTreeSet;
+
 use crate::diff::{
     ColumnChanges, CommentObjectType, DomainChanges, EnumValuePosition, GrantObjectKind,
-    MigrationOp, OwnerObjectKind, PolicyChanges, SequenceChanges,
+    IdentityChange, MigrationOp, OwnerObjectKind, PolicyChanges, SequenceChanges,
+    ViewOptionChanges,
 };
 use crate::model::{
-    parse_qualified_name, versioned_schema_name, Aggregate, AggregateParallel, ArgMode,
-    CheckConstraint, Column, Domain, ExclusionConstraint, ForeignKey, Function, Index, IndexType,
-    Partition, PartitionBound, PartitionStrategy, PgType, Policy, PolicyCommand, Privilege,
-    QualifiedName, ReferentialAction, SecurityType, Sequence, SequenceDataType, Table, Trigger,
-    TriggerEnabled, TriggerEvent, TriggerTiming, VersionView, View, Volatility,
+    config_value_items, parse_qualified_name, qualified_name, versioned_schema_name, Aggregate,
+    AggregateParallel, ArgMode, CheckConstraint, Column, Domain, ExclusionConstraint, ForeignKey,
+    Function, Identity, IdentityGeneration, Index, IndexColumnOptions, IndexType, Partition,
+    PartitionBound, PartitionStrategy, PgType, Policy, PolicyCommand, Privilege, QualifiedName,
+    ReferentialAction, ReplicaIdentity, SecurityType, Sequence, SequenceDataType, Table, Trigger,
+    TriggerEnabled, TriggerEvent, TriggerTiming, UniqueConstraint, VersionView, View, Volatility,
+    RAW_OBJECTS_TABLE, ROLE_KEYWORDS,
 };
+use crate::parser::SourceLocations;
 
 pub fn generate_sql(ops: &[MigrationOp]) -> Vec<String> {
-    ops.iter().flat_map(generate_op_sql).collect()
+    generate_sql_iter(ops).collect()
+}
+
+/// Generates the statements for `ops` lazily, one operation at a time, so
+/// callers that write or execute statements as they go never hold the whole
+/// script in memory.
+pub fn generate_sql_iter(ops: &[MigrationOp]) -> impl Iterator<Item = String> + '_ {
+    ops.iter().flat_map(generate_op_sql)
+}
+
+/// Like [`generate_sql`], with each statement prefixed by the
+/// [`annotation`] of the operation it came from.
+pub fn generate_annotated_sql(ops: &[MigrationOp], locations: &SourceLocations) -> Vec<String> {
+    ops.iter()
+        .flat_map(|op| {
+            let annotation = annotation(op, locations);
+            generate_op_sql(op)
+                .into_iter()
+                .map(move |statement| format!("{annotation}\n{statement}"))
+        })
+        .collect()
+}
+
+/// Comment naming an operation's kind and object, and the schema source
+/// defining that object when `locations` has it:
+/// `-- pgmold: AddColumn public.users.email (schema/users.sql:12)`.
+pub fn annotation(op: &MigrationOp, locations: &SourceLocations) -> String {
+    let mut comment = format!("-- pgmold: {}", op.kind());
+    if let Some(object) = op.object() {
+        comment.push(' ');
+        comment.push_str(&object);
+    }
+    if let Some(location) = op.subject().and_then(|subject| locations.get(&subject)) {
+        comment.push_str(&format!(" ({location})"));
+    }
+    comment
+}
+
+/// Options for [`generate_sql_with_options`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SqlOptions {
+    /// Append `ANALYZE` for each of the [`rewritten_tables`], so query plans
+    /// see statistics for the new rows rather than the old ones.
+    pub analyze_rewritten_tables: bool,
+    /// Create materialized views `WITH NO DATA` and populate them with the
+    /// [`materialized_view_refreshes`] after every other statement, so a
+    /// large view does not hold up the rest of the migration.
+    pub defer_materialized_view_data: bool,
+}
+
+/// Like [`generate_sql`], followed by the maintenance statements `options`
+/// asks for.
+pub fn generate_sql_with_options(ops: &[MigrationOp], options: &SqlOptions) -> Vec<String> {
+    let mut statements: Vec<String> = ops
+        .iter()
+        .flat_map(|op| generate_op_sql_with_options(op, options))
+        .collect();
+    if options.defer_materialized_view_data {
+        statements.extend(generate_sql(&materialized_view_refreshes(ops)));
+    }
+    if options.analyze_rewritten_tables {
+        statements.extend(analyze_statements(ops));
+    }
+    statements
+}
+
+/// Tables whose rows `ops` rewrite: a column type change or a new stored
+/// generated column rewrites the whole table, and a backfill updates every row.
+pub fn rewritten_tables(ops: &[MigrationOp]) -> BTreeSet<QualifiedName> {
+    ops.iter()
+        .filter_map(|op| match op {
+            MigrationOp::AlterColumn { table, changes, .. } if changes.data_type.is_some() => {
+                Some(table.clone())
+            }
+            MigrationOp::AddColumn { table, column } if column.generated.is_some() => {
+                Some(table.clone())
+            }
+            MigrationOp::BackfillHint { table, .. } => Some(table.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Tables a backfill in `ops` leaves full of dead rows. `VACUUM` cannot run
+/// inside the migration transaction, so these are reported, not planned.
+pub fn vacuum_advisable_tables(ops: &[MigrationOp]) -> BTreeSet<QualifiedName> {
+    ops.iter()
+        .filter_map(|op| match op {
+            MigrationOp::BackfillHint { table, .. } => Some(table.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The statements for one operation, with materialized views created
+/// `WITH NO DATA` when `options` defers their data.
+pub fn generate_op_sql_with_options(op: &MigrationOp, options: &SqlOptions) -> Vec<String> {
+    match op {
+        MigrationOp::CreateView(view) if options.defer_materialized_view_data => {
+            generate_view_ddl(view, false, true)
+        }
+        MigrationOp::AlterView { new_view, .. } if options.defer_materialized_view_data => {
+            generate_view_ddl(new_view, true, true)
+        }
+        _ => generate_op_sql(op),
+    }
+}
+
+/// A [`MigrationOp::RefreshMaterializedView`] for each materialized view
+/// `ops` create or recreate.
+pub fn materialized_view_refreshes(ops: &[MigrationOp]) -> Vec<MigrationOp> {
+    ops.iter()
+        .filter_map(|op| match op {
+            MigrationOp::CreateView(view) | MigrationOp::AlterView { new_view: view, .. }
+                if view.materialized =>
+            {
+                Some(MigrationOp::RefreshMaterializedView {
+                    name: qualified_name(&view.schema, &view.name),
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// `ANALYZE` for each of the [`rewritten_tables`] of `ops`.
+pub fn analyze_statements(ops: &[MigrationOp]) -> Vec<String> {
+    rewritten_tables(ops)
+        .iter()
+        .map(|table| format!("ANALYZE {};", quote_qualified(&table.schema, &table.name)))
+        .collect()
 }
 
 fn generate_op_sql(op: &MigrationOp) -> Vec<String> {
@@ -22,10 +158,11 @@ fn generate_op_sql(op: &MigrationOp) -> Vec<String> {
                 quote_ident(&pg_schema.name)
             )]
         }
-        MigrationOp::DropSchema(name) => {
+        MigrationOp::DropSchema { name, cascade } => {
             vec![format!(
-                "DROP SCHEMA IF EXISTS {} CASCADE;",
-                quote_ident(name)
+                "DROP SCHEMA IF EXISTS {}{};",
+                quote_ident(name),
+                if *cascade { " CASCADE" } else { "" }
             )]
         }
 
@@ -45,6 +182,22 @@ fn generate_op_sql(op: &MigrationOp) -> Vec<String> {
             vec![format!("DROP EXTENSION IF EXISTS {};", quote_ident(name))]
         }
 
+        MigrationOp::SetExtensionSchema { name, schema, .. } => {
+            vec![format!(
+                "ALTER EXTENSION {} SET SCHEMA {};",
+                quote_ident(name),
+                quote_ident(schema)
+            )]
+        }
+
+        MigrationOp::UpdateExtension { name, version, .. } => {
+            vec![format!(
+                "ALTER EXTENSION {} UPDATE TO '{}';",
+                quote_ident(name),
+                escape_string(version)
+            )]
+        }
+
         MigrationOp::CreateServer(server) => {
             vec![generate_create_server(server)]
         }
@@ -126,11 +279,13 @@ fn generate_op_sql(op: &MigrationOp) -> Vec<String> {
         }
 
         MigrationOp::AddColumn { table, column } => {
-            vec![format!(
+            let mut statements = vec![format!(
                 "ALTER TABLE {} ADD COLUMN {};",
                 quote_qualified(&table.schema, &table.name),
                 format_column(column)
-            )]
+            )];
+            statements.extend(generate_column_settings(&table.schema, &table.name, column));
+            statements
         }
 
         MigrationOp::DropColumn { table, column } => {
@@ -164,15 +319,7 @@ fn generate_op_sql(op: &MigrationOp) -> Vec<String> {
         }
 
         MigrationOp::AddIndex { table, index } => {
-            if index.is_constraint {
-                vec![generate_add_unique_constraint(
-                    &table.schema,
-                    &table.name,
-                    index,
-                )]
-            } else {
-                vec![generate_create_index(&table.schema, &table.name, index)]
-            }
+            vec![generate_create_index(&table.schema, &table.name, index)]
         }
 
         MigrationOp::DropIndex { table, index_name } => {
@@ -182,6 +329,17 @@ fn generate_op_sql(op: &MigrationOp) -> Vec<String> {
             )]
         }
 
+        MigrationOp::AddUniqueConstraint {
+            table,
+            unique_constraint,
+        } => {
+            vec![generate_add_unique_constraint(
+                &table.schema,
+                &table.name,
+                unique_constraint,
+            )]
+        }
+
         MigrationOp::DropUniqueConstraint {
             table,
             constraint_name,
@@ -212,6 +370,20 @@ fn generate_op_sql(op: &MigrationOp) -> Vec<String> {
             )]
         }
 
+        MigrationOp::AlterForeignKeyDeferral {
+            table,
+            foreign_key_name,
+            deferrable,
+            initially_deferred,
+        } => {
+            vec![format!(
+                "ALTER TABLE {} ALTER CONSTRAINT {} {};",
+                quote_qualified(&table.schema, &table.name),
+                quote_ident(foreign_key_name),
+                deferral_clause(*deferrable, *initially_deferred)
+            )]
+        }
+
         MigrationOp::AddCheckConstraint {
             table,
             check_constraint,
@@ -284,6 +456,21 @@ fn generate_op_sql(op: &MigrationOp) -> Vec<String> {
             )]
         }
 
+        MigrationOp::SetReplicaIdentity { table, identity } => {
+            let identity = match identity {
+                None => "DEFAULT".to_string(),
+                Some(ReplicaIdentity::Full) => "FULL".to_string(),
+                Some(ReplicaIdentity::Nothing) => "NOTHING".to_string(),
+                Some(ReplicaIdentity::Index(index)) => {
+                    format!("USING INDEX {}", quote_ident(index))
+                }
+            };
+            vec![format!(
+                "ALTER TABLE {} REPLICA IDENTITY {identity};",
+                quote_qualified(&table.schema, &table.name)
+            )]
+        }
+
         MigrationOp::CreatePolicy(policy) => vec![generate_create_policy(policy)],
 
         MigrationOp::DropPolicy { table, name } => {
@@ -329,7 +516,7 @@ fn generate_op_sql(op: &MigrationOp) -> Vec<String> {
             )]
         }
 
-        MigrationOp::CreateView(view) => generate_view_ddl(view, false),
+        MigrationOp::CreateView(view) => generate_view_ddl(view, false, false),
 
         MigrationOp::DropView { name, materialized } => {
             let (schema, view_name) = parse_qualified_name(name);
@@ -345,7 +532,19 @@ fn generate_op_sql(op: &MigrationOp) -> Vec<String> {
             )]
         }
 
-        MigrationOp::AlterView { new_view, .. } => generate_view_ddl(new_view, true),
+        MigrationOp::AlterView { new_view, .. } => generate_view_ddl(new_view, true, false),
+
+        MigrationOp::AlterViewOptions { name, changes } => {
+            generate_alter_view_options(name, changes)
+        }
+
+        MigrationOp::RefreshMaterializedView { name } => {
+            let (schema, view_name) = parse_qualified_name(name);
+            vec![format!(
+                "REFRESH MATERIALIZED VIEW {};",
+                quote_qualified(&schema, &view_name)
+            )]
+        }
 
         MigrationOp::CreateTrigger(trigger) => {
             let mut statements = vec![generate_create_trigger(trigger)];
@@ -400,6 +599,14 @@ fn generate_op_sql(op: &MigrationOp) -> Vec<String> {
             vec![generate_alter_sequence(name, changes)]
         }
 
+        MigrationOp::SetSequenceValue { name, value } => {
+            let (schema, seq_name) = parse_qualified_name(name);
+            vec![format!(
+                "SELECT setval('{}', {value});",
+                escape_string(&quote_qualified(&schema, &seq_name))
+            )]
+        }
+
         MigrationOp::AlterOwner {
             object_kind,
             schema,
@@ -414,6 +621,42 @@ fn generate_op_sql(op: &MigrationOp) -> Vec<String> {
             new_owner,
         )],
 
+        MigrationOp::SetSchema {
+            object_kind,
+            schema,
+            name,
+            args,
+            new_schema,
+        } => vec![format!(
+            "ALTER {} {} SET SCHEMA {};",
+            owner_object_keyword(object_kind),
+            object_signature(schema, name, args),
+            quote_ident(new_schema)
+        )],
+
+        MigrationOp::RenameTable { table, new_name } => vec![format!(
+            "ALTER TABLE {} RENAME TO {};",
+            quote_qualified(&table.schema, &table.name),
+            quote_ident(new_name)
+        )],
+
+        MigrationOp::RenameSequence { sequence, new_name } => vec![format!(
+            "ALTER SEQUENCE {} RENAME TO {};",
+            quote_qualified(&sequence.schema, &sequence.name),
+            quote_ident(new_name)
+        )],
+
+        MigrationOp::RenameColumn {
+            table,
+            column,
+            new_name,
+        } => vec![format!(
+            "ALTER TABLE {} RENAME COLUMN {} TO {};",
+            quote_qualified(&table.schema, &table.name),
+            quote_ident(column),
+            quote_ident(new_name)
+        )],
+
         MigrationOp::CreateDomain(domain) => {
             vec![generate_create_domain(domain)]
         }
@@ -440,6 +683,47 @@ fn generate_op_sql(op: &MigrationOp) -> Vec<String> {
             )]
         }
 
+        MigrationOp::CreateIndexConcurrently { table, index } => {
+            vec![
+                generate_create_index(&table.schema, &table.name, index).replacen(
+                    " INDEX ",
+                    " INDEX CONCURRENTLY ",
+                    1,
+                ),
+            ]
+        }
+
+        MigrationOp::DropIndexConcurrently { table, index_name } => {
+            vec![format!(
+                "DROP INDEX CONCURRENTLY {};",
+                quote_qualified(&table.schema, index_name)
+            )]
+        }
+
+        MigrationOp::AddPrimaryKeyUsingIndex { table, index_name } => {
+            vec![format!(
+                "ALTER TABLE {} ADD CONSTRAINT {} PRIMARY KEY USING INDEX {};",
+                quote_qualified(&table.schema, &table.name),
+                quote_ident(index_name),
+                quote_ident(index_name)
+            )]
+        }
+
+        MigrationOp::AddUniqueConstraintUsingIndex {
+            table,
+            index_name,
+            deferrable,
+            initially_deferred,
+        } => {
+            vec![format!(
+                "ALTER TABLE {} ADD CONSTRAINT {} UNIQUE USING INDEX {}{};",
+                quote_qualified(&table.schema, &table.name),
+                quote_ident(index_name),
+                quote_ident(index_name),
+                added_deferral_clause(*deferrable, *initially_deferred)
+            )]
+        }
+
         MigrationOp::GrantPrivileges {
             object_kind,
             schema,
@@ -679,9 +963,41 @@ fn generate_op_sql(op: &MigrationOp) -> Vec<String> {
                 quote_qualified(version_schema, name)
             )]
         }
+
+        MigrationOp::ApplyRawObject(raw) => {
+            let (tracking_schema, _) = parse_qualified_name(RAW_OBJECTS_TABLE);
+            vec![
+                format!("CREATE SCHEMA IF NOT EXISTS {};", quote_ident(&tracking_schema)),
+                format!(
+                    "CREATE TABLE IF NOT EXISTS {} (name TEXT PRIMARY KEY, content_hash TEXT NOT NULL);",
+                    raw_objects_table()
+                ),
+                raw.sql.clone(),
+                format!(
+                    "INSERT INTO {} (name, content_hash) VALUES ('{}', '{}') \
+                     ON CONFLICT (name) DO UPDATE SET content_hash = EXCLUDED.content_hash;",
+                    raw_objects_table(),
+                    escape_string(&raw.name),
+                    raw.content_hash
+                ),
+            ]
+        }
+
+        MigrationOp::DropRawObject(name) => {
+            vec![format!(
+                "DELETE FROM {} WHERE name = '{}';",
+                raw_objects_table(),
+                escape_string(name)
+            )]
+        }
     }
 }
 
+fn raw_objects_table() -> String {
+    let (schema, name) = parse_qualified_name(RAW_OBJECTS_TABLE);
+    quote_qualified(&schema, &name)
+}
+
 fn generate_create_server(server: &crate::model::Server) -> String {
     let mut sql = format!("CREATE SERVER {}", quote_ident(&server.name));
     if let Some(ref server_type) = server.server_type {
@@ -753,16 +1069,20 @@ fn generate_create_table(table: &Table) -> Vec<String> {
         partition_clause
     ));
 
+    for column in table.columns.values() {
+        statements.extend(generate_column_settings(&table.schema, &table.name, column));
+    }
+
     for index in &table.indexes {
-        if index.is_constraint {
-            statements.push(generate_add_unique_constraint(
-                &table.schema,
-                &table.name,
-                index,
-            ));
-        } else {
-            statements.push(generate_create_index(&table.schema, &table.name, index));
-        }
+        statements.push(generate_create_index(&table.schema, &table.name, index));
+    }
+
+    for unique_constraint in &table.unique_constraints {
+        statements.push(generate_add_unique_constraint(
+            &table.schema,
+            &table.name,
+            unique_constraint,
+        ));
     }
 
     for foreign_key in &table.foreign_keys {
@@ -819,12 +1139,15 @@ fn generate_create_partition(partition: &Partition) -> String {
 fn generate_create_index(schema: &str, table: &str, index: &Index) -> String {
     let unique = if index.unique { "UNIQUE " } else { "" };
     let index_type = match index.index_type {
-        IndexType::BTree => "",
-        IndexType::Hash => " USING hash",
-        IndexType::Gin => " USING gin",
-        IndexType::Gist => " USING gist",
+        IndexType::BTree => String::new(),
+        ref method => format!(" USING {}", method.method()),
     };
 
+    let nulls = if index.nulls_not_distinct {
+        " NULLS NOT DISTINCT"
+    } else {
+        ""
+    };
     let where_clause = index
         .predicate
         .as_ref()
@@ -832,39 +1155,51 @@ fn generate_create_index(schema: &str, table: &str, index: &Index) -> String {
         .unwrap_or_default();
 
     format!(
-        "CREATE {}INDEX {} ON {}{} ({}){};",
+        "CREATE {}INDEX {} ON {}{} ({}){}{};",
         unique,
         quote_ident(&index.name),
         quote_qualified(schema, table),
         index_type,
-        format_index_column_list(&index.columns),
+        format_index_column_list(&index.columns, &index.column_options),
+        nulls,
         where_clause
     )
 }
 
-fn generate_add_unique_constraint(schema: &str, table: &str, index: &Index) -> String {
-    debug_assert!(
-        index.predicate.is_none(),
-        "unique constraints cannot have a WHERE predicate — use CREATE UNIQUE INDEX instead"
-    );
+fn generate_add_unique_constraint(
+    schema: &str,
+    table: &str,
+    unique_constraint: &UniqueConstraint,
+) -> String {
+    let nulls = if unique_constraint.nulls_not_distinct {
+        " NULLS NOT DISTINCT"
+    } else {
+        ""
+    };
     format!(
-        "ALTER TABLE {} ADD CONSTRAINT {} UNIQUE ({});",
+        "ALTER TABLE {} ADD CONSTRAINT {} UNIQUE{} ({}){};",
         quote_qualified(schema, table),
-        quote_ident(&index.name),
-        format_column_list(&index.columns)
+        quote_ident(&unique_constraint.name),
+        nulls,
+        format_column_list(&unique_constraint.columns),
+        added_deferral_clause(
+            unique_constraint.deferrable,
+            unique_constraint.initially_deferred
+        )
     )
 }
 
 fn generate_add_foreign_key(schema: &str, table: &str, foreign_key: &ForeignKey) -> String {
     format!(
-        "ALTER TABLE {} ADD CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {} ({}) ON DELETE {} ON UPDATE {};",
+        "ALTER TABLE {} ADD CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {} ({}) ON DELETE {} ON UPDATE {}{};",
         quote_qualified(schema, table),
         quote_ident(&foreign_key.name),
         format_column_list(&foreign_key.columns),
         quote_qualified(&foreign_key.referenced_schema, &foreign_key.referenced_table),
         format_column_list(&foreign_key.referenced_columns),
         format_referential_action(&foreign_key.on_delete),
-        format_referential_action(&foreign_key.on_update)
+        format_referential_action(&foreign_key.on_update),
+        added_deferral_clause(foreign_key.deferrable, foreign_key.initially_deferred)
     )
 }
 
@@ -898,16 +1233,6 @@ fn generate_add_exclusion_constraint(
         .map(|w| format!(" WHERE ({w})"))
         .unwrap_or_default();
 
-    let deferrable_clause = if exclusion_constraint.deferrable {
-        if exclusion_constraint.initially_deferred {
-            " DEFERRABLE INITIALLY DEFERRED"
-        } else {
-            " DEFERRABLE INITIALLY IMMEDIATE"
-        }
-    } else {
-        ""
-    };
-
     format!(
         "ALTER TABLE {} ADD CONSTRAINT {} EXCLUDE USING {} ({}){}{};",
         quote_qualified(schema, table),
@@ -915,10 +1240,33 @@ fn generate_add_exclusion_constraint(
         exclusion_constraint.index_method,
         elements.join(", "),
         where_clause,
-        deferrable_clause,
+        added_deferral_clause(
+            exclusion_constraint.deferrable,
+            exclusion_constraint.initially_deferred
+        ),
     )
 }
 
+/// The `DEFERRABLE` clause giving a constraint the deferral described by the
+/// flags.
+fn deferral_clause(deferrable: bool, initially_deferred: bool) -> &'static str {
+    match (deferrable, initially_deferred) {
+        (false, _) => "NOT DEFERRABLE",
+        (true, false) => "DEFERRABLE INITIALLY IMMEDIATE",
+        (true, true) => "DEFERRABLE INITIALLY DEFERRED",
+    }
+}
+
+/// [`deferral_clause`] with a leading space for a constraint being added,
+/// or nothing for the default of `NOT DEFERRABLE`.
+fn added_deferral_clause(deferrable: bool, initially_deferred: bool) -> String {
+    if deferrable {
+        format!(" {}", deferral_clause(deferrable, initially_deferred))
+    } else {
+        String::new()
+    }
+}
+
 fn generate_alter_column(
     table: &QualifiedName,
     column: &str,
@@ -926,6 +1274,28 @@ fn generate_alter_column(
 ) -> Vec<String> {
     let qualified = quote_qualified(&table.schema, &table.name);
     let mut statements = Vec::new();
+    let drop_default = format!(
+        "ALTER TABLE {} ALTER COLUMN {} DROP DEFAULT;",
+        qualified,
+        quote_ident(column)
+    );
+
+    // A column cannot have both an identity and a default, so an identity is
+    // dropped before anything else and added once the default is gone.
+    if let Some(IdentityChange::Drop) = changes.identity {
+        statements.push(generate_identity_change(
+            table,
+            column,
+            &IdentityChange::Drop,
+        ));
+    }
+
+    // The old default may not cast to the new type, so it is dropped before
+    // the type changes and the new one set afterwards.
+    let default_dropped_first = changes.data_type.is_some() && changes.default.is_some();
+    if default_dropped_first {
+        statements.push(drop_default.clone());
+    }
 
     if let Some(ref data_type) = changes.data_type {
         let type_str = format_pg_type(data_type);
@@ -965,19 +1335,87 @@ fn generate_alter_column(
                     value
                 ));
             }
-            None => {
-                statements.push(format!(
-                    "ALTER TABLE {} ALTER COLUMN {} DROP DEFAULT;",
-                    qualified,
-                    quote_ident(column)
-                ));
-            }
+            None if default_dropped_first => {}
+            None => statements.push(drop_default),
         }
     }
 
+    if let Some(target) = changes.statistics {
+        statements.push(generate_set_statistics(
+            &table.schema,
+            &table.name,
+            column,
+            target,
+        ));
+    }
+
+    if let Some(ref method) = changes.compression {
+        statements.push(generate_set_compression(
+            &table.schema,
+            &table.name,
+            column,
+            method.as_deref(),
+        ));
+    }
+
+    match changes.identity {
+        Some(ref change @ (IdentityChange::Add(_) | IdentityChange::Set(_))) => {
+            statements.push(generate_identity_change(table, column, change));
+        }
+        Some(IdentityChange::Drop) | None => {}
+    }
+
     statements
 }
 
+/// Settings `CREATE TABLE` and `ADD COLUMN` cannot carry, applied once the
+/// column exists.
+fn generate_column_settings(schema: &str, table: &str, column: &Column) -> Vec<String> {
+    let mut statements = Vec::new();
+    if column.statistics.is_some() {
+        statements.push(generate_set_statistics(
+            schema,
+            table,
+            &column.name,
+            column.statistics,
+        ));
+    }
+    if let Some(ref method) = column.compression {
+        statements.push(generate_set_compression(
+            schema,
+            table,
+            &column.name,
+            Some(method),
+        ));
+    }
+    statements
+}
+
+/// `None` resets the column to `default_statistics_target`.
+fn generate_set_statistics(schema: &str, table: &str, column: &str, target: Option<i32>) -> String {
+    format!(
+        "ALTER TABLE {} ALTER COLUMN {} SET STATISTICS {};",
+        quote_qualified(schema, table),
+        quote_ident(column),
+        target.unwrap_or(-1)
+    )
+}
+
+/// `None` resets the column to `default_toast_compression`.
+fn generate_set_compression(
+    schema: &str,
+    table: &str,
+    column: &str,
+    method: Option<&str>,
+) -> String {
+    format!(
+        "ALTER TABLE {} ALTER COLUMN {} SET COMPRESSION {};",
+        quote_qualified(schema, table),
+        quote_ident(column),
+        method.unwrap_or("default")
+    )
+}
+
 fn format_column(column: &Column) -> String {
     let mut parts = vec![quote_ident(&column.name), format_pg_type(&column.data_type)];
 
@@ -987,6 +1425,8 @@ fn format_column(column: &Column) -> String {
 
     if let Some(ref expr) = column.generated {
         parts.push(format!("GENERATED ALWAYS AS ({expr}) STORED"));
+    } else if let Some(ref identity) = column.identity {
+        parts.push(format_identity(identity));
     } else if let Some(ref default) = column.default {
         parts.push(format!("DEFAULT {default}"));
     }
@@ -994,6 +1434,79 @@ fn format_column(column: &Column) -> String {
     parts.join(" ")
 }
 
+fn format_identity(identity: &Identity) -> String {
+    let generation = format_identity_generation(identity.generation);
+    let options = identity_options(identity);
+    if options.is_empty() {
+        format!("GENERATED {generation} AS IDENTITY")
+    } else {
+        format!("GENERATED {generation} AS IDENTITY ({})", options.join(" "))
+    }
+}
+
+fn format_identity_generation(generation: IdentityGeneration) -> &'static str {
+    match generation {
+        IdentityGeneration::Always => "ALWAYS",
+        IdentityGeneration::ByDefault => "BY DEFAULT",
+    }
+}
+
+/// The sequence options an identity declares, as written in `AS IDENTITY
+/// (...)` and, each prefixed with `SET`, in `ALTER COLUMN`. They follow the
+/// `CREATE SEQUENCE` order, the only one the source parser accepts.
+fn identity_options(identity: &Identity) -> Vec<String> {
+    let mut options = Vec::new();
+    if let Some(increment) = identity.increment {
+        options.push(format!("INCREMENT BY {increment}"));
+    }
+    if let Some(min_value) = identity.min_value {
+        options.push(format!("MINVALUE {min_value}"));
+    }
+    if let Some(max_value) = identity.max_value {
+        options.push(format!("MAXVALUE {max_value}"));
+    }
+    if let Some(start) = identity.start {
+        options.push(format!("START WITH {start}"));
+    }
+    if let Some(cache) = identity.cache {
+        options.push(format!("CACHE {cache}"));
+    }
+    match identity.cycle {
+        Some(true) => options.push("CYCLE".to_string()),
+        Some(false) => options.push("NO CYCLE".to_string()),
+        None => {}
+    }
+    options
+}
+
+fn generate_identity_change(
+    table: &QualifiedName,
+    column: &str,
+    change: &IdentityChange,
+) -> String {
+    let prefix = format!(
+        "ALTER TABLE {} ALTER COLUMN {}",
+        quote_qualified(&table.schema, &table.name),
+        quote_ident(column)
+    );
+    match change {
+        IdentityChange::Add(identity) => format!("{prefix} ADD {};", format_identity(identity)),
+        IdentityChange::Drop => format!("{prefix} DROP IDENTITY IF EXISTS;"),
+        IdentityChange::Set(identity) => {
+            let mut clauses = vec![format!(
+                "SET GENERATED {}",
+                format_identity_generation(identity.generation)
+            )];
+            clauses.extend(
+                identity_options(identity)
+                    .into_iter()
+                    .map(|option| format!("SET {option}")),
+            );
+            format!("{prefix} {};", clauses.join(" "))
+        }
+    }
+}
+
 fn format_pg_type(pg_type: &PgType) -> String {
     match pg_type {
         PgType::Integer => "INTEGER".to_string(),
@@ -1074,15 +1587,33 @@ fn is_expression_column(column: &str) -> bool {
     column.contains('(')
 }
 
-fn format_index_column_list(columns: &[String]) -> String {
+fn format_index_column_list(columns: &[String], options: &[IndexColumnOptions]) -> String {
     columns
         .iter()
-        .map(|c| {
-            if is_expression_column(c) {
+        .enumerate()
+        .map(|(i, c)| {
+            let mut column = if is_expression_column(c) {
                 c.clone()
             } else {
                 quote_ident(c)
+            };
+            if let Some(options) = options.get(i) {
+                if let Some(collation) = &options.collation {
+                    column.push_str(&format!(" COLLATE {}", quote_ident(collation)));
+                }
+                if let Some(opclass) = &options.opclass {
+                    column.push_str(&format!(" {opclass}"));
+                }
+                if options.descending {
+                    column.push_str(" DESC");
+                }
+                match options.nulls_first {
+                    Some(true) => column.push_str(" NULLS FIRST"),
+                    Some(false) => column.push_str(" NULLS LAST"),
+                    None => {}
+                }
             }
+            column
         })
         .collect::<Vec<_>>()
         .join(", ")
@@ -1107,7 +1638,7 @@ fn quote_qualified(schema: &str, name: &str) -> String {
     format!("{}.{}", quote_ident(schema), quote_ident(name))
 }
 
-fn escape_string(value: &str) -> String {
+pub(crate) fn escape_string(value: &str) -> String {
     value.replace('\'', "''")
 }
 
@@ -1116,9 +1647,10 @@ fn escape_string(value: &str) -> String {
 /// Role names are only quoted if they contain special characters.
 /// The "public" pseudo-role is a keyword meaning "all roles" and must be unquoted.
 fn format_role_name(role: &str) -> String {
-    // PUBLIC is a keyword, not a role name
-    if role.eq_ignore_ascii_case("public") {
-        return "public".to_string();
+    // PUBLIC, CURRENT_USER and the like are keywords, not role names
+    let lowered = role.to_ascii_lowercase();
+    if ROLE_KEYWORDS.contains(&lowered.as_str()) {
+        return lowered;
     }
 
     // Check if role name is a simple identifier (doesn't need quoting)
@@ -1277,7 +1809,11 @@ fn generate_function_ddl(func: &Function, replace: bool) -> String {
     }
 
     for (k, v) in &func.config_params {
-        parts.push(format!("SET {k} = {v}"));
+        if v.eq_ignore_ascii_case("FROM CURRENT") {
+            parts.push(format!("SET {k} FROM CURRENT"));
+        } else {
+            parts.push(format!("SET {k} = {}", format_config_value(v)));
+        }
     }
 
     parts.push(format!("AS $${}$$;", func.body));
@@ -1285,6 +1821,31 @@ fn generate_function_ddl(func: &Function, replace: bool) -> String {
     parts.join(" ")
 }
 
+/// Quotes each item of a `SET` value that is not already quoted, an
+/// identifier or a number, since `proconfig` stores values such as `64MB`
+/// bare.
+fn format_config_value(value: &str) -> String {
+    config_value_items(value)
+        .into_iter()
+        .map(|item| {
+            let is_word = item
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+                && item
+                    .chars()
+                    .next()
+                    .is_some_and(|c| !c.is_ascii_digit() && c != '$');
+            let is_number = item.parse::<f64>().is_ok();
+            if item.starts_with('\'') || item.starts_with('"') || is_word || is_number {
+                item.to_string()
+            } else {
+                format!("'{}'", item.replace('\'', "''"))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 fn generate_aggregate_ddl(agg: &Aggregate) -> String {
     let args = if agg.args.is_empty() {
         "*".to_string()
@@ -1327,22 +1888,21 @@ fn generate_aggregate_ddl(agg: &Aggregate) -> String {
     )
 }
 
-fn generate_view_ddl(view: &View, replace: bool) -> Vec<String> {
+fn generate_view_ddl(view: &View, replace: bool, with_no_data: bool) -> Vec<String> {
     let qualified_name = quote_qualified(&view.schema, &view.name);
     if view.materialized {
+        let no_data_clause = if with_no_data { " WITH NO DATA" } else { "" };
+        let create_stmt = format!(
+            "CREATE MATERIALIZED VIEW {} AS {}{};",
+            qualified_name, view.query, no_data_clause
+        );
         if replace {
             vec![
                 format!("DROP MATERIALIZED VIEW IF EXISTS {};", qualified_name),
-                format!(
-                    "CREATE MATERIALIZED VIEW {} AS {};",
-                    qualified_name, view.query
-                ),
+                create_stmt,
             ]
         } else {
-            vec![format!(
-                "CREATE MATERIALIZED VIEW {} AS {};",
-                qualified_name, view.query
-            )]
+            vec![create_stmt]
         }
     } else {
         let create_stmt = if replace {
@@ -1350,13 +1910,75 @@ fn generate_view_ddl(view: &View, replace: bool) -> Vec<String> {
         } else {
             "CREATE VIEW"
         };
+        let mut options = Vec::new();
+        if view.security_barrier {
+            options.push("security_barrier = true");
+        }
+        if view.security_invoker {
+            options.push("security_invoker = true");
+        }
+        let with_clause = if options.is_empty() {
+            String::new()
+        } else {
+            format!(" WITH ({})", options.join(", "))
+        };
+        let check_option_clause = view
+            .check_option
+            .map(|check_option| format!(" WITH {} CHECK OPTION", check_option.as_sql_str()))
+            .unwrap_or_default();
         vec![format!(
-            "{} {} AS {};",
-            create_stmt, qualified_name, view.query
+            "{} {}{} AS {}{};",
+            create_stmt, qualified_name, with_clause, view.query, check_option_clause
         )]
     }
 }
 
+/// Generate `ALTER VIEW ... SET/RESET (...)` for view option changes.
+/// Disabled options are reset rather than set to false so the catalog matches
+/// a freshly created view.
+fn generate_alter_view_options(name: &str, changes: &ViewOptionChanges) -> Vec<String> {
+    let (schema, view_name) = parse_qualified_name(name);
+    let qualified = quote_qualified(&schema, &view_name);
+
+    let mut set = Vec::new();
+    let mut reset = Vec::new();
+    for (option, value) in [
+        ("security_barrier", changes.security_barrier),
+        ("security_invoker", changes.security_invoker),
+    ] {
+        match value {
+            Some(true) => set.push(format!("{option} = true")),
+            Some(false) => reset.push(option),
+            None => {}
+        }
+    }
+    match changes.check_option {
+        Some(Some(check_option)) => set.push(format!(
+            "check_option = {}",
+            check_option.as_sql_str().to_lowercase()
+        )),
+        Some(None) => reset.push("check_option"),
+        None => {}
+    }
+
+    let mut statements = Vec::new();
+    if !set.is_empty() {
+        statements.push(format!(
+            "ALTER VIEW {} SET ({});",
+            qualified,
+            set.join(", ")
+        ));
+    }
+    if !reset.is_empty() {
+        statements.push(format!(
+            "ALTER VIEW {} RESET ({});",
+            qualified,
+            reset.join(", ")
+        ));
+    }
+    statements
+}
+
 /// Generate DDL for a version view with column mappings.
 /// Version views are used in expand/contract migrations to expose multiple schema versions.
 fn generate_version_view_ddl(view: &VersionView) -> String {
@@ -1513,7 +2135,16 @@ fn generate_alter_owner(
     args: &Option<String>,
     new_owner: &str,
 ) -> String {
-    let object_type = match object_kind {
+    format!(
+        "ALTER {} {} OWNER TO {};",
+        owner_object_keyword(object_kind),
+        object_signature(schema, name, args),
+        format_role_name(new_owner)
+    )
+}
+
+fn owner_object_keyword(object_kind: &OwnerObjectKind) -> &'static str {
+    match object_kind {
         OwnerObjectKind::Table | OwnerObjectKind::Partition => "TABLE",
         OwnerObjectKind::View => "VIEW",
         OwnerObjectKind::MaterializedView => "MATERIALIZED VIEW",
@@ -1522,22 +2153,16 @@ fn generate_alter_owner(
         OwnerObjectKind::Aggregate => "AGGREGATE",
         OwnerObjectKind::Type => "TYPE",
         OwnerObjectKind::Domain => "DOMAIN",
-    };
+    }
+}
 
+/// The quoted name of an object, with the argument list for a function.
+fn object_signature(schema: &str, name: &str, args: &Option<String>) -> String {
     let qualified_name = quote_qualified(schema, name);
-
-    let full_name = if let Some(function_args) = args {
-        format!("{qualified_name}({function_args})")
-    } else {
-        qualified_name
-    };
-
-    format!(
-        "ALTER {} {} OWNER TO {};",
-        object_type,
-        full_name,
-        format_role_name(new_owner)
-    )
+    match args {
+        Some(function_args) => format!("{qualified_name}({function_args})"),
+        None => qualified_name,
+    }
 }
 
 fn generate_create_domain(domain: &Domain) -> String {
@@ -1768,7 +2393,7 @@ fn format_grant_object_name(kind: &GrantObjectKind, schema: &str, name: &str) ->
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::model::{EnumType, PrimaryKey, QualifiedName};
+    use crate::model::{EnumType, PrimaryKey, QualifiedName, ViewCheckOption};
     use std::collections::BTreeMap;
 
     #[test]
@@ -1792,6 +2417,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn raw_object_runs_its_sql_and_records_its_hash() {
+        let raw = crate::model::RawObject::new(
+            "o'brien_search",
+            "CREATE TEXT SEARCH CONFIGURATION obrien (COPY = english);\n",
+            Vec::new(),
+        );
+        let sql = generate_sql(&[MigrationOp::ApplyRawObject(raw.clone())]);
+        assert_eq!(sql.len(), 4);
+        assert_eq!(sql[0], "CREATE SCHEMA IF NOT EXISTS \"pgmold\";");
+        assert_eq!(
+            sql[2],
+            "CREATE TEXT SEARCH CONFIGURATION obrien (COPY = english);"
+        );
+        assert!(sql[3].starts_with(&format!(
+            "INSERT INTO \"pgmold\".\"raw_objects\" (name, content_hash) VALUES ('o''brien_search', '{}')",
+            raw.content_hash
+        )));
+
+        assert_eq!(
+            generate_sql(&[MigrationOp::DropRawObject("legacy".to_string())]),
+            vec!["DELETE FROM \"pgmold\".\"raw_objects\" WHERE name = 'legacy';"]
+        );
+    }
+
+    #[test]
+    fn annotated_sql_names_operation_and_source() {
+        let locations = SourceLocations::from([(
+            "public.users".to_string(),
+            crate::parser::SourceLocation {
+                file: "schema/users.sql".to_string(),
+                line: 12,
+            },
+        )]);
+        let ops = vec![
+            MigrationOp::DropColumn {
+                table: QualifiedName::new("public", "users"),
+                column: "email".to_string(),
+            },
+            MigrationOp::DropTable("public.sessions".to_string()),
+        ];
+
+        assert_eq!(
+            generate_annotated_sql(&ops, &locations),
+            vec![
+                "-- pgmold: DropColumn public.users.email (schema/users.sql:12)\nALTER TABLE \"public\".\"users\" DROP COLUMN \"email\" CASCADE;",
+                "-- pgmold: DropTable public.sessions\nDROP TABLE \"public\".\"sessions\";",
+            ]
+        );
+    }
+
     #[test]
     fn create_enum_generates_valid_sql() {
         let ops = vec![MigrationOp::CreateEnum(EnumType {
@@ -1832,6 +2508,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         }];
 
@@ -1870,6 +2549,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
         columns.insert(
@@ -1881,6 +2563,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
 
@@ -1894,10 +2579,12 @@ mod tests {
             }),
             foreign_keys: vec![],
             check_constraints: vec![],
+            unique_constraints: Vec::new(),
             exclusion_constraints: vec![],
             comment: None,
             row_level_security: false,
             force_row_level_security: false,
+            replica_identity: None,
             policies: vec![],
             partition_by: None,
 
@@ -1929,7 +2616,8 @@ mod tests {
                 unique: true,
                 index_type: IndexType::BTree,
                 predicate: None,
-                is_constraint: false,
+                nulls_not_distinct: false,
+                column_options: Vec::new(),
             },
         }];
 
@@ -1943,76 +2631,327 @@ mod tests {
 
     #[test]
     fn add_unique_constraint_generates_alter_table() {
-        let ops = vec![MigrationOp::AddIndex {
+        let ops = vec![MigrationOp::AddUniqueConstraint {
             table: QualifiedName::new("auth", "mfa_amr_claims"),
-            index: Index {
+            unique_constraint: UniqueConstraint {
                 name: "mfa_amr_claims_session_id_authentication_method_pkey".to_string(),
                 columns: vec![
                     "session_id".to_string(),
                     "authentication_method".to_string(),
                 ],
-                unique: true,
-                index_type: IndexType::BTree,
-                predicate: None,
-                is_constraint: true,
+                nulls_not_distinct: false,
+                deferrable: false,
+                initially_deferred: false,
+            },
+        }];
+
+        let sql = generate_sql(&ops);
+        assert_eq!(sql.len(), 1);
+        assert_eq!(
+            sql[0],
+            "ALTER TABLE \"auth\".\"mfa_amr_claims\" ADD CONSTRAINT \"mfa_amr_claims_session_id_authentication_method_pkey\" UNIQUE (\"session_id\", \"authentication_method\");"
+        );
+    }
+
+    #[test]
+    fn nulls_not_distinct_is_emitted_for_indexes_and_constraints() {
+        let users = QualifiedName::new("public", "users");
+
+        let sql = generate_sql(&[
+            MigrationOp::AddUniqueConstraint {
+                table: users.clone(),
+                unique_constraint: UniqueConstraint {
+                    name: "users_email_key".to_string(),
+                    columns: vec!["email".to_string()],
+                    nulls_not_distinct: true,
+                    deferrable: false,
+                    initially_deferred: false,
+                },
+            },
+            MigrationOp::AddIndex {
+                table: users,
+                index: Index {
+                    name: "users_email_key".to_string(),
+                    columns: vec!["email".to_string()],
+                    unique: true,
+                    index_type: IndexType::BTree,
+                    predicate: None,
+                    nulls_not_distinct: true,
+                    column_options: Vec::new(),
+                },
+            },
+        ]);
+        assert_eq!(
+            sql,
+            vec![
+                "ALTER TABLE \"public\".\"users\" ADD CONSTRAINT \"users_email_key\" UNIQUE NULLS NOT DISTINCT (\"email\");",
+                "CREATE UNIQUE INDEX \"users_email_key\" ON \"public\".\"users\" (\"email\") NULLS NOT DISTINCT;",
+            ]
+        );
+    }
+
+    #[test]
+    fn drop_unique_constraint_generates_alter_table() {
+        let ops = vec![MigrationOp::DropUniqueConstraint {
+            table: QualifiedName::new("auth", "mfa_amr_claims"),
+            constraint_name: "mfa_amr_claims_session_id_authentication_method_pkey".to_string(),
+        }];
+
+        let sql = generate_sql(&ops);
+        assert_eq!(sql.len(), 1);
+        assert_eq!(
+            sql[0],
+            "ALTER TABLE \"auth\".\"mfa_amr_claims\" DROP CONSTRAINT \"mfa_amr_claims_session_id_authentication_method_pkey\";"
+        );
+    }
+
+    #[test]
+    fn drop_index_generates_schema_qualified_sql() {
+        let ops = vec![MigrationOp::DropIndex {
+            table: QualifiedName::new("auth", "mfa_factors"),
+            index_name: "mfa_factors_user_friendly_name_unique".to_string(),
+        }];
+
+        let sql = generate_sql(&ops);
+        assert_eq!(sql.len(), 1);
+        assert_eq!(
+            sql[0],
+            "DROP INDEX \"auth\".\"mfa_factors_user_friendly_name_unique\";"
+        );
+    }
+
+    #[test]
+    fn drop_index_concurrently_generates_schema_qualified_sql() {
+        let ops = vec![MigrationOp::DropIndexConcurrently {
+            table: QualifiedName::new("auth", "sessions"),
+            index_name: "sessions_user_id_idx".to_string(),
+        }];
+
+        assert_eq!(
+            generate_sql(&ops),
+            vec!["DROP INDEX CONCURRENTLY \"auth\".\"sessions_user_id_idx\";"]
+        );
+    }
+
+    #[test]
+    fn unique_constraint_is_attached_to_an_existing_index() {
+        let ops = vec![MigrationOp::AddUniqueConstraintUsingIndex {
+            table: QualifiedName::new("public", "users"),
+            index_name: "users_handle_key".to_string(),
+            deferrable: true,
+            initially_deferred: false,
+        }];
+
+        assert_eq!(
+            generate_sql(&ops),
+            vec!["ALTER TABLE \"public\".\"users\" ADD CONSTRAINT \"users_handle_key\" UNIQUE USING INDEX \"users_handle_key\" DEFERRABLE INITIALLY IMMEDIATE;"]
+        );
+    }
+
+    #[test]
+    fn foreign_key_deferral_is_added_and_altered_in_place() {
+        let users = QualifiedName::new("public", "posts");
+        let ops = vec![
+            MigrationOp::AddForeignKey {
+                table: users.clone(),
+                foreign_key: ForeignKey {
+                    name: "posts_author_id_fkey".to_string(),
+                    columns: vec!["author_id".to_string()],
+                    referenced_schema: "public".to_string(),
+                    referenced_table: "users".to_string(),
+                    referenced_columns: vec!["id".to_string()],
+                    on_delete: ReferentialAction::NoAction,
+                    on_update: ReferentialAction::NoAction,
+                    deferrable: true,
+                    initially_deferred: true,
+                },
+            },
+            MigrationOp::AlterForeignKeyDeferral {
+                table: users,
+                foreign_key_name: "posts_author_id_fkey".to_string(),
+                deferrable: false,
+                initially_deferred: false,
+            },
+        ];
+
+        assert_eq!(
+            generate_sql(&ops),
+            vec![
+                "ALTER TABLE \"public\".\"posts\" ADD CONSTRAINT \"posts_author_id_fkey\" FOREIGN KEY (\"author_id\") REFERENCES \"public\".\"users\" (\"id\") ON DELETE NO ACTION ON UPDATE NO ACTION DEFERRABLE INITIALLY DEFERRED;",
+                "ALTER TABLE \"public\".\"posts\" ALTER CONSTRAINT \"posts_author_id_fkey\" NOT DEFERRABLE;",
+            ]
+        );
+    }
+
+    #[test]
+    fn alter_column_type_generates_valid_sql_with_using_clause() {
+        let ops = vec![MigrationOp::AlterColumn {
+            table: QualifiedName::new("public", "users"),
+            column: "name".to_string(),
+            changes: ColumnChanges {
+                data_type: Some(PgType::Varchar(Some(100))),
+                nullable: None,
+                default: None,
+                statistics: None,
+                compression: None,
+                identity: None,
+            },
+        }];
+
+        let sql = generate_sql(&ops);
+        assert_eq!(sql.len(), 1);
+        assert_eq!(
+            sql[0],
+            "ALTER TABLE \"public\".\"users\" ALTER COLUMN \"name\" TYPE VARCHAR(100) USING \"name\"::VARCHAR(100);"
+        );
+    }
+
+    #[test]
+    fn alter_column_type_and_default_drops_old_default_first() {
+        let ops = vec![MigrationOp::AlterColumn {
+            table: QualifiedName::new("public", "users"),
+            column: "status".to_string(),
+            changes: ColumnChanges {
+                data_type: Some(PgType::Integer),
+                nullable: None,
+                default: Some(Some("0".to_string())),
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         }];
 
-        let sql = generate_sql(&ops);
-        assert_eq!(sql.len(), 1);
         assert_eq!(
-            sql[0],
-            "ALTER TABLE \"auth\".\"mfa_amr_claims\" ADD CONSTRAINT \"mfa_amr_claims_session_id_authentication_method_pkey\" UNIQUE (\"session_id\", \"authentication_method\");"
+            generate_sql(&ops),
+            vec![
+                "ALTER TABLE \"public\".\"users\" ALTER COLUMN \"status\" DROP DEFAULT;",
+                "ALTER TABLE \"public\".\"users\" ALTER COLUMN \"status\" TYPE INTEGER USING \"status\"::INTEGER;",
+                "ALTER TABLE \"public\".\"users\" ALTER COLUMN \"status\" SET DEFAULT 0;",
+            ]
         );
     }
 
     #[test]
-    fn drop_unique_constraint_generates_alter_table() {
-        let ops = vec![MigrationOp::DropUniqueConstraint {
-            table: QualifiedName::new("auth", "mfa_amr_claims"),
-            constraint_name: "mfa_amr_claims_session_id_authentication_method_pkey".to_string(),
-        }];
+    fn statistics_targets_are_set_on_new_and_altered_columns() {
+        let ops = vec![
+            MigrationOp::AddColumn {
+                table: QualifiedName::new("public", "events"),
+                column: Column {
+                    name: "kind".to_string(),
+                    data_type: PgType::Text,
+                    nullable: true,
+                    default: None,
+                    comment: None,
+                    generated: None,
+                    statistics: Some(1000),
+                    compression: None,
+                    identity: None,
+                },
+            },
+            MigrationOp::AlterColumn {
+                table: QualifiedName::new("public", "users"),
+                column: "email".to_string(),
+                changes: ColumnChanges {
+                    data_type: None,
+                    nullable: None,
+                    default: None,
+                    statistics: Some(None),
+                    compression: None,
+                    identity: None,
+                },
+            },
+        ];
 
         let sql = generate_sql(&ops);
-        assert_eq!(sql.len(), 1);
         assert_eq!(
-            sql[0],
-            "ALTER TABLE \"auth\".\"mfa_amr_claims\" DROP CONSTRAINT \"mfa_amr_claims_session_id_authentication_method_pkey\";"
+            sql[1],
+            "ALTER TABLE \"public\".\"events\" ALTER COLUMN \"kind\" SET STATISTICS 1000;"
+        );
+        assert_eq!(
+            sql[2],
+            "ALTER TABLE \"public\".\"users\" ALTER COLUMN \"email\" SET STATISTICS -1;"
         );
     }
 
     #[test]
-    fn drop_index_generates_schema_qualified_sql() {
-        let ops = vec![MigrationOp::DropIndex {
-            table: QualifiedName::new("auth", "mfa_factors"),
-            index_name: "mfa_factors_user_friendly_name_unique".to_string(),
-        }];
+    fn compression_changes_set_or_reset_the_method() {
+        let change = |compression| MigrationOp::AlterColumn {
+            table: QualifiedName::new("public", "documents"),
+            column: "body".to_string(),
+            changes: ColumnChanges {
+                data_type: None,
+                nullable: None,
+                default: None,
+                statistics: None,
+                compression: Some(compression),
+                identity: None,
+            },
+        };
 
-        let sql = generate_sql(&ops);
-        assert_eq!(sql.len(), 1);
         assert_eq!(
-            sql[0],
-            "DROP INDEX \"auth\".\"mfa_factors_user_friendly_name_unique\";"
+            generate_sql(&[change(Some("lz4".to_string())), change(None)]),
+            vec![
+                "ALTER TABLE \"public\".\"documents\" ALTER COLUMN \"body\" SET COMPRESSION lz4;",
+                "ALTER TABLE \"public\".\"documents\" ALTER COLUMN \"body\" SET COMPRESSION default;",
+            ]
         );
     }
 
     #[test]
-    fn alter_column_type_generates_valid_sql_with_using_clause() {
-        let ops = vec![MigrationOp::AlterColumn {
-            table: QualifiedName::new("public", "users"),
-            column: "name".to_string(),
+    fn identity_changes_generate_add_set_and_drop() {
+        let change = |identity| MigrationOp::AlterColumn {
+            table: QualifiedName::new("public", "orders"),
+            column: "id".to_string(),
             changes: ColumnChanges {
-                data_type: Some(PgType::Varchar(Some(100))),
+                data_type: None,
                 nullable: None,
                 default: None,
+                statistics: None,
+                compression: None,
+                identity: Some(identity),
             },
-        }];
+        };
+        let identity = Identity {
+            generation: IdentityGeneration::ByDefault,
+            start: Some(100),
+            increment: None,
+            min_value: None,
+            max_value: None,
+            cache: None,
+            cycle: Some(false),
+        };
 
-        let sql = generate_sql(&ops);
-        assert_eq!(sql.len(), 1);
         assert_eq!(
-            sql[0],
-            "ALTER TABLE \"public\".\"users\" ALTER COLUMN \"name\" TYPE VARCHAR(100) USING \"name\"::VARCHAR(100);"
+            generate_sql(&[
+                change(IdentityChange::Add(identity.clone())),
+                change(IdentityChange::Set(identity)),
+                change(IdentityChange::Drop),
+            ]),
+            vec![
+                "ALTER TABLE \"public\".\"orders\" ALTER COLUMN \"id\" ADD GENERATED BY DEFAULT AS IDENTITY (START WITH 100 NO CYCLE);",
+                "ALTER TABLE \"public\".\"orders\" ALTER COLUMN \"id\" SET GENERATED BY DEFAULT SET START WITH 100 SET NO CYCLE;",
+                "ALTER TABLE \"public\".\"orders\" ALTER COLUMN \"id\" DROP IDENTITY IF EXISTS;",
+            ]
+        );
+    }
+
+    #[test]
+    fn replica_identity_generates_alter_table() {
+        let set = |identity| MigrationOp::SetReplicaIdentity {
+            table: QualifiedName::new("public", "events"),
+            identity,
+        };
+        assert_eq!(
+            generate_sql(&[
+                set(Some(ReplicaIdentity::Full)),
+                set(Some(ReplicaIdentity::Index("events_key_idx".to_string()))),
+                set(None),
+            ]),
+            vec![
+                "ALTER TABLE \"public\".\"events\" REPLICA IDENTITY FULL;",
+                "ALTER TABLE \"public\".\"events\" REPLICA IDENTITY USING INDEX \"events_key_idx\";",
+                "ALTER TABLE \"public\".\"events\" REPLICA IDENTITY DEFAULT;",
+            ]
         );
     }
 
@@ -2025,6 +2964,9 @@ mod tests {
                 data_type: Some(PgType::Uuid),
                 nullable: None,
                 default: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         }];
 
@@ -2047,6 +2989,9 @@ mod tests {
             owner: None,
             grants: Vec::new(),
             comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
         })];
 
         let sql = generate_sql(&ops);
@@ -2068,6 +3013,9 @@ mod tests {
             owner: None,
             grants: Vec::new(),
             comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
         })];
 
         let sql = generate_sql(&ops);
@@ -2114,6 +3062,8 @@ mod tests {
                 referenced_columns: vec!["id".to_string()],
                 on_delete: ReferentialAction::Cascade,
                 on_update: ReferentialAction::NoAction,
+                deferrable: false,
+                initially_deferred: false,
             },
         }];
 
@@ -2230,6 +3180,7 @@ mod tests {
             name: "uuid-ossp".to_string(),
             version: None,
             schema: None,
+            relocatable: false,
             comment: None,
         })];
 
@@ -2244,6 +3195,7 @@ mod tests {
             name: "pgcrypto".to_string(),
             version: Some("1.3".to_string()),
             schema: Some("crypto".to_string()),
+            relocatable: false,
             comment: None,
         })];
 
@@ -2255,6 +3207,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn set_extension_schema_generates_alter_extension() {
+        let ops = vec![MigrationOp::SetExtensionSchema {
+            name: "citext".to_string(),
+            schema: "ext".to_string(),
+            relocatable: true,
+        }];
+
+        assert_eq!(
+            generate_sql(&ops),
+            vec!["ALTER EXTENSION \"citext\" SET SCHEMA \"ext\";"]
+        );
+    }
+
+    #[test]
+    fn update_extension_generates_alter_extension() {
+        let ops = vec![MigrationOp::UpdateExtension {
+            name: "postgis".to_string(),
+            from_version: Some("3.3.2".to_string()),
+            version: "3.4.0".to_string(),
+        }];
+
+        assert_eq!(
+            generate_sql(&ops),
+            vec!["ALTER EXTENSION \"postgis\" UPDATE TO '3.4.0';"]
+        );
+    }
+
     #[test]
     fn drop_extension_generates_valid_sql() {
         let ops = vec![MigrationOp::DropExtension("uuid-ossp".to_string())];
@@ -2423,11 +3403,19 @@ mod tests {
 
     #[test]
     fn drop_schema_generates_valid_sql() {
-        let ops = vec![MigrationOp::DropSchema("old_schema".to_string())];
+        let drop = |cascade| MigrationOp::DropSchema {
+            name: "old_schema".to_string(),
+            cascade,
+        };
 
-        let sql = generate_sql(&ops);
-        assert_eq!(sql.len(), 1);
-        assert_eq!(sql[0], "DROP SCHEMA IF EXISTS \"old_schema\" CASCADE;");
+        let sql = generate_sql(&[drop(false), drop(true)]);
+        assert_eq!(
+            sql,
+            vec![
+                "DROP SCHEMA IF EXISTS \"old_schema\";",
+                "DROP SCHEMA IF EXISTS \"old_schema\" CASCADE;",
+            ]
+        );
     }
 
     #[test]
@@ -2442,6 +3430,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         );
 
@@ -2455,10 +3446,12 @@ mod tests {
             }),
             foreign_keys: vec![],
             check_constraints: vec![],
+            unique_constraints: Vec::new(),
             exclusion_constraints: vec![],
             comment: None,
             row_level_security: false,
             force_row_level_security: false,
+            replica_identity: None,
             policies: vec![],
             partition_by: None,
 
@@ -2945,6 +3938,18 @@ mod tests {
         assert_eq!(sql[0], "DROP SEQUENCE \"public\".\"users_id_seq\";");
     }
 
+    #[test]
+    fn sqlgen_set_sequence_value() {
+        let op = MigrationOp::SetSequenceValue {
+            name: "public.orders_id_seq".to_string(),
+            value: 1042,
+        };
+        assert_eq!(
+            generate_sql(&[op]),
+            vec!["SELECT setval('\"public\".\"orders_id_seq\"', 1042);"]
+        );
+    }
+
     #[test]
     fn sqlgen_alter_sequence_increment() {
         use crate::diff::SequenceChanges;
@@ -3348,6 +4353,65 @@ mod tests {
         );
     }
 
+    #[test]
+    fn analyze_follows_rewritten_tables() {
+        let ops = vec![
+            MigrationOp::AlterColumn {
+                table: QualifiedName::new("public", "users"),
+                column: "name".to_string(),
+                changes: ColumnChanges {
+                    data_type: Some(PgType::Text),
+                    nullable: None,
+                    default: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
+                },
+            },
+            MigrationOp::AlterColumn {
+                table: QualifiedName::new("public", "posts"),
+                column: "title".to_string(),
+                changes: ColumnChanges {
+                    data_type: None,
+                    nullable: Some(true),
+                    default: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
+                },
+            },
+            MigrationOp::BackfillHint {
+                table: QualifiedName::new("public", "orders"),
+                column: "total".to_string(),
+                hint: "UPDATE orders SET total = 0 WHERE total IS NULL;".to_string(),
+            },
+        ];
+
+        let plain = generate_sql_with_options(&ops, &SqlOptions::default());
+        assert_eq!(plain, generate_sql(&ops));
+
+        let sql = generate_sql_with_options(
+            &ops,
+            &SqlOptions {
+                analyze_rewritten_tables: true,
+                ..SqlOptions::default()
+            },
+        );
+        assert_eq!(
+            sql[sql.len() - 2..],
+            [
+                "ANALYZE \"public\".\"orders\";".to_string(),
+                "ANALYZE \"public\".\"users\";".to_string(),
+            ]
+        );
+        assert_eq!(
+            vacuum_advisable_tables(&ops)
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec![QualifiedName::new("public", "orders")]
+        );
+    }
+
     #[test]
     fn sqlgen_set_column_not_null() {
         let op = MigrationOp::SetColumnNotNull {
@@ -3460,6 +4524,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn generate_function_ddl_quotes_introspected_config_values() {
+        use crate::model::{Function, SecurityType, Volatility};
+
+        let func = Function {
+            name: "test_func".to_string(),
+            schema: "public".to_string(),
+            arguments: vec![],
+            return_type: "void".to_string(),
+            language: "sql".to_string(),
+            body: "SELECT 1".to_string(),
+            volatility: Volatility::Volatile,
+            security: SecurityType::Definer,
+            config_params: vec![
+                ("search_path".to_string(), "\"$user\", public".to_string()),
+                ("work_mem".to_string(), "64MB".to_string()),
+                ("timezone".to_string(), "FROM CURRENT".to_string()),
+            ],
+            owner: None,
+            grants: Vec::new(),
+            comment: None,
+        };
+
+        let ddl = generate_function_ddl(&func, false);
+
+        assert!(
+            ddl.contains(
+                "SET search_path = \"$user\", public SET work_mem = '64MB' SET timezone FROM CURRENT"
+            ),
+            "Expected quoted SET clauses in: {ddl}"
+        );
+    }
+
     #[test]
     fn create_function_does_not_generate_owner_to() {
         use crate::model::{Function, SecurityType, Volatility};
@@ -4166,6 +5263,7 @@ mod tests {
         assert_eq!(format_role_name("PUBLIC"), "public");
         assert_eq!(format_role_name("Public"), "public");
         assert_eq!(format_role_name("PuBLiC"), "public");
+        assert_eq!(format_role_name("CURRENT_USER"), "current_user");
 
         // Simple identifiers - no quotes needed
         assert_eq!(format_role_name("admin"), "admin");
@@ -4287,6 +5385,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         }];
 
@@ -4309,6 +5410,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         }];
 
@@ -4331,6 +5435,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         }];
 
@@ -4390,6 +5497,9 @@ mod tests {
                 owner: None,
                 grants: vec![],
                 comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: None,
             },
         }];
 
@@ -4405,6 +5515,119 @@ mod tests {
         );
     }
 
+    #[test]
+    fn deferred_materialized_views_are_created_empty_and_refreshed_last() {
+        let view = View {
+            name: "summary".to_string(),
+            schema: "public".to_string(),
+            query: "SELECT count(*) FROM orders".to_string(),
+            materialized: true,
+            owner: None,
+            grants: vec![],
+            comment: None,
+            security_barrier: false,
+            security_invoker: false,
+            check_option: None,
+        };
+        let ops = vec![
+            MigrationOp::CreateView(view.clone()),
+            MigrationOp::CreateView(View {
+                name: "recent".to_string(),
+                materialized: false,
+                query: "SELECT * FROM orders".to_string(),
+                ..view
+            }),
+        ];
+
+        let sql = generate_sql_with_options(
+            &ops,
+            &SqlOptions {
+                defer_materialized_view_data: true,
+                ..SqlOptions::default()
+            },
+        );
+        assert_eq!(
+            sql,
+            vec![
+                "CREATE MATERIALIZED VIEW \"public\".\"summary\" AS SELECT count(*) FROM orders WITH NO DATA;",
+                "CREATE VIEW \"public\".\"recent\" AS SELECT * FROM orders;",
+                "REFRESH MATERIALIZED VIEW \"public\".\"summary\";",
+            ]
+        );
+    }
+
+    #[test]
+    fn create_view_emits_security_options() {
+        let ops = vec![MigrationOp::CreateView(View {
+            name: "my_docs".to_string(),
+            schema: "public".to_string(),
+            query: "SELECT * FROM docs".to_string(),
+            materialized: false,
+            owner: None,
+            grants: vec![],
+            comment: None,
+            security_barrier: true,
+            security_invoker: true,
+            check_option: None,
+        })];
+
+        let sql = generate_sql(&ops);
+        assert_eq!(
+            sql,
+            vec![
+                "CREATE VIEW \"public\".\"my_docs\" WITH (security_barrier = true, security_invoker = true) AS SELECT * FROM docs;"
+            ]
+        );
+    }
+
+    #[test]
+    fn alter_view_options_sets_and_resets() {
+        let ops = vec![MigrationOp::AlterViewOptions {
+            name: "public.my_docs".to_string(),
+            changes: ViewOptionChanges {
+                security_barrier: Some(false),
+                security_invoker: Some(true),
+                check_option: Some(Some(ViewCheckOption::Local)),
+            },
+        }];
+
+        let sql = generate_sql(&ops);
+        assert_eq!(
+            sql,
+            vec![
+                "ALTER VIEW \"public\".\"my_docs\" SET (security_invoker = true, check_option = local);",
+                "ALTER VIEW \"public\".\"my_docs\" RESET (security_barrier);",
+            ]
+        );
+    }
+
+    #[test]
+    fn create_view_emits_check_option() {
+        let ops = vec![MigrationOp::AlterView {
+            name: "public.open_orders".to_string(),
+            new_view: View {
+                name: "open_orders".to_string(),
+                schema: "public".to_string(),
+                query: "SELECT * FROM orders WHERE status = 'open'".to_string(),
+                materialized: false,
+                owner: None,
+                grants: vec![],
+                comment: None,
+                security_barrier: false,
+                security_invoker: false,
+                check_option: Some(ViewCheckOption::Cascaded),
+            },
+        }];
+
+        let sql = generate_sql(&ops);
+        assert_eq!(
+            sql,
+            vec![
+                "CREATE OR REPLACE VIEW \"public\".\"open_orders\" AS SELECT * FROM orders WHERE status = 'open' WITH CASCADED CHECK OPTION;"
+            ]
+        );
+    }
+
     #[test]
     fn add_column_with_time_type_generates_valid_sql() {
         let ops = vec![MigrationOp::AddColumn {
@@ -4416,6 +5639,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         }];
         let sql = generate_sql(&ops);
@@ -4437,6 +5663,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         }];
         let sql = generate_sql(&ops);
@@ -4458,6 +5687,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         }];
         let sql = generate_sql(&ops);
@@ -4479,6 +5711,9 @@ mod tests {
                 default: None,
                 comment: None,
                 generated: None,
+                statistics: None,
+                compression: None,
+                identity: None,
             },
         }];
         let sql = generate_sql(&ops);
@@ -4501,6 +5736,9 @@ mod tests {
                     default: None,
                     comment: None,
                     generated: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
                 },
             },
             MigrationOp::AddColumn {
@@ -4512,6 +5750,9 @@ mod tests {
                     default: None,
                     comment: None,
                     generated: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
                 },
             },
         ];
@@ -4539,6 +5780,9 @@ mod tests {
                     default: None,
                     comment: None,
                     generated: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
                 },
             },
             MigrationOp::AddColumn {
@@ -4550,6 +5794,9 @@ mod tests {
                     default: None,
                     comment: None,
                     generated: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
                 },
             },
         ];
@@ -4573,7 +5820,8 @@ mod tests {
             unique: false,
             index_type: IndexType::BTree,
             predicate: None,
-            is_constraint: false,
+            nulls_not_distinct: false,
+            column_options: Vec::new(),
         };
         let sql = generate_create_index("public", "users", &index);
         assert_eq!(
@@ -4590,7 +5838,8 @@ mod tests {
             unique: true,
             index_type: IndexType::BTree,
             predicate: None,
-            is_constraint: false,
+            nulls_not_distinct: false,
+            column_options: Vec::new(),
         };
         let sql = generate_create_index("public", "users", &index);
         assert_eq!(
@@ -4607,7 +5856,8 @@ mod tests {
             unique: false,
             index_type: IndexType::BTree,
             predicate: None,
-            is_constraint: false,
+            nulls_not_distinct: false,
+            column_options: Vec::new(),
         };
         let sql = generate_create_index("public", "events", &index);
         assert_eq!(
@@ -4624,7 +5874,8 @@ mod tests {
             unique: false,
             index_type: IndexType::Gist,
             predicate: Some("geometry IS NOT NULL".to_string()),
-            is_constraint: false,
+            nulls_not_distinct: false,
+            column_options: Vec::new(),
         };
         let sql = generate_create_index("mrv", "Polygon", &index);
         assert_eq!(
@@ -4641,7 +5892,8 @@ mod tests {
             unique: false,
             index_type: IndexType::Gin,
             predicate: None,
-            is_constraint: false,
+            nulls_not_distinct: false,
+            column_options: Vec::new(),
         };
         let sql = generate_create_index("public", "documents", &index);
         assert_eq!(
@@ -4658,7 +5910,8 @@ mod tests {
             unique: false,
             index_type: IndexType::Hash,
             predicate: None,
-            is_constraint: false,
+            nulls_not_distinct: false,
+            column_options: Vec::new(),
         };
         let sql = generate_create_index("public", "users", &index);
         assert_eq!(
@@ -4667,6 +5920,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn brin_index_generates_using_brin_clause() {
+        let index = Index {
+            name: "events_created_at_idx".to_string(),
+            columns: vec!["created_at".to_string()],
+            unique: false,
+            index_type: IndexType::Brin,
+            predicate: None,
+            nulls_not_distinct: false,
+            column_options: Vec::new(),
+        };
+        let sql = generate_create_index("public", "events", &index);
+        assert_eq!(
+            sql,
+            "CREATE INDEX \"events_created_at_idx\" ON \"public\".\"events\" USING brin (\"created_at\");"
+        );
+    }
+
+    #[test]
+    fn index_columns_keep_collation_opclass_and_order() {
+        let index = Index {
+            name: "products_idx".to_string(),
+            columns: vec!["sku".to_string(), "lower(name)".to_string()],
+            unique: false,
+            index_type: IndexType::BTree,
+            predicate: None,
+            nulls_not_distinct: false,
+            column_options: vec![
+                IndexColumnOptions::new(None, Some("text_pattern_ops".to_string()), false, None),
+                IndexColumnOptions::new(Some("C".to_string()), None, true, Some(false)),
+            ],
+        };
+        let sql = generate_create_index("public", "products", &index);
+        assert_eq!(
+            sql,
+            "CREATE INDEX \"products_idx\" ON \"public\".\"products\" (\"sku\" text_pattern_ops, lower(name) COLLATE \"C\" DESC NULLS LAST);"
+        );
+    }
+
     #[test]
     fn btree_index_generates_without_using_clause() {
         let index = Index {
@@ -4675,7 +5967,8 @@ mod tests {
             unique: false,
             index_type: IndexType::BTree,
             predicate: None,
-            is_constraint: false,
+            nulls_not_distinct: false,
+            column_options: Vec::new(),
         };
         let sql = generate_create_index("public", "users", &index);
         assert_eq!(
@@ -0,0 +1,103 @@
+use crate::pg::connection::PgConnection;
+use crate::util::{Result, SchemaError};
+use serde::Serialize;
+use sqlx::Row;
+
+/// How many objects of a kind pgmold does not manage exist in a schema.
+/// Drift and dump results leave these out, so a database can differ in them
+/// without being reported as drifted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnmanagedObjects {
+    /// `None` for database-wide objects such as foreign-data wrappers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    pub kind: String,
+    pub count: i64,
+}
+
+impl std::fmt::Display for UnmanagedObjects {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.schema {
+            Some(schema) => write!(f, "{schema}: {} {}", self.count, self.kind),
+            None => write!(f, "{} {}", self.count, self.kind),
+        }
+    }
+}
+
+/// Counts the objects in `target_schemas` of each kind pgmold does not
+/// manage, plus foreign-data wrappers. Objects belonging to an extension are
+/// left out, since the extension manages them.
+pub async fn count_unmanaged_objects(
+    connection: &PgConnection,
+    target_schemas: &[String],
+) -> Result<Vec<UnmanagedObjects>> {
+    let rows = sqlx::query(
+        r#"
+        WITH objects AS (
+            SELECT 'pg_operator'::regclass AS classid, oid, oprnamespace AS nsp, 'operator' AS kind
+            FROM pg_operator
+            UNION ALL
+            SELECT 'pg_opclass'::regclass, oid, opcnamespace, 'operator class' FROM pg_opclass
+            UNION ALL
+            SELECT 'pg_opfamily'::regclass, oid, opfnamespace, 'operator family' FROM pg_opfamily
+            UNION ALL
+            SELECT 'pg_collation'::regclass, oid, collnamespace, 'collation' FROM pg_collation
+            UNION ALL
+            SELECT 'pg_conversion'::regclass, oid, connamespace, 'conversion' FROM pg_conversion
+            UNION ALL
+            SELECT 'pg_ts_config'::regclass, oid, cfgnamespace, 'text search configuration'
+            FROM pg_ts_config
+            UNION ALL
+            SELECT 'pg_ts_dict'::regclass, oid, dictnamespace, 'text search dictionary'
+            FROM pg_ts_dict
+            UNION ALL
+            SELECT 'pg_statistic_ext'::regclass, oid, stxnamespace, 'statistics object'
+            FROM pg_statistic_ext
+            UNION ALL
+            SELECT 'pg_class'::regclass, oid, relnamespace, 'foreign table'
+            FROM pg_class WHERE relkind = 'f'
+            UNION ALL
+            SELECT 'pg_type'::regclass, t.oid, t.typnamespace, 'composite type'
+            FROM pg_type t JOIN pg_class c ON c.oid = t.typrelid
+            WHERE t.typtype = 'c' AND c.relkind = 'c'
+            UNION ALL
+            SELECT 'pg_rewrite'::regclass, r.oid, c.relnamespace, 'rule'
+            FROM pg_rewrite r JOIN pg_class c ON c.oid = r.ev_class
+            WHERE r.rulename <> '_RETURN'
+        )
+        SELECT n.nspname AS schema, o.kind, count(*) AS count
+        FROM objects o
+        JOIN pg_namespace n ON n.oid = o.nsp
+        WHERE n.nspname = ANY($1)
+          AND NOT EXISTS (
+              SELECT 1 FROM pg_depend d
+              WHERE d.classid = o.classid AND d.objid = o.oid AND d.deptype = 'e'
+          )
+        GROUP BY n.nspname, o.kind
+        UNION ALL
+        SELECT NULL, 'foreign-data wrapper', count(*)
+        FROM pg_foreign_data_wrapper w
+        WHERE NOT EXISTS (
+            SELECT 1 FROM pg_depend d
+            WHERE d.classid = 'pg_foreign_data_wrapper'::regclass
+              AND d.objid = w.oid
+              AND d.deptype = 'e'
+        )
+        HAVING count(*) > 0
+        ORDER BY 1 NULLS FIRST, 2
+        "#,
+    )
+    .bind(target_schemas)
+    .fetch_all(connection.pool())
+    .await
+    .map_err(|e| SchemaError::DatabaseError(format!("Failed to count unmanaged objects: {e}")))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| UnmanagedObjects {
+            schema: row.get("schema"),
+            kind: row.get("kind"),
+            count: row.get("count"),
+        })
+        .collect())
+}
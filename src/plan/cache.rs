@@ -0,0 +1,135 @@
+//! On-disk cache for `plan`. Parsed target schemas are keyed by the hash of
+//! their source files; computed operations additionally by the fingerprint of
+//! the introspected database and a hash of the planning options. An entry is
+//! only reused when every input is byte-for-byte unchanged.
+
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::diff::MigrationOp;
+use crate::model::Schema;
+
+#[derive(Debug, Clone)]
+pub struct PlanCache {
+    dir: PathBuf,
+}
+
+impl PlanCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The `plans` directory under pgmold's cache directory
+    /// (`PGMOLD_CACHE_DIR`, else `$XDG_CACHE_HOME/pgmold`, else `~/.cache/pgmold`).
+    pub fn default_location() -> Self {
+        Self::new(crate::provider::cache_dir().join("plans"))
+    }
+
+    pub fn load_schema(&self, source_hash: &str) -> Option<Schema> {
+        self.read(&self.schema_path(source_hash))
+    }
+
+    pub fn store_schema(&self, source_hash: &str, schema: &Schema) {
+        self.write(&self.schema_path(source_hash), schema);
+    }
+
+    pub fn load_ops(&self, key: &str) -> Option<Vec<MigrationOp>> {
+        self.read(&self.ops_path(key))
+    }
+
+    pub fn store_ops(&self, key: &str, ops: &[MigrationOp]) {
+        self.write(&self.ops_path(key), &ops);
+    }
+
+    fn schema_path(&self, source_hash: &str) -> PathBuf {
+        self.dir.join(format!("schema-{source_hash}.json"))
+    }
+
+    fn ops_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("ops-{key}.json"))
+    }
+
+    /// A missing, unreadable or outdated entry is a cache miss.
+    fn read<T: DeserializeOwned>(&self, path: &Path) -> Option<T> {
+        let content = std::fs::read(path).ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+
+    /// Failing to write only costs the next run a recomputation, so errors
+    /// are ignored. Entries are written to a temporary file and renamed into
+    /// place so a concurrent reader never sees a partial entry.
+    fn write<T: Serialize + ?Sized>(&self, path: &Path, value: &T) {
+        let Ok(content) = serde_json::to_vec(value) else {
+            return;
+        };
+        if std::fs::create_dir_all(&self.dir).is_err() {
+            return;
+        }
+        let temp = path.with_extension(format!("tmp-{}", std::process::id()));
+        if std::fs::write(&temp, content).is_ok() && std::fs::rename(&temp, path).is_err() {
+            let _ = std::fs::remove_file(&temp);
+        }
+    }
+}
+
+/// Key for the operations planned from sources hashing to `source_hash`
+/// against a database whose introspected schema has `database_fingerprint`,
+/// under options hashing to `options_hash`. Includes the pgmold version, since
+/// a new release may plan the same inputs differently.
+pub fn plan_cache_key(source_hash: &str, database_fingerprint: &str, options_hash: &str) -> String {
+    let mut hasher = Sha256::new();
+    for part in [
+        env!("CARGO_PKG_VERSION"),
+        source_hash,
+        database_fingerprint,
+        options_hash,
+    ] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    hex::encode(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn stores_and_loads_ops_and_schemas() {
+        let dir = TempDir::new().unwrap();
+        let cache = PlanCache::new(dir.path().join("plans"));
+        let ops = vec![MigrationOp::DropTable("public.legacy".to_string())];
+
+        assert_eq!(cache.load_ops("k"), None);
+        cache.store_ops("k", &ops);
+        assert_eq!(cache.load_ops("k"), Some(ops));
+
+        let schema = crate::parser::parse_sql_string(
+            "CREATE TABLE users (id BIGINT PRIMARY KEY, email TEXT NOT NULL);",
+        )
+        .unwrap();
+        cache.store_schema("s", &schema);
+        assert_eq!(cache.load_schema("s"), Some(schema));
+    }
+
+    #[test]
+    fn corrupt_entry_is_a_miss() {
+        let dir = TempDir::new().unwrap();
+        let cache = PlanCache::new(dir.path());
+        std::fs::write(dir.path().join("ops-k.json"), "{not json").unwrap();
+        assert_eq!(cache.load_ops("k"), None);
+    }
+
+    #[test]
+    fn key_changes_with_every_input() {
+        let key = plan_cache_key("src", "db", "opts");
+        assert_eq!(plan_cache_key("src", "db", "opts"), key);
+        assert_ne!(plan_cache_key("src2", "db", "opts"), key);
+        assert_ne!(plan_cache_key("src", "db2", "opts"), key);
+        assert_ne!(plan_cache_key("src", "db", "opts2"), key);
+    }
+}
@@ -0,0 +1,177 @@
+use crate::diff::{MigrationOp, PolicyChanges};
+use crate::model::{Policy, Schema};
+use crate::pg::sqlgen::generate_sql;
+
+/// Lines of context kept around each change.
+const CONTEXT_LINES: usize = 3;
+
+/// A unified diff of the definition `op` replaces in `current` against the one
+/// it installs, for the operations that rewrite a whole definition: function
+/// bodies, view queries and policy expressions. None for any other operation,
+/// or when `current` does not have the object.
+pub fn render_diff(op: &MigrationOp, current: &Schema) -> Option<String> {
+    let (object, old, new) = match op {
+        MigrationOp::AlterFunction {
+            name,
+            args,
+            new_function,
+        } => {
+            let old = current.functions.get(&format!("{name}({args})"))?;
+            (
+                format!("function {name}({args})"),
+                definition(MigrationOp::CreateFunction(old.clone())),
+                definition(MigrationOp::CreateFunction(new_function.clone())),
+            )
+        }
+        MigrationOp::AlterView { name, new_view } => {
+            let old = current.views.get(name)?;
+            let kind = if new_view.materialized {
+                "materialized view"
+            } else {
+                "view"
+            };
+            (
+                format!("{kind} {name}"),
+                definition(MigrationOp::CreateView(old.clone())),
+                definition(MigrationOp::CreateView(new_view.clone())),
+            )
+        }
+        MigrationOp::AlterPolicy {
+            table,
+            name,
+            changes,
+        } => {
+            let old = current
+                .tables
+                .get(&table.to_string())?
+                .policies
+                .iter()
+                .find(|policy| policy.name == *name)?;
+            (
+                format!("policy {name} on {table}"),
+                policy_definition(old),
+                policy_definition(&with_changes(old, changes)),
+            )
+        }
+        _ => return None,
+    };
+    unified_diff(&object, &old, &new)
+}
+
+fn definition(create: MigrationOp) -> String {
+    generate_sql(&[create]).join("\n")
+}
+
+/// A policy one clause per line, so a changed expression shows up on its own.
+fn policy_definition(policy: &Policy) -> String {
+    let mut lines = vec![format!("FOR {:?}", policy.command).to_uppercase()];
+    if !policy.roles.is_empty() {
+        lines.push(format!("TO {}", policy.roles.join(", ")));
+    }
+    if let Some(using) = &policy.using_expr {
+        lines.push(format!("USING ({using})"));
+    }
+    if let Some(check) = &policy.check_expr {
+        lines.push(format!("WITH CHECK ({check})"));
+    }
+    lines.join("\n")
+}
+
+fn with_changes(policy: &Policy, changes: &PolicyChanges) -> Policy {
+    let mut changed = policy.clone();
+    if let Some(roles) = &changes.roles {
+        changed.roles = roles.clone();
+    }
+    if let Some(using) = &changes.using_expr {
+        changed.using_expr = using.clone();
+    }
+    if let Some(check) = &changes.check_expr {
+        changed.check_expr = check.clone();
+    }
+    changed
+}
+
+fn unified_diff(object: &str, old: &str, new: &str) -> Option<String> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let lines = difflib::unified_diff(
+        &old,
+        &new,
+        &format!("{object} (database)"),
+        &format!("{object} (schema sources)"),
+        "",
+        "",
+        CONTEXT_LINES,
+    );
+    if lines.is_empty() {
+        return None;
+    }
+    Some(
+        lines
+            .iter()
+            .map(|line| line.trim_end())
+            .collect::<Vec<_>>()
+            .join("\n"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::compute_diff;
+    use crate::parser::parse_sql_string;
+
+    fn only_diff(current_sql: &str, target_sql: &str) -> String {
+        let current = parse_sql_string(current_sql).unwrap();
+        let target = parse_sql_string(target_sql).unwrap();
+        let diffs: Vec<String> = compute_diff(&current, &target)
+            .iter()
+            .filter_map(|op| render_diff(op, &current))
+            .collect();
+        assert_eq!(diffs.len(), 1, "unexpected diffs: {diffs:?}");
+        diffs.into_iter().next().unwrap()
+    }
+
+    #[test]
+    fn function_body_change_is_diffed_line_by_line() {
+        let diff = only_diff(
+            "CREATE FUNCTION total(a integer, b integer) RETURNS integer LANGUAGE plpgsql AS $$
+BEGIN
+    RETURN a + b;
+END;
+$$;",
+            "CREATE FUNCTION total(a integer, b integer) RETURNS integer LANGUAGE plpgsql AS $$
+BEGIN
+    RETURN a + b + 1;
+END;
+$$;",
+        );
+
+        assert!(diff.starts_with(
+            "--- function public.total(integer, integer) (database)\n\
+             +++ function public.total(integer, integer) (schema sources)\n@@"
+        ));
+        assert!(diff.contains("\n-    RETURN a + b;\n+    RETURN a + b + 1;\n"));
+        assert!(diff.ends_with("\n END;$$;"));
+    }
+
+    #[test]
+    fn policy_change_shows_only_the_changed_clause() {
+        let diff = only_diff(
+            "CREATE TABLE docs (owner text);
+             CREATE POLICY own ON docs FOR SELECT USING (owner = current_user);",
+            "CREATE TABLE docs (owner text);
+             CREATE POLICY own ON docs FOR SELECT USING (owner = session_user);",
+        );
+
+        assert!(diff.contains("--- policy own on public.docs (database)"));
+        assert!(diff.contains("\n FOR SELECT\n"));
+        assert!(diff.contains("\n-USING (owner = current_user)\n+USING (owner = session_user)"));
+    }
+
+    #[test]
+    fn other_operations_have_no_diff() {
+        let op = MigrationOp::DropTable("public.users".to_string());
+        assert_eq!(render_diff(&op, &Schema::new()), None);
+    }
+}
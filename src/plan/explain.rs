@@ -0,0 +1,265 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::diff::planner::dependency_edges;
+use crate::diff::MigrationOp;
+use crate::dump::schema_to_create_ops;
+use crate::model::{parse_qualified_name, qualified_name, Schema};
+use crate::parser::SourceLocations;
+use crate::pg::sqlgen::generate_sql;
+
+/// Why a plan does what it does to one object, as printed by `plan --explain`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Explanation {
+    /// Qualified name of the object.
+    pub object: String,
+    /// File and line of the schema sources declaring it, when they do.
+    pub declared_at: Option<String>,
+    /// The object as the database has it, as SQL; empty when it does not exist.
+    pub current: Vec<String>,
+    /// Each field whose value differs between the database and the sources.
+    pub differences: Vec<FieldDifference>,
+    /// The planned operations on the object, in plan order.
+    pub operations: Vec<ExplainedOperation>,
+}
+
+/// One field of an object whose database and declared values differ. Values
+/// are JSON, and absent when the field (or the whole object) is missing on
+/// that side.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldDifference {
+    /// Path to the field, such as `table.columns.email.nullable`.
+    pub field: String,
+    pub current: Option<String>,
+    pub declared: Option<String>,
+}
+
+/// A planned operation on the explained object and the operations that fix
+/// its position in the plan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExplainedOperation {
+    pub key: String,
+    pub statements: Vec<String>,
+    /// Operations that must run before this one.
+    pub runs_after: Vec<String>,
+    /// Operations that cannot run until this one has.
+    pub runs_before: Vec<String>,
+}
+
+/// Explains what `ops`, planned from `current` to `target`, do to `object`
+/// and why. An unqualified `object` is looked up in the `public` schema; a
+/// table's triggers and a function's overloads are explained along with it.
+pub fn explain_object(
+    object: &str,
+    ops: &[MigrationOp],
+    current: &Schema,
+    target: &Schema,
+    locations: &SourceLocations,
+) -> Explanation {
+    let (schema, name) = parse_qualified_name(object);
+    let object = qualified_name(&schema, &name);
+
+    let current_only = isolate(current, &object);
+    let target_only = isolate(target, &object);
+    let mut differences = Vec::new();
+    diff_values(
+        "",
+        Some(&definitions(&current_only)),
+        Some(&definitions(&target_only)),
+        &mut differences,
+    );
+
+    let on_object = |op: &MigrationOp| op.subject().as_deref() == Some(object.as_str());
+    let edges = dependency_edges(ops.to_vec(), on_object);
+    let operations = ops
+        .iter()
+        .filter(|op| on_object(op))
+        .map(|op| {
+            let key = op.identity();
+            let runs_after = edges
+                .iter()
+                .filter(|(_, to)| to.identity() == key)
+                .map(|(from, _)| from.identity())
+                .collect();
+            let runs_before = edges
+                .iter()
+                .filter(|(from, _)| from.identity() == key)
+                .map(|(_, to)| to.identity())
+                .collect();
+            ExplainedOperation {
+                statements: generate_sql(std::slice::from_ref(op)),
+                key,
+                runs_after,
+                runs_before,
+            }
+        })
+        .collect();
+
+    Explanation {
+        declared_at: locations.get(&object).map(ToString::to_string),
+        current: generate_sql(&schema_to_create_ops(&current_only)),
+        object,
+        differences,
+        operations,
+    }
+}
+
+/// The parts of `schema` defining `object`: the relation, type, sequence or
+/// function overloads of that name, and the triggers on it.
+pub(crate) fn isolate(schema: &Schema, object: &str) -> Schema {
+    fn named<T: Clone>(map: &BTreeMap<String, T>, object: &str) -> BTreeMap<String, T> {
+        map.iter()
+            .filter(|(key, _)| {
+                key.strip_prefix(object).is_some_and(|rest| {
+                    rest.is_empty() || rest.starts_with('(') || rest.starts_with('.')
+                })
+            })
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+
+    let mut isolated = Schema::new();
+    isolated.tables = named(&schema.tables, object);
+    isolated.partitions = named(&schema.partitions, object);
+    isolated.views = named(&schema.views, object);
+    isolated.sequences = named(&schema.sequences, object);
+    isolated.enums = named(&schema.enums, object);
+    isolated.domains = named(&schema.domains, object);
+    isolated.functions = named(&schema.functions, object);
+    isolated.aggregates = named(&schema.aggregates, object);
+    isolated.triggers = named(&schema.triggers, object);
+    isolated
+}
+
+/// The objects of an [`isolate`]d schema as JSON, keyed by kind followed by
+/// whatever tells entries of that kind apart, such as `function(integer)`.
+fn definitions(isolated: &Schema) -> Value {
+    fn add<T: Serialize>(
+        definitions: &mut serde_json::Map<String, Value>,
+        kind: &str,
+        map: &BTreeMap<String, T>,
+    ) {
+        for (key, value) in map {
+            let distinguishing = key
+                .find('(')
+                .map(|paren| &key[paren..])
+                .or_else(|| {
+                    (kind == "trigger")
+                        .then(|| key.rsplit('.').next())
+                        .flatten()
+                })
+                .unwrap_or_default();
+            let label = match distinguishing {
+                "" => kind.to_string(),
+                args if args.starts_with('(') => format!("{kind}{args}"),
+                trigger => format!("{kind} {trigger}"),
+            };
+            let value = serde_json::to_value(value).expect("model types serialize to JSON");
+            definitions.insert(label, value);
+        }
+    }
+
+    let mut definitions = serde_json::Map::new();
+    add(&mut definitions, "table", &isolated.tables);
+    add(&mut definitions, "partition", &isolated.partitions);
+    add(&mut definitions, "view", &isolated.views);
+    add(&mut definitions, "sequence", &isolated.sequences);
+    add(&mut definitions, "type", &isolated.enums);
+    add(&mut definitions, "domain", &isolated.domains);
+    add(&mut definitions, "function", &isolated.functions);
+    add(&mut definitions, "aggregate", &isolated.aggregates);
+    add(&mut definitions, "trigger", &isolated.triggers);
+    Value::Object(definitions)
+}
+
+/// Appends the leaves that differ between `current` and `declared`, walking
+/// into JSON objects present on both sides. Arrays are compared whole.
+fn diff_values(
+    path: &str,
+    current: Option<&Value>,
+    declared: Option<&Value>,
+    differences: &mut Vec<FieldDifference>,
+) {
+    if current == declared {
+        return;
+    }
+    if let (Some(Value::Object(current)), Some(Value::Object(declared))) = (current, declared) {
+        let fields: BTreeSet<&String> = current.keys().chain(declared.keys()).collect();
+        for field in fields {
+            let path = if path.is_empty() {
+                field.clone()
+            } else {
+                format!("{path}.{field}")
+            };
+            diff_values(&path, current.get(field), declared.get(field), differences);
+        }
+        return;
+    }
+    differences.push(FieldDifference {
+        field: path.to_string(),
+        current: current.map(ToString::to_string),
+        declared: declared.map(ToString::to_string),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::compute_diff;
+    use crate::diff::planner::plan_migration;
+    use crate::parser::{parse_sql_string, SourceLocation};
+
+    #[test]
+    fn explains_source_differences_and_ordering() {
+        let current = parse_sql_string(
+            "CREATE TABLE users (id integer PRIMARY KEY, email text);
+             CREATE TABLE audit_log (id integer);",
+        )
+        .unwrap();
+        let target = parse_sql_string(
+            "CREATE TABLE users (id integer PRIMARY KEY, email text NOT NULL);
+             CREATE TABLE audit_log (id integer);
+             CREATE TABLE authors (id integer PRIMARY KEY);
+             CREATE TABLE posts (id integer, author_id integer REFERENCES authors (id));",
+        )
+        .unwrap();
+        let ops = plan_migration(compute_diff(&current, &target));
+        let locations = SourceLocations::from([(
+            "public.posts".to_string(),
+            SourceLocation {
+                file: "schema/posts.sql".to_string(),
+                line: 3,
+            },
+        )]);
+
+        let users = explain_object("users", &ops, &current, &target, &locations);
+        assert_eq!(users.object, "public.users");
+        assert_eq!(users.declared_at, None);
+        assert!(users.current[0].starts_with("CREATE TABLE \"public\".\"users\""));
+        assert_eq!(
+            users.differences,
+            vec![FieldDifference {
+                field: "table.columns.email.nullable".to_string(),
+                current: Some("true".to_string()),
+                declared: Some("false".to_string()),
+            }]
+        );
+
+        let posts = explain_object("public.posts", &ops, &current, &target, &locations);
+        assert_eq!(posts.declared_at.as_deref(), Some("schema/posts.sql:3"));
+        assert!(posts.current.is_empty());
+        assert_eq!(posts.differences.len(), 1);
+        assert_eq!(posts.differences[0].field, "table");
+        assert_eq!(posts.differences[0].current, None);
+        assert!(
+            posts.operations.iter().any(|op| op
+                .runs_after
+                .iter()
+                .any(|key| key.contains("public.authors"))),
+            "expected posts to wait for authors: {:?}",
+            posts.operations
+        );
+    }
+}
@@ -0,0 +1,320 @@
+use std::collections::{BTreeSet, HashSet};
+
+use crate::diff::MigrationOp;
+use crate::model::{
+    mentions_identifier, qualified_name, PgType, QualifiedName, ReferenceTarget, Schema,
+};
+use crate::parser::{extract_function_references, extract_table_references};
+
+/// Lists the objects in `schema` that depend on whatever `op` drops or alters:
+/// views selecting from it (transitively), policies and triggers on it or calling
+/// it, foreign keys pointing at it, and columns declared with it. A dropped or
+/// altered column only reports the objects naming that column. Creations and
+/// other additive operations have no blast radius and yield an empty list.
+pub fn affected_objects(op: &MigrationOp, schema: &Schema) -> Vec<String> {
+    if !changes_existing_object(op) {
+        return Vec::new();
+    }
+    if let MigrationOp::DropColumn { table, column }
+    | MigrationOp::AlterColumn { table, column, .. } = op
+    {
+        return column_dependents(table, column, schema);
+    }
+    let Some(subject) = op.subject() else {
+        return Vec::new();
+    };
+
+    let mut affected = BTreeSet::new();
+    let mut referenced: HashSet<String> = HashSet::from([subject.clone()]);
+
+    // Views can select from views, so keep widening until no new view is found.
+    loop {
+        let mut found = Vec::new();
+        for view in schema.views.values() {
+            let view_name = qualified_name(&view.schema, &view.name);
+            if referenced.contains(&view_name) {
+                continue;
+            }
+            let uses_referenced = extract_table_references(&view.query, &view.schema)
+                .iter()
+                .chain(extract_function_references(&view.query, &view.schema).iter())
+                .any(|r| referenced.contains(&r.qualified_name()));
+            if uses_referenced {
+                let kind = if view.materialized {
+                    "materialized view"
+                } else {
+                    "view"
+                };
+                affected.insert(format!("{kind} {view_name}"));
+                found.push(view_name);
+            }
+        }
+        if found.is_empty() {
+            break;
+        }
+        referenced.extend(found);
+    }
+
+    for table in schema.tables.values() {
+        let table_name = qualified_name(&table.schema, &table.name);
+        for policy in &table.policies {
+            let on_subject = table_name == subject;
+            let calls_subject = policy
+                .using_expr
+                .iter()
+                .chain(policy.check_expr.iter())
+                .any(|expr| {
+                    extract_function_references(expr, &table.schema)
+                        .iter()
+                        .any(|r| r.qualified_name() == subject)
+                });
+            if on_subject || calls_subject {
+                affected.insert(format!("policy {} on {table_name}", policy.name));
+            }
+        }
+        for fk in &table.foreign_keys {
+            let target = qualified_name(&fk.referenced_schema, &fk.referenced_table);
+            if target == subject && table_name != subject {
+                affected.insert(format!("foreign key {} on {table_name}", fk.name));
+            }
+        }
+        for column in table.columns.values() {
+            if user_type_name(&column.data_type) == Some(subject.as_str()) {
+                affected.insert(format!("column {table_name}.{}", column.name));
+            }
+        }
+    }
+
+    for trigger in schema.triggers.values() {
+        let target = qualified_name(&trigger.target_schema, &trigger.target_name);
+        let function = qualified_name(&trigger.function_schema, &trigger.function_name);
+        if target == subject || function == subject {
+            affected.insert(format!("trigger {} on {target}", trigger.name));
+        }
+    }
+
+    affected.into_iter().collect()
+}
+
+/// The indexes, constraints and foreign keys listing `column` of `table`, the
+/// triggers firing on `UPDATE OF` it, and the views, policies and other
+/// expressions naming it (see [`Schema::references_of`]). Views selecting the
+/// column from such a view are followed as well.
+fn column_dependents(table: &QualifiedName, column: &str, schema: &Schema) -> Vec<String> {
+    let mut affected = BTreeSet::new();
+    let lists = |columns: &[String]| columns.iter().any(|c| c == column);
+
+    if let Some(owner) = schema.tables.get(&table.to_string()) {
+        if owner
+            .primary_key
+            .as_ref()
+            .is_some_and(|pk| lists(&pk.columns))
+        {
+            affected.insert(format!("primary key on {table}"));
+        }
+        for index in &owner.indexes {
+            let names_column = index
+                .columns
+                .iter()
+                .chain(index.predicate.iter())
+                .any(|expr| mentions_identifier(expr, column));
+            if names_column {
+                affected.insert(format!("index {} on {table}", index.name));
+            }
+        }
+        for unique in &owner.unique_constraints {
+            if lists(&unique.columns) {
+                affected.insert(format!("unique constraint {} on {table}", unique.name));
+            }
+        }
+        for exclusion in &owner.exclusion_constraints {
+            let names_column = exclusion
+                .elements
+                .iter()
+                .map(|element| &element.column_or_expression)
+                .chain(exclusion.where_clause.iter())
+                .any(|expr| mentions_identifier(expr, column));
+            if names_column {
+                affected.insert(format!(
+                    "exclusion constraint {} on {table}",
+                    exclusion.name
+                ));
+            }
+        }
+        for fk in &owner.foreign_keys {
+            if lists(&fk.columns) {
+                affected.insert(format!("foreign key {} on {table}", fk.name));
+            }
+        }
+    }
+    for other in schema.tables.values() {
+        for fk in &other.foreign_keys {
+            if fk.referenced_schema == table.schema
+                && fk.referenced_table == table.name
+                && lists(&fk.referenced_columns)
+            {
+                let other_name = qualified_name(&other.schema, &other.name);
+                affected.insert(format!("foreign key {} on {other_name}", fk.name));
+            }
+        }
+    }
+
+    // A view passes the column on under the same name, so keep following the
+    // views that select it until no new view is found.
+    let mut pending = vec![table.clone()];
+    let mut seen: HashSet<QualifiedName> = HashSet::from([table.clone()]);
+    while let Some(relation) = pending.pop() {
+        let found = schema.references_of(&ReferenceTarget::Column {
+            table: relation,
+            column: column.to_string(),
+        });
+        for view in schema.views.values() {
+            let view_name = QualifiedName::new(&view.schema, &view.name);
+            let kind = if view.materialized {
+                "materialized view"
+            } else {
+                "view"
+            };
+            if found.contains(&format!("{kind} {view_name}")) && seen.insert(view_name.clone()) {
+                pending.push(view_name);
+            }
+        }
+        affected.extend(found);
+    }
+
+    affected.into_iter().collect()
+}
+
+fn changes_existing_object(op: &MigrationOp) -> bool {
+    matches!(
+        op,
+        MigrationOp::DropTable(_)
+            | MigrationOp::DropColumn { .. }
+            | MigrationOp::AlterColumn { .. }
+            | MigrationOp::DropPrimaryKey { .. }
+            | MigrationOp::DropView { .. }
+            | MigrationOp::AlterView { .. }
+            | MigrationOp::AlterViewOptions { .. }
+            | MigrationOp::DropFunction { .. }
+            | MigrationOp::AlterFunction { .. }
+            | MigrationOp::DropEnum(_)
+            | MigrationOp::AddEnumValue { .. }
+            | MigrationOp::DropDomain(_)
+            | MigrationOp::AlterDomain { .. }
+            | MigrationOp::DropSequence(_)
+            | MigrationOp::AlterSequence { .. }
+    )
+}
+
+fn user_type_name(data_type: &PgType) -> Option<&str> {
+    match data_type {
+        PgType::Array(inner) => user_type_name(inner),
+        PgType::UserDefined(name) => Some(name),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::ColumnChanges;
+    use crate::model::QualifiedName;
+    use crate::parser::parse_sql_string;
+
+    fn schema() -> Schema {
+        parse_sql_string(
+            r#"
+            CREATE TYPE status AS ENUM ('active', 'inactive');
+            CREATE TABLE users (id BIGINT PRIMARY KEY, email TEXT, state status);
+            CREATE TABLE orders (id BIGINT PRIMARY KEY, user_id BIGINT REFERENCES users(id));
+            CREATE VIEW active_users AS SELECT id, email FROM users;
+            CREATE VIEW active_emails AS SELECT email FROM active_users;
+            CREATE FUNCTION touch() RETURNS trigger LANGUAGE plpgsql AS $$ BEGIN RETURN NEW; END $$;
+            CREATE TRIGGER users_touch BEFORE UPDATE ON users FOR EACH ROW EXECUTE FUNCTION touch();
+            CREATE FUNCTION audit_email() RETURNS trigger LANGUAGE plpgsql AS $$ BEGIN RETURN NEW; END $$;
+            CREATE TRIGGER users_email_audit AFTER UPDATE OF email ON users
+                FOR EACH ROW EXECUTE FUNCTION audit_email();
+            CREATE INDEX users_email_idx ON users (lower(email));
+            ALTER TABLE users ENABLE ROW LEVEL SECURITY;
+            CREATE POLICY own_rows ON users USING (id = 1);
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn column_type_change_reports_objects_naming_the_column() {
+        let op = MigrationOp::AlterColumn {
+            table: QualifiedName::new("public", "users"),
+            column: "email".to_string(),
+            changes: ColumnChanges {
+                data_type: Some(PgType::Varchar(Some(320))),
+                nullable: None,
+                default: None,
+                statistics: None,
+                compression: None,
+                identity: None,
+            },
+        };
+        assert_eq!(
+            affected_objects(&op, &schema()),
+            vec![
+                "index users_email_idx on public.users",
+                "trigger users_email_audit on public.users",
+                "view public.active_emails",
+                "view public.active_users",
+            ]
+        );
+    }
+
+    #[test]
+    fn dropped_key_column_reports_constraints_and_foreign_keys_on_it() {
+        let op = MigrationOp::DropColumn {
+            table: QualifiedName::new("public", "users"),
+            column: "id".to_string(),
+        };
+        assert_eq!(
+            affected_objects(&op, &schema()),
+            vec![
+                "foreign key orders_user_id_fkey on public.orders",
+                "policy own_rows on public.users",
+                "primary key on public.users",
+                "view public.active_users",
+            ]
+        );
+    }
+
+    #[test]
+    fn dropped_function_reports_triggers_calling_it() {
+        let op = MigrationOp::DropFunction {
+            name: "public.touch".to_string(),
+            args: String::new(),
+        };
+        assert_eq!(
+            affected_objects(&op, &schema()),
+            vec!["trigger users_touch on public.users"]
+        );
+    }
+
+    #[test]
+    fn enum_change_reports_columns_using_it() {
+        let op = MigrationOp::AddEnumValue {
+            enum_name: "public.status".to_string(),
+            value: "banned".to_string(),
+            position: None,
+        };
+        assert_eq!(
+            affected_objects(&op, &schema()),
+            vec!["column public.users.state"]
+        );
+    }
+
+    #[test]
+    fn additive_operations_have_no_blast_radius() {
+        let op = MigrationOp::AddColumn {
+            table: QualifiedName::new("public", "users"),
+            column: schema().tables["public.users"].columns["email"].clone(),
+        };
+        assert!(affected_objects(&op, &schema()).is_empty());
+    }
+}
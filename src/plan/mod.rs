@@ -1,13 +1,41 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
 
-use crate::diff::{compute_diff_with_flags, planner::plan_migration_checked, MigrationOp};
-use crate::filter::{filter_by_target_schemas, filter_schema, Filter};
+use serde::{Deserialize, Serialize};
+
+use crate::diff::{
+    compute_diff_with_options, orphaned_objects, planner::plan_migration_checked, AdoptionMode,
+    DefaultComparison, DiffOptions, MigrationOp, TypeEquivalences,
+};
+use crate::filter::{exclude_external, filter_by_target_schemas, filter_schema, Filter};
+use crate::lint::{cascade_schema_drops, inbound_foreign_key_drops};
 use crate::model::Schema;
 use crate::pg::connection::PgConnection;
-use crate::pg::introspect::introspect_schema;
-use crate::provider::load_schema_from_sources;
+use crate::pg::introspect::introspect_schema_with_known_bodies;
+use crate::pg::metrics::Metrics;
+use crate::pg::retention::expired_partitions;
+use crate::pg::sequence_values::{lagging_sequences, sequence_values};
+use crate::pg::sqlgen::generate_sql;
+use crate::provider::{hash_sources, load_schema_from_sources};
 use crate::util::{Result, SchemaError};
 
+mod cache;
+mod definition_diff;
+mod explain;
+mod impact;
+mod risk;
+mod trigger_functions;
+
+pub use cache::{plan_cache_key, PlanCache};
+pub use definition_diff::render_diff;
+pub(crate) use explain::isolate;
+pub use explain::{explain_object, ExplainedOperation, Explanation, FieldDifference};
+pub use impact::affected_objects;
+pub use risk::{assess_risk, risk_tables, OperationRisk, PlanRisk, RiskLevel};
+pub use trigger_functions::check_trigger_functions;
+pub(crate) use trigger_functions::BUILTIN_TRIGGER_FUNCTIONS;
+
 /// The resolved schemas and computed migration operations from a plan pass.
 ///
 /// Carries both the ops and the filtered schemas so callers can pass them
@@ -19,6 +47,33 @@ pub struct MigrationPlan {
     pub current_schema: Schema,
     /// The filtered target (desired) schema.
     pub target_schema: Schema,
+    /// Introspection timings, when `PlanOptions::collect_metrics` is set.
+    pub metrics: Option<Metrics>,
+    /// With `PlanOptions::orphans_only`, what the database has and the
+    /// sources leave out, grouped by kind; see [`orphaned_objects`].
+    pub orphans: BTreeMap<String, Vec<String>>,
+}
+
+/// Rewrites the planned operations before any SQL is generated from them,
+/// for embedding applications that force `CONCURRENTLY` on some tables, add
+/// grants of their own and so on. The operations it returns are used as-is:
+/// they are not re-ordered, and a transform that adds operations must place
+/// them after whatever they depend on.
+#[derive(Clone)]
+pub struct OpTransform(Arc<dyn Fn(Vec<MigrationOp>) -> Vec<MigrationOp> + Send + Sync>);
+
+impl OpTransform {
+    pub fn new(
+        transform: impl Fn(Vec<MigrationOp>) -> Vec<MigrationOp> + Send + Sync + 'static,
+    ) -> Self {
+        Self(Arc::new(transform))
+    }
+}
+
+impl fmt::Debug for OpTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("OpTransform")
+    }
 }
 
 /// Options that control how the diff is computed.
@@ -29,13 +84,98 @@ pub struct PlanOptions {
     pub excluded_grant_roles: HashSet<String>,
     pub include_extension_objects: bool,
     pub exclude_unmanaged_partitions: bool,
+    /// Reuse parsed schemas and plans from earlier runs with identical
+    /// inputs. Not used when a source cannot be hashed (Drizzle configs).
+    pub cache: Option<PlanCache>,
+    /// Time introspection and count its queries, returned in
+    /// `MigrationPlan::metrics`.
+    pub collect_metrics: bool,
+    /// `Additive` never drops or strips anything the sources leave out.
+    pub adoption_mode: AdoptionMode,
+    /// Roles named in the sources mapped to the roles that stand for them in
+    /// this database; see `Schema::apply_role_aliases`.
+    pub role_aliases: BTreeMap<String, String>,
+    /// Drop the foreign keys that reference a dropped table before dropping
+    /// it (see `lint::inbound_foreign_keys`), and drop schemas with `CASCADE`.
+    pub cascade: bool,
+    /// Applied to the finished plan, including one loaded from the cache
+    /// (which stores the plan as it was before the transform).
+    pub op_transform: Option<OpTransform>,
+    /// Column types the diff treats as the same; see [`TypeEquivalences`].
+    pub type_equivalences: TypeEquivalences,
+    pub default_comparison: DefaultComparison,
+    /// See [`DiffOptions::detect_renames`].
+    pub detect_renames: bool,
+    /// Only list the objects a full plan would drop, in
+    /// `MigrationPlan::orphans`, and plan no operations.
+    pub orphans_only: bool,
+    /// Move sequences forward to the values the sources set with `setval`.
+    pub sequence_values: bool,
+    /// Create and drop indexes `CONCURRENTLY`; see [`with_concurrent_indexes`].
+    pub concurrent_indexes: bool,
+}
+
+impl PlanOptions {
+    pub fn diff_options(&self) -> DiffOptions<'_> {
+        DiffOptions {
+            manage_ownership: self.manage_ownership,
+            manage_grants: self.manage_grants,
+            excluded_grant_roles: &self.excluded_grant_roles,
+            adoption_mode: self.adoption_mode,
+            type_equivalences: &self.type_equivalences,
+            default_comparison: self.default_comparison,
+            detect_renames: self.detect_renames,
+        }
+    }
+
+    fn transform_ops(&self, ops: Vec<MigrationOp>) -> Vec<MigrationOp> {
+        let ops = if self.concurrent_indexes {
+            with_concurrent_indexes(ops)
+        } else {
+            ops
+        };
+        match &self.op_transform {
+            Some(OpTransform(transform)) => transform(ops),
+            None => ops,
+        }
+    }
+
+    /// Hash of everything besides the sources and the database that changes
+    /// the computed plan.
+    fn cache_hash(&self, target_schemas: &[String], filter: &Filter) -> String {
+        use sha2::{Digest, Sha256};
+
+        let mut excluded: Vec<&String> = self.excluded_grant_roles.iter().collect();
+        excluded.sort();
+        let description = format!(
+            "target_schemas={target_schemas:?};filter={};manage_ownership={};manage_grants={};\
+             excluded_grant_roles={excluded:?};include_extension_objects={};\
+             exclude_unmanaged_partitions={};adoption_mode={:?};role_aliases={:?};cascade={};\
+             type_equivalences={};default_comparison={:?};detect_renames={}",
+            filter.cache_key(),
+            self.manage_ownership,
+            self.manage_grants,
+            self.include_extension_objects,
+            self.exclude_unmanaged_partitions,
+            self.adoption_mode,
+            self.role_aliases,
+            self.cascade,
+            self.type_equivalences.cache_key(),
+            self.default_comparison,
+            self.detect_renames,
+        );
+        hex::encode(Sha256::digest(description.as_bytes()))
+    }
 }
 
 /// Load the desired schema from `schema_sources`, introspect the current
 /// database state, apply the given `filter` and `target_schemas` constraints,
-/// then compute and return the ordered migration operations.
+/// then compute and return the ordered migration operations. Changes to
+/// schemas the filter marks observe-only are left out of the plan.
 ///
 /// This covers the shared sequence used by both `plan` and `apply` CLI commands.
+/// With `options.cache` set, parsing is skipped when the source files are
+/// unchanged, and diffing and ordering when the database is too.
 pub async fn compute_migration_plan(
     schema_sources: &[String],
     connection: &PgConnection,
@@ -43,38 +183,379 @@ pub async fn compute_migration_plan(
     filter: &Filter,
     options: &PlanOptions,
 ) -> Result<MigrationPlan> {
-    let raw_target = load_schema_from_sources(schema_sources)?;
-    let target_schema = filter_schema(
-        &filter_by_target_schemas(&raw_target, target_schemas),
-        filter,
+    let cache = match &options.cache {
+        Some(cache) => hash_sources(schema_sources)?.map(|hash| (cache, hash)),
+        None => None,
+    };
+
+    let cached_target = cache
+        .as_ref()
+        .and_then(|(cache, hash)| cache.load_schema(hash));
+    let mut raw_target = match cached_target {
+        Some(schema) => schema,
+        None => {
+            let schema = load_schema_from_sources(schema_sources)?;
+            if let Some((cache, hash)) = &cache {
+                cache.store_schema(hash, &schema);
+            }
+            schema
+        }
+    };
+    raw_target.apply_role_aliases(&options.role_aliases);
+    let target_schema = exclude_external(
+        &filter_schema(
+            &filter_by_target_schemas(&raw_target, target_schemas),
+            filter,
+        ),
+        &raw_target.external,
     );
 
-    let raw_current = introspect_schema(
+    if options.collect_metrics {
+        connection.start_metrics();
+    }
+    let raw_current = introspect_schema_with_known_bodies(
         connection,
         target_schemas,
         options.include_extension_objects,
+        &target_schema,
     )
-    .await?;
-    let current_schema = filter_schema(&raw_current, filter);
+    .await;
+    let metrics = connection.finish_metrics();
+    let raw_current = raw_current?;
+    let current_schema =
+        exclude_external(&filter_schema(&raw_current, filter), &raw_target.external);
+    let expired = if target_schema.partition_retention.is_empty() {
+        Vec::new()
+    } else {
+        expired_partitions(connection, &current_schema, &target_schema).await?
+    };
+    let lagging = if options.sequence_values && !target_schema.sequence_values.is_empty() {
+        lagging_sequences(
+            &sequence_values(connection, target_schemas).await?,
+            &target_schema.sequence_values,
+        )
+    } else {
+        Vec::new()
+    };
     let current_schema = if options.exclude_unmanaged_partitions {
         crate::filter::exclude_unmanaged_partitions(&current_schema, &target_schema)
     } else {
         current_schema
     };
+    if options.orphans_only {
+        return Ok(MigrationPlan {
+            ops: Vec::new(),
+            orphans: orphaned_objects(&current_schema, &target_schema, &options.diff_options()),
+            current_schema,
+            target_schema,
+            metrics,
+        });
+    }
+
+    let ops_key = cache.as_ref().map(|(cache, hash)| {
+        let key = plan_cache_key(
+            hash,
+            &raw_current.fingerprint(),
+            &options.cache_hash(target_schemas, filter),
+        );
+        (*cache, key)
+    });
+    if let Some(ops) = ops_key
+        .as_ref()
+        .and_then(|(cache, key)| cache.load_ops(key))
+    {
+        return Ok(MigrationPlan {
+            ops: options.transform_ops(with_sequence_values(
+                with_retention_drops(ops, &expired, filter),
+                &lagging,
+                filter,
+            )),
+            current_schema,
+            target_schema,
+            metrics,
+            orphans: BTreeMap::new(),
+        });
+    }
 
-    let ops = plan_migration_checked(compute_diff_with_flags(
+    let ops: Vec<MigrationOp> = plan_migration_checked(compute_diff_with_options(
         &current_schema,
         &target_schema,
-        options.manage_ownership,
-        options.manage_grants,
-        &options.excluded_grant_roles,
+        &options.diff_options(),
     ))
-    .map_err(|e| SchemaError::ValidationError(e.to_string()))?;
+    .map_err(|e| SchemaError::ValidationError(e.to_string()))?
+    .into_iter()
+    .filter(|op| filter.should_plan(op))
+    .collect();
+    let ops = if options.cascade {
+        let mut cascaded = inbound_foreign_key_drops(&ops, &current_schema);
+        let ops = if cascaded.is_empty() {
+            ops
+        } else {
+            cascaded.extend(ops);
+            plan_migration_checked(cascaded)
+                .map_err(|e| SchemaError::ValidationError(e.to_string()))?
+        };
+        cascade_schema_drops(ops)
+    } else {
+        ops
+    };
+    check_trigger_functions(&ops, &raw_target)?;
+    if let Some((cache, key)) = &ops_key {
+        cache.store_ops(key, &ops);
+    }
 
     Ok(MigrationPlan {
-        ops,
+        ops: options.transform_ops(with_sequence_values(
+            with_retention_drops(ops, &expired, filter),
+            &lagging,
+            filter,
+        )),
         current_schema,
         target_schema,
+        metrics,
+        orphans: BTreeMap::new(),
+    })
+}
+
+/// `ops` followed by a `DropPartition` for each `expired` partition the plan
+/// does not drop already. They are added after the plan is cached, since
+/// which partitions have expired changes with the clock.
+pub(crate) fn with_retention_drops(
+    mut ops: Vec<MigrationOp>,
+    expired: &[String],
+    filter: &Filter,
+) -> Vec<MigrationOp> {
+    for name in expired {
+        let drop = MigrationOp::DropPartition(name.clone());
+        if !ops.contains(&drop) && filter.should_plan(&drop) {
+            ops.push(drop);
+        }
+    }
+    ops
+}
+
+/// `ops` followed by a `SetSequenceValue` for each `lagging` sequence. Like
+/// retention drops they are added after the plan is cached, since the
+/// values the database has reached change with every insert.
+fn with_sequence_values(
+    mut ops: Vec<MigrationOp>,
+    lagging: &[(String, i64)],
+    filter: &Filter,
+) -> Vec<MigrationOp> {
+    for (name, value) in lagging {
+        let op = MigrationOp::SetSequenceValue {
+            name: name.clone(),
+            value: *value,
+        };
+        if filter.should_plan(&op) {
+            ops.push(op);
+        }
+    }
+    ops
+}
+
+/// A plan split around its transaction, since PostgreSQL refuses to run
+/// `CREATE INDEX CONCURRENTLY` or `DROP INDEX CONCURRENTLY` inside one.
+#[derive(Debug, Default, PartialEq)]
+pub struct TransactionPhases {
+    /// Concurrent index drops, run before the transaction begins.
+    pub before: Vec<MigrationOp>,
+    pub transaction: Vec<MigrationOp>,
+    /// Concurrent index builds, run after the transaction commits.
+    pub after: Vec<MigrationOp>,
+}
+
+/// Splits `ops` into [`TransactionPhases`], keeping the order within each.
+/// Indexes are dropped before the transaction so it can reuse their names,
+/// and built after it commits so the tables and columns they cover exist.
+pub fn transaction_phases(ops: Vec<MigrationOp>) -> TransactionPhases {
+    let mut phases = TransactionPhases::default();
+    for op in ops {
+        match op {
+            MigrationOp::DropIndexConcurrently { .. } => phases.before.push(op),
+            MigrationOp::CreateIndexConcurrently { .. }
+            | MigrationOp::AddUniqueConstraintUsingIndex { .. } => phases.after.push(op),
+            _ => phases.transaction.push(op),
+        }
+    }
+    phases
+}
+
+/// `ops` with each index created or dropped `CONCURRENTLY`, so writes to its
+/// table are not blocked while it is built, and reordered into
+/// [`transaction_phases`] order so the plan reads as apply runs it. A unique
+/// constraint becomes a unique index built concurrently and then attached with
+/// `UNIQUE USING INDEX`. A unique constraint or index whose columns a foreign
+/// key added in the same plan references stays in the transaction, as the
+/// foreign key needs it there.
+pub fn with_concurrent_indexes(ops: Vec<MigrationOp>) -> Vec<MigrationOp> {
+    let referenced: HashSet<(String, Vec<String>)> = ops
+        .iter()
+        .flat_map(|op| match op {
+            MigrationOp::CreateTable(table) => table.foreign_keys.iter().collect(),
+            MigrationOp::AddForeignKey { foreign_key, .. } => vec![foreign_key],
+            _ => Vec::new(),
+        })
+        .map(|fk| {
+            (
+                crate::model::qualified_name(&fk.referenced_schema, &fk.referenced_table),
+                fk.referenced_columns.clone(),
+            )
+        })
+        .collect();
+
+    let phases = transaction_phases(
+        ops.into_iter()
+            .flat_map(|op| match op {
+                MigrationOp::AddIndex { table, index }
+                    if index.unique
+                        && referenced.contains(&(table.to_string(), index.columns.clone())) =>
+                {
+                    vec![MigrationOp::AddIndex { table, index }]
+                }
+                MigrationOp::AddIndex { table, index } => {
+                    vec![MigrationOp::CreateIndexConcurrently { table, index }]
+                }
+                MigrationOp::AddUniqueConstraint {
+                    table,
+                    unique_constraint,
+                } if !referenced
+                    .contains(&(table.to_string(), unique_constraint.columns.clone())) =>
+                {
+                    vec![
+                        MigrationOp::CreateIndexConcurrently {
+                            table: table.clone(),
+                            index: crate::model::Index {
+                                name: unique_constraint.name.clone(),
+                                columns: unique_constraint.columns,
+                                unique: true,
+                                index_type: crate::model::IndexType::BTree,
+                                predicate: None,
+                                nulls_not_distinct: unique_constraint.nulls_not_distinct,
+                                column_options: Vec::new(),
+                            },
+                        },
+                        MigrationOp::AddUniqueConstraintUsingIndex {
+                            table,
+                            index_name: unique_constraint.name,
+                            deferrable: unique_constraint.deferrable,
+                            initially_deferred: unique_constraint.initially_deferred,
+                        },
+                    ]
+                }
+                MigrationOp::DropIndex { table, index_name } => {
+                    vec![MigrationOp::DropIndexConcurrently { table, index_name }]
+                }
+                op => vec![op],
+            })
+            .collect(),
+    );
+    phases
+        .before
+        .into_iter()
+        .chain(phases.transaction)
+        .chain(phases.after)
+        .collect()
+}
+
+/// One operation of a saved plan, as written to `plan --json` output.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlannedOperation {
+    /// Identity used to match the same operation across two plans.
+    pub key: String,
+    pub statements: Vec<String>,
+    /// Existing objects that depend on what this operation drops or alters.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub affected_objects: Vec<String>,
+}
+
+/// Pairs each operation with its identity, generated SQL, and the objects in
+/// `current` it affects.
+pub fn planned_operations(ops: &[MigrationOp], current: &Schema) -> Vec<PlannedOperation> {
+    ops.iter()
+        .map(|op| PlannedOperation {
+            key: op.identity(),
+            statements: generate_sql(std::slice::from_ref(op)),
+            affected_objects: affected_objects(op, current),
+        })
+        .collect()
+}
+
+/// An operation present in both plans whose SQL differs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChangedOperation {
+    pub key: String,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+}
+
+/// The delta between two saved plans.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PlanDiff {
+    pub added: Vec<PlannedOperation>,
+    pub removed: Vec<PlannedOperation>,
+    pub changed: Vec<ChangedOperation>,
+    pub unchanged_count: usize,
+}
+
+impl PlanDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compares two plans operation by operation. Added and changed operations are
+/// listed in `after` order, removed ones in `before` order. Reordering alone is
+/// not reported. Operations sharing a key are paired up in plan order.
+pub fn diff_plans(before: &[PlannedOperation], after: &[PlannedOperation]) -> PlanDiff {
+    let mut before_by_key: HashMap<&str, std::collections::VecDeque<usize>> = HashMap::new();
+    for (index, op) in before.iter().enumerate() {
+        before_by_key
+            .entry(op.key.as_str())
+            .or_default()
+            .push_back(index);
+    }
+
+    let mut diff = PlanDiff::default();
+    for op in after {
+        let previous = before_by_key
+            .get_mut(op.key.as_str())
+            .and_then(|indexes| indexes.pop_front())
+            .map(|index| &before[index]);
+        match previous {
+            None => diff.added.push(op.clone()),
+            Some(previous) if previous.statements != op.statements => {
+                diff.changed.push(ChangedOperation {
+                    key: op.key.clone(),
+                    before: previous.statements.clone(),
+                    after: op.statements.clone(),
+                })
+            }
+            Some(_) => diff.unchanged_count += 1,
+        }
+    }
+    let mut unmatched: Vec<usize> = before_by_key.into_values().flatten().collect();
+    unmatched.sort_unstable();
+    diff.removed = unmatched
+        .into_iter()
+        .map(|index| before[index].clone())
+        .collect();
+    diff
+}
+
+#[derive(Deserialize)]
+struct SavedPlan {
+    steps: Option<Vec<PlannedOperation>>,
+}
+
+/// Reads the operations from a plan saved with `plan --json`.
+pub fn parse_saved_plan(json: &str) -> Result<Vec<PlannedOperation>> {
+    let saved: SavedPlan = serde_json::from_str(json)
+        .map_err(|e| SchemaError::ParseError(format!("Invalid saved plan: {e}")))?;
+    saved.steps.ok_or_else(|| {
+        SchemaError::ParseError(
+            "Saved plan has no \"steps\" field; regenerate it with `plan --json`".to_string(),
+        )
     })
 }
 
@@ -84,17 +565,257 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn op_transform_rewrites_the_plan() {
+        let options = PlanOptions {
+            op_transform: Some(OpTransform::new(|ops| {
+                ops.into_iter()
+                    .map(|op| match op {
+                        MigrationOp::AddIndex { table, index } if table.schema == "tenant" => {
+                            MigrationOp::CreateIndexConcurrently { table, index }
+                        }
+                        op => op,
+                    })
+                    .collect()
+            })),
+            ..PlanOptions::default()
+        };
+        let target = crate::parser::parse_sql_string(
+            "CREATE SCHEMA tenant;
+             CREATE TABLE tenant.events (id BIGINT, kind TEXT);
+             CREATE INDEX events_kind_idx ON tenant.events (kind);",
+        )
+        .unwrap();
+        let mut current = target.clone();
+        current
+            .tables
+            .get_mut("tenant.events")
+            .unwrap()
+            .indexes
+            .clear();
+
+        let ops = options.transform_ops(
+            plan_migration_checked(compute_diff_with_options(
+                &current,
+                &target,
+                &options.diff_options(),
+            ))
+            .unwrap(),
+        );
+        assert!(matches!(
+            ops.as_slice(),
+            [MigrationOp::CreateIndexConcurrently { .. }]
+        ));
+        assert_eq!(options.transform_ops(Vec::new()), Vec::new());
+    }
+
+    #[test]
+    fn concurrent_indexes_run_around_the_transaction() {
+        let current = crate::parser::parse_sql_string(
+            "CREATE TABLE users (id BIGINT PRIMARY KEY, email TEXT, name TEXT);
+             CREATE INDEX users_name_idx ON users (name);",
+        )
+        .unwrap();
+        let target = crate::parser::parse_sql_string(
+            "CREATE TABLE users (
+                 id BIGINT PRIMARY KEY, email TEXT, name TEXT, handle TEXT,
+                 CONSTRAINT users_handle_key UNIQUE (handle)
+             );
+             CREATE INDEX users_email_idx ON users (email);",
+        )
+        .unwrap();
+        let options = PlanOptions {
+            concurrent_indexes: true,
+            ..PlanOptions::default()
+        };
+
+        let ops = options.transform_ops(
+            plan_migration_checked(compute_diff_with_options(
+                &current,
+                &target,
+                &options.diff_options(),
+            ))
+            .unwrap(),
+        );
+        assert!(matches!(
+            ops.first(),
+            Some(MigrationOp::DropIndexConcurrently { index_name, .. })
+                if index_name == "users_name_idx"
+        ));
+        let built: Vec<&str> = ops
+            .iter()
+            .filter_map(|op| match op {
+                MigrationOp::CreateIndexConcurrently { index, .. } => Some(index.name.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(built.len(), 2);
+        assert!(built.contains(&"users_email_idx") && built.contains(&"users_handle_key"));
+        assert!(matches!(
+            ops.last(),
+            Some(MigrationOp::AddUniqueConstraintUsingIndex { index_name, .. })
+                if index_name == "users_handle_key"
+        ));
+        assert!(!ops
+            .iter()
+            .any(|op| matches!(op, MigrationOp::AddUniqueConstraint { .. })));
+
+        let phases = transaction_phases(ops);
+        assert_eq!(phases.before.len(), 1);
+        assert_eq!(phases.after.len(), 3);
+        assert!(phases
+            .transaction
+            .iter()
+            .any(|op| matches!(op, MigrationOp::AddColumn { .. })));
+    }
+
+    #[test]
+    fn unique_constraint_referenced_by_a_new_foreign_key_stays_in_the_transaction() {
+        let current = crate::parser::parse_sql_string(
+            "CREATE TABLE users (id BIGINT PRIMARY KEY, handle TEXT, email TEXT);
+             CREATE TABLE posts (id BIGINT PRIMARY KEY, author TEXT, editor TEXT);",
+        )
+        .unwrap();
+        let target = crate::parser::parse_sql_string(
+            "CREATE TABLE users (
+                 id BIGINT PRIMARY KEY, handle TEXT, email TEXT,
+                 CONSTRAINT users_handle_key UNIQUE (handle)
+             );
+             CREATE UNIQUE INDEX users_email_idx ON users (email);
+             CREATE TABLE posts (
+                 id BIGINT PRIMARY KEY, author TEXT, editor TEXT,
+                 CONSTRAINT posts_author_fkey FOREIGN KEY (author) REFERENCES users (handle),
+                 CONSTRAINT posts_editor_fkey FOREIGN KEY (editor) REFERENCES users (email)
+             );",
+        )
+        .unwrap();
+
+        let ops = with_concurrent_indexes(
+            plan_migration_checked(crate::diff::compute_diff(&current, &target)).unwrap(),
+        );
+        assert!(ops.iter().any(|op| matches!(
+            op,
+            MigrationOp::AddUniqueConstraint { unique_constraint, .. }
+                if unique_constraint.name == "users_handle_key"
+        )));
+        assert!(ops.iter().any(|op| matches!(
+            op,
+            MigrationOp::AddIndex { index, .. } if index.name == "users_email_idx"
+        )));
+        assert!(transaction_phases(ops).after.is_empty());
+    }
+
     #[test]
     fn migration_plan_exposes_ops_and_schemas() {
         let plan = MigrationPlan {
             ops: vec![MigrationOp::DropTable("t".to_string())],
             current_schema: Schema::default(),
             target_schema: Schema::default(),
+            metrics: None,
+            orphans: BTreeMap::new(),
         };
         assert_eq!(plan.ops.len(), 1);
         assert!(matches!(plan.ops[0], MigrationOp::DropTable(_)));
     }
 
+    fn step(key: &str, sql: &str) -> PlannedOperation {
+        PlannedOperation {
+            key: key.to_string(),
+            statements: vec![sql.to_string()],
+            affected_objects: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn diff_plans_reports_added_removed_and_changed() {
+        let before = vec![
+            step("CreateTable:public.users", "CREATE TABLE users ();"),
+            step("CreateTable:public.legacy", "CREATE TABLE legacy ();"),
+            step("CreateView:public.v", "CREATE VIEW v AS SELECT 1;"),
+        ];
+        let after = vec![
+            step("CreateTable:public.users", "CREATE TABLE users ();"),
+            step("CreateView:public.v", "CREATE VIEW v AS SELECT 2;"),
+            step("CreateTable:public.orders", "CREATE TABLE orders ();"),
+        ];
+
+        let diff = diff_plans(&before, &after);
+
+        assert_eq!(diff.added, vec![after[2].clone()]);
+        assert_eq!(diff.removed, vec![before[1].clone()]);
+        assert_eq!(
+            diff.changed,
+            vec![ChangedOperation {
+                key: "CreateView:public.v".to_string(),
+                before: vec!["CREATE VIEW v AS SELECT 1;".to_string()],
+                after: vec!["CREATE VIEW v AS SELECT 2;".to_string()],
+            }]
+        );
+        assert_eq!(diff.unchanged_count, 1);
+    }
+
+    #[test]
+    fn diff_plans_ignores_reordering() {
+        let a = step("CreateTable:public.a", "CREATE TABLE a ();");
+        let b = step("CreateTable:public.b", "CREATE TABLE b ();");
+        let diff = diff_plans(&[a.clone(), b.clone()], &[b, a]);
+        assert!(diff.is_empty());
+        assert_eq!(diff.unchanged_count, 2);
+    }
+
+    #[test]
+    fn diff_plans_counts_operations_sharing_a_key() {
+        let hint = step("BackfillHint:public.t.c", "-- backfill t.c");
+        let diff = diff_plans(std::slice::from_ref(&hint), &[hint.clone(), hint.clone()]);
+        assert_eq!(diff.added, vec![hint.clone()]);
+        assert_eq!(diff.unchanged_count, 1);
+
+        let diff = diff_plans(&[hint.clone(), hint.clone()], std::slice::from_ref(&hint));
+        assert_eq!(diff.removed, vec![hint]);
+    }
+
+    #[test]
+    fn operation_identities_are_explicit_strings() {
+        use crate::diff::GrantObjectKind;
+        use crate::model::QualifiedName;
+
+        assert_eq!(
+            MigrationOp::DropTable("public.users".to_string()).identity(),
+            "DropTable:public.users"
+        );
+        assert_eq!(
+            MigrationOp::DropColumn {
+                table: QualifiedName::new("public", "users"),
+                column: "email".to_string(),
+            }
+            .identity(),
+            "DropColumn:public.users.email"
+        );
+        assert_eq!(
+            MigrationOp::RevokePrivileges {
+                object_kind: GrantObjectKind::Function,
+                schema: "public".to_string(),
+                name: "total".to_string(),
+                args: Some("integer".to_string()),
+                grantee: "app".to_string(),
+                privileges: vec![],
+                revoke_grant_option: false,
+            }
+            .identity(),
+            "RevokePrivileges:public.total(integer):function:app"
+        );
+    }
+
+    #[test]
+    fn saved_plan_round_trips_through_plan_json() {
+        let ops = vec![MigrationOp::DropTable("public.t".to_string())];
+        let steps = planned_operations(&ops, &Schema::default());
+        let json = serde_json::json!({ "operations": [], "steps": steps }).to_string();
+
+        assert_eq!(parse_saved_plan(&json).unwrap(), steps);
+        assert!(parse_saved_plan(r#"{"operations": []}"#).is_err());
+    }
+
     #[test]
     fn plan_options_default_disables_ownership_and_grants() {
         let options = PlanOptions::default();
@@ -102,5 +823,6 @@ mod tests {
         assert!(!options.manage_grants);
         assert!(options.excluded_grant_roles.is_empty());
         assert!(!options.include_extension_objects);
+        assert!(!options.collect_metrics);
     }
 }
@@ -0,0 +1,267 @@
+//! A rough risk score per operation and per plan, so a reviewer can find the
+//! operations that need attention in a large plan. Each operation scores
+//! 0-100 from what it would do to a live database: the lint results it
+//! raises, whether it destroys data, the strongest lock it takes, and whether
+//! it rewrites a table, weighted by the table's estimated size. The plan
+//! scores as its riskiest operation.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::diff::MigrationOp;
+use crate::lint::locks::{detect_lock_hazards, LockLevel};
+use crate::lint::{lint_migration_plan, LintOptions, LintSeverity, TableSize};
+use crate::pg::sqlgen::rewritten_tables;
+
+const MAX_SCORE: u32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+impl RiskLevel {
+    fn from_score(score: u32) -> Self {
+        match score {
+            0..=19 => Self::Low,
+            20..=49 => Self::Medium,
+            _ => Self::High,
+        }
+    }
+}
+
+impl fmt::Display for RiskLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OperationRisk {
+    /// The operation's identity, as in `plan --json` steps.
+    pub key: String,
+    pub score: u32,
+    /// What contributed to the score, such as `ACCESS EXCLUSIVE lock`.
+    pub factors: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PlanRisk {
+    /// The highest operation score.
+    pub score: u32,
+    pub level: RiskLevel,
+    /// One entry per operation, in plan order.
+    pub operations: Vec<OperationRisk>,
+}
+
+impl PlanRisk {
+    /// Operations that scored above zero, riskiest first.
+    pub fn riskiest(&self) -> Vec<&OperationRisk> {
+        let mut risky: Vec<&OperationRisk> =
+            self.operations.iter().filter(|op| op.score > 0).collect();
+        risky.sort_by_key(|op| std::cmp::Reverse(op.score));
+        risky
+    }
+}
+
+/// Tables whose estimated size [`assess_risk`] takes into account: those the
+/// plan rewrites or locks exclusively.
+pub fn risk_tables(ops: &[MigrationOp]) -> Vec<String> {
+    let mut tables: Vec<String> = rewritten_tables(ops)
+        .into_iter()
+        .map(|table| table.to_string())
+        .chain(
+            detect_lock_hazards(ops)
+                .into_iter()
+                .filter(|warning| warning.lock_level == LockLevel::AccessExclusive)
+                .map(|warning| warning.table),
+        )
+        .collect();
+    tables.sort();
+    tables.dedup();
+    tables
+}
+
+/// Scores each of `ops` as linted with `lint_options`. `table_sizes` holds
+/// estimates for the [`risk_tables`]; a table without one counts as small.
+pub fn assess_risk(
+    ops: &[MigrationOp],
+    lint_options: &LintOptions,
+    table_sizes: &BTreeMap<String, TableSize>,
+) -> PlanRisk {
+    let operations: Vec<OperationRisk> = ops
+        .iter()
+        .map(|op| assess_operation(op, lint_options, table_sizes))
+        .collect();
+    let score = operations.iter().map(|op| op.score).max().unwrap_or(0);
+    PlanRisk {
+        score,
+        level: RiskLevel::from_score(score),
+        operations,
+    }
+}
+
+fn assess_operation(
+    op: &MigrationOp,
+    lint_options: &LintOptions,
+    table_sizes: &BTreeMap<String, TableSize>,
+) -> OperationRisk {
+    let ops = std::slice::from_ref(op);
+    let mut score = 0;
+    let mut factors = Vec::new();
+
+    for result in lint_migration_plan(ops, lint_options) {
+        let (weight, severity) = match result.severity {
+            LintSeverity::Error => (40, "error"),
+            LintSeverity::Warning => (15, "warning"),
+        };
+        score += weight;
+        factors.push(format!("lint {severity} {}", result.rule));
+    }
+
+    if destroys_data(op) {
+        score += 30;
+        factors.push("destroys data".to_string());
+    }
+
+    let mut tables = Vec::new();
+    if let Some(warning) = detect_lock_hazards(ops)
+        .into_iter()
+        .min_by_key(|warning| lock_rank(&warning.lock_level))
+    {
+        let (weight, lock) = match warning.lock_level {
+            LockLevel::AccessExclusive => (20, "ACCESS EXCLUSIVE"),
+            LockLevel::ShareRowExclusive => (10, "SHARE ROW EXCLUSIVE"),
+            LockLevel::ShareUpdateExclusive => (5, "SHARE UPDATE EXCLUSIVE"),
+        };
+        score += weight;
+        factors.push(format!("{lock} lock"));
+        if warning.lock_level == LockLevel::AccessExclusive {
+            tables.push(warning.table);
+        }
+    }
+
+    for table in rewritten_tables(ops) {
+        score += 15;
+        factors.push("rewrites every row".to_string());
+        tables.push(table.to_string());
+    }
+
+    if let Some((table, size)) = tables
+        .iter()
+        .filter_map(|table| Some((table, table_sizes.get(table)?)))
+        .max_by_key(|(_, size)| size.bytes)
+    {
+        let weight = match (size.rows.unwrap_or(0), size.bytes) {
+            (rows, bytes) if rows >= 1_000_000 || bytes >= 1 << 30 => 20,
+            (rows, bytes) if rows >= 100_000 || bytes >= 100 << 20 => 10,
+            _ => 0,
+        };
+        if weight > 0 {
+            score += weight;
+            factors.push(format!("{table} is large ({size})"));
+        }
+    }
+
+    OperationRisk {
+        key: op.identity(),
+        score: score.min(MAX_SCORE),
+        factors,
+    }
+}
+
+fn destroys_data(op: &MigrationOp) -> bool {
+    matches!(
+        op,
+        MigrationOp::DropSchema { .. }
+            | MigrationOp::DropTable(_)
+            | MigrationOp::DropPartition(_)
+            | MigrationOp::DropColumn { .. }
+            | MigrationOp::DropSequence(_)
+    )
+}
+
+/// Orders lock levels strongest first.
+fn lock_rank(level: &LockLevel) -> u8 {
+    match level {
+        LockLevel::AccessExclusive => 0,
+        LockLevel::ShareRowExclusive => 1,
+        LockLevel::ShareUpdateExclusive => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::ColumnChanges;
+    use crate::model::{PgType, QualifiedName};
+
+    #[test]
+    fn scores_destructive_and_rewriting_operations() {
+        let users = QualifiedName::new("public", "users");
+        let ops = vec![
+            MigrationOp::DropColumn {
+                table: users.clone(),
+                column: "bio".to_string(),
+            },
+            MigrationOp::AlterColumn {
+                table: users.clone(),
+                column: "age".to_string(),
+                changes: ColumnChanges {
+                    data_type: Some(PgType::BigInt),
+                    nullable: None,
+                    default: None,
+                    statistics: None,
+                    compression: None,
+                    identity: None,
+                },
+            },
+        ];
+        let sizes = BTreeMap::from([(
+            "public.users".to_string(),
+            TableSize {
+                rows: Some(5_000_000),
+                bytes: 2 << 30,
+            },
+        )]);
+
+        let risk = assess_risk(&ops, &LintOptions::default(), &sizes);
+
+        let drop = &risk.operations[0];
+        assert!(drop.factors.contains(&"destroys data".to_string()));
+        assert!(drop
+            .factors
+            .iter()
+            .any(|factor| factor.starts_with("lint error")));
+        let alter = &risk.operations[1];
+        assert!(alter.factors.contains(&"rewrites every row".to_string()));
+        assert!(alter
+            .factors
+            .iter()
+            .any(|factor| factor.starts_with("public.users is large")));
+        assert_eq!(risk.level, RiskLevel::High);
+        assert_eq!(risk.riskiest().len(), 2);
+    }
+
+    #[test]
+    fn additive_plan_is_low_risk() {
+        let ops = vec![MigrationOp::CreateSchema(crate::model::PgSchema {
+            name: "app".to_string(),
+            grants: Vec::new(),
+            comment: None,
+        })];
+        let risk = assess_risk(&ops, &LintOptions::default(), &BTreeMap::new());
+        assert_eq!(risk.score, 0);
+        assert_eq!(risk.level, RiskLevel::Low);
+        assert!(risk.riskiest().is_empty());
+    }
+}
@@ -0,0 +1,115 @@
+use crate::diff::MigrationOp;
+use crate::model::{qualified_name, Schema};
+use crate::util::{Result, SchemaError};
+
+/// Trigger functions PostgreSQL ships in `pg_catalog`, which a trigger may
+/// call without the schema declaring them.
+pub(crate) const BUILTIN_TRIGGER_FUNCTIONS: &[&str] = &[
+    "suppress_redundant_updates_trigger",
+    "tsvector_update_trigger",
+    "tsvector_update_trigger_column",
+];
+
+/// Checks that every trigger `ops` creates calls a function `target` declares
+/// with return type `trigger`, so a miswired trigger is reported when the plan
+/// is computed instead of when `CREATE TRIGGER` fails. A function the schema
+/// does not declare is only reported when no extension could provide it and
+/// it is not a built-in trigger function.
+pub fn check_trigger_functions(ops: &[MigrationOp], target: &Schema) -> Result<()> {
+    let mut errors = Vec::new();
+    for op in ops {
+        let MigrationOp::CreateTrigger(trigger) = op else {
+            continue;
+        };
+        let function = qualified_name(&trigger.function_schema, &trigger.function_name);
+        let table = qualified_name(&trigger.target_schema, &trigger.target_name);
+        let candidates: Vec<&str> = target
+            .functions
+            .values()
+            .filter(|f| f.schema == trigger.function_schema && f.name == trigger.function_name)
+            .map(|f| f.return_type.as_str())
+            .collect();
+
+        if candidates.is_empty() {
+            let may_be_external = trigger.function_schema == "pg_catalog"
+                || !target.extensions.is_empty()
+                || BUILTIN_TRIGGER_FUNCTIONS.contains(&trigger.function_name.as_str());
+            if !may_be_external {
+                errors.push(format!(
+                    "Trigger \"{}\" on {table} calls function {function}(), which the schema does not define",
+                    trigger.name
+                ));
+            }
+        } else if !candidates
+            .iter()
+            .any(|return_type| return_type.eq_ignore_ascii_case("trigger"))
+        {
+            errors.push(format!(
+                "Trigger \"{}\" on {table} calls function {function}(), which returns {} instead of trigger",
+                trigger.name,
+                candidates.join(" or ")
+            ));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(SchemaError::ValidationError(errors.join("\n")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::compute_diff;
+    use crate::parser::parse_sql_string;
+
+    fn check(sql: &str) -> Result<()> {
+        let target = parse_sql_string(sql).unwrap();
+        check_trigger_functions(&compute_diff(&Schema::new(), &target), &target)
+    }
+
+    #[test]
+    fn rejects_trigger_calling_non_trigger_function() {
+        let err = check(
+            "CREATE TABLE users (id BIGINT PRIMARY KEY);
+             CREATE FUNCTION touch() RETURNS void LANGUAGE sql AS $$ SELECT 1 $$;
+             CREATE TRIGGER users_touch BEFORE UPDATE ON users
+                 FOR EACH ROW EXECUTE FUNCTION touch();",
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(
+            err.contains(
+                "Trigger \"users_touch\" on public.users calls function public.touch(), which returns void instead of trigger"
+            ),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn accepts_trigger_functions_and_builtins_and_reports_missing_ones() {
+        check(
+            "CREATE TABLE users (id BIGINT PRIMARY KEY, body TEXT, search TSVECTOR);
+             CREATE FUNCTION touch() RETURNS TRIGGER LANGUAGE plpgsql AS $$ BEGIN RETURN NEW; END $$;
+             CREATE TRIGGER users_touch BEFORE UPDATE ON users
+                 FOR EACH ROW EXECUTE FUNCTION touch();
+             CREATE TRIGGER users_search BEFORE INSERT ON users
+                 FOR EACH ROW EXECUTE FUNCTION tsvector_update_trigger(search, 'pg_catalog.english', body);",
+        )
+        .unwrap();
+
+        let err = check(
+            "CREATE TABLE users (id BIGINT PRIMARY KEY);
+             CREATE TRIGGER users_audit AFTER INSERT ON users
+                 FOR EACH ROW EXECUTE FUNCTION audit.record();",
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(
+            err.contains("audit.record(), which the schema does not define"),
+            "{err}"
+        );
+    }
+}
@@ -0,0 +1,222 @@
+//! How objects defined by more than one schema source are resolved.
+//!
+//! Sources passed with repeated `--schema` flags are merged in order. By
+//! default an object defined in two sources is an error; a merge policy can
+//! instead keep the first or the last definition, and an override list lets
+//! specific objects be redefined by a later source while every other duplicate
+//! is still an error. The CLI's `--merge-policy` / `--merge-override` flags
+//! reach the loader through `PGMOLD_MERGE_POLICY` and `PGMOLD_MERGE_OVERRIDES`,
+//! mirroring `--strict`.
+
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::util::{Result, SchemaError};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// A duplicate definition is an error.
+    #[default]
+    Error,
+    /// The definition from the earliest source is kept.
+    FirstWins,
+    /// The definition from the latest source replaces earlier ones.
+    LastWins,
+}
+
+impl FromStr for MergePolicy {
+    type Err = SchemaError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "error" => Ok(Self::Error),
+            "first-wins" => Ok(Self::FirstWins),
+            "last-wins" => Ok(Self::LastWins),
+            other => Err(SchemaError::ParseError(format!(
+                "Invalid merge policy '{other}': expected error, first-wins or last-wins"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for MergePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Error => "error",
+            Self::FirstWins => "first-wins",
+            Self::LastWins => "last-wins",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeOptions {
+    pub policy: MergePolicy,
+    /// Objects a later source may redefine whatever the policy, written
+    /// `type:name` such as `function:public.audit` or `table:vendor.events`.
+    /// A function name without an argument list covers every overload.
+    pub overrides: BTreeSet<String>,
+    /// Globs for objects other systems own, added to every loaded schema's
+    /// `external` list as if each source declared them with
+    /// `-- pgmold:external`.
+    pub external: BTreeSet<String>,
+}
+
+impl MergeOptions {
+    pub fn from_env() -> Result<Self> {
+        let policy = match std::env::var("PGMOLD_MERGE_POLICY") {
+            Ok(value) if !value.trim().is_empty() => value.parse()?,
+            _ => MergePolicy::default(),
+        };
+        let list = |var: &str| {
+            std::env::var(var)
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|entry| !entry.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        Ok(Self {
+            policy,
+            overrides: list("PGMOLD_MERGE_OVERRIDES"),
+            external: list("PGMOLD_EXTERNAL"),
+        })
+    }
+
+    /// Identifies these options in cache keys; empty for the default.
+    pub fn cache_key(&self) -> String {
+        if *self == Self::default() {
+            return String::new();
+        }
+        let overrides: Vec<&str> = self.overrides.iter().map(String::as_str).collect();
+        let external: Vec<&str> = self.external.iter().map(String::as_str).collect();
+        format!(
+            "{}:{}:{}",
+            self.policy,
+            overrides.join(","),
+            external.join(",")
+        )
+    }
+
+    fn is_overridden(&self, object_type: &str, key: &str) -> bool {
+        self.overrides.iter().any(|entry| {
+            entry
+                .strip_prefix(object_type)
+                .and_then(|rest| rest.strip_prefix(':'))
+                .is_some_and(|name| {
+                    key == name
+                        || key
+                            .strip_prefix(name)
+                            .is_some_and(|args| args.starts_with('('))
+                })
+        })
+    }
+}
+
+/// Merges `source` into `target`, resolving keys present in both according
+/// to `options`.
+pub(super) fn merge_collection<V>(
+    target: &mut BTreeMap<String, V>,
+    source: BTreeMap<String, V>,
+    object_type: &str,
+    options: &MergeOptions,
+) -> Result<()> {
+    for (name, value) in source {
+        match target.entry(name) {
+            Entry::Occupied(mut entry) => {
+                if options.is_overridden(object_type, entry.key()) {
+                    entry.insert(value);
+                    continue;
+                }
+                match options.policy {
+                    MergePolicy::Error => {
+                        return Err(SchemaError::ParseError(format!(
+                            "Duplicate {object_type} \"{}\" from multiple sources",
+                            entry.key()
+                        )));
+                    }
+                    MergePolicy::FirstWins => {}
+                    MergePolicy::LastWins => {
+                        entry.insert(value);
+                    }
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merge(options: &MergeOptions) -> Result<BTreeMap<String, &'static str>> {
+        let mut target = BTreeMap::from([
+            ("public.audit(text)".to_string(), "vendor"),
+            ("public.users".to_string(), "vendor"),
+        ]);
+        let source = BTreeMap::from([
+            ("public.audit(text)".to_string(), "overlay"),
+            ("public.orders".to_string(), "overlay"),
+        ]);
+        merge_collection(&mut target, source, "function", options)?;
+        Ok(target)
+    }
+
+    #[test]
+    fn policies_resolve_duplicates() {
+        let err = merge(&MergeOptions::default()).unwrap_err().to_string();
+        assert!(
+            err.contains("Duplicate function \"public.audit(text)\""),
+            "{err}"
+        );
+
+        let first = merge(&MergeOptions {
+            policy: MergePolicy::FirstWins,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(first["public.audit(text)"], "vendor");
+        assert_eq!(first["public.orders"], "overlay");
+
+        let last = merge(&MergeOptions {
+            policy: MergePolicy::LastWins,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(last["public.audit(text)"], "overlay");
+    }
+
+    #[test]
+    fn override_list_covers_named_objects_and_overloads() {
+        let options = MergeOptions {
+            policy: MergePolicy::Error,
+            overrides: BTreeSet::from(["function:public.audit".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(merge(&options).unwrap()["public.audit(text)"], "overlay");
+
+        let options = MergeOptions {
+            policy: MergePolicy::Error,
+            overrides: BTreeSet::from(["table:public.audit".to_string()]),
+            ..Default::default()
+        };
+        assert!(merge(&options).is_err());
+    }
+
+    #[test]
+    fn parses_policy_names() {
+        assert_eq!(
+            "last-wins".parse::<MergePolicy>().unwrap(),
+            MergePolicy::LastWins
+        );
+        assert_eq!(MergePolicy::FirstWins.to_string(), "first-wins");
+        assert!("newest".parse::<MergePolicy>().is_err());
+    }
+}
@@ -1,14 +1,38 @@
+//! Loads the target schema from `--schema` sources (SQL files, Drizzle
+//! configs, snapshots, remote archives). Callers that describe the schema in
+//! code can build it with [`crate::model::SchemaBuilder`] instead.
+
 mod drizzle;
+mod merge;
+mod remote;
 
 use crate::model::Schema;
-use crate::parser::load_schema_sources;
+use crate::parser::{
+    hash_schema_sources, load_schema_sources, locate_schema_objects, InterpolationOptions,
+    SourceLocations,
+};
+use crate::snapshot::load_snapshot;
 use crate::util::SchemaError;
+use merge::merge_collection;
 
 pub use drizzle::load_drizzle_schema;
+pub use merge::{MergeOptions, MergePolicy};
+pub(crate) use remote::cache_dir;
+pub use remote::load_remote_schema;
 
 type Result<T> = std::result::Result<T, SchemaError>;
 
 pub fn load_schema_from_sources(sources: &[String]) -> Result<Schema> {
+    load_schema_from_sources_with_merge(sources, &MergeOptions::from_env()?)
+}
+
+/// Loads and merges `sources`, resolving objects defined by more than one
+/// source according to `merge`. `load_schema_from_sources` reads the options
+/// from the environment instead.
+pub fn load_schema_from_sources_with_merge(
+    sources: &[String],
+    merge: &MergeOptions,
+) -> Result<Schema> {
     if sources.is_empty() {
         return Err(SchemaError::ParseError(
             "No schema sources provided".to_string(),
@@ -20,7 +44,71 @@ pub fn load_schema_from_sources(sources: &[String]) -> Result<Schema> {
         .map(|source| load_single_source(source))
         .collect::<Result<_>>()?;
 
-    merge_schemas(schemas)
+    // A snapshot is what a database held, not what sources declare, and may
+    // reference anything outside the schemas it was taken of.
+    let check_references = !sources.iter().any(|s| s.starts_with("snapshot:"));
+    merge_schemas(schemas, merge, check_references)
+}
+
+/// Content hash of `sources`, or `None` when one of them cannot be hashed
+/// without loading it (Drizzle configs are evaluated by an external tool).
+/// Remote sources are pinned by checksum, so the source string identifies
+/// their content. The pgmold version and the parse and merge options from the
+/// environment are included, since they change the loaded schema: strict
+/// mode, the merge options, and the `${VAR}` allowlist with each allowed
+/// variable's current value.
+pub fn hash_sources(sources: &[String]) -> Result<Option<String>> {
+    hash_sources_with(sources, &InterpolationOptions::from_env(), |name| {
+        std::env::var(name).ok()
+    })
+}
+
+fn hash_sources_with(
+    sources: &[String],
+    interpolation: &InterpolationOptions,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<Option<String>> {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+    hasher.update([0, u8::from(crate::parser::strict_mode_from_env())]);
+    hasher.update(MergeOptions::from_env()?.cache_key().as_bytes());
+    hasher.update([0]);
+    hasher.update(interpolation.cache_key_with(lookup).as_bytes());
+    hasher.update([0]);
+    for source in sources {
+        let part = if let Some(path) = source.strip_prefix("sql:") {
+            hash_schema_sources(&[path.to_string()])?
+        } else if source.starts_with("https:") {
+            source.clone()
+        } else if let Some(path) = source.strip_prefix("snapshot:") {
+            let content = std::fs::read(path).map_err(|e| {
+                SchemaError::ParseError(format!("Cannot read snapshot {path}: {e}"))
+            })?;
+            hex::encode(Sha256::digest(&content))
+        } else {
+            return Ok(None);
+        };
+        hasher.update(source.as_bytes());
+        hasher.update([0]);
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    Ok(Some(hex::encode(hasher.finalize())))
+}
+
+/// Where the objects defined by the `sql:` sources in `sources` live. Other
+/// sources are not files pgmold can point into and contribute nothing.
+pub fn locate_sources(sources: &[String]) -> Result<SourceLocations> {
+    let paths: Vec<String> = sources
+        .iter()
+        .filter_map(|source| source.strip_prefix("sql:").map(str::to_string))
+        .collect();
+    if paths.is_empty() {
+        return Ok(SourceLocations::new());
+    }
+    locate_schema_objects(&paths)
 }
 
 fn load_single_source(source: &str) -> Result<Schema> {
@@ -28,10 +116,16 @@ fn load_single_source(source: &str) -> Result<Schema> {
         load_sql_source(path)
     } else if let Some(path) = source.strip_prefix("drizzle:") {
         load_drizzle_schema(path)
+    } else if source.starts_with("https:") {
+        load_remote_schema(source)
+    } else if let Some(path) = source.strip_prefix("snapshot:") {
+        Ok(load_snapshot(std::path::Path::new(path))?.schema)
     } else {
         Err(SchemaError::ParseError(format!(
             "Unknown schema source prefix: {source}. \
-             Use 'sql:' for SQL files/directories or 'drizzle:' for Drizzle ORM configs."
+             Use 'sql:' for SQL files/directories, 'drizzle:' for Drizzle ORM configs, \
+             'https:' for a checksum-pinned remote schema, or 'snapshot:' for a \
+             `pgmold snapshot` file."
         )))
     }
 }
@@ -40,36 +134,21 @@ fn load_sql_source(path: &str) -> Result<Schema> {
     load_schema_sources(&[path.to_string()])
 }
 
-fn merge_collection<V>(
-    target: &mut std::collections::BTreeMap<String, V>,
-    source: std::collections::BTreeMap<String, V>,
-    object_type: &str,
-) -> Result<()> {
-    use std::collections::btree_map::Entry;
-
-    for (name, value) in source {
-        match target.entry(name) {
-            Entry::Occupied(entry) => {
-                return Err(SchemaError::ParseError(format!(
-                    "Duplicate {object_type} \"{}\" from multiple sources",
-                    entry.key()
-                )));
-            }
-            Entry::Vacant(entry) => {
-                entry.insert(value);
-            }
-        }
-    }
-    Ok(())
-}
-
-fn merge_schemas(schemas: Vec<Schema>) -> Result<Schema> {
+fn merge_schemas(
+    schemas: Vec<Schema>,
+    options: &MergeOptions,
+    check_references: bool,
+) -> Result<Schema> {
     if schemas.is_empty() {
         return Err(SchemaError::ParseError("No schemas to merge".to_string()));
     }
 
     if schemas.len() == 1 {
         let mut schema = schemas.into_iter().next().unwrap();
+        schema.external.extend(options.external.iter().cloned());
+        if check_references {
+            reject_undeclared_references(&schema)?;
+        }
         schema.finalize().map_err(SchemaError::ParseError)?;
         return Ok(schema);
     }
@@ -77,30 +156,81 @@ fn merge_schemas(schemas: Vec<Schema>) -> Result<Schema> {
     let mut merged = Schema::new();
 
     for schema in schemas {
-        merge_collection(&mut merged.tables, schema.tables, "table")?;
-        merge_collection(&mut merged.enums, schema.enums, "enum")?;
-        merge_collection(&mut merged.functions, schema.functions, "function")?;
-        merge_collection(&mut merged.aggregates, schema.aggregates, "aggregate")?;
-        merge_collection(&mut merged.views, schema.views, "view")?;
-        merge_collection(&mut merged.triggers, schema.triggers, "trigger")?;
-        merge_collection(&mut merged.sequences, schema.sequences, "sequence")?;
-        merge_collection(&mut merged.domains, schema.domains, "domain")?;
-        merge_collection(&mut merged.extensions, schema.extensions, "extension")?;
-        merge_collection(&mut merged.schemas, schema.schemas, "schema")?;
-        merge_collection(&mut merged.partitions, schema.partitions, "partition")?;
-
+        merge_collection(&mut merged.tables, schema.tables, "table", options)?;
+        merge_collection(&mut merged.enums, schema.enums, "enum", options)?;
+        merge_collection(&mut merged.functions, schema.functions, "function", options)?;
+        merge_collection(
+            &mut merged.aggregates,
+            schema.aggregates,
+            "aggregate",
+            options,
+        )?;
+        merge_collection(&mut merged.views, schema.views, "view", options)?;
+        merge_collection(&mut merged.triggers, schema.triggers, "trigger", options)?;
+        merge_collection(&mut merged.sequences, schema.sequences, "sequence", options)?;
+        merge_collection(&mut merged.domains, schema.domains, "domain", options)?;
+        merge_collection(
+            &mut merged.extensions,
+            schema.extensions,
+            "extension",
+            options,
+        )?;
+        merge_collection(&mut merged.schemas, schema.schemas, "schema", options)?;
+        merge_collection(
+            &mut merged.partitions,
+            schema.partitions,
+            "partition",
+            options,
+        )?;
+        merge_collection(
+            &mut merged.raw_objects,
+            schema.raw_objects,
+            "raw object",
+            options,
+        )?;
+
+        merged.external.extend(schema.external);
+        merged.schema_moves.extend(schema.schema_moves);
+        merged.renames.extend(schema.renames);
+        merged
+            .partition_retention
+            .extend(schema.partition_retention);
+        merged.sequence_values.extend(schema.sequence_values);
         merged.pending_policies.extend(schema.pending_policies);
         merged.pending_owners.extend(schema.pending_owners);
         merged.pending_grants.extend(schema.pending_grants);
         merged.pending_revokes.extend(schema.pending_revokes);
         merged.pending_comments.extend(schema.pending_comments);
     }
+    merged.external.extend(options.external.iter().cloned());
 
+    if check_references {
+        reject_undeclared_references(&merged)?;
+    }
     merged.finalize().map_err(SchemaError::ParseError)?;
 
     Ok(merged)
 }
 
+/// Fails with every [`Schema::undeclared_references`] at once, which would
+/// otherwise surface one by one as errors from PostgreSQL during apply.
+fn reject_undeclared_references(schema: &Schema) -> Result<()> {
+    let undeclared = schema.undeclared_references();
+    if undeclared.is_empty() {
+        return Ok(());
+    }
+    Err(SchemaError::ValidationError(format!(
+        "{} reference(s) to objects no schema source declares (declare them, or mark them \
+         with `-- pgmold:external <schema.name>`):\n{}",
+        undeclared.len(),
+        undeclared
+            .iter()
+            .map(|line| format!("  - {line}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    )))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -166,6 +296,73 @@ mod tests {
         assert!(err.contains("nonexistent_table"));
     }
 
+    #[test]
+    fn undeclared_references_are_reported_together() {
+        let dir = TempDir::new().unwrap();
+        let file = write_sql_file(
+            &dir,
+            "app.sql",
+            br#"
+            CREATE TABLE public.orders (
+                id BIGINT PRIMARY KEY,
+                user_id UUID REFERENCES auth.users(id),
+                legacy_id BIGINT REFERENCES public.legacy_orders(id)
+            );
+            CREATE TRIGGER orders_audit AFTER INSERT ON public.orders
+                FOR EACH ROW EXECUTE FUNCTION public.audit_row();
+            CREATE VIEW public.recent AS
+                WITH latest AS (SELECT * FROM public.orders)
+                SELECT latest.id FROM latest JOIN public.archive ON archive.id = latest.id;
+            "#,
+        );
+
+        let err = load_schema_from_sources(&[sql_source(&file)])
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("Validation error: 4 reference(s)"), "{err}");
+        for expected in [
+            "references table auth.users",
+            "references table public.legacy_orders",
+            "calls function public.audit_row()",
+            "view public.recent selects from public.archive",
+        ] {
+            assert!(err.contains(expected), "missing {expected:?} in {err}");
+        }
+
+        let external = write_sql_file(
+            &dir,
+            "external.sql",
+            b"-- pgmold:external auth.*, public.legacy_orders public.audit_row public.archive\n",
+        );
+        load_schema_from_sources(&[sql_source(&file), sql_source(&external)]).unwrap();
+    }
+
+    #[test]
+    fn override_lets_a_later_source_redefine_a_function() {
+        let vendor = TempDir::new().unwrap();
+        let overlay = TempDir::new().unwrap();
+        let define = |body: &str| {
+            format!(
+                "CREATE FUNCTION public.audit() RETURNS int LANGUAGE sql AS $$ SELECT {body} $$;"
+            )
+        };
+        let vendor_file = write_sql_file(&vendor, "audit.sql", define("1").as_bytes());
+        let overlay_file = write_sql_file(&overlay, "audit.sql", define("2").as_bytes());
+        let sources = [sql_source(&vendor_file), sql_source(&overlay_file)];
+
+        let err = load_schema_from_sources_with_merge(&sources, &MergeOptions::default())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Duplicate function"), "{err}");
+
+        let options = MergeOptions {
+            overrides: ["function:public.audit".to_string()].into(),
+            ..Default::default()
+        };
+        let merged = load_schema_from_sources_with_merge(&sources, &options).unwrap();
+        assert!(merged.functions["public.audit()"].body.contains('2'));
+    }
+
     #[test]
     fn ownership_from_secondary_source_applied() {
         let dir1 = TempDir::new().unwrap();
@@ -248,4 +445,41 @@ mod tests {
             std::collections::BTreeSet::from([crate::model::Privilege::Select])
         );
     }
+
+    #[test]
+    fn hash_sources_skips_drizzle_configs() {
+        let dir = TempDir::new().unwrap();
+        let file = write_sql_file(&dir, "users.sql", b"CREATE TABLE users (id INT);");
+
+        let sql = vec![sql_source(&file)];
+        assert!(hash_sources(&sql).unwrap().is_some());
+        assert_eq!(hash_sources(&sql).unwrap(), hash_sources(&sql).unwrap());
+
+        let with_drizzle = vec![sql_source(&file), "drizzle:drizzle.config.ts".to_string()];
+        assert_eq!(hash_sources(&with_drizzle).unwrap(), None);
+    }
+
+    #[test]
+    fn hash_sources_changes_with_interpolated_variables() {
+        let dir = TempDir::new().unwrap();
+        let file = write_sql_file(
+            &dir,
+            "grants.sql",
+            b"CREATE TABLE users (id INT); GRANT SELECT ON users TO ${APP_ROLE};",
+        );
+        let sql = vec![sql_source(&file)];
+        let interpolation = InterpolationOptions {
+            allowed: ["APP_ROLE".to_string()].into(),
+            strict: false,
+        };
+        let hash = |role: &'static str| {
+            hash_sources_with(&sql, &interpolation, |name| {
+                (name == "APP_ROLE").then(|| role.to_string())
+            })
+            .unwrap()
+        };
+
+        assert_eq!(hash("app_rw"), hash("app_rw"));
+        assert_ne!(hash("app_rw"), hash("app_ro"));
+    }
 }
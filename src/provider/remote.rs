@@ -0,0 +1,214 @@
+use crate::model::Schema;
+use crate::parser::load_schema_sources;
+use crate::util::SchemaError;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+type Result<T> = std::result::Result<T, SchemaError>;
+
+/// Refuse downloads larger than this; canonical schemas are nowhere near it.
+const MAX_DOWNLOAD_BYTES: u64 = 64 * 1024 * 1024;
+
+/// A remote schema URL together with the SHA-256 its content must hash to.
+#[derive(Debug, PartialEq, Eq)]
+struct PinnedUrl {
+    url: String,
+    sha256: String,
+}
+
+/// Loads a schema from `https://host/path#sha256=<hex>`. The file (a `.sql`
+/// file or a `.tar.gz` / `.tgz` / `.zip` bundle) is cached by checksum, so
+/// later loads with the same pin skip the network entirely.
+pub fn load_remote_schema(source: &str) -> Result<Schema> {
+    let pinned = parse_pinned_url(source)?;
+    let path = fetch_pinned(&pinned, &cache_dir())?;
+    load_schema_sources(&[path.display().to_string()])
+}
+
+fn parse_pinned_url(source: &str) -> Result<PinnedUrl> {
+    let (url, fragment) = source.split_once('#').ok_or_else(|| {
+        SchemaError::ParseError(format!(
+            "Remote schema source {source} must pin its content with #sha256=<hex>"
+        ))
+    })?;
+    let sha256 = fragment
+        .strip_prefix("sha256=")
+        .filter(|hex| hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+        .ok_or_else(|| {
+            SchemaError::ParseError(format!(
+                "Invalid checksum pin in {source}: expected #sha256=<64 hex characters>"
+            ))
+        })?;
+    Ok(PinnedUrl {
+        url: url.to_string(),
+        sha256: sha256.to_ascii_lowercase(),
+    })
+}
+
+/// `PGMOLD_CACHE_DIR`, else `$XDG_CACHE_HOME/pgmold`, else `~/.cache/pgmold`.
+pub(crate) fn cache_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("PGMOLD_CACHE_DIR") {
+        return PathBuf::from(dir);
+    }
+    if let Some(dir) = std::env::var_os("XDG_CACHE_HOME") {
+        return PathBuf::from(dir).join("pgmold");
+    }
+    match std::env::var_os("HOME") {
+        Some(home) => PathBuf::from(home).join(".cache").join("pgmold"),
+        None => std::env::temp_dir().join("pgmold"),
+    }
+}
+
+/// Returns the cached copy of `pinned`, downloading it first when the cache
+/// has no copy whose content still matches the pin.
+fn fetch_pinned(pinned: &PinnedUrl, cache_dir: &Path) -> Result<PathBuf> {
+    let cached = cache_dir.join(format!("{}-{}", pinned.sha256, file_name(&pinned.url)));
+    if let Ok(content) = std::fs::read(&cached) {
+        if sha256_hex(&content) == pinned.sha256 {
+            return Ok(cached);
+        }
+    }
+
+    let content = download(&pinned.url)?;
+    let actual = sha256_hex(&content);
+    if actual != pinned.sha256 {
+        return Err(SchemaError::ParseError(format!(
+            "Checksum mismatch for {}: expected sha256 {}, got {actual}",
+            pinned.url, pinned.sha256
+        )));
+    }
+
+    let cache_error = |e: std::io::Error| {
+        SchemaError::ParseError(format!(
+            "Failed to cache {} in {}: {e}",
+            pinned.url,
+            cache_dir.display()
+        ))
+    };
+    std::fs::create_dir_all(cache_dir).map_err(cache_error)?;
+    let partial = cached.with_extension("partial");
+    std::fs::write(&partial, &content).map_err(cache_error)?;
+    std::fs::rename(&partial, &cached).map_err(cache_error)?;
+    Ok(cached)
+}
+
+fn download(url: &str) -> Result<Vec<u8>> {
+    if !url.starts_with("https://") {
+        return Err(SchemaError::ParseError(format!(
+            "Remote schema sources must use https: {url}"
+        )));
+    }
+    let response = ureq::get(url)
+        .call()
+        .map_err(|e| SchemaError::ParseError(format!("Failed to fetch {url}: {e}")))?;
+    let mut content = Vec::new();
+    response
+        .into_reader()
+        .take(MAX_DOWNLOAD_BYTES + 1)
+        .read_to_end(&mut content)
+        .map_err(|e| SchemaError::ParseError(format!("Failed to read {url}: {e}")))?;
+    if content.len() as u64 > MAX_DOWNLOAD_BYTES {
+        return Err(SchemaError::ParseError(format!(
+            "{url} exceeds the {MAX_DOWNLOAD_BYTES} byte download limit"
+        )));
+    }
+    Ok(content)
+}
+
+/// Last path segment of `url`, restricted to characters safe in a file name.
+/// The extension is kept so archives are still recognized once cached.
+fn file_name(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let name: String = path
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+        .collect();
+    if name.trim_matches('.').is_empty() {
+        "schema.sql".to_string()
+    } else {
+        name
+    }
+}
+
+fn sha256_hex(content: &[u8]) -> String {
+    hex::encode(Sha256::digest(content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const SQL: &str = "CREATE TABLE users (id INT PRIMARY KEY);";
+
+    fn pinned(url: &str, content: &str) -> PinnedUrl {
+        PinnedUrl {
+            url: url.to_string(),
+            sha256: sha256_hex(content.as_bytes()),
+        }
+    }
+
+    #[test]
+    fn parses_checksum_pin() {
+        let hash = "AB".repeat(32);
+        let parsed = parse_pinned_url(&format!("https://example.com/s.sql#sha256={hash}")).unwrap();
+        assert_eq!(parsed.url, "https://example.com/s.sql");
+        assert_eq!(parsed.sha256, "ab".repeat(32));
+    }
+
+    #[test]
+    fn rejects_unpinned_or_malformed_pins() {
+        assert!(parse_pinned_url("https://example.com/s.sql").is_err());
+        assert!(parse_pinned_url("https://example.com/s.sql#sha256=abc").is_err());
+        assert!(
+            parse_pinned_url(&format!("https://example.com/s.sql#md5={}", "a".repeat(64))).is_err()
+        );
+    }
+
+    #[test]
+    fn cached_copy_is_used_without_network() {
+        let cache = TempDir::new().unwrap();
+        // Port 9 is unreachable, so any network attempt would fail the test.
+        let pin = pinned("https://127.0.0.1:9/schemas/core.sql", SQL);
+        let cached = cache.path().join(format!("{}-core.sql", pin.sha256));
+        std::fs::write(&cached, SQL).unwrap();
+
+        assert_eq!(fetch_pinned(&pin, cache.path()).unwrap(), cached);
+    }
+
+    #[test]
+    fn tampered_cache_entry_is_not_trusted() {
+        let cache = TempDir::new().unwrap();
+        let pin = pinned("https://127.0.0.1:9/schemas/core.sql", SQL);
+        std::fs::write(
+            cache.path().join(format!("{}-core.sql", pin.sha256)),
+            "DROP TABLE users;",
+        )
+        .unwrap();
+
+        let err = fetch_pinned(&pin, cache.path()).unwrap_err().to_string();
+        assert!(err.contains("Failed to fetch"), "{err}");
+    }
+
+    #[test]
+    fn plain_http_is_refused() {
+        let cache = TempDir::new().unwrap();
+        let pin = pinned("http://example.com/core.sql", SQL);
+        let err = fetch_pinned(&pin, cache.path()).unwrap_err().to_string();
+        assert!(err.contains("must use https"), "{err}");
+    }
+
+    #[test]
+    fn file_name_keeps_archive_extension() {
+        assert_eq!(
+            file_name("https://cdn.example.com/v2/bundle.tar.gz?token=x"),
+            "bundle.tar.gz"
+        );
+        assert_eq!(file_name("https://cdn.example.com/"), "schema.sql");
+        assert_eq!(file_name("https://cdn.example.com/../.."), "schema.sql");
+    }
+}
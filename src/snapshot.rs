@@ -0,0 +1,94 @@
+//! Introspected schemas saved to a file, so environments without access to
+//! the database can still diff against it. `pgmold snapshot` writes one and
+//! `snapshot:<path>` reads it back as a schema source.
+
+use crate::model::Schema;
+use crate::util::{Result, SchemaError};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Version of the snapshot file layout. Bumped whenever a change to the
+/// format would make older pgmold releases misread a snapshot.
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Snapshot {
+    pub format_version: u32,
+    /// pgmold release that wrote the snapshot.
+    pub pgmold_version: String,
+    /// PostgreSQL schemas that were introspected.
+    pub target_schemas: Vec<String>,
+    pub schema: Schema,
+}
+
+impl Snapshot {
+    pub fn new(schema: Schema, target_schemas: &[String]) -> Self {
+        Self {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            pgmold_version: env!("CARGO_PKG_VERSION").to_string(),
+            target_schemas: target_schemas.to_vec(),
+            schema,
+        }
+    }
+}
+
+pub fn write_snapshot<W: Write>(snapshot: &Snapshot, out: &mut W) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *out, snapshot)?;
+    out.write_all(b"\n")
+}
+
+/// Reads a snapshot, refusing formats newer than this release understands.
+pub fn load_snapshot(path: &Path) -> Result<Snapshot> {
+    let content = std::fs::read(path).map_err(|e| {
+        SchemaError::ParseError(format!("Cannot read snapshot {}: {e}", path.display()))
+    })?;
+    let version: SnapshotVersion = serde_json::from_slice(&content).map_err(|e| {
+        SchemaError::ParseError(format!("Invalid snapshot {}: {e}", path.display()))
+    })?;
+    if version.format_version != SNAPSHOT_FORMAT_VERSION {
+        return Err(SchemaError::ParseError(format!(
+            "Snapshot {} uses format version {}, but this pgmold reads version {SNAPSHOT_FORMAT_VERSION}",
+            path.display(),
+            version.format_version
+        )));
+    }
+    serde_json::from_slice(&content)
+        .map_err(|e| SchemaError::ParseError(format!("Invalid snapshot {}: {e}", path.display())))
+}
+
+/// Read first, so a snapshot from a newer format is reported as such rather
+/// than as whatever field it no longer matches.
+#[derive(Deserialize)]
+struct SnapshotVersion {
+    format_version: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_sql_string;
+    use tempfile::TempDir;
+
+    #[test]
+    fn snapshot_round_trips_and_rejects_other_versions() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("snapshot.json");
+        let schema = parse_sql_string(
+            "CREATE TYPE status AS ENUM ('active', 'closed');
+             CREATE TABLE accounts (id BIGINT PRIMARY KEY, status status NOT NULL);",
+        )
+        .unwrap();
+        let snapshot = Snapshot::new(schema, &["public".to_string()]);
+
+        let mut file = std::fs::File::create(&path).unwrap();
+        write_snapshot(&snapshot, &mut file).unwrap();
+        assert_eq!(load_snapshot(&path).unwrap(), snapshot);
+
+        let mut newer = serde_json::to_value(&snapshot).unwrap();
+        newer["format_version"] = serde_json::json!(SNAPSHOT_FORMAT_VERSION + 1);
+        std::fs::write(&path, newer.to_string()).unwrap();
+        let error = load_snapshot(&path).unwrap_err().to_string();
+        assert!(error.contains("format version 2"), "{error}");
+    }
+}
@@ -0,0 +1,167 @@
+//! Schema-per-tenant deployments: one logical schema, declared in a schema
+//! named `{tenant}`, instantiated for each tenant's PostgreSQL schema.
+
+use sqlx::Row;
+
+use crate::apply::{apply_target_schema, ApplyOptions, ApplyResult};
+use crate::drift::{compare_schemas, DriftReport};
+use crate::dump::generate_dump;
+use crate::model::Schema;
+use crate::parser::parse_sql_string;
+use crate::pg::connection::PgConnection;
+use crate::pg::introspect::introspect_schema;
+use crate::util::{Result, SchemaError};
+
+/// The schema name sources declare tenant objects in, e.g.
+/// `CREATE TABLE "{tenant}".users (...)`. Every occurrence, including inside
+/// function bodies and expressions, is replaced with the tenant's schema.
+pub const TENANT_PLACEHOLDER: &str = "{tenant}";
+
+/// The template with `{tenant}` replaced by `tenant` throughout.
+pub fn instantiate(template: &Schema, tenant: &str) -> Result<Schema> {
+    if tenant.is_empty() || tenant.contains(['"', '\'']) || tenant.contains(TENANT_PLACEHOLDER) {
+        return Err(SchemaError::ValidationError(format!(
+            "Invalid tenant schema name '{tenant}'"
+        )));
+    }
+    let sql = generate_dump(template, None).replace(TENANT_PLACEHOLDER, tenant);
+    let mut schema = parse_sql_string(&sql).map_err(|e| {
+        SchemaError::ParseError(format!("Failed to instantiate tenant {tenant}: {e}"))
+    })?;
+    schema.external = template
+        .external
+        .iter()
+        .map(|name| name.replace(TENANT_PLACEHOLDER, tenant))
+        .collect();
+    schema.partition_retention = template
+        .partition_retention
+        .iter()
+        .map(|(table, interval)| (table.replace(TENANT_PLACEHOLDER, tenant), interval.clone()))
+        .collect();
+    Ok(schema)
+}
+
+/// Tenant schema names returned by `query`, one per row in its first column.
+pub async fn discover_tenants(connection: &PgConnection, query: &str) -> Result<Vec<String>> {
+    let rows = sqlx::query(query)
+        .fetch_all(connection.pool())
+        .await
+        .map_err(|e| SchemaError::DatabaseError(format!("Tenant query failed: {e}")))?;
+    rows.iter()
+        .map(|row| {
+            row.try_get::<String, _>(0).map_err(|e| {
+                SchemaError::DatabaseError(format!("Tenant query must return schema names: {e}"))
+            })
+        })
+        .collect()
+}
+
+/// The outcome of applying the template to one tenant.
+#[derive(Debug)]
+pub struct TenantApplyResult {
+    pub tenant: String,
+    pub result: Result<ApplyResult>,
+}
+
+/// Applies the template to each tenant schema in turn. Each tenant runs in
+/// its own transaction, so one tenant failing leaves the others applied.
+pub async fn apply_to_tenants(
+    template: &Schema,
+    connection: &PgConnection,
+    tenants: &[String],
+    options: ApplyOptions,
+) -> Vec<TenantApplyResult> {
+    let mut results = Vec::with_capacity(tenants.len());
+    for tenant in tenants {
+        let result = match instantiate(template, tenant) {
+            Ok(target) => {
+                apply_target_schema(
+                    &target,
+                    connection,
+                    options.clone(),
+                    std::slice::from_ref(tenant),
+                )
+                .await
+            }
+            Err(e) => Err(e),
+        };
+        results.push(TenantApplyResult {
+            tenant: tenant.clone(),
+            result,
+        });
+    }
+    results
+}
+
+/// How one tenant schema differs from the template.
+#[derive(Debug, Clone)]
+pub struct TenantDrift {
+    pub tenant: String,
+    pub report: DriftReport,
+}
+
+/// Compares each tenant schema with the template.
+pub async fn detect_tenant_drift(
+    template: &Schema,
+    connection: &PgConnection,
+    tenants: &[String],
+) -> Result<Vec<TenantDrift>> {
+    let mut drift = Vec::with_capacity(tenants.len());
+    for tenant in tenants {
+        let expected = instantiate(template, tenant)?;
+        let actual = introspect_schema(connection, std::slice::from_ref(tenant), false).await?;
+        drift.push(TenantDrift {
+            tenant: tenant.clone(),
+            report: compare_schemas(&actual, &expected),
+        });
+    }
+    Ok(drift)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &str = r#"
+CREATE SCHEMA "{tenant}";
+CREATE TABLE "{tenant}".accounts (id BIGINT PRIMARY KEY);
+CREATE TABLE "{tenant}".invoices (
+    id BIGINT PRIMARY KEY,
+    account_id BIGINT NOT NULL REFERENCES "{tenant}".accounts (id)
+);
+"#;
+
+    #[test]
+    fn instantiates_template_into_tenant_schema() {
+        let template = parse_sql_string(TEMPLATE).unwrap();
+
+        let acme = instantiate(&template, "acme").unwrap();
+        assert!(acme.tables.contains_key("acme.accounts"));
+        let invoices = &acme.tables["acme.invoices"];
+        let foreign_key = &invoices.foreign_keys[0];
+        assert_eq!(foreign_key.referenced_schema, "acme");
+        assert!(!acme
+            .tables
+            .keys()
+            .any(|key| key.contains(TENANT_PLACEHOLDER)));
+    }
+
+    #[test]
+    fn instantiated_tenants_keep_external_objects() {
+        let template = parse_sql_string(&format!(
+            "-- pgmold:external {{tenant}}.audit_*\n{TEMPLATE}"
+        ))
+        .unwrap();
+
+        let acme = instantiate(&template, "acme").unwrap();
+        assert!(acme.external.contains("acme.audit_*"));
+    }
+
+    #[test]
+    fn rejects_tenant_names_that_break_quoting() {
+        let template = parse_sql_string(TEMPLATE).unwrap();
+
+        assert!(instantiate(&template, "acme\"; DROP").is_err());
+        assert!(instantiate(&template, "").is_err());
+    }
+}
@@ -23,6 +23,12 @@ async fn apply_succeeds_with_valid_schema() {
         ApplyOptions {
             dry_run: false,
             allow_destructive: false,
+            only: Vec::new(),
+            cascade: false,
+            transaction_age_check: None,
+            defer_materialized_view_refresh: false,
+            concurrent_indexes: false,
+            ..ApplyOptions::default()
         },
     )
     .await
@@ -69,6 +75,12 @@ async fn apply_returns_error_on_invalid_sql() {
         ApplyOptions {
             dry_run: false,
             allow_destructive: false,
+            only: Vec::new(),
+            cascade: false,
+            transaction_age_check: None,
+            defer_materialized_view_refresh: false,
+            concurrent_indexes: false,
+            ..ApplyOptions::default()
         },
     )
     .await;
@@ -97,6 +109,12 @@ async fn apply_rolls_back_on_failure() {
         ApplyOptions {
             dry_run: false,
             allow_destructive: false,
+            only: Vec::new(),
+            cascade: false,
+            transaction_age_check: None,
+            defer_materialized_view_refresh: false,
+            concurrent_indexes: false,
+            ..ApplyOptions::default()
         },
     )
     .await
@@ -130,6 +148,12 @@ async fn apply_rolls_back_on_failure() {
         ApplyOptions {
             dry_run: false,
             allow_destructive: false,
+            only: Vec::new(),
+            cascade: false,
+            transaction_age_check: None,
+            defer_materialized_view_refresh: false,
+            concurrent_indexes: false,
+            ..ApplyOptions::default()
         },
     )
     .await;
@@ -145,3 +169,159 @@ async fn apply_rolls_back_on_failure() {
         "table_b should not exist after rollback"
     );
 }
+
+#[tokio::test]
+async fn apply_builds_indexes_concurrently_outside_the_transaction() {
+    let (_container, url) = setup_postgres().await;
+    let connection = PgConnection::new(&url).await.unwrap();
+
+    sqlx::query("CREATE TABLE users (id INT PRIMARY KEY, email TEXT, name TEXT)")
+        .execute(connection.pool())
+        .await
+        .unwrap();
+    sqlx::query("CREATE INDEX users_name_idx ON users (name)")
+        .execute(connection.pool())
+        .await
+        .unwrap();
+
+    let schema_file = write_sql_temp_file(
+        r#"
+        CREATE TABLE users (id INT PRIMARY KEY, email TEXT, name TEXT, bio TEXT);
+        CREATE INDEX users_email_idx ON users (email);
+        "#,
+    );
+    let result = apply_migration(
+        &[schema_file.path().to_str().unwrap().to_string()],
+        &connection,
+        ApplyOptions {
+            allow_destructive: true,
+            concurrent_indexes: true,
+            ..ApplyOptions::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    assert!(result.applied);
+    assert!(result
+        .sql_statements
+        .iter()
+        .any(|statement| statement.starts_with("CREATE INDEX CONCURRENTLY")));
+    assert!(result
+        .sql_statements
+        .iter()
+        .any(|statement| statement.starts_with("DROP INDEX CONCURRENTLY")));
+
+    let schema = introspect_schema(&connection, &["public".to_string()], false)
+        .await
+        .unwrap();
+    let users = &schema.tables["public.users"];
+    assert!(users.columns.contains_key("bio"));
+    let index_names: Vec<&str> = users.indexes.iter().map(|i| i.name.as_str()).collect();
+    assert_eq!(index_names, vec!["users_email_idx"]);
+}
+
+#[tokio::test]
+async fn apply_adds_unique_constraints_through_a_concurrent_index() {
+    let (_container, url) = setup_postgres().await;
+    let connection = PgConnection::new(&url).await.unwrap();
+
+    sqlx::query("CREATE TABLE users (id INT PRIMARY KEY, handle TEXT)")
+        .execute(connection.pool())
+        .await
+        .unwrap();
+    sqlx::query("INSERT INTO users VALUES (1, 'ada'), (2, 'ada')")
+        .execute(connection.pool())
+        .await
+        .unwrap();
+
+    let schema_file = write_sql_temp_file(
+        r#"
+        CREATE TABLE users (
+            id INT PRIMARY KEY,
+            handle TEXT,
+            CONSTRAINT users_handle_key UNIQUE (handle)
+        );
+        "#,
+    );
+    let options = || ApplyOptions {
+        concurrent_indexes: true,
+        ..ApplyOptions::default()
+    };
+    let sources = [schema_file.path().to_str().unwrap().to_string()];
+
+    let error = apply_migration(&sources, &connection, options())
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.contains("dropped the invalid index"),
+        "unexpected error: {error}"
+    );
+    let leftover: Option<String> =
+        sqlx::query_scalar("SELECT to_regclass('users_handle_key')::text")
+            .fetch_one(connection.pool())
+            .await
+            .unwrap();
+    assert_eq!(leftover, None);
+
+    sqlx::query("DELETE FROM users WHERE id = 2")
+        .execute(connection.pool())
+        .await
+        .unwrap();
+    let result = apply_migration(&sources, &connection, options())
+        .await
+        .unwrap();
+    assert!(result
+        .sql_statements
+        .iter()
+        .any(|statement| statement.starts_with("CREATE UNIQUE INDEX CONCURRENTLY")));
+    assert!(result
+        .sql_statements
+        .iter()
+        .any(|statement| statement.contains("UNIQUE USING INDEX")));
+
+    let schema = introspect_schema(&connection, &["public".to_string()], false)
+        .await
+        .unwrap();
+    let users = &schema.tables["public.users"];
+    assert!(users.indexes.is_empty());
+    assert_eq!(users.unique_constraints[0].name, "users_handle_key");
+}
+
+#[tokio::test]
+async fn apply_leaves_external_objects_alone() {
+    let (_container, url) = setup_postgres().await;
+    let connection = PgConnection::new(&url).await.unwrap();
+    sqlx::query("CREATE TABLE legacy_orders (id BIGINT)")
+        .execute(connection.pool())
+        .await
+        .unwrap();
+
+    let schema_file = write_sql_temp_file(
+        r#"
+        -- pgmold:external public.legacy_*
+        CREATE TABLE users (id BIGINT PRIMARY KEY);
+        "#,
+    );
+    let result = apply_migration(
+        &[schema_file.path().to_str().unwrap().to_string()],
+        &connection,
+        ApplyOptions {
+            allow_destructive: true,
+            ..ApplyOptions::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    assert!(result
+        .sql_statements
+        .iter()
+        .all(|statement| !statement.contains("legacy_orders")));
+    let legacy: Option<String> = sqlx::query_scalar("SELECT to_regclass('legacy_orders')::text")
+        .fetch_one(connection.pool())
+        .await
+        .unwrap();
+    assert_eq!(legacy.as_deref(), Some("legacy_orders"));
+}
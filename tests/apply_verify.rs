@@ -27,6 +27,12 @@ async fn verify_after_apply_succeeds_when_convergent() {
         ApplyOptions {
             dry_run: false,
             allow_destructive: true,
+            only: Vec::new(),
+            cascade: false,
+            transaction_age_check: None,
+            defer_materialized_view_refresh: false,
+            concurrent_indexes: false,
+            ..ApplyOptions::default()
         },
     )
     .await
@@ -67,6 +73,12 @@ async fn verify_after_apply_returns_residual_ops_when_not_convergent() {
         ApplyOptions {
             dry_run: false,
             allow_destructive: false,
+            only: Vec::new(),
+            cascade: false,
+            transaction_age_check: None,
+            defer_materialized_view_refresh: false,
+            concurrent_indexes: false,
+            ..ApplyOptions::default()
         },
     )
     .await
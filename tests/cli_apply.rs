@@ -62,3 +62,93 @@ async fn apply_json_emits_error_on_sql_failure() {
         output.status
     );
 }
+
+#[tokio::test]
+#[allow(deprecated)] // Command::cargo_bin
+async fn additive_apply_to_a_databases_file_drops_nothing() {
+    let (_container, url) = setup_postgres().await;
+    let connection = PgConnection::new(&url).await.unwrap();
+    sqlx::query("CREATE TABLE legacy (id BIGINT)")
+        .execute(connection.pool())
+        .await
+        .unwrap();
+
+    let schema_file = write_sql_temp_file("CREATE TABLE users (id BIGINT PRIMARY KEY);");
+    let databases_file = write_sql_temp_file(&format!("{url}\n"));
+    let schema_arg = format!("sql:{}", schema_file.path().display());
+
+    let output = Command::cargo_bin("pgmold")
+        .unwrap()
+        .args([
+            "apply",
+            "--json",
+            "--schema",
+            &schema_arg,
+            "--databases-file",
+            &databases_file.path().display().to_string(),
+            "--additive",
+            "--allow-destructive",
+        ])
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        output.status.success(),
+        "stdout: {stdout}\nstderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let parsed: serde_json::Value = serde_json::from_str(stdout.trim()).unwrap();
+    let applied = parsed["databases"][0]["applied"].as_array().unwrap();
+    assert!(
+        applied
+            .iter()
+            .any(|statement| statement.as_str().unwrap().contains("CREATE TABLE")),
+        "{parsed}"
+    );
+    assert!(
+        !applied
+            .iter()
+            .any(|statement| statement.as_str().unwrap().contains("DROP")),
+        "{parsed}"
+    );
+
+    let legacy: i64 = sqlx::query_scalar(
+        "SELECT count(*) FROM pg_tables WHERE schemaname = 'public' AND tablename = 'legacy'",
+    )
+    .fetch_one(connection.pool())
+    .await
+    .unwrap();
+    assert_eq!(legacy, 1);
+}
+
+#[test]
+#[allow(deprecated)] // Command::cargo_bin
+fn fan_out_conflicts_name_the_flag_used() {
+    for (fan_out, value) in [
+        ("--databases-file", "databases.txt"),
+        ("--tenants", "acme,globex"),
+    ] {
+        let output = Command::cargo_bin("pgmold")
+            .unwrap()
+            .args([
+                "apply",
+                "--schema",
+                "sql:schema.sql",
+                "--database",
+                "postgres://localhost/app",
+                fan_out,
+                value,
+                "--sequence-values",
+            ])
+            .output()
+            .unwrap();
+
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains(&format!("{fan_out} cannot be combined with")),
+            "{stderr}"
+        );
+    }
+}
@@ -42,12 +42,15 @@ async fn unique_constraint_round_trip_no_orphan_index() {
         .await
         .unwrap();
 
-    // Debug: check what indexes exist
+    // Debug: check what unique constraints exist
     let db_table = db_schema.tables.get("auth.mfa_amr_claims").unwrap();
     let parsed_table = parsed_schema.tables.get("auth.mfa_amr_claims").unwrap();
 
-    println!("DB indexes: {:?}", db_table.indexes);
-    println!("Parsed indexes: {:?}", parsed_table.indexes);
+    println!("DB unique constraints: {:?}", db_table.unique_constraints);
+    println!(
+        "Parsed unique constraints: {:?}",
+        parsed_table.unique_constraints
+    );
 
     let second_diff = compute_diff(&db_schema, &parsed_schema);
     let index_ops: Vec<_> = second_diff
@@ -55,7 +58,10 @@ async fn unique_constraint_round_trip_no_orphan_index() {
         .filter(|op| {
             matches!(
                 op,
-                MigrationOp::AddIndex { .. } | MigrationOp::DropIndex { .. }
+                MigrationOp::AddIndex { .. }
+                    | MigrationOp::DropIndex { .. }
+                    | MigrationOp::AddUniqueConstraint { .. }
+                    | MigrationOp::DropUniqueConstraint { .. }
             )
         })
         .collect();
@@ -84,6 +84,26 @@ async fn table_with_indexes() {
     .await;
 }
 
+#[tokio::test]
+async fn index_column_collation_opclass_and_order() {
+    assert_convergence_public(
+        r#"
+        CREATE TABLE public.products (
+            id          BIGSERIAL NOT NULL,
+            sku         TEXT      NOT NULL,
+            name        TEXT      NOT NULL,
+            created_at  TIMESTAMPTZ,
+            PRIMARY KEY (id)
+        );
+
+        CREATE INDEX products_sku_pattern_idx ON public.products (sku text_pattern_ops);
+        CREATE INDEX products_name_c_idx ON public.products (name COLLATE "C");
+        CREATE INDEX products_recent_idx ON public.products (created_at DESC NULLS LAST, id);
+        "#,
+    )
+    .await;
+}
+
 #[tokio::test]
 async fn table_with_foreign_keys() {
     assert_convergence_public(
@@ -1215,15 +1235,14 @@ async fn unique_constraint_dump_round_trip() {
     );
 
     let reparsed_table = reparsed.tables.get("auth.mfa_amr_claims").unwrap();
-    let constraint_index = reparsed_table
-        .indexes
-        .iter()
-        .find(|i| i.name == "mfa_amr_claims_session_id_authentication_method_pkey")
-        .expect("Dump round-trip should preserve the unique constraint");
     assert!(
-        constraint_index.is_constraint,
-        "Dump round-trip should preserve is_constraint=true, got is_constraint=false"
+        reparsed_table
+            .unique_constraints
+            .iter()
+            .any(|c| c.name == "mfa_amr_claims_session_id_authentication_method_pkey"),
+        "Dump round-trip should preserve the unique constraint"
     );
+    assert!(reparsed_table.indexes.is_empty());
     assert!(
         !dump_output.contains("CREATE UNIQUE INDEX"),
         "Dump should emit ALTER TABLE ADD CONSTRAINT, not CREATE UNIQUE INDEX.\nDump output:\n{}",
@@ -1327,3 +1346,49 @@ async fn aggregate_referenced_by_view_converges() {
     )
     .await;
 }
+
+#[tokio::test]
+async fn comments_are_introspected() {
+    let (_container, url) = setup_postgres().await;
+    let connection = PgConnection::new(&url).await.unwrap();
+    let target = parse_sql_string(
+        r#"
+        CREATE TYPE public.status AS ENUM ('active', 'inactive');
+        CREATE TABLE public.users (id BIGSERIAL PRIMARY KEY, email TEXT NOT NULL);
+        CREATE FUNCTION public.user_count() RETURNS bigint
+            LANGUAGE sql STABLE AS $$ SELECT count(*) FROM public.users $$;
+        CREATE VIEW public.user_emails AS SELECT email FROM public.users;
+
+        COMMENT ON TYPE public.status IS 'Account state';
+        COMMENT ON TABLE public.users IS 'Registered accounts';
+        COMMENT ON COLUMN public.users.email IS 'Login; never shared';
+        COMMENT ON FUNCTION public.user_count() IS 'Number of accounts';
+        COMMENT ON VIEW public.user_emails IS 'Addresses only';
+        "#,
+    )
+    .unwrap();
+    apply_and_assert_convergence(&connection, &target, &["public"]).await;
+
+    let schema = introspect_schema(&connection, &["public".to_string()], false)
+        .await
+        .unwrap();
+    let users = &schema.tables["public.users"];
+    assert_eq!(users.comment.as_deref(), Some("Registered accounts"));
+    assert_eq!(
+        users.columns["email"].comment.as_deref(),
+        Some("Login; never shared")
+    );
+    assert_eq!(users.columns["id"].comment, None);
+    assert_eq!(
+        schema.enums["public.status"].comment.as_deref(),
+        Some("Account state")
+    );
+    assert_eq!(
+        schema.functions["public.user_count()"].comment.as_deref(),
+        Some("Number of accounts")
+    );
+    assert_eq!(
+        schema.views["public.user_emails"].comment.as_deref(),
+        Some("Addresses only")
+    );
+}
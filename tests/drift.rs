@@ -53,6 +53,42 @@ async fn drift_detection() {
     assert!(!report_after.differences.is_empty());
 }
 
+#[tokio::test]
+async fn drift_reports_unmanaged_objects() {
+    let (_container, url) = setup_postgres().await;
+    let connection = PgConnection::new(&url).await.unwrap();
+
+    sqlx::query(USERS_DDL)
+        .execute(connection.pool())
+        .await
+        .unwrap();
+    sqlx::query(
+        "CREATE FUNCTION text_longer(a text, b text) RETURNS boolean \
+         LANGUAGE sql IMMUTABLE AS $$ SELECT length(a) > length(b) $$",
+    )
+    .execute(connection.pool())
+    .await
+    .unwrap();
+    sqlx::query("CREATE OPERATOR >>> (LEFTARG = text, RIGHTARG = text, FUNCTION = text_longer)")
+        .execute(connection.pool())
+        .await
+        .unwrap();
+
+    let schema_file = write_sql_temp_file(USERS_SCHEMA);
+    let sources = vec![format!("sql:{}", schema_file.path().display())];
+
+    let report = detect_drift(&sources, &connection, &["public".to_string()])
+        .await
+        .unwrap();
+    let operators = report
+        .unmanaged_objects
+        .iter()
+        .find(|objects| objects.kind == "operator")
+        .unwrap();
+    assert_eq!(operators.schema.as_deref(), Some("public"));
+    assert_eq!(operators.count, 1);
+}
+
 #[tokio::test]
 #[allow(deprecated)] // Command::cargo_bin
 async fn drift_cli_no_drift() {
@@ -182,3 +218,39 @@ async fn drift_cli_json_output() {
     assert!(json["differences"].is_array());
     assert!(!json["differences"].as_array().unwrap().is_empty());
 }
+
+#[tokio::test]
+async fn drift_accept_keeps_column_comments() {
+    let (_container, url) = setup_postgres().await;
+    let connection = PgConnection::new(&url).await.unwrap();
+
+    sqlx::query(USERS_DDL)
+        .execute(connection.pool())
+        .await
+        .unwrap();
+    sqlx::query("COMMENT ON COLUMN users.email IS 'Login; never shared'")
+        .execute(connection.pool())
+        .await
+        .unwrap();
+    sqlx::query("ALTER TABLE users ADD COLUMN bio TEXT")
+        .execute(connection.pool())
+        .await
+        .unwrap();
+
+    let schema_file = write_sql_temp_file(&format!(
+        "{USERS_SCHEMA_NO_OWNER}\nCOMMENT ON COLUMN users.email IS 'Login; never shared';\n"
+    ));
+    let sources = vec![format!("sql:{}", schema_file.path().display())];
+
+    let accepted =
+        pgmold::drift::accept_drift("users", &sources, &connection, &["public".to_string()])
+            .await
+            .unwrap();
+    let reparsed = parse_sql_string(&accepted.content).unwrap();
+    let users = &reparsed.tables["public.users"];
+    assert!(users.columns.contains_key("bio"));
+    assert_eq!(
+        users.columns["email"].comment.as_deref(),
+        Some("Login; never shared")
+    );
+}
@@ -8,6 +8,13 @@ use pgmold::provider::load_schema_from_sources;
 const NON_DESTRUCTIVE_LINT: LintOptions = LintOptions {
     allow_destructive: false,
     is_production: false,
+    severity_overrides: std::collections::BTreeMap::new(),
+    protected: Vec::new(),
+    logical_replication: false,
+    drop_threshold: None,
+    table_sizes: std::collections::BTreeMap::new(),
+    required_settings: Vec::new(),
+    current: None,
 };
 
 #[test]
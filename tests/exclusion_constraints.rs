@@ -105,6 +105,7 @@ fn diff_produces_drop_exclusion_constraint_when_removed() {
         primary_key: None,
         foreign_keys: vec![],
         check_constraints: vec![],
+        unique_constraints: Vec::new(),
         exclusion_constraints: vec![ExclusionConstraint {
             name: "bookings_during_excl".to_string(),
             index_method: "gist".to_string(),
@@ -119,6 +120,7 @@ fn diff_produces_drop_exclusion_constraint_when_removed() {
         comment: None,
         row_level_security: false,
         force_row_level_security: false,
+        replica_identity: None,
         policies: vec![],
         partition_by: None,
         owner: None,
@@ -133,6 +135,9 @@ fn diff_produces_drop_exclusion_constraint_when_removed() {
             default: None,
             comment: None,
             generated: None,
+            statistics: None,
+            compression: None,
+            identity: None,
         },
     );
     from_schema
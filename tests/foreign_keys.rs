@@ -610,3 +610,60 @@ async fn fk_type_change_referenced_column_only() {
         "DropForeignKey (pos {drop_fk_pos}) must come before AlterColumn (pos {alter_pos})"
     );
 }
+
+#[tokio::test]
+async fn deferrable_constraints_round_trip_and_alter_in_place() {
+    let (_container, url) = setup_postgres().await;
+    let connection = PgConnection::new(&url).await.unwrap();
+
+    for stmt in [
+        "CREATE TABLE users (id integer PRIMARY KEY, email text CONSTRAINT users_email_key UNIQUE DEFERRABLE INITIALLY DEFERRED)",
+        "CREATE TABLE posts (id integer PRIMARY KEY, author_id integer CONSTRAINT posts_author_id_fkey REFERENCES users(id))",
+    ] {
+        sqlx::query(stmt).execute(connection.pool()).await.unwrap();
+    }
+
+    let target_schema = parse_sql_string(
+        r#"
+        CREATE TABLE users (
+            id integer PRIMARY KEY,
+            email text CONSTRAINT users_email_key UNIQUE DEFERRABLE INITIALLY DEFERRED
+        );
+        CREATE TABLE posts (
+            id integer PRIMARY KEY,
+            author_id integer CONSTRAINT posts_author_id_fkey REFERENCES users(id)
+                DEFERRABLE INITIALLY DEFERRED
+        );
+        "#,
+    )
+    .unwrap();
+
+    let current_schema = introspect_schema(&connection, &["public".to_string()], false)
+        .await
+        .unwrap();
+    let users = current_schema.tables.get("public.users").unwrap();
+    let unique = &users.unique_constraints[0];
+    assert!(unique.deferrable && unique.initially_deferred);
+
+    let planned = plan_migration(compute_diff(&current_schema, &target_schema));
+    assert_eq!(
+        planned,
+        vec![MigrationOp::AlterForeignKeyDeferral {
+            table: pgmold::model::QualifiedName::new("public", "posts"),
+            foreign_key_name: "posts_author_id_fkey".to_string(),
+            deferrable: true,
+            initially_deferred: true,
+        }]
+    );
+    for statement in generate_sql(&planned) {
+        sqlx::query(&statement)
+            .execute(connection.pool())
+            .await
+            .unwrap();
+    }
+
+    let after = introspect_schema(&connection, &["public".to_string()], false)
+        .await
+        .unwrap();
+    assert!(compute_diff(&after, &target_schema).is_empty());
+}
@@ -757,3 +757,39 @@ async fn uppercase_default_preserves_original_case() {
         "Parser should preserve original string literal case"
     );
 }
+
+#[tokio::test]
+async fn long_bodies_are_only_fetched_when_they_differ() {
+    let (_container, url) = setup_postgres().await;
+    let connection = PgConnection::new(&url).await.unwrap();
+
+    let padding = "-- padding\n".repeat(2_000);
+    let function_sql = |marker: &str| {
+        format!(
+            "CREATE FUNCTION public.generated() RETURNS integer LANGUAGE sql AS $$\n{padding}SELECT {marker}\n$$;"
+        )
+    };
+    sqlx::raw_sql(&function_sql("1"))
+        .execute(connection.pool())
+        .await
+        .unwrap();
+    let eager = introspect_schema(&connection, &["public".to_string()], false)
+        .await
+        .unwrap();
+    let eager_body = &eager.functions["public.generated()"].body;
+    assert!(eager_body.len() > pgmold::pg::introspect::LAZY_BODY_BYTES as usize);
+
+    for marker in ["1", "2"] {
+        let known = parse_sql_string(&function_sql(marker)).unwrap();
+        let lazy = pgmold::pg::introspect::introspect_schema_with_known_bodies(
+            &connection,
+            &["public".to_string()],
+            false,
+            &known,
+        )
+        .await
+        .unwrap();
+        assert_eq!(&lazy.functions["public.generated()"].body, eager_body);
+        assert_eq!(lazy.fingerprint(), eager.fingerprint());
+    }
+}
@@ -0,0 +1,79 @@
+mod common;
+use common::*;
+use pgmold::model::IdentityGeneration;
+
+async fn apply(connection: &PgConnection, from: &Schema, to: &Schema) {
+    let ops = plan_migration(compute_diff(from, to));
+    for stmt in generate_sql(&ops) {
+        sqlx::query(&stmt)
+            .execute(connection.pool())
+            .await
+            .unwrap_or_else(|e| panic!("{stmt}: {e}"));
+    }
+}
+
+async fn introspect(connection: &PgConnection) -> Schema {
+    introspect_schema(connection, &["public".to_string()], false)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn identity_columns_converge_and_change_in_place() {
+    let (_container, url) = setup_postgres().await;
+    let connection = PgConnection::new(&url).await.unwrap();
+
+    let always = parse_sql_string(
+        "CREATE TABLE public.orders (
+            id BIGINT GENERATED ALWAYS AS IDENTITY (START WITH 100),
+            total NUMERIC
+        );",
+    )
+    .unwrap();
+    apply(&connection, &Schema::new(), &always).await;
+
+    let db_schema = introspect(&connection).await;
+    let id = &db_schema.tables["public.orders"].columns["id"];
+    let identity = id
+        .identity
+        .as_ref()
+        .expect("id should be an identity column");
+    assert_eq!(identity.generation, IdentityGeneration::Always);
+    assert_eq!(identity.start, Some(100));
+    assert_eq!(id.default, None);
+    assert!(
+        compute_diff(&db_schema, &always).is_empty(),
+        "identity column should converge"
+    );
+
+    let by_default = parse_sql_string(
+        "CREATE TABLE public.orders (
+            id BIGINT GENERATED BY DEFAULT AS IDENTITY (INCREMENT BY 5),
+            total NUMERIC
+        );",
+    )
+    .unwrap();
+    apply(&connection, &db_schema, &by_default).await;
+    let db_schema = introspect(&connection).await;
+    let identity = db_schema.tables["public.orders"].columns["id"]
+        .identity
+        .clone()
+        .unwrap();
+    assert_eq!(identity.generation, IdentityGeneration::ByDefault);
+    assert_eq!(identity.increment, Some(5));
+    assert!(compute_diff(&db_schema, &by_default).is_empty());
+
+    let plain = parse_sql_string("CREATE TABLE public.orders (id BIGINT NOT NULL, total NUMERIC);")
+        .unwrap();
+    apply(&connection, &db_schema, &plain).await;
+    let db_schema = introspect(&connection).await;
+    assert_eq!(
+        db_schema.tables["public.orders"].columns["id"].identity,
+        None
+    );
+    assert!(compute_diff(&db_schema, &plain).is_empty());
+
+    apply(&connection, &db_schema, &always).await;
+    let db_schema = introspect(&connection).await;
+    assert!(compute_diff(&db_schema, &always).is_empty());
+}
@@ -413,3 +413,40 @@ async fn partition_remove_partition() {
     let final_ops = compute_diff(&after_schema, &desired_schema);
     assert!(final_ops.is_empty(), "Diff should be empty after migration");
 }
+
+#[tokio::test]
+async fn retention_expires_old_range_partitions() {
+    let (_container, url) = setup_postgres().await;
+    let connection = PgConnection::new(&url).await.unwrap();
+
+    connection
+        .pool()
+        .execute(
+            r#"
+            CREATE TABLE events (at timestamptz NOT NULL) PARTITION BY RANGE (at);
+            CREATE TABLE events_2020 PARTITION OF events
+                FOR VALUES FROM ('2020-01-01') TO ('2021-01-01');
+            CREATE TABLE events_future PARTITION OF events
+                FOR VALUES FROM ('2021-01-01') TO (MAXVALUE);
+            CREATE TABLE events_default PARTITION OF events DEFAULT;
+            "#,
+        )
+        .await
+        .unwrap();
+
+    let target = parse_sql_string(
+        r#"
+        -- pgmold:retention=90d
+        CREATE TABLE events (at timestamptz NOT NULL) PARTITION BY RANGE (at);
+        "#,
+    )
+    .unwrap();
+    let current = introspect_schema(&connection, &["public".to_string()], false)
+        .await
+        .unwrap();
+
+    let expired = pgmold::pg::retention::expired_partitions(&connection, &current, &target)
+        .await
+        .unwrap();
+    assert_eq!(expired, vec!["public.events_2020".to_string()]);
+}
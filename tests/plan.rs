@@ -97,7 +97,7 @@ async fn plan_with_zero_downtime_flag() {
         .unwrap();
 
     let ops = plan_migration(compute_diff(&current, &target));
-    let phased_plan = expand_operations(ops);
+    let phased_plan = expand_operations(ops, &current);
 
     assert!(
         !phased_plan.expand_ops.is_empty(),
@@ -156,3 +156,32 @@ async fn plan_with_zero_downtime_flag() {
         "Contract phase should add NOT NULL constraint"
     );
 }
+
+#[tokio::test]
+async fn introspect_api_returns_schema_with_relation_stats() {
+    let (_container, url) = setup_postgres().await;
+    let connection = PgConnection::new(&url).await.unwrap();
+    sqlx::query("CREATE TABLE accounts (id INT PRIMARY KEY, name TEXT)")
+        .execute(connection.pool())
+        .await
+        .unwrap();
+    sqlx::query("CREATE VIEW account_names AS SELECT name FROM accounts")
+        .execute(connection.pool())
+        .await
+        .unwrap();
+
+    let result = pgmold::api::introspect(pgmold::api::IntrospectOptions::new(
+        url.as_str(),
+        vec!["public".to_string()],
+    ))
+    .await
+    .unwrap();
+
+    assert!(result.schema.tables.contains_key("public.accounts"));
+    let table = &result.stats["public.accounts"];
+    assert!(table.oid > 0);
+    assert!(table.total_bytes > 0);
+    assert!(!table.owner.is_empty());
+    assert_eq!(result.stats["public.account_names"].estimated_rows, None);
+    assert!(result.metrics.is_none());
+}
@@ -0,0 +1,92 @@
+//! Plan ordering is deterministic: the same schema change always produces the
+//! same statements in the same order, whatever order the diff listed them in.
+//!
+//! For each corpus file below, the plan that creates it from an empty database
+//! is compared to a committed snapshot in `tests/fixtures/plan_order/`.
+//!
+//! Updating the snapshots:
+//!   PGMOLD_UPDATE_PLAN_SNAPSHOTS=1 cargo test --test plan_ordering
+
+use std::path::{Path, PathBuf};
+
+use pgmold::diff::{compute_diff, planner::plan_migration_checked, MigrationOp};
+use pgmold::model::Schema;
+use pgmold::parser::parse_sql_string;
+use pgmold::pg::sqlgen::generate_sql;
+
+const CORPUS_FILES: &[&str] = &[
+    "handcrafted_multi_schema_auth",
+    "handcrafted_rls_enum",
+    "handcrafted_saas_app",
+    "handcrafted_views_and_functions",
+];
+
+fn manifest_path(relative: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(relative)
+}
+
+fn load_corpus(name: &str) -> Schema {
+    let path = manifest_path(&format!("tests/corpus/{name}.sql"));
+    let content = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("cannot read {}: {e}", path.display()));
+    parse_sql_string(&content).unwrap_or_else(|e| panic!("cannot parse {name}: {e}"))
+}
+
+fn plan_sql(ops: Vec<MigrationOp>) -> Vec<String> {
+    generate_sql(&plan_migration_checked(ops).expect("plan should not cycle"))
+}
+
+#[test]
+fn create_plans_match_snapshots() {
+    let update = std::env::var_os("PGMOLD_UPDATE_PLAN_SNAPSHOTS").is_some();
+    let mut failed: Vec<&str> = Vec::new();
+
+    for name in CORPUS_FILES {
+        let ops = compute_diff(&Schema::default(), &load_corpus(name));
+        let actual = plan_sql(ops).join("\n") + "\n";
+        let snapshot = manifest_path(&format!("tests/fixtures/plan_order/{name}.snap"));
+
+        if update {
+            std::fs::write(&snapshot, &actual)
+                .unwrap_or_else(|e| panic!("cannot write {}: {e}", snapshot.display()));
+            println!("wrote {}", snapshot.display());
+            continue;
+        }
+
+        let expected = std::fs::read_to_string(&snapshot).unwrap_or_default();
+        if actual != expected {
+            eprintln!(
+                "Plan snapshot mismatch for {name}.\n--- expected\n{expected}\n--- actual\n{actual}"
+            );
+            failed.push(name);
+        }
+    }
+
+    assert!(
+        failed.is_empty(),
+        "plan snapshots diverged for {failed:?}; if intentional, regenerate with:\n  \
+         PGMOLD_UPDATE_PLAN_SNAPSHOTS=1 cargo test --test plan_ordering"
+    );
+}
+
+#[test]
+fn plan_order_does_not_depend_on_diff_order() {
+    for name in CORPUS_FILES {
+        let schema = load_corpus(name);
+        for ops in [
+            compute_diff(&Schema::default(), &schema),
+            compute_diff(&schema, &Schema::default()),
+        ] {
+            let expected = plan_sql(ops.clone());
+
+            let mut reversed = ops.clone();
+            reversed.reverse();
+            assert_eq!(plan_sql(reversed), expected, "{name}: reversed input");
+
+            let mut rotated = ops;
+            let mid = rotated.len() / 2;
+            rotated.rotate_left(mid);
+            assert_eq!(plan_sql(rotated), expected, "{name}: rotated input");
+        }
+    }
+}
@@ -282,14 +282,14 @@ async fn policy_dropped_when_referenced_function_changes() {
         sqlx::query(stmt).execute(connection.pool()).await.unwrap();
     }
 
-    // Modified schema: argument default changed (triggers drop/recreate)
+    // Modified schema: argument renamed (triggers drop/recreate)
     let modified_sql = r#"
-        CREATE FUNCTION public.check_user_access(user_name TEXT DEFAULT 'superuser')
+        CREATE FUNCTION public.check_user_access(role_name TEXT DEFAULT 'admin')
         RETURNS BOOLEAN
         LANGUAGE SQL
         STABLE
         AS $$
-            SELECT current_user = user_name
+            SELECT current_user = role_name
         $$;
 
         CREATE TABLE public.secure_data (
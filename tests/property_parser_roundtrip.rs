@@ -236,7 +236,7 @@ fn regression_typed_defaults_roundtrip() {
 // idempotence tests would not (both sides drop equally).
 
 #[test]
-fn extracts_inline_column_unique_as_unique_index() {
+fn extracts_inline_column_unique_as_unique_constraint() {
     let sql = "CREATE TABLE public.users (\n  id bigserial PRIMARY KEY,\n  email text NOT NULL UNIQUE\n);";
     let schema = parse_sql_string(sql).expect("parse");
     let table = schema
@@ -244,13 +244,13 @@ fn extracts_inline_column_unique_as_unique_index() {
         .get("public.users")
         .expect("users table should be parsed");
     let has_unique_on_email = table
-        .indexes
+        .unique_constraints
         .iter()
-        .any(|idx| idx.unique && idx.columns == vec!["email".to_string()]);
+        .any(|unique| unique.columns == vec!["email".to_string()]);
     assert!(
         has_unique_on_email,
-        "inline column UNIQUE on email was dropped by parser.\nIndexes: {:#?}",
-        table.indexes,
+        "inline column UNIQUE on email was dropped by parser.\nUnique constraints: {:#?}",
+        table.unique_constraints,
     );
 }
 
@@ -108,6 +108,7 @@ async fn drop_column_blocked() {
     let lint_options = LintOptions {
         allow_destructive: false,
         is_production: false,
+        ..LintOptions::default()
     };
     let lint_results = lint_migration_plan(&ops, &lint_options);
 
@@ -235,7 +236,7 @@ async fn schema_creation_round_trip() {
         .filter(|op| {
             matches!(
                 op,
-                MigrationOp::CreateSchema(_) | MigrationOp::DropSchema(_)
+                MigrationOp::CreateSchema(_) | MigrationOp::DropSchema { .. }
             )
         })
         .collect();
@@ -244,3 +245,41 @@ async fn schema_creation_round_trip() {
         "Should have no schema diff after round-trip, got: {remaining_schema_ops:?}"
     );
 }
+
+#[tokio::test]
+async fn replica_identity_round_trip() {
+    let (_container, url) = setup_postgres().await;
+    let connection = PgConnection::new(&url).await.unwrap();
+
+    let target = parse_sql_string(
+        r#"
+        CREATE TABLE events (id BIGINT PRIMARY KEY, key TEXT NOT NULL);
+        CREATE UNIQUE INDEX events_key_idx ON events (key);
+        ALTER TABLE events REPLICA IDENTITY USING INDEX events_key_idx;
+        CREATE TABLE audit (id BIGINT);
+        ALTER TABLE audit REPLICA IDENTITY FULL;
+        "#,
+    )
+    .unwrap();
+
+    let ops = plan_migration(compute_diff(&Schema::new(), &target));
+    for stmt in generate_sql(&ops) {
+        sqlx::query(&stmt)
+            .execute(connection.pool())
+            .await
+            .unwrap_or_else(|e| panic!("{stmt}: {e}"));
+    }
+
+    let db_schema = introspect_schema(&connection, &["public".to_string()], false)
+        .await
+        .unwrap();
+    assert_eq!(
+        db_schema.tables["public.events"].replica_identity,
+        target.tables["public.events"].replica_identity
+    );
+    assert_eq!(
+        db_schema.tables["public.audit"].replica_identity,
+        target.tables["public.audit"].replica_identity
+    );
+    assert!(compute_diff(&db_schema, &target).is_empty());
+}